@@ -0,0 +1,6 @@
+mod page;
+mod panel;
+mod register;
+
+pub use page::AlnRegisterPage;
+pub use panel::AlnPanel;
@@ -0,0 +1,71 @@
+use super::register::*;
+use crate::{app::AppContext, error::*};
+use chrono::Utc;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Everyone on the ALN register in the user's years, the next review due first
+#[function_component(AlnRegisterPage)]
+pub fn aln_register_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ALN REGISTER PAGE");
+    let rows: UseStateHandle<Vec<RegisterRow>> = use_state_eq(Vec::new);
+
+    {
+        clone!(ctx, rows);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_register(&ctx.auth_token).await {
+                        Ok(fetched) => rows.set(fetched),
+                        Err(error) => {
+                            error!("failed to get the ALN register:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+    let today = Utc::now().date_naive();
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white">
+            <table class="w-full text-sm text-left">
+                <thead>
+                    <tr>
+                        <th>{"Pupil"}</th>
+                        <th>{"Year"}</th>
+                        <th>{"Provision"}</th>
+                        <th>{"Areas of need"}</th>
+                        <th>{"ALNCo"}</th>
+                        <th>{"Last reviewed"}</th>
+                        <th>{"Review due"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows.iter().map(|row| {
+                        let overdue = row.entry.review_date < today;
+                        html! {
+                            <tr key={row.entry.pupil_id.to_string()} class="border-t border-slate-200">
+                                <td>{format!("{} {}", row.first_names, row.last_name)}</td>
+                                <td>{row.year}</td>
+                                <td>{label(&PROVISIONS, &row.entry.provision)}</td>
+                                <td>{row.entry.areas_of_need.iter().map(|area| label(&AREAS_OF_NEED, area)).collect::<Vec<&str>>().join(", ")}</td>
+                                <td>{row.entry.alnco.clone().unwrap_or_default()}</td>
+                                <td>{row.entry.last_reviewed.map(|date| date.format("%d/%m/%Y").to_string()).unwrap_or_default()}</td>
+                                <td class={classes!(overdue.then_some("text-red-500"))}>{row.entry.review_date.format("%d/%m/%Y").to_string()}</td>
+                            </tr>
+                        }
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+            if rows.is_empty() {
+                <p>{"No one in your years is on the ALN register"}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,215 @@
+use super::register::*;
+use crate::{app::AppContext, elements::IconButton};
+use chrono::{Months, Utc};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The pupil's ALN register entry and IDP versions, only the ALNCo or an admin can change them
+#[function_component(AlnPanel)]
+pub fn aln_panel(props: &AlnPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ALN PANEL");
+    let entry: UseStateHandle<Option<RegisterEntry>> = use_state_eq(|| None);
+    let idps: UseStateHandle<Vec<Idp>> = use_state_eq(Vec::new);
+    let update = use_state_eq(|| EntryUpdate {
+        provision: PROVISIONS[0].0.to_owned(),
+        areas_of_need: Vec::new(),
+        alnco: None,
+        review_date: Utc::now().date_naive() + Months::new(12),
+        last_reviewed: None,
+    });
+    let new_idp = use_state_eq(NewIdp::default);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let can_edit = ctx.current_user.is_alnco();
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, entry, idps, update);
+        Callback::from(move |_: ()| {
+            clone!(ctx, entry, idps, update);
+            spawn_local(async move {
+                match fetch_entry(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => {
+                        if let Some(fetched) = fetched.as_ref().filter(|fetched| fetched.closed_on.is_none()) {
+                            update.set(fetched.into());
+                        }
+                        entry.set(fetched);
+                    }
+                    Err(error) => error!("failed to get ALN register entry:", error.to_string()),
+                }
+                match fetch_idps(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => idps.set(fetched),
+                    Err(error) => error!("failed to get IDPs:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let update_entry = {
+        clone!(update);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut changed = (*update).clone();
+            match target.id().as_str() {
+                "aln_provision" => changed.provision = target.value(),
+                "aln_review_date" => {
+                    changed.review_date = target.value().parse().unwrap_or(changed.review_date)
+                }
+                "aln_last_reviewed" => changed.last_reviewed = target.value().parse().ok(),
+                area => {
+                    let area = area.trim_start_matches("aln_area_").to_owned();
+                    changed.areas_of_need.retain(|existing| *existing != area);
+                    if target.checked() {
+                        changed.areas_of_need.push(area);
+                    }
+                }
+            }
+            update.set(changed);
+        })
+    };
+    let save = {
+        clone!(ctx, update, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, update, errors, refresh);
+            spawn_local(async move {
+                match save_entry(&pupil_id, &update, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to save ALN register entry:", error.to_string()),
+                }
+            });
+        })
+    };
+    let close = {
+        clone!(ctx, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, refresh);
+            spawn_local(async move {
+                match close_entry(&pupil_id, &ctx.auth_token).await {
+                    Ok(_) => refresh.emit(()),
+                    Err(error) => error!("failed to close ALN register entry:", error.to_string()),
+                }
+            });
+        })
+    };
+    let update_idp = {
+        clone!(new_idp);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut changed = (*new_idp).clone();
+            match target.id().as_str() {
+                "idp_outcomes" => changed.outcomes = target.value(),
+                "idp_provision" => changed.provision = target.value(),
+                _ => {}
+            }
+            new_idp.set(changed);
+        })
+    };
+    let add_idp = {
+        clone!(ctx, new_idp, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_idp, errors, refresh);
+            spawn_local(async move {
+                match create_idp(&pupil_id, &new_idp, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to write IDP:", error.to_string()),
+                }
+            });
+        })
+    };
+    let is_open = entry.as_ref().is_some_and(|entry| entry.closed_on.is_none());
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Additional learning needs"}</h3>
+            <div class="text-xs">
+                {match entry.as_ref() {
+                    Some(entry) if entry.closed_on.is_none() => html! {
+                        <>
+                            <p>{format!(
+                                "{} since {}, review due {}",
+                                label(&PROVISIONS, &entry.provision),
+                                entry.added_on.format("%d/%m/%Y"),
+                                entry.review_date.format("%d/%m/%Y")
+                            )}</p>
+                            <p>{entry.areas_of_need.iter().map(|area| label(&AREAS_OF_NEED, area)).collect::<Vec<&str>>().join(", ")}</p>
+                            if let Some(alnco) = &entry.alnco {
+                                <p class="text-slate-500">{format!("ALNCo: {alnco}")}</p>
+                            }
+                        </>
+                    },
+                    Some(entry) => html!(<p class="text-slate-500">{format!("Taken off the register on {}", entry.closed_on.unwrap_or(entry.added_on).format("%d/%m/%Y"))}</p>),
+                    None => html!(<p class="text-slate-500">{"Not on the ALN register"}</p>),
+                }}
+            </div>
+            if can_edit {
+                <div class="flex flex-col gap-1 text-sm">
+                    <select id="aln_provision" class="border-2 border-slate-200 rounded-md" onchange={&update_entry}>
+                        {PROVISIONS.iter().map(|(value, text)| html! {
+                            <option value={*value} selected={update.provision == *value}>{*text}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    {AREAS_OF_NEED.iter().map(|(value, text)| {
+                        let id = format!("aln_area_{value}");
+                        html! {
+                            <label class="flex gap-1 items-center text-xs" for={id.clone()}>
+                                <input type="checkbox" {id} checked={update.areas_of_need.iter().any(|area| area == value)} onchange={&update_entry}/>
+                                {*text}
+                            </label>
+                        }
+                    }).collect::<Html>()}
+                    <div class="flex justify-between items-center gap-1">
+                        <label for="aln_review_date">{"Review on"}</label>
+                        <input type="date" id="aln_review_date" class="border-2 border-slate-200 rounded-md" value={update.review_date.to_string()} onchange={&update_entry}/>
+                    </div>
+                    <div class="flex justify-between items-center gap-1">
+                        <label for="aln_last_reviewed">{"Last reviewed"}</label>
+                        <input type="date" id="aln_last_reviewed" class="border-2 border-slate-200 rounded-md" value={update.last_reviewed.map(|date| date.to_string()).unwrap_or_default()} onchange={&update_entry}/>
+                        <IconButton onclick={&save} icon="save" />
+                        if is_open {
+                            <IconButton onclick={&close} icon="close" />
+                        }
+                    </div>
+                </div>
+            }
+            <h3 class="text-md">{"Individual development plan"}</h3>
+            if can_edit && is_open {
+                <div class="flex flex-col gap-1">
+                    <textarea id="idp_outcomes" class="border-2 border-slate-200 rounded-md text-sm" rows="2" placeholder="Outcomes" value={new_idp.outcomes.clone()} onchange={&update_idp}/>
+                    <textarea id="idp_provision" class="border-2 border-slate-200 rounded-md text-sm" rows="2" placeholder="Provision to meet them" value={new_idp.provision.clone()} onchange={&update_idp}/>
+                    <div class="flex justify-end">
+                        <IconButton onclick={&add_idp} icon="add" />
+                    </div>
+                </div>
+            }
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            <ul class="flex flex-col gap-1 text-xs">
+                {idps.iter().map(|idp| html! {
+                    <li key={idp.id.to_string()} class="border-l-4 border-slate-300 pl-2">
+                        <span class="text-slate-500">{format!("Version {} · {} · {}", idp.version, idp.created_at.format("%d/%m/%Y"), idp.created_by)}</span>
+                        <p class="text-sm">{&idp.outcomes}</p>
+                        <p class="italic">{&idp.provision}</p>
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct AlnPanelProps {
+    pub pupil_id: Uuid,
+}
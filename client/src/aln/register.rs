@@ -0,0 +1,185 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static PROVISIONS: [(&str, &str); 2] = [
+    ("school", "School maintained"),
+    ("local_authority", "Local authority maintained"),
+];
+pub static AREAS_OF_NEED: [(&str, &str); 4] = [
+    ("cognition_and_learning", "Cognition and learning"),
+    ("communication_and_interaction", "Communication and interaction"),
+    ("behaviour_emotional_and_social", "Behaviour, emotional and social"),
+    ("sensory_and_physical", "Sensory and physical"),
+];
+
+/// The label for a value from one of the lists above, or the value itself if it isn't in it
+pub fn label<'a>(options: &[(&str, &'a str)], value: &'a str) -> &'a str {
+    options
+        .iter()
+        .find(|(option, _)| *option == value)
+        .map_or(value, |(_, label)| label)
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct RegisterEntry {
+    pub pupil_id: Uuid,
+    pub provision: String,
+    pub areas_of_need: Vec<String>,
+    #[serde(default)]
+    pub alnco: Option<String>,
+    pub added_on: NaiveDate,
+    pub review_date: NaiveDate,
+    #[serde(default)]
+    pub last_reviewed: Option<NaiveDate>,
+    #[serde(default)]
+    pub closed_on: Option<NaiveDate>,
+}
+
+/// A row of the register, with enough of the pupil to show who it is
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct RegisterRow {
+    pub first_names: String,
+    pub last_name: String,
+    pub year: i32,
+    #[serde(flatten)]
+    pub entry: RegisterEntry,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct EntryUpdate {
+    pub provision: String,
+    pub areas_of_need: Vec<String>,
+    pub alnco: Option<String>,
+    pub review_date: NaiveDate,
+    pub last_reviewed: Option<NaiveDate>,
+}
+
+impl From<&RegisterEntry> for EntryUpdate {
+    fn from(value: &RegisterEntry) -> Self {
+        Self {
+            provision: value.provision.clone(),
+            areas_of_need: value.areas_of_need.clone(),
+            alnco: value.alnco.clone(),
+            review_date: value.review_date,
+            last_reviewed: value.last_reviewed,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Idp {
+    pub id: Uuid,
+    pub version: i32,
+    pub outcomes: String,
+    pub provision: String,
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct NewIdp {
+    pub outcomes: String,
+    pub provision: String,
+}
+
+fn aln_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/aln", constant::PUPILS_PATH)
+}
+
+fn idp_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/idp", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_register(token: &str) -> Result<Vec<RegisterRow>> {
+    let response = Request::get(&format!("{}/register", constant::ALN_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<RegisterRow>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// The pupil's entry, or `None` if they've never been on the register
+pub async fn fetch_entry(pupil_id: &Uuid, token: &str) -> Result<Option<RegisterEntry>> {
+    let response = Request::get(&aln_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Option<RegisterEntry>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the entry
+pub async fn save_entry(
+    pupil_id: &Uuid,
+    update: &EntryUpdate,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&aln_path(pupil_id))
+        .json(update)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn close_entry(pupil_id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&aln_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_idps(pupil_id: &Uuid, token: &str) -> Result<Vec<Idp>> {
+    let response = Request::get(&idp_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Idp>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the plan
+pub async fn create_idp(
+    pupil_id: &Uuid,
+    idp: &NewIdp,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&idp_path(pupil_id))
+        .json(idp)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,8 @@
+mod chart;
+mod gap;
+mod gap_page;
+mod page;
+mod report;
+
+pub use gap_page::GapAnalysisPage;
+pub use page::CohortDashboardPage;
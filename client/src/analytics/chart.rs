@@ -0,0 +1,90 @@
+use super::report::{key_label, label, CohortReport, DIMENSIONS};
+use yew::prelude::*;
+
+const WIDTH: f64 = 480.0;
+const HEIGHT: f64 = 220.0;
+const AXIS: f64 = 30.0;
+const COLOURS: [&str; 6] = [
+    "fill-sky-500",
+    "fill-amber-500",
+    "fill-emerald-500",
+    "fill-rose-500",
+    "fill-violet-500",
+    "fill-slate-500",
+];
+
+/// The percentage of each group with a flag, as a bar chart drawn in the page. Groups are along
+/// the bottom with a bar for each column, and a hidden percentage leaves a gap marked with a star.
+#[function_component(FlagChart)]
+pub fn flag_chart(props: &FlagChartProps) -> Html {
+    let report = &props.report;
+    let groups = report.row_keys.len().max(1) as f64;
+    let bars = report.column_keys.len().max(1) as f64;
+    let group_width = (WIDTH - AXIS) / groups;
+    let bar_width = group_width * 0.8 / bars;
+    let y = |percentage: f64| (HEIGHT - AXIS) * (1.0 - percentage / 100.0);
+
+    html! {
+        <div class="flex flex-col gap-1">
+            <h3 class="text-md">{format!("{} (%)", label(&DIMENSIONS, &props.flag))}</h3>
+            <svg viewBox={format!("0 0 {WIDTH} {HEIGHT}")} class="w-full max-w-lg text-xs">
+                {[0.0, 25.0, 50.0, 75.0, 100.0].iter().map(|tick| html! {
+                    <g>
+                        <line x1={AXIS.to_string()} x2={WIDTH.to_string()} y1={y(*tick).to_string()} y2={y(*tick).to_string()} class="stroke-slate-200"/>
+                        <text x={(AXIS - 4.0).to_string()} y={(y(*tick) + 4.0).to_string()} text-anchor="end" class="fill-slate-500">{tick.to_string()}</text>
+                    </g>
+                }).collect::<Html>()}
+                {report.row_keys.iter().zip(&report.cells).enumerate().map(|(row, (key, cells))| {
+                    let left = AXIS + group_width * row as f64 + group_width * 0.1;
+                    html! {
+                        <g>
+                            {cells.iter().enumerate().map(|(column, cell)| {
+                                let x = left + bar_width * column as f64;
+                                match cell.flags.get(&props.flag).and_then(|share| share.percentage) {
+                                    Some(percentage) => html! {
+                                        <rect x={x.to_string()} y={y(percentage).to_string()} width={bar_width.to_string()} height={(y(0.0) - y(percentage)).to_string()} class={COLOURS[column % COLOURS.len()]}>
+                                            <title>{format!("{}: {percentage}%", key_label(report.column_dimension(), &report.column_keys[column]))}</title>
+                                        </rect>
+                                    },
+                                    None => html! {
+                                        <text x={(x + bar_width / 2.0).to_string()} y={(y(0.0) - 4.0).to_string()} text-anchor="middle" class="fill-slate-400">{"*"}</text>
+                                    },
+                                }
+                            }).collect::<Html>()}
+                            <text x={(left + group_width * 0.4).to_string()} y={(HEIGHT - AXIS / 2.0).to_string()} text-anchor="middle">{key_label(&report.rows, key)}</text>
+                        </g>
+                    }
+                }).collect::<Html>()}
+            </svg>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct FlagChartProps {
+    pub report: CohortReport,
+    pub flag: String,
+}
+
+/// Which colour is which column, shared by every chart on the page
+#[function_component(ChartKey)]
+pub fn chart_key(props: &ChartKeyProps) -> Html {
+    let report = &props.report;
+    html! {
+        <div class="flex gap-3 text-sm">
+            {report.column_keys.iter().enumerate().map(|(column, key)| html! {
+                <span class="flex items-center gap-1">
+                    <svg viewBox="0 0 10 10" class="w-3 h-3">
+                        <rect width="10" height="10" class={COLOURS[column % COLOURS.len()]}/>
+                    </svg>
+                    {key_label(report.column_dimension(), key)}
+                </span>
+            }).collect::<Html>()}
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ChartKeyProps {
+    pub report: CohortReport,
+}
@@ -0,0 +1,151 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Measure {
+    Assessments {
+        assessment_ids: Vec<Uuid>,
+    },
+    Judgements {
+        what_matters_ids: Vec<Uuid>,
+        dates: Vec<NaiveDate>,
+    },
+}
+
+/// Flags left out of the map can be either way
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct Group {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub flags: BTreeMap<String, bool>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct GapQuery {
+    pub measure: Measure,
+    pub groups: [Group; 2],
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Band {
+    pub label: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct GroupSummary {
+    pub pupils: Option<usize>,
+    pub mean: Option<f64>,
+    pub distribution: Vec<Option<usize>>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct GapPoint {
+    pub date: NaiveDate,
+    pub label: String,
+    pub scale: String,
+    pub bands: Vec<Band>,
+    pub groups: Vec<GroupSummary>,
+    pub gap: Option<f64>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct GapReport {
+    pub threshold: usize,
+    pub groups: Vec<String>,
+    pub points: Vec<GapPoint>,
+    pub gap_change: Option<f64>,
+}
+
+fn shown<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or("*".into())
+}
+
+impl GapReport {
+    /// A row for each point with each group's size and mean, and the gap between them
+    pub fn trend_table(&self) -> Vec<Vec<String>> {
+        let mut header = vec!["Point".to_owned(), "Date".to_owned(), "Scale".to_owned()];
+        for group in &self.groups {
+            header.push(format!("{group} pupils"));
+            header.push(format!("{group} mean"));
+        }
+        header.push("Gap".into());
+        let mut table = vec![header];
+        for point in &self.points {
+            let mut row = vec![
+                point.label.clone(),
+                point.date.format("%d/%m/%Y").to_string(),
+                point.scale.replace('_', " "),
+            ];
+            for group in &point.groups {
+                row.push(shown(group.pupils));
+                row.push(shown(group.mean));
+            }
+            row.push(shown(point.gap));
+            table.push(row);
+        }
+        table
+    }
+
+    /// How many in each group scored in each band, a row per band for every point
+    pub fn distribution_table(&self) -> Vec<Vec<String>> {
+        let mut header = vec!["Point".to_owned(), "Band".to_owned()];
+        header.extend(self.groups.iter().cloned());
+        let mut table = vec![header];
+        for point in &self.points {
+            for (band, label) in point.bands.iter().enumerate() {
+                let mut row = vec![point.label.clone(), label.label.clone()];
+                for group in &point.groups {
+                    row.push(shown(group.distribution.get(band).copied().flatten()));
+                }
+                table.push(row);
+            }
+        }
+        table
+    }
+}
+
+/// Quote anything with a comma, quote or line break in it
+pub fn to_csv(table: &[Vec<String>]) -> String {
+    table
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| {
+                    if value.contains([',', '"', '\n']) {
+                        format!("\"{}\"", value.replace('"', "\"\""))
+                    } else {
+                        value.clone()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+/// Returns the server's field errors if it couldn't compare the groups
+pub async fn fetch_gap(
+    query: &GapQuery,
+    token: &str,
+) -> Result<std::result::Result<GapReport, HashMap<String, String>>> {
+    let response = Request::post(&format!("{}/gap", constant::ANALYTICS_PATH))
+        .json(query)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<GapReport>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,309 @@
+use super::{gap::*, report::FLAGS};
+use crate::{
+    app::AppContext,
+    assessments::{fetch_assessments, Assessment},
+    curriculum::{fetch_framework, Framework},
+    elements::{Button, IconButton},
+    error::*,
+    utils::use_download,
+};
+use chrono::NaiveDate;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Compare two groups of pupils, picked by their flags, on assessment results or progression
+/// judgements, with the gap at each point and tables that download as CSV.
+#[function_component(GapAnalysisPage)]
+pub fn gap_analysis_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN GAP ANALYSIS PAGE");
+    let assessments: UseStateHandle<Vec<Assessment>> = use_state_eq(Vec::new);
+    let framework: UseStateHandle<Framework> = use_state_eq(Framework::default);
+    let kind = use_state_eq(|| "assessments".to_owned());
+    let chosen: UseStateHandle<Vec<Uuid>> = use_state_eq(Vec::new);
+    let dates: UseStateHandle<Vec<NaiveDate>> = use_state_eq(Vec::new);
+    let groups: UseStateHandle<[Group; 2]> = use_state_eq(|| {
+        [
+            Group {
+                name: None,
+                flags: [("fsm".to_owned(), true)].into(),
+            },
+            Group {
+                name: None,
+                flags: [("fsm".to_owned(), false)].into(),
+            },
+        ]
+    });
+    let report: UseStateHandle<Option<GapReport>> = use_state_eq(|| None);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let file = use_download();
+    {
+        clone!(ctx, assessments, framework);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    let fetched = match fetch_assessments(&ctx.auth_token).await {
+                        Ok(fetched) => fetch_framework(&ctx.auth_token)
+                            .await
+                            .map(|fetched_framework| (fetched, fetched_framework)),
+                        Err(error) => Err(error),
+                    };
+                    match fetched {
+                        Ok((fetched, fetched_framework)) => {
+                            assessments.set(fetched);
+                            framework.set(fetched_framework);
+                        }
+                        Err(error) => {
+                            error!(
+                                "failed to get assessments and statements:",
+                                error.to_string()
+                            );
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+
+    let choose_kind = {
+        clone!(kind, chosen, report);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            kind.set(target.value());
+            chosen.set(vec![]);
+            report.set(None);
+        })
+    };
+    let toggle = {
+        clone!(chosen);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let Ok(id) = target.value().parse::<Uuid>() else {
+                return;
+            };
+            let mut next = (*chosen).clone();
+            next.retain(|chosen| *chosen != id);
+            if target.checked() {
+                next.push(id);
+            }
+            chosen.set(next);
+        })
+    };
+    let add_date = {
+        clone!(dates);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            if let Ok(date) = NaiveDate::parse_from_str(&target.value(), "%Y-%m-%d") {
+                let mut next = (*dates).clone();
+                next.push(date);
+                next.sort_unstable();
+                next.dedup();
+                dates.set(next);
+            }
+            target.set_value("");
+        })
+    };
+    let update_group = {
+        clone!(groups);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let id = target.id();
+            let Some((group, field)) = id
+                .strip_prefix("gap_group_")
+                .and_then(|rest| rest.split_once('_'))
+            else {
+                return;
+            };
+            let Ok(group) = group.parse::<usize>() else {
+                return;
+            };
+            let mut next = (*groups).clone();
+            let value = target.value();
+            match field {
+                "name" => next[group].name = (!value.trim().is_empty()).then_some(value),
+                flag => match value.as_str() {
+                    "yes" => {
+                        next[group].flags.insert(flag.into(), true);
+                    }
+                    "no" => {
+                        next[group].flags.insert(flag.into(), false);
+                    }
+                    _ => {
+                        next[group].flags.remove(flag);
+                    }
+                },
+            }
+            groups.set(next);
+        })
+    };
+    let compare = {
+        clone!(ctx, kind, chosen, dates, groups, report, errors);
+        Callback::from(move |_: MouseEvent| {
+            let query = GapQuery {
+                measure: match kind.as_str() {
+                    "judgements" => Measure::Judgements {
+                        what_matters_ids: (*chosen).clone(),
+                        dates: (*dates).clone(),
+                    },
+                    _ => Measure::Assessments {
+                        assessment_ids: (*chosen).clone(),
+                    },
+                },
+                groups: (*groups).clone(),
+            };
+            clone!(ctx, report, errors);
+            spawn_local(async move {
+                match fetch_gap(&query, &ctx.auth_token).await {
+                    Ok(Ok(fetched)) => {
+                        errors.set(HashMap::new());
+                        report.set(Some(fetched));
+                    }
+                    Ok(Err(fields)) => {
+                        report.set(None);
+                        errors.set(fields);
+                    }
+                    Err(error) => error!("failed to compare groups:", error.to_string()),
+                }
+            });
+        })
+    };
+    let download = {
+        clone!(file);
+        Callback::from(move |(name, table): (&'static str, Vec<Vec<String>>)| {
+            file.save(to_csv(&table).as_str(), "text/csv", &format!("{name}.csv"));
+        })
+    };
+
+    let flag_choice = |group: usize, flag: &str| {
+        let current = groups[group].flags.get(flag).copied();
+        html! {
+            <label class="flex items-center justify-between gap-1">
+                {flag.to_uppercase()}
+                <select id={format!("gap_group_{group}_{flag}")} class="border-2 border-slate-200 rounded-md" onchange={&update_group}>
+                    <option value="" selected={current.is_none()}>{"either"}</option>
+                    <option value="yes" selected={current == Some(true)}>{"yes"}</option>
+                    <option value="no" selected={current == Some(false)}>{"no"}</option>
+                </select>
+            </label>
+        }
+    };
+    let table = |rows: Vec<Vec<String>>| {
+        html! {
+            <table class="w-full text-sm text-left">
+                <thead>
+                    <tr>
+                        {rows.first().into_iter().flatten().map(|heading| html!(<th>{heading}</th>)).collect::<Html>()}
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows.iter().skip(1).map(|row| html! {
+                        <tr class="border-t border-slate-200">
+                            {row.iter().map(|value| html!(<td>{value}</td>)).collect::<Html>()}
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        }
+    };
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white flex flex-col gap-3">
+            <div class="flex gap-2 items-center">
+                <h2 class="text-xl">{"Attainment gap"}</h2>
+                <select id="gap_kind" class="border-2 border-slate-200 rounded-md" onchange={choose_kind}>
+                    <option value="assessments" selected={*kind == "assessments"}>{"on assessments"}</option>
+                    <option value="judgements" selected={*kind == "judgements"}>{"on progression judgements"}</option>
+                </select>
+            </div>
+            <div class="flex gap-6 text-sm">
+                <div class="flex flex-col gap-1 max-h-64 overflow-y-auto scrollbar w-[400px]">
+                    if *kind == "judgements" {
+                        {framework.areas.iter().map(|area| html! {
+                            <>
+                                <p class="font-semibold">{&area.name}</p>
+                                {area.what_matters.iter().map(|statement| html! {
+                                    <label class="flex items-center gap-1">
+                                        <input type="checkbox" value={statement.id.to_string()} checked={chosen.contains(&statement.id)} onchange={&toggle}/>
+                                        {&statement.statement}
+                                    </label>
+                                }).collect::<Html>()}
+                            </>
+                        }).collect::<Html>()}
+                    } else {
+                        {assessments.iter().filter_map(|assessment| Some((assessment.id?, assessment))).map(|(id, assessment)| html! {
+                            <label class="flex items-center gap-1">
+                                <input type="checkbox" value={id.to_string()} checked={chosen.contains(&id)} onchange={&toggle}/>
+                                {format!("{} (year {}, {})", assessment.name, assessment.year, assessment.date.format("%d/%m/%Y"))}
+                            </label>
+                        }).collect::<Html>()}
+                    }
+                </div>
+                if *kind == "judgements" {
+                    <div class="flex flex-col gap-1">
+                        <label class="flex items-center gap-1">
+                            {"As at"}
+                            <input type="date" id="gap_date" class="border-2 border-slate-200 rounded-md" onchange={add_date}/>
+                        </label>
+                        if dates.is_empty() {
+                            <span class="text-slate-500">{"today"}</span>
+                        }
+                        {dates.iter().map(|date| {
+                            let remove = {
+                                clone!(dates);
+                                let date = *date;
+                                Callback::from(move |_: MouseEvent| {
+                                    dates.set(dates.iter().filter(|d| **d != date).cloned().collect());
+                                })
+                            };
+                            html! {
+                                <span class="flex items-center gap-1">
+                                    {date.format("%d/%m/%Y").to_string()}
+                                    <IconButton icon="close" onclick={remove} />
+                                </span>
+                            }
+                        }).collect::<Html>()}
+                    </div>
+                }
+                {[0, 1].into_iter().map(|group| html! {
+                    <div class="flex flex-col gap-1 w-40">
+                        <input type="text" id={format!("gap_group_{group}_name")} placeholder={if group == 0 { "First group" } else { "Second group" }} class="border-2 border-slate-200 rounded-md" value={groups[group].name.clone().unwrap_or_default()} onchange={&update_group}/>
+                        {FLAGS.iter().map(|flag| flag_choice(group, flag)).collect::<Html>()}
+                    </div>
+                }).collect::<Html>()}
+            </div>
+            <Button color="green" text="Compare" onclick={compare} />
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            if let Some(report) = &*report {
+                <p class="text-sm text-slate-500">
+                    {format!("The gap is {} less {}. Groups and bands under {} pupils are hidden and show as *.", report.groups[0], report.groups[1], report.threshold)}
+                </p>
+                if let Some(change) = report.gap_change {
+                    <p class="text-md">{format!("The gap has moved by {change:+} since the first point")}</p>
+                }
+                <div class="flex gap-2 items-center">
+                    <h3 class="text-md">{"Means and gap"}</h3>
+                    <IconButton icon="download" onclick={
+                        clone!(download, report);
+                        Callback::from(move |_: MouseEvent| download.emit(("attainment_gap", report.trend_table())))
+                    }/>
+                </div>
+                {table(report.trend_table())}
+                <div class="flex gap-2 items-center">
+                    <h3 class="text-md">{"Distributions"}</h3>
+                    <IconButton icon="download" onclick={
+                        clone!(download, report);
+                        Callback::from(move |_: MouseEvent| download.emit(("attainment_distribution", report.distribution_table())))
+                    }/>
+                </div>
+                {table(report.distribution_table())}
+            }
+            <a ref={file.link.clone()} class="hidden"></a>
+        </div>
+    }
+}
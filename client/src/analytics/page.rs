@@ -0,0 +1,159 @@
+use super::{
+    chart::{ChartKey, FlagChart},
+    report::*,
+};
+use crate::{app::AppContext, error::*};
+use chrono::NaiveDate;
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Headline numbers for the user's years: how many pupils are in each group and what share of
+/// them have each flag, by one dimension or crossed with a second. Small numbers come back hidden
+/// from the server and show as a star.
+#[function_component(CohortDashboardPage)]
+pub fn cohort_dashboard_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN COHORT DASHBOARD PAGE");
+    let rows = use_state_eq(|| "year".to_owned());
+    let columns: UseStateHandle<Option<String>> = use_state_eq(|| None);
+    let as_at: UseStateHandle<Option<NaiveDate>> = use_state_eq(|| None);
+    let report: UseStateHandle<Option<CohortReport>> = use_state_eq(|| None);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    {
+        clone!(ctx, report, errors);
+        use_effect_with_deps(
+            move |(rows, columns, as_at): &(String, Option<String>, Option<NaiveDate>)| {
+                clone!(rows, columns, as_at);
+                spawn_local(async move {
+                    match fetch_cohort(&rows, columns.as_deref(), as_at, &ctx.auth_token).await {
+                        Ok(Ok(fetched)) => {
+                            errors.set(HashMap::new());
+                            report.set(Some(fetched));
+                        }
+                        Ok(Err(fields)) => {
+                            report.set(None);
+                            errors.set(fields);
+                        }
+                        Err(error) => {
+                            error!("failed to get the cohort:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            ((*rows).clone(), (*columns).clone(), *as_at),
+        );
+    }
+
+    let update = {
+        clone!(rows, columns, as_at);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let value = target.value();
+            match target.id().as_str() {
+                "cohort_rows" => rows.set(value),
+                "cohort_columns" => columns.set((!value.is_empty()).then_some(value)),
+                "cohort_as_at" => as_at.set(NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok()),
+                _ => {}
+            }
+        })
+    };
+
+    let share = |value: Option<&Share>| match value {
+        Some(Share {
+            count: Some(count),
+            percentage: Some(percentage),
+        }) => format!("{count} ({percentage}%)"),
+        Some(Share {
+            count: Some(count), ..
+        }) => count.to_string(),
+        _ => "*".into(),
+    };
+    let count = |count: Option<usize>| count.map(|c| c.to_string()).unwrap_or("*".into());
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white flex flex-col gap-3">
+            <div class="flex gap-2 items-center">
+                <h2 class="text-xl">{"Cohort"}</h2>
+                <label class="flex items-center gap-1 text-sm">
+                    {"by"}
+                    <select id="cohort_rows" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                        {DIMENSIONS.iter().map(|(value, name)| html! {
+                            <option value={*value} selected={*rows == *value}>{*name}</option>
+                        }).collect::<Html>()}
+                    </select>
+                </label>
+                <label class="flex items-center gap-1 text-sm">
+                    {"and"}
+                    <select id="cohort_columns" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                        <option value="" selected={columns.is_none()}>{"nothing else"}</option>
+                        {DIMENSIONS.iter().map(|(value, name)| html! {
+                            <option value={*value} selected={columns.as_deref() == Some(*value)}>{*name}</option>
+                        }).collect::<Html>()}
+                    </select>
+                </label>
+                <label class="flex items-center gap-1 text-sm">
+                    {"as at"}
+                    <input type="date" id="cohort_as_at" class="border-2 border-slate-200 rounded-md" onchange={&update}/>
+                </label>
+            </div>
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            if let Some(report) = &*report {
+                <p class="text-sm text-slate-500">
+                    {format!("Pupils on roll on {}. Counts under {} are hidden, along with any that would give them away, and show as *.", report.as_at.format("%d/%m/%Y"), report.threshold)}
+                </p>
+                <ChartKey report={report.clone()} />
+                <div class="grid grid-cols-2 gap-3">
+                    {FLAGS.iter().filter(|flag| **flag != report.rows && Some(**flag) != report.columns.as_deref()).map(|flag| html! {
+                        <FlagChart report={report.clone()} flag={flag.to_string()} />
+                    }).collect::<Html>()}
+                </div>
+                <table class="w-full text-sm text-left">
+                    <thead>
+                        <tr>
+                            <th>{label(&DIMENSIONS, &report.rows)}</th>
+                            if report.columns.is_some() {
+                                <th>{label(&DIMENSIONS, report.column_dimension())}</th>
+                            }
+                            <th>{"Pupils"}</th>
+                            {FLAGS.iter().map(|flag| html!(<th>{label(&DIMENSIONS, flag)}</th>)).collect::<Html>()}
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {report.row_keys.iter().zip(&report.cells).zip(&report.row_totals).map(|((key, cells), total)| html! {
+                            <>
+                                {cells.iter().zip(&report.column_keys).filter(|_| report.columns.is_some()).map(|(cell, column)| html! {
+                                    <tr class="border-t border-slate-200">
+                                        <td>{key_label(&report.rows, key)}</td>
+                                        <td>{key_label(report.column_dimension(), column)}</td>
+                                        <td>{count(cell.count)}</td>
+                                        {FLAGS.iter().map(|flag| html!(<td>{share(cell.flags.get(*flag))}</td>)).collect::<Html>()}
+                                    </tr>
+                                }).collect::<Html>()}
+                                <tr class="border-t border-slate-200 font-semibold">
+                                    <td>{key_label(&report.rows, key)}</td>
+                                    if report.columns.is_some() {
+                                        <td>{"All"}</td>
+                                    }
+                                    <td>{count(total.count)}</td>
+                                    {FLAGS.iter().map(|flag| html!(<td>{share(total.flags.get(*flag))}</td>)).collect::<Html>()}
+                                </tr>
+                            </>
+                        }).collect::<Html>()}
+                        <tr class="border-t-2 border-slate-400 font-semibold">
+                            <td>{"All"}</td>
+                            if report.columns.is_some() {
+                                <td></td>
+                            }
+                            <td>{count(report.total.count)}</td>
+                            {FLAGS.iter().map(|flag| html!(<td>{share(report.total.flags.get(*flag))}</td>)).collect::<Html>()}
+                        </tr>
+                    </tbody>
+                </table>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+pub static DIMENSIONS: [(&str, &str); 7] = [
+    ("year", "Year group"),
+    ("gender", "Gender"),
+    ("fsm", "FSM"),
+    ("eal", "EAL"),
+    ("aln", "ALN"),
+    ("mat", "MAT"),
+    ("lac", "LAC"),
+];
+pub static FLAGS: [&str; 5] = ["fsm", "eal", "aln", "mat", "lac"];
+
+pub fn label<'a>(options: &[(&str, &'a str)], value: &'a str) -> &'a str {
+    options
+        .iter()
+        .find(|(option, _)| *option == value)
+        .map_or(value, |(_, label)| label)
+}
+
+/// How a group reads on screen, given the dimension it's a group of
+pub fn key_label(dimension: &str, key: &str) -> String {
+    match (dimension, key) {
+        (_, "all") => "All".into(),
+        ("year", year) => format!("Year {year}"),
+        ("gender", gender) => {
+            let mut chars = gender.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        (flag, "yes") => label(&DIMENSIONS, flag).to_owned(),
+        (flag, _) => format!("Not {}", label(&DIMENSIONS, flag)),
+    }
+}
+
+/// A count left out by the server is one small enough to pick pupils out by, or one that would
+/// let a hidden count be worked out from the totals.
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Share {
+    pub count: Option<usize>,
+    pub percentage: Option<f64>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Cell {
+    pub count: Option<usize>,
+    pub flags: BTreeMap<String, Share>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct CohortReport {
+    pub as_at: NaiveDate,
+    pub threshold: usize,
+    pub rows: String,
+    #[serde(default)]
+    pub columns: Option<String>,
+    pub row_keys: Vec<String>,
+    pub column_keys: Vec<String>,
+    pub cells: Vec<Vec<Cell>>,
+    pub row_totals: Vec<Cell>,
+    pub column_totals: Vec<Cell>,
+    pub total: Cell,
+}
+
+impl CohortReport {
+    /// The dimension the columns are, for labelling them
+    pub fn column_dimension(&self) -> &str {
+        self.columns.as_deref().unwrap_or("all")
+    }
+}
+
+/// Returns the server's errors if it won't break the cohort down that way
+pub async fn fetch_cohort(
+    rows: &str,
+    columns: Option<&str>,
+    as_at: Option<NaiveDate>,
+    token: &str,
+) -> Result<std::result::Result<CohortReport, HashMap<String, String>>> {
+    let as_at = as_at.map(|date| date.to_string());
+    let mut query = vec![("rows", rows)];
+    if let Some(columns) = columns {
+        query.push(("columns", columns));
+    }
+    if let Some(as_at) = &as_at {
+        query.push(("as_at", as_at.as_str()));
+    }
+    let response = Request::get(&format!("{}/cohort", constant::ANALYTICS_PATH))
+        .query(query)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<CohortReport>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
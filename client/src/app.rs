@@ -1,6 +1,6 @@
 use crate::elements::ModalProvider;
 use crate::utils;
-use crate::{constant, debug, error, login, menu, navbar, pupils, routes::Route, users::User};
+use crate::{aln, analytics, assessments, attendance, census, comments, concerns, constant, curriculum, debug, error, interventions, login, medical, menu, navbar, pupils, quality, reports, routes::Route, users::User};
 use gloo_net::http::Request;
 use gloo_storage::{errors::StorageError, SessionStorage, Storage};
 use serde::Deserialize;
@@ -77,6 +77,20 @@ pub fn app() -> Html {
                                             {match route {
                                                 Route::Login |
                                                 Route::ManagePupils  => html! { <pupils::PupilTable />},
+                                                Route::ClassLists    => html! { <pupils::ClassListPage />},
+                                                Route::Comments      => html! { <comments::CommentsPage />},
+                                                Route::Concerns      => html! { <concerns::ConcernsPage />},
+                                                Route::Assessments   => html! { <assessments::AssessmentsPage />},
+                                                Route::Attendance    => html! { <attendance::RegisterPage />},
+                                                Route::Progression   => html! { <curriculum::OverviewPage />},
+                                                Route::Interventions => html! { <interventions::InterventionsPage />},
+                                                Route::AlnRegister   => html! { <aln::AlnRegisterPage />},
+                                                Route::Allergies     => html! { <medical::AllergyReportPage />},
+                                                Route::Analytics     => html! { <analytics::CohortDashboardPage />},
+                                                Route::AttainmentGap => html! { <analytics::GapAnalysisPage />},
+                                                Route::Reports       => html! { <reports::ReportsPage />},
+                                                Route::Census        => html! { <census::CensusPage />},
+                                                Route::DataQuality   => html! { <quality::DataQualityPage />},
                                                 Route::ManageUsers   => html! { <pupils::PupilTable />},
                                             }}
                                         </div>
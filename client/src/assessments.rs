@@ -0,0 +1,7 @@
+mod assessment;
+mod create_box;
+mod grid;
+mod page;
+
+pub use assessment::{fetch_assessments, Assessment};
+pub use page::AssessmentsPage;
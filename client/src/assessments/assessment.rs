@@ -0,0 +1,129 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+use yew::UseStateHandle;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct StandardisationRow {
+    pub raw: i32,
+    pub standardised: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Assessment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub subject: String,
+    pub year: i32,
+    pub date: NaiveDate,
+    pub max_score: i32,
+    #[serde(default)]
+    pub standardisation: Vec<StandardisationRow>,
+}
+
+/// A pupil's row in the results grid, the scores are only there once a raw score is saved
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct GridRow {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub raw_score: Option<i32>,
+    pub percentage: Option<f64>,
+    pub standardised_score: Option<i32>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct ResultGrid {
+    pub assessment: Assessment,
+    pub rows: Vec<GridRow>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct ResultEntry {
+    pub pupil_id: Uuid,
+    pub raw_score: Option<i32>,
+}
+
+fn results_path(id: &Uuid) -> String {
+    format!("{}/{id}/results", constant::ASSESSMENTS_PATH)
+}
+
+pub async fn fetch_assessments(token: &str) -> Result<Vec<Assessment>> {
+    let response = Request::get(constant::ASSESSMENTS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Assessment>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the assessment
+pub async fn create_assessment(
+    assessment: &Assessment,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(constant::ASSESSMENTS_PATH)
+        .json(assessment)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_grid(
+    id: &Uuid,
+    token: &str,
+    grid: UseStateHandle<Option<ResultGrid>>,
+) -> Result<()> {
+    let response = Request::get(&results_path(id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => {
+            grid.set(Some(response.json::<ResultGrid>().await?));
+            Ok(())
+        }
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Saves the changed cells and shows the scores the server worked out, or returns its errors
+/// keyed by pupil id
+pub async fn save_results(
+    id: &Uuid,
+    entries: &[ResultEntry],
+    token: &str,
+    grid: UseStateHandle<Option<ResultGrid>>,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&results_path(id))
+        .json(&entries)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => {
+            grid.set(Some(response.json::<ResultGrid>().await?));
+            Ok(None)
+        }
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,158 @@
+use super::assessment::*;
+use crate::{app::AppContext, elements::Button};
+use chrono::NaiveDate;
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The raw text of the create form, only turned into an Assessment when it's submitted
+#[derive(Clone, PartialEq, Default)]
+struct InputState {
+    name: String,
+    subject: String,
+    year: String,
+    date: String,
+    max_score: String,
+    standardisation: String,
+    errors: HashMap<String, String>,
+}
+
+impl InputState {
+    fn update(&mut self, target: HtmlInputElement) {
+        let id = target.id();
+        self.errors.remove(&id);
+        match id.as_str() {
+            "name" => self.name = target.value(),
+            "subject" => self.subject = target.value(),
+            "year" => self.year = target.value(),
+            "date" => self.date = target.value(),
+            "max_score" => self.max_score = target.value(),
+            "standardisation" => self.standardisation = target.value(),
+            _ => {}
+        }
+    }
+
+    /// Parse the form, collecting a message for every input that isn't usable
+    fn to_assessment(&self) -> std::result::Result<Assessment, HashMap<String, String>> {
+        let mut errors = HashMap::new();
+        let year = self.year.parse::<i32>().map_err(|_| {
+            errors.insert("year".to_string(), "choose a year".to_string());
+        });
+        let date = self.date.parse::<NaiveDate>().map_err(|_| {
+            errors.insert("date".to_string(), "date must be a valid date".to_string());
+        });
+        let max_score = self.max_score.parse::<i32>().map_err(|_| {
+            errors.insert("max_score".to_string(), "max score must be a whole number".to_string());
+        });
+        let standardisation = parse_standardisation(&self.standardisation).map_err(|_| {
+            errors.insert(
+                "standardisation".to_string(),
+                "write each row as raw:standardised, separated by commas or new lines".to_string(),
+            );
+        });
+        match (year, date, max_score, standardisation) {
+            (Ok(year), Ok(date), Ok(max_score), Ok(standardisation)) => Ok(Assessment {
+                id: None,
+                name: self.name.clone(),
+                subject: self.subject.clone(),
+                year,
+                date,
+                max_score,
+                standardisation,
+            }),
+            _ => Err(errors),
+        }
+    }
+
+    fn error(&self, id: &str) -> Html {
+        match self.errors.get(id) {
+            Some(error) => html!(<span class="text-xs text-red-500">{error}</span>),
+            None => html!(),
+        }
+    }
+}
+
+fn parse_standardisation(text: &str) -> std::result::Result<Vec<StandardisationRow>, ()> {
+    text.split(|c| c == ',' || c == '\n')
+        .map(str::trim)
+        .filter(|row| !row.is_empty())
+        .map(|row| {
+            let (raw, standardised) = row.split_once(':').ok_or(())?;
+            Ok(StandardisationRow {
+                raw: raw.trim().parse().map_err(|_| ())?,
+                standardised: standardised.trim().parse().map_err(|_| ())?,
+            })
+        })
+        .collect()
+}
+
+#[function_component(AssessmentCreateBox)]
+pub fn assessment_create_box(props: &AssessmentCreateBoxProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ASSESSMENT CREATE BOX");
+    let input_state = use_state(InputState::default);
+
+    let update_state_cb = {
+        clone!(input_state);
+        Callback::from(move |ev: Event| {
+            let mut state = (*input_state).clone();
+            state.update(ev.target_unchecked_into());
+            input_state.set(state);
+        })
+    };
+    let create_callback = {
+        let refresh_callback = props.refresh_callback.clone();
+        clone!(input_state, ctx);
+        Callback::from(move |_| {
+            clone!(input_state, ctx, refresh_callback);
+            let assessment = match input_state.to_assessment() {
+                Ok(assessment) => assessment,
+                Err(errors) => {
+                    input_state.set(InputState { errors, ..(*input_state).clone() });
+                    return;
+                }
+            };
+            spawn_local(async move {
+                match create_assessment(&assessment, &ctx.auth_token).await {
+                    Ok(None) => {
+                        refresh_callback.emit(());
+                        input_state.set(InputState::default());
+                    }
+                    Ok(Some(errors)) => input_state.set(InputState { errors, ..(*input_state).clone() }),
+                    Err(error) => error!("failed to create assessment:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-1 p-3 shadow-lg rounded-md bg-white">
+            <input id="name" class="border-2 border-slate-200 rounded-md" placeholder="Name" value={input_state.name.clone()} onchange={&update_state_cb}/>
+            {input_state.error("name")}
+            <input id="subject" class="border-2 border-slate-200 rounded-md" placeholder="Subject" value={input_state.subject.clone()} onchange={&update_state_cb}/>
+            {input_state.error("subject")}
+            <div class="flex gap-2">
+                <select id="year" class="border-2 border-slate-200 rounded-md" onchange={&update_state_cb}>
+                    <option value="" selected={input_state.year.is_empty()} disabled=true>{"Year"}</option>
+                    {ctx.current_user.years.iter().map(|year| html! {
+                        <option value={year.to_string()} selected={input_state.year == year.to_string()}>{year}</option>
+                    }).collect::<Html>()}
+                </select>
+                <input id="date" type="date" class="border-2 border-slate-200 rounded-md" value={input_state.date.clone()} onchange={&update_state_cb}/>
+                <input id="max_score" type="number" min="1" class="border-2 border-slate-200 rounded-md w-24" placeholder="Max score" value={input_state.max_score.clone()} onchange={&update_state_cb}/>
+            </div>
+            {input_state.error("year")}
+            {input_state.error("date")}
+            {input_state.error("max_score")}
+            <textarea id="standardisation" class="border-2 border-slate-200 rounded-md text-sm" rows="2" placeholder="Standardisation table (optional), e.g. 0:70, 20:100, 30:115" value={input_state.standardisation.clone()} onchange={&update_state_cb}/>
+            {input_state.error("standardisation")}
+            {input_state.error("form")}
+            <Button icon={html!(<yew_feather::Plus size="16" />)} text="Add" color="green" onclick={&create_callback} />
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct AssessmentCreateBoxProps {
+    pub refresh_callback: Callback<()>,
+}
@@ -0,0 +1,123 @@
+use super::assessment::*;
+use crate::{app::AppContext, elements::Button};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Bulk entry of raw scores for every pupil in the assessment's year
+#[function_component(ResultsGrid)]
+pub fn results_grid(props: &ResultsGridProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN RESULTS GRID");
+    let grid: UseStateHandle<Option<ResultGrid>> = use_state_eq(|| None);
+    // cells changed since the last save, keyed by pupil, with the text as typed
+    let edits: UseStateHandle<HashMap<Uuid, String>> = use_state_eq(HashMap::new);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    {
+        clone!(ctx, grid, edits, errors);
+        use_effect_with_deps(
+            move |id: &Uuid| {
+                let id = *id;
+                edits.set(HashMap::new());
+                errors.set(HashMap::new());
+                spawn_local(async move {
+                    if let Err(error) = fetch_grid(&id, &ctx.auth_token, grid).await {
+                        error!("failed to get results grid:", error.to_string());
+                    }
+                });
+            },
+            props.assessment_id,
+        );
+    }
+
+    let save = {
+        clone!(ctx, grid, edits, errors);
+        let id = props.assessment_id;
+        Callback::from(move |_| {
+            clone!(ctx, grid, edits, errors);
+            let mut entries = vec![];
+            let mut parse_errors = HashMap::new();
+            for (pupil_id, text) in edits.iter() {
+                match text.trim() {
+                    "" => entries.push(ResultEntry { pupil_id: *pupil_id, raw_score: None }),
+                    text => match text.parse::<i32>() {
+                        Ok(raw) => entries.push(ResultEntry { pupil_id: *pupil_id, raw_score: Some(raw) }),
+                        Err(_) => {
+                            parse_errors.insert(pupil_id.to_string(), "score must be a whole number".to_string());
+                        }
+                    },
+                }
+            }
+            if !parse_errors.is_empty() {
+                errors.set(parse_errors);
+                return;
+            }
+            spawn_local(async move {
+                match save_results(&id, &entries, &ctx.auth_token, grid).await {
+                    Ok(None) => {
+                        edits.set(HashMap::new());
+                        errors.set(HashMap::new());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to save results:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let Some(result_grid) = (*grid).clone() else {
+        return html!(<p class="p-3">{"Loading results..."}</p>);
+    };
+    html! {
+        <div class="flex flex-col gap-2 p-3 shadow-lg rounded-md bg-white">
+            <div class="flex justify-between items-center">
+                <h2 class="text-xl">{format!("{} ({}), year {}, out of {}", result_grid.assessment.name, result_grid.assessment.subject, result_grid.assessment.year, result_grid.assessment.max_score)}</h2>
+                <Button icon={html!(<yew_feather::Save size="16" />)} text="Save" color="green" onclick={&save} />
+            </div>
+            if let Some(error) = errors.get("form") {
+                <p class="text-xs text-red-500">{error}</p>
+            }
+            <div class="overflow-y-auto [max-height:calc(80vh-60px)] scrollbar">
+                <table class="w-full text-left">
+                    <thead>
+                        <tr><th>{"Pupil"}</th><th>{"Raw"}</th><th>{"%"}</th><th>{"Standardised"}</th></tr>
+                    </thead>
+                    <tbody>
+                        {result_grid.rows.iter().map(|row| {
+                            let pupil_id = row.pupil_id;
+                            let value = edits.get(&pupil_id).cloned().unwrap_or_else(|| row.raw_score.map(|raw| raw.to_string()).unwrap_or_default());
+                            let onchange = {
+                                clone!(edits);
+                                Callback::from(move |ev: Event| {
+                                    let target: HtmlInputElement = ev.target_unchecked_into();
+                                    let mut changed = (*edits).clone();
+                                    changed.insert(pupil_id, target.value());
+                                    edits.set(changed);
+                                })
+                            };
+                            html! {
+                                <tr class="border-t border-slate-200">
+                                    <td>{format!("{} {}", row.first_names, row.last_name)}</td>
+                                    <td>
+                                        <input type="number" min="0" max={result_grid.assessment.max_score.to_string()} class="border-2 border-slate-200 rounded-md w-20" {value} {onchange}/>
+                                        if let Some(error) = errors.get(&pupil_id.to_string()) {
+                                            <span class="block text-xs text-red-500">{error}</span>
+                                        }
+                                    </td>
+                                    <td>{row.percentage.map(|p| format!("{p:.1}")).unwrap_or_default()}</td>
+                                    <td>{row.standardised_score.map(|s| s.to_string()).unwrap_or_default()}</td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+            </div>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ResultsGridProps {
+    pub assessment_id: Uuid,
+}
@@ -0,0 +1,66 @@
+use super::{assessment::*, create_box::AssessmentCreateBox, grid::ResultsGrid};
+use crate::{app::AppContext, error::*};
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Assessments for the user's years, with the results grid for whichever one is selected
+#[function_component(AssessmentsPage)]
+pub fn assessments_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ASSESSMENTS PAGE");
+    let assessments: UseStateHandle<Vec<Assessment>> = use_state_eq(Vec::new);
+    let selected: UseStateHandle<Option<Uuid>> = use_state_eq(|| None);
+
+    let refresh_callback = {
+        clone!(ctx, assessments);
+        Callback::from(move |_: ()| {
+            clone!(ctx, assessments);
+            spawn_local(async move {
+                match fetch_assessments(&ctx.auth_token).await {
+                    Ok(fetched) => assessments.set(fetched),
+                    Err(error) => {
+                        error!("failed to get assessments:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh_callback);
+        use_effect_with_deps(move |_| refresh_callback.emit(()), ());
+    }
+
+    html! {
+        <div class="flex m-3 gap-3">
+            <div class="flex flex-col gap-3 w-[350px]">
+                <AssessmentCreateBox refresh_callback={&refresh_callback} />
+                <ul class="flex flex-col gap-1 p-3 shadow-lg rounded-md bg-white overflow-y-auto [max-height:calc(60vh)] scrollbar">
+                    {assessments.iter().map(|assessment| {
+                        let id = assessment.id;
+                        let is_selected = *selected == id;
+                        html! {
+                            <li class={classes!("cursor-pointer", "rounded-md", "p-1", is_selected.then_some("bg-slate-200"))} onclick={
+                                clone!(selected);
+                                Callback::from(move |_| selected.set(id))
+                            }>
+                                <p>{&assessment.name}</p>
+                                <p class="text-xs text-slate-500">{format!("{} · year {} · {}", assessment.subject, assessment.year, assessment.date.format("%d/%m/%Y"))}</p>
+                            </li>
+                        }
+                    }).collect::<Html>()}
+                </ul>
+            </div>
+            <div class="grow">
+                if let Some(id) = *selected {
+                    <ResultsGrid assessment_id={id} />
+                } else {
+                    <p class="p-3">{"Choose an assessment to enter results"}</p>
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,4 @@
+mod attachment;
+mod panel;
+
+pub use panel::AttachmentsPanel;
@@ -0,0 +1,101 @@
+use crate::error::{ErrorResponse, Result};
+use chrono::NaiveDateTime;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static ACCEPTED_TYPES: &str = ".pdf,.docx,.jpg,.jpeg,.png,.txt";
+
+/// The details of a stored file, the file itself is fetched through a short lived link
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: usize,
+    pub scan_status: String,
+    #[serde(default)]
+    pub description: String,
+    pub uploaded_by: String,
+    pub uploaded_at: NaiveDateTime,
+}
+
+impl Attachment {
+    pub fn display_size(&self) -> String {
+        match self.size {
+            size if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+            size if size >= 1024 => format!("{} KB", size / 1024),
+            size => format!("{size} bytes"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub description: String,
+    /// the file contents, base64 encoded
+    pub data: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct DownloadLink {
+    pub url: String,
+}
+
+pub async fn fetch_attachments(path: &str, token: &str) -> Result<Vec<Attachment>> {
+    let response = Request::get(path)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Attachment>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the file
+pub async fn upload_attachment(
+    path: &str,
+    upload: &NewAttachment,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(path)
+        .json(upload)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_link(path: &str, id: &Uuid, token: &str) -> Result<DownloadLink> {
+    let response = Request::get(&format!("{path}/{id}"))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<DownloadLink>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_attachment(path: &str, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{path}/{id}"))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
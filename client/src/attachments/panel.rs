@@ -0,0 +1,154 @@
+use super::attachment::*;
+use crate::{app::AppContext, elements::IconButton};
+use base64::{engine::general_purpose, Engine};
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The files attached to a pupil or a concern, with an upload box. Downloads go through a signed
+/// link from the server, so they are checked against the user's access when they're fetched.
+#[function_component(AttachmentsPanel)]
+pub fn attachments_panel(props: &AttachmentsPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ATTACHMENTS PANEL");
+    let attachments: UseStateHandle<Vec<Attachment>> = use_state_eq(Vec::new);
+    let description = use_state_eq(String::new);
+    let uploading = use_state_eq(|| false);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let path = props.path.clone();
+
+    let refresh = {
+        clone!(ctx, attachments, path);
+        Callback::from(move |_: ()| {
+            clone!(ctx, attachments, path);
+            spawn_local(async move {
+                match fetch_attachments(&path, &ctx.auth_token).await {
+                    Ok(fetched) => attachments.set(fetched),
+                    Err(error) => error!("failed to get attachments:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.path.clone());
+    }
+
+    let upload = {
+        clone!(ctx, path, description, uploading, errors, refresh);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let Some(file) = target.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            target.set_value("");
+            let file = gloo_file::File::from(file);
+            uploading.set(true);
+            clone!(ctx, path, description, uploading, errors, refresh);
+            spawn_local(async move {
+                let bytes = match gloo_file::futures::read_as_bytes(&file).await {
+                    Ok(bytes) => bytes,
+                    Err(error) => {
+                        error!("failed to read file:", error.to_string());
+                        uploading.set(false);
+                        return;
+                    }
+                };
+                let new = NewAttachment {
+                    file_name: file.name(),
+                    content_type: file.raw_mime_type(),
+                    description: (*description).clone(),
+                    data: general_purpose::STANDARD.encode(bytes),
+                };
+                match upload_attachment(&path, &new, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        description.set(String::new());
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to upload file:", error.to_string()),
+                }
+                uploading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Documents"}</h3>
+            <div class="flex flex-col gap-1 text-sm">
+                <input type="text" id="attachment_description" placeholder="Description" class="border-2 border-slate-200 rounded-md" value={(*description).clone()} onchange={
+                    clone!(description);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        description.set(target.value());
+                    })
+                }/>
+                <input type="file" id="attachment_file" accept={ACCEPTED_TYPES} class="text-xs" disabled={*uploading} onchange={upload}/>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-1 text-xs">
+                {attachments.iter().map(|attachment| {
+                    let id = attachment.id;
+                    let download = {
+                        clone!(ctx, path);
+                        Callback::from(move |_| {
+                            clone!(ctx, path);
+                            spawn_local(async move {
+                                match fetch_link(&path, &id, &ctx.auth_token).await {
+                                    Ok(link) => {
+                                        if let Some(window) = web_sys::window() {
+                                            let _ = window.location().set_href(&link.url);
+                                        }
+                                    }
+                                    Err(error) => error!("failed to get download link:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    let delete = if props.can_delete && attachment.uploaded_by == ctx.current_user.email_address {
+                        clone!(ctx, path, refresh);
+                        html!(<IconButton icon="delete" onclick={Callback::from(move |_| {
+                            clone!(ctx, path, refresh);
+                            spawn_local(async move {
+                                match delete_attachment(&path, &id, &ctx.auth_token).await {
+                                    Ok(_) => refresh.emit(()),
+                                    Err(error) => error!("failed to remove file:", error.to_string()),
+                                }
+                            });
+                        })} />)
+                    } else {
+                        html!()
+                    };
+                    html! {
+                        <li key={id.to_string()} class="border-l-4 border-slate-300 pl-2">
+                            <div class="flex justify-between items-center">
+                                <span class="text-sm underline cursor-pointer" onclick={download}>{&attachment.file_name}</span>
+                                {delete}
+                            </div>
+                            <p class="text-slate-500">
+                                {format!("{} · {} · {}", attachment.display_size(), attachment.uploaded_by, attachment.uploaded_at.format("%d/%m/%Y"))}
+                                if attachment.scan_status == "unscanned" {
+                                    {" · not virus scanned"}
+                                }
+                            </p>
+                            if !attachment.description.is_empty() {
+                                <p class="italic">{&attachment.description}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct AttachmentsPanelProps {
+    /// the attachments endpoint of whatever the files are attached to
+    pub path: String,
+    /// whether the uploader can remove their files, which isn't allowed on concerns
+    #[prop_or_default]
+    pub can_delete: bool,
+}
@@ -0,0 +1,6 @@
+mod page;
+mod register;
+mod summary;
+
+pub use page::RegisterPage;
+pub use summary::AttendanceLine;
@@ -0,0 +1,206 @@
+use super::register::*;
+use crate::{app::AppContext, elements::Button, error::*};
+use chrono::{NaiveDate, Utc};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Take the AM and PM register for one of the user's year groups on a given day
+#[function_component(RegisterPage)]
+pub fn register_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN REGISTER PAGE");
+    let codes: UseStateHandle<Vec<AttendanceCode>> = use_state_eq(Vec::new);
+    let year = use_state_eq(|| {
+        ctx.current_user
+            .years
+            .first()
+            .map(|y| *y as i32)
+            .unwrap_or_default()
+    });
+    let date = use_state_eq(|| Utc::now().date_naive());
+    let register: UseStateHandle<Option<Register>> = use_state_eq(|| None);
+    // marks changed since the last save, keyed by pupil and session
+    let edits: UseStateHandle<HashMap<(Uuid, String), String>> = use_state_eq(HashMap::new);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    {
+        clone!(ctx, codes);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_codes(&ctx.auth_token).await {
+                        Ok(fetched) => codes.set(fetched),
+                        Err(error) => {
+                            error!("failed to get attendance codes:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+    {
+        clone!(ctx, register, edits, errors);
+        use_effect_with_deps(
+            move |(year, date): &(i32, NaiveDate)| {
+                let (year, date) = (*year, *date);
+                edits.set(HashMap::new());
+                errors.set(HashMap::new());
+                spawn_local(async move {
+                    match fetch_register(year, &date, &ctx.auth_token).await {
+                        Ok(fetched) => register.set(Some(fetched)),
+                        Err(error) => error!("failed to get register:", error.to_string()),
+                    }
+                });
+            },
+            (*year, *date),
+        );
+    }
+
+    let save = {
+        clone!(ctx, year, date, register, edits, errors);
+        Callback::from(move |_| {
+            clone!(ctx, register, edits, errors);
+            let (year, date) = (*year, *date);
+            let entries: Vec<MarkEntry> = edits
+                .iter()
+                .map(|((pupil_id, session), code)| MarkEntry {
+                    pupil_id: *pupil_id,
+                    session: session.clone(),
+                    code: (!code.is_empty()).then(|| code.clone()),
+                })
+                .collect();
+            spawn_local(async move {
+                match save_register(year, &date, &entries, &ctx.auth_token).await {
+                    Ok(Ok(saved)) => {
+                        register.set(Some(saved));
+                        edits.set(HashMap::new());
+                        errors.set(HashMap::new());
+                    }
+                    Ok(Err(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to save register:", error.to_string()),
+                }
+            });
+        })
+    };
+    let mark_all_present = {
+        clone!(register, edits);
+        Callback::from(move |_| {
+            let Some(register) = &*register else { return };
+            let mut changed = (*edits).clone();
+            for row in &register.rows {
+                for (session, current) in [("am", &row.am), ("pm", &row.pm)] {
+                    let key = (row.pupil_id, session.to_string());
+                    if current.is_none() && !changed.contains_key(&key) {
+                        changed.insert(key, "/".into());
+                    }
+                }
+            }
+            edits.set(changed);
+        })
+    };
+
+    let mark_select = |pupil_id: Uuid, session: &str, saved: &Option<String>| {
+        let key = (pupil_id, session.to_string());
+        let value = edits
+            .get(&key)
+            .cloned()
+            .or_else(|| saved.clone())
+            .unwrap_or_default();
+        let error = errors.get(&format!("{pupil_id}.{session}")).cloned();
+        let onchange = {
+            clone!(edits);
+            Callback::from(move |ev: Event| {
+                let target: HtmlInputElement = ev.target_unchecked_into();
+                let mut changed = (*edits).clone();
+                changed.insert(key.clone(), target.value());
+                edits.set(changed);
+            })
+        };
+        html! {
+            <td class="px-2">
+                <select class="border-2 border-slate-200 rounded-md w-full" {onchange}>
+                    <option value="" selected={value.is_empty()}>{"-"}</option>
+                    {codes.iter().map(|code| html! {
+                        <option value={code.code.clone()} title={code.description.clone()} selected={value == code.code}>
+                            {format!("{} {}", code.code, code.description)}
+                        </option>
+                    }).collect::<Html>()}
+                </select>
+                if let Some(error) = error {
+                    <span class="text-xs text-red-500">{error}</span>
+                }
+            </td>
+        }
+    };
+
+    html! {
+        <div class="flex flex-col m-3 gap-3">
+            <div class="flex p-3 gap-2 justify-between items-center shadow-lg rounded-md bg-white">
+                <div class="flex gap-2 items-center">
+                    <h2 class="text-xl">{"Register"}</h2>
+                    <select id="register_year" class="border-2 border-slate-200 rounded-md" onchange={
+                        clone!(year);
+                        Callback::from(move |ev: Event| {
+                            let target: HtmlInputElement = ev.target_unchecked_into();
+                            if let Ok(value) = target.value().parse() {
+                                year.set(value);
+                            }
+                        })
+                    }>
+                        {ctx.current_user.years.iter().map(|y| html! {
+                            <option value={y.to_string()} selected={*year == *y as i32}>{format!("Year {y}")}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    <input type="date" id="register_date" class="border-2 border-slate-200 rounded-md" value={date.to_string()} onchange={
+                        clone!(date);
+                        Callback::from(move |ev: Event| {
+                            let target: HtmlInputElement = ev.target_unchecked_into();
+                            if let Ok(value) = target.value().parse() {
+                                date.set(value);
+                            }
+                        })
+                    }/>
+                </div>
+                <div class="flex gap-2">
+                    <Button icon={html!(<yew_feather::CheckSquare size="16" />)} text="All present" color="blue" onclick={&mark_all_present} />
+                    <Button icon={html!(<yew_feather::Save size="16" />)} text="Save" color="green" onclick={&save} />
+                </div>
+            </div>
+            if let Some(error) = errors.get("form") {
+                <p class="text-xs text-red-500">{error}</p>
+            }
+            <div class="overflow-y-auto [max-height:calc(90vh-60px)] p-3 scrollbar shadow-lg rounded-md bg-white">
+                if let Some(register) = &*register {
+                    <table class="w-full">
+                        <thead>
+                            <tr class="text-left">
+                                <th>{"Pupil"}</th>
+                                <th class="px-2">{"AM"}</th>
+                                <th class="px-2">{"PM"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {register.rows.iter().map(|row| html! {
+                                <tr>
+                                    <td>{format!("{} {}", row.first_names, row.last_name)}</td>
+                                    {mark_select(row.pupil_id, "am", &row.am)}
+                                    {mark_select(row.pupil_id, "pm", &row.pm)}
+                                </tr>
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                    if register.rows.is_empty() {
+                        <p class="text-sm text-slate-500">{"Nobody in this year was enrolled on that day"}</p>
+                    }
+                } else {
+                    <p>{"Loading register..."}</p>
+                }
+            </div>
+        </div>
+    }
+}
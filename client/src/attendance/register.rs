@@ -0,0 +1,111 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct AttendanceCode {
+    pub code: String,
+    pub description: String,
+    pub meaning: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct RegisterRow {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub am: Option<String>,
+    pub pm: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Register {
+    pub year: i32,
+    pub date: NaiveDate,
+    pub rows: Vec<RegisterRow>,
+}
+
+/// One session mark to save, where no code removes the mark
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct MarkEntry {
+    pub pupil_id: Uuid,
+    pub session: String,
+    pub code: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct AttendanceSummary {
+    pub possible_sessions: u32,
+    pub present_sessions: u32,
+    pub authorised_absences: u32,
+    pub unauthorised_absences: u32,
+    pub percentage: Option<f64>,
+    pub persistent_absence: bool,
+}
+
+fn register_path(year: i32, date: &NaiveDate) -> String {
+    format!("{}/register/{year}/{date}", constant::ATTENDANCE_PATH)
+}
+
+pub async fn fetch_codes(token: &str) -> Result<Vec<AttendanceCode>> {
+    let response = Request::get(&format!("{}/codes", constant::ATTENDANCE_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<AttendanceCode>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_register(year: i32, date: &NaiveDate, token: &str) -> Result<Register> {
+    let response = Request::get(&register_path(year, date))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Register>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Saves the marks and returns the updated register, or the server's errors keyed by
+/// `pupil_id.session` if it rejected any of them
+pub async fn save_register(
+    year: i32,
+    date: &NaiveDate,
+    entries: &[MarkEntry],
+    token: &str,
+) -> Result<std::result::Result<Register, HashMap<String, String>>> {
+    let response = Request::put(&register_path(year, date))
+        .json(entries)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<Register>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_summary(pupil_id: &Uuid, token: &str) -> Result<AttendanceSummary> {
+    let response = Request::get(&format!("{}/{pupil_id}/attendance", constant::PUPILS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<AttendanceSummary>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
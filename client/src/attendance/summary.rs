@@ -0,0 +1,51 @@
+use super::register::*;
+use crate::app::AppContext;
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// A pupil's attendance so far this academic year, for their details
+#[function_component(AttendanceLine)]
+pub fn attendance_line(props: &AttendanceLineProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ATTENDANCE LINE");
+    let summary: UseStateHandle<Option<AttendanceSummary>> = use_state_eq(|| None);
+    {
+        clone!(ctx, summary);
+        use_effect_with_deps(
+            move |pupil_id: &Uuid| {
+                let pupil_id = *pupil_id;
+                spawn_local(async move {
+                    match fetch_summary(&pupil_id, &ctx.auth_token).await {
+                        Ok(fetched) => summary.set(Some(fetched)),
+                        Err(error) => error!("failed to get attendance:", error.to_string()),
+                    }
+                });
+            },
+            props.pupil_id,
+        );
+    }
+
+    match &*summary {
+        Some(AttendanceSummary {
+            percentage: Some(percentage),
+            persistent_absence,
+            possible_sessions,
+            ..
+        }) => html! {
+            <span class={classes!(persistent_absence.then_some("text-red-500"))} title={format!("{possible_sessions} possible sessions this year")}>
+                {format!("{percentage:.1}%")}
+                if *persistent_absence {
+                    {" (persistent absence)"}
+                }
+            </span>
+        },
+        Some(_) => html!(<span class="text-slate-500">{"No sessions yet"}</span>),
+        None => html!(),
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct AttendanceLineProps {
+    pub pupil_id: Uuid,
+}
@@ -0,0 +1,4 @@
+mod page;
+mod record;
+
+pub use page::CensusPage;
@@ -0,0 +1,212 @@
+use super::record::*;
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, ModalCallbacks},
+    error::*,
+    pupils::PupilDetails,
+    utils::use_download,
+};
+use chrono::{NaiveDate, Utc};
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The school census: who is on roll on the census date, the problems to fix on their records
+/// before the return can be made, and the return file itself.
+#[function_component(CensusPage)]
+pub fn census_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CENSUS PAGE");
+    let (invoke_modal, dismiss_modal) =
+        use_context::<ModalCallbacks>().expect("failed to get modal callbacks");
+    let is_admin = ctx
+        .current_user
+        .roles
+        .iter()
+        .any(|role| role == constant::ROLE_ADMIN);
+    let census_date = use_state_eq(|| Utc::now().date_naive());
+    let census: UseStateHandle<Option<Census>> = use_state_eq(|| None);
+    // bumped to check the census again after a pupil is fixed
+    let refresh = use_state_eq(|| 0);
+    let message: UseStateHandle<Option<String>> = use_state_eq(|| None);
+    let file = use_download();
+    {
+        clone!(ctx, census);
+        use_effect_with_deps(
+            move |(census_date, _): &(NaiveDate, i32)| {
+                let census_date = *census_date;
+                spawn_local(async move {
+                    match fetch_census(census_date, &ctx.auth_token).await {
+                        Ok(fetched) => census.set(Some(fetched)),
+                        Err(error) => {
+                            error!("failed to get the census:", error.to_string());
+                            census.set(None);
+                        }
+                    }
+                });
+            },
+            (*census_date, *refresh),
+        );
+    }
+
+    let choose_date = {
+        clone!(census_date, message);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            if let Ok(date) = NaiveDate::parse_from_str(&target.value(), "%Y-%m-%d") {
+                census_date.set(date);
+                message.set(None);
+            }
+        })
+    };
+    let open_pupil = {
+        clone!(ctx, refresh);
+        Callback::from(move |(ev, pupil_id): (MouseEvent, Uuid)| {
+            clone!(ctx, refresh, invoke_modal, dismiss_modal);
+            spawn_local(async move {
+                match fetch_pupil(&pupil_id, &ctx.auth_token).await {
+                    Ok(pupil) => {
+                        let refresh_callback = Callback::from(move |_: bool| refresh.set(*refresh + 1));
+                        invoke_modal.emit((ev, html!(<PupilDetails pupil={pupil} refresh_callback={refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-200px)]")));
+                    }
+                    Err(error) => {
+                        error!("failed to get pupil:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                }
+            });
+        })
+    };
+    let download = {
+        clone!(ctx, census_date, message, file);
+        Callback::from(move |_: MouseEvent| {
+            let census_date = *census_date;
+            clone!(ctx, message, file);
+            spawn_local(async move {
+                match fetch_return(census_date, &ctx.auth_token).await {
+                    Ok(Ok(data)) => {
+                        file.save(
+                            data.as_slice(),
+                            "application/xml",
+                            &format!("census_{census_date}.xml"),
+                        );
+                        message.set(None);
+                    }
+                    Ok(Err(reason)) => message.set(Some(reason)),
+                    Err(error) => error!("failed to download the census return:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    if !is_admin {
+        return html! {
+            <div class="m-3 p-3 shadow-lg rounded-md bg-white">
+                <p>{"Only an admin can prepare the census return"}</p>
+            </div>
+        };
+    }
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white flex flex-col gap-3">
+            <div class="flex gap-2 items-center">
+                <h2 class="text-xl">{"School census"}</h2>
+                <label class="flex items-center gap-1">
+                    {"as at"}
+                    <input type="date" id="census_date" class="border-2 border-slate-200 rounded-md" value={census_date.format("%Y-%m-%d").to_string()} onchange={choose_date}/>
+                </label>
+                <Button color="green" text="Download return" onclick={download} />
+            </div>
+            if let Some(message) = &*message {
+                <span class="text-sm text-red-500">{message}</span>
+            }
+            if let Some(census) = &*census {
+                <span class="text-sm text-slate-500">
+                    {format!("{} pupils on roll, {} errors and {} queries", census.pupils.len(), census.errors(), census.problems.len() - census.errors())}
+                </span>
+                if !census.problems.is_empty() {
+                    <h3 class="text-lg">{"To check"}</h3>
+                    <table class="w-full text-sm text-left">
+                        <thead>
+                            <tr>
+                                <th>{"Pupil"}</th>
+                                <th>{"Year"}</th>
+                                <th>{"Field"}</th>
+                                <th>{"Problem"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {census.problems.iter().map(|problem| {
+                                let pupil_id = problem.pupil_id;
+                                let colour = if problem.severity == "error" { "text-red-500" } else { "text-yellow-600" };
+                                html! {
+                                    <tr class="border-t border-slate-200 cursor-pointer hover:bg-slate-100" onclick={
+                                        clone!(open_pupil);
+                                        Callback::from(move |ev: MouseEvent| open_pupil.emit((ev, pupil_id)))
+                                    }>
+                                        <td class="underline">{format!("{} {}", problem.first_names, problem.last_name)}</td>
+                                        <td>{problem.year}</td>
+                                        <td>{problem.field.replace('_', " ")}</td>
+                                        <td class={colour}>{&problem.message}</td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                }
+                <h3 class="text-lg">{"On roll"}</h3>
+                <table class="w-full text-sm text-left">
+                    <thead>
+                        <tr>
+                            <th>{"Pupil"}</th>
+                            <th>{"UPN"}</th>
+                            <th>{"Date of birth"}</th>
+                            <th>{"Sex"}</th>
+                            <th>{"NC year"}</th>
+                            <th>{"Started"}</th>
+                            <th>{"FSM"}</th>
+                            <th>{"EAL"}</th>
+                            <th>{"ALN"}</th>
+                            <th>{"LAC"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {census.pupils.iter().map(|pupil| {
+                            let yes_no = |on: bool| if on { "Yes" } else { "" };
+                            html! {
+                                <tr key={pupil.pupil_id.to_string()} class="border-t border-slate-200">
+                                    <td>{format!("{} {}", pupil.first_names, pupil.last_name)}</td>
+                                    <td>{pupil.upn.clone().unwrap_or_default()}</td>
+                                    <td>{pupil.date_of_birth.map(|date| date.format("%d/%m/%Y").to_string()).unwrap_or_default()}</td>
+                                    <td>{&pupil.sex}</td>
+                                    <td>{&pupil.nc_year}</td>
+                                    <td>{pupil.start_date.format("%d/%m/%Y").to_string()}</td>
+                                    <td>{yes_no(pupil.fsm)}</td>
+                                    <td>{match (&pupil.eal_stage, pupil.eal) {
+                                        (Some(stage), true) => format!("Stage {stage}"),
+                                        (None, true) => "Yes".into(),
+                                        _ => String::new(),
+                                    }}</td>
+                                    <td>{match (&pupil.aln_provision, pupil.aln) {
+                                        (Some(provision), true) => provision.replace('_', " "),
+                                        (None, true) => "Yes".into(),
+                                        _ => String::new(),
+                                    }}</td>
+                                    <td>{yes_no(pupil.looked_after)}</td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+                if census.pupils.is_empty() {
+                    <p>{"There were no pupils on roll on this date"}</p>
+                }
+            }
+            <a ref={file.link.clone()} class="hidden"></a>
+        </div>
+    }
+}
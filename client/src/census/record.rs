@@ -0,0 +1,104 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+    pupils::Pupil,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use gloo_net::http::Request;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct CensusRecord {
+    pub pupil_id: Uuid,
+    #[serde(default)]
+    pub upn: Option<String>,
+    pub first_names: String,
+    pub last_name: String,
+    #[serde(default)]
+    pub date_of_birth: Option<NaiveDate>,
+    pub sex: String,
+    pub nc_year: String,
+    pub start_date: NaiveDate,
+    pub fsm: bool,
+    pub eal: bool,
+    #[serde(default)]
+    pub eal_stage: Option<String>,
+    pub aln: bool,
+    #[serde(default)]
+    pub aln_provision: Option<String>,
+    pub looked_after: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Problem {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub year: i32,
+    pub field: String,
+    pub message: String,
+    pub severity: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Census {
+    pub census_date: NaiveDate,
+    pub created: NaiveDateTime,
+    pub pupils: Vec<CensusRecord>,
+    pub problems: Vec<Problem>,
+}
+
+impl Census {
+    pub fn errors(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == "error")
+            .count()
+    }
+}
+
+pub async fn fetch_census(date: NaiveDate, token: &str) -> Result<Census> {
+    let response = Request::get(&format!("{}/{date}", constant::CENSUS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Census>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// The return file, or the server's reason for refusing it while there are errors
+pub async fn fetch_return(
+    date: NaiveDate,
+    token: &str,
+) -> Result<std::result::Result<Vec<u8>, String>> {
+    let response = Request::get(&format!("{}/{date}/return", constant::CENSUS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.binary().await?)),
+        400 => {
+            let error = response.json::<ErrorResponse>().await?;
+            Ok(Err(error.details.unwrap_or(error.error)))
+        }
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// The pupil a problem is about, to open their record and fix it
+pub async fn fetch_pupil(pupil_id: &Uuid, token: &str) -> Result<Pupil> {
+    let response = Request::get(&format!("{}/{pupil_id}", constant::PUPILS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Pupil>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,7 @@
+mod comment;
+mod page;
+mod timeline;
+
+pub use comment::Comment;
+pub use page::CommentsPage;
+pub use timeline::CommentTimeline;
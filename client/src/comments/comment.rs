@@ -0,0 +1,94 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDateTime;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Comment {
+    pub id: Uuid,
+    pub pupil_id: Uuid,
+    pub author: String,
+    pub category: String,
+    pub body: String,
+    pub visibility: String,
+    pub created_at: NaiveDateTime,
+    pub edited_at: Option<NaiveDateTime>,
+}
+
+impl Comment {
+    pub fn is_private(&self) -> bool {
+        self.visibility == "private"
+    }
+}
+
+/// What the server needs to add a comment, it fills in the author and timestamps
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct NewComment {
+    pub category: String,
+    pub body: String,
+    pub visibility: String,
+}
+
+fn pupil_comments_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/comments", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_pupil_comments(pupil_id: &Uuid, token: &str) -> Result<Vec<Comment>> {
+    fetch(&pupil_comments_path(pupil_id), token).await
+}
+
+pub async fn fetch_all_comments(token: &str) -> Result<Vec<Comment>> {
+    fetch(constant::COMMENTS_PATH, token).await
+}
+
+async fn fetch(path: &str, token: &str) -> Result<Vec<Comment>> {
+    let response = Request::get(path)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Comment>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the comment
+pub async fn create_comment(
+    pupil_id: &Uuid,
+    comment: &NewComment,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&pupil_comments_path(pupil_id))
+        .json(comment)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_comment(comment: &Comment, token: &str) -> Result<()> {
+    let response = Request::delete(&format!(
+        "{}/{}",
+        pupil_comments_path(&comment.pupil_id),
+        comment.id
+    ))
+    .header("Authorization", &format!("Bearer {token}"))
+    .send()
+    .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,93 @@
+use super::comment::*;
+use crate::{app::AppContext, constant, error::*, pupils::Pupil};
+use gloo_net::http::Request;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Every comment the user can see across their years, newest first
+#[function_component(CommentsPage)]
+pub fn comments_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN COMMENTS PAGE");
+    let comments: UseStateHandle<Vec<Comment>> = use_state_eq(Vec::new);
+    let pupil_names: UseStateHandle<HashMap<Uuid, String>> = use_state_eq(HashMap::new);
+    let category = use_state_eq(String::new);
+    {
+        clone!(ctx, comments, pupil_names);
+        use_effect_with_deps(
+            move |_| {
+                clone!(ctx, comments, pupil_names);
+                spawn_local(async move {
+                    if let Err(error) = fetch_page(&ctx.auth_token, comments, pupil_names).await {
+                        error!("failed to get comments:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+
+    html! {
+        <div class="flex flex-col m-3 gap-3">
+            <div class="flex p-3 gap-2 justify-between items-center shadow-lg rounded-md bg-white">
+                <h2 class="text-xl">{"General comments"}</h2>
+                <select id="category" class="border-2 border-slate-200 rounded-md" onchange={
+                    clone!(category);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        category.set(target.value());
+                    })
+                }>
+                    <option value="" selected={category.is_empty()}>{"All categories"}</option>
+                    {constant::COMMENT_CATEGORIES.iter().map(|c| html! {
+                        <option value={c.to_string()} selected={*category == *c}>{c}</option>
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div class="overflow-y-auto [max-height:calc(90vh-60px)] p-5 scrollbar shadow-lg rounded-md bg-white">
+                <ul class="flex flex-col gap-3">
+                    {comments.iter().filter(|c| category.is_empty() || c.category == *category).map(|comment| html! {
+                        <li class="border-l-4 border-slate-300 pl-2">
+                            <div class="flex justify-between text-xs text-slate-500">
+                                <span class="font-bold text-slate-700">{pupil_names.get(&comment.pupil_id).cloned().unwrap_or_default()}</span>
+                                <span>{format!("{} · {} · {}", comment.created_at.format("%d/%m/%Y %H:%M"), comment.category, comment.author)}</span>
+                            </div>
+                            <p class="text-sm whitespace-pre-wrap">{&comment.body}</p>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+            </div>
+        </div>
+    }
+}
+
+async fn fetch_page(
+    token: &str,
+    comments: UseStateHandle<Vec<Comment>>,
+    pupil_names: UseStateHandle<HashMap<Uuid, String>>,
+) -> Result<()> {
+    let response = Request::get(constant::PUPILS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => {
+            let pupils = response.json::<Vec<Pupil>>().await?;
+            pupil_names.set(
+                pupils
+                    .iter()
+                    .filter_map(|p| p.id.map(|id| (id, p.display_name())))
+                    .collect(),
+            );
+        }
+        401 => return Err(Unauthorized!()),
+        unknown => return Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+    comments.set(fetch_all_comments(token).await?);
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use super::comment::*;
+use crate::{app::AppContext, constant, elements::IconButton};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The comments on one pupil, newest first, with a box to add another
+#[function_component(CommentTimeline)]
+pub fn comment_timeline(props: &CommentTimelineProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN COMMENT TIMELINE");
+    let comments: UseStateHandle<Vec<Comment>> = use_state_eq(Vec::new);
+    let new_comment = use_state_eq(|| NewComment {
+        category: constant::COMMENT_CATEGORIES[0].into(),
+        visibility: "shared".into(),
+        ..Default::default()
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+
+    let refresh = {
+        clone!(ctx, comments);
+        let pupil_id = props.pupil_id;
+        Callback::from(move |_: ()| {
+            clone!(ctx, comments);
+            spawn_local(async move {
+                match fetch_pupil_comments(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => comments.set(fetched),
+                    Err(error) => error!("failed to get pupil comments:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let update_new_comment = {
+        clone!(new_comment);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut comment = (*new_comment).clone();
+            match target.id().as_str() {
+                "comment_category" => comment.category = target.value(),
+                "comment_body" => comment.body = target.value(),
+                "comment_private" => {
+                    comment.visibility = if target.checked() { "private" } else { "shared" }.into()
+                }
+                _ => {}
+            }
+            new_comment.set(comment);
+        })
+    };
+    let add_comment = {
+        clone!(ctx, new_comment, errors, refresh);
+        let pupil_id = props.pupil_id;
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_comment, errors, refresh);
+            spawn_local(async move {
+                match create_comment(&pupil_id, &new_comment, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_comment.set(NewComment {
+                            body: String::new(),
+                            ..(*new_comment).clone()
+                        });
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to add comment:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col h-full gap-2">
+            <div class="flex flex-col gap-1">
+                <div class="flex justify-between items-center">
+                    <select id="comment_category" class="border-2 border-slate-200 rounded-md" onchange={&update_new_comment}>
+                        {constant::COMMENT_CATEGORIES.iter().map(|category| html! {
+                            <option value={category.to_string()} selected={new_comment.category == *category}>{category}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    <label for="comment_private" class="text-xs flex items-center gap-1">
+                        <input type="checkbox" id="comment_private" checked={new_comment.visibility == "private"} onchange={&update_new_comment}/>
+                        {"Private"}
+                    </label>
+                    <IconButton onclick={&add_comment} icon="add" />
+                </div>
+                <textarea id="comment_body" class="border-2 border-slate-200 rounded-md text-sm" rows="2" value={new_comment.body.clone()} onchange={&update_new_comment}/>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-2 overflow-y-auto scrollbar">
+                {comments.iter().map(|comment| {
+                    let delete = if comment.author == ctx.current_user.email_address {
+                        clone!(ctx, refresh);
+                        let comment = comment.clone();
+                        html!(<IconButton icon="delete" onclick={Callback::from(move |_| {
+                            clone!(ctx, refresh, comment);
+                            spawn_local(async move {
+                                match delete_comment(&comment, &ctx.auth_token).await {
+                                    Ok(_) => refresh.emit(()),
+                                    Err(error) => error!("failed to delete comment:", error.to_string()),
+                                }
+                            });
+                        })} />)
+                    } else {
+                        html!()
+                    };
+                    html! {
+                        <li class="border-l-4 border-slate-300 pl-2">
+                            <div class="flex justify-between items-center text-xs text-slate-500">
+                                <span>{format!("{} · {} · {}", comment.created_at.format("%d/%m/%Y %H:%M"), comment.category, comment.author)}</span>
+                                <span class="flex items-center gap-1">
+                                    if comment.is_private() {
+                                        <yew_feather::Lock size="12" />
+                                    }
+                                    if comment.edited_at.is_some() {
+                                        <em>{"edited"}</em>
+                                    }
+                                    {delete}
+                                </span>
+                            </div>
+                            <p class="text-sm whitespace-pre-wrap">{&comment.body}</p>
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CommentTimelineProps {
+    pub pupil_id: Uuid,
+}
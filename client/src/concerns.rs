@@ -0,0 +1,7 @@
+mod chronology;
+mod concern;
+mod page;
+
+pub use chronology::ConcernChronology;
+pub use concern::Concern;
+pub use page::ConcernsPage;
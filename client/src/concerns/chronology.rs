@@ -0,0 +1,58 @@
+use super::concern::*;
+use crate::app::AppContext;
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Everything that has happened on the concerns about one pupil, oldest first
+#[function_component(ConcernChronology)]
+pub fn concern_chronology(props: &ConcernChronologyProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CONCERN CHRONOLOGY");
+    let entries: UseStateHandle<Vec<ChronologyEntry>> = use_state_eq(Vec::new);
+    {
+        clone!(ctx, entries);
+        use_effect_with_deps(
+            move |(pupil_id, _)| {
+                let pupil_id = *pupil_id;
+                spawn_local(async move {
+                    match fetch_chronology(&pupil_id, &ctx.auth_token).await {
+                        Ok(fetched) => entries.set(fetched),
+                        Err(error) => error!("failed to get chronology:", error.to_string()),
+                    }
+                });
+            },
+            (props.pupil_id, props.version),
+        );
+    }
+
+    html! {
+        <ol class="flex flex-col gap-2 overflow-y-auto scrollbar">
+            {entries.iter().map(|entry| html! {
+                <li class={classes!("border-l-4", "pl-2", event_colour(&entry.event))}>
+                    <div class="text-xs text-slate-500">
+                        {format!("{} · {} · {} · {}", entry.at.format("%d/%m/%Y %H:%M"), entry.event, entry.category, entry.by)}
+                    </div>
+                    <p class="text-sm whitespace-pre-wrap">{&entry.summary}</p>
+                </li>
+            }).collect::<Html>()}
+        </ol>
+    }
+}
+
+fn event_colour(event: &str) -> &'static str {
+    match event {
+        "raised" => "border-amber-400",
+        "triaged" => "border-sky-400",
+        "escalated" => "border-red-500",
+        _ => "border-slate-300",
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ConcernChronologyProps {
+    pub pupil_id: Uuid,
+    /// bump to refetch after the concerns change
+    #[prop_or_default]
+    pub version: u32,
+}
@@ -0,0 +1,125 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDateTime;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Concern {
+    pub id: Uuid,
+    pub pupil_id: Uuid,
+    pub reporter: String,
+    pub category: String,
+    pub narrative: String,
+    pub actions_taken: String,
+    pub status: String,
+    pub raised_at: NaiveDateTime,
+    pub triaged_by: Option<String>,
+    pub triaged_at: Option<NaiveDateTime>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub outcome: Option<String>,
+}
+
+impl Concern {
+    /// The statuses a DSL can move this concern on to
+    pub fn next_statuses(&self) -> &'static [&'static str] {
+        match self.status.as_str() {
+            "raised" => &["triaged"],
+            "triaged" => &["closed", "escalated"],
+            "escalated" => &["closed"],
+            _ => &[],
+        }
+    }
+}
+
+/// What the server needs to raise a concern, it fills in the reporter and status
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct NewConcern {
+    pub pupil_id: Option<Uuid>,
+    pub category: String,
+    pub narrative: String,
+    pub actions_taken: String,
+}
+
+/// One dated event in a pupil's safeguarding chronology
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct ChronologyEntry {
+    pub concern_id: Uuid,
+    pub at: NaiveDateTime,
+    pub event: String,
+    pub by: String,
+    pub category: String,
+    pub summary: String,
+}
+
+pub async fn fetch_concerns(token: &str) -> Result<Vec<Concern>> {
+    let response = Request::get(constant::CONCERNS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Concern>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_chronology(pupil_id: &Uuid, token: &str) -> Result<Vec<ChronologyEntry>> {
+    let response = Request::get(&format!("{}/{pupil_id}/concerns", constant::PUPILS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<ChronologyEntry>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the concern
+pub async fn create_concern(
+    concern: &NewConcern,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(constant::CONCERNS_PATH)
+        .json(concern)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's reason if it refused the change
+pub async fn set_status(
+    concern: &Concern,
+    status: &str,
+    outcome: &str,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let outcome = (!outcome.is_empty()).then_some(outcome);
+    let response = Request::post(&format!(
+        "{}/{}/status",
+        constant::CONCERNS_PATH,
+        concern.id
+    ))
+    .json(&serde_json::json!({"status": status, "outcome": outcome}))?
+    .header("Authorization", &format!("Bearer {token}"))
+    .send()
+    .await?;
+    match response.status() {
+        200 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
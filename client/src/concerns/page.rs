@@ -0,0 +1,217 @@
+use super::{chronology::ConcernChronology, concern::*};
+use crate::{
+    app::AppContext, attachments::AttachmentsPanel, constant, elements::Button, error::*,
+    pupils::Pupil,
+};
+use gloo_net::http::Request;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Safeguarding concerns the user has raised, or every concern for a DSL, with a form to raise a
+/// new one and the chronology of the selected concern's pupil
+#[function_component(ConcernsPage)]
+pub fn concerns_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CONCERNS PAGE");
+    let concerns: UseStateHandle<Vec<Concern>> = use_state_eq(Vec::new);
+    let pupils: UseStateHandle<Vec<Pupil>> = use_state_eq(Vec::new);
+    let selected: UseStateHandle<Option<Uuid>> = use_state_eq(|| None);
+    let new_concern = use_state_eq(|| NewConcern {
+        category: constant::CONCERN_CATEGORIES[0].into(),
+        ..Default::default()
+    });
+    let outcome = use_state_eq(String::new);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let version = use_state_eq(|| 0u32);
+
+    let refresh = {
+        clone!(ctx, concerns, pupils, version);
+        Callback::from(move |_: ()| {
+            clone!(ctx, concerns, pupils, version);
+            spawn_local(async move {
+                match fetch_page(&ctx.auth_token, pupils).await {
+                    Ok(_) => match fetch_concerns(&ctx.auth_token).await {
+                        Ok(fetched) => {
+                            concerns.set(fetched);
+                            version.set(*version + 1);
+                        }
+                        Err(error) => error!("failed to get concerns:", error.to_string()),
+                    },
+                    Err(error) => {
+                        error!("failed to get pupils:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), ());
+    }
+
+    let update_new_concern = {
+        clone!(new_concern);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut concern = (*new_concern).clone();
+            match target.id().as_str() {
+                "concern_pupil" => concern.pupil_id = target.value().parse().ok(),
+                "concern_category" => concern.category = target.value(),
+                "concern_narrative" => concern.narrative = target.value(),
+                "concern_actions" => concern.actions_taken = target.value(),
+                _ => {}
+            }
+            new_concern.set(concern);
+        })
+    };
+    let raise = {
+        clone!(ctx, new_concern, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_concern, errors, refresh);
+            if new_concern.pupil_id.is_none() {
+                errors.set(HashMap::from([(
+                    "pupil_id".into(),
+                    "choose a pupil".into(),
+                )]));
+                return;
+            }
+            spawn_local(async move {
+                match create_concern(&new_concern, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_concern.set(NewConcern {
+                            category: constant::CONCERN_CATEGORIES[0].into(),
+                            ..Default::default()
+                        });
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to raise concern:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let pupil_name = |id: &Uuid| {
+        pupils
+            .iter()
+            .find(|p| p.id.as_ref() == Some(id))
+            .map(|p| p.display_name())
+            .unwrap_or_default()
+    };
+    let selected_concern = selected
+        .and_then(|id| concerns.iter().find(|c| c.id == id))
+        .cloned();
+
+    html! {
+        <div class="flex m-3 gap-3 [height:calc(90vh-20px)]">
+            <div class="flex flex-col w-1/2 gap-3">
+                <div class="flex flex-col p-3 gap-2 shadow-lg rounded-md bg-white">
+                    <h2 class="text-xl">{"Raise a concern"}</h2>
+                    <div class="flex gap-2">
+                        <select id="concern_pupil" class="border-2 border-slate-200 rounded-md grow" onchange={&update_new_concern}>
+                            <option value="" selected={new_concern.pupil_id.is_none()}>{"Choose a pupil"}</option>
+                            {pupils.iter().filter_map(|p| p.id.map(|id| html! {
+                                <option value={id.to_string()} selected={new_concern.pupil_id == Some(id)}>{p.display_name()}</option>
+                            })).collect::<Html>()}
+                        </select>
+                        <select id="concern_category" class="border-2 border-slate-200 rounded-md" onchange={&update_new_concern}>
+                            {constant::CONCERN_CATEGORIES.iter().map(|c| html! {
+                                <option value={c.to_string()} selected={new_concern.category == *c}>{c.replace('_', " ")}</option>
+                            }).collect::<Html>()}
+                        </select>
+                    </div>
+                    <textarea id="concern_narrative" placeholder="What did you see or hear?" class="border-2 border-slate-200 rounded-md text-sm" rows="4" value={new_concern.narrative.clone()} onchange={&update_new_concern}/>
+                    <textarea id="concern_actions" placeholder="What have you done about it?" class="border-2 border-slate-200 rounded-md text-sm" rows="2" value={new_concern.actions_taken.clone()} onchange={&update_new_concern}/>
+                    {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+                    <Button icon={html!(<yew_feather::Send size="16" />)} text="Raise" color="green" onclick={&raise} />
+                </div>
+                <div class="overflow-y-auto grow p-3 scrollbar shadow-lg rounded-md bg-white">
+                    <ul class="flex flex-col gap-2">
+                        {concerns.iter().map(|concern| {
+                            let id = concern.id;
+                            let onclick = {
+                                clone!(selected);
+                                Callback::from(move |_: MouseEvent| selected.set(Some(id)))
+                            };
+                            let highlight = if *selected == Some(id) { "bg-slate-100" } else { "" };
+                            html! {
+                                <li class={classes!("border-l-4", "border-slate-300", "pl-2", "cursor-pointer", highlight)} {onclick}>
+                                    <div class="flex justify-between text-xs text-slate-500">
+                                        <span class="font-bold text-slate-700">{pupil_name(&concern.pupil_id)}</span>
+                                        <span>{format!("{} · {} · {}", concern.raised_at.format("%d/%m/%Y %H:%M"), concern.category, concern.status)}</span>
+                                    </div>
+                                    <p class="text-sm truncate">{&concern.narrative}</p>
+                                </li>
+                            }
+                        }).collect::<Html>()}
+                    </ul>
+                </div>
+            </div>
+            <div class="flex flex-col w-1/2 gap-3 p-3 shadow-lg rounded-md bg-white">
+                if let Some(concern) = selected_concern {
+                    <div class="flex justify-between items-center">
+                        <h2 class="text-xl">{pupil_name(&concern.pupil_id)}</h2>
+                        <span class="text-sm">{format!("{} · raised by {}", concern.status, concern.reporter)}</span>
+                    </div>
+                    if ctx.current_user.is_dsl() && !concern.next_statuses().is_empty() {
+                        <div class="flex gap-2 items-center">
+                            <input id="concern_outcome" placeholder="Outcome" class="border-2 border-slate-200 rounded-md text-sm grow" value={(*outcome).clone()} onchange={
+                                clone!(outcome);
+                                Callback::from(move |ev: Event| {
+                                    let target: HtmlInputElement = ev.target_unchecked_into();
+                                    outcome.set(target.value());
+                                })
+                            }/>
+                            {concern.next_statuses().iter().map(|status| {
+                                let onclick = {
+                                    clone!(ctx, concern, outcome, errors, refresh);
+                                    Callback::from(move |_: MouseEvent| {
+                                        clone!(ctx, concern, outcome, errors, refresh);
+                                        spawn_local(async move {
+                                            match set_status(&concern, status, &outcome, &ctx.auth_token).await {
+                                                Ok(None) => {
+                                                    outcome.set(String::new());
+                                                    refresh.emit(());
+                                                }
+                                                Ok(Some(fields)) => errors.set(fields),
+                                                Err(error) => error!("failed to change concern status:", error.to_string()),
+                                            }
+                                        });
+                                    })
+                                };
+                                let color = if *status == "escalated" { "red" } else { "green" };
+                                html!(<Button text={status.to_string()} color={color} onclick={onclick} />)
+                            }).collect::<Html>()}
+                        </div>
+                    }
+                    <h3 class="text-md font-bold">{"Chronology"}</h3>
+                    <ConcernChronology pupil_id={concern.pupil_id} version={*version} />
+                    <AttachmentsPanel path={format!("{}/{}/attachments", constant::CONCERNS_PATH, concern.id)} />
+                } else {
+                    <p class="text-sm text-slate-500">{"Select a concern to see the pupil's chronology"}</p>
+                }
+            </div>
+        </div>
+    }
+}
+
+async fn fetch_page(token: &str, pupils: UseStateHandle<Vec<Pupil>>) -> Result<()> {
+    let response = Request::get(constant::PUPILS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => {
+            pupils.set(response.json::<Vec<Pupil>>().await?);
+            Ok(())
+        }
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -4,8 +4,48 @@ pub static LOG_PREFIX: &str = "|| LEARNER TRACKER v0.1 ||";
 pub static AUTH_TOKEN_STORAGE_KEY: &str = "token";
 pub static USER_STORAGE_KEY: &str = "user";
 
+// Pupil values the server will accept
+pub static GENDERS: [&str; 3] = ["female", "male", "other"];
+pub static COMMENT_CATEGORIES: [&str; 5] =
+    ["general", "academic", "behaviour", "pastoral", "achievement"];
+pub static CONCERN_CATEGORIES: [&str; 8] = [
+    "neglect",
+    "physical",
+    "emotional",
+    "sexual",
+    "online",
+    "peer_on_peer",
+    "radicalisation",
+    "other",
+];
+pub static CONTACT_RELATIONSHIPS: [&str; 7] = [
+    "mother",
+    "father",
+    "step_parent",
+    "carer",
+    "grandparent",
+    "sibling",
+    "other",
+];
+pub static ROLE_DSL: &str = "dsl";
+pub static ROLE_ALNCO: &str = "alnco";
+pub static ROLE_ADMIN: &str = "admin";
+pub static ROLE_MEDICAL: &str = "medical";
+
 // API Paths
 pub static PUPILS_PATH: &str = "/api/data/pupils";
+pub static ASSESSMENTS_PATH: &str = "/api/data/assessments";
+pub static COMMENTS_PATH: &str = "/api/data/comments";
+pub static CONCERNS_PATH: &str = "/api/data/concerns";
+pub static ATTENDANCE_PATH: &str = "/api/data/attendance";
+pub static CURRICULUM_PATH: &str = "/api/data/curriculum";
+pub static INTERVENTIONS_PATH: &str = "/api/data/interventions";
+pub static ALN_PATH: &str = "/api/data/aln";
+pub static MEDICAL_PATH: &str = "/api/data/medical";
+pub static ANALYTICS_PATH: &str = "/api/data/analytics";
+pub static REPORTS_PATH: &str = "/api/data/reports";
+pub static CENSUS_PATH: &str = "/api/data/census";
+pub static QUALITY_PATH: &str = "/api/data/quality";
 // pub static USERS_PATH: &str = "/api/data/users";
 pub static LOGIN_PATH: &str = "/api/auth/login";
 pub static LOGOUT_PATH: &str = "/api/auth/logout";
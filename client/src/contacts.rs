@@ -0,0 +1,4 @@
+mod contact;
+mod panel;
+
+pub use panel::ContactsPanel;
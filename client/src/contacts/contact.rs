@@ -0,0 +1,112 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A contact as they are to one pupil, the details are shared with any siblings they're linked to
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct PupilContact {
+    pub contact_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub preferred_language: Option<String>,
+    pub relationship: String,
+    pub priority: i32,
+    pub parental_responsibility: bool,
+    pub no_contact: bool,
+    #[serde(default)]
+    pub restriction_notes: Option<String>,
+}
+
+impl PupilContact {
+    pub fn name(&self) -> String {
+        format!("{} {}", self.first_names, self.last_name)
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewContact {
+    pub first_names: String,
+    pub last_name: String,
+    pub phone: String,
+    pub email: String,
+    pub preferred_language: String,
+    pub relationship: String,
+    pub priority: i32,
+    pub parental_responsibility: bool,
+    pub no_contact: bool,
+    pub restriction_notes: String,
+}
+
+impl Default for NewContact {
+    fn default() -> Self {
+        Self {
+            first_names: String::new(),
+            last_name: String::new(),
+            phone: String::new(),
+            email: String::new(),
+            preferred_language: String::new(),
+            relationship: constant::CONTACT_RELATIONSHIPS[0].to_owned(),
+            priority: 1,
+            parental_responsibility: false,
+            no_contact: false,
+            restriction_notes: String::new(),
+        }
+    }
+}
+
+fn contacts_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/contacts", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_contacts(pupil_id: &Uuid, token: &str) -> Result<Vec<PupilContact>> {
+    let response = Request::get(&contacts_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<PupilContact>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the contact
+pub async fn create_contact(
+    pupil_id: &Uuid,
+    contact: &NewContact,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&contacts_path(pupil_id))
+        .json(contact)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn unlink_contact(pupil_id: &Uuid, contact_id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{contact_id}", contacts_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,182 @@
+use super::contact::*;
+use crate::{app::AppContext, constant, elements::IconButton};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// A pupil's contacts in the order to try them. Anyone the school must not contact about the pupil
+/// is called out at the top so it can't be missed.
+#[function_component(ContactsPanel)]
+pub fn contacts_panel(props: &ContactsPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CONTACTS PANEL");
+    let contacts: UseStateHandle<Vec<PupilContact>> = use_state_eq(Vec::new);
+    let new_contact = use_state_eq(NewContact::default);
+    let adding = use_state_eq(|| false);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, contacts);
+        Callback::from(move |_: ()| {
+            clone!(ctx, contacts);
+            spawn_local(async move {
+                match fetch_contacts(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => contacts.set(fetched),
+                    Err(error) => error!("failed to get pupil contacts:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let update = {
+        clone!(new_contact);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut new = (*new_contact).clone();
+            match target.id().as_str() {
+                "contact_first_names" => new.first_names = target.value(),
+                "contact_last_name" => new.last_name = target.value(),
+                "contact_phone" => new.phone = target.value(),
+                "contact_email" => new.email = target.value(),
+                "contact_language" => new.preferred_language = target.value(),
+                "contact_relationship" => new.relationship = target.value(),
+                "contact_priority" => new.priority = target.value().parse().unwrap_or(new.priority),
+                "contact_pr" => new.parental_responsibility = target.checked(),
+                "contact_no_contact" => new.no_contact = target.checked(),
+                "contact_restriction" => new.restriction_notes = target.value(),
+                _ => {}
+            }
+            new_contact.set(new);
+        })
+    };
+    let add = {
+        clone!(ctx, new_contact, adding, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_contact, adding, errors, refresh);
+            spawn_local(async move {
+                match create_contact(&pupil_id, &new_contact, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_contact.set(NewContact::default());
+                        adding.set(false);
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to add contact:", error.to_string()),
+                }
+            });
+        })
+    };
+    let restricted: Vec<&PupilContact> = contacts.iter().filter(|contact| contact.no_contact).collect();
+
+    html! {
+        <div class="flex flex-col gap-2">
+            if !restricted.is_empty() {
+                <div class="flex flex-col gap-1 p-2 rounded-md border-2 border-red-500 bg-red-100 text-red-800">
+                    <span class="font-bold">{"Do not contact"}</span>
+                    {restricted.iter().map(|contact| html! {
+                        <p class="text-sm">
+                            {format!("{} ({})", contact.name(), contact.relationship.replace('_', " "))}
+                            if let Some(notes) = &contact.restriction_notes {
+                                <span class="block text-xs">{notes}</span>
+                            }
+                        </p>
+                    }).collect::<Html>()}
+                </div>
+            }
+            <div class="flex justify-between items-center">
+                <h3 class="text-md">{"Contacts"}</h3>
+                <IconButton icon={if *adding { "close" } else { "add" }} onclick={
+                    clone!(adding);
+                    Callback::from(move |_| adding.set(!*adding))
+                } />
+            </div>
+            if *adding {
+                <div class="flex flex-col gap-1 text-sm">
+                    <div class="flex gap-1">
+                        <input type="text" id="contact_first_names" placeholder="First names" class="border-2 border-slate-200 rounded-md w-1/2" value={new_contact.first_names.clone()} onchange={&update}/>
+                        <input type="text" id="contact_last_name" placeholder="Last name" class="border-2 border-slate-200 rounded-md w-1/2" value={new_contact.last_name.clone()} onchange={&update}/>
+                    </div>
+                    <input type="tel" id="contact_phone" placeholder="Phone" class="border-2 border-slate-200 rounded-md" value={new_contact.phone.clone()} onchange={&update}/>
+                    <input type="email" id="contact_email" placeholder="Email" class="border-2 border-slate-200 rounded-md" value={new_contact.email.clone()} onchange={&update}/>
+                    <input type="text" id="contact_language" placeholder="Preferred language" class="border-2 border-slate-200 rounded-md" value={new_contact.preferred_language.clone()} onchange={&update}/>
+                    <div class="flex gap-1 items-center">
+                        <select id="contact_relationship" class="border-2 border-slate-200 rounded-md grow" onchange={&update}>
+                            {constant::CONTACT_RELATIONSHIPS.iter().map(|relationship| html! {
+                                <option value={*relationship} selected={new_contact.relationship == *relationship}>{relationship.replace('_', " ")}</option>
+                            }).collect::<Html>()}
+                        </select>
+                        <label for="contact_priority">{"Priority"}</label>
+                        <input type="number" min="1" id="contact_priority" class="border-2 border-slate-200 rounded-md w-[50px]" value={new_contact.priority.to_string()} onchange={&update}/>
+                    </div>
+                    <label class="flex gap-1 items-center text-xs" for="contact_pr">
+                        <input type="checkbox" id="contact_pr" checked={new_contact.parental_responsibility} onchange={&update}/>
+                        {"Parental responsibility"}
+                    </label>
+                    <label class="flex gap-1 items-center text-xs" for="contact_no_contact">
+                        <input type="checkbox" id="contact_no_contact" checked={new_contact.no_contact} onchange={&update}/>
+                        {"Must not be contacted"}
+                    </label>
+                    if new_contact.no_contact {
+                        <input type="text" id="contact_restriction" placeholder="Court order or other restriction" class="border-2 border-red-300 rounded-md" value={new_contact.restriction_notes.clone()} onchange={&update}/>
+                    }
+                    <div class="flex justify-end">
+                        <IconButton onclick={&add} icon="save" />
+                    </div>
+                    {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+                </div>
+            }
+            <ul class="flex flex-col gap-1 text-xs">
+                {contacts.iter().map(|contact| {
+                    let contact_id = contact.contact_id;
+                    let remove = {
+                        clone!(ctx, refresh);
+                        Callback::from(move |_| {
+                            clone!(ctx, refresh);
+                            spawn_local(async move {
+                                match unlink_contact(&pupil_id, &contact_id, &ctx.auth_token).await {
+                                    Ok(_) => refresh.emit(()),
+                                    Err(error) => error!("failed to remove contact:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    html! {
+                        <li key={contact_id.to_string()} class={classes!("border-l-4", "pl-2", if contact.no_contact { "border-red-500" } else { "border-slate-300" })}>
+                            <div class="flex justify-between items-center">
+                                <span class="text-sm">{format!("{}. {}", contact.priority, contact.name())}</span>
+                                <IconButton icon="delete" onclick={remove} />
+                            </div>
+                            <p class="text-slate-500">
+                                {contact.relationship.replace('_', " ")}
+                                if contact.parental_responsibility {
+                                    {" · parental responsibility"}
+                                }
+                                if let Some(language) = &contact.preferred_language {
+                                    {format!(" · speaks {language}")}
+                                }
+                            </p>
+                            if let Some(phone) = &contact.phone {
+                                <p>{phone}</p>
+                            }
+                            if let Some(email) = &contact.email {
+                                <p>{email}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ContactsPanelProps {
+    pub pupil_id: Uuid,
+}
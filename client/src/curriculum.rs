@@ -0,0 +1,7 @@
+mod framework;
+mod overview;
+mod panel;
+
+pub use framework::{fetch_framework, Framework};
+pub use overview::OverviewPage;
+pub use panel::ProgressionPanel;
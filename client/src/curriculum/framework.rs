@@ -0,0 +1,159 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct WhatMatters {
+    pub id: Uuid,
+    pub area_id: Uuid,
+    pub statement: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Area {
+    pub id: Uuid,
+    pub name: String,
+    pub what_matters: Vec<WhatMatters>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Step {
+    pub step: i32,
+    pub name: String,
+    pub typical_age: i32,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Framework {
+    pub areas: Vec<Area>,
+    pub steps: Vec<Step>,
+}
+
+impl Framework {
+    pub fn area(&self, id: &Uuid) -> Option<&Area> {
+        self.areas.iter().find(|area| area.id == *id)
+    }
+
+    pub fn statement(&self, id: &Uuid) -> Option<&WhatMatters> {
+        self.areas
+            .iter()
+            .flat_map(|area| &area.what_matters)
+            .find(|statement| statement.id == *id)
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Judgement {
+    pub id: Uuid,
+    pub what_matters_id: Uuid,
+    pub step: i32,
+    pub date: NaiveDate,
+    pub judged_by: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct Progression {
+    pub current: HashMap<Uuid, Judgement>,
+    pub history: Vec<Judgement>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewJudgement {
+    pub what_matters_id: Option<Uuid>,
+    pub step: i32,
+    pub date: NaiveDate,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct OverviewRow {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub steps: HashMap<Uuid, i32>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Overview {
+    pub year: i32,
+    pub what_matters: Vec<WhatMatters>,
+    pub rows: Vec<OverviewRow>,
+}
+
+fn progression_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/progression", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_framework(token: &str) -> Result<Framework> {
+    let response = Request::get(&format!("{}/framework", constant::CURRICULUM_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Framework>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_overview(year: i32, token: &str) -> Result<Overview> {
+    let response = Request::get(&format!("{}/overview/{year}", constant::CURRICULUM_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Overview>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_progression(pupil_id: &Uuid, token: &str) -> Result<Progression> {
+    let response = Request::get(&progression_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Progression>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the judgement
+pub async fn create_judgement(
+    pupil_id: &Uuid,
+    judgement: &NewJudgement,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&progression_path(pupil_id))
+        .json(judgement)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_judgement(pupil_id: &Uuid, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", progression_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,138 @@
+use super::framework::*;
+use crate::{app::AppContext, error::*};
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Current progression steps for a whole year group, one column per statement of what matters
+#[function_component(OverviewPage)]
+pub fn overview_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN PROGRESSION OVERVIEW");
+    let framework: UseStateHandle<Framework> = use_state_eq(Framework::default);
+    let year = use_state_eq(|| {
+        ctx.current_user
+            .years
+            .first()
+            .map(|y| *y as i32)
+            .unwrap_or_default()
+    });
+    // no area selected shows every statement
+    let area: UseStateHandle<Option<Uuid>> = use_state_eq(|| None);
+    let overview: UseStateHandle<Option<Overview>> = use_state_eq(|| None);
+    {
+        clone!(ctx, framework);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_framework(&ctx.auth_token).await {
+                        Ok(fetched) => framework.set(fetched),
+                        Err(error) => {
+                            error!("failed to get curriculum framework:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+    {
+        clone!(ctx, overview);
+        use_effect_with_deps(
+            move |year: &i32| {
+                let year = *year;
+                spawn_local(async move {
+                    match fetch_overview(year, &ctx.auth_token).await {
+                        Ok(fetched) => overview.set(Some(fetched)),
+                        Err(error) => {
+                            error!("failed to get progression overview:", error.to_string())
+                        }
+                    }
+                });
+            },
+            *year,
+        );
+    }
+
+    let columns: Vec<WhatMatters> = overview
+        .as_ref()
+        .map(|overview| {
+            overview
+                .what_matters
+                .iter()
+                .filter(|statement| area.map_or(true, |area| statement.area_id == area))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    html! {
+        <div class="flex flex-col m-3 gap-3">
+            <div class="flex p-3 gap-2 items-center shadow-lg rounded-md bg-white">
+                <h2 class="text-xl">{"Progression"}</h2>
+                <select id="progression_year" class="border-2 border-slate-200 rounded-md" onchange={
+                    clone!(year);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        if let Ok(value) = target.value().parse() {
+                            year.set(value);
+                        }
+                    })
+                }>
+                    {ctx.current_user.years.iter().map(|y| html! {
+                        <option value={y.to_string()} selected={*year == *y as i32}>{format!("Year {y}")}</option>
+                    }).collect::<Html>()}
+                </select>
+                <select id="progression_area" class="border-2 border-slate-200 rounded-md" onchange={
+                    clone!(area);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        area.set(target.value().parse().ok());
+                    })
+                }>
+                    <option value="" selected={area.is_none()}>{"All areas"}</option>
+                    {framework.areas.iter().map(|a| html! {
+                        <option value={a.id.to_string()} selected={*area == Some(a.id)}>{&a.name}</option>
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <div class="overflow-auto [max-height:calc(90vh-60px)] p-3 scrollbar shadow-lg rounded-md bg-white">
+                if let Some(overview) = &*overview {
+                    <table class="text-sm">
+                        <thead>
+                            <tr class="text-left align-bottom">
+                                <th class="sticky left-0 bg-white">{"Pupil"}</th>
+                                {columns.iter().map(|statement| html! {
+                                    <th class="px-1 font-normal text-xs max-w-[120px]" title={statement.statement.clone()}>
+                                        <span class="block text-slate-500">{framework.area(&statement.area_id).map(|a| a.name.clone()).unwrap_or_default()}</span>
+                                        <span class="line-clamp-3">{&statement.statement}</span>
+                                    </th>
+                                }).collect::<Html>()}
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {overview.rows.iter().map(|row| html! {
+                                <tr class="border-t border-slate-200">
+                                    <td class="sticky left-0 bg-white whitespace-nowrap pr-2">{format!("{} {}", row.first_names, row.last_name)}</td>
+                                    {columns.iter().map(|statement| match row.steps.get(&statement.id) {
+                                        Some(step) => html!(<td class="text-center">{step}</td>),
+                                        None => html!(<td class="text-center text-slate-300">{"-"}</td>),
+                                    }).collect::<Html>()}
+                                </tr>
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                    if overview.rows.is_empty() {
+                        <p class="text-sm text-slate-500">{"There are no active pupils in this year"}</p>
+                    }
+                } else {
+                    <p>{"Loading progression..."}</p>
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,194 @@
+use super::framework::*;
+use crate::{app::AppContext, elements::IconButton};
+use chrono::Utc;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Where a pupil is against each statement of what matters, with a form to record a new judgement
+#[function_component(ProgressionPanel)]
+pub fn progression_panel(props: &ProgressionPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN PROGRESSION PANEL");
+    let framework: UseStateHandle<Framework> = use_state_eq(Framework::default);
+    let progression: UseStateHandle<Progression> = use_state_eq(Progression::default);
+    let new_judgement = use_state_eq(|| NewJudgement {
+        what_matters_id: None,
+        step: 1,
+        date: Utc::now().date_naive(),
+        notes: None,
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    {
+        clone!(ctx, framework);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_framework(&ctx.auth_token).await {
+                        Ok(fetched) => framework.set(fetched),
+                        Err(error) => {
+                            error!("failed to get curriculum framework:", error.to_string())
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+
+    let refresh = {
+        clone!(ctx, progression);
+        let pupil_id = props.pupil_id;
+        Callback::from(move |_: ()| {
+            clone!(ctx, progression);
+            spawn_local(async move {
+                match fetch_progression(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => progression.set(fetched),
+                    Err(error) => error!("failed to get pupil progression:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let update_new_judgement = {
+        clone!(new_judgement);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut judgement = (*new_judgement).clone();
+            match target.id().as_str() {
+                "judgement_statement" => judgement.what_matters_id = target.value().parse().ok(),
+                "judgement_step" => {
+                    judgement.step = target.value().parse().unwrap_or(judgement.step)
+                }
+                "judgement_date" => {
+                    judgement.date = target.value().parse().unwrap_or(judgement.date)
+                }
+                "judgement_notes" => {
+                    judgement.notes = Some(target.value()).filter(|notes| !notes.trim().is_empty())
+                }
+                _ => {}
+            }
+            new_judgement.set(judgement);
+        })
+    };
+    let add_judgement = {
+        clone!(ctx, new_judgement, errors, refresh);
+        let pupil_id = props.pupil_id;
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_judgement, errors, refresh);
+            if new_judgement.what_matters_id.is_none() {
+                errors.set(HashMap::from([(
+                    "what_matters_id".into(),
+                    "choose a statement of what matters".into(),
+                )]));
+                return;
+            }
+            spawn_local(async move {
+                match create_judgement(&pupil_id, &new_judgement, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_judgement.set(NewJudgement {
+                            notes: None,
+                            ..(*new_judgement).clone()
+                        });
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to record judgement:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let statement_name = |id: &Uuid| {
+        framework
+            .statement(id)
+            .map(|statement| statement.statement.clone())
+            .unwrap_or_default()
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Progression"}</h3>
+            <div class="flex flex-col gap-1">
+                <select id="judgement_statement" class="border-2 border-slate-200 rounded-md text-sm" onchange={&update_new_judgement}>
+                    <option value="" selected={new_judgement.what_matters_id.is_none()}>{"Statement of what matters"}</option>
+                    {framework.areas.iter().map(|area| html! {
+                        <optgroup label={area.name.clone()}>
+                            {area.what_matters.iter().map(|statement| html! {
+                                <option value={statement.id.to_string()} selected={new_judgement.what_matters_id == Some(statement.id)}>
+                                    {&statement.statement}
+                                </option>
+                            }).collect::<Html>()}
+                        </optgroup>
+                    }).collect::<Html>()}
+                </select>
+                <div class="flex justify-between items-center gap-1">
+                    <select id="judgement_step" class="border-2 border-slate-200 rounded-md text-sm" onchange={&update_new_judgement}>
+                        {framework.steps.iter().map(|step| html! {
+                            <option value={step.step.to_string()} selected={new_judgement.step == step.step}>{&step.name}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    <input type="date" id="judgement_date" class="border-2 border-slate-200 rounded-md text-sm" value={new_judgement.date.to_string()} onchange={&update_new_judgement}/>
+                    <IconButton onclick={&add_judgement} icon="add" />
+                </div>
+                <input type="text" id="judgement_notes" placeholder="Notes" class="border-2 border-slate-200 rounded-md text-sm" value={new_judgement.notes.clone().unwrap_or_default()} onchange={&update_new_judgement}/>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-1 text-sm">
+                {framework.areas.iter().flat_map(|area| &area.what_matters).filter_map(|statement| {
+                    progression.current.get(&statement.id).map(|judgement| html! {
+                        <li class="flex justify-between gap-2" title={statement.statement.clone()}>
+                            <span class="truncate">{&statement.statement}</span>
+                            <span class="whitespace-nowrap">{format!("Step {}", judgement.step)}</span>
+                        </li>
+                    })
+                }).collect::<Html>()}
+            </ul>
+            <details class="text-xs">
+                <summary class="text-slate-500">{format!("History ({})", progression.history.len())}</summary>
+                <ul class="flex flex-col gap-1">
+                    {progression.history.iter().map(|judgement| {
+                        let delete = if judgement.judged_by == ctx.current_user.email_address {
+                            clone!(ctx, refresh);
+                            let (pupil_id, id) = (props.pupil_id, judgement.id);
+                            html!(<IconButton icon="delete" onclick={Callback::from(move |_| {
+                                clone!(ctx, refresh);
+                                spawn_local(async move {
+                                    match delete_judgement(&pupil_id, &id, &ctx.auth_token).await {
+                                        Ok(_) => refresh.emit(()),
+                                        Err(error) => error!("failed to remove judgement:", error.to_string()),
+                                    }
+                                });
+                            })} />)
+                        } else {
+                            html!()
+                        };
+                        html! {
+                            <li class="border-l-4 border-slate-300 pl-2">
+                                <div class="flex justify-between items-center text-slate-500">
+                                    <span>{format!("{} · step {} · {}", judgement.date.format("%d/%m/%Y"), judgement.step, judgement.judged_by)}</span>
+                                    {delete}
+                                </div>
+                                <p>{statement_name(&judgement.what_matters_id)}</p>
+                                if let Some(notes) = &judgement.notes {
+                                    <p class="italic">{notes}</p>
+                                }
+                            </li>
+                        }
+                    }).collect::<Html>()}
+                </ul>
+            </details>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ProgressionPanelProps {
+    pub pupil_id: Uuid,
+}
@@ -0,0 +1,4 @@
+mod assessment;
+mod panel;
+
+pub use panel::EalPanel;
@@ -0,0 +1,83 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static STAGES: [(&str, &str); 5] = [
+    ("A", "New to English"),
+    ("B", "Early acquisition"),
+    ("C", "Developing competence"),
+    ("D", "Competent"),
+    ("E", "Fluent"),
+];
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct EalAssessment {
+    pub id: Uuid,
+    pub stage: String,
+    #[serde(default)]
+    pub home_language: Option<String>,
+    pub assessed_on: NaiveDate,
+    pub assessed_by: String,
+    pub notes: String,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewEalAssessment {
+    pub stage: String,
+    pub home_language: String,
+    pub assessed_on: NaiveDate,
+    pub notes: String,
+}
+
+fn eal_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/eal", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_assessments(pupil_id: &Uuid, token: &str) -> Result<Vec<EalAssessment>> {
+    let response = Request::get(&eal_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<EalAssessment>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the assessment
+pub async fn create_assessment(
+    pupil_id: &Uuid,
+    assessment: &NewEalAssessment,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&eal_path(pupil_id))
+        .json(assessment)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_assessment(pupil_id: &Uuid, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", eal_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,157 @@
+use super::assessment::*;
+use crate::{app::AppContext, elements::IconButton};
+use chrono::Utc;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// A pupil's EAL assessments, latest first, with a form to record another. Recording or removing
+/// one can change the pupil's stage, so the new current stage is passed back up.
+#[function_component(EalPanel)]
+pub fn eal_panel(props: &EalPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN EAL PANEL");
+    let assessments: UseStateHandle<Vec<EalAssessment>> = use_state_eq(Vec::new);
+    let home_language = props.home_language.clone().unwrap_or_default();
+    let new_assessment = use_state_eq(|| NewEalAssessment {
+        stage: STAGES[0].0.to_owned(),
+        home_language: home_language.clone(),
+        assessed_on: Utc::now().date_naive(),
+        notes: String::new(),
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, assessments);
+        Callback::from(move |_: ()| {
+            clone!(ctx, assessments);
+            spawn_local(async move {
+                match fetch_assessments(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => assessments.set(fetched),
+                    Err(error) => error!("failed to get EAL assessments:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+    let changed = {
+        clone!(ctx, assessments);
+        let stage_changed = props.stage_changed.clone();
+        Callback::from(move |_: ()| {
+            clone!(ctx, assessments, stage_changed);
+            spawn_local(async move {
+                match fetch_assessments(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => {
+                        stage_changed.emit(fetched.first().map(|latest| latest.stage.clone()));
+                        assessments.set(fetched);
+                    }
+                    Err(error) => error!("failed to get EAL assessments:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let update = {
+        clone!(new_assessment);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut new = (*new_assessment).clone();
+            match target.id().as_str() {
+                "eal_stage" => new.stage = target.value(),
+                "eal_home_language" => new.home_language = target.value(),
+                "eal_assessed_on" => {
+                    new.assessed_on = target.value().parse().unwrap_or(new.assessed_on)
+                }
+                "eal_notes" => new.notes = target.value(),
+                _ => {}
+            }
+            new_assessment.set(new);
+        })
+    };
+    let add = {
+        clone!(ctx, new_assessment, errors, changed);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_assessment, errors, changed);
+            spawn_local(async move {
+                match create_assessment(&pupil_id, &new_assessment, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_assessment.set(NewEalAssessment {
+                            notes: String::new(),
+                            ..(*new_assessment).clone()
+                        });
+                        changed.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to record EAL assessment:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"English as an additional language"}</h3>
+            <div class="flex flex-col gap-1 text-sm">
+                <select id="eal_stage" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                    {STAGES.iter().map(|(stage, description)| html! {
+                        <option value={*stage} selected={new_assessment.stage == *stage}>{format!("{stage}: {description}")}</option>
+                    }).collect::<Html>()}
+                </select>
+                <input type="text" id="eal_home_language" placeholder="Home language" class="border-2 border-slate-200 rounded-md" value={new_assessment.home_language.clone()} onchange={&update}/>
+                <input type="text" id="eal_notes" placeholder="Notes" class="border-2 border-slate-200 rounded-md" value={new_assessment.notes.clone()} onchange={&update}/>
+                <div class="flex justify-between items-center gap-1">
+                    <label for="eal_assessed_on">{"Assessed on"}</label>
+                    <input type="date" id="eal_assessed_on" class="border-2 border-slate-200 rounded-md" value={new_assessment.assessed_on.to_string()} onchange={&update}/>
+                    <IconButton onclick={&add} icon="add" />
+                </div>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-1 text-xs">
+                {assessments.iter().map(|assessment| {
+                    let id = assessment.id;
+                    let delete = if assessment.assessed_by == ctx.current_user.email_address {
+                        clone!(ctx, changed);
+                        html!(<IconButton icon="delete" onclick={Callback::from(move |_| {
+                            clone!(ctx, changed);
+                            spawn_local(async move {
+                                match delete_assessment(&pupil_id, &id, &ctx.auth_token).await {
+                                    Ok(_) => changed.emit(()),
+                                    Err(error) => error!("failed to remove EAL assessment:", error.to_string()),
+                                }
+                            });
+                        })} />)
+                    } else {
+                        html!()
+                    };
+                    html! {
+                        <li key={id.to_string()} class="border-l-4 border-yellow-200 pl-2">
+                            <div class="flex justify-between items-center">
+                                <span>{format!("Stage {} · {} · {}", assessment.stage, assessment.assessed_on.format("%d/%m/%Y"), assessment.assessed_by)}</span>
+                                {delete}
+                            </div>
+                            if let Some(language) = &assessment.home_language {
+                                <p class="text-slate-500">{format!("Home language: {language}")}</p>
+                            }
+                            if !assessment.notes.is_empty() {
+                                <p class="italic">{&assessment.notes}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct EalPanelProps {
+    pub pupil_id: Uuid,
+    pub home_language: Option<String>,
+    pub stage_changed: Callback<Option<String>>,
+}
@@ -10,8 +10,18 @@ pub fn editable_field(props: &EditableFieldProps) -> Html {
     } else {
         "".into()
     };
+    let error = match &props.error {
+        Some(error) => html!(<span class="block text-xs text-red-500">{error}</span>),
+        None => html!(),
+    };
     if edit_mode {
-        html!(<input class={classes!("border-2", "border-slate-200", "rounded-md", Classes::from(class))} min="0" id={props.id.to_owned()} {value} type={props.input_type.to_owned()} {onchange}/>)
+        let border = if props.error.is_some() { "border-red-300" } else { "border-slate-200" };
+        html! {
+            <div class="flex flex-col">
+                <input class={classes!("border-2", border, "rounded-md", Classes::from(class))} min="0" id={props.id.to_owned()} {value} type={props.input_type.to_owned()} {onchange}/>
+                {error}
+            </div>
+        }
     } else {
         html!(<span {class}>{format!("{}", value)}</span>)
     }
@@ -25,4 +35,5 @@ pub struct EditableFieldProps {
     pub id: String,
     pub input_type: String,
     pub class: Option<String>,
+    pub error: Option<String>,
 }
@@ -10,6 +10,9 @@ pub fn icon_button(props: &IconButtonProps) -> Html {
         "refresh" => html!(<RefreshCcw />),
         "search" => html!(<Search />),
         "logout" => html!(<LogOut />),
+        "delete" => html!(<Trash2 size="14" />),
+        "save" => html!(<Save size="16" />),
+        "download" => html!(<Download size="16" />),
         unknown => panic!("{unknown} not a recognised button, maybe needs adding from yew_feather?")
     };
 
@@ -1,6 +1,9 @@
 use yew::prelude::*;
 
-use crate::{elements::Tag, pupils::PupilInputState};
+use crate::{
+    elements::Tag,
+    pupils::{eal_tag, PupilInputState},
+};
 
 #[function_component(PupilTags)]
 pub fn pupil_tags(props: &PupilTagsProps) -> Html {
@@ -22,8 +25,9 @@ pub fn pupil_tags(props: &PupilTagsProps) -> Html {
     html! {
         <div {class}>
             <Tag edit_mode={&props.edit_mode} id="mat" active={props.state.mat} color="purple" text="MAT" onclick={&onclick}/>
-            <Tag edit_mode={&props.edit_mode} id="eal" active={props.state.eal} color="yellow" text="EAL" onclick={&onclick}/>
-            <Tag edit_mode={&props.edit_mode} id="aln" active={props.state.aln} color="orange" text="ALN" onclick={&onclick}/>
+            // EAL and ALN are kept in step with assessments and the register, so not toggled here
+            <Tag id="eal" active={props.state.eal} color="yellow" text={eal_tag(props.state.eal_stage.as_deref())} />
+            <Tag id="aln" active={props.state.aln} color="orange" text="ALN" />
             <Tag edit_mode={&props.edit_mode} id="fsm" active={props.state.fsm} color="green" text="FSM" onclick={&onclick}/>
             <Tag edit_mode={&props.edit_mode} id="lac" active={props.state.lac} color="blue" text="LAC" onclick={&onclick}/>
         </div>
@@ -1,4 +1,6 @@
 use gloo_storage::errors::StorageError;
+use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Error {
@@ -28,6 +30,26 @@ impl std::fmt::Display for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The body the server sends back with an error status
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub details: Option<String>,
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+}
+
+impl ErrorResponse {
+    /// Errors to show against a form's inputs, with anything not about a field under "form"
+    pub fn into_fields(self) -> HashMap<String, String> {
+        if self.fields.is_empty() {
+            HashMap::from([("form".into(), self.details.unwrap_or(self.error))])
+        } else {
+            self.fields
+        }
+    }
+}
+
 from_error!(gloo_net::Error > ServerError);
 from_error!(std::num::ParseIntError > ValueError: "failed to parse to int");
 from_error!(chrono::ParseError > ValueError: "failed to parse date or time");
@@ -0,0 +1,4 @@
+mod panel;
+mod period;
+
+pub use panel::FlagHistoryPanel;
@@ -0,0 +1,191 @@
+use super::period::*;
+use crate::{app::AppContext, elements::IconButton};
+use chrono::Utc;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The dated history behind the pupil's flags, with a form to add a period that was missed or
+/// recorded late. The ticks on the pupil are worked out from these, so the flags that apply today
+/// are passed back up after any change.
+#[function_component(FlagHistoryPanel)]
+pub fn flag_history_panel(props: &FlagHistoryPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN FLAG HISTORY PANEL");
+    let periods: UseStateHandle<Vec<FlagPeriod>> = use_state_eq(Vec::new);
+    let new_period = use_state_eq(|| NewFlagPeriod {
+        flag: FLAGS[0].0.to_owned(),
+        start_date: Utc::now().date_naive(),
+        end_date: None,
+        source: String::new(),
+        notes: String::new(),
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, periods);
+        Callback::from(move |_: ()| {
+            clone!(ctx, periods);
+            spawn_local(async move {
+                match fetch_periods(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => periods.set(fetched),
+                    Err(error) => error!("failed to get flag periods:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+    let changed = {
+        clone!(ctx, periods);
+        let flags_changed = props.flags_changed.clone();
+        Callback::from(move |_: ()| {
+            clone!(ctx, periods, flags_changed);
+            spawn_local(async move {
+                match fetch_periods(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => {
+                        let today = Utc::now().date_naive();
+                        flags_changed.emit(
+                            fetched
+                                .iter()
+                                .filter(|period| period.covers(today))
+                                .map(|period| period.flag.clone())
+                                .collect(),
+                        );
+                        periods.set(fetched);
+                    }
+                    Err(error) => error!("failed to get flag periods:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let update = {
+        clone!(new_period);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut new = (*new_period).clone();
+            match target.id().as_str() {
+                "flag_period_flag" => new.flag = target.value(),
+                "flag_period_start" => {
+                    new.start_date = target.value().parse().unwrap_or(new.start_date)
+                }
+                "flag_period_end" => new.end_date = target.value().parse().ok(),
+                "flag_period_source" => new.source = target.value(),
+                "flag_period_notes" => new.notes = target.value(),
+                _ => {}
+            }
+            new_period.set(new);
+        })
+    };
+    let add = {
+        clone!(ctx, new_period, errors, changed);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_period, errors, changed);
+            spawn_local(async move {
+                match create_period(&pupil_id, &new_period, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_period.set(NewFlagPeriod {
+                            end_date: None,
+                            source: String::new(),
+                            notes: String::new(),
+                            ..(*new_period).clone()
+                        });
+                        changed.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to add flag period:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Flag history"}</h3>
+            <div class="flex flex-col gap-1 text-sm">
+                <select id="flag_period_flag" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                    {FLAGS.iter().map(|(flag, name)| html! {
+                        <option value={*flag} selected={new_period.flag == *flag}>{*name}</option>
+                    }).collect::<Html>()}
+                </select>
+                <div class="flex justify-between items-center gap-1">
+                    <label for="flag_period_start">{"From"}</label>
+                    <input type="date" id="flag_period_start" class="border-2 border-slate-200 rounded-md" value={new_period.start_date.to_string()} onchange={&update}/>
+                </div>
+                <div class="flex justify-between items-center gap-1">
+                    <label for="flag_period_end">{"To"}</label>
+                    <input type="date" id="flag_period_end" class="border-2 border-slate-200 rounded-md" value={new_period.end_date.map(|date| date.to_string()).unwrap_or_default()} onchange={&update}/>
+                </div>
+                <input type="text" id="flag_period_source" placeholder="Source, like a parent declaration" class="border-2 border-slate-200 rounded-md" value={new_period.source.clone()} onchange={&update}/>
+                <div class="flex justify-between items-center gap-1">
+                    <input type="text" id="flag_period_notes" placeholder="Notes" class="border-2 border-slate-200 rounded-md grow" value={new_period.notes.clone()} onchange={&update}/>
+                    <IconButton onclick={&add} icon="add" />
+                </div>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-1 text-xs">
+                {periods.iter().map(|period| {
+                    let id = period.id;
+                    let delete = {
+                        clone!(ctx, changed);
+                        Callback::from(move |_| {
+                            clone!(ctx, changed);
+                            spawn_local(async move {
+                                match delete_period(&pupil_id, &id, &ctx.auth_token).await {
+                                    Ok(_) => changed.emit(()),
+                                    Err(error) => error!("failed to remove flag period:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    let end_today = {
+                        clone!(ctx, changed, errors);
+                        Callback::from(move |_| {
+                            clone!(ctx, changed, errors);
+                            spawn_local(async move {
+                                match end_period(&pupil_id, &id, Utc::now().date_naive(), &ctx.auth_token).await {
+                                    Ok(None) => changed.emit(()),
+                                    Ok(Some(fields)) => errors.set(fields),
+                                    Err(error) => error!("failed to end flag period:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    let dates = match period.end_date {
+                        Some(end) => format!("{} to {}", period.start_date.format("%d/%m/%Y"), end.format("%d/%m/%Y")),
+                        None => format!("from {}", period.start_date.format("%d/%m/%Y")),
+                    };
+                    html! {
+                        <li key={id.to_string()} class={classes!("border-l-4", "pl-2", if period.end_date.is_none() { "border-green-300" } else { "border-slate-200" })}>
+                            <div class="flex justify-between items-center">
+                                <span>{format!("{} · {dates}", flag_name(&period.flag))}</span>
+                                <div class="flex items-center">
+                                    if period.end_date.is_none() {
+                                        <button class="underline" onclick={end_today}>{"End today"}</button>
+                                    }
+                                    <IconButton icon="delete" onclick={delete} />
+                                </div>
+                            </div>
+                            <p class="text-slate-500">{&period.source}</p>
+                            if !period.notes.is_empty() {
+                                <p class="italic">{&period.notes}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct FlagHistoryPanelProps {
+    pub pupil_id: Uuid,
+    pub flags_changed: Callback<Vec<String>>,
+}
@@ -0,0 +1,121 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static FLAGS: [(&str, &str); 5] = [
+    ("fsm", "Free school meals"),
+    ("lac", "Looked after"),
+    ("eal", "English as additional language"),
+    ("mat", "More able and talented"),
+    ("aln", "Additional learning needs"),
+];
+
+pub fn flag_name(flag: &str) -> &str {
+    FLAGS
+        .iter()
+        .find(|(value, _)| *value == flag)
+        .map(|(_, name)| *name)
+        .unwrap_or(flag)
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct FlagPeriod {
+    pub id: Uuid,
+    pub flag: String,
+    pub start_date: NaiveDate,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    pub source: String,
+    pub notes: String,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+}
+
+impl FlagPeriod {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && self.end_date.is_none_or(|end| date <= end)
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewFlagPeriod {
+    pub flag: String,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub source: String,
+    pub notes: String,
+}
+
+fn flags_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/flags", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_periods(pupil_id: &Uuid, token: &str) -> Result<Vec<FlagPeriod>> {
+    let response = Request::get(&flags_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<FlagPeriod>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the period
+pub async fn create_period(
+    pupil_id: &Uuid,
+    period: &NewFlagPeriod,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&flags_path(pupil_id))
+        .json(period)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Close an open period on the given day. Returns the server's field errors if it rejected it.
+pub async fn end_period(
+    pupil_id: &Uuid,
+    id: &Uuid,
+    end_date: NaiveDate,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::patch(&format!("{}/{id}", flags_path(pupil_id)))
+        .json(&json!({ "end_date": end_date }))?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_period(pupil_id: &Uuid, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", flags_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,9 @@
+mod details;
+mod intervention;
+mod page;
+mod panel;
+mod targets;
+
+pub use page::InterventionsPage;
+pub use panel::InterventionsPanel;
+pub use targets::TargetsPanel;
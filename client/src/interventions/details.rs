@@ -0,0 +1,280 @@
+use super::intervention::*;
+use crate::{
+    app::AppContext,
+    elements::{Button, IconButton},
+    pupils::Pupil,
+};
+use chrono::Utc;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Members with their entry and exit measures, and the sessions the group has had. Only the lead
+/// gets the controls to change them, the server checks this too.
+#[function_component(InterventionDetails)]
+pub fn intervention_details(props: &InterventionDetailsProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN INTERVENTION DETAILS");
+    let details: UseStateHandle<Option<Details>> = use_state_eq(|| None);
+    let pupils: UseStateHandle<Vec<Pupil>> = use_state_eq(Vec::new);
+    let new_session = use_state_eq(|| NewSession {
+        date: Utc::now().date_naive(),
+        duration_minutes: 30,
+        notes: String::new(),
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let id = props.intervention_id;
+
+    let refresh = {
+        clone!(ctx, details);
+        Callback::from(move |_: ()| {
+            clone!(ctx, details);
+            spawn_local(async move {
+                match fetch_details(&id, &ctx.auth_token).await {
+                    Ok(fetched) => details.set(Some(fetched)),
+                    Err(error) => error!("failed to get intervention:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), id);
+    }
+    {
+        clone!(ctx, pupils);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_pupils(&ctx.auth_token).await {
+                        Ok(fetched) => pupils.set(fetched),
+                        Err(error) => error!("failed to get pupils:", error.to_string()),
+                    }
+                });
+            },
+            (),
+        );
+    }
+
+    let save = {
+        clone!(ctx, details);
+        Callback::from(move |entry: MemberEntry| {
+            clone!(ctx, details);
+            spawn_local(async move {
+                match save_member(&id, &entry, &ctx.auth_token).await {
+                    Ok(saved) => details.set(Some(saved)),
+                    Err(error) => error!("failed to save intervention member:", error.to_string()),
+                }
+            });
+        })
+    };
+    let update_new_session = {
+        clone!(new_session);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut session = (*new_session).clone();
+            match target.id().as_str() {
+                "session_date" => session.date = target.value().parse().unwrap_or(session.date),
+                "session_duration" => {
+                    session.duration_minutes =
+                        target.value().parse().unwrap_or(session.duration_minutes)
+                }
+                "session_notes" => session.notes = target.value(),
+                _ => {}
+            }
+            new_session.set(session);
+        })
+    };
+    let add_session = {
+        clone!(ctx, new_session, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_session, errors, refresh);
+            spawn_local(async move {
+                match log_session(&id, &new_session, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_session.set(NewSession {
+                            notes: String::new(),
+                            ..(*new_session).clone()
+                        });
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to log session:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let details = match &*details {
+        Some(details) => details,
+        None => return html!(<p class="p-3">{"Loading intervention..."}</p>),
+    };
+    let intervention = &details.intervention;
+    let is_lead = intervention.lead_staff == ctx.current_user.email_address;
+    let measure = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+    let today = Utc::now().date_naive();
+
+    html! {
+        <div class="flex flex-col gap-3">
+            <div class="flex flex-col gap-1 p-3 shadow-lg rounded-md bg-white">
+                <div class="flex justify-between items-center">
+                    <h2 class="text-xl">{&intervention.name}</h2>
+                    if is_lead {
+                        <div class="flex gap-2">
+                            if intervention.end_date.is_none() {
+                                <Button color="yellow" text="End today" onclick={
+                                    clone!(ctx, refresh);
+                                    let refresh_page = props.refresh_callback.clone();
+                                    Callback::from(move |_| {
+                                        clone!(ctx, refresh, refresh_page);
+                                        spawn_local(async move {
+                                            match end_intervention(&id, Some(today), &ctx.auth_token).await {
+                                                Ok(None) => {
+                                                    refresh.emit(());
+                                                    refresh_page.emit(false);
+                                                }
+                                                Ok(Some(fields)) => error!("intervention could not be ended:", format!("{fields:?}")),
+                                                Err(error) => error!("failed to end intervention:", error.to_string()),
+                                            }
+                                        });
+                                    })
+                                } />
+                            }
+                            <Button color="red" text="Delete" icon={html!(<yew_feather::Trash size="16" />)} onclick={
+                                clone!(ctx);
+                                let refresh_page = props.refresh_callback.clone();
+                                Callback::from(move |_| {
+                                    clone!(ctx, refresh_page);
+                                    spawn_local(async move {
+                                        match delete_intervention(&id, &ctx.auth_token).await {
+                                            Ok(_) => refresh_page.emit(true),
+                                            Err(error) => error!("failed to delete intervention:", error.to_string()),
+                                        }
+                                    });
+                                })
+                            } />
+                        </div>
+                    }
+                </div>
+                <p class="text-sm text-slate-500">{format!(
+                    "Led by {} · {} to {}",
+                    intervention.lead_staff,
+                    intervention.start_date.format("%d/%m/%Y"),
+                    intervention.end_date.map(|end| end.format("%d/%m/%Y").to_string()).unwrap_or_else(|| "ongoing".into())
+                )}</p>
+                <p class="text-sm whitespace-pre-wrap">{&intervention.description}</p>
+            </div>
+            <div class="flex gap-3">
+                <div class="flex flex-col gap-2 p-3 shadow-lg rounded-md bg-white grow">
+                    <h3 class="text-md">{"Members"}</h3>
+                    <table class="text-sm">
+                        <thead>
+                            <tr class="text-left">
+                                <th>{"Pupil"}</th>
+                                <th title={intervention.measure.clone()}>{"Entry"}</th>
+                                <th title={intervention.measure.clone()}>{"Exit"}</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {details.members.iter().map(|member| {
+                                let pupil_id = member.pupil_id;
+                                let (entry_measure, exit_measure) = (member.entry_measure, member.exit_measure);
+                                let on_measure = {
+                                    clone!(save);
+                                    Callback::from(move |ev: Event| {
+                                        let target: HtmlInputElement = ev.target_unchecked_into();
+                                        let value = target.value().parse().ok();
+                                        save.emit(match target.name().as_str() {
+                                            "entry" => MemberEntry { pupil_id, entry_measure: value, exit_measure },
+                                            _ => MemberEntry { pupil_id, entry_measure, exit_measure: value },
+                                        });
+                                    })
+                                };
+                                html! {
+                                    <tr class="border-t border-slate-200">
+                                        <td>{format!("{} {} (Y{})", member.first_names, member.last_name, member.year)}</td>
+                                        <td><input type="number" name="entry" class="border-2 border-slate-200 rounded-md w-20" disabled={!is_lead} value={measure(entry_measure)} onchange={&on_measure}/></td>
+                                        <td><input type="number" name="exit" class="border-2 border-slate-200 rounded-md w-20" disabled={!is_lead} value={measure(exit_measure)} onchange={&on_measure}/></td>
+                                        <td>
+                                            if is_lead {
+                                                <IconButton icon="delete" onclick={
+                                                    clone!(ctx, refresh);
+                                                    Callback::from(move |_| {
+                                                        clone!(ctx, refresh);
+                                                        spawn_local(async move {
+                                                            match remove_member(&id, &pupil_id, &ctx.auth_token).await {
+                                                                Ok(_) => refresh.emit(()),
+                                                                Err(error) => error!("failed to remove intervention member:", error.to_string()),
+                                                            }
+                                                        });
+                                                    })
+                                                } />
+                                            }
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                    if is_lead {
+                        <select id="add_member" class="border-2 border-slate-200 rounded-md text-sm" onchange={
+                            clone!(save);
+                            Callback::from(move |ev: Event| {
+                                let target: HtmlInputElement = ev.target_unchecked_into();
+                                if let Ok(pupil_id) = target.value().parse::<Uuid>() {
+                                    save.emit(MemberEntry { pupil_id, entry_measure: None, exit_measure: None });
+                                }
+                                target.set_value("");
+                            })
+                        }>
+                            <option value="" selected=true>{"Add a pupil"}</option>
+                            {pupils.iter()
+                                .filter(|pupil| pupil.active)
+                                .filter(|pupil| !details.members.iter().any(|member| Some(member.pupil_id) == pupil.id))
+                                .map(|pupil| html! {
+                                    <option value={pupil.id.map(|id| id.to_string()).unwrap_or_default()}>
+                                        {format!("{} (Y{})", pupil.display_name(), pupil.year)}
+                                    </option>
+                                }).collect::<Html>()}
+                        </select>
+                    }
+                </div>
+                <div class="flex flex-col gap-2 p-3 shadow-lg rounded-md bg-white w-[350px]">
+                    <h3 class="text-md">{format!("Sessions ({})", details.sessions.len())}</h3>
+                    if is_lead {
+                        <div class="flex flex-col gap-1">
+                            <div class="flex gap-1 items-center text-sm">
+                                <input type="date" id="session_date" class="border-2 border-slate-200 rounded-md" value={new_session.date.to_string()} onchange={&update_new_session}/>
+                                <input type="number" min="1" id="session_duration" class="border-2 border-slate-200 rounded-md w-16" value={new_session.duration_minutes.to_string()} onchange={&update_new_session}/>
+                                <span>{"mins"}</span>
+                                <IconButton icon="add" onclick={&add_session} />
+                            </div>
+                            <input type="text" id="session_notes" placeholder="Notes" class="border-2 border-slate-200 rounded-md text-sm" value={new_session.notes.clone()} onchange={&update_new_session}/>
+                            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+                        </div>
+                    }
+                    <ul class="flex flex-col gap-1 text-xs">
+                        {details.sessions.iter().map(|session| html! {
+                            <li class="border-l-4 border-slate-300 pl-2">
+                                <p class="text-slate-500">{format!("{} · {} mins · {}", session.date.format("%d/%m/%Y"), session.duration_minutes, session.recorded_by)}</p>
+                                if !session.notes.is_empty() {
+                                    <p>{&session.notes}</p>
+                                }
+                            </li>
+                        }).collect::<Html>()}
+                    </ul>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct InterventionDetailsProps {
+    pub intervention_id: Uuid,
+    /// true when the intervention was deleted
+    pub refresh_callback: Callback<bool>,
+}
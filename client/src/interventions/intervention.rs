@@ -0,0 +1,316 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+    pupils::Pupil,
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static OUTCOMES: [(&str, &str); 3] = [
+    ("achieved", "Achieved"),
+    ("partially_achieved", "Partially achieved"),
+    ("not_achieved", "Not achieved"),
+];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Intervention {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub description: String,
+    pub lead_staff: String,
+    pub start_date: NaiveDate,
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+    pub measure: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Member {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub year: i32,
+    pub entry_measure: Option<i32>,
+    pub exit_measure: Option<i32>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Session {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub duration_minutes: i32,
+    pub notes: String,
+    pub recorded_by: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Details {
+    #[serde(flatten)]
+    pub intervention: Intervention,
+    pub members: Vec<Member>,
+    pub sessions: Vec<Session>,
+}
+
+/// An intervention as seen from one of its members
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct PupilIntervention {
+    #[serde(flatten)]
+    pub intervention: Intervention,
+    pub active: bool,
+    pub entry_measure: Option<i32>,
+    pub exit_measure: Option<i32>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct MemberEntry {
+    pub pupil_id: Uuid,
+    pub entry_measure: Option<i32>,
+    pub exit_measure: Option<i32>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewSession {
+    pub date: NaiveDate,
+    pub duration_minutes: i32,
+    pub notes: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Target {
+    pub id: Uuid,
+    pub description: String,
+    pub success_criteria: String,
+    pub set_by: String,
+    pub set_on: NaiveDate,
+    pub review_date: NaiveDate,
+    pub outcome: Option<String>,
+    pub reviewed_on: Option<NaiveDate>,
+    pub review_notes: Option<String>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewTarget {
+    pub description: String,
+    pub success_criteria: String,
+    pub review_date: NaiveDate,
+}
+
+fn intervention_path(id: &Uuid) -> String {
+    format!("{}/{id}", constant::INTERVENTIONS_PATH)
+}
+
+fn targets_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/targets", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_interventions(token: &str) -> Result<Vec<Intervention>> {
+    let response = Request::get(constant::INTERVENTIONS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Intervention>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_details(id: &Uuid, token: &str) -> Result<Details> {
+    let response = Request::get(&intervention_path(id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Details>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// The pupils the user can add to an intervention
+pub async fn fetch_pupils(token: &str) -> Result<Vec<Pupil>> {
+    let response = Request::get(constant::PUPILS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Pupil>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the intervention
+pub async fn create_intervention(
+    intervention: &Intervention,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(constant::INTERVENTIONS_PATH)
+        .json(intervention)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Sets or clears the end date, returning the server's field errors if it rejected it
+pub async fn end_intervention(
+    id: &Uuid,
+    end_date: Option<NaiveDate>,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::patch(&intervention_path(id))
+        .json(&json!({ "end_date": end_date }))?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_intervention(id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&intervention_path(id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn save_member(id: &Uuid, entry: &MemberEntry, token: &str) -> Result<Details> {
+    let response = Request::put(&format!("{}/members", intervention_path(id)))
+        .json(entry)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Details>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn remove_member(id: &Uuid, pupil_id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/members/{pupil_id}", intervention_path(id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the session
+pub async fn log_session(
+    id: &Uuid,
+    session: &NewSession,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&format!("{}/sessions", intervention_path(id)))
+        .json(session)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_pupil_interventions(
+    pupil_id: &Uuid,
+    token: &str,
+) -> Result<Vec<PupilIntervention>> {
+    let response = Request::get(&format!("{}/{pupil_id}/interventions", constant::PUPILS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<PupilIntervention>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_targets(pupil_id: &Uuid, token: &str) -> Result<Vec<Target>> {
+    let response = Request::get(&targets_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<Target>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the target
+pub async fn create_target(
+    pupil_id: &Uuid,
+    target: &NewTarget,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&targets_path(pupil_id))
+        .json(target)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Records the outcome of a review, an empty outcome clears it
+pub async fn review_target(
+    pupil_id: &Uuid,
+    id: &Uuid,
+    outcome: Option<&str>,
+    token: &str,
+) -> Result<()> {
+    let response = Request::patch(&format!("{}/{id}", targets_path(pupil_id)))
+        .json(&json!({ "outcome": outcome }))?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_target(pupil_id: &Uuid, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", targets_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,200 @@
+use super::{details::InterventionDetails, intervention::*};
+use crate::{app::AppContext, elements::Button, error::*};
+use chrono::{NaiveDate, Utc};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The raw text of the create form, only turned into an Intervention when it's submitted
+#[derive(Clone, PartialEq, Default)]
+struct InputState {
+    name: String,
+    description: String,
+    measure: String,
+    start_date: String,
+    end_date: String,
+    errors: HashMap<String, String>,
+}
+
+impl InputState {
+    fn update(&mut self, target: HtmlInputElement) {
+        let id = target.id();
+        self.errors.remove(&id);
+        match id.as_str() {
+            "intervention_name" => self.name = target.value(),
+            "intervention_description" => self.description = target.value(),
+            "intervention_measure" => self.measure = target.value(),
+            "intervention_start_date" => self.start_date = target.value(),
+            "intervention_end_date" => self.end_date = target.value(),
+            _ => {}
+        }
+    }
+
+    /// Parse the form with the user as the lead, an empty end date leaves it open ended
+    fn to_intervention(
+        &self,
+        lead_staff: &str,
+    ) -> std::result::Result<Intervention, HashMap<String, String>> {
+        let mut errors = HashMap::new();
+        let start_date = self.start_date.parse::<NaiveDate>().map_err(|_| {
+            errors.insert("start_date".to_string(), "start date must be a valid date".to_string());
+        });
+        let end_date = match self.end_date.as_str() {
+            "" => Ok(None),
+            date => date.parse::<NaiveDate>().map(Some).map_err(|_| {
+                errors.insert("end_date".to_string(), "end date must be a valid date".to_string());
+            }),
+        };
+        match (start_date, end_date) {
+            (Ok(start_date), Ok(end_date)) => Ok(Intervention {
+                id: None,
+                name: self.name.clone(),
+                description: self.description.clone(),
+                lead_staff: lead_staff.to_owned(),
+                start_date,
+                end_date,
+                measure: self.measure.clone(),
+            }),
+            _ => Err(errors),
+        }
+    }
+
+    fn error(&self, id: &str) -> Html {
+        match self.errors.get(id) {
+            Some(error) => html!(<span class="text-xs text-red-500">{error}</span>),
+            None => html!(),
+        }
+    }
+}
+
+/// Every intervention group, with the members and sessions of whichever one is selected
+#[function_component(InterventionsPage)]
+pub fn interventions_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN INTERVENTIONS PAGE");
+    let interventions: UseStateHandle<Vec<Intervention>> = use_state_eq(Vec::new);
+    let selected: UseStateHandle<Option<Uuid>> = use_state_eq(|| None);
+    let input_state = use_state(|| InputState {
+        start_date: Utc::now().date_naive().to_string(),
+        ..Default::default()
+    });
+
+    let refresh_callback = {
+        clone!(ctx, interventions);
+        Callback::from(move |_: ()| {
+            clone!(ctx, interventions);
+            spawn_local(async move {
+                match fetch_interventions(&ctx.auth_token).await {
+                    Ok(fetched) => interventions.set(fetched),
+                    Err(error) => {
+                        error!("failed to get interventions:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh_callback);
+        use_effect_with_deps(move |_| refresh_callback.emit(()), ());
+    }
+
+    let update_state_cb = {
+        clone!(input_state);
+        Callback::from(move |ev: Event| {
+            let mut state = (*input_state).clone();
+            state.update(ev.target_unchecked_into());
+            input_state.set(state);
+        })
+    };
+    let create_callback = {
+        clone!(input_state, ctx, refresh_callback);
+        Callback::from(move |_| {
+            clone!(input_state, ctx, refresh_callback);
+            let intervention = match input_state.to_intervention(&ctx.current_user.email_address) {
+                Ok(intervention) => intervention,
+                Err(errors) => {
+                    input_state.set(InputState { errors, ..(*input_state).clone() });
+                    return;
+                }
+            };
+            spawn_local(async move {
+                match create_intervention(&intervention, &ctx.auth_token).await {
+                    Ok(None) => {
+                        refresh_callback.emit(());
+                        input_state.set(InputState {
+                            start_date: Utc::now().date_naive().to_string(),
+                            ..Default::default()
+                        });
+                    }
+                    Ok(Some(errors)) => input_state.set(InputState { errors, ..(*input_state).clone() }),
+                    Err(error) => error!("failed to create intervention:", error.to_string()),
+                }
+            });
+        })
+    };
+    let today = Utc::now().date_naive();
+
+    html! {
+        <div class="flex m-3 gap-3">
+            <div class="flex flex-col gap-3 w-[350px]">
+                <div class="flex flex-col gap-1 p-3 shadow-lg rounded-md bg-white">
+                    <input id="intervention_name" class="border-2 border-slate-200 rounded-md" placeholder="Name" value={input_state.name.clone()} onchange={&update_state_cb}/>
+                    {input_state.error("name")}
+                    <textarea id="intervention_description" class="border-2 border-slate-200 rounded-md text-sm" rows="2" placeholder="What the group does" value={input_state.description.clone()} onchange={&update_state_cb}/>
+                    <input id="intervention_measure" class="border-2 border-slate-200 rounded-md" placeholder="Measure, e.g. reading age in months" value={input_state.measure.clone()} onchange={&update_state_cb}/>
+                    <div class="flex gap-2 items-center text-sm">
+                        <label for="intervention_start_date">{"From"}</label>
+                        <input id="intervention_start_date" type="date" class="border-2 border-slate-200 rounded-md" value={input_state.start_date.clone()} onchange={&update_state_cb}/>
+                        <label for="intervention_end_date">{"to"}</label>
+                        <input id="intervention_end_date" type="date" class="border-2 border-slate-200 rounded-md" value={input_state.end_date.clone()} onchange={&update_state_cb}/>
+                    </div>
+                    {input_state.error("start_date")}
+                    {input_state.error("end_date")}
+                    {input_state.error("lead_staff")}
+                    <Button icon={html!(<yew_feather::Plus size="16" />)} text="Add" color="green" onclick={&create_callback} />
+                </div>
+                <ul class="flex flex-col gap-1 p-3 shadow-lg rounded-md bg-white overflow-y-auto [max-height:calc(60vh)] scrollbar">
+                    {interventions.iter().map(|intervention| {
+                        let id = intervention.id;
+                        let is_selected = *selected == id;
+                        let running = intervention.start_date <= today
+                            && intervention.end_date.map_or(true, |end| end >= today);
+                        html! {
+                            <li class={classes!("cursor-pointer", "rounded-md", "p-1", is_selected.then_some("bg-slate-200"))} onclick={
+                                clone!(selected);
+                                Callback::from(move |_| selected.set(id))
+                            }>
+                                <p class={classes!((!running).then_some("text-slate-500"))}>{&intervention.name}</p>
+                                <p class="text-xs text-slate-500">{format!(
+                                    "{} · {} to {}",
+                                    intervention.lead_staff,
+                                    intervention.start_date.format("%d/%m/%Y"),
+                                    intervention.end_date.map(|end| end.format("%d/%m/%Y").to_string()).unwrap_or_else(|| "ongoing".into())
+                                )}</p>
+                            </li>
+                        }
+                    }).collect::<Html>()}
+                </ul>
+            </div>
+            <div class="grow">
+                if let Some(id) = *selected {
+                    <InterventionDetails intervention_id={id} refresh_callback={
+                        clone!(selected, refresh_callback);
+                        Callback::from(move |deleted: bool| {
+                            if deleted {
+                                selected.set(None);
+                            }
+                            refresh_callback.emit(());
+                        })
+                    } />
+                } else {
+                    <p class="p-3">{"Choose an intervention to see who is in it"}</p>
+                }
+            </div>
+        </div>
+    }
+}
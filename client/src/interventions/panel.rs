@@ -0,0 +1,70 @@
+use super::intervention::*;
+use crate::app::AppContext;
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// The interventions a pupil has been in, running ones first, with their entry and exit measures
+#[function_component(InterventionsPanel)]
+pub fn interventions_panel(props: &InterventionsPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN INTERVENTIONS PANEL");
+    let interventions: UseStateHandle<Vec<PupilIntervention>> = use_state_eq(Vec::new);
+    {
+        clone!(ctx, interventions);
+        use_effect_with_deps(
+            move |pupil_id: &Uuid| {
+                let pupil_id = *pupil_id;
+                spawn_local(async move {
+                    match fetch_pupil_interventions(&pupil_id, &ctx.auth_token).await {
+                        Ok(mut fetched) => {
+                            fetched.sort_by_key(|intervention| !intervention.active);
+                            interventions.set(fetched)
+                        }
+                        Err(error) => {
+                            error!("failed to get pupil interventions:", error.to_string())
+                        }
+                    }
+                });
+            },
+            props.pupil_id,
+        );
+    }
+
+    let measure = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or("-".into());
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Interventions"}</h3>
+            if interventions.is_empty() {
+                <p class="text-xs text-slate-500">{"Not in any interventions"}</p>
+            }
+            <ul class="flex flex-col gap-1 text-sm">
+                {interventions.iter().map(|pupil_intervention| {
+                    let intervention = &pupil_intervention.intervention;
+                    html! {
+                        <li class={classes!("border-l-4", "pl-2", if pupil_intervention.active { "border-green-400" } else { "border-slate-300" })}>
+                            <div class="flex justify-between gap-2">
+                                <span class="truncate" title={intervention.description.clone()}>{&intervention.name}</span>
+                                <span class="whitespace-nowrap text-xs" title={intervention.measure.clone()}>
+                                    {format!("{} → {}", measure(pupil_intervention.entry_measure), measure(pupil_intervention.exit_measure))}
+                                </span>
+                            </div>
+                            <p class="text-xs text-slate-500">{format!(
+                                "{} · {} to {}",
+                                intervention.lead_staff,
+                                intervention.start_date.format("%d/%m/%Y"),
+                                intervention.end_date.map(|end| end.format("%d/%m/%Y").to_string()).unwrap_or_else(|| "ongoing".into())
+                            )}</p>
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct InterventionsPanelProps {
+    pub pupil_id: Uuid,
+}
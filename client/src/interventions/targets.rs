@@ -0,0 +1,157 @@
+use super::intervention::*;
+use crate::{app::AppContext, elements::IconButton};
+use chrono::{Duration, Utc};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// A pupil's SMART targets, next due for review first, with a form to set another
+#[function_component(TargetsPanel)]
+pub fn targets_panel(props: &TargetsPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN TARGETS PANEL");
+    let targets: UseStateHandle<Vec<Target>> = use_state_eq(Vec::new);
+    let new_target = use_state_eq(|| NewTarget {
+        description: String::new(),
+        success_criteria: String::new(),
+        review_date: Utc::now().date_naive() + Duration::weeks(6),
+    });
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, targets);
+        Callback::from(move |_: ()| {
+            clone!(ctx, targets);
+            spawn_local(async move {
+                match fetch_targets(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => targets.set(fetched),
+                    Err(error) => error!("failed to get pupil targets:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let update_new_target = {
+        clone!(new_target);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut new = (*new_target).clone();
+            match target.id().as_str() {
+                "target_description" => new.description = target.value(),
+                "target_criteria" => new.success_criteria = target.value(),
+                "target_review_date" => {
+                    new.review_date = target.value().parse().unwrap_or(new.review_date)
+                }
+                _ => {}
+            }
+            new_target.set(new);
+        })
+    };
+    let add_target = {
+        clone!(ctx, new_target, errors, refresh);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_target, errors, refresh);
+            spawn_local(async move {
+                match create_target(&pupil_id, &new_target, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_target.set(NewTarget {
+                            description: String::new(),
+                            success_criteria: String::new(),
+                            ..(*new_target).clone()
+                        });
+                        refresh.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to set target:", error.to_string()),
+                }
+            });
+        })
+    };
+    let today = Utc::now().date_naive();
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Targets"}</h3>
+            <div class="flex flex-col gap-1">
+                <input type="text" id="target_description" placeholder="Target" class="border-2 border-slate-200 rounded-md text-sm" value={new_target.description.clone()} onchange={&update_new_target}/>
+                <input type="text" id="target_criteria" placeholder="How we'll know it's met" class="border-2 border-slate-200 rounded-md text-sm" value={new_target.success_criteria.clone()} onchange={&update_new_target}/>
+                <div class="flex justify-between items-center gap-1 text-sm">
+                    <label for="target_review_date">{"Review on"}</label>
+                    <input type="date" id="target_review_date" class="border-2 border-slate-200 rounded-md" value={new_target.review_date.to_string()} onchange={&update_new_target}/>
+                    <IconButton onclick={&add_target} icon="add" />
+                </div>
+                {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            </div>
+            <ul class="flex flex-col gap-1 text-xs">
+                {targets.iter().map(|target| {
+                    let id = target.id;
+                    let overdue = target.outcome.is_none() && target.review_date < today;
+                    let review = {
+                        clone!(ctx, refresh);
+                        Callback::from(move |ev: Event| {
+                            let select: HtmlInputElement = ev.target_unchecked_into();
+                            let outcome = Some(select.value()).filter(|outcome| !outcome.is_empty());
+                            clone!(ctx, refresh);
+                            spawn_local(async move {
+                                match review_target(&pupil_id, &id, outcome.as_deref(), &ctx.auth_token).await {
+                                    Ok(_) => refresh.emit(()),
+                                    Err(error) => error!("failed to review target:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    let delete = if target.set_by == ctx.current_user.email_address {
+                        clone!(ctx, refresh);
+                        html!(<IconButton icon="delete" onclick={Callback::from(move |_| {
+                            clone!(ctx, refresh);
+                            spawn_local(async move {
+                                match delete_target(&pupil_id, &id, &ctx.auth_token).await {
+                                    Ok(_) => refresh.emit(()),
+                                    Err(error) => error!("failed to remove target:", error.to_string()),
+                                }
+                            });
+                        })} />)
+                    } else {
+                        html!()
+                    };
+                    html! {
+                        <li class="border-l-4 border-slate-300 pl-2">
+                            <div class="flex justify-between items-center">
+                                <span class={classes!(overdue.then_some("text-red-500"))}>
+                                    {format!("Review {} · set by {}", target.review_date.format("%d/%m/%Y"), target.set_by)}
+                                </span>
+                                {delete}
+                            </div>
+                            <p class="text-sm">{&target.description}</p>
+                            <p class="italic">{&target.success_criteria}</p>
+                            <select class="border-2 border-slate-200 rounded-md" onchange={review}>
+                                <option value="" selected={target.outcome.is_none()}>{"Not reviewed"}</option>
+                                {OUTCOMES.iter().map(|(value, label)| html! {
+                                    <option value={*value} selected={target.outcome.as_deref() == Some(*value)}>{*label}</option>
+                                }).collect::<Html>()}
+                            </select>
+                            if let Some(reviewed_on) = target.reviewed_on {
+                                <span class="text-slate-500">{format!(" on {}", reviewed_on.format("%d/%m/%Y"))}</span>
+                            }
+                            if let Some(notes) = &target.review_notes {
+                                <p>{notes}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct TargetsPanelProps {
+    pub pupil_id: Uuid,
+}
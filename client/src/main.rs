@@ -2,13 +2,30 @@
 mod macros;
 #[macro_use]
 mod error;
+mod aln;
+mod analytics;
 mod app;
+mod assessments;
+mod attachments;
+mod attendance;
+mod census;
+mod comments;
+mod concerns;
 mod constant;
+mod contacts;
+mod curriculum;
+mod eal;
 mod elements;
+mod flags;
+mod interventions;
 mod login;
+mod medical;
 mod menu;
 mod navbar;
+mod photos;
 mod pupils;
+mod quality;
+mod reports;
 mod routes;
 mod search;
 mod users;
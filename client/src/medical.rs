@@ -0,0 +1,6 @@
+mod page;
+mod panel;
+mod record;
+
+pub use page::AllergyReportPage;
+pub use panel::MedicalPanel;
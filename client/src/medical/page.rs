@@ -0,0 +1,91 @@
+use super::record::*;
+use crate::{app::AppContext, error::*};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Allergies and dietary needs for one of the user's years, worst allergy first, for lunch and
+/// trip lists
+#[function_component(AllergyReportPage)]
+pub fn allergy_report_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN ALLERGY REPORT PAGE");
+    let year = use_state_eq(|| {
+        ctx.current_user
+            .years
+            .first()
+            .map(|y| *y as i32)
+            .unwrap_or_default()
+    });
+    let rows: UseStateHandle<Vec<AllergyRow>> = use_state_eq(Vec::new);
+    {
+        clone!(ctx, rows);
+        use_effect_with_deps(
+            move |year: &i32| {
+                let year = *year;
+                spawn_local(async move {
+                    match fetch_allergy_report(year, &ctx.auth_token).await {
+                        Ok(fetched) => rows.set(fetched),
+                        Err(error) => {
+                            error!("failed to get the allergy report:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            *year,
+        );
+    }
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white">
+            <div class="flex gap-2 items-center mb-2">
+                <h2 class="text-xl">{"Allergies and diets"}</h2>
+                <select id="allergy_year" class="border-2 border-slate-200 rounded-md" onchange={
+                    clone!(year);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        if let Ok(value) = target.value().parse() {
+                            year.set(value);
+                        }
+                    })
+                }>
+                    {ctx.current_user.years.iter().map(|y| html! {
+                        <option value={y.to_string()} selected={*year == *y as i32}>{format!("Year {y}")}</option>
+                    }).collect::<Html>()}
+                </select>
+            </div>
+            <table class="w-full text-sm text-left">
+                <thead>
+                    <tr>
+                        <th>{"Pupil"}</th>
+                        <th>{"Allergies"}</th>
+                        <th>{"Diet"}</th>
+                        <th>{"Carries medication"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {rows.iter().map(|row| html! {
+                        <tr key={row.pupil_id.to_string()} class="border-t border-slate-200">
+                            <td>{format!("{} {}", row.first_names, row.last_name)}</td>
+                            <td>
+                                {row.allergies.iter().map(|allergen| html! {
+                                    <p class={classes!((allergen.severity == "anaphylaxis").then_some("text-red-500 font-bold"))} title={allergen.details.clone()}>
+                                        {format!("{} ({})", allergen.name, label(&SEVERITIES, &allergen.severity))}
+                                    </p>
+                                }).collect::<Html>()}
+                            </td>
+                            <td>{row.diets.join(", ")}</td>
+                            <td>{if row.carries_medication { "Yes" } else { "" }}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+            if rows.is_empty() {
+                <p>{"No one in this year has an allergy or dietary need recorded"}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,172 @@
+use super::record::*;
+use crate::{app::AppContext, elements::IconButton};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// A pupil's medical record for medical staff. Adding or removing an allergy or carried
+/// medication changes the alerts on the pupil, so the parent is told to refresh.
+#[function_component(MedicalPanel)]
+pub fn medical_panel(props: &MedicalPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN MEDICAL PANEL");
+    let items: UseStateHandle<Vec<MedicalItem>> = use_state_eq(Vec::new);
+    let new_item = use_state_eq(NewMedicalItem::default);
+    let adding = use_state_eq(|| false);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, items);
+        Callback::from(move |_: ()| {
+            clone!(ctx, items);
+            spawn_local(async move {
+                match fetch_record(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => items.set(fetched),
+                    Err(error) => error!("failed to get medical record:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+    let changed = {
+        clone!(refresh);
+        let alerts_changed = props.alerts_changed.clone();
+        Callback::from(move |_: ()| {
+            refresh.emit(());
+            alerts_changed.emit(());
+        })
+    };
+
+    let update = {
+        clone!(new_item);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut new = (*new_item).clone();
+            match target.id().as_str() {
+                "medical_kind" => {
+                    new.kind = target.value();
+                    new.severity = (new.kind == "allergy").then(|| SEVERITIES[3].0.to_owned());
+                    new.carried = new.carried && new.kind == "medication";
+                }
+                "medical_name" => new.name = target.value(),
+                "medical_severity" => new.severity = Some(target.value()),
+                "medical_details" => new.details = target.value(),
+                "medical_carried" => new.carried = target.checked(),
+                "medical_review_date" => new.review_date = target.value().parse().ok(),
+                _ => {}
+            }
+            new_item.set(new);
+        })
+    };
+    let add = {
+        clone!(ctx, new_item, adding, errors, changed);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, new_item, adding, errors, changed);
+            spawn_local(async move {
+                match create_item(&pupil_id, &new_item, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        new_item.set(NewMedicalItem::default());
+                        adding.set(false);
+                        changed.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to add to medical record:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <div class="flex justify-between items-center">
+                <h3 class="text-md">{"Medical"}</h3>
+                <IconButton icon={if *adding { "close" } else { "add" }} onclick={
+                    clone!(adding);
+                    Callback::from(move |_| adding.set(!*adding))
+                } />
+            </div>
+            if *adding {
+                <div class="flex flex-col gap-1 text-sm">
+                    <select id="medical_kind" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                        {KINDS.iter().map(|(kind, text)| html! {
+                            <option value={*kind} selected={new_item.kind == *kind}>{*text}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    <input type="text" id="medical_name" placeholder="Name" class="border-2 border-slate-200 rounded-md" value={new_item.name.clone()} onchange={&update}/>
+                    if new_item.kind == "allergy" {
+                        <select id="medical_severity" class="border-2 border-slate-200 rounded-md" onchange={&update}>
+                            {SEVERITIES.iter().map(|(severity, text)| html! {
+                                <option value={*severity} selected={new_item.severity.as_deref() == Some(*severity)}>{*text}</option>
+                            }).collect::<Html>()}
+                        </select>
+                    }
+                    <input type="text" id="medical_details" placeholder="Details" class="border-2 border-slate-200 rounded-md" value={new_item.details.clone()} onchange={&update}/>
+                    if new_item.kind == "medication" {
+                        <label class="flex gap-1 items-center text-xs" for="medical_carried">
+                            <input type="checkbox" id="medical_carried" checked={new_item.carried} onchange={&update}/>
+                            {"Carried by the pupil"}
+                        </label>
+                    }
+                    <div class="flex justify-between items-center gap-1">
+                        <label for="medical_review_date">{"Review on"}</label>
+                        <input type="date" id="medical_review_date" class="border-2 border-slate-200 rounded-md" value={new_item.review_date.map(|date| date.to_string()).unwrap_or_default()} onchange={&update}/>
+                        <IconButton onclick={&add} icon="save" />
+                    </div>
+                    {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+                </div>
+            }
+            <ul class="flex flex-col gap-1 text-xs">
+                {items.iter().map(|item| {
+                    let id = item.id;
+                    let remove = {
+                        clone!(ctx, changed);
+                        Callback::from(move |_| {
+                            clone!(ctx, changed);
+                            spawn_local(async move {
+                                match delete_item(&pupil_id, &id, &ctx.auth_token).await {
+                                    Ok(_) => changed.emit(()),
+                                    Err(error) => error!("failed to remove from medical record:", error.to_string()),
+                                }
+                            });
+                        })
+                    };
+                    let severe = matches!(item.severity.as_deref(), Some("severe" | "anaphylaxis"));
+                    html! {
+                        <li key={id.to_string()} class={classes!("border-l-4", "pl-2", if severe { "border-red-500" } else { "border-slate-300" })}>
+                            <div class="flex justify-between items-center">
+                                <span class="text-sm">{format!("{}: {}", label(&KINDS, &item.kind), item.name)}</span>
+                                <IconButton icon="delete" onclick={remove} />
+                            </div>
+                            <p class="text-slate-500">
+                                if let Some(severity) = &item.severity {
+                                    {label(&SEVERITIES, severity)}
+                                }
+                                if item.carried {
+                                    {" · carried by the pupil"}
+                                }
+                                if let Some(date) = item.review_date {
+                                    {format!(" · review {}", date.format("%d/%m/%Y"))}
+                                }
+                            </p>
+                            if !item.details.is_empty() {
+                                <p>{&item.details}</p>
+                            }
+                        </li>
+                    }
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct MedicalPanelProps {
+    pub pupil_id: Uuid,
+    pub alerts_changed: Callback<()>,
+}
@@ -0,0 +1,147 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static KINDS: [(&str, &str); 5] = [
+    ("condition", "Condition"),
+    ("allergy", "Allergy"),
+    ("medication", "Medication"),
+    ("care_plan", "Care plan"),
+    ("diet", "Dietary need"),
+];
+pub static SEVERITIES: [(&str, &str); 4] = [
+    ("mild", "Mild"),
+    ("moderate", "Moderate"),
+    ("severe", "Severe"),
+    ("anaphylaxis", "Anaphylaxis"),
+];
+
+/// The label for a value from one of the lists above, or the value itself if it isn't in it
+pub fn label<'a>(options: &[(&str, &'a str)], value: &'a str) -> &'a str {
+    options
+        .iter()
+        .find(|(option, _)| *option == value)
+        .map_or(value, |(_, label)| label)
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct MedicalItem {
+    pub id: Uuid,
+    pub kind: String,
+    pub name: String,
+    #[serde(default)]
+    pub severity: Option<String>,
+    pub details: String,
+    pub carried: bool,
+    #[serde(default)]
+    pub review_date: Option<NaiveDate>,
+    pub updated_by: String,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewMedicalItem {
+    pub kind: String,
+    pub name: String,
+    pub severity: Option<String>,
+    pub details: String,
+    pub carried: bool,
+    pub review_date: Option<NaiveDate>,
+}
+
+impl Default for NewMedicalItem {
+    fn default() -> Self {
+        Self {
+            kind: KINDS[0].0.to_owned(),
+            name: String::new(),
+            severity: None,
+            details: String::new(),
+            carried: false,
+            review_date: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Allergen {
+    pub name: String,
+    pub severity: String,
+    #[serde(default)]
+    pub details: String,
+}
+
+/// A row of the allergy report, what's needed at lunch or on a trip
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct AllergyRow {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub allergies: Vec<Allergen>,
+    pub diets: Vec<String>,
+    pub carries_medication: bool,
+}
+
+fn medical_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/medical", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_record(pupil_id: &Uuid, token: &str) -> Result<Vec<MedicalItem>> {
+    let response = Request::get(&medical_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<MedicalItem>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it rejected the item
+pub async fn create_item(
+    pupil_id: &Uuid,
+    item: &NewMedicalItem,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&medical_path(pupil_id))
+        .json(item)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_item(pupil_id: &Uuid, id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", medical_path(pupil_id)))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_allergy_report(year: i32, token: &str) -> Result<Vec<AllergyRow>> {
+    let response = Request::get(&format!("{}/allergies/{year}", constant::MEDICAL_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<AllergyRow>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -8,9 +8,20 @@ pub fn menu() -> Html {
         <div id="menu" class="flex flex-col justify-between bg-slate-100 h-full my-3">
             <div class="flex flex-col gap-2 p-2 mt-1">
                 <MenuItem route={Route::ManagePupils} title="Manage pupils"/>
-                <MenuItem route={Route::ManagePupils} title="General comments"/>
-                <MenuItem route={Route::ManagePupils} title="Test results"/>
-                <MenuItem route={Route::ManagePupils} title="My concern"/>
+                <MenuItem route={Route::ClassLists} title="Class lists"/>
+                <MenuItem route={Route::Comments} title="General comments"/>
+                <MenuItem route={Route::Assessments} title="Test results"/>
+                <MenuItem route={Route::Attendance} title="Attendance register"/>
+                <MenuItem route={Route::Progression} title="Progression"/>
+                <MenuItem route={Route::Interventions} title="Interventions"/>
+                <MenuItem route={Route::AlnRegister} title="ALN register"/>
+                <MenuItem route={Route::Allergies} title="Allergies and diets"/>
+                <MenuItem route={Route::Analytics} title="Cohort analytics"/>
+                <MenuItem route={Route::AttainmentGap} title="Attainment gap"/>
+                <MenuItem route={Route::Reports} title="Reports"/>
+                <MenuItem route={Route::Census} title="School census"/>
+                <MenuItem route={Route::DataQuality} title="Data quality"/>
+                <MenuItem route={Route::Concerns} title="My concern"/>
                 <MenuItem route={Route::ManageUsers} title="Manage users"/>
             </div>
 
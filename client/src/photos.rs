@@ -0,0 +1,5 @@
+mod panel;
+mod photo;
+
+pub use panel::PhotoPanel;
+pub use photo::PhotoLinks;
@@ -0,0 +1,146 @@
+use super::photo::*;
+use crate::app::AppContext;
+use base64::{engine::general_purpose, Engine};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The pupil's photo with an upload box and the photo consent switch. The server does the resizing
+/// and strips the EXIF data, so whatever the camera produced can be uploaded as it is.
+#[function_component(PhotoPanel)]
+pub fn photo_panel(props: &PhotoPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN PHOTO PANEL");
+    let photo: UseStateHandle<PupilPhoto> = use_state_eq(PupilPhoto::default);
+    let uploading = use_state_eq(|| false);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let pupil_id = props.pupil_id;
+
+    let refresh = {
+        clone!(ctx, photo);
+        Callback::from(move |_: ()| {
+            clone!(ctx, photo);
+            spawn_local(async move {
+                match fetch_photo(&pupil_id, &ctx.auth_token).await {
+                    Ok(fetched) => photo.set(fetched),
+                    Err(error) => error!("failed to get photo:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(refresh);
+        use_effect_with_deps(move |_| refresh.emit(()), props.pupil_id);
+    }
+
+    let upload = {
+        clone!(ctx, uploading, errors, refresh);
+        let photo_changed = props.photo_changed.clone();
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let Some(file) = target.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            target.set_value("");
+            let file = gloo_file::File::from(file);
+            uploading.set(true);
+            clone!(ctx, uploading, errors, refresh, photo_changed);
+            spawn_local(async move {
+                let bytes = match gloo_file::futures::read_as_bytes(&file).await {
+                    Ok(bytes) => bytes,
+                    Err(error) => {
+                        error!("failed to read photo:", error.to_string());
+                        uploading.set(false);
+                        return;
+                    }
+                };
+                let new = NewPhoto {
+                    data: general_purpose::STANDARD.encode(bytes),
+                };
+                match upload_photo(&pupil_id, &new, &ctx.auth_token).await {
+                    Ok(None) => {
+                        errors.set(HashMap::new());
+                        refresh.emit(());
+                        photo_changed.emit(());
+                    }
+                    Ok(Some(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to upload photo:", error.to_string()),
+                }
+                uploading.set(false);
+            });
+        })
+    };
+
+    let remove = {
+        clone!(ctx, refresh);
+        let photo_changed = props.photo_changed.clone();
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, refresh, photo_changed);
+            spawn_local(async move {
+                match delete_photo(&pupil_id, &ctx.auth_token).await {
+                    Ok(_) => {
+                        refresh.emit(());
+                        photo_changed.emit(());
+                    }
+                    Err(error) => error!("failed to remove photo:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let toggle_consent = {
+        clone!(ctx, photo);
+        let photo_changed = props.photo_changed.clone();
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let withdrawn = target.checked();
+            clone!(ctx, photo, photo_changed);
+            spawn_local(async move {
+                match set_consent(&pupil_id, withdrawn, &ctx.auth_token).await {
+                    Ok(updated) => {
+                        photo.set(updated);
+                        photo_changed.emit(());
+                    }
+                    Err(error) => error!("failed to change photo consent:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Photo"}</h3>
+            <div class="flex gap-3 items-start text-sm">
+                if let Some(links) = &photo.links {
+                    <img src={links.medium.clone()} alt="" class="w-[120px] rounded-md" />
+                } else {
+                    <div class="w-[120px] h-[120px] rounded-md bg-slate-100 flex items-center justify-center text-slate-400">
+                        <yew_feather::User size="48" />
+                    </div>
+                }
+                <div class="flex flex-col gap-1">
+                    <input type="file" id="photo_file" accept={ACCEPTED_TYPES} class="text-xs" disabled={*uploading} onchange={upload}/>
+                    {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+                    if photo.uploaded_at.is_some() {
+                        <button class="text-xs underline text-left" onclick={remove}>{"Remove photo"}</button>
+                    }
+                    <label class="flex items-center gap-1 text-xs">
+                        <input type="checkbox" id="photo_consent_withdrawn" checked={photo.consent_withdrawn} onchange={toggle_consent}/>
+                        {"Photo consent withdrawn"}
+                    </label>
+                    if photo.consent_withdrawn && photo.uploaded_at.is_some() {
+                        <p class="text-xs text-slate-500">{"The photo is kept but not shown anywhere"}</p>
+                    }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PhotoPanelProps {
+    pub pupil_id: Uuid,
+    /// so lists can pick up the new thumbnail
+    pub photo_changed: Callback<()>,
+}
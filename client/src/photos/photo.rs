@@ -0,0 +1,97 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::NaiveDateTime;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub static ACCEPTED_TYPES: &str = ".jpg,.jpeg,.png";
+
+/// Signed links to the two sizes the server keeps. They only last a few minutes, so they come
+/// fresh with each fetch of the pupil rather than being stored.
+#[derive(Deserialize, Clone, PartialEq, Eq, Debug, PartialOrd, Ord)]
+pub struct PhotoLinks {
+    pub thumbnail: String,
+    pub medium: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct PupilPhoto {
+    pub uploaded_by: Option<String>,
+    pub uploaded_at: Option<NaiveDateTime>,
+    pub consent_withdrawn: bool,
+    pub consent_updated_by: Option<String>,
+    pub consent_updated_at: Option<NaiveDateTime>,
+    /// only sent when the photo can be shown
+    pub links: Option<PhotoLinks>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct NewPhoto {
+    /// the image, base64 encoded
+    pub data: String,
+}
+
+fn photo_path(pupil_id: &Uuid) -> String {
+    format!("{}/{pupil_id}/photo", constant::PUPILS_PATH)
+}
+
+pub async fn fetch_photo(pupil_id: &Uuid, token: &str) -> Result<PupilPhoto> {
+    let response = Request::get(&photo_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<PupilPhoto>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it couldn't use the image
+pub async fn upload_photo(
+    pupil_id: &Uuid,
+    upload: &NewPhoto,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(&photo_path(pupil_id))
+        .json(upload)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_photo(pupil_id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&photo_path(pupil_id))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn set_consent(pupil_id: &Uuid, withdrawn: bool, token: &str) -> Result<PupilPhoto> {
+    let response = Request::post(&format!("{}/consent", photo_path(pupil_id)))
+        .json(&json!({ "withdrawn": withdrawn }))?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<PupilPhoto>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
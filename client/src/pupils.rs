@@ -1,16 +1,28 @@
+mod class_list;
 mod create_box;
+mod ctf_box;
 mod details;
+mod export_box;
+mod import_box;
 mod input_state;
+mod leaver_ctf;
 mod pupil;
 mod row;
+mod subject_access;
 mod table;
 mod types;
 mod filter;
 
+pub use class_list::ClassListPage;
 pub use details::PupilDetails;
 pub use input_state::InputState as PupilInputState;
-pub use pupil::Pupil;
+pub use pupil::{eal_tag, Pupil};
 pub use table::PupilTable;
 pub use filter::{Filter as PupilFilter, TableFilter as PupilTableFilter};
 pub use create_box::PupilCreateBox;
+pub use ctf_box::CtfImportBox;
+pub use export_box::PupilExportBox;
+pub use import_box::PupilImportBox;
+pub use leaver_ctf::LeaverCtfPanel;
 pub use row::PupilRow;
+pub use subject_access::SubjectAccessPanel;
@@ -0,0 +1,102 @@
+use super::pupil::Pupil;
+use crate::{app::AppContext, constant, elements::Button, error::*};
+use gloo_net::http::Request;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// A printable list of the active pupils in one of the user's years, with photos so a supply
+/// teacher can put faces to names. Pupils without a photo, or whose consent is withdrawn, get a
+/// blank space instead.
+#[function_component(ClassListPage)]
+pub fn class_list_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CLASS LIST PAGE");
+    let year = use_state_eq(|| {
+        ctx.current_user
+            .years
+            .first()
+            .map(|y| *y as i32)
+            .unwrap_or_default()
+    });
+    let pupils: UseStateHandle<Vec<Pupil>> = use_state_eq(Vec::new);
+    {
+        clone!(ctx, pupils);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_pupils(&ctx.auth_token).await {
+                        Ok(fetched) => pupils.set(fetched),
+                        Err(error) => {
+                            error!(
+                                "failed to get pupils for the class list:",
+                                error.to_string()
+                            );
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (),
+        );
+    }
+    let print = Callback::from(|_| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.print();
+        }
+    });
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white print:shadow-none print:m-0">
+            <div class="flex gap-2 items-center mb-2">
+                <h2 class="text-xl">{format!("Year {}", *year)}</h2>
+                <select id="class_list_year" class="border-2 border-slate-200 rounded-md print:hidden" onchange={
+                    clone!(year);
+                    Callback::from(move |ev: Event| {
+                        let target: HtmlInputElement = ev.target_unchecked_into();
+                        if let Ok(value) = target.value().parse() {
+                            year.set(value);
+                        }
+                    })
+                }>
+                    {ctx.current_user.years.iter().map(|y| html! {
+                        <option value={y.to_string()} selected={*year == *y as i32}>{format!("Year {y}")}</option>
+                    }).collect::<Html>()}
+                </select>
+                <div class="print:hidden">
+                    <Button icon={html!(<yew_feather::Printer size="16" />)} text="Print" color="blue" onclick={print} />
+                </div>
+            </div>
+            <ul class="grid grid-cols-4 lg:grid-cols-6 gap-3">
+                {pupils.iter().filter(|p| p.active && p.year == *year).map(|pupil| html! {
+                    <li key={pupil.id.map(|id| id.to_string()).unwrap_or_default()} class="flex flex-col items-center gap-1 break-inside-avoid">
+                        if let Some(photo) = &pupil.photo {
+                            <img src={photo.medium.clone()} alt="" class="w-[96px] h-[96px] object-cover rounded-md" />
+                        } else {
+                            <div class="w-[96px] h-[96px] rounded-md bg-slate-100" />
+                        }
+                        <span class="text-sm text-center">{pupil.display_name()}</span>
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+async fn fetch_pupils(token: &str) -> Result<Vec<Pupil>> {
+    let response = Request::get(constant::PUPILS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => {
+            let mut pupils = response.json::<Vec<Pupil>>().await?;
+            pupils.sort();
+            Ok(pupils)
+        }
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -4,11 +4,11 @@ use crate::{
     clone, constant,
     elements::{Button, IconButton, PupilTags, EditableField},
     error,
-    error::Result,
+    error::{ErrorResponse, Result},
     pupils::PupilInputState,
 };
 use gloo_net::http::Request;
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
@@ -37,13 +37,24 @@ pub fn pupil_create_box(props: &PupilCreateBoxProps) -> Html {
         clone!(input_state, ctx);
         Callback::from(move |_| {
             clone!(input_state, ctx, refresh_callback);
+            if input_state.has_errors() {
+                return;
+            }
             let pupil = Pupil::from(&(*input_state));
             spawn_local(async move {
-                if let Err(error) = create_pupil(&pupil, ctx.as_ref(), refresh_callback).await {
-                    error!("failed to create a new pupil:", error.to_string());
+                match create_pupil(&pupil, ctx.as_ref()).await {
+                    Ok(None) => {
+                        refresh_callback.emit(true);
+                        input_state.set(PupilInputState::default());
+                    }
+                    Ok(Some(fields)) => {
+                        let mut state = (*input_state).clone();
+                        state.set_server_errors(fields);
+                        input_state.set(state);
+                    }
+                    Err(error) => error!("failed to create a new pupil:", error.to_string()),
                 }
             });
-            input_state.set(PupilInputState::default());
         })
     };
 
@@ -63,14 +74,35 @@ pub fn pupil_create_box(props: &PupilCreateBoxProps) -> Html {
                 <span class="text-3xl">{"Add a learner"}</span>
                 <IconButton icon="close" onclick={props.close_callback.clone()}/>
             </div>
-            <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="name" input_type="text" edit_mode={true} value={(*input_state).name.to_string()} onchange={&update_state_cb}/>
+            <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="name" input_type="text" edit_mode={true} value={(*input_state).name.to_string()} onchange={&update_state_cb} error={input_state.error("name")}/>
+            <div class="flex justify-between items-center hover:bg-slate-200">
+                <label><span>{"Preferred name"}</span></label>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="preferred_name" input_type="text" edit_mode={true} value={(*input_state).preferred_name.clone()} onchange={&update_state_cb} error={input_state.error("preferred_name")}/>
+            </div>
             <div class="flex justify-between">
-                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="gender" input_type="text" edit_mode={true} value={(*input_state).gender.to_string()} onchange={&update_state_cb}/>
-                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="year" input_type="number" edit_mode={true} value={(*input_state).year.to_string()} onchange={&update_state_cb}/>
+                <div class="flex flex-col">
+                    <GenderSelect value={(*input_state).gender.to_string()} onchange={&update_state_cb}/>
+                    if let Some(error) = input_state.error("gender") {
+                        <span class="text-xs text-red-500">{error}</span>
+                    }
+                </div>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="year" input_type="number" edit_mode={true} value={(*input_state).year.to_string()} onchange={&update_state_cb} error={input_state.error("year")}/>
+            </div>
+            <div class="flex justify-between items-center hover:bg-slate-200">
+                <label><span>{"Date of birth"}</span></label>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="date_of_birth" input_type="date" edit_mode={true} value={(*input_state).date_of_birth.map(|d| d.to_string()).unwrap_or_default()} onchange={&update_state_cb} error={input_state.error("date_of_birth")}/>
+            </div>
+            <div class="flex justify-between items-center hover:bg-slate-200">
+                <label><span>{"UPN"}</span></label>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="upn" input_type="text" edit_mode={true} value={(*input_state).upn.clone()} onchange={&update_state_cb} error={input_state.error("upn")}/>
+            </div>
+            <div class="flex justify-between items-center hover:bg-slate-200">
+                <label><span>{"Home language"}</span></label>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="home_language" input_type="text" edit_mode={true} value={(*input_state).home_language.clone()} onchange={&update_state_cb} error={input_state.error("home_language")}/>
             </div>
             <div class="flex justify-between items-center hover:bg-slate-200">
                 <label><span>{"Start date"}</span></label>
-                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="start_date" input_type="date" edit_mode={true} value={(*input_state).start_date.to_string()} onchange={&update_state_cb}/>
+                <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="start_date" input_type="date" edit_mode={true} value={(*input_state).start_date.to_string()} onchange={&update_state_cb} error={input_state.error("start_date")}/>
             </div>
             <div class="flex justify-between items-center hover:bg-slate-200">
                 <label for="active"><span>{"Active?"}</span></label>
@@ -80,7 +112,7 @@ pub fn pupil_create_box(props: &PupilCreateBoxProps) -> Html {
                 html!{
                     <div class="flex justify-between items-center hover:bg-slate-200">
                         <label><span>{"Leave date"}</span></label>
-                        <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="leave_date" input_type="date" edit_mode={true} value={(*input_state).leave_date.expect("inactive pupil should have leave date").to_string()} onchange={&update_state_cb}/>
+                        <EditableField class="hover:bg-slate-100 focus:outline-none w-36 my-2" id="leave_date" input_type="date" edit_mode={true} value={(*input_state).leave_date.map(|d| d.to_string()).unwrap_or_default()} onchange={&update_state_cb} error={input_state.error("leave_date")}/>
                     </div>
                 }
             } else {
@@ -89,6 +121,9 @@ pub fn pupil_create_box(props: &PupilCreateBoxProps) -> Html {
             <div class="my-3">
                 <PupilTags state={(*input_state).clone()} edit_mode=true onchange={&update_state_cb}/>
             </div>
+            if let Some(error) = input_state.error("form") {
+                <p class="text-xs text-red-500 my-2">{error}</p>
+            }
 
             <div class="flex justify-between">
                 <Button icon={html!(<yew_feather::RefreshCcw size="16" />)} color="yellow" onclick={reset_callback} text="Reset"/>
@@ -98,17 +133,35 @@ pub fn pupil_create_box(props: &PupilCreateBoxProps) -> Html {
     }
 }
 
-async fn create_pupil(
-    pupil: &Pupil,
-    ctx: &AppContext,
-    refresh_callback: Callback<bool>,
-) -> Result<()> {
-    Request::put(constant::PUPILS_PATH)
-        .json(&pupil)
-        .expect("TODO this should be able to convert into our error")
+#[derive(PartialEq, Properties)]
+pub struct GenderSelectProps {
+    pub value: String,
+    pub onchange: Callback<Event>,
+}
+
+#[function_component(GenderSelect)]
+pub fn gender_select(props: &GenderSelectProps) -> Html {
+    html! {
+        <select id="gender" class="border-2 border-slate-200 rounded-md w-36 my-2" onchange={props.onchange.clone()}>
+            <option value="" selected={props.value.is_empty()} disabled=true>{"Gender"}</option>
+            {constant::GENDERS.iter().map(|gender| html! {
+                <option value={gender.to_string()} selected={props.value == *gender}>{gender}</option>
+            }).collect::<Html>()}
+        </select>
+    }
+}
+
+/// Returns the server's field errors if it rejected the pupil
+async fn create_pupil(pupil: &Pupil, ctx: &AppContext) -> Result<Option<HashMap<String, String>>> {
+    let response = Request::put(constant::PUPILS_PATH)
+        .json(&pupil)?
         .header("Authorization", &format!("Bearer {}", ctx.auth_token))
         .send()
         .await?;
-    refresh_callback.emit(true);
-    Ok(())
+    match response.status() {
+        201 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
 }
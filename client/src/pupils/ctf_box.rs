@@ -0,0 +1,284 @@
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, IconButton},
+    error::{ErrorResponse, Result},
+};
+use base64::{engine::general_purpose, Engine};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+struct CtfImport {
+    /// the file, base64 encoded
+    data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_date: Option<NaiveDate>,
+    dry_run: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct School {
+    lea: String,
+    estab: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct Match {
+    kind: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct ContactReport {
+    name: String,
+    relationship: String,
+    action: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct YearData {
+    year: i32,
+    sessions_possible: u32,
+    sessions_authorised: u32,
+    sessions_unauthorised: u32,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct StageResult {
+    stage: String,
+    subject: String,
+    result: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct PupilReport {
+    index: usize,
+    #[serde(default)]
+    upn: Option<String>,
+    name: String,
+    #[serde(default)]
+    date_of_birth: Option<NaiveDate>,
+    matched: Match,
+    action: String,
+    #[serde(default)]
+    changes: Vec<String>,
+    #[serde(default)]
+    errors: BTreeMap<String, String>,
+    contacts: Vec<ContactReport>,
+    #[serde(default)]
+    warnings: Vec<String>,
+    attendance: Vec<YearData>,
+    assessments: Vec<StageResult>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct CtfReport {
+    source: School,
+    pupils: Vec<PupilReport>,
+    creates: usize,
+    updates: usize,
+    unchanged: usize,
+    errors: usize,
+    committed: bool,
+}
+
+/// Bring in a common transfer file from another school. Picking a file shows who each pupil in it
+/// matches here and what would change, and the import only goes ahead once every pupil is clean.
+#[function_component(CtfImportBox)]
+pub fn ctf_import_box(props: &CtfImportBoxProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN CTF BOX");
+    let import: UseStateHandle<Option<CtfImport>> = use_state_eq(|| None);
+    let report: UseStateHandle<Option<CtfReport>> = use_state_eq(|| None);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+
+    let send = {
+        clone!(ctx, import, report, errors);
+        let refresh = props.refresh_callback.clone();
+        Callback::from(move |next: CtfImport| {
+            import.set(Some(CtfImport {
+                dry_run: true,
+                ..next.clone()
+            }));
+            clone!(ctx, report, errors, refresh);
+            spawn_local(async move {
+                match run_import(&next, &ctx.auth_token).await {
+                    Ok(Ok(fetched)) => {
+                        errors.set(HashMap::new());
+                        if fetched.committed {
+                            refresh.emit(true);
+                        }
+                        report.set(Some(fetched));
+                    }
+                    Ok(Err(fields)) => {
+                        report.set(None);
+                        errors.set(fields);
+                    }
+                    Err(error) => error!("failed to import CTF:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let choose_file = {
+        clone!(import, send);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let Some(file) = target.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let file = gloo_file::File::from(file);
+            let start_date = import.as_ref().and_then(|i| i.start_date);
+            clone!(send);
+            spawn_local(async move {
+                match gloo_file::futures::read_as_bytes(&file).await {
+                    Ok(bytes) => send.emit(CtfImport {
+                        data: general_purpose::STANDARD.encode(bytes),
+                        start_date,
+                        dry_run: true,
+                    }),
+                    Err(error) => error!("failed to read CTF:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let choose_start_date = {
+        clone!(import, send);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let start_date = NaiveDate::parse_from_str(&target.value(), "%Y-%m-%d").ok();
+            if let Some(current) = (*import).clone() {
+                send.emit(CtfImport {
+                    start_date,
+                    ..current
+                });
+            }
+        })
+    };
+
+    let commit = {
+        clone!(import, send);
+        Callback::from(move |_: MouseEvent| {
+            if let Some(current) = (*import).clone() {
+                send.emit(CtfImport {
+                    dry_run: false,
+                    ..current
+                });
+            }
+        })
+    };
+
+    let ready = report
+        .as_ref()
+        .is_some_and(|r| !r.committed && r.errors == 0 && r.creates + r.updates > 0);
+
+    html! {
+        <div class="flex flex-col gap-3 w-[900px] max-h-[80vh] p-3">
+            <div class="flex justify-between">
+                <span class="text-2xl">{"Import a CTF"}</span>
+                <IconButton icon="close" onclick={&props.close_callback} />
+            </div>
+            <p class="text-sm text-slate-500">{"A common transfer file from another school. Pupils are matched on their UPN, or their name and date of birth, and new contacts are added. Attendance and results are shown for reference but not saved."}</p>
+            <div class="flex gap-3 items-center text-sm">
+                <input type="file" id="ctf_file" accept=".xml" onchange={choose_file}/>
+                <label class="flex items-center gap-1">
+                    {"New pupils start on"}
+                    <input type="date" id="ctf_start_date" class="border-2 border-slate-200 rounded-md" onchange={choose_start_date}/>
+                </label>
+            </div>
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            if let Some(report) = &*report {
+                <p class="text-sm">
+                    {format!("From {}{} {}", report.source.lea, report.source.estab, report.source.name.clone().unwrap_or_default())}
+                </p>
+                <p class="text-sm">
+                    if report.committed {
+                        {format!("Imported: {} created, {} updated, {} unchanged", report.creates, report.updates, report.unchanged)}
+                    } else {
+                        {format!("{} to create, {} to update, {} unchanged, {} with errors", report.creates, report.updates, report.unchanged, report.errors)}
+                    }
+                </p>
+                <div class="overflow-y-auto scrollbar">
+                    <table class="w-full text-xs">
+                        <thead>
+                            <tr class="text-left">
+                                <th>{"#"}</th><th>{"UPN"}</th><th>{"Name"}</th><th>{"Date of birth"}</th><th>{"Matches"}</th><th>{"Action"}</th><th>{"Details"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.pupils.iter().map(|pupil| html! {
+                                <tr key={pupil.index.to_string()} class={classes!("align-top", (pupil.action == "error").then_some("text-red-500"))}>
+                                    <td>{pupil.index.to_string()}</td>
+                                    <td>{pupil.upn.clone().unwrap_or_default()}</td>
+                                    <td>{&pupil.name}</td>
+                                    <td>{pupil.date_of_birth.map(|d| d.format("%d/%m/%Y").to_string()).unwrap_or_default()}</td>
+                                    <td>
+                                        {match pupil.matched.kind.as_str() {
+                                            "upn" => format!("{} (UPN)", pupil.matched.name.clone().unwrap_or_default()),
+                                            "name_and_date_of_birth" => format!("{} (name and date of birth)", pupil.matched.name.clone().unwrap_or_default()),
+                                            _ => "new pupil".to_owned(),
+                                        }}
+                                    </td>
+                                    <td>{&pupil.action}</td>
+                                    <td>
+                                        {pupil.errors.iter().map(|(field, error)| html!(<p>{format!("{field}: {error}")}</p>)).collect::<Html>()}
+                                        if !pupil.changes.is_empty() {
+                                            <p>{format!("changes {}", pupil.changes.join(", ").replace('_', " "))}</p>
+                                        }
+                                        {pupil.contacts.iter().filter(|c| c.action == "create").map(|c| html!(<p>{format!("adds {} ({})", c.name, c.relationship.replace('_', " "))}</p>)).collect::<Html>()}
+                                        {pupil.warnings.iter().map(|w| html!(<p class="text-amber-600">{w}</p>)).collect::<Html>()}
+                                        {pupil.attendance.iter().map(|year| html! {
+                                            <p class="text-slate-500">{format!("{}/{}: {} sessions, {} authorised and {} unauthorised absences", year.year, (year.year + 1) % 100, year.sessions_possible, year.sessions_authorised, year.sessions_unauthorised)}</p>
+                                        }).collect::<Html>()}
+                                        {pupil.assessments.iter().map(|result| html! {
+                                            <p class="text-slate-500">{format!("{} {}: {}", result.stage, result.subject, result.result)}</p>
+                                        }).collect::<Html>()}
+                                    </td>
+                                </tr>
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                </div>
+            }
+            if ready {
+                <Button color="green" text="Import" onclick={commit} />
+            }
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CtfImportBoxProps {
+    pub refresh_callback: Callback<bool>,
+    pub close_callback: Callback<MouseEvent>,
+}
+
+/// Returns the server's field errors if it couldn't read the file
+async fn run_import(
+    import: &CtfImport,
+    token: &str,
+) -> Result<std::result::Result<CtfReport, HashMap<String, String>>> {
+    let response = Request::post(&format!("{}/ctf", constant::PUPILS_PATH))
+        .json(import)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<CtfReport>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -1,12 +1,24 @@
-use super::pupil::Pupil;
+use super::{create_box::GenderSelect, pupil::Pupil};
 use crate::{
+    aln::AlnPanel,
     app::AppContext,
+    attachments::AttachmentsPanel,
+    attendance::AttendanceLine,
+    comments::CommentTimeline,
     constant,
+    contacts::ContactsPanel,
+    curriculum::ProgressionPanel,
+    eal::EalPanel,
     elements::{Button, EditableField, IconButton, PupilTags},
-    pupils::PupilInputState,
+    error::{ErrorResponse, Result},
+    flags::FlagHistoryPanel,
+    interventions::{InterventionsPanel, TargetsPanel},
+    medical::MedicalPanel,
+    photos::PhotoPanel,
+    pupils::{LeaverCtfPanel, PupilInputState, SubjectAccessPanel},
 };
 use gloo_net::http::Request;
-use std::{rc::Rc, str::FromStr};
+use std::{collections::HashMap, rc::Rc, str::FromStr};
 use uuid::Uuid;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
@@ -38,86 +50,190 @@ pub fn pupil_details(props: &PupilDetailsProps) -> Html {
         };
 
         html! {
-            <div class="w-[450px] h-[240px] flex flex-col">
-                <div class="flex justify-between mb-3">
-                    <EditableField id="name" class={Some("text-2xl")} input_type="text" edit_mode={*edit_mode} value={(*input_state).name.to_string()} onchange={&update_state_cb}/>
-                    <IconButton onclick={&props.close_callback} icon="close" />
-                </div>
-                <div class="flex flex-col justify-between h-full">
-                    <div class="flex flex-col h-full">
-                        <ul class="flex flex-col justify-between h-full">
-                            <li class="flex justify-between">
-                                <span class="text-bold w-[120px]">{"Year"}</span>
-                                <EditableField id="year" input_type="number" edit_mode={*edit_mode} value={(*input_state).year.to_string()} onchange={&update_state_cb}/>
-                            </li>
-                            <li class="flex justify-between">
-                                <span class="text-bold w-[120px]">{"Gender"}</span>
-                                <EditableField id="gender" input_type="text" edit_mode={*edit_mode} value={(*input_state).gender.clone()} onchange={&update_state_cb}/>
-                            </li>
-                            <li class="flex justify-between">
-                                <span class="text-bold w-[120px]">{"Start date"}</span>
-                                <EditableField id="start_date" input_type="date" edit_mode={*edit_mode} value={(*input_state).start_date.to_string()} onchange={&update_state_cb}/>
-                            </li>
-                            <li class="flex justify-between">
-                                <label for="active"><span>{"Active?"}</span></label>
-                                <input type="checkbox" id="active" checked={(*input_state).active} onchange={&update_state_cb} disabled={!(*edit_mode)}/>
-                            </li>
-                            {if !(*input_state).active {
-                                html!{
-                                    <li class="flex justify-between">
-                                        <span class="text-bold w-[120px]">{"Leave date"}</span>
-                                        <EditableField id="leave_date" input_type="date" edit_mode={*edit_mode} value={(*input_state).leave_date.expect("inactive pupil should have leave date").to_string()} onchange={&update_state_cb}/>
+            <div class="h-[400px] flex gap-4">
+                <div class="w-[450px] flex flex-col">
+                    <div class="flex justify-between mb-3">
+                        <EditableField id="name" class={Some("text-2xl")} input_type="text" edit_mode={*edit_mode} value={(*input_state).name.to_string()} onchange={&update_state_cb} error={input_state.error("name")}/>
+                        <IconButton onclick={&props.close_callback} icon="close" />
+                    </div>
+                    <div class="flex flex-col justify-between h-full">
+                        <div class="flex flex-col h-full">
+                            <ul class="flex flex-col justify-between h-full">
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Preferred name"}</span>
+                                    <EditableField id="preferred_name" input_type="text" edit_mode={*edit_mode} value={(*input_state).preferred_name.clone()} onchange={&update_state_cb} error={input_state.error("preferred_name")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Date of birth"}</span>
+                                    <EditableField id="date_of_birth" input_type="date" edit_mode={*edit_mode} value={(*input_state).date_of_birth.map(|d| d.to_string()).unwrap_or_default()} onchange={&update_state_cb} error={input_state.error("date_of_birth")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"UPN"}</span>
+                                    <EditableField id="upn" input_type="text" edit_mode={*edit_mode} value={(*input_state).upn.clone()} onchange={&update_state_cb} error={input_state.error("upn")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Home language"}</span>
+                                    <EditableField id="home_language" input_type="text" edit_mode={*edit_mode} value={(*input_state).home_language.clone()} onchange={&update_state_cb} error={input_state.error("home_language")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Year"}</span>
+                                    <EditableField id="year" input_type="number" edit_mode={*edit_mode} value={(*input_state).year.to_string()} onchange={&update_state_cb} error={input_state.error("year")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Gender"}</span>
+                                    if *edit_mode {
+                                        <div class="flex flex-col">
+                                            <GenderSelect value={(*input_state).gender.clone()} onchange={&update_state_cb}/>
+                                            if let Some(error) = input_state.error("gender") {
+                                                <span class="text-xs text-red-500">{error}</span>
+                                            }
+                                        </div>
+                                    } else {
+                                        <span>{(*input_state).gender.clone()}</span>
+                                    }
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Start date"}</span>
+                                    <EditableField id="start_date" input_type="date" edit_mode={*edit_mode} value={(*input_state).start_date.to_string()} onchange={&update_state_cb} error={input_state.error("start_date")}/>
+                                </li>
+                                <li class="flex justify-between">
+                                    <label for="active"><span>{"Active?"}</span></label>
+                                    <input type="checkbox" id="active" checked={(*input_state).active} onchange={&update_state_cb} disabled={!(*edit_mode)}/>
+                                </li>
+                                {if !(*input_state).active {
+                                    html!{
+                                        <li class="flex justify-between">
+                                            <span class="text-bold w-[120px]">{"Leave date"}</span>
+                                            <EditableField id="leave_date" input_type="date" edit_mode={*edit_mode} value={(*input_state).leave_date.map(|d| d.to_string()).unwrap_or_default()} onchange={&update_state_cb} error={input_state.error("leave_date")}/>
+                                        </li>
+                                    }
+                                } else {
+                                    html!()
+                                }}
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Attendance"}</span>
+                                    <AttendanceLine pupil_id={pupil.id.unwrap()} />
+                                </li>
+                                <li class="flex justify-between">
+                                    <span class="text-bold w-[120px]">{"Tags"}</span>
+                                    <PupilTags state={(*input_state).clone()} edit_mode={*edit_mode} onchange={&update_state_cb}/>
+                                </li>
+                                if pupil.has_allergy || pupil.carries_medication {
+                                    <li class="flex justify-between text-red-500">
+                                        <span class="text-bold w-[120px]">{"Medical"}</span>
+                                        <span class="flex items-center gap-1">
+                                            <yew_feather::AlertTriangle size="16" />
+                                            {pupil.medical_alert()}
+                                        </span>
                                     </li>
                                 }
-                            } else {
-                                html!()
-                            }}
-                            <li class="flex justify-between">
-                                <span class="text-bold w-[120px]">{"Tags"}</span>
-                                <PupilTags state={(*input_state).clone()} edit_mode={*edit_mode} onchange={&update_state_cb}/>
-                            </li>
-                        </ul>
-                    </div>
-                    <div class="flex justify-around my-3">
-                        <Button visible={Some(*edit_mode)} icon={html!(<yew_feather::X size="16" />)} color="yellow" text="Cancel" onclick={
-                            clone!(edit_mode, pupil, input_state);
-                            Callback::from(move |_ev| {
+                            </ul>
+                            if let Some(error) = input_state.error("form") {
+                                <p class="text-xs text-red-500">{error}</p>
+                            }
+                        </div>
+                        <div class="flex justify-around my-3">
+                            <Button visible={Some(*edit_mode)} icon={html!(<yew_feather::X size="16" />)} color="yellow" text="Cancel" onclick={
                                 clone!(edit_mode, pupil, input_state);
-                                spawn_local(async move {
-                                    edit_mode.set(!*edit_mode);
-                                    input_state.set(PupilInputState::from(&pupil.clone()));
-                                })
-                        })} />
-                        <Button visible={Some(*edit_mode)} icon={html!(<yew_feather::Save size="16" />)} color="green" text="Save" onclick={
-                            clone!(edit_mode, pupil, input_state, refresh_callback, ctx);
-                            Callback::from(move |_ev| {
+                                Callback::from(move |_ev| {
+                                    clone!(edit_mode, pupil, input_state);
+                                    spawn_local(async move {
+                                        edit_mode.set(!*edit_mode);
+                                        input_state.set(PupilInputState::from(&pupil.clone()));
+                                    })
+                            })} />
+                            <Button visible={Some(*edit_mode)} icon={html!(<yew_feather::Save size="16" />)} color="green" text="Save" onclick={
                                 clone!(edit_mode, pupil, input_state, refresh_callback, ctx);
-                                spawn_local(async move {
-                                    update_pupil(&pupil.id.unwrap().to_string(), &(*input_state), &ctx.auth_token).await;
-                                    refresh_callback.emit(true);
-                                    edit_mode.set(!*edit_mode);
-                                })
-                        })} />
-                        <Button visible={Some(!*edit_mode)} icon={html!(<yew_feather::Edit size="16" />)} color="yellow" text="Edit" onclick={
-                            clone!(edit_mode);
-                            Callback::from(move |_ev| {
+                                Callback::from(move |_ev| {
+                                    clone!(edit_mode, pupil, input_state, refresh_callback, ctx);
+                                    if input_state.has_errors() {
+                                        return;
+                                    }
+                                    spawn_local(async move {
+                                        match update_pupil(&pupil.id.unwrap().to_string(), &(*input_state), &ctx.auth_token).await {
+                                            Ok(None) => {
+                                                refresh_callback.emit(true);
+                                                edit_mode.set(!*edit_mode);
+                                            }
+                                            Ok(Some(fields)) => {
+                                                let mut state = (*input_state).clone();
+                                                state.set_server_errors(fields);
+                                                input_state.set(state);
+                                            }
+                                            Err(error) => error!("error updating pupil:", error.to_string()),
+                                        }
+                                    })
+                            })} />
+                            <Button visible={Some(!*edit_mode)} icon={html!(<yew_feather::Edit size="16" />)} color="yellow" text="Edit" onclick={
                                 clone!(edit_mode);
-                                spawn_local(async move {
-                                    edit_mode.set(!*edit_mode);
-                                })
-                        })} />
-                        <Button visible={Some(!*edit_mode)} icon={html!(<yew_feather::Trash size="16" />)} color="red" text="Delete" onclick={
-                            clone!(pupil, refresh_callback, close_callback, ctx);
-                            Callback::from(move |ev| {
+                                Callback::from(move |_ev| {
+                                    clone!(edit_mode);
+                                    spawn_local(async move {
+                                        edit_mode.set(!*edit_mode);
+                                    })
+                            })} />
+                            <Button visible={Some(!*edit_mode)} icon={html!(<yew_feather::Trash size="16" />)} color="red" text="Delete" onclick={
                                 clone!(pupil, refresh_callback, close_callback, ctx);
-                                spawn_local(async move {
-                                    delete_pupil(&pupil.id.unwrap().to_string(), &ctx.auth_token).await;
-                                    refresh_callback.emit(true);
-                                    close_callback.emit(ev);
-                                })
-                        })} />
+                                Callback::from(move |ev| {
+                                    clone!(pupil, refresh_callback, close_callback, ctx);
+                                    spawn_local(async move {
+                                        delete_pupil(&pupil.id.unwrap().to_string(), &ctx.auth_token).await;
+                                        refresh_callback.emit(true);
+                                        close_callback.emit(ev);
+                                    })
+                            })} />
+                        </div>
                     </div>
                 </div>
+                <div class="flex flex-col gap-4 w-[350px] border-l-2 border-slate-200 pl-4">
+                    <PhotoPanel pupil_id={pupil.id.unwrap()} photo_changed={
+                        clone!(refresh_callback);
+                        Callback::from(move |_| refresh_callback.emit(true))
+                    } />
+                    <ContactsPanel pupil_id={pupil.id.unwrap()} />
+                    if ctx.current_user.is_medical() {
+                        <MedicalPanel pupil_id={pupil.id.unwrap()} alerts_changed={
+                            clone!(refresh_callback);
+                            Callback::from(move |_| refresh_callback.emit(true))
+                        } />
+                    }
+                    <CommentTimeline pupil_id={pupil.id.unwrap()} />
+                    <AttachmentsPanel path={format!("{}/{}/attachments", constant::PUPILS_PATH, pupil.id.unwrap())} can_delete=true />
+                    <LeaverCtfPanel pupil_id={pupil.id.unwrap()} />
+                    if ctx.current_user.roles.iter().any(|role| role == constant::ROLE_ADMIN) {
+                        <SubjectAccessPanel pupil_id={pupil.id.unwrap()} />
+                    }
+                    <ProgressionPanel pupil_id={pupil.id.unwrap()} />
+                    <InterventionsPanel pupil_id={pupil.id.unwrap()} />
+                    <TargetsPanel pupil_id={pupil.id.unwrap()} />
+                    <AlnPanel pupil_id={pupil.id.unwrap()} />
+                    <EalPanel pupil_id={pupil.id.unwrap()} home_language={pupil.home_language.clone()} stage_changed={
+                        clone!(input_state, refresh_callback);
+                        Callback::from(move |stage: Option<String>| {
+                            input_state.set(PupilInputState {
+                                eal: stage.is_some(),
+                                eal_stage: stage,
+                                ..(*input_state).clone()
+                            });
+                            refresh_callback.emit(true);
+                        })
+                    } />
+                    <FlagHistoryPanel pupil_id={pupil.id.unwrap()} flags_changed={
+                        clone!(input_state, refresh_callback);
+                        Callback::from(move |flags: Vec<String>| {
+                            let on = |flag: &str| flags.iter().any(|f| f == flag);
+                            input_state.set(PupilInputState {
+                                mat: on("mat"),
+                                lac: on("lac"),
+                                fsm: on("fsm"),
+                                eal: on("eal"),
+                                aln: on("aln"),
+                                ..(*input_state).clone()
+                            });
+                            refresh_callback.emit(true);
+                        })
+                    } />
+                </div>
             </div>
         }
     } else {
@@ -144,33 +260,27 @@ async fn delete_pupil(id: &str, token: &str) {
     }
 }
 
-async fn update_pupil(id: &str, is: &PupilInputState, token: &str) {
+/// Returns the server's field errors if it rejected the update
+async fn update_pupil(
+    id: &str,
+    is: &PupilInputState,
+    token: &str,
+) -> Result<Option<HashMap<String, String>>> {
     debug!("updating", id);
-    let name = is.name.split(" ").collect::<Vec<&str>>();
-    let (last_name, first_names) = name.split_last().expect("returns if name not 2 parts");
     let pupil = Pupil {
         id: Some(Uuid::from_str(id).expect("string was not a valid uuid")),
-        first_names: first_names.join(" "),
-        last_name: last_name.to_string(),
-        year: is.year,
-        start_date: is.start_date,
-        end_date: is.leave_date,
-        active: is.active,
-        more_able_and_talented: is.mat,
-        english_as_additional_language: is.eal,
-        free_school_meals: is.fsm,
-        additional_learning_needs: is.aln,
-        looked_after_child: is.lac,
-        gender: is.gender.clone(),
+        ..Pupil::from(is)
     };
-    match Request::post(&format!("{}/{}", constant::PUPILS_PATH, id))
-        .json(&pupil)
-        .expect("pupil json")
+    // sent as a merge patch, so a re-enrolled pupil's null end_date clears the old one
+    let response = Request::patch(&format!("{}/{}", constant::PUPILS_PATH, id))
+        .json(&pupil)?
         .header("Authorization", &format!("Bearer {}", token))
         .send()
-        .await
-    {
-        Ok(_res) => {}
-        Err(error) => error!("error updating pupil:", error.to_string()),
+        .await?;
+    match response.status() {
+        200 => Ok(None),
+        400 => Ok(Some(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
     }
 }
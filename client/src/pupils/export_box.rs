@@ -0,0 +1,182 @@
+use super::filter::Filter;
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, IconButton},
+    error::*,
+    utils::use_download,
+};
+use gloo_net::http::Request;
+use serde::Serialize;
+use std::{collections::HashSet, rc::Rc};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The columns the server can export, with whether they're ticked to begin with
+static COLUMNS: [(&str, &str, bool); 20] = [
+    ("upn", "UPN", true),
+    ("first_names", "First names", true),
+    ("last_name", "Last name", true),
+    ("preferred_first_names", "Preferred first names", false),
+    ("preferred_last_name", "Preferred last name", false),
+    ("year", "Year", true),
+    ("gender", "Gender", true),
+    ("date_of_birth", "Date of birth", true),
+    ("start_date", "Start date", false),
+    ("end_date", "Leave date", false),
+    ("active", "Active", false),
+    ("home_language", "Home language", false),
+    ("free_school_meals", "Free school meals", true),
+    ("looked_after_child", "Looked after", true),
+    ("more_able_and_talented", "More able and talented", true),
+    (
+        "english_as_additional_language",
+        "English as additional language",
+        true,
+    ),
+    ("eal_stage", "EAL stage", false),
+    (
+        "additional_learning_needs",
+        "Additional learning needs",
+        true,
+    ),
+    ("persistent_absence", "Persistent absence", false),
+    ("in_intervention", "In an intervention", false),
+];
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+struct PupilExport {
+    format: String,
+    filters: Vec<Filter>,
+    columns: Vec<String>,
+}
+
+/// Download the pupils the table is showing, with the filters that are applied, as a spreadsheet.
+#[function_component(PupilExportBox)]
+pub fn pupil_export_box(props: &PupilExportBoxProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN EXPORT BOX");
+    let format = use_state_eq(|| "xlsx".to_owned());
+    let columns: UseStateHandle<HashSet<String>> = use_state_eq(|| {
+        COLUMNS
+            .iter()
+            .filter(|(_, _, ticked)| *ticked)
+            .map(|(column, _, _)| column.to_string())
+            .collect()
+    });
+    let file = use_download();
+
+    let choose_format = {
+        clone!(format);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            format.set(target.value());
+        })
+    };
+    let toggle_column = {
+        clone!(columns);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let column = target.id().trim_start_matches("export_").to_owned();
+            let mut chosen = (*columns).clone();
+            if target.checked() {
+                chosen.insert(column);
+            } else {
+                chosen.remove(&column);
+            }
+            columns.set(chosen);
+        })
+    };
+    let download = {
+        clone!(ctx, format, columns, file);
+        let filters = props.filters.clone();
+        Callback::from(move |_: MouseEvent| {
+            let export = PupilExport {
+                format: (*format).clone(),
+                filters: filters.clone(),
+                // in the order they're listed rather than the order they were ticked
+                columns: COLUMNS
+                    .iter()
+                    .filter(|(column, _, _)| columns.contains(*column))
+                    .map(|(column, _, _)| column.to_string())
+                    .collect(),
+            };
+            clone!(ctx, file);
+            spawn_local(async move {
+                match export_pupils(&export, &ctx.auth_token).await {
+                    Ok(data) => {
+                        let content_type = match export.format.as_str() {
+                            "csv" => "text/csv",
+                            _ => {
+                                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+                            }
+                        };
+                        file.save(
+                            data.as_slice(),
+                            content_type,
+                            &format!("pupils.{}", export.format),
+                        );
+                    }
+                    Err(error) => error!("failed to export pupils:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-3 w-[600px] p-3">
+            <div class="flex justify-between">
+                <span class="text-2xl">{"Export pupils"}</span>
+                <IconButton icon="close" onclick={&props.close_callback} />
+            </div>
+            <p class="text-sm text-slate-500">
+                if props.filters.is_empty() {
+                    {"Every pupil in your year groups."}
+                } else {
+                    {"The pupils matching the filters applied to the table."}
+                }
+            </p>
+            <div class="flex gap-3 text-sm">
+                <label class="flex items-center gap-1">
+                    <input type="radio" name="export_format" value="xlsx" checked={*format == "xlsx"} onchange={&choose_format}/>
+                    {"Excel (XLSX)"}
+                </label>
+                <label class="flex items-center gap-1">
+                    <input type="radio" name="export_format" value="csv" checked={*format == "csv"} onchange={&choose_format}/>
+                    {"CSV"}
+                </label>
+            </div>
+            <div class="grid grid-cols-2 gap-1 text-sm">
+                {COLUMNS.iter().map(|(column, name, _)| html! {
+                    <label class="flex items-center gap-1">
+                        <input type="checkbox" id={format!("export_{column}")} checked={columns.contains(*column)} onchange={&toggle_column}/>
+                        {*name}
+                    </label>
+                }).collect::<Html>()}
+            </div>
+            <a ref={file.link.clone()} class="hidden"></a>
+            if !columns.is_empty() {
+                <Button icon={html!(<yew_feather::Download size="16" />)} text="Download" color="blue" onclick={download} />
+            }
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PupilExportBoxProps {
+    pub filters: Vec<Filter>,
+    pub close_callback: Callback<MouseEvent>,
+}
+
+async fn export_pupils(export: &PupilExport, token: &str) -> Result<Vec<u8>> {
+    let response = Request::post(&format!("{}/export", constant::PUPILS_PATH))
+        .json(export)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.binary().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
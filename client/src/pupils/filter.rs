@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use super::Pupil;
 use crate::elements::{Button, IconButton};
+use chrono::NaiveDate;
+use serde::Serialize;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
@@ -36,6 +38,8 @@ pub fn table_filter(props: &TableFilterProps) -> Html {
                         "fsm" => Filter::Fsm,
                         "aln" => Filter::Aln,
                         "eal" => Filter::Eal,
+                        "pa" => Filter::PersistentAbsence,
+                        "intervention" => Filter::InIntervention,
                         unknown => panic!("{unknown} is not a valid filter"),
                     };
                     filters.push(to_apply);
@@ -48,6 +52,9 @@ pub fn table_filter(props: &TableFilterProps) -> Html {
             if let Some(year) = (*state).year {
                 filters.push(Filter::Year(year));
             }
+            if let Some(date) = (*state).as_at {
+                filters.push(Filter::AsAt(date));
+            }
             update_selected_filters.emit(filters);
             close.emit(ev);
         })
@@ -88,6 +95,14 @@ pub fn table_filter(props: &TableFilterProps) -> Html {
                     <label for="aln">{"Additional learning needs"}</label>
                     <input type="checkbox" id="aln" onchange={&onchange} checked={(*state).flags["aln"]} />
                 </li>
+                <li class="flex justify-between">
+                    <label for="pa">{"Persistent absence"}</label>
+                    <input type="checkbox" id="pa" onchange={&onchange} checked={(*state).flags["pa"]} />
+                </li>
+                <li class="flex justify-between">
+                    <label for="intervention">{"In an active intervention"}</label>
+                    <input type="checkbox" id="intervention" onchange={&onchange} checked={(*state).flags["intervention"]} />
+                </li>
                 <li class="flex justify-between">
                     <label for="name">{"Name"}</label>
                     <input type="text" id="name" onchange={&onchange} value={(*state).name.to_owned()} />
@@ -102,6 +117,12 @@ pub fn table_filter(props: &TableFilterProps) -> Html {
                         }
                     } />
                 </li>
+                <li class="flex justify-between" title="Show the flags as they were on this day">
+                    <label for="as_at">{"Flags as at"}</label>
+                    <input type="date" id="as_at" class="w-[120px]" onchange={&onchange} value={
+                        (*state).as_at.map(|date| date.to_string()).unwrap_or_default()
+                    } />
+                </li>
             </ul>
             <Button color="green" text="Apply" onclick={&apply_filters} />
             <Button color="red" text="Clear" onclick={
@@ -133,12 +154,14 @@ struct TableFilterState {
     flags: HashMap<String, bool>,
     name: String,
     year: Option<i32>,
+    as_at: Option<NaiveDate>,
 }
 
 impl TableFilterState {
     fn update(&mut self, target: HtmlInputElement) {
         match target.id().as_str() {
-            "active" | "inactive" | "mat" | "fsm" | "eal" | "lac" | "aln" => {
+            "active" | "inactive" | "mat" | "fsm" | "eal" | "lac" | "aln" | "pa"
+            | "intervention" => {
                 if let Some(filter_ref) = self.flags.get_mut(&target.id()) {
                     *filter_ref = target.checked();
                 }
@@ -152,6 +175,7 @@ impl TableFilterState {
                         .expect("invalid input, not an integer"),
                 )
             }
+            "as_at" => self.as_at = target.value().parse().ok(),
             unknown => panic!("{} is not a valid filter", unknown),
         }
     }
@@ -159,19 +183,32 @@ impl TableFilterState {
 
 impl Default for TableFilterState {
     fn default() -> TableFilterState {
-        let kvs = ["active", "inactive", "mat", "aln", "fsm", "eal", "lac"]
-            .iter()
-            .map(|v| (v.to_string(), false))
-            .collect::<HashMap<String, bool>>();
+        let kvs = [
+            "active",
+            "inactive",
+            "mat",
+            "aln",
+            "fsm",
+            "eal",
+            "lac",
+            "pa",
+            "intervention",
+        ]
+        .iter()
+        .map(|v| (v.to_string(), false))
+        .collect::<HashMap<String, bool>>();
         TableFilterState {
             flags: kvs,
             name: String::new(),
             year: None,
+            as_at: None,
         }
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+/// Sent as is to the export endpoint, which applies the same filters on the server
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Filter {
     Active,
     Inactive,
@@ -180,8 +217,12 @@ pub enum Filter {
     Fsm,
     Lac,
     Eal,
+    PersistentAbsence,
+    InIntervention,
     Name(String),
     Year(i32),
+    /// Fetch the pupils with their flags as they stood on a past date, rather than filtering
+    AsAt(NaiveDate),
 }
 
 impl Filter {
@@ -194,7 +235,10 @@ impl Filter {
             Filter::Fsm => pupil.free_school_meals,
             Filter::Lac => pupil.looked_after_child,
             Filter::Eal => pupil.english_as_additional_language,
+            Filter::PersistentAbsence => pupil.persistent_absence,
+            Filter::InIntervention => pupil.in_intervention,
             Filter::Year(filter_year) => pupil.year == *filter_year,
+            Filter::AsAt(_) => true,
             Filter::Name(filter_name) => {
                 // concat pupil name
                 let concat_name = pupil.first_names.to_owned() + " " + &pupil.last_name;
@@ -204,6 +248,14 @@ impl Filter {
     }
 }
 
+/// The date the pupils' flags should be worked out for, if not today
+pub fn as_at(filters: &[Filter]) -> Option<NaiveDate> {
+    filters.iter().find_map(|filter| match filter {
+        Filter::AsAt(date) => Some(*date),
+        _ => None,
+    })
+}
+
 pub fn filter(pupil: &Pupil, filters: Vec<Filter>) -> bool {
     for filter in filters {
         if !filter.apply(pupil) {
@@ -225,8 +277,11 @@ impl From<Vec<Filter>> for TableFilterState {
                 Filter::Fsm => *tf.flags.get_mut("fsm").unwrap() = true,
                 Filter::Lac => *tf.flags.get_mut("lac").unwrap() = true,
                 Filter::Eal => *tf.flags.get_mut("eal").unwrap() = true,
+                Filter::PersistentAbsence => *tf.flags.get_mut("pa").unwrap() = true,
+                Filter::InIntervention => *tf.flags.get_mut("intervention").unwrap() = true,
                 Filter::Name(name) => tf.name = name,
                 Filter::Year(year) => tf.year = Some(year),
+                Filter::AsAt(date) => tf.as_at = Some(date),
             }
         }
         tf
@@ -0,0 +1,261 @@
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, IconButton},
+    error::{ErrorResponse, Result},
+};
+use base64::{engine::general_purpose, Engine};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The pupil fields a column can be mapped onto, required ones first
+static FIELDS: [(&str, &str); 15] = [
+    ("upn", "UPN"),
+    ("first_names", "First names"),
+    ("last_name", "Last name"),
+    ("year", "Year"),
+    ("gender", "Gender"),
+    ("start_date", "Start date"),
+    ("end_date", "Leave date"),
+    ("active", "Active"),
+    ("date_of_birth", "Date of birth"),
+    ("preferred_first_names", "Preferred first names"),
+    ("preferred_last_name", "Preferred last name"),
+    ("home_language", "Home language"),
+    ("more_able_and_talented", "More able and talented"),
+    ("free_school_meals", "Free school meals"),
+    ("looked_after_child", "Looked after"),
+];
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+struct PupilImport {
+    format: String,
+    /// the file, base64 encoded
+    data: String,
+    mapping: BTreeMap<String, String>,
+    dry_run: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct RowReport {
+    row: usize,
+    action: String,
+    #[serde(default)]
+    upn: Option<String>,
+    name: String,
+    #[serde(default)]
+    changes: Vec<String>,
+    #[serde(default)]
+    errors: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct ImportReport {
+    headings: Vec<String>,
+    mapping: BTreeMap<String, String>,
+    missing: Vec<String>,
+    rows: Vec<RowReport>,
+    creates: usize,
+    updates: usize,
+    unchanged: usize,
+    errors: usize,
+    committed: bool,
+}
+
+/// Bring in a CSV or XLSX export of pupils. Picking a file previews it straight away, columns can
+/// be remapped if their headings weren't recognised, and the import only goes ahead once every
+/// row is clean.
+#[function_component(PupilImportBox)]
+pub fn pupil_import_box(props: &PupilImportBoxProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN IMPORT BOX");
+    let import: UseStateHandle<Option<PupilImport>> = use_state_eq(|| None);
+    let report: UseStateHandle<Option<ImportReport>> = use_state_eq(|| None);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+
+    let send = {
+        clone!(ctx, import, report, errors);
+        let refresh = props.refresh_callback.clone();
+        Callback::from(move |next: PupilImport| {
+            import.set(Some(PupilImport {
+                dry_run: true,
+                ..next.clone()
+            }));
+            clone!(ctx, report, errors, refresh);
+            spawn_local(async move {
+                match run_import(&next, &ctx.auth_token).await {
+                    Ok(Ok(fetched)) => {
+                        errors.set(HashMap::new());
+                        if fetched.committed {
+                            refresh.emit(true);
+                        }
+                        report.set(Some(fetched));
+                    }
+                    Ok(Err(fields)) => {
+                        report.set(None);
+                        errors.set(fields);
+                    }
+                    Err(error) => error!("failed to import pupils:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let choose_file = {
+        clone!(send);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let Some(file) = target.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let format = if file.name().to_lowercase().ends_with(".xlsx") {
+                "xlsx"
+            } else {
+                "csv"
+            };
+            let file = gloo_file::File::from(file);
+            clone!(send);
+            spawn_local(async move {
+                match gloo_file::futures::read_as_bytes(&file).await {
+                    Ok(bytes) => send.emit(PupilImport {
+                        format: format.to_owned(),
+                        data: general_purpose::STANDARD.encode(bytes),
+                        mapping: BTreeMap::new(),
+                        dry_run: true,
+                    }),
+                    Err(error) => error!("failed to read import file:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    let remap = {
+        clone!(import, report, send);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let (Some(current), Some(report)) = ((*import).clone(), (*report).clone()) else {
+                return;
+            };
+            let field = target.id().trim_start_matches("import_").to_owned();
+            // start from what the server worked out so the other columns stay where they are, an
+            // empty heading keeps a field the server would otherwise guess out of the import
+            let mut mapping = report.mapping;
+            for (field, heading) in &current.mapping {
+                if heading.is_empty() {
+                    mapping.insert(field.clone(), String::new());
+                }
+            }
+            mapping.insert(field, target.value());
+            send.emit(PupilImport { mapping, ..current });
+        })
+    };
+
+    let commit = {
+        clone!(import, send);
+        Callback::from(move |_: MouseEvent| {
+            if let Some(current) = (*import).clone() {
+                send.emit(PupilImport {
+                    dry_run: false,
+                    ..current
+                });
+            }
+        })
+    };
+
+    let ready = report.as_ref().is_some_and(|r| {
+        !r.committed && r.missing.is_empty() && r.errors == 0 && r.creates + r.updates > 0
+    });
+
+    html! {
+        <div class="flex flex-col gap-3 w-[800px] max-h-[80vh] p-3">
+            <div class="flex justify-between">
+                <span class="text-2xl">{"Import pupils"}</span>
+                <IconButton icon="close" onclick={&props.close_callback} />
+            </div>
+            <p class="text-sm text-slate-500">{"A CSV or XLSX file with a heading row. Pupils are matched on their UPN, so importing the same file again only changes what's different."}</p>
+            <input type="file" id="import_file" accept=".csv,.xlsx" class="text-sm" onchange={choose_file}/>
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            if let Some(report) = &*report {
+                <div class="grid grid-cols-3 gap-1 text-sm">
+                    {FIELDS.iter().map(|(field, name)| {
+                        let missing = report.missing.iter().any(|m| m == field);
+                        html! {
+                            <label class={classes!("flex", "flex-col", missing.then_some("text-red-500"))}>
+                                {*name}
+                                <select id={format!("import_{field}")} class="border-2 border-slate-200 rounded-md" onchange={&remap}>
+                                    <option value="" selected={!report.mapping.contains_key(*field)}>{"Not imported"}</option>
+                                    {report.headings.iter().map(|heading| html! {
+                                        <option value={heading.clone()} selected={report.mapping.get(*field) == Some(heading)}>{heading}</option>
+                                    }).collect::<Html>()}
+                                </select>
+                            </label>
+                        }
+                    }).collect::<Html>()}
+                </div>
+                <p class="text-sm">
+                    if report.committed {
+                        {format!("Imported: {} created, {} updated, {} unchanged", report.creates, report.updates, report.unchanged)}
+                    } else {
+                        {format!("{} to create, {} to update, {} unchanged, {} with errors", report.creates, report.updates, report.unchanged, report.errors)}
+                    }
+                </p>
+                <div class="overflow-y-auto scrollbar">
+                    <table class="w-full text-xs">
+                        <thead>
+                            <tr class="text-left"><th>{"Row"}</th><th>{"UPN"}</th><th>{"Name"}</th><th>{"Action"}</th><th>{"Details"}</th></tr>
+                        </thead>
+                        <tbody>
+                            {report.rows.iter().map(|row| html! {
+                                <tr key={row.row.to_string()} class={classes!((row.action == "error").then_some("text-red-500"))}>
+                                    <td>{row.row.to_string()}</td>
+                                    <td>{row.upn.clone().unwrap_or_default()}</td>
+                                    <td>{&row.name}</td>
+                                    <td>{&row.action}</td>
+                                    <td>
+                                        {row.errors.iter().map(|(field, error)| html!(<p>{format!("{field}: {error}")}</p>)).collect::<Html>()}
+                                        if !row.changes.is_empty() {
+                                            <p>{row.changes.join(", ").replace('_', " ")}</p>
+                                        }
+                                    </td>
+                                </tr>
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                </div>
+            }
+            if ready {
+                <Button color="green" text="Import" onclick={commit} />
+            }
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PupilImportBoxProps {
+    pub refresh_callback: Callback<bool>,
+    pub close_callback: Callback<MouseEvent>,
+}
+
+/// Returns the server's field errors if it couldn't read the file
+async fn run_import(
+    import: &PupilImport,
+    token: &str,
+) -> Result<std::result::Result<ImportReport, HashMap<String, String>>> {
+    let response = Request::post(&format!("{}/import", constant::PUPILS_PATH))
+        .json(import)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<ImportReport>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
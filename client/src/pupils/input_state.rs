@@ -1,5 +1,6 @@
 use super::Pupil;
 use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
 use web_sys::HtmlInputElement;
 
 #[derive(Clone, PartialEq)]
@@ -12,28 +13,58 @@ pub struct InputState {
     pub mat: bool,
     pub lac: bool,
     pub fsm: bool,
+    /// only shown, the server keeps it in step with EAL assessments
     pub eal: bool,
+    pub eal_stage: Option<String>,
+    /// only shown, the server keeps it in step with the ALN register
     pub aln: bool,
     pub year: i32,
+    pub preferred_name: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub upn: String,
+    pub home_language: String,
+    /// Messages for inputs that couldn't be parsed or were rejected by the server, keyed by input id
+    pub errors: HashMap<String, String>,
 }
 
 impl InputState {
     /// Update the InputState from a HtmlInputElement
     pub fn update(&mut self, target: HtmlInputElement) {
         debug!("changing state");
-        match target.id().as_str() {
+        let id = target.id();
+        self.errors.remove(&id);
+        match id.as_str() {
             "name" => self.name = target.value(),
             "gender" => self.gender = target.value(),
-            "year" => self.year = target.value().parse::<i32>().expect("TODO HANDLE"),
-            "start_date" => {
-                self.start_date = target.value().parse::<NaiveDate>().expect("TODO HANDLE")
-            }
-            "leave_date" => {
-                self.leave_date = Some(target.value().parse::<NaiveDate>().expect("TODO HANDLE"))
-            }
+            "year" => match target.value().parse::<i32>() {
+                Ok(year) => self.year = year,
+                Err(_) => self.set_error(&id, "year must be a whole number"),
+            },
+            "start_date" => match target.value().parse::<NaiveDate>() {
+                Ok(date) => self.start_date = date,
+                Err(_) => self.set_error(&id, "start date must be a valid date"),
+            },
+            "leave_date" => match target.value().parse::<NaiveDate>() {
+                Ok(date) => self.leave_date = Some(date),
+                Err(_) => self.set_error(&id, "leave date must be a valid date"),
+            },
+            "preferred_name" => self.preferred_name = target.value(),
+            "date_of_birth" => match target.value().as_str() {
+                "" => self.date_of_birth = None,
+                value => match value.parse::<NaiveDate>() {
+                    Ok(date) => self.date_of_birth = Some(date),
+                    Err(_) => self.set_error(&id, "date of birth must be a valid date"),
+                },
+            },
+            "upn" => self.upn = target.value().trim().to_uppercase(),
+            "home_language" => self.home_language = target.value(),
             "active" => {
                 self.active = target.checked();
-                self.leave_date = Some(Utc::now().date_naive());
+                self.leave_date = if self.active {
+                    None
+                } else {
+                    Some(Utc::now().date_naive())
+                };
             },
             "mat" => {
                 let is_active = target
@@ -53,15 +84,6 @@ impl InputState {
                     _ => true,
                 }
             }
-            "aln" => {
-                let is_active = target
-                    .get_attribute("active")
-                    .expect("tag has no active attribute");
-                self.aln = match is_active.as_str() {
-                    "true" => false,
-                    _ => true,
-                }
-            }
             "fsm" => {
                 let is_active = target
                     .get_attribute("active")
@@ -71,18 +93,52 @@ impl InputState {
                     _ => true,
                 }
             }
-            "eal" => {
-                let is_active = target
-                    .get_attribute("active")
-                    .expect("tag has no active attribute");
-                self.eal = match is_active.as_str() {
-                    "true" => false,
-                    _ => true,
-                }
-            }
             _ => panic!("input trying to change non-existent state"),
         }
     }
+
+    /// The preferred names split like the legal name, or `None` for whichever weren't given
+    pub fn preferred_names(&self) -> (Option<String>, Option<String>) {
+        let name = self.preferred_name.split_whitespace().collect::<Vec<&str>>();
+        match name.split_last() {
+            Some((last_name, first_names)) if !first_names.is_empty() => {
+                (Some(first_names.join(" ")), Some(last_name.to_string()))
+            }
+            Some((first_name, _)) => (Some(first_name.to_string()), None),
+            None => (None, None),
+        }
+    }
+
+    pub fn error(&self, id: &str) -> Option<String> {
+        self.errors.get(id).cloned()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Show the field errors from a failed create or update against the matching inputs
+    pub fn set_server_errors(&mut self, fields: HashMap<String, String>) {
+        for (field, message) in fields {
+            let id = match field.as_str() {
+                "first_names" | "last_name" => "name",
+                "end_date" => "leave_date",
+                "preferred_first_names" | "preferred_last_name" => "preferred_name",
+                other => other,
+            };
+            self.set_error(id, &message);
+        }
+    }
+
+    fn set_error(&mut self, id: &str, message: &str) {
+        self.errors
+            .entry(id.to_owned())
+            .and_modify(|existing| {
+                existing.push_str(", ");
+                existing.push_str(message);
+            })
+            .or_insert_with(|| message.to_owned());
+    }
 }
 
 impl Default for InputState {
@@ -98,8 +154,14 @@ impl Default for InputState {
             lac: Default::default(),
             fsm: Default::default(),
             eal: Default::default(),
+            eal_stage: None,
             aln: Default::default(),
             year: Default::default(),
+            preferred_name: Default::default(),
+            date_of_birth: None,
+            upn: Default::default(),
+            home_language: Default::default(),
+            errors: HashMap::new(),
         }
     }
 }
@@ -116,8 +178,18 @@ impl From<&Pupil> for InputState {
             lac: value.looked_after_child,
             fsm: value.free_school_meals,
             eal: value.english_as_additional_language,
+            eal_stage: value.eal_stage.clone(),
             aln: value.additional_learning_needs,
             year: value.year,
+            preferred_name: [&value.preferred_first_names, &value.preferred_last_name]
+                .into_iter()
+                .filter_map(|name| name.as_deref())
+                .collect::<Vec<&str>>()
+                .join(" "),
+            date_of_birth: value.date_of_birth,
+            upn: value.upn.clone().unwrap_or_default(),
+            home_language: value.home_language.clone().unwrap_or_default(),
+            errors: HashMap::new(),
         }
     }
 }
@@ -126,7 +198,8 @@ impl From<&InputState> for Pupil {
     fn from(value: &InputState) -> Self {
         let name = value.name.split(" ").collect::<Vec<&str>>();
         let (last_name, first_names) = name.split_last().expect("returns if name not 2 parts");
-        Pupil::new(
+        let (preferred_first_names, preferred_last_name) = value.preferred_names();
+        let pupil = Pupil::new(
             first_names.join(" "),
             last_name.to_string(),
             value.year,
@@ -139,6 +212,21 @@ impl From<&InputState> for Pupil {
             value.aln,
             value.lac,
             value.gender.clone(),
-        )
+        );
+        Pupil {
+            date_of_birth: value.date_of_birth,
+            upn: non_empty(&value.upn),
+            preferred_first_names,
+            preferred_last_name,
+            home_language: non_empty(&value.home_language),
+            ..pupil
+        }
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    match value.trim() {
+        "" => None,
+        trimmed => Some(trimmed.to_owned()),
     }
 }
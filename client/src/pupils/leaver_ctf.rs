@@ -0,0 +1,106 @@
+use crate::{
+    app::AppContext,
+    constant,
+    elements::IconButton,
+    error::{ErrorResponse, Result},
+    utils::use_download,
+};
+use gloo_net::http::Request;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Download a common transfer file for a pupil who's leaving. The destination school can be left
+/// blank when it isn't known yet, and anything that stops the file passing the schema is listed
+/// so it can be fixed on the record first.
+#[function_component(LeaverCtfPanel)]
+pub fn leaver_ctf_panel(props: &LeaverCtfPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN LEAVER CTF PANEL");
+    let lea = use_state_eq(String::new);
+    let estab = use_state_eq(String::new);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let file = use_download();
+    let pupil_id = props.pupil_id;
+
+    let update = {
+        clone!(lea, estab);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            match target.id().as_str() {
+                "ctf_dest_lea" => lea.set(target.value().trim().to_owned()),
+                "ctf_dest_estab" => estab.set(target.value().trim().to_owned()),
+                _ => {}
+            }
+        })
+    };
+    let download = {
+        clone!(ctx, lea, estab, errors, file);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, lea, estab, errors, file);
+            spawn_local(async move {
+                match fetch_ctf(&pupil_id, &lea, &estab, &ctx.auth_token).await {
+                    Ok(Ok(data)) => {
+                        errors.set(HashMap::new());
+                        file.save(
+                            data.as_slice(),
+                            "application/xml",
+                            &format!("ctf_{pupil_id}.xml"),
+                        );
+                    }
+                    Ok(Err(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to get leaver CTF:", error.to_string()),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Leaver CTF"}</h3>
+            <div class="flex justify-between items-center gap-1 text-sm">
+                <input type="text" id="ctf_dest_lea" placeholder="LA" maxlength="3" class="border-2 border-slate-200 rounded-md w-16" value={(*lea).clone()} onchange={&update}/>
+                <input type="text" id="ctf_dest_estab" placeholder="Estab" maxlength="4" class="border-2 border-slate-200 rounded-md w-20" value={(*estab).clone()} onchange={&update}/>
+                <IconButton onclick={&download} icon="download" />
+            </div>
+            <p class="text-xs text-slate-500">{"Leave the school blank if it isn't known yet."}</p>
+            <ul class="flex flex-col text-xs text-red-500">
+                {errors.iter().map(|(path, error)| html!(<li>{format!("{}: {error}", path.rsplit('/').next().unwrap_or(path))}</li>)).collect::<Html>()}
+            </ul>
+            <a ref={file.link.clone()} class="hidden"></a>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct LeaverCtfPanelProps {
+    pub pupil_id: Uuid,
+}
+
+/// The file, or the server's errors saying why it failed the schema
+async fn fetch_ctf(
+    pupil_id: &Uuid,
+    lea: &str,
+    estab: &str,
+    token: &str,
+) -> Result<std::result::Result<Vec<u8>, HashMap<String, String>>> {
+    let mut query = vec![];
+    if !lea.is_empty() {
+        query.push(("dest_lea", lea));
+    }
+    if !estab.is_empty() {
+        query.push(("dest_estab", estab));
+    }
+    let response = Request::get(&format!("{}/{pupil_id}/ctf", constant::PUPILS_PATH))
+        .query(query)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.binary().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -1,3 +1,4 @@
+use crate::photos::PhotoLinks;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -18,6 +19,28 @@ pub struct Pupil {
     pub additional_learning_needs: bool,
     pub looked_after_child: bool,
     pub gender: String,
+    pub date_of_birth: Option<NaiveDate>,
+    pub upn: Option<String>,
+    pub preferred_first_names: Option<String>,
+    pub preferred_last_name: Option<String>,
+    pub home_language: Option<String>,
+    /// worked out by the server from the attendance register, so never sent back
+    #[serde(default, skip_serializing)]
+    pub persistent_absence: bool,
+    /// worked out by the server from intervention membership
+    #[serde(default, skip_serializing)]
+    pub in_intervention: bool,
+    /// the A to E stage from the pupil's latest EAL assessment
+    #[serde(default, skip_serializing)]
+    pub eal_stage: Option<String>,
+    /// worked out by the server from the medical record
+    #[serde(default, skip_serializing)]
+    pub has_allergy: bool,
+    #[serde(default, skip_serializing)]
+    pub carries_medication: bool,
+    /// missing when there's no photo or consent for it has been withdrawn
+    #[serde(default, skip_serializing)]
+    pub photo: Option<PhotoLinks>,
 }
 
 impl Pupil {
@@ -49,8 +72,40 @@ impl Pupil {
             additional_learning_needs,
             looked_after_child,
             gender,
+            ..Default::default()
         }
     }
+
+    /// What the medical alert is for, without giving away anything else on the record
+    pub fn medical_alert(&self) -> String {
+        match (self.has_allergy, self.carries_medication) {
+            (true, true) => "Allergy, carries medication".into(),
+            (true, false) => "Allergy".into(),
+            (false, true) => "Carries medication".into(),
+            (false, false) => String::new(),
+        }
+    }
+
+    /// The EAL tag text, with the stage letter once the pupil has been assessed
+    pub fn eal_tag(&self) -> String {
+        eal_tag(self.eal_stage.as_deref())
+    }
+
+    /// The name the pupil goes by, falling back to their legal names
+    pub fn display_name(&self) -> String {
+        format!(
+            "{} {}",
+            self.preferred_first_names.as_ref().unwrap_or(&self.first_names),
+            self.preferred_last_name.as_ref().unwrap_or(&self.last_name)
+        )
+    }
+}
+
+pub fn eal_tag(stage: Option<&str>) -> String {
+    match stage {
+        Some(stage) => format!("EAL {stage}"),
+        None => "EAL".to_owned(),
+    }
 }
 
 impl PartialOrd for Pupil {
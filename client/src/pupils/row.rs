@@ -21,13 +21,23 @@ pub fn pupil_row(p: &PupilRowProps) -> Html {
         html! { 
             <li key={id.clone()} class="snap-start cursor-pointer break-inside-avoid-column" onclick={open_pupil_details}>
                 <div class="h-[42px] hover:bg-slate-100 w-full flex justify-between flex-no-wrap rounded items-center px-2">
-                    <span>{format!("{} {}", pupil.first_names, pupil.last_name)}</span>
+                    <span class="flex items-center gap-1">
+                        if let Some(photo) = &pupil.photo {
+                            <img src={photo.thumbnail.clone()} alt="" loading="lazy" class="w-[32px] h-[32px] rounded-full mr-1" />
+                        }
+                        {pupil.display_name()}
+                        if pupil.has_allergy || pupil.carries_medication {
+                            <span class="text-red-500" title={pupil.medical_alert()}>
+                                <yew_feather::AlertTriangle size="16" />
+                            </span>
+                        }
+                    </span>
                     <div class="hidden lg:flex justify-start items-center space-x-1 w-[200px]">
                         if pupil.more_able_and_talented {
                             <Tag id="mat" color="purple" text="MAT" />
                         }
                         if pupil.english_as_additional_language {
-                            <Tag id="eal" color="yellow" text="EAL" />
+                            <Tag id="eal" color="yellow" text={pupil.eal_tag()} />
                         }
                         if pupil.additional_learning_needs {
                             <Tag id="aln" color="orange" text="ALN" />
@@ -46,7 +56,7 @@ pub fn pupil_row(p: &PupilRowProps) -> Html {
         html!{
             <li key={id.clone()} class="snap-start cursor-pointer break-inside-avoid-column" onclick={open_pupil_details}>
                 <div class="h-[42px] flex justify-between flex-no-wrap rounded items-center px-2">
-                    <span class="text-slate-200" >{format!("{} {}", pupil.first_names, pupil.last_name)}</span>
+                    <span class="text-slate-200" >{pupil.display_name()}</span>
                 </div>
             </li>
         }
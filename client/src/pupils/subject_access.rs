@@ -0,0 +1,226 @@
+use crate::{app::AppContext, constant, elements::IconButton, error::Result, utils::use_download};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+struct SubjectAccessRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    withhold: Vec<Uuid>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct PreviewComment {
+    id: Uuid,
+    category: String,
+    body: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct PreviewContact {
+    contact_id: Uuid,
+    first_names: String,
+    last_name: String,
+    relationship: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct PreviewAttachment {
+    id: Uuid,
+    file_name: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct Redacted {
+    section: String,
+    what: String,
+    count: usize,
+}
+
+/// Only the parts of the export the admin can choose to withhold, and what was redacted
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct Preview {
+    comments: Vec<PreviewComment>,
+    contacts: Vec<PreviewContact>,
+    attachments: Vec<PreviewAttachment>,
+    redacted: Vec<Redacted>,
+}
+
+/// Answer a subject access request for the pupil. The preview lists the comments, contacts and
+/// attachments that will be sent so any mostly about someone else can be withheld, and the
+/// download is recorded in the subject access log.
+#[function_component(SubjectAccessPanel)]
+pub fn subject_access_panel(props: &SubjectAccessPanelProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN SUBJECT ACCESS PANEL");
+    let reference = use_state_eq(String::new);
+    let preview: UseStateHandle<Option<Preview>> = use_state_eq(|| None);
+    let withhold: UseStateHandle<HashSet<Uuid>> = use_state_eq(HashSet::new);
+    let file = use_download();
+    let pupil_id = props.pupil_id;
+
+    let request = {
+        let reference = (*reference).clone();
+        let withhold = withhold.iter().copied().collect();
+        SubjectAccessRequest {
+            reference: (!reference.is_empty()).then_some(reference),
+            withhold,
+        }
+    };
+    let update_reference = {
+        clone!(reference);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            reference.set(target.value().trim().to_owned());
+        })
+    };
+    let toggle = {
+        clone!(withhold);
+        Callback::from(move |id: Uuid| {
+            let mut next = (*withhold).clone();
+            if !next.remove(&id) {
+                next.insert(id);
+            }
+            withhold.set(next);
+        })
+    };
+    let show_preview = {
+        clone!(ctx, preview, withhold, request);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, preview, withhold, request);
+            spawn_local(async move {
+                let request = SubjectAccessRequest {
+                    withhold: vec![],
+                    ..request
+                };
+                match fetch_preview(&pupil_id, &request, &ctx.auth_token).await {
+                    Ok(fetched) => {
+                        withhold.set(HashSet::new());
+                        preview.set(Some(fetched));
+                    }
+                    Err(error) => error!(
+                        "failed to preview subject access export:",
+                        error.to_string()
+                    ),
+                }
+            });
+        })
+    };
+    let download = {
+        clone!(ctx, file, request);
+        Callback::from(move |_: MouseEvent| {
+            clone!(ctx, file, request);
+            spawn_local(async move {
+                match fetch_export(&pupil_id, &request, &ctx.auth_token).await {
+                    Ok(data) => {
+                        file.save(
+                            data.as_slice(),
+                            "application/zip",
+                            &format!("subject_access_{pupil_id}.zip"),
+                        );
+                    }
+                    Err(error) => {
+                        error!("failed to export subject access data:", error.to_string())
+                    }
+                }
+            });
+        })
+    };
+    let item = |id: Uuid, label: String| {
+        clone!(toggle);
+        html! {
+            <li class="flex items-start gap-1">
+                <input type="checkbox" checked={withhold.contains(&id)} onchange={Callback::from(move |_| toggle.emit(id))}/>
+                <span class="truncate">{label}</span>
+            </li>
+        }
+    };
+
+    html! {
+        <div class="flex flex-col gap-2">
+            <h3 class="text-md">{"Subject access request"}</h3>
+            <div class="flex justify-between items-center gap-1 text-sm">
+                <input type="text" id="sar_reference" placeholder="Reference" class="border-2 border-slate-200 rounded-md w-32" value={(*reference).clone()} onchange={update_reference}/>
+                <IconButton onclick={show_preview} icon="visibility" />
+                <IconButton onclick={download} icon="download" />
+            </div>
+            <p class="text-xs text-slate-500">{"Safeguarding concerns aren't included and are reviewed separately by the DSL."}</p>
+            if let Some(preview) = &*preview {
+                <div class="flex flex-col gap-1 text-xs">
+                    <p class="text-slate-500">{"Tick anything to withhold in full."}</p>
+                    if !preview.comments.is_empty() {
+                        <h4 class="font-bold">{"Comments"}</h4>
+                        <ul class="flex flex-col">
+                            {preview.comments.iter().map(|comment| item(comment.id, format!("{}: {}", comment.category, comment.body))).collect::<Html>()}
+                        </ul>
+                    }
+                    if !preview.contacts.is_empty() {
+                        <h4 class="font-bold">{"Contacts"}</h4>
+                        <ul class="flex flex-col">
+                            {preview.contacts.iter().map(|contact| item(contact.contact_id, format!("{} {} ({})", contact.first_names, contact.last_name, contact.relationship))).collect::<Html>()}
+                        </ul>
+                    }
+                    if !preview.attachments.is_empty() {
+                        <h4 class="font-bold">{"Files"}</h4>
+                        <ul class="flex flex-col">
+                            {preview.attachments.iter().map(|attachment| item(attachment.id, attachment.file_name.clone())).collect::<Html>()}
+                        </ul>
+                    }
+                    <ul class="flex flex-col text-slate-500">
+                        {preview.redacted.iter().map(|redacted| html!(<li>{format!("{} {} redacted from {}", redacted.count, redacted.what, redacted.section)}</li>)).collect::<Html>()}
+                    </ul>
+                </div>
+            }
+            <a ref={file.link.clone()} class="hidden"></a>
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct SubjectAccessPanelProps {
+    pub pupil_id: Uuid,
+}
+
+async fn fetch_preview(
+    pupil_id: &Uuid,
+    request: &SubjectAccessRequest,
+    token: &str,
+) -> Result<Preview> {
+    let response = Request::post(&format!(
+        "{}/{pupil_id}/subject-access/preview",
+        constant::PUPILS_PATH
+    ))
+    .json(request)?
+    .header("Authorization", &format!("Bearer {token}"))
+    .send()
+    .await?;
+    match response.status() {
+        200 => Ok(response.json().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+async fn fetch_export(
+    pupil_id: &Uuid,
+    request: &SubjectAccessRequest,
+    token: &str,
+) -> Result<Vec<u8>> {
+    let response = Request::post(&format!(
+        "{}/{pupil_id}/subject-access",
+        constant::PUPILS_PATH
+    ))
+    .json(request)?
+    .header("Authorization", &format!("Bearer {token}"))
+    .send()
+    .await?;
+    match response.status() {
+        200 => Ok(response.binary().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
     error,
     error::*,
 };
+use chrono::NaiveDate;
 use gloo_net::http::Request;
 use web_sys::HtmlInputElement;
 use std::rc::Rc;
@@ -19,15 +20,18 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
     let show_inactive =  use_state_eq(|| false);
 
     // PUPILS ===================================================================================
+    let filters = use_state(|| Vec::<PupilFilter>::new());
+    let as_at = filter::as_at(&filters);
     let pupils: UseStateHandle<Vec<Pupil>> = use_state_eq(|| vec![]);
     let pupils_cache: UseStateHandle<Vec<Pupil>> = use_state_eq(|| vec![]);
     {
         clone!(ctx, pupils, pupils_cache);
         use_effect_with_deps(
-            move |_| {
+            move |as_at| {
                 clone!(ctx, pupils, pupils_cache);
+                let as_at = *as_at;
                 spawn_local(async move {
-                    if let Err(error) = fetch_pupils(&ctx.auth_token, pupils.clone(), pupils_cache).await {
+                    if let Err(error) = fetch_pupils(&ctx.auth_token, as_at, pupils.clone(), pupils_cache).await {
                         error!(
                             "failed to get pupils in pupil table on load:",
                             error.to_string()
@@ -38,7 +42,7 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
                     }
                 });
             },
-            (),
+            as_at,
         );
     }
     let refresh_callback = {
@@ -48,7 +52,7 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
             spawn_local(async move {
                 clone!(pupils);
                 if use_server {
-                    if let Err(error) = fetch_pupils(&ctx.auth_token, pupils.clone(), pupils_cache).await {
+                    if let Err(error) = fetch_pupils(&ctx.auth_token, as_at, pupils.clone(), pupils_cache).await {
                         error!(
                             "failed to refresh pupils in pupil table:",
                             error.to_string()
@@ -62,8 +66,6 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
     };
 
     // FILTER ===================================================================================
-    
-    let filters = use_state(|| Vec::<PupilFilter>::new());
     pupils.set(
         (*pupils)
             .clone()
@@ -87,10 +89,28 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
             invoke_modal.emit((ev, html!(<PupilCreateBox refresh_callback={&refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-300px)]")));
         })
     };
+    let open_import_box = {
+        clone!(invoke_modal, dismiss_modal, refresh_callback);
+        Callback::from(move |ev: MouseEvent| {
+            invoke_modal.emit((ev, html!(<PupilImportBox refresh_callback={&refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-300px)]")));
+        })
+    };
+    let open_ctf_box = {
+        clone!(invoke_modal, dismiss_modal, refresh_callback);
+        Callback::from(move |ev: MouseEvent| {
+            invoke_modal.emit((ev, html!(<CtfImportBox refresh_callback={&refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-300px)]")));
+        })
+    };
+    let open_export_box = {
+        clone!(invoke_modal, dismiss_modal, filters);
+        Callback::from(move |ev: MouseEvent| {
+            invoke_modal.emit((ev, html!(<PupilExportBox filters={(*filters).clone()} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-300px)]")));
+        })
+    };
     let open_pupil_details = {
         clone!(invoke_modal, dismiss_modal, refresh_callback);
         Callback::from(move |(ev, pupil): (MouseEvent, Pupil)| {
-            invoke_modal.emit((ev, html!(<PupilDetails pupil={pupil.clone()} refresh_callback={&refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-120px)]")));
+            invoke_modal.emit((ev, html!(<PupilDetails pupil={pupil.clone()} refresh_callback={&refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-200px)]")));
         })
     };
     let open_filter = {
@@ -104,7 +124,12 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
     html! {
         <div class="flex flex-col m-3 gap-3">
             <div class="flex p-3 gap-2 justify-between shadow-lg rounded-md bg-white">
-                <Button icon={html!(<yew_feather::Plus />)} text="Add" color="green" onclick={&open_create_box} />
+                <div class="flex gap-2">
+                    <Button icon={html!(<yew_feather::Plus />)} text="Add" color="green" onclick={&open_create_box} />
+                    <Button icon={html!(<yew_feather::Upload size="16" />)} text="Import" color="blue" onclick={&open_import_box} />
+                    <Button icon={html!(<yew_feather::Upload size="16" />)} text="Import CTF" color="blue" onclick={&open_ctf_box} />
+                    <Button icon={html!(<yew_feather::Download size="16" />)} text="Export" color="blue" onclick={&open_export_box} />
+                </div>
                 <div class="flex items-center gap-3">
                     <label for="show_inactive"><em>{"Show inactive learners"}</em></label>
                     <input id="show_inactive" type="checkbox" checked={(*show_inactive).clone()} onchange={clone!(show_inactive);Callback::from(move |ev: Event| {
@@ -118,7 +143,7 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
                             clone!(ctx, pupils, pupils_cache);
                                 spawn_local(async move {
                                     clone!(pupils);
-                                    if let Err(error) = fetch_pupils(&ctx.auth_token, pupils.clone(), pupils_cache).await {
+                                    if let Err(error) = fetch_pupils(&ctx.auth_token, as_at, pupils.clone(), pupils_cache).await {
                                         error!(
                                             "failed to refresh pupils in pupil table:",
                                             error.to_string()
@@ -146,8 +171,12 @@ pub fn pupil_table(_props: &PupilTableProps) -> Html {
     }
 }
 
-async fn fetch_pupils(token: &str, pupils: UseStateHandle<Vec<Pupil>>, cache: UseStateHandle<Vec<Pupil>>) -> Result<()> {
-    match Request::get(constant::PUPILS_PATH)
+async fn fetch_pupils(token: &str, as_at: Option<NaiveDate>, pupils: UseStateHandle<Vec<Pupil>>, cache: UseStateHandle<Vec<Pupil>>) -> Result<()> {
+    let url = match as_at {
+        Some(date) => format!("{}?as_at={date}", constant::PUPILS_PATH),
+        None => constant::PUPILS_PATH.to_owned(),
+    };
+    match Request::get(&url)
         .header("Authorization", &format!("Bearer {token}"))
         .send()
         .await
@@ -0,0 +1,5 @@
+mod merge_box;
+mod page;
+mod record;
+
+pub use page::DataQualityPage;
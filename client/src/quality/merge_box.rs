@@ -0,0 +1,175 @@
+use super::record::*;
+use crate::{
+    app::AppContext,
+    elements::{Button, IconButton},
+};
+use serde_json::Value;
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+fn shown(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Merge two pupils who are the same child. The admin picks which record to keep and which of the
+/// other record's details to take, and sees what will move before anything is saved.
+#[function_component(MergeBox)]
+pub fn merge_box(props: &MergeBoxProps) -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN MERGE BOX");
+    let pupils = use_state_eq(|| (props.keep, props.remove));
+    let take: UseStateHandle<Vec<String>> = use_state_eq(Vec::new);
+    let report: UseStateHandle<Option<MergeReport>> = use_state_eq(|| None);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+
+    let send = {
+        clone!(ctx, report, errors);
+        let refresh = props.refresh_callback.clone();
+        Callback::from(move |merge: MergeRequest| {
+            clone!(ctx, report, errors, refresh);
+            spawn_local(async move {
+                match run_merge(&merge, &ctx.auth_token).await {
+                    Ok(Ok(fetched)) => {
+                        errors.set(HashMap::new());
+                        if fetched.committed {
+                            refresh.emit(true);
+                        }
+                        report.set(Some(fetched));
+                    }
+                    Ok(Err(fields)) => {
+                        report.set(None);
+                        errors.set(fields);
+                    }
+                    Err(error) => error!("failed to merge pupils:", error.to_string()),
+                }
+            });
+        })
+    };
+    {
+        clone!(send);
+        use_effect_with_deps(
+            move |((keep, remove), take): &((Uuid, Uuid), Vec<String>)| {
+                send.emit(MergeRequest {
+                    keep: *keep,
+                    remove: *remove,
+                    take: take.clone(),
+                    dry_run: true,
+                });
+            },
+            (*pupils, (*take).clone()),
+        );
+    }
+
+    let swap = {
+        clone!(pupils, take);
+        Callback::from(move |_: MouseEvent| {
+            let (keep, remove) = *pupils;
+            pupils.set((remove, keep));
+            take.set(vec![]);
+        })
+    };
+    let choose = {
+        clone!(take);
+        Callback::from(move |(ev, field): (Event, String)| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let mut next: Vec<String> = take.iter().filter(|f| **f != field).cloned().collect();
+            if target.checked() {
+                next.push(field);
+            }
+            take.set(next);
+        })
+    };
+    let commit = {
+        clone!(pupils, take, send);
+        Callback::from(move |_: MouseEvent| {
+            let (keep, remove) = *pupils;
+            send.emit(MergeRequest {
+                keep,
+                remove,
+                take: (*take).clone(),
+                dry_run: false,
+            });
+        })
+    };
+
+    let name = |pupil: &crate::pupils::Pupil| format!("{} {}", pupil.first_names, pupil.last_name);
+
+    html! {
+        <div class="flex flex-col gap-3 w-[700px] max-h-[80vh] p-3">
+            <div class="flex justify-between">
+                <span class="text-2xl">{"Merge pupils"}</span>
+                <IconButton icon="close" onclick={&props.close_callback} />
+            </div>
+            {errors.values().map(|error| html!(<span class="text-xs text-red-500">{error}</span>)).collect::<Html>()}
+            if let Some(report) = &*report {
+                if report.committed {
+                    <p class="text-sm">{format!("{} has been merged into {}", name(&report.removed), name(&report.merged))}</p>
+                } else {
+                    <div class="flex gap-2 items-center text-sm">
+                        <span>{format!("Keep {} and remove {}", name(&report.merged), name(&report.removed))}</span>
+                        <Button color="blue" text="Swap" onclick={swap} />
+                    </div>
+                    if !report.differences.is_empty() {
+                        <table class="w-full text-sm text-left">
+                            <thead>
+                                <tr>
+                                    <th>{"Field"}</th>
+                                    <th>{"Kept record"}</th>
+                                    <th>{"Removed record"}</th>
+                                    <th>{"Use removed"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {report.differences.iter().map(|difference| {
+                                    let field = difference.field.clone();
+                                    let checked = take.contains(&field);
+                                    html! {
+                                        <tr key={field.clone()} class="border-t border-slate-200">
+                                            <td>{field.replace('_', " ")}</td>
+                                            <td>{shown(&difference.keep)}</td>
+                                            <td>{shown(&difference.remove)}</td>
+                                            <td>
+                                                <input type="checkbox" checked={checked} onchange={
+                                                    clone!(choose);
+                                                    Callback::from(move |ev: Event| choose.emit((ev, field.clone())))
+                                                }/>
+                                            </td>
+                                        </tr>
+                                    }
+                                }).collect::<Html>()}
+                            </tbody>
+                        </table>
+                    }
+                }
+                <ul class="text-sm">
+                    {report.linked.iter().filter(|linked| linked.moved + linked.dropped > 0).map(|linked| html! {
+                        <li>
+                            {format!("{}: {} moved", linked.table, linked.moved)}
+                            if linked.dropped > 0 {
+                                <span class="text-yellow-600">{format!(", {} dropped as the kept record already has them", linked.dropped)}</span>
+                            }
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                if !report.committed {
+                    <p class="text-xs text-slate-500">{"Flag histories are combined. The removed record is deleted and can't be brought back."}</p>
+                    <Button color="red" text="Merge" onclick={commit} />
+                }
+            }
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct MergeBoxProps {
+    pub keep: Uuid,
+    pub remove: Uuid,
+    pub refresh_callback: Callback<bool>,
+    pub close_callback: Callback<MouseEvent>,
+}
@@ -0,0 +1,199 @@
+use super::{merge_box::MergeBox, record::*};
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, ModalCallbacks},
+    error::*,
+    pupils::PupilDetails,
+};
+use std::{rc::Rc, str::FromStr};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Likely duplicate pupils and staff accounts, and records with missing or inconsistent data. Pupil
+/// issues open the pupil to fix, and duplicate pupils can be merged.
+#[function_component(DataQualityPage)]
+pub fn data_quality_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN DATA QUALITY PAGE");
+    let (invoke_modal, dismiss_modal) =
+        use_context::<ModalCallbacks>().expect("failed to get modal callbacks");
+    let is_admin = ctx
+        .current_user
+        .roles
+        .iter()
+        .any(|role| role == constant::ROLE_ADMIN);
+    let report: UseStateHandle<Option<QualityReport>> = use_state_eq(|| None);
+    // bumped to check again after a pupil is fixed or merged
+    let refresh = use_state_eq(|| 0);
+    {
+        clone!(ctx, report);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_report(&ctx.auth_token).await {
+                        Ok(fetched) => report.set(Some(fetched)),
+                        Err(error) => {
+                            error!("failed to check data quality:", error.to_string());
+                            report.set(None);
+                        }
+                    }
+                });
+            },
+            *refresh,
+        );
+    }
+
+    let open_pupil = {
+        clone!(ctx, refresh, invoke_modal, dismiss_modal);
+        Callback::from(move |(ev, pupil_id): (MouseEvent, Uuid)| {
+            clone!(ctx, refresh, invoke_modal, dismiss_modal);
+            spawn_local(async move {
+                match fetch_pupil(&pupil_id, &ctx.auth_token).await {
+                    Ok(pupil) => {
+                        let refresh_callback =
+                            Callback::from(move |_: bool| refresh.set(*refresh + 1));
+                        invoke_modal.emit((ev, html!(<PupilDetails pupil={pupil} refresh_callback={refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-200px)]")));
+                    }
+                    Err(error) => {
+                        error!("failed to get pupil:", error.to_string());
+                        if error.kind == ErrorKind::Unauthorized {
+                            ctx.logout_callback.emit(());
+                        }
+                    }
+                }
+            });
+        })
+    };
+    let open_merge = {
+        clone!(refresh);
+        Callback::from(move |(ev, keep, remove): (MouseEvent, Uuid, Uuid)| {
+            clone!(refresh);
+            let refresh_callback = Callback::from(move |_: bool| refresh.set(*refresh + 1));
+            invoke_modal.emit((ev, html!(<MergeBox keep={keep} remove={remove} refresh_callback={refresh_callback} close_callback={&dismiss_modal}/>), classes!("shadow-lg", "rounded-md", "mx-auto", "my-[calc(50vh-200px)]")));
+        })
+    };
+
+    if !is_admin {
+        return html! {
+            <div class="m-3 p-3 shadow-lg rounded-md bg-white">
+                <p>{"Only an admin can check data quality"}</p>
+            </div>
+        };
+    }
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white flex flex-col gap-3">
+            <h2 class="text-xl">{"Data quality"}</h2>
+            if let Some(report) = &*report {
+                <span class="text-sm text-slate-500">
+                    {format!("{} pupils and {} users checked", report.checked_pupils, report.checked_users)}
+                </span>
+                <h3 class="text-lg">{"Possible duplicate pupils"}</h3>
+                if report.pupil_duplicates.is_empty() {
+                    <p class="text-sm">{"None found"}</p>
+                } else {
+                    <table class="w-full text-sm text-left">
+                        <thead>
+                            <tr>
+                                <th>{"Pupil"}</th>
+                                <th>{"Pupil"}</th>
+                                <th>{"Match"}</th>
+                                <th>{"Why"}</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.pupil_duplicates.iter().map(|pair| {
+                                let describe = |pupil: &PupilSummary| format!(
+                                    "{} {}, year {}{}{}",
+                                    pupil.first_names,
+                                    pupil.last_name,
+                                    pupil.year,
+                                    pupil.date_of_birth.map(|date| format!(", born {}", date.format("%d/%m/%Y"))).unwrap_or_default(),
+                                    if pupil.active { "" } else { ", left" },
+                                );
+                                let (keep, remove) = (pair.first.id, pair.second.id);
+                                html! {
+                                    <tr key={format!("{keep}{remove}")} class="border-t border-slate-200">
+                                        <td>{describe(&pair.first)}</td>
+                                        <td>{describe(&pair.second)}</td>
+                                        <td>{format!("{:.0}%", pair.score * 100.0)}</td>
+                                        <td>{pair.reasons.join(", ")}</td>
+                                        <td>
+                                            <Button color="yellow" text="Merge" onclick={
+                                                clone!(open_merge);
+                                                Callback::from(move |ev: MouseEvent| open_merge.emit((ev, keep, remove)))
+                                            } />
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                }
+                <h3 class="text-lg">{"Possible duplicate users"}</h3>
+                if report.user_duplicates.is_empty() {
+                    <p class="text-sm">{"None found"}</p>
+                } else {
+                    <table class="w-full text-sm text-left">
+                        <thead>
+                            <tr>
+                                <th>{"User"}</th>
+                                <th>{"User"}</th>
+                                <th>{"Why"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.user_duplicates.iter().map(|pair| {
+                                let describe = |user: &UserSummary| format!("{} {} ({})", user.first_names, user.last_name, user.email_address);
+                                html! {
+                                    <tr class="border-t border-slate-200">
+                                        <td>{describe(&pair.first)}</td>
+                                        <td>{describe(&pair.second)}</td>
+                                        <td>{pair.reasons.join(", ")}</td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                }
+                <h3 class="text-lg">{"Missing or inconsistent data"}</h3>
+                if report.issues.is_empty() {
+                    <p class="text-sm">{"None found"}</p>
+                } else {
+                    <table class="w-full text-sm text-left">
+                        <thead>
+                            <tr>
+                                <th>{"Record"}</th>
+                                <th>{"Field"}</th>
+                                <th>{"Problem"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {report.issues.iter().map(|issue| {
+                                let row = html! {
+                                    <>
+                                        <td>{&issue.name}</td>
+                                        <td>{issue.field.replace('_', " ")}</td>
+                                        <td class="text-red-500">{&issue.message}</td>
+                                    </>
+                                };
+                                // users are fixed from the users page, pupils straight from here
+                                match Uuid::from_str(&issue.id).ok().filter(|_| issue.subject == "pupil") {
+                                    Some(pupil_id) => html! {
+                                        <tr class="border-t border-slate-200 cursor-pointer hover:bg-slate-100" onclick={
+                                            clone!(open_pupil);
+                                            Callback::from(move |ev: MouseEvent| open_pupil.emit((ev, pupil_id)))
+                                        }>{row}</tr>
+                                    },
+                                    None => html!(<tr class="border-t border-slate-200" title={issue.id.clone()}>{row}</tr>),
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                }
+            }
+        </div>
+    }
+}
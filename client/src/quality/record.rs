@@ -0,0 +1,140 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+    pupils::Pupil,
+};
+use chrono::NaiveDate;
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct PupilSummary {
+    pub id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    pub year: i32,
+    #[serde(default)]
+    pub date_of_birth: Option<NaiveDate>,
+    pub start_date: NaiveDate,
+    pub active: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct PupilPair {
+    pub first: PupilSummary,
+    pub second: PupilSummary,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct UserSummary {
+    pub email_address: String,
+    pub first_names: String,
+    pub last_name: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct UserPair {
+    pub first: UserSummary,
+    pub second: UserSummary,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Issue {
+    pub subject: String,
+    /// the pupil's id or the user's email address
+    pub id: String,
+    pub name: String,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct QualityReport {
+    pub checked_pupils: usize,
+    pub checked_users: usize,
+    pub pupil_duplicates: Vec<PupilPair>,
+    pub user_duplicates: Vec<UserPair>,
+    pub issues: Vec<Issue>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct MergeRequest {
+    pub keep: Uuid,
+    pub remove: Uuid,
+    /// fields to take from the pupil being removed
+    pub take: Vec<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Difference {
+    pub field: String,
+    pub keep: Value,
+    pub remove: Value,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct LinkedRows {
+    pub table: String,
+    pub moved: usize,
+    pub dropped: usize,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct MergeReport {
+    pub merged: Pupil,
+    pub removed: Pupil,
+    pub differences: Vec<Difference>,
+    pub linked: Vec<LinkedRows>,
+    pub committed: bool,
+}
+
+pub async fn fetch_report(token: &str) -> Result<QualityReport> {
+    let response = Request::get(constant::QUALITY_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<QualityReport>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Returns the server's field errors if it refused the merge
+pub async fn run_merge(
+    merge: &MergeRequest,
+    token: &str,
+) -> Result<std::result::Result<MergeReport, HashMap<String, String>>> {
+    let response = Request::post(&format!("{}/merge", constant::QUALITY_PATH))
+        .json(merge)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<MergeReport>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// The pupil an issue is about, to open their record and fix it
+pub async fn fetch_pupil(pupil_id: &Uuid, token: &str) -> Result<Pupil> {
+    let response = Request::get(&format!("{}/{pupil_id}", constant::PUPILS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Pupil>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -0,0 +1,4 @@
+mod page;
+mod report;
+
+pub use page::ReportsPage;
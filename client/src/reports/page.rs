@@ -0,0 +1,361 @@
+use super::report::*;
+use crate::{
+    app::AppContext,
+    constant,
+    elements::{Button, IconButton},
+    error::*,
+    utils::use_download,
+};
+use chrono::{NaiveDate, Utc};
+use std::{collections::HashMap, rc::Rc};
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// End of term reports: admins write the templates, and teachers check each pupil's PDF, sign it
+/// off and download the year's reports as one ZIP.
+#[function_component(ReportsPage)]
+pub fn reports_page() -> Html {
+    let ctx = use_context::<Rc<AppContext>>().expect("NO CTX IN REPORTS PAGE");
+    let is_admin = ctx
+        .current_user
+        .roles
+        .iter()
+        .any(|role| role == constant::ROLE_ADMIN);
+    let templates: UseStateHandle<Vec<ReportTemplate>> = use_state_eq(Vec::new);
+    let selected: UseStateHandle<Option<Uuid>> = use_state_eq(|| None);
+    // the template being written, shown to admins only
+    let draft: UseStateHandle<Option<ReportTemplate>> = use_state_eq(|| None);
+    let year = use_state_eq(|| {
+        ctx.current_user
+            .years
+            .first()
+            .map(|y| *y as i32)
+            .unwrap_or_default()
+    });
+    let statuses: UseStateHandle<Vec<ReportStatus>> = use_state_eq(Vec::new);
+    // bumped to fetch the templates or statuses again after a change
+    let refresh = use_state_eq(|| 0);
+    let errors: UseStateHandle<HashMap<String, String>> = use_state_eq(HashMap::new);
+    let file = use_download();
+    {
+        clone!(ctx, templates, selected);
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match fetch_templates(&ctx.auth_token).await {
+                        Ok(fetched) => {
+                            if selected.is_none() {
+                                selected.set(fetched.first().and_then(|t| t.id));
+                            }
+                            templates.set(fetched);
+                        }
+                        Err(error) => {
+                            error!("failed to get report templates:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            *refresh,
+        );
+    }
+    {
+        clone!(ctx, statuses);
+        use_effect_with_deps(
+            move |(selected, year, _): &(Option<Uuid>, i32, i32)| {
+                let (selected, year) = (*selected, *year);
+                spawn_local(async move {
+                    let Some(id) = selected else {
+                        statuses.set(vec![]);
+                        return;
+                    };
+                    match fetch_statuses(&id, year, &ctx.auth_token).await {
+                        Ok(fetched) => statuses.set(fetched),
+                        Err(error) => {
+                            error!("failed to get report sign-offs:", error.to_string());
+                            if error.kind == ErrorKind::Unauthorized {
+                                ctx.logout_callback.emit(());
+                            }
+                        }
+                    }
+                });
+            },
+            (*selected, *year, *refresh),
+        );
+    }
+
+    let choose = {
+        clone!(selected, draft, errors);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            selected.set(target.value().parse::<Uuid>().ok());
+            draft.set(None);
+            errors.set(HashMap::new());
+        })
+    };
+    let choose_year = {
+        clone!(year);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            if let Ok(value) = target.value().parse() {
+                year.set(value);
+            }
+        })
+    };
+    let new_template = {
+        clone!(draft, errors);
+        Callback::from(move |_: MouseEvent| {
+            let today = Utc::now().date_naive();
+            draft.set(Some(ReportTemplate {
+                id: None,
+                name: String::new(),
+                body: "# {{report}} for {{name}}\n\n{{comments}}\n\n# Results\n{{assessments}}\n\n# Attendance\n{{name}} attended {{attendance}}.".into(),
+                from_date: today,
+                to_date: today,
+                updated_by: None,
+            }));
+            errors.set(HashMap::new());
+        })
+    };
+    let edit_template = {
+        clone!(draft, templates, selected, errors);
+        Callback::from(move |_: MouseEvent| {
+            draft.set(templates.iter().find(|t| t.id == *selected).cloned());
+            errors.set(HashMap::new());
+        })
+    };
+    let update_draft = {
+        clone!(draft);
+        Callback::from(move |ev: Event| {
+            let Some(mut next) = (*draft).clone() else {
+                return;
+            };
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            let value = target.value();
+            match target.id().as_str() {
+                "report_name" => next.name = value,
+                "report_from" => {
+                    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                        next.from_date = date;
+                    }
+                }
+                "report_to" => {
+                    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                        next.to_date = date;
+                    }
+                }
+                _ => {}
+            }
+            draft.set(Some(next));
+        })
+    };
+    let update_body = {
+        clone!(draft);
+        Callback::from(move |ev: Event| {
+            let target: HtmlInputElement = ev.target_unchecked_into();
+            if let Some(mut next) = (*draft).clone() {
+                next.body = target.value();
+                draft.set(Some(next));
+            }
+        })
+    };
+    let save = {
+        clone!(ctx, draft, selected, refresh, errors);
+        Callback::from(move |_: MouseEvent| {
+            let Some(template) = (*draft).clone() else {
+                return;
+            };
+            clone!(ctx, draft, selected, refresh, errors);
+            spawn_local(async move {
+                match save_template(&template, &ctx.auth_token).await {
+                    Ok(Ok(saved)) => {
+                        errors.set(HashMap::new());
+                        draft.set(None);
+                        selected.set(saved.id);
+                        refresh.set(*refresh + 1);
+                    }
+                    Ok(Err(fields)) => errors.set(fields),
+                    Err(error) => error!("failed to save report template:", error.to_string()),
+                }
+            });
+        })
+    };
+    let remove = {
+        clone!(ctx, draft, selected, refresh);
+        Callback::from(move |_: MouseEvent| {
+            let Some(id) = draft.as_ref().and_then(|t| t.id) else {
+                draft.set(None);
+                return;
+            };
+            clone!(ctx, draft, selected, refresh);
+            spawn_local(async move {
+                match delete_template(&id, &ctx.auth_token).await {
+                    Ok(()) => {
+                        draft.set(None);
+                        selected.set(None);
+                        refresh.set(*refresh + 1);
+                    }
+                    Err(error) => error!("failed to delete report template:", error.to_string()),
+                }
+            });
+        })
+    };
+    let download = {
+        clone!(ctx, selected, year, file);
+        Callback::from(move |pupil: Option<(Uuid, String)>| {
+            let Some(id) = *selected else {
+                return;
+            };
+            let year = *year;
+            clone!(ctx, file);
+            spawn_local(async move {
+                match fetch_file(&id, year, pupil.as_ref().map(|p| p.0), &ctx.auth_token).await {
+                    Ok(data) => {
+                        let (name, content_type) = match &pupil {
+                            Some((_, name)) => (format!("{name}.pdf"), "application/pdf"),
+                            None => (format!("reports_year_{year}.zip"), "application/zip"),
+                        };
+                        file.save(data.as_slice(), content_type, &name);
+                    }
+                    Err(error) => error!("failed to download reports:", error.to_string()),
+                }
+            });
+        })
+    };
+    let toggle_sign_off = {
+        clone!(ctx, selected, refresh);
+        Callback::from(move |(pupil_id, signed): (Uuid, bool)| {
+            let Some(id) = *selected else {
+                return;
+            };
+            clone!(ctx, refresh);
+            spawn_local(async move {
+                match set_sign_off(&id, &pupil_id, signed, &ctx.auth_token).await {
+                    Ok(()) => refresh.set(*refresh + 1),
+                    Err(error) => error!("failed to change sign-off:", error.to_string()),
+                }
+            });
+        })
+    };
+    let signed_off = statuses
+        .iter()
+        .filter(|status| status.signed_off_by.is_some())
+        .count();
+
+    html! {
+        <div class="m-3 p-3 shadow-lg rounded-md bg-white flex flex-col gap-3">
+            <div class="flex gap-2 items-center">
+                <h2 class="text-xl">{"Reports"}</h2>
+                <select id="report_template" class="border-2 border-slate-200 rounded-md" onchange={choose}>
+                    if templates.is_empty() {
+                        <option value="">{"No reports yet"}</option>
+                    }
+                    {templates.iter().map(|template| html! {
+                        <option value={template.id.map(|id| id.to_string()).unwrap_or_default()} selected={template.id == *selected}>
+                            {format!("{} ({} to {})", template.name, template.from_date.format("%d/%m/%Y"), template.to_date.format("%d/%m/%Y"))}
+                        </option>
+                    }).collect::<Html>()}
+                </select>
+                <select id="report_year" class="border-2 border-slate-200 rounded-md" onchange={choose_year}>
+                    {ctx.current_user.years.iter().map(|y| html! {
+                        <option value={y.to_string()} selected={*year == *y as i32}>{format!("Year {y}")}</option>
+                    }).collect::<Html>()}
+                </select>
+                if is_admin {
+                    <Button color="blue" text="New report" onclick={new_template} />
+                    if selected.is_some() {
+                        <Button color="blue" text="Edit report" onclick={edit_template} />
+                    }
+                }
+            </div>
+            if let Some(template) = &*draft {
+                <div class="flex gap-6 text-sm">
+                    <div class="flex flex-col gap-2 grow">
+                        <input type="text" id="report_name" placeholder="Report name" class="border-2 border-slate-200 rounded-md" value={template.name.clone()} onchange={&update_draft}/>
+                        <div class="flex gap-2 items-center">
+                            <label class="flex items-center gap-1">
+                                {"From"}
+                                <input type="date" id="report_from" class="border-2 border-slate-200 rounded-md" value={template.from_date.format("%Y-%m-%d").to_string()} onchange={&update_draft}/>
+                            </label>
+                            <label class="flex items-center gap-1">
+                                {"to"}
+                                <input type="date" id="report_to" class="border-2 border-slate-200 rounded-md" value={template.to_date.format("%Y-%m-%d").to_string()} onchange={&update_draft}/>
+                            </label>
+                        </div>
+                        <textarea id="report_body" class="border-2 border-slate-200 rounded-md font-mono" rows="16" value={template.body.clone()} onchange={update_body}/>
+                        <p class="text-xs text-slate-500">{"Start a line with # for a heading or - for a bullet point. Leave a blank line between paragraphs."}</p>
+                        {errors.iter().map(|(field, error)| html!(<span class="text-xs text-red-500">{format!("{}: {error}", field.replace('_', " "))}</span>)).collect::<Html>()}
+                        <div class="flex gap-2">
+                            <Button color="green" text="Save" onclick={save} />
+                            <Button color="red" text={if template.id.is_some() { "Delete" } else { "Cancel" }} onclick={remove} />
+                        </div>
+                    </div>
+                    <table class="text-xs text-left self-start">
+                        {PLACEHOLDERS.iter().map(|(placeholder, meaning)| html! {
+                            <tr>
+                                <td class="font-mono pr-2">{*placeholder}</td>
+                                <td>{*meaning}</td>
+                            </tr>
+                        }).collect::<Html>()}
+                    </table>
+                </div>
+            }
+            if selected.is_some() {
+                <div class="flex gap-2 items-center">
+                    <span class="text-sm text-slate-500">{format!("{signed_off} of {} signed off", statuses.len())}</span>
+                    <Button color="green" text="Download ZIP" onclick={
+                        clone!(download);
+                        Callback::from(move |_: MouseEvent| download.emit(None))
+                    }/>
+                </div>
+                <table class="w-full text-sm text-left">
+                    <thead>
+                        <tr>
+                            <th>{"Pupil"}</th>
+                            <th>{"Signed off"}</th>
+                            <th>{"PDF"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {statuses.iter().map(|status| {
+                            let pupil_id = status.pupil_id;
+                            let signed = status.signed_off_by.is_some();
+                            let name = format!("{}_{}", status.last_name, status.first_names).replace(' ', "_");
+                            html! {
+                                <tr key={pupil_id.to_string()} class="border-t border-slate-200">
+                                    <td>{format!("{} {}", status.first_names, status.last_name)}</td>
+                                    <td>
+                                        <label class="flex items-center gap-1">
+                                            <input type="checkbox" checked={signed} onchange={
+                                                clone!(toggle_sign_off);
+                                                Callback::from(move |_: Event| toggle_sign_off.emit((pupil_id, !signed)))
+                                            }/>
+                                            {match (&status.signed_off_by, status.signed_off_at) {
+                                                (Some(by), Some(at)) => format!("{by}, {}", at.format("%d/%m/%Y")),
+                                                _ => String::new(),
+                                            }}
+                                        </label>
+                                    </td>
+                                    <td>
+                                        <IconButton icon="download" onclick={
+                                            clone!(download);
+                                            Callback::from(move |_: MouseEvent| download.emit(Some((pupil_id, name.clone()))))
+                                        }/>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+                if statuses.is_empty() {
+                    <p>{"There are no pupils on roll in this year"}</p>
+                }
+            }
+            <a ref={file.link.clone()} class="hidden"></a>
+        </div>
+    }
+}
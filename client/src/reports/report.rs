@@ -0,0 +1,156 @@
+use crate::{
+    constant,
+    error::{ErrorResponse, Result},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// What the server fills in, shown next to the editor as a reminder
+pub static PLACEHOLDERS: [(&str, &str); 18] = [
+    ("{{name}}", "the name the pupil goes by"),
+    ("{{first_names}}", "first names as registered"),
+    ("{{last_name}}", "last name as registered"),
+    ("{{year}}", "year group"),
+    ("{{gender}}", "gender"),
+    ("{{date_of_birth}}", "date of birth"),
+    ("{{upn}}", "UPN"),
+    ("{{start_date}}", "date they started"),
+    ("{{school}}", "school name"),
+    ("{{report}}", "the report's name"),
+    ("{{today}}", "the date it's printed"),
+    ("{{from}}", "start of the report"),
+    ("{{to}}", "end of the report"),
+    ("{{attendance}}", "sessions attended out of possible"),
+    ("{{attendance_percentage}}", "attendance as a percentage"),
+    ("{{comments}}", "shared comments, on a line of its own"),
+    ("{{comments:<category>}}", "shared comments in one category"),
+    (
+        "{{assessments}}",
+        "assessment results, on a line of its own",
+    ),
+];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ReportTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    pub name: String,
+    pub body: String,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    #[serde(default, skip_serializing)]
+    pub updated_by: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct ReportStatus {
+    pub pupil_id: Uuid,
+    pub first_names: String,
+    pub last_name: String,
+    #[serde(default)]
+    pub signed_off_by: Option<String>,
+    #[serde(default)]
+    pub signed_off_at: Option<NaiveDateTime>,
+}
+
+pub async fn fetch_templates(token: &str) -> Result<Vec<ReportTemplate>> {
+    let response = Request::get(constant::REPORTS_PATH)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<ReportTemplate>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Creates the template when it has no id yet. Returns the server's field errors if it rejected it.
+pub async fn save_template(
+    template: &ReportTemplate,
+    token: &str,
+) -> Result<std::result::Result<ReportTemplate, HashMap<String, String>>> {
+    let request = match template.id {
+        Some(id) => Request::post(&format!("{}/{id}", constant::REPORTS_PATH)),
+        None => Request::put(constant::REPORTS_PATH),
+    };
+    let response = request
+        .json(template)?
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(Ok(response.json::<ReportTemplate>().await?)),
+        400 => Ok(Err(response.json::<ErrorResponse>().await?.into_fields())),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn delete_template(id: &Uuid, token: &str) -> Result<()> {
+    let response = Request::delete(&format!("{}/{id}", constant::REPORTS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+pub async fn fetch_statuses(id: &Uuid, year: i32, token: &str) -> Result<Vec<ReportStatus>> {
+    let response = Request::get(&format!("{}/{id}/years/{year}", constant::REPORTS_PATH))
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.json::<Vec<ReportStatus>>().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// A pupil's PDF, or with no pupil the ZIP of the whole year's
+pub async fn fetch_file(
+    id: &Uuid,
+    year: i32,
+    pupil_id: Option<Uuid>,
+    token: &str,
+) -> Result<Vec<u8>> {
+    let url = match pupil_id {
+        Some(pupil_id) => format!("{}/{id}/pupils/{pupil_id}", constant::REPORTS_PATH),
+        None => format!("{}/{id}/years/{year}/zip", constant::REPORTS_PATH),
+    };
+    let response = Request::get(&url)
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(response.binary().await?),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
+
+/// Sign off the pupil's report, or take the sign-off back
+pub async fn set_sign_off(id: &Uuid, pupil_id: &Uuid, signed: bool, token: &str) -> Result<()> {
+    let url = format!("{}/{id}/pupils/{pupil_id}/sign-off", constant::REPORTS_PATH);
+    let request = if signed {
+        Request::put(&url)
+    } else {
+        Request::delete(&url)
+    };
+    let response = request
+        .header("Authorization", &format!("Bearer {token}"))
+        .send()
+        .await?;
+    match response.status() {
+        200 => Ok(()),
+        401 => Err(Unauthorized!()),
+        unknown => Err(ServerError!(format!("unknown status code {unknown}"))),
+    }
+}
@@ -7,6 +7,34 @@ pub enum Route {
     Login,
     #[at("/pupils")]
     ManagePupils,
+    #[at("/class-lists")]
+    ClassLists,
+    #[at("/comments")]
+    Comments,
+    #[at("/concerns")]
+    Concerns,
+    #[at("/attendance")]
+    Attendance,
+    #[at("/progression")]
+    Progression,
+    #[at("/interventions")]
+    Interventions,
+    #[at("/aln")]
+    AlnRegister,
+    #[at("/allergies")]
+    Allergies,
+    #[at("/analytics")]
+    Analytics,
+    #[at("/attainment-gap")]
+    AttainmentGap,
+    #[at("/reports")]
+    Reports,
+    #[at("/census")]
+    Census,
+    #[at("/data-quality")]
+    DataQuality,
+    #[at("/assessments")]
+    Assessments,
     #[at("/users")]
     ManageUsers,
 }
@@ -1,3 +1,4 @@
+use crate::constant;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -6,4 +7,26 @@ pub struct User {
     pub last_name: String,
     pub email_address: String,
     pub years: Vec<u32>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl User {
+    pub fn is_dsl(&self) -> bool {
+        self.roles.iter().any(|role| role == constant::ROLE_DSL)
+    }
+
+    /// The ALNCo or an admin, who keep the ALN register and write IDPs
+    pub fn is_alnco(&self) -> bool {
+        self.roles
+            .iter()
+            .any(|role| role == constant::ROLE_ALNCO || role == constant::ROLE_ADMIN)
+    }
+
+    /// Medical staff or an admin, who keep pupils' medical records
+    pub fn is_medical(&self) -> bool {
+        self.roles
+            .iter()
+            .any(|role| role == constant::ROLE_MEDICAL || role == constant::ROLE_ADMIN)
+    }
 }
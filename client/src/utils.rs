@@ -1,5 +1,9 @@
 use crate::error::Result;
 use base64::{engine::general_purpose, *};
+use gloo_file::{Blob, BlobContents, ObjectUrl};
+use std::rc::Rc;
+use web_sys::HtmlElement;
+use yew::prelude::*;
 
 use crate::users::User;
 
@@ -14,3 +18,32 @@ pub fn decode_auth_token(token: String) -> Result<User> {
         None => Err(DecodeError!()),
     }
 }
+
+/// Hands a file to the browser to save. The page renders `link` as a hidden anchor, which is
+/// clicked to start the download.
+#[derive(Clone)]
+pub struct Download {
+    pub link: NodeRef,
+    // kept until the next download so the browser can still read the file
+    file: UseStateHandle<Option<Rc<ObjectUrl>>>,
+}
+
+impl Download {
+    pub fn save<T: BlobContents>(&self, data: T, content_type: &str, file_name: &str) {
+        let url = ObjectUrl::from(Blob::new_with_options(data, Some(content_type)));
+        if let Some(anchor) = self.link.cast::<HtmlElement>() {
+            let _ = anchor.set_attribute("href", &url);
+            let _ = anchor.set_attribute("download", file_name);
+            anchor.click();
+        }
+        self.file.set(Some(Rc::new(url)));
+    }
+}
+
+#[hook]
+pub fn use_download() -> Download {
+    Download {
+        link: use_node_ref(),
+        file: use_state(|| None),
+    }
+}
@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "aln_register")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pupil_id: Uuid,
+    pub provision: String,
+    pub areas_of_need: String,
+    pub alnco: Option<String>,
+    pub added_on: Date,
+    pub review_date: Date,
+    pub last_reviewed: Option<Date>,
+    pub closed_on: Option<Date>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,17 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "assessment_result")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub assessment_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pupil_id: Uuid,
+    pub raw_score: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "attendance_mark")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pupil_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub date: Date,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub session: String,
+    pub code: String,
+    pub recorded_by: String,
+    pub recorded_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "concern")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub pupil_id: Uuid,
+    pub reporter: String,
+    pub category: String,
+    pub narrative: String,
+    pub actions_taken: String,
+    pub status: String,
+    pub raised_at: DateTime,
+    pub triaged_by: Option<String>,
+    pub triaged_at: Option<DateTime>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime>,
+    pub outcome: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "concern_access")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub concern_id: Uuid,
+    pub accessed_by: String,
+    pub accessed_at: DateTime,
+    pub action: String,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "intervention_member")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub intervention_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pupil_id: Uuid,
+    pub entry_measure: Option<i32>,
+    pub exit_measure: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,2 +1,29 @@
+pub mod aln_register;
+pub mod assessment;
+pub mod assessment_result;
+pub mod attachment;
+pub mod attendance_code;
+pub mod attendance_mark;
+pub mod comment;
+pub mod concern;
+pub mod concern_access;
+pub mod contact;
+pub mod curriculum_area;
+pub mod eal_assessment;
+pub mod flag_period;
+pub mod idp;
+pub mod intervention;
+pub mod intervention_member;
+pub mod intervention_session;
+pub mod medical_item;
+pub mod progression_judgement;
+pub mod progression_step;
 pub mod pupil;
+pub mod pupil_contact;
+pub mod pupil_photo;
+pub mod report_sign_off;
+pub mod report_template;
+pub mod subject_access_log;
+pub mod target;
 pub mod user;
+pub mod what_matters;
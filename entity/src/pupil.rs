@@ -18,6 +18,12 @@ pub struct Model {
     pub additional_learning_needs: bool,
     pub looked_after_child: bool,
     pub gender: String,
+    pub date_of_birth: Option<Date>,
+    #[sea_orm(unique)]
+    pub upn: Option<String>,
+    pub preferred_first_names: Option<String>,
+    pub preferred_last_name: Option<String>,
+    pub home_language: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Default, Deserialize, Serialize)]
+#[sea_orm(table_name = "pupil_photo")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub pupil_id: Uuid,
+    pub uploaded_by: Option<String>,
+    pub uploaded_at: Option<DateTime>,
+    pub consent_withdrawn: bool,
+    pub consent_updated_by: Option<String>,
+    pub consent_updated_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,6 +12,7 @@ pub struct Model {
     pub years: String,
     pub secret: Vec<u8>,
     pub last_refresh: DateTime,
+    pub roles: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -17,7 +17,8 @@ pub fn derive_kind_error(_in: TokenStream) -> TokenStream {
                 #[derive(Clone, Debug, PartialEq)]
                 pub struct Error {
                     pub kind: ErrorKind,
-                    pub message: Option<String>
+                    pub message: Option<String>,
+                    pub fields: Option<std::collections::BTreeMap<String, String>>
                 }
 
                 impl std::fmt::Display for Error {
@@ -42,7 +43,9 @@ pub fn derive_kind_error(_in: TokenStream) -> TokenStream {
                 struct ErrorResponse {
                     error: String,
                     #[serde(skip_serializing_if="Option::is_none")]
-                    details: Option<String>
+                    details: Option<String>,
+                    #[serde(skip_serializing_if="Option::is_none")]
+                    fields: Option<std::collections::BTreeMap<String, String>>
                 }
             }
             .into()
@@ -0,0 +1,149 @@
+#![allow(dead_code)]
+use chrono::{Months, Utc};
+use sea_orm_migration::{prelude::*, sea_orm::ConnectionTrait};
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum AlnRegister {
+    Table,
+    PupilId,
+    Provision,
+    AreasOfNeed,
+    Alnco,
+    AddedOn,
+    ReviewDate,
+    LastReviewed,
+    ClosedOn,
+}
+
+#[derive(Iden)]
+enum Idp {
+    Table,
+    Id,
+    PupilId,
+    Version,
+    Outcomes,
+    Provision,
+    CreatedBy,
+    CreatedAt,
+}
+
+pub async fn build_aln_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(AlnRegister::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AlnRegister::PupilId)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(AlnRegister::Provision).string().not_null())
+                .col(ColumnDef::new(AlnRegister::AreasOfNeed).string().not_null())
+                .col(ColumnDef::new(AlnRegister::Alnco).string())
+                .col(ColumnDef::new(AlnRegister::AddedOn).date().not_null())
+                .col(ColumnDef::new(AlnRegister::ReviewDate).date().not_null())
+                .col(ColumnDef::new(AlnRegister::LastReviewed).date())
+                .col(ColumnDef::new(AlnRegister::ClosedOn).date())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-aln_register-pupil_id")
+                        .from(AlnRegister::Table, AlnRegister::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-aln_register-alnco")
+                        .from(AlnRegister::Table, AlnRegister::Alnco)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::SetNull),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(Idp::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Idp::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(Idp::PupilId).uuid().not_null())
+                .col(ColumnDef::new(Idp::Version).integer().not_null())
+                .col(ColumnDef::new(Idp::Outcomes).text().not_null())
+                .col(ColumnDef::new(Idp::Provision).text().not_null())
+                .col(ColumnDef::new(Idp::CreatedBy).string().not_null())
+                .col(ColumnDef::new(Idp::CreatedAt).date_time().not_null())
+                .index(
+                    Index::create()
+                        .name("idx-idp-pupil_id-version")
+                        .col(Idp::PupilId)
+                        .col(Idp::Version)
+                        .unique(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-idp-pupil_id")
+                        .from(Idp::Table, Idp::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-idp-created_by")
+                        .from(Idp::Table, Idp::CreatedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    register_flagged_pupils(manager.get_connection()).await
+}
+
+/// Put every pupil flagged with additional learning needs on the register, as school maintained
+/// with no ALNCo and a review due in a year. Pupils already on it are left alone.
+pub async fn register_flagged_pupils<C: ConnectionTrait>(db: &C) -> Result<(), DbErr> {
+    let today = Utc::now().date_naive();
+    let review_date = today + Months::new(12);
+    let flagged = Query::select()
+        .column(Pupil::Id)
+        .expr(Expr::val("school"))
+        .expr(Expr::val(""))
+        .expr(Expr::val(today))
+        .expr(Expr::val(review_date))
+        .from(Pupil::Table)
+        .and_where(Expr::col(Pupil::AdditionalLearningNeeds).eq(true))
+        .to_owned();
+    let insert = Query::insert()
+        .into_table(AlnRegister::Table)
+        .columns([
+            AlnRegister::PupilId,
+            AlnRegister::Provision,
+            AlnRegister::AreasOfNeed,
+            AlnRegister::AddedOn,
+            AlnRegister::ReviewDate,
+        ])
+        .select_from(flagged)
+        .map_err(|error| DbErr::Custom(error.to_string()))?
+        .on_conflict(
+            OnConflict::column(AlnRegister::PupilId)
+                .do_nothing()
+                .to_owned(),
+        )
+        .to_owned();
+    db.execute(db.get_database_backend().build(&insert)).await?;
+    Ok(())
+}
+
+pub async fn drop_aln_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for table in [Idp::Table.into_iden(), AlnRegister::Table.into_iden()] {
+        manager
+            .drop_table(Table::drop().table(table).to_owned())
+            .await?;
+    }
+    Ok(())
+}
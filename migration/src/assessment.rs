@@ -0,0 +1,95 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::pupil::Pupil;
+
+#[derive(Iden)]
+enum Assessment {
+    Table,
+    Id,
+    Name,
+    Subject,
+    Year,
+    Date,
+    MaxScore,
+    Standardisation,
+}
+
+#[derive(Iden)]
+enum AssessmentResult {
+    Table,
+    AssessmentId,
+    PupilId,
+    RawScore,
+}
+
+pub async fn build_assessment_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Assessment::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Assessment::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Assessment::Name).string().not_null())
+                .col(ColumnDef::new(Assessment::Subject).string().not_null())
+                .col(ColumnDef::new(Assessment::Year).integer().not_null())
+                .col(ColumnDef::new(Assessment::Date).date().not_null())
+                .col(ColumnDef::new(Assessment::MaxScore).integer().not_null())
+                .col(ColumnDef::new(Assessment::Standardisation).text())
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(AssessmentResult::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AssessmentResult::AssessmentId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(AssessmentResult::PupilId).uuid().not_null())
+                .col(
+                    ColumnDef::new(AssessmentResult::RawScore)
+                        .integer()
+                        .not_null(),
+                )
+                .primary_key(
+                    Index::create()
+                        .col(AssessmentResult::AssessmentId)
+                        .col(AssessmentResult::PupilId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-assessment_result-assessment_id")
+                        .from(AssessmentResult::Table, AssessmentResult::AssessmentId)
+                        .to(Assessment::Table, Assessment::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-assessment_result-pupil_id")
+                        .from(AssessmentResult::Table, AssessmentResult::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_assessment_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(AssessmentResult::Table).to_owned())
+        .await?;
+    manager
+        .drop_table(Table::drop().table(Assessment::Table).to_owned())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    comment::Comment,
+    concern::{build_concern_attachment_table, drop_concern_attachment_table, Concern},
+    pupil::Pupil,
+    user::User,
+};
+
+#[derive(Iden)]
+enum Attachment {
+    Table,
+    Id,
+    PupilId,
+    CommentId,
+    ConcernId,
+    FileName,
+    ContentType,
+    Size,
+    Checksum,
+    StorageKey,
+    ScanStatus,
+    Description,
+    UploadedBy,
+    UploadedAt,
+}
+
+/// Attachments always belong to a pupil, and optionally to one of their comments or concerns. The
+/// file itself lives in the blob store under `storage_key`. Concern attachments used to be kept in
+/// the database, that table goes now they share this one.
+pub async fn build_attachment_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Attachment::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Attachment::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Attachment::PupilId).uuid().not_null())
+                .col(ColumnDef::new(Attachment::CommentId).uuid())
+                .col(ColumnDef::new(Attachment::ConcernId).uuid())
+                .col(ColumnDef::new(Attachment::FileName).string().not_null())
+                .col(ColumnDef::new(Attachment::ContentType).string().not_null())
+                .col(ColumnDef::new(Attachment::Size).big_integer().not_null())
+                .col(ColumnDef::new(Attachment::Checksum).string().not_null())
+                .col(
+                    ColumnDef::new(Attachment::StorageKey)
+                        .string()
+                        .not_null()
+                        .unique_key(),
+                )
+                .col(ColumnDef::new(Attachment::ScanStatus).string().not_null())
+                .col(ColumnDef::new(Attachment::Description).text().not_null())
+                .col(ColumnDef::new(Attachment::UploadedBy).string().not_null())
+                .col(
+                    ColumnDef::new(Attachment::UploadedAt)
+                        .date_time()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attachment-pupil_id")
+                        .from(Attachment::Table, Attachment::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attachment-comment_id")
+                        .from(Attachment::Table, Attachment::CommentId)
+                        .to(Comment::Table, Comment::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attachment-concern_id")
+                        .from(Attachment::Table, Attachment::ConcernId)
+                        .to(Concern::Table, Concern::Id)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attachment-uploaded_by")
+                        .from(Attachment::Table, Attachment::UploadedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_index(
+            Index::create()
+                .name("idx-attachment-pupil_id")
+                .table(Attachment::Table)
+                .col(Attachment::PupilId)
+                .to_owned(),
+        )
+        .await?;
+    drop_concern_attachment_table(manager).await
+}
+
+pub async fn drop_attachment_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    build_concern_attachment_table(manager).await?;
+    manager
+        .drop_table(Table::drop().table(Attachment::Table).to_owned())
+        .await
+}
@@ -0,0 +1,131 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::pupil::Pupil;
+
+#[derive(Iden)]
+enum AttendanceCode {
+    Table,
+    Code,
+    Description,
+    Meaning,
+}
+
+#[derive(Iden)]
+enum AttendanceMark {
+    Table,
+    PupilId,
+    Date,
+    Session,
+    Code,
+    RecordedBy,
+    RecordedAt,
+}
+
+/// The statutory codes a school starts with, which they can change or add to afterwards.
+const DEFAULT_CODES: [(&str, &str, &str); 17] = [
+    ("/", "Present", "present"),
+    ("L", "Late before registers closed", "present"),
+    ("B", "Educated off site", "approved_activity"),
+    ("P", "Approved sporting activity", "approved_activity"),
+    ("V", "Educational visit or trip", "approved_activity"),
+    ("C", "Other authorised circumstances", "authorised"),
+    ("E", "Excluded with no alternative provision", "authorised"),
+    ("H", "Authorised holiday", "authorised"),
+    ("I", "Illness", "authorised"),
+    ("M", "Medical or dental appointment", "authorised"),
+    ("G", "Unauthorised holiday", "unauthorised"),
+    ("N", "No reason yet provided", "unauthorised"),
+    ("O", "Unauthorised absence", "unauthorised"),
+    ("U", "Late after registers closed", "unauthorised"),
+    ("X", "Not required to attend", "not_required"),
+    ("Y", "Enforced closure", "not_required"),
+    ("#", "Planned school closure", "not_required"),
+];
+
+pub async fn build_attendance_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(AttendanceCode::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AttendanceCode::Code)
+                        .string()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(AttendanceCode::Description)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(AttendanceCode::Meaning).string().not_null())
+                .to_owned(),
+        )
+        .await?;
+    let mut insert = Query::insert()
+        .into_table(AttendanceCode::Table)
+        .columns([
+            AttendanceCode::Code,
+            AttendanceCode::Description,
+            AttendanceCode::Meaning,
+        ])
+        .to_owned();
+    for (code, description, meaning) in DEFAULT_CODES {
+        insert.values_panic([code.into(), description.into(), meaning.into()]);
+    }
+    manager.exec_stmt(insert).await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(AttendanceMark::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(AttendanceMark::PupilId).uuid().not_null())
+                .col(ColumnDef::new(AttendanceMark::Date).date().not_null())
+                .col(ColumnDef::new(AttendanceMark::Session).string().not_null())
+                .col(ColumnDef::new(AttendanceMark::Code).string().not_null())
+                .col(
+                    ColumnDef::new(AttendanceMark::RecordedBy)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(AttendanceMark::RecordedAt)
+                        .timestamp()
+                        .not_null(),
+                )
+                .primary_key(
+                    Index::create()
+                        .col(AttendanceMark::PupilId)
+                        .col(AttendanceMark::Date)
+                        .col(AttendanceMark::Session),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attendance_mark-pupil_id")
+                        .from(AttendanceMark::Table, AttendanceMark::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-attendance_mark-code")
+                        .from(AttendanceMark::Table, AttendanceMark::Code)
+                        .to(AttendanceCode::Table, AttendanceCode::Code)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_attendance_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(AttendanceMark::Table).to_owned())
+        .await?;
+    manager
+        .drop_table(Table::drop().table(AttendanceCode::Table).to_owned())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+pub(crate) enum Comment {
+    Table,
+    Id,
+    PupilId,
+    Author,
+    Category,
+    Body,
+    Visibility,
+    CreatedAt,
+    EditedAt,
+}
+
+pub async fn build_comment_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Comment::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Comment::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(Comment::PupilId).uuid().not_null())
+                .col(ColumnDef::new(Comment::Author).string().not_null())
+                .col(ColumnDef::new(Comment::Category).string().not_null())
+                .col(ColumnDef::new(Comment::Body).text().not_null())
+                .col(
+                    ColumnDef::new(Comment::Visibility)
+                        .string()
+                        .not_null()
+                        .default("shared"),
+                )
+                .col(ColumnDef::new(Comment::CreatedAt).date_time().not_null())
+                .col(ColumnDef::new(Comment::EditedAt).date_time())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-comment-pupil_id")
+                        .from(Comment::Table, Comment::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-comment-author")
+                        .from(Comment::Table, Comment::Author)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_comment_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(Comment::Table).to_owned())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+pub(crate) enum Concern {
+    Table,
+    Id,
+    PupilId,
+    Reporter,
+    Category,
+    Narrative,
+    ActionsTaken,
+    Status,
+    RaisedAt,
+    TriagedBy,
+    TriagedAt,
+    ResolvedBy,
+    ResolvedAt,
+    Outcome,
+}
+
+#[derive(Iden)]
+enum ConcernAttachment {
+    Table,
+    Id,
+    ConcernId,
+    FileName,
+    ContentType,
+    Data,
+    UploadedBy,
+    UploadedAt,
+}
+
+#[derive(Iden)]
+enum ConcernAccess {
+    Table,
+    Id,
+    ConcernId,
+    AccessedBy,
+    AccessedAt,
+    Action,
+    PreviousHash,
+    Hash,
+}
+
+// safeguarding records outlive pupils and users, so neither can be deleted while they have concerns,
+// and the access log has no foreign keys at all so nothing can remove entries from it
+pub async fn build_concern_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Concern::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Concern::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(Concern::PupilId).uuid().not_null())
+                .col(ColumnDef::new(Concern::Reporter).string().not_null())
+                .col(ColumnDef::new(Concern::Category).string().not_null())
+                .col(ColumnDef::new(Concern::Narrative).text().not_null())
+                .col(ColumnDef::new(Concern::ActionsTaken).text().not_null().default(""))
+                .col(ColumnDef::new(Concern::Status).string().not_null().default("raised"))
+                .col(ColumnDef::new(Concern::RaisedAt).date_time().not_null())
+                .col(ColumnDef::new(Concern::TriagedBy).string())
+                .col(ColumnDef::new(Concern::TriagedAt).date_time())
+                .col(ColumnDef::new(Concern::ResolvedBy).string())
+                .col(ColumnDef::new(Concern::ResolvedAt).date_time())
+                .col(ColumnDef::new(Concern::Outcome).text())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-concern-pupil_id")
+                        .from(Concern::Table, Concern::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-concern-reporter")
+                        .from(Concern::Table, Concern::Reporter)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    build_concern_attachment_table(manager).await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(ConcernAccess::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ConcernAccess::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ConcernAccess::ConcernId).uuid().not_null())
+                .col(ColumnDef::new(ConcernAccess::AccessedBy).string().not_null())
+                .col(ColumnDef::new(ConcernAccess::AccessedAt).date_time().not_null())
+                .col(ColumnDef::new(ConcernAccess::Action).string().not_null())
+                .col(ColumnDef::new(ConcernAccess::PreviousHash).string().not_null())
+                .col(ColumnDef::new(ConcernAccess::Hash).string().not_null())
+                .to_owned(),
+        )
+        .await
+}
+
+/// Concern attachments were kept in the database until attachments moved to a blob store, see
+/// `attachment`.
+pub async fn build_concern_attachment_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(ConcernAttachment::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(ConcernAttachment::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(ConcernAttachment::ConcernId).uuid().not_null())
+                .col(ColumnDef::new(ConcernAttachment::FileName).string().not_null())
+                .col(ColumnDef::new(ConcernAttachment::ContentType).string().not_null())
+                .col(ColumnDef::new(ConcernAttachment::Data).binary().not_null())
+                .col(ColumnDef::new(ConcernAttachment::UploadedBy).string().not_null())
+                .col(ColumnDef::new(ConcernAttachment::UploadedAt).date_time().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-concern_attachment-concern_id")
+                        .from(ConcernAttachment::Table, ConcernAttachment::ConcernId)
+                        .to(Concern::Table, Concern::Id)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_concern_attachment_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(ConcernAttachment::Table).to_owned())
+        .await
+}
+
+pub async fn drop_concern_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for table in [
+        ConcernAccess::Table.into_iden(),
+        ConcernAttachment::Table.into_iden(),
+        Concern::Table.into_iden(),
+    ] {
+        manager.drop_table(Table::drop().table(table).to_owned()).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::pupil::Pupil;
+
+#[derive(Iden)]
+enum Contact {
+    Table,
+    Id,
+    FirstNames,
+    LastName,
+    Phone,
+    Email,
+    PreferredLanguage,
+}
+
+#[derive(Iden)]
+enum PupilContact {
+    Table,
+    PupilId,
+    ContactId,
+    Relationship,
+    Priority,
+    ParentalResponsibility,
+    NoContact,
+    RestrictionNotes,
+}
+
+pub async fn build_contact_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Contact::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Contact::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(Contact::FirstNames).string().not_null())
+                .col(ColumnDef::new(Contact::LastName).string().not_null())
+                .col(ColumnDef::new(Contact::Phone).string())
+                .col(ColumnDef::new(Contact::Email).string())
+                .col(ColumnDef::new(Contact::PreferredLanguage).string())
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(PupilContact::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(PupilContact::PupilId).uuid().not_null())
+                .col(ColumnDef::new(PupilContact::ContactId).uuid().not_null())
+                .col(
+                    ColumnDef::new(PupilContact::Relationship)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(PupilContact::Priority).integer().not_null())
+                .col(
+                    ColumnDef::new(PupilContact::ParentalResponsibility)
+                        .boolean()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(PupilContact::NoContact).boolean().not_null())
+                .col(ColumnDef::new(PupilContact::RestrictionNotes).text())
+                .primary_key(
+                    Index::create()
+                        .col(PupilContact::PupilId)
+                        .col(PupilContact::ContactId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-pupil_contact-pupil_id")
+                        .from(PupilContact::Table, PupilContact::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-pupil_contact-contact_id")
+                        .from(PupilContact::Table, PupilContact::ContactId)
+                        .to(Contact::Table, Contact::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_contact_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for table in [PupilContact::Table.into_iden(), Contact::Table.into_iden()] {
+        manager
+            .drop_table(Table::drop().table(table).to_owned())
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,310 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+use uuid::Uuid;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum CurriculumArea {
+    Table,
+    Id,
+    Name,
+    Position,
+}
+
+#[derive(Iden)]
+enum WhatMatters {
+    Table,
+    Id,
+    AreaId,
+    Statement,
+    Position,
+}
+
+#[derive(Iden)]
+enum ProgressionStep {
+    Table,
+    Step,
+    Name,
+    TypicalAge,
+}
+
+#[derive(Iden)]
+enum ProgressionJudgement {
+    Table,
+    Id,
+    PupilId,
+    WhatMattersId,
+    Step,
+    Date,
+    JudgedBy,
+    Notes,
+}
+
+/// The six Areas of Learning and Experience and their statements of what matters, as published.
+/// Schools can reword or add to these once they're in.
+const DEFAULT_FRAMEWORK: [(&str, &[&str]); 6] = [
+    (
+        "Expressive Arts",
+        &[
+            "Exploring the expressive arts is essential to developing artistic skills and knowledge and it enables learners to become curious and creative individuals.",
+            "Responding and reflecting, both as artist and audience, is a fundamental part of learning in the expressive arts.",
+            "Creating combines skills and knowledge, drawing on the senses, inspiration and imagination.",
+        ],
+    ),
+    (
+        "Health and Well-being",
+        &[
+            "Developing physical health and well-being has lifelong benefits.",
+            "How we process and respond to our experiences affects our mental health and emotional well-being.",
+            "Our decision-making impacts on the quality of our lives and the lives of others.",
+            "How we engage with social influences shapes who we are and our health and well-being.",
+            "Healthy relationships are fundamental to our well-being.",
+        ],
+    ),
+    (
+        "Humanities",
+        &[
+            "Enquiry, exploration and investigation inspire curiosity about the world, its past, present and future.",
+            "Events and human experiences are complex, and are perceived, interpreted and represented in different ways.",
+            "Our natural world is diverse and dynamic, influenced by processes and human actions.",
+            "Human societies are complex and diverse, and shaped by human actions and beliefs.",
+            "Informed, self-aware citizens engage with the challenges and opportunities that face humanity, and are able to take considered and ethical action.",
+        ],
+    ),
+    (
+        "Languages, Literacy and Communication",
+        &[
+            "Languages connect us.",
+            "Understanding languages is key to understanding the world around us.",
+            "Expressing ourselves through languages is key to communication.",
+            "Literature fires imagination and inspires creativity.",
+        ],
+    ),
+    (
+        "Mathematics and Numeracy",
+        &[
+            "The number system is used to represent and compare relationships between numbers and quantities.",
+            "Algebra uses symbol systems to express the structure of mathematical relationships.",
+            "Geometry focuses on relationships involving shape, space and position, and measurement focuses on quantifying phenomena in the physical world.",
+            "Statistics represent data, probability models chance, and both support informed inferences and decisions.",
+        ],
+    ),
+    (
+        "Science and Technology",
+        &[
+            "Being curious and searching for answers is essential to understanding and predicting phenomena.",
+            "Design thinking and engineering offer technical and creative ways to meet society's needs and wants.",
+            "The world around us is full of living things which depend on each other for survival.",
+            "Matter and the way it behaves defines our universe and shapes our lives.",
+            "Forces and energy provide a foundation for understanding our universe.",
+            "Computation is the foundation for our digital world.",
+        ],
+    ),
+];
+
+/// Progression steps and the age each one broadly relates to.
+const DEFAULT_STEPS: [(i32, i32); 5] = [(1, 5), (2, 8), (3, 11), (4, 14), (5, 16)];
+
+pub async fn build_curriculum_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(CurriculumArea::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(CurriculumArea::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(CurriculumArea::Name).string().not_null())
+                .col(
+                    ColumnDef::new(CurriculumArea::Position)
+                        .integer()
+                        .not_null(),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(WhatMatters::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(WhatMatters::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(WhatMatters::AreaId).uuid().not_null())
+                .col(ColumnDef::new(WhatMatters::Statement).text().not_null())
+                .col(ColumnDef::new(WhatMatters::Position).integer().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-what_matters-area_id")
+                        .from(WhatMatters::Table, WhatMatters::AreaId)
+                        .to(CurriculumArea::Table, CurriculumArea::Id)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(ProgressionStep::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ProgressionStep::Step)
+                        .integer()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ProgressionStep::Name).string().not_null())
+                .col(
+                    ColumnDef::new(ProgressionStep::TypicalAge)
+                        .integer()
+                        .not_null(),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(ProgressionJudgement::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ProgressionJudgement::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(ProgressionJudgement::PupilId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(ProgressionJudgement::WhatMattersId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(ProgressionJudgement::Step)
+                        .integer()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(ProgressionJudgement::Date).date().not_null())
+                .col(
+                    ColumnDef::new(ProgressionJudgement::JudgedBy)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(ProgressionJudgement::Notes).text())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-progression_judgement-pupil_id")
+                        .from(ProgressionJudgement::Table, ProgressionJudgement::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-progression_judgement-what_matters_id")
+                        .from(
+                            ProgressionJudgement::Table,
+                            ProgressionJudgement::WhatMattersId,
+                        )
+                        .to(WhatMatters::Table, WhatMatters::Id)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-progression_judgement-step")
+                        .from(ProgressionJudgement::Table, ProgressionJudgement::Step)
+                        .to(ProgressionStep::Table, ProgressionStep::Step)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-progression_judgement-judged_by")
+                        .from(ProgressionJudgement::Table, ProgressionJudgement::JudgedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    seed_framework(manager).await
+}
+
+async fn seed_framework(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let mut areas = Query::insert()
+        .into_table(CurriculumArea::Table)
+        .columns([
+            CurriculumArea::Id,
+            CurriculumArea::Name,
+            CurriculumArea::Position,
+        ])
+        .to_owned();
+    let mut statements = Query::insert()
+        .into_table(WhatMatters::Table)
+        .columns([
+            WhatMatters::Id,
+            WhatMatters::AreaId,
+            WhatMatters::Statement,
+            WhatMatters::Position,
+        ])
+        .to_owned();
+    for (area_position, (name, what_matters)) in DEFAULT_FRAMEWORK.iter().enumerate() {
+        let area_id = Uuid::new_v4();
+        areas.values_panic([
+            area_id.into(),
+            (*name).into(),
+            (area_position as i32).into(),
+        ]);
+        for (position, statement) in what_matters.iter().enumerate() {
+            statements.values_panic([
+                Uuid::new_v4().into(),
+                area_id.into(),
+                (*statement).into(),
+                (position as i32).into(),
+            ]);
+        }
+    }
+    let mut steps = Query::insert()
+        .into_table(ProgressionStep::Table)
+        .columns([
+            ProgressionStep::Step,
+            ProgressionStep::Name,
+            ProgressionStep::TypicalAge,
+        ])
+        .to_owned();
+    for (step, typical_age) in DEFAULT_STEPS {
+        steps.values_panic([
+            step.into(),
+            format!("Progression step {step}").into(),
+            typical_age.into(),
+        ]);
+    }
+    manager.exec_stmt(areas).await?;
+    manager.exec_stmt(statements).await?;
+    manager.exec_stmt(steps).await
+}
+
+pub async fn drop_curriculum_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for table in [
+        ProgressionJudgement::Table.into_iden(),
+        ProgressionStep::Table.into_iden(),
+        WhatMatters::Table.into_iden(),
+        CurriculumArea::Table.into_iden(),
+    ] {
+        manager
+            .drop_table(Table::drop().table(table).to_owned())
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum EalAssessment {
+    Table,
+    Id,
+    PupilId,
+    Stage,
+    HomeLanguage,
+    AssessedOn,
+    AssessedBy,
+    Notes,
+}
+
+pub async fn build_eal_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(EalAssessment::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(EalAssessment::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(EalAssessment::PupilId).uuid().not_null())
+                .col(ColumnDef::new(EalAssessment::Stage).string().not_null())
+                .col(ColumnDef::new(EalAssessment::HomeLanguage).string())
+                .col(ColumnDef::new(EalAssessment::AssessedOn).date().not_null())
+                .col(
+                    ColumnDef::new(EalAssessment::AssessedBy)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(EalAssessment::Notes).text().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-eal_assessment-pupil_id")
+                        .from(EalAssessment::Table, EalAssessment::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-eal_assessment-assessed_by")
+                        .from(EalAssessment::Table, EalAssessment::AssessedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_index(
+            Index::create()
+                .name("idx-eal_assessment-pupil_id-assessed_on")
+                .table(EalAssessment::Table)
+                .col(EalAssessment::PupilId)
+                .col(EalAssessment::AssessedOn)
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_eal_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(EalAssessment::Table).to_owned())
+        .await
+}
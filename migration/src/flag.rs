@@ -0,0 +1,163 @@
+#![allow(dead_code)]
+use chrono::{NaiveDate, Utc};
+use entity::{flag_period, pupil};
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{
+        ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter,
+        QuerySelect,
+    },
+};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum FlagPeriod {
+    Table,
+    Id,
+    PupilId,
+    Flag,
+    StartDate,
+    EndDate,
+    Source,
+    Notes,
+    UpdatedBy,
+    UpdatedAt,
+}
+
+/// Where periods for flags that were set before they had a history say they came from
+pub const BACKFILL_SOURCE: &str = "recorded before flag history";
+
+pub async fn build_flag_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(FlagPeriod::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(FlagPeriod::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(FlagPeriod::PupilId).uuid().not_null())
+                .col(ColumnDef::new(FlagPeriod::Flag).string().not_null())
+                .col(ColumnDef::new(FlagPeriod::StartDate).date().not_null())
+                .col(ColumnDef::new(FlagPeriod::EndDate).date())
+                .col(ColumnDef::new(FlagPeriod::Source).string().not_null())
+                .col(ColumnDef::new(FlagPeriod::Notes).text().not_null())
+                .col(ColumnDef::new(FlagPeriod::UpdatedBy).string())
+                .col(ColumnDef::new(FlagPeriod::UpdatedAt).date_time().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-flag_period-pupil_id")
+                        .from(FlagPeriod::Table, FlagPeriod::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-flag_period-updated_by")
+                        .from(FlagPeriod::Table, FlagPeriod::UpdatedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_index(
+            Index::create()
+                .name("idx-flag_period-pupil_id")
+                .table(FlagPeriod::Table)
+                .col(FlagPeriod::PupilId)
+                .to_owned(),
+        )
+        .await?;
+    backfill_flag_periods(manager.get_connection()).await
+}
+
+/// Just the columns the backfill needs, so it keeps working as the pupil table grows
+#[derive(FromQueryResult)]
+struct Flagged {
+    id: Uuid,
+    start_date: NaiveDate,
+    free_school_meals: bool,
+    looked_after_child: bool,
+    english_as_additional_language: bool,
+    more_able_and_talented: bool,
+    additional_learning_needs: bool,
+}
+
+/// Give every flag that is set on a pupil but has no history an open period from the pupil's start
+/// date, so the flags worked out from periods match what was there before.
+pub async fn backfill_flag_periods<C: ConnectionTrait>(db: &C) -> Result<(), DbErr> {
+    let recorded: HashSet<(Uuid, String)> = flag_period::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|period| (period.pupil_id, period.flag))
+        .collect();
+    let flagged = pupil::Entity::find()
+        .select_only()
+        .columns([
+            pupil::Column::Id,
+            pupil::Column::StartDate,
+            pupil::Column::FreeSchoolMeals,
+            pupil::Column::LookedAfterChild,
+            pupil::Column::EnglishAsAdditionalLanguage,
+            pupil::Column::MoreAbleAndTalented,
+            pupil::Column::AdditionalLearningNeeds,
+        ])
+        .filter(
+            Condition::any()
+                .add(pupil::Column::FreeSchoolMeals.eq(true))
+                .add(pupil::Column::LookedAfterChild.eq(true))
+                .add(pupil::Column::EnglishAsAdditionalLanguage.eq(true))
+                .add(pupil::Column::MoreAbleAndTalented.eq(true))
+                .add(pupil::Column::AdditionalLearningNeeds.eq(true)),
+        )
+        .into_model::<Flagged>()
+        .all(db)
+        .await?;
+    let now = Utc::now().naive_utc();
+    let periods: Vec<flag_period::ActiveModel> = flagged
+        .iter()
+        .flat_map(|pupil| {
+            [
+                ("fsm", pupil.free_school_meals),
+                ("lac", pupil.looked_after_child),
+                ("eal", pupil.english_as_additional_language),
+                ("mat", pupil.more_able_and_talented),
+                ("aln", pupil.additional_learning_needs),
+            ]
+            .into_iter()
+            .filter(|(flag, set)| *set && !recorded.contains(&(pupil.id, flag.to_string())))
+            .map(|(flag, _)| flag_period::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                pupil_id: Set(pupil.id),
+                flag: Set(flag.to_owned()),
+                start_date: Set(pupil.start_date),
+                end_date: Set(None),
+                source: Set(BACKFILL_SOURCE.to_owned()),
+                notes: Set(String::new()),
+                updated_by: Set(None),
+                updated_at: Set(now),
+            })
+        })
+        .collect();
+    for chunk in periods.chunks(500) {
+        flag_period::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn drop_flag_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(FlagPeriod::Table).to_owned())
+        .await
+}
@@ -0,0 +1,226 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum Intervention {
+    Table,
+    Id,
+    Name,
+    Description,
+    LeadStaff,
+    StartDate,
+    EndDate,
+    Measure,
+}
+
+#[derive(Iden)]
+enum InterventionMember {
+    Table,
+    InterventionId,
+    PupilId,
+    EntryMeasure,
+    ExitMeasure,
+}
+
+#[derive(Iden)]
+enum InterventionSession {
+    Table,
+    Id,
+    InterventionId,
+    Date,
+    DurationMinutes,
+    Notes,
+    RecordedBy,
+}
+
+#[derive(Iden)]
+enum Target {
+    Table,
+    Id,
+    PupilId,
+    InterventionId,
+    Description,
+    SuccessCriteria,
+    SetBy,
+    SetOn,
+    ReviewDate,
+    Outcome,
+    ReviewedOn,
+    ReviewNotes,
+}
+
+pub async fn build_intervention_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(Intervention::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Intervention::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Intervention::Name).string().not_null())
+                .col(ColumnDef::new(Intervention::Description).text().not_null())
+                .col(ColumnDef::new(Intervention::LeadStaff).string().not_null())
+                .col(ColumnDef::new(Intervention::StartDate).date().not_null())
+                .col(ColumnDef::new(Intervention::EndDate).date())
+                .col(ColumnDef::new(Intervention::Measure).string().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-intervention-lead_staff")
+                        .from(Intervention::Table, Intervention::LeadStaff)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(InterventionMember::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(InterventionMember::InterventionId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(InterventionMember::PupilId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(InterventionMember::EntryMeasure).integer())
+                .col(ColumnDef::new(InterventionMember::ExitMeasure).integer())
+                .primary_key(
+                    Index::create()
+                        .col(InterventionMember::InterventionId)
+                        .col(InterventionMember::PupilId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-intervention_member-intervention_id")
+                        .from(
+                            InterventionMember::Table,
+                            InterventionMember::InterventionId,
+                        )
+                        .to(Intervention::Table, Intervention::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-intervention_member-pupil_id")
+                        .from(InterventionMember::Table, InterventionMember::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(InterventionSession::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(InterventionSession::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(InterventionSession::InterventionId)
+                        .uuid()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(InterventionSession::Date).date().not_null())
+                .col(
+                    ColumnDef::new(InterventionSession::DurationMinutes)
+                        .integer()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(InterventionSession::Notes).text().not_null())
+                .col(
+                    ColumnDef::new(InterventionSession::RecordedBy)
+                        .string()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-intervention_session-intervention_id")
+                        .from(
+                            InterventionSession::Table,
+                            InterventionSession::InterventionId,
+                        )
+                        .to(Intervention::Table, Intervention::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-intervention_session-recorded_by")
+                        .from(InterventionSession::Table, InterventionSession::RecordedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(Target::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Target::Id).uuid().not_null().primary_key())
+                .col(ColumnDef::new(Target::PupilId).uuid().not_null())
+                .col(ColumnDef::new(Target::InterventionId).uuid())
+                .col(ColumnDef::new(Target::Description).text().not_null())
+                .col(ColumnDef::new(Target::SuccessCriteria).text().not_null())
+                .col(ColumnDef::new(Target::SetBy).string().not_null())
+                .col(ColumnDef::new(Target::SetOn).date().not_null())
+                .col(ColumnDef::new(Target::ReviewDate).date().not_null())
+                .col(ColumnDef::new(Target::Outcome).string())
+                .col(ColumnDef::new(Target::ReviewedOn).date())
+                .col(ColumnDef::new(Target::ReviewNotes).text())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-target-pupil_id")
+                        .from(Target::Table, Target::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-target-intervention_id")
+                        .from(Target::Table, Target::InterventionId)
+                        .to(Intervention::Table, Intervention::Id)
+                        .on_delete(ForeignKeyAction::SetNull),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-target-set_by")
+                        .from(Target::Table, Target::SetBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_intervention_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for table in [
+        Target::Table.into_iden(),
+        InterventionSession::Table.into_iden(),
+        InterventionMember::Table.into_iden(),
+        Intervention::Table.into_iden(),
+    ] {
+        manager
+            .drop_table(Table::drop().table(table).to_owned())
+            .await?;
+    }
+    Ok(())
+}
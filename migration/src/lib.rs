@@ -1,17 +1,72 @@
+mod aln;
+mod attachment;
+mod assessment;
+mod attendance;
+mod comment;
+mod concern;
+mod contact;
+mod curriculum;
+mod eal;
+mod flag;
+mod intervention;
+mod medical;
+mod photo;
 mod pupil;
+mod report;
+mod subject_access;
 mod user;
 mod utils;
 
-pub use crate::{pupil::*, user::*, utils::seed_database};
+pub use crate::{
+    aln::*, assessment::*, attachment::*, attendance::*, comment::*, concern::*, contact::*,
+    curriculum::*, eal::*, flag::*, intervention::*, medical::*, photo::*, pupil::*, report::*,
+    subject_access::*, user::*, utils::seed_database,
+};
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20230301_000002_add_pupil_identity;
+mod m20230315_000003_create_comment_table;
+mod m20230322_000004_create_assessment_tables;
+mod m20230329_000005_add_user_roles;
+mod m20230329_000006_create_concern_tables;
+mod m20230405_000007_create_attendance_tables;
+mod m20230412_000008_create_curriculum_tables;
+mod m20230419_000009_create_intervention_tables;
+mod m20230426_000010_create_aln_tables;
+mod m20230503_000011_create_eal_tables;
+mod m20230510_000012_create_contact_tables;
+mod m20230517_000013_create_medical_tables;
+mod m20230524_000014_create_attachment_tables;
+mod m20230531_000015_create_photo_tables;
+mod m20230607_000016_create_flag_tables;
+mod m20230614_000017_create_report_tables;
+mod m20230621_000018_create_subject_access_tables;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20230301_000002_add_pupil_identity::Migration),
+            Box::new(m20230315_000003_create_comment_table::Migration),
+            Box::new(m20230322_000004_create_assessment_tables::Migration),
+            Box::new(m20230329_000005_add_user_roles::Migration),
+            Box::new(m20230329_000006_create_concern_tables::Migration),
+            Box::new(m20230405_000007_create_attendance_tables::Migration),
+            Box::new(m20230412_000008_create_curriculum_tables::Migration),
+            Box::new(m20230419_000009_create_intervention_tables::Migration),
+            Box::new(m20230426_000010_create_aln_tables::Migration),
+            Box::new(m20230503_000011_create_eal_tables::Migration),
+            Box::new(m20230510_000012_create_contact_tables::Migration),
+            Box::new(m20230517_000013_create_medical_tables::Migration),
+            Box::new(m20230524_000014_create_attachment_tables::Migration),
+            Box::new(m20230531_000015_create_photo_tables::Migration),
+            Box::new(m20230607_000016_create_flag_tables::Migration),
+            Box::new(m20230614_000017_create_report_tables::Migration),
+            Box::new(m20230621_000018_create_subject_access_tables::Migration),
+        ]
     }
 }
@@ -0,0 +1,17 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{add_pupil_identity_columns, drop_pupil_identity_columns};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        add_pupil_identity_columns(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_pupil_identity_columns(manager).await
+    }
+}
@@ -0,0 +1,17 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{build_comment_table, drop_comment_table};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        build_comment_table(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_comment_table(manager).await
+    }
+}
@@ -0,0 +1,17 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{build_assessment_tables, drop_assessment_tables};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        build_assessment_tables(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_assessment_tables(manager).await
+    }
+}
@@ -0,0 +1,17 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{add_user_roles_column, drop_user_roles_column};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        add_user_roles_column(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_user_roles_column(manager).await
+    }
+}
@@ -0,0 +1,17 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{build_concern_tables, drop_concern_tables};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        build_concern_tables(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_concern_tables(manager).await
+    }
+}
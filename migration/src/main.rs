@@ -1,5 +1,18 @@
 use dotenv::dotenv;
 use sea_orm_migration::prelude::*;
+mod aln;
+mod attachment;
+mod assessment;
+mod attendance;
+mod comment;
+mod concern;
+mod contact;
+mod curriculum;
+mod eal;
+mod flag;
+mod intervention;
+mod medical;
+mod photo;
 mod pupil;
 mod user;
 mod utils;
@@ -0,0 +1,78 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum MedicalItem {
+    Table,
+    Id,
+    PupilId,
+    Kind,
+    Name,
+    Severity,
+    Details,
+    Carried,
+    ReviewDate,
+    UpdatedBy,
+    UpdatedAt,
+}
+
+pub async fn build_medical_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(MedicalItem::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(MedicalItem::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(MedicalItem::PupilId).uuid().not_null())
+                .col(ColumnDef::new(MedicalItem::Kind).string().not_null())
+                .col(ColumnDef::new(MedicalItem::Name).string().not_null())
+                .col(ColumnDef::new(MedicalItem::Severity).string())
+                .col(ColumnDef::new(MedicalItem::Details).text().not_null())
+                .col(ColumnDef::new(MedicalItem::Carried).boolean().not_null())
+                .col(ColumnDef::new(MedicalItem::ReviewDate).date())
+                .col(ColumnDef::new(MedicalItem::UpdatedBy).string().not_null())
+                .col(
+                    ColumnDef::new(MedicalItem::UpdatedAt)
+                        .date_time()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-medical_item-pupil_id")
+                        .from(MedicalItem::Table, MedicalItem::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-medical_item-updated_by")
+                        .from(MedicalItem::Table, MedicalItem::UpdatedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_index(
+            Index::create()
+                .name("idx-medical_item-pupil_id")
+                .table(MedicalItem::Table)
+                .col(MedicalItem::PupilId)
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_medical_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(MedicalItem::Table).to_owned())
+        .await
+}
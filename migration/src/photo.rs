@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum PupilPhoto {
+    Table,
+    PupilId,
+    UploadedBy,
+    UploadedAt,
+    ConsentWithdrawn,
+    ConsentUpdatedBy,
+    ConsentUpdatedAt,
+}
+
+pub async fn build_photo_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(PupilPhoto::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(PupilPhoto::PupilId)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(PupilPhoto::UploadedBy).string())
+                .col(ColumnDef::new(PupilPhoto::UploadedAt).date_time())
+                .col(
+                    ColumnDef::new(PupilPhoto::ConsentWithdrawn)
+                        .boolean()
+                        .not_null()
+                        .default(false),
+                )
+                .col(ColumnDef::new(PupilPhoto::ConsentUpdatedBy).string())
+                .col(ColumnDef::new(PupilPhoto::ConsentUpdatedAt).date_time())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-pupil_photo-pupil_id")
+                        .from(PupilPhoto::Table, PupilPhoto::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-pupil_photo-uploaded_by")
+                        .from(PupilPhoto::Table, PupilPhoto::UploadedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-pupil_photo-consent_updated_by")
+                        .from(PupilPhoto::Table, PupilPhoto::ConsentUpdatedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_photo_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(PupilPhoto::Table).to_owned())
+        .await
+}
@@ -9,7 +9,7 @@ use sea_orm_migration::{
 use crate::utils::generate_pupils;
 
 #[derive(Iden)]
-enum Pupil {
+pub(crate) enum Pupil {
     Table,
     Id,
     FirstNames,
@@ -24,6 +24,11 @@ enum Pupil {
     AdditionalLearningNeeds,
     LookedAfterChild,
     Gender,
+    DateOfBirth,
+    Upn,
+    PreferredFirstNames,
+    PreferredLastName,
+    HomeLanguage,
 }
 
 pub async fn build_pupil_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
@@ -55,6 +60,49 @@ pub async fn drop_pupil_table(manager: &SchemaManager<'_>) -> Result<(), DbErr>
     Ok(())
 }
 
+// sqlite can only take one column per alter statement, so each gets its own
+pub async fn add_pupil_identity_columns(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    for column in [
+        ColumnDef::new(Pupil::DateOfBirth).date().to_owned(),
+        ColumnDef::new(Pupil::Upn).string_len(13).to_owned(),
+        ColumnDef::new(Pupil::PreferredFirstNames).string().to_owned(),
+        ColumnDef::new(Pupil::PreferredLastName).string().to_owned(),
+        ColumnDef::new(Pupil::HomeLanguage).string().to_owned(),
+    ] {
+        manager
+            .alter_table(Table::alter().table(Pupil::Table).add_column(&mut column.clone()).to_owned())
+            .await?;
+    }
+    manager
+        .create_index(
+            Index::create()
+                .name("idx-pupil-upn")
+                .table(Pupil::Table)
+                .col(Pupil::Upn)
+                .unique()
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_pupil_identity_columns(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_index(Index::drop().name("idx-pupil-upn").table(Pupil::Table).to_owned())
+        .await?;
+    for column in [
+        Pupil::DateOfBirth,
+        Pupil::Upn,
+        Pupil::PreferredFirstNames,
+        Pupil::PreferredLastName,
+        Pupil::HomeLanguage,
+    ] {
+        manager
+            .alter_table(Table::alter().table(Pupil::Table).drop_column(column).to_owned())
+            .await?;
+    }
+    Ok(())
+}
+
 
 // =================================================================================================================
 
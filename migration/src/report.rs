@@ -0,0 +1,115 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::{pupil::Pupil, user::User};
+
+#[derive(Iden)]
+enum ReportTemplate {
+    Table,
+    Id,
+    Name,
+    Body,
+    FromDate,
+    ToDate,
+    UpdatedBy,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum ReportSignOff {
+    Table,
+    TemplateId,
+    PupilId,
+    SignedOffBy,
+    SignedOffAt,
+}
+
+pub async fn build_report_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(ReportTemplate::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ReportTemplate::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ReportTemplate::Name).string().not_null())
+                .col(ColumnDef::new(ReportTemplate::Body).text().not_null())
+                .col(ColumnDef::new(ReportTemplate::FromDate).date().not_null())
+                .col(ColumnDef::new(ReportTemplate::ToDate).date().not_null())
+                .col(ColumnDef::new(ReportTemplate::UpdatedBy).string())
+                .col(
+                    ColumnDef::new(ReportTemplate::UpdatedAt)
+                        .date_time()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-report_template-updated_by")
+                        .from(ReportTemplate::Table, ReportTemplate::UpdatedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .create_table(
+            Table::create()
+                .table(ReportSignOff::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(ReportSignOff::TemplateId).uuid().not_null())
+                .col(ColumnDef::new(ReportSignOff::PupilId).uuid().not_null())
+                .col(
+                    ColumnDef::new(ReportSignOff::SignedOffBy)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(ReportSignOff::SignedOffAt)
+                        .date_time()
+                        .not_null(),
+                )
+                .primary_key(
+                    Index::create()
+                        .col(ReportSignOff::TemplateId)
+                        .col(ReportSignOff::PupilId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-report_sign_off-template_id")
+                        .from(ReportSignOff::Table, ReportSignOff::TemplateId)
+                        .to(ReportTemplate::Table, ReportTemplate::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-report_sign_off-pupil_id")
+                        .from(ReportSignOff::Table, ReportSignOff::PupilId)
+                        .to(Pupil::Table, Pupil::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-report_sign_off-signed_off_by")
+                        .from(ReportSignOff::Table, ReportSignOff::SignedOffBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_report_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(ReportSignOff::Table).to_owned())
+        .await?;
+    manager
+        .drop_table(Table::drop().table(ReportTemplate::Table).to_owned())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+#![allow(dead_code)]
+use sea_orm_migration::prelude::*;
+
+use crate::user::User;
+
+#[derive(Iden)]
+enum SubjectAccessLog {
+    Table,
+    Id,
+    PupilId,
+    PupilName,
+    Reference,
+    ExportedBy,
+    ExportedAt,
+    Checksum,
+    Redactions,
+    PreviousHash,
+    Hash,
+}
+
+/// The log keeps the pupil's id and name but has no foreign key to them, so it outlives the pupil's
+/// record.
+pub async fn build_subject_access_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(SubjectAccessLog::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(SubjectAccessLog::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(SubjectAccessLog::PupilId).uuid().not_null())
+                .col(
+                    ColumnDef::new(SubjectAccessLog::PupilName)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(SubjectAccessLog::Reference).string())
+                .col(
+                    ColumnDef::new(SubjectAccessLog::ExportedBy)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(SubjectAccessLog::ExportedAt)
+                        .date_time()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(SubjectAccessLog::Checksum)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(SubjectAccessLog::Redactions)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(SubjectAccessLog::PreviousHash)
+                        .string()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(SubjectAccessLog::Hash).string().not_null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-subject_access_log-exported_by")
+                        .from(SubjectAccessLog::Table, SubjectAccessLog::ExportedBy)
+                        .to(User::Table, User::EmailAddress)
+                        .on_delete(ForeignKeyAction::Restrict),
+                )
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_subject_access_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(SubjectAccessLog::Table).to_owned())
+        .await
+}
@@ -8,7 +8,7 @@ use sea_orm_migration::{
 };
 
 #[derive(Iden)]
-enum User {
+pub(crate) enum User {
     Table,
     FirstNames,
     LastName,
@@ -17,6 +17,7 @@ enum User {
     Years,
     Secret,
     LastRefresh,
+    Roles,
 }
 
 pub async fn build_user_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
@@ -41,6 +42,23 @@ pub async fn drop_user_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
     Ok(())
 }
 
+pub async fn add_user_roles_column(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .alter_table(
+            Table::alter()
+                .table(User::Table)
+                .add_column(ColumnDef::new(User::Roles).string().not_null().default(""))
+                .to_owned(),
+        )
+        .await
+}
+
+pub async fn drop_user_roles_column(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .alter_table(Table::alter().table(User::Table).drop_column(User::Roles).to_owned())
+        .await
+}
+
 // =================================================================================================================
 
 pub async fn seed_users(db: &DatabaseConnection) -> Result<(), DbErr> {
@@ -52,6 +70,7 @@ pub async fn seed_users(db: &DatabaseConnection) -> Result<(), DbErr> {
         years: Set("1,6".into()),
         secret: Set(vec![127; 64]),
         last_refresh: Set(Utc::now().naive_local()),
+        roles: Set("dsl".into()),
     }
     .insert(db)
     .await?;
@@ -1,4 +1,7 @@
-use crate::{pupil::seed_pupils, sea_orm::DatabaseConnection, user::seed_users};
+use crate::{
+    aln::register_flagged_pupils, flag::backfill_flag_periods, pupil::seed_pupils,
+    sea_orm::DatabaseConnection, user::seed_users,
+};
 use chrono::{Datelike, NaiveDate};
 use entity::pupil::ActiveModel as Pupil;
 use rand::prelude::*;
@@ -9,17 +12,20 @@ use uuid::Uuid;
 pub async fn seed_database(db: &DatabaseConnection) {
     seed_pupils(db).await.expect("seeding pupils");
     seed_users(db).await.expect("seeding users");
+    register_flagged_pupils(db).await.expect("seeding the ALN register");
+    backfill_flag_periods(db).await.expect("seeding flag periods");
 }
 
 pub fn generate_pupils(n: i32) -> Vec<Pupil> {
     let mut rng = thread_rng();
-    (0..n).into_iter().map(|_| generate_pupil(&mut rng)).collect()
+    (0..n).map(|_| generate_pupil(&mut rng)).collect()
 }
 
 pub fn generate_pupil(rng: &mut ThreadRng) -> Pupil {
     let year = [0i32, 1, 2, 3, 4, 5, 6].choose(rng).unwrap().to_owned();
     let active = [(true, 10), (false, 1)].choose_weighted(rng, |ch| ch.1).unwrap().0;
     let (start_date, end_date) = get_dates_from_year(year, active);
+    let date_of_birth = get_date_of_birth(year, rng);
     let mat = [(true, 1), (false, 10)].choose_weighted(rng, |ch| ch.1).unwrap().0;
     let aln = if mat {
         false
@@ -41,9 +47,32 @@ pub fn generate_pupil(rng: &mut ThreadRng) -> Pupil {
         additional_learning_needs: Set(aln),
         looked_after_child: Set([(true, 1), (false, 8)].choose_weighted(rng, |ch| ch.1).unwrap().0),
         gender: Set(["male", "female"].choose(rng).unwrap().to_string()),
+        date_of_birth: Set(Some(date_of_birth)),
+        upn: Set(Some(generate_upn(rng))),
+        preferred_first_names: Set(None),
+        preferred_last_name: Set(None),
+        home_language: Set(Some(
+            [("English", 12), ("Welsh", 4), ("Polish", 1), ("Arabic", 1)]
+                .choose_weighted(rng, |ch| ch.1)
+                .unwrap()
+                .0
+                .to_string(),
+        )),
     }
 }
 
+/// A UPN for a made up school, with a valid check letter so it passes the server's validation
+pub fn generate_upn(rng: &mut ThreadRng) -> String {
+    const CHECK_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRTUVWXYZ";
+    let digits: String = (0..12).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect();
+    let sum: u32 = digits
+        .chars()
+        .zip(2..)
+        .map(|(c, weight)| c.to_digit(10).unwrap() * weight)
+        .sum();
+    format!("{}{digits}", CHECK_LETTERS[(sum % 23) as usize] as char)
+}
+
 fn get_first_name(rng: &mut ThreadRng) -> String {
     let first_names: Vec<&str> = vec!["Ben", "Gemma Victoria", "Daisy Enfys", "Aaron", "Kevin Huw", "Helen", "Belle", "Tyrion"];
     first_names.choose(rng).expect("no first_names").to_string()
@@ -54,6 +83,12 @@ fn get_last_name(rng: &mut ThreadRng) -> String {
     first_names.choose(rng).expect("no last_names").to_string()
 }
 
+fn get_date_of_birth(year: i32, rng: &mut ThreadRng) -> NaiveDate {
+    // reception pupils turn 5 during the school year that starts in september
+    let born_from = NaiveDate::from_ymd_opt(chrono::Utc::now().year() - year - 5, 9, 1).unwrap();
+    born_from + chrono::Duration::days(rng.gen_range(0..365))
+}
+
 fn get_dates_from_year(year: i32, is_active: bool) -> (NaiveDate, Option<NaiveDate>) {
     let curr_year = chrono::Utc::now().year();
     (
@@ -61,7 +96,7 @@ fn get_dates_from_year(year: i32, is_active: bool) -> (NaiveDate, Option<NaiveDa
         if is_active {
             None
         } else {
-            Some(NaiveDate::from_ymd_opt(curr_year - year, 7, 21).unwrap())
+            Some(NaiveDate::from_ymd_opt(curr_year - year + 1, 7, 21).unwrap())
         }
     )
 }
@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use crate::{
+    aln::{idp::*, register::*},
+    app::state::AppState,
+    core::{constant, error::*},
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+const ALNCO_ONLY: &str = "only the ALNCo or an admin can change the ALN register";
+
+pub async fn get_aln_register(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested the ALN register");
+    match RegisterEntry::open_from_db(&user, state.database().as_ref()).await {
+        Ok(rows) => Ok(Json(json!(rows))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_aln(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested the ALN register entry for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match RegisterEntry::one_from_db(&user, pupil_id, state.database().as_ref()).await {
+        Ok(entry) => Ok(Json(json!(entry))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_pupil_aln(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(update): Json<EntryUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving the ALN register entry for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_ALNCO, ALNCO_ONLY)?;
+    }
+    let existing = RegisterEntry::one_from_db(&user, pupil_id, state.database()).await?;
+    let today = Utc::now().date_naive();
+    let entry = RegisterEntry::from_update(existing, pupil_id, update, today);
+    entry.validate(today, state.database()).await?;
+    match entry.save(state.database().as_ref()).await {
+        Ok(entry) => Ok(Json(json!(entry))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn close_pupil_aln(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("taking pupil {pupil_id} off the ALN register");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_ALNCO, ALNCO_ONLY)?;
+    }
+    let mut entry = match RegisterEntry::one_from_db(&user, pupil_id, state.database()).await? {
+        Some(entry) => entry,
+        None => return Err(ValidationError!("pupil is not on the ALN register")),
+    };
+    entry.closed_on.get_or_insert(Utc::now().date_naive());
+    match entry.save(state.database().as_ref()).await {
+        Ok(entry) => Ok(Json(json!(entry))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_idps(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested IDPs for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Idp::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(idps) => Ok(Json(json!(idps))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_idp_version(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewIdp>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("writing a new IDP version for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_ALNCO, ALNCO_ONLY)?;
+    }
+    let entry = RegisterEntry::one_from_db(&user, pupil_id, state.database()).await?;
+    let versions = Idp::all_for_pupil(&user, pupil_id, state.database()).await?;
+    let idp = Idp::new(pupil_id, &user, new, versions.first());
+    idp.validate(entry.as_ref())?;
+    match idp.insert(state.database().as_ref()).await {
+        Ok(idp) => Ok((StatusCode::CREATED, Json(json!(idp)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    aln::register::RegisterEntry, core::error::Result, pupil::model::Pupil, user::model::User,
+};
+use chrono::{NaiveDateTime, Utc};
+use entity::idp::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// One version of a pupil's individual development plan. Versions are never changed, saving the
+/// plan again adds the next version so earlier ones can still be read.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Idp {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) version: i32,
+    pub(crate) outcomes: String,
+    pub(crate) provision: String,
+    pub(crate) created_by: String,
+    pub(crate) created_at: NaiveDateTime,
+}
+
+impl Idp {
+    /// The next version of the plan after `latest`, or the first if there isn't one yet.
+    pub fn new(pupil_id: Uuid, user: &User, new: NewIdp, latest: Option<&Idp>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            version: latest.map_or(1, |idp| idp.version + 1),
+            outcomes: new.outcomes,
+            provision: new.provision,
+            created_by: user.email_address.clone(),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    /// Every version of the pupil's plan, the current one first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_desc(Column::Version)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!(
+            "inserting IDP version {} for {}",
+            self.version,
+            self.pupil_id
+        );
+        Ok(ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            version: Set(self.version),
+            outcomes: Set(self.outcomes.clone()),
+            provision: Set(self.provision.clone()),
+            created_by: Set(self.created_by.clone()),
+            created_at: Set(self.created_at),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    /// Plans are only written for pupils on the register, and need both sections filled in.
+    pub fn validate(&self, entry: Option<&RegisterEntry>) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if !entry.is_some_and(RegisterEntry::is_open) {
+            errors.insert("pupil_id".into(), "pupil is not on the ALN register".into());
+        }
+        if self.outcomes.trim().is_empty() {
+            errors.insert("outcomes".into(), "outcomes cannot be empty".into());
+        }
+        if self.provision.trim().is_empty() {
+            errors.insert("provision".into(), "provision cannot be empty".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("IDP failed validation").with_fields(errors))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewIdp {
+    outcomes: String,
+    provision: String,
+}
+
+impl From<Model> for Idp {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            version: value.version,
+            outcomes: value.outcomes,
+            provision: value.provision,
+            created_by: value.created_by,
+            created_at: value.created_at,
+        }
+    }
+}
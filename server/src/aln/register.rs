@@ -0,0 +1,344 @@
+use crate::{
+    core::{constant, error::Result},
+    flag::model::{Flag, FlagPeriod, ALN_SOURCE},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::NaiveDate;
+use entity::aln_register::{ActiveModel, Column, Entity, Model};
+use migration::{Condition, Expr, OnConflict};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Who maintains a pupil's individual development plan under the ALN code.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provision {
+    School,
+    LocalAuthority,
+}
+
+impl Provision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provision::School => "school",
+            Provision::LocalAuthority => "local_authority",
+        }
+    }
+
+    /// Anything unrecognised is treated as local authority maintained, the higher level of need.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "school" => Provision::School,
+            _ => Provision::LocalAuthority,
+        }
+    }
+}
+
+/// A pupil's entry on the ALN register. Closing it takes the pupil off the register but keeps the
+/// entry and their IDPs, and the pupil's `additional_learning_needs` flag is kept in step with it.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct RegisterEntry {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) provision: Provision,
+    pub(crate) areas_of_need: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) alnco: Option<String>,
+    pub(crate) added_on: NaiveDate,
+    pub(crate) review_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) last_reviewed: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) closed_on: Option<NaiveDate>,
+}
+
+impl RegisterEntry {
+    /// The pupil's entry, open or closed, or `None` if they have never been on the register.
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Option<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find_by_id(pupil_id).one(db).await?.map(Into::into))
+    }
+
+    /// Everyone on the register in the user's years, the next review due first.
+    pub async fn open_from_db(user: &User, db: &DatabaseConnection) -> Result<Vec<RegisterRow>> {
+        let mut years = Condition::any();
+        for year in &user.years {
+            years = years.add(entity::pupil::Column::Year.eq(*year));
+        }
+        let pupils: HashMap<Uuid, entity::pupil::Model> = entity::pupil::Entity::find()
+            .filter(years)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|pupil| (pupil.id, pupil))
+            .collect();
+        Ok(Entity::find()
+            .filter(Column::ClosedOn.is_null())
+            .filter(Column::PupilId.is_in(pupils.keys().copied()))
+            .order_by_asc(Column::ReviewDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|entry| {
+                let pupil = pupils.get(&entry.pupil_id)?;
+                Some(RegisterRow {
+                    first_names: pupil.first_names.clone(),
+                    last_name: pupil.last_name.clone(),
+                    year: pupil.year,
+                    entry: entry.into(),
+                })
+            })
+            .collect())
+    }
+
+    /// Put the pupil on the register, or change their entry if they're already on it. Reopening a
+    /// closed entry starts it again from today.
+    pub fn from_update(
+        existing: Option<Self>,
+        pupil_id: Uuid,
+        update: EntryUpdate,
+        today: NaiveDate,
+    ) -> Self {
+        let added_on = match existing {
+            Some(entry) if entry.closed_on.is_none() => entry.added_on,
+            _ => today,
+        };
+        Self {
+            pupil_id,
+            provision: update.provision,
+            areas_of_need: update.areas_of_need,
+            alnco: update.alnco,
+            added_on,
+            review_date: update.review_date,
+            last_reviewed: update.last_reviewed,
+            closed_on: None,
+        }
+    }
+
+    /// Save the entry and the pupil's flag together, opening or closing the flag's period.
+    pub async fn save(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("saving ALN register entry {:?}", self);
+        let txn = db.begin().await?;
+        Entity::insert(ActiveModel {
+            pupil_id: Set(self.pupil_id),
+            provision: Set(self.provision.as_str().to_owned()),
+            areas_of_need: Set(self.areas_of_need.join(",")),
+            alnco: Set(self.alnco.clone()),
+            added_on: Set(self.added_on),
+            review_date: Set(self.review_date),
+            last_reviewed: Set(self.last_reviewed),
+            closed_on: Set(self.closed_on),
+        })
+        .on_conflict(
+            OnConflict::column(Column::PupilId)
+                .update_columns([
+                    Column::Provision,
+                    Column::AreasOfNeed,
+                    Column::Alnco,
+                    Column::AddedOn,
+                    Column::ReviewDate,
+                    Column::LastReviewed,
+                    Column::ClosedOn,
+                ])
+                .to_owned(),
+        )
+        .exec(&txn)
+        .await?;
+        entity::pupil::Entity::update_many()
+            .col_expr(
+                entity::pupil::Column::AdditionalLearningNeeds,
+                Expr::value(self.closed_on.is_none()),
+            )
+            .filter(entity::pupil::Column::Id.eq(self.pupil_id))
+            .exec(&txn)
+            .await?;
+        FlagPeriod::set_from(
+            &txn,
+            self.pupil_id,
+            Flag::Aln,
+            self.closed_on.is_none(),
+            self.closed_on.unwrap_or(self.added_on),
+            ALN_SOURCE,
+            None,
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(self.clone())
+    }
+
+    pub async fn validate(&self, today: NaiveDate, db: &DatabaseConnection) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.areas_of_need.is_empty() {
+            errors.insert(
+                "areas_of_need".into(),
+                "choose at least one area of need".into(),
+            );
+        } else if let Some(area) = self
+            .areas_of_need
+            .iter()
+            .find(|area| !constant::ALN_AREAS_OF_NEED.contains(&area.as_str()))
+        {
+            errors.insert(
+                "areas_of_need".into(),
+                format!("{area} is not an area of need"),
+            );
+        }
+        if self.review_date < self.added_on {
+            errors.insert(
+                "review_date".into(),
+                "review date cannot be before the pupil was added to the register".into(),
+            );
+        }
+        if self.last_reviewed.is_some_and(|date| date > today) {
+            errors.insert(
+                "last_reviewed".into(),
+                "last review cannot be in the future".into(),
+            );
+        }
+        if let Some(alnco) = &self.alnco {
+            if User::one_from_db(alnco, db).await.is_err() {
+                errors.insert("alnco".into(), format!("{alnco} is not a user"));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("register entry failed validation").with_fields(errors))
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.closed_on.is_none()
+    }
+}
+
+/// Only the ALNCo or an admin can change the register or write IDPs.
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct RegisterRow {
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) year: i32,
+    #[serde(flatten)]
+    pub(crate) entry: RegisterEntry,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct EntryUpdate {
+    provision: Provision,
+    areas_of_need: Vec<String>,
+    #[serde(default)]
+    alnco: Option<String>,
+    review_date: NaiveDate,
+    #[serde(default)]
+    last_reviewed: Option<NaiveDate>,
+}
+
+impl From<Model> for RegisterEntry {
+    fn from(value: Model) -> Self {
+        Self {
+            pupil_id: value.pupil_id,
+            provision: Provision::from_db(&value.provision),
+            areas_of_need: value
+                .areas_of_need
+                .split(',')
+                .filter(|area| !area.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            alnco: value.alnco,
+            added_on: value.added_on,
+            review_date: value.review_date,
+            last_reviewed: value.last_reviewed,
+            closed_on: value.closed_on,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[fixture]
+    fn update() -> EntryUpdate {
+        serde_json::from_str(
+            r#"{
+                "provision": "local_authority",
+                "areas_of_need": ["cognition_and_learning"],
+                "review_date": "2023-09-01"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    fn reopening_starts_again(update: EntryUpdate) {
+        let entry =
+            RegisterEntry::from_update(None, Uuid::new_v4(), update.clone(), date("2022-09-05"));
+        assert_eq!(entry.added_on, date("2022-09-05"));
+        assert_eq!(entry.provision, Provision::LocalAuthority);
+
+        let kept = RegisterEntry::from_update(
+            Some(entry.clone()),
+            entry.pupil_id,
+            update.clone(),
+            date("2023-01-10"),
+        );
+        assert_eq!(kept.added_on, date("2022-09-05"));
+
+        let closed = RegisterEntry {
+            closed_on: Some(date("2023-02-01")),
+            ..entry.clone()
+        };
+        let reopened =
+            RegisterEntry::from_update(Some(closed), entry.pupil_id, update, date("2023-03-01"));
+        assert_eq!(reopened.added_on, date("2023-03-01"));
+        assert!(reopened.is_open());
+    }
+
+    #[rstest]
+    fn areas_of_need_round_trip() {
+        let entry: RegisterEntry = Model {
+            pupil_id: Uuid::new_v4(),
+            provision: "school".into(),
+            areas_of_need: "cognition_and_learning,sensory_and_physical".into(),
+            added_on: date("2022-09-05"),
+            review_date: date("2023-09-05"),
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(entry.provision, Provision::School);
+        assert_eq!(
+            entry.areas_of_need,
+            vec!["cognition_and_learning", "sensory_and_physical"]
+        );
+
+        let migrated: RegisterEntry = Model {
+            provision: "school".into(),
+            areas_of_need: "".into(),
+            ..Default::default()
+        }
+        .into();
+        assert!(migrated.areas_of_need.is_empty());
+    }
+
+    #[rstest]
+    #[case("school", Provision::School)]
+    #[case("local_authority", Provision::LocalAuthority)]
+    #[case("something else", Provision::LocalAuthority)]
+    fn provision_from_db(#[case] value: &str, #[case] expected: Provision) {
+        assert_eq!(Provision::from_db(value), expected);
+    }
+}
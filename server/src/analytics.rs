@@ -0,0 +1,4 @@
+pub mod cohort;
+pub mod gap;
+pub mod handlers;
+pub mod suppression;
@@ -0,0 +1,454 @@
+use super::suppression::{hide, is_small};
+use crate::{
+    app::config::Config,
+    core::{constant, error::Result},
+    flag::model::{Flag, FlagPeriod},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::NaiveDate;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What pupils can be grouped by. The flags split pupils into those with and without them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Year,
+    Gender,
+    Fsm,
+    Eal,
+    Aln,
+    Mat,
+    Lac,
+}
+
+impl Dimension {
+    fn flag(&self) -> Option<Flag> {
+        match self {
+            Dimension::Year | Dimension::Gender => None,
+            Dimension::Fsm => Some(Flag::Fsm),
+            Dimension::Eal => Some(Flag::Eal),
+            Dimension::Aln => Some(Flag::Aln),
+            Dimension::Mat => Some(Flag::Mat),
+            Dimension::Lac => Some(Flag::Lac),
+        }
+    }
+
+    pub fn key(&self, pupil: &Pupil) -> String {
+        match (self, self.flag()) {
+            (Dimension::Year, _) => pupil.year.to_string(),
+            (Dimension::Gender, _) => pupil.gender.clone(),
+            (_, Some(flag)) if flag.on(pupil) => "yes".into(),
+            _ => "no".into(),
+        }
+    }
+
+    /// Every group in a sensible order. Genders and flags always have all of theirs so charts
+    /// compare like with like, years only the ones there are pupils in.
+    pub fn keys(&self, pupils: &[Pupil]) -> Vec<String> {
+        match self {
+            Dimension::Year => {
+                let mut years: Vec<i32> = pupils.iter().map(|pupil| pupil.year).collect();
+                years.sort_unstable();
+                years.dedup();
+                years.into_iter().map(|year| year.to_string()).collect()
+            }
+            Dimension::Gender => {
+                let mut genders: Vec<String> =
+                    constant::GENDERS.iter().map(|g| g.to_string()).collect();
+                for pupil in pupils {
+                    if !genders.contains(&pupil.gender) {
+                        genders.push(pupil.gender.clone());
+                    }
+                }
+                genders
+            }
+            _ => vec!["yes".into(), "no".into()],
+        }
+    }
+}
+
+/// Whether a pupil was on roll on the date: started, and not yet left. A pupil marked inactive
+/// with no leaving date is taken to have left.
+pub fn on_roll(pupil: &Pupil, date: NaiveDate) -> bool {
+    pupil.start_date <= date
+        && match pupil.end_date {
+            Some(end_date) => date <= end_date,
+            None => pupil.active,
+        }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CohortQuery {
+    #[serde(default)]
+    pub(crate) rows: Option<Dimension>,
+    /// a second dimension for a cross-tab
+    #[serde(default)]
+    pub(crate) columns: Option<Dimension>,
+    #[serde(default)]
+    pub(crate) as_at: Option<NaiveDate>,
+}
+
+/// How many pupils in a group have a flag, or none when that's few enough to identify them.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Share {
+    pub(crate) count: Option<usize>,
+    /// of the group, to one decimal place
+    pub(crate) percentage: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Cell {
+    /// none when the number is suppressed
+    pub(crate) count: Option<usize>,
+    pub(crate) flags: BTreeMap<&'static str, Share>,
+}
+
+/// Headline numbers for the pupils on roll, grouped by one dimension or cross-tabulated by two.
+/// Small numbers are suppressed everywhere, including where they could be worked out from the
+/// totals.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CohortReport {
+    pub(crate) as_at: NaiveDate,
+    pub(crate) threshold: usize,
+    pub(crate) rows: Dimension,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) columns: Option<Dimension>,
+    pub(crate) row_keys: Vec<String>,
+    /// just "all" when there's no second dimension
+    pub(crate) column_keys: Vec<String>,
+    /// a row of cells for each row key, one for each column key
+    pub(crate) cells: Vec<Vec<Cell>>,
+    pub(crate) row_totals: Vec<Cell>,
+    pub(crate) column_totals: Vec<Cell>,
+    pub(crate) total: Cell,
+}
+
+/// Raw counts for a grid of groups, overall and for each flag.
+struct Counts {
+    /// kept as well as the grids, which have no rows to tell it from when there are no pupils
+    columns: usize,
+    pupils: Vec<Vec<usize>>,
+    flags: Vec<(Flag, Vec<Vec<usize>>)>,
+}
+
+impl Counts {
+    fn new(rows: usize, columns: usize) -> Self {
+        Self {
+            columns,
+            pupils: vec![vec![0; columns]; rows],
+            flags: Flag::ALL
+                .into_iter()
+                .map(|flag| (flag, vec![vec![0; columns]; rows]))
+                .collect(),
+        }
+    }
+
+    fn add(&mut self, row: usize, column: usize, pupil: &Pupil) {
+        self.pupils[row][column] += 1;
+        for (flag, counts) in &mut self.flags {
+            if flag.on(pupil) {
+                counts[row][column] += 1;
+            }
+        }
+    }
+
+    /// Suppress across the grid and turn it into cells. A flag count is also hidden when the
+    /// pupils without the flag would be a small number, or when the group itself is hidden.
+    fn cells(&self, threshold: usize) -> Vec<Vec<Cell>> {
+        let hidden = hide(&self.pupils, |r, c| is_small(self.pupils[r][c], threshold));
+        let flags_hidden: Vec<Vec<Vec<bool>>> = self
+            .flags
+            .iter()
+            .map(|(_, counts)| {
+                hide(counts, |r, c| {
+                    hidden[r][c]
+                        || is_small(counts[r][c], threshold)
+                        || is_small(self.pupils[r][c] - counts[r][c], threshold)
+                })
+            })
+            .collect();
+        self.pupils
+            .iter()
+            .enumerate()
+            .map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(c, count)| Cell {
+                        count: (!hidden[r][c]).then_some(*count),
+                        flags: self
+                            .flags
+                            .iter()
+                            .zip(&flags_hidden)
+                            .map(|((flag, counts), flag_hidden)| {
+                                let shown = !flag_hidden[r][c];
+                                (
+                                    flag.as_str(),
+                                    Share {
+                                        count: shown.then_some(counts[r][c]),
+                                        percentage: (shown && *count > 0)
+                                            .then(|| percentage(counts[r][c], *count)),
+                                    },
+                                )
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn transpose(&self) -> Self {
+        let rows = self.pupils.len();
+        let flip = |grid: &Vec<Vec<usize>>| -> Vec<Vec<usize>> {
+            (0..self.columns)
+                .map(|c| grid.iter().map(|row| row[c]).collect())
+                .collect()
+        };
+        Self {
+            columns: rows,
+            pupils: flip(&self.pupils),
+            flags: self
+                .flags
+                .iter()
+                .map(|(flag, counts)| (*flag, flip(counts)))
+                .collect(),
+        }
+    }
+
+    /// Sums along each row, as a grid with a single column
+    fn row_totals(&self) -> Self {
+        let sum = |grid: &Vec<Vec<usize>>| -> Vec<Vec<usize>> {
+            grid.iter().map(|row| vec![row.iter().sum()]).collect()
+        };
+        Self {
+            columns: 1,
+            pupils: sum(&self.pupils),
+            flags: self
+                .flags
+                .iter()
+                .map(|(flag, counts)| (*flag, sum(counts)))
+                .collect(),
+        }
+    }
+
+    /// Everything added up, as a grid with a single cell
+    fn total(&self) -> Self {
+        let sum = |grid: &Vec<Vec<usize>>| vec![vec![grid.iter().flatten().sum()]];
+        Self {
+            columns: 1,
+            pupils: sum(&self.pupils),
+            flags: self
+                .flags
+                .iter()
+                .map(|(flag, counts)| (*flag, sum(counts)))
+                .collect(),
+        }
+    }
+}
+
+fn percentage(part: usize, whole: usize) -> f64 {
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+impl CohortReport {
+    /// The report for the pupils on roll in the user's years, with their flags as they stood on
+    /// the date.
+    pub async fn from_db(
+        user: &User,
+        query: &CohortQuery,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        let rows = query.rows.unwrap_or(Dimension::Year);
+        if query.columns == Some(rows) {
+            return Err(ValidationError!("rows and columns must be different"));
+        }
+        let as_at = query
+            .as_at
+            .unwrap_or_else(|| chrono::Utc::now().date_naive());
+        let mut pupils = Pupil::all_from_db(user, db).await?;
+        pupils.retain(|pupil| on_roll(pupil, as_at));
+        FlagPeriod::flag_as_at(&mut pupils, as_at, db).await?;
+        Ok(Self::build(
+            &pupils,
+            rows,
+            query.columns,
+            as_at,
+            config.small_number_threshold,
+        ))
+    }
+
+    pub fn build(
+        pupils: &[Pupil],
+        rows: Dimension,
+        columns: Option<Dimension>,
+        as_at: NaiveDate,
+        threshold: usize,
+    ) -> Self {
+        let row_keys = rows.keys(pupils);
+        let column_keys = match columns {
+            Some(columns) => columns.keys(pupils),
+            None => vec!["all".into()],
+        };
+        let mut counts = Counts::new(row_keys.len(), column_keys.len());
+        for pupil in pupils {
+            let row = row_keys.iter().position(|key| *key == rows.key(pupil));
+            let column = match columns {
+                Some(columns) => column_keys
+                    .iter()
+                    .position(|key| *key == columns.key(pupil)),
+                None => Some(0),
+            };
+            if let (Some(row), Some(column)) = (row, column) {
+                counts.add(row, column, pupil);
+            }
+        }
+
+        let row_totals = counts.row_totals();
+        let column_totals = counts.transpose().row_totals();
+        Self {
+            as_at,
+            threshold,
+            rows,
+            columns,
+            cells: counts.cells(threshold),
+            // the totals run along a single line, so hiding one also hides the next smallest
+            // beside it, or the grand total would give it away
+            row_totals: row_totals
+                .transpose()
+                .cells(threshold)
+                .into_iter()
+                .flatten()
+                .collect(),
+            column_totals: column_totals
+                .transpose()
+                .cells(threshold)
+                .into_iter()
+                .flatten()
+                .collect(),
+            total: counts.total().cells(threshold).remove(0).remove(0),
+            row_keys,
+            column_keys,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pupil::import::blank;
+    use rstest::*;
+
+    fn pupil(year: i32, gender: &str, fsm: bool) -> Pupil {
+        Pupil {
+            year,
+            gender: gender.into(),
+            free_school_meals: fsm,
+            start_date: "2020-09-01".parse().unwrap(),
+            ..blank("")
+        }
+    }
+
+    /// Year 5 is ten girls and ten boys with six on FSM and two looked after, year 6 ten girls
+    /// and three boys with eight on FSM.
+    fn pupils() -> Vec<Pupil> {
+        let mut pupils = vec![];
+        for i in 0..20 {
+            let mut pupil = pupil(5, if i % 2 == 0 { "female" } else { "male" }, i < 6);
+            pupil.looked_after_child = i < 2;
+            pupils.push(pupil);
+        }
+        for i in 0..13 {
+            pupils.push(pupil(6, if i < 10 { "female" } else { "male" }, i < 8));
+        }
+        pupils
+    }
+
+    #[rstest]
+    fn test_group_by_year() {
+        let report = CohortReport::build(
+            &pupils(),
+            Dimension::Year,
+            None,
+            "2023-01-01".parse().unwrap(),
+            5,
+        );
+        assert_eq!(report.row_keys, ["5", "6"]);
+        assert_eq!(report.column_keys, ["all"]);
+        assert_eq!(report.cells[0][0].count, Some(20));
+        let fsm = &report.cells[0][0].flags["fsm"];
+        assert_eq!(fsm.count, Some(6));
+        assert_eq!(fsm.percentage, Some(30.0));
+        assert_eq!(report.cells[1][0].flags["fsm"].count, Some(8));
+        assert_eq!(report.total.flags["fsm"].count, Some(14));
+        // two looked after children could be picked out, and so could the total
+        assert_eq!(report.cells[0][0].flags["lac"].count, None);
+        assert_eq!(report.cells[0][0].flags["lac"].percentage, None);
+        assert_eq!(report.cells[1][0].flags["lac"].count, Some(0));
+        assert_eq!(report.total.flags["lac"].count, None);
+        assert_eq!(report.total.count, Some(33));
+    }
+
+    #[rstest]
+    fn test_cross_tab_suppresses_small_cells_and_what_would_give_them_away() {
+        let mut pupils = pupils();
+        for i in 0..27 {
+            pupils.push(pupil(4, if i < 12 { "female" } else { "male" }, false));
+        }
+        let report = CohortReport::build(
+            &pupils,
+            Dimension::Year,
+            Some(Dimension::Gender),
+            "2023-01-01".parse().unwrap(),
+            5,
+        );
+        assert_eq!(report.row_keys, ["4", "5", "6"]);
+        assert_eq!(report.column_keys, ["female", "male", "other"]);
+        let counts: Vec<Vec<Option<usize>>> = report
+            .cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.count).collect())
+            .collect();
+        // three boys in year 6 is small. The girls beside them go so the year total doesn't
+        // give them away, then the smallest in each column so the gender totals don't either.
+        assert_eq!(
+            counts,
+            [
+                [Some(12), Some(15), Some(0)],
+                [None, None, Some(0)],
+                [None, None, Some(0)],
+            ]
+        );
+        assert_eq!(report.row_totals[2].count, Some(13));
+        assert_eq!(report.column_totals[1].count, Some(28));
+        assert_eq!(report.total.count, Some(60));
+    }
+
+    #[rstest]
+    fn test_no_pupils() {
+        let report = CohortReport::build(
+            &[],
+            Dimension::Year,
+            Some(Dimension::Fsm),
+            "2023-01-01".parse().unwrap(),
+            5,
+        );
+        assert!(report.cells.is_empty());
+        assert_eq!(report.column_totals.len(), 2);
+        assert_eq!(report.total.count, Some(0));
+    }
+
+    #[rstest]
+    fn test_on_roll() {
+        let mut pupil = pupil(5, "female", false);
+        assert!(on_roll(&pupil, "2023-01-01".parse().unwrap()));
+        assert!(!on_roll(&pupil, "2020-08-31".parse().unwrap()));
+        pupil.end_date = Some("2022-07-20".parse().unwrap());
+        pupil.active = false;
+        assert!(on_roll(&pupil, "2022-07-20".parse().unwrap()));
+        assert!(!on_roll(&pupil, "2023-01-01".parse().unwrap()));
+    }
+}
@@ -0,0 +1,551 @@
+use super::{
+    cohort::on_roll,
+    suppression::{hide, is_small},
+};
+use crate::{
+    app::config::Config,
+    assessment::model::Assessment,
+    core::error::Result,
+    curriculum::{
+        framework::{Step, WhatMatters},
+        progression::{latest_per_statement, Judgement},
+    },
+    flag::model::{Flag, FlagPeriod},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::NaiveDate;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// What's being compared. Each assessment is a point on the trend, taken on the day it was sat.
+/// Judgements are a pupil's mean step across the statements, as they stood on each of the dates.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Measure {
+    Assessments {
+        assessment_ids: Vec<Uuid>,
+    },
+    Judgements {
+        what_matters_ids: Vec<Uuid>,
+        /// today when there are none
+        #[serde(default)]
+        dates: Vec<NaiveDate>,
+    },
+}
+
+/// Pupils who have each of the flags set to true and none of those set to false. With no flags
+/// it's everyone, for comparing one group against the whole cohort.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Group {
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) flags: HashMap<Flag, bool>,
+}
+
+impl Group {
+    fn contains(&self, pupil: &Pupil) -> bool {
+        self.flags.iter().all(|(flag, on)| flag.on(pupil) == *on)
+    }
+
+    /// The name given, or one made from the flags such as "FSM and not EAL".
+    fn name(&self) -> String {
+        if let Some(name) = self.name.as_ref().filter(|name| !name.trim().is_empty()) {
+            return name.trim().to_owned();
+        }
+        let mut flags: Vec<(&Flag, &bool)> = self.flags.iter().collect();
+        flags.sort_by_key(|(flag, _)| flag.as_str());
+        if flags.is_empty() {
+            return "all pupils".into();
+        }
+        flags
+            .into_iter()
+            .map(|(flag, on)| match on {
+                true => flag.as_str().to_uppercase(),
+                false => format!("not {}", flag.as_str().to_uppercase()),
+            })
+            .collect::<Vec<String>>()
+            .join(" and ")
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GapQuery {
+    pub(crate) measure: Measure,
+    /// the gap is the first group's mean less the second's
+    pub(crate) groups: [Group; 2],
+}
+
+impl GapQuery {
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        match &self.measure {
+            Measure::Assessments { assessment_ids } if assessment_ids.is_empty() => {
+                errors.insert(
+                    "assessment_ids".into(),
+                    "choose at least one assessment".into(),
+                );
+            }
+            Measure::Judgements {
+                what_matters_ids, ..
+            } if what_matters_ids.is_empty() => {
+                errors.insert(
+                    "what_matters_ids".into(),
+                    "choose at least one statement".into(),
+                );
+            }
+            _ => {}
+        }
+        if self.groups[0].flags == self.groups[1].flags {
+            errors.insert("groups".into(), "the two groups must be different".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("gap analysis failed validation").with_fields(errors))
+        }
+    }
+}
+
+/// What the scores on a point are, which decides the bands they're counted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scale {
+    Percentage,
+    Standardised,
+    Step,
+}
+
+/// A band of scores from its lower bound up to the next band's.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Band {
+    pub(crate) label: String,
+    pub(crate) from: f64,
+}
+
+impl Scale {
+    fn bands(&self, steps: &[Step]) -> Vec<Band> {
+        let band = |label: &str, from: f64| Band {
+            label: label.into(),
+            from,
+        };
+        match self {
+            Scale::Percentage => vec![
+                band("0-19%", 0.0),
+                band("20-39%", 20.0),
+                band("40-59%", 40.0),
+                band("60-79%", 60.0),
+                band("80-100%", 80.0),
+            ],
+            Scale::Standardised => vec![
+                band("below 85", 0.0),
+                band("85-99", 85.0),
+                band("100-114", 100.0),
+                band("115 and above", 115.0),
+            ],
+            Scale::Step => steps
+                .iter()
+                .map(|step| band(&step.name, step.step as f64))
+                .collect(),
+        }
+    }
+}
+
+/// One group's results on a point, with anything hidden that's few enough pupils to identify.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GroupSummary {
+    pub(crate) pupils: Option<usize>,
+    /// to one decimal place
+    pub(crate) mean: Option<f64>,
+    /// a count for each of the point's bands
+    pub(crate) distribution: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GapPoint {
+    pub(crate) date: NaiveDate,
+    pub(crate) label: String,
+    pub(crate) scale: Scale,
+    pub(crate) bands: Vec<Band>,
+    /// in the same order as the query's groups
+    pub(crate) groups: Vec<GroupSummary>,
+    /// the first group's mean less the second's, when both can be shown
+    pub(crate) gap: Option<f64>,
+}
+
+impl GapPoint {
+    /// Pupils with their score on the point, flagged as they were on its date.
+    pub fn build(
+        date: NaiveDate,
+        label: String,
+        scale: Scale,
+        bands: Vec<Band>,
+        scores: &[(Pupil, f64)],
+        groups: &[Group; 2],
+        threshold: usize,
+    ) -> Self {
+        let in_group: Vec<Vec<f64>> = groups
+            .iter()
+            .map(|group| {
+                scores
+                    .iter()
+                    .filter(|(pupil, _)| group.contains(pupil))
+                    .map(|(_, score)| *score)
+                    .collect()
+            })
+            .collect();
+        let groups: Vec<GroupSummary> = in_group
+            .iter()
+            .map(|scores| {
+                let mut counts = vec![0; bands.len()];
+                for score in scores {
+                    if let Some(band) = bands.iter().rposition(|band| band.from <= *score) {
+                        counts[band] += 1;
+                    }
+                }
+                // the group's size is shown, so the bands are only protected along the row
+                let hidden = match is_small(scores.len(), threshold) {
+                    true => vec![true; counts.len()],
+                    false => {
+                        hide(&[counts.clone()], |_, c| is_small(counts[c], threshold)).remove(0)
+                    }
+                };
+                let shown = scores.len() >= threshold;
+                GroupSummary {
+                    pupils: (!is_small(scores.len(), threshold)).then_some(scores.len()),
+                    mean: shown.then(|| round(scores.iter().sum::<f64>() / scores.len() as f64)),
+                    distribution: counts
+                        .into_iter()
+                        .zip(hidden)
+                        .map(|(count, hidden)| (!hidden).then_some(count))
+                        .collect(),
+                }
+            })
+            .collect();
+        let gap = match (groups[0].mean, groups[1].mean) {
+            (Some(first), Some(second)) => Some(round(first - second)),
+            _ => None,
+        };
+        Self {
+            date,
+            label,
+            scale,
+            bands,
+            groups,
+            gap,
+        }
+    }
+}
+
+/// The gap between two groups of pupils on each point, oldest first, with small groups hidden
+/// in the same way as the cohort numbers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GapReport {
+    pub(crate) threshold: usize,
+    pub(crate) groups: Vec<String>,
+    pub(crate) points: Vec<GapPoint>,
+    /// how far the gap has moved from the first point it could be shown on to the last
+    pub(crate) gap_change: Option<f64>,
+}
+
+impl GapReport {
+    pub async fn from_db(
+        user: &User,
+        query: &GapQuery,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        query.validate()?;
+        let threshold = config.small_number_threshold;
+        let points = match &query.measure {
+            Measure::Assessments { assessment_ids } => {
+                assessment_points(user, assessment_ids, &query.groups, threshold, db).await?
+            }
+            Measure::Judgements {
+                what_matters_ids,
+                dates,
+            } => {
+                judgement_points(user, what_matters_ids, dates, &query.groups, threshold, db)
+                    .await?
+            }
+        };
+        Ok(Self::new(&query.groups, points, threshold))
+    }
+
+    pub fn new(groups: &[Group; 2], points: Vec<GapPoint>, threshold: usize) -> Self {
+        let mut gaps = points.iter().filter_map(|point| point.gap);
+        let first = gaps.next();
+        let gap_change = match (first, gaps.next_back()) {
+            (Some(first), Some(last)) => Some(round(last - first)),
+            _ => None,
+        };
+        Self {
+            threshold,
+            groups: groups.iter().map(Group::name).collect(),
+            points,
+            gap_change,
+        }
+    }
+}
+
+/// A point for each assessment, scored on the standardised score where the assessment has a
+/// table and the percentage otherwise.
+async fn assessment_points(
+    user: &User,
+    ids: &[Uuid],
+    groups: &[Group; 2],
+    threshold: usize,
+    db: &DatabaseConnection,
+) -> Result<Vec<GapPoint>> {
+    use entity::assessment_result::{Column, Entity};
+    let mut assessments = vec![];
+    for id in ids {
+        assessments.push(Assessment::one_from_db(user, *id, db).await?);
+    }
+    assessments.sort_by_key(|assessment| assessment.date);
+    assessments.dedup_by_key(|assessment| assessment.id);
+    let results = Entity::find()
+        .filter(Column::AssessmentId.is_in(ids.iter().cloned()))
+        .all(db)
+        .await?;
+    let pupils: HashMap<Uuid, Pupil> = entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Id.is_in(results.iter().map(|r| r.pupil_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|pupil| (pupil.id, pupil.into()))
+        .collect();
+
+    let mut points = vec![];
+    for assessment in assessments {
+        let scale = match assessment.standardisation.is_empty() {
+            true => Scale::Percentage,
+            false => Scale::Standardised,
+        };
+        let (mut sat, scores): (Vec<Pupil>, Vec<f64>) = results
+            .iter()
+            .filter(|result| result.assessment_id == assessment.id)
+            .filter_map(|result| {
+                let score = assessment.score(result.raw_score);
+                let score = match scale {
+                    Scale::Standardised => score.standardised_score? as f64,
+                    _ => score.percentage,
+                };
+                Some((pupils.get(&result.pupil_id)?.clone(), score))
+            })
+            .unzip();
+        FlagPeriod::flag_as_at(&mut sat, assessment.date, db).await?;
+        points.push(GapPoint::build(
+            assessment.date,
+            assessment.name.clone(),
+            scale,
+            scale.bands(&[]),
+            &sat.into_iter().zip(scores).collect::<Vec<(Pupil, f64)>>(),
+            groups,
+            threshold,
+        ));
+    }
+    Ok(points)
+}
+
+/// A point for each date, scoring the pupils on roll by the mean of their latest step for each
+/// statement. Pupils with no judgements yet aren't counted.
+async fn judgement_points(
+    user: &User,
+    what_matters_ids: &[Uuid],
+    dates: &[NaiveDate],
+    groups: &[Group; 2],
+    threshold: usize,
+    db: &DatabaseConnection,
+) -> Result<Vec<GapPoint>> {
+    use entity::progression_judgement::{Column, Entity};
+    let known: Vec<Uuid> = WhatMatters::all_from_db(db)
+        .await?
+        .into_iter()
+        .map(|statement| statement.id)
+        .collect();
+    if let Some(id) = what_matters_ids.iter().find(|id| !known.contains(id)) {
+        return Err(
+            ValidationError!("gap analysis failed validation").with_fields(BTreeMap::from([(
+                "what_matters_ids".to_owned(),
+                format!("there's no statement {id}"),
+            )])),
+        );
+    }
+    let mut dates = dates.to_vec();
+    if dates.is_empty() {
+        dates.push(chrono::Utc::now().date_naive());
+    }
+    dates.sort_unstable();
+    dates.dedup();
+    let bands = Scale::Step.bands(&Step::all_from_db(db).await?);
+    let pupils = Pupil::all_from_db(user, db).await?;
+    let mut judgements: HashMap<Uuid, Vec<Judgement>> = HashMap::new();
+    for judgement in Entity::find()
+        .filter(Column::WhatMattersId.is_in(what_matters_ids.iter().cloned()))
+        .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+        .all(db)
+        .await?
+    {
+        judgements
+            .entry(judgement.pupil_id)
+            .or_default()
+            .push(judgement.into());
+    }
+
+    let mut points = vec![];
+    for date in dates {
+        let (mut judged, scores): (Vec<Pupil>, Vec<f64>) = pupils
+            .iter()
+            .filter(|pupil| on_roll(pupil, date))
+            .filter_map(|pupil| {
+                let latest = latest_per_statement(
+                    judgements
+                        .get(&pupil.id)?
+                        .iter()
+                        .filter(|judgement| judgement.date <= date)
+                        .cloned(),
+                );
+                if latest.is_empty() {
+                    return None;
+                }
+                let steps: i32 = latest.values().map(|judgement| judgement.step).sum();
+                Some((pupil.clone(), steps as f64 / latest.len() as f64))
+            })
+            .unzip();
+        FlagPeriod::flag_as_at(&mut judged, date, db).await?;
+        points.push(GapPoint::build(
+            date,
+            date.format("%d/%m/%Y").to_string(),
+            Scale::Step,
+            bands.clone(),
+            &judged
+                .into_iter()
+                .zip(scores)
+                .collect::<Vec<(Pupil, f64)>>(),
+            groups,
+            threshold,
+        ));
+    }
+    Ok(points)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pupil::import::blank;
+    use rstest::*;
+
+    fn fsm() -> [Group; 2] {
+        [
+            Group {
+                name: None,
+                flags: HashMap::from([(Flag::Fsm, true)]),
+            },
+            Group {
+                name: None,
+                flags: HashMap::from([(Flag::Fsm, false)]),
+            },
+        ]
+    }
+
+    fn scores(fsm: &[f64], not_fsm: &[f64]) -> Vec<(Pupil, f64)> {
+        let mut scores = vec![];
+        for (on, group) in [(true, fsm), (false, not_fsm)] {
+            for score in group {
+                let mut pupil = blank("");
+                pupil.free_school_meals = on;
+                scores.push((pupil, *score));
+            }
+        }
+        scores
+    }
+
+    #[rstest]
+    fn test_means_distribution_and_gap() {
+        let point = GapPoint::build(
+            "2023-03-20".parse().unwrap(),
+            "Spring reading".into(),
+            Scale::Percentage,
+            Scale::Percentage.bands(&[]),
+            &scores(
+                &[10.0, 30.0, 35.0, 45.0, 50.0, 55.0],
+                &[50.0, 60.0, 65.0, 70.0, 75.0, 85.0, 90.0, 95.0],
+            ),
+            &fsm(),
+            5,
+        );
+        assert_eq!(point.groups[0].pupils, Some(6));
+        assert_eq!(point.groups[0].mean, Some(37.5));
+        assert_eq!(point.groups[1].mean, Some(73.8));
+        assert_eq!(point.gap, Some(-36.3));
+        // 1, 2 and 3 in the bands are all small, and zeros are left alone
+        assert_eq!(
+            point.groups[0].distribution,
+            [None, None, None, Some(0), Some(0)]
+        );
+        assert_eq!(
+            point.groups[1].distribution,
+            [Some(0), Some(0), None, None, None]
+        );
+    }
+
+    #[rstest]
+    fn test_small_group_hides_its_mean_and_the_gap() {
+        let point = GapPoint::build(
+            "2023-03-20".parse().unwrap(),
+            "Spring reading".into(),
+            Scale::Standardised,
+            Scale::Standardised.bands(&[]),
+            &scores(&[90.0, 101.0], &[85.0, 100.0, 100.0, 100.0, 110.0, 120.0]),
+            &fsm(),
+            5,
+        );
+        assert_eq!(point.groups[0].pupils, None);
+        assert_eq!(point.groups[0].mean, None);
+        assert!(point.groups[0].distribution.iter().all(Option::is_none));
+        assert_eq!(point.groups[1].mean, Some(102.5));
+        assert_eq!(point.groups[1].distribution, [Some(0), None, None, None]);
+        assert_eq!(point.gap, None);
+    }
+
+    #[rstest]
+    fn test_gap_change_and_names() {
+        let point = |gap| GapPoint {
+            date: "2023-03-20".parse().unwrap(),
+            label: "".into(),
+            scale: Scale::Step,
+            bands: vec![],
+            groups: vec![],
+            gap,
+        };
+        let report = GapReport::new(
+            &fsm(),
+            vec![point(Some(-8.0)), point(None), point(Some(-5.5))],
+            5,
+        );
+        assert_eq!(report.gap_change, Some(2.5));
+        assert_eq!(report.groups, ["FSM", "not FSM"]);
+        let everyone = Group::default();
+        assert_eq!(everyone.name(), "all pupils");
+    }
+
+    #[rstest]
+    fn test_validate() {
+        let query: GapQuery = serde_json::from_value(serde_json::json!({
+            "measure": {"kind": "assessments", "assessment_ids": []},
+            "groups": [{"flags": {"eal": true}}, {"flags": {"eal": true}}]
+        }))
+        .unwrap();
+        let fields = query.validate().unwrap_err().fields.unwrap();
+        assert_eq!(fields["assessment_ids"], "choose at least one assessment");
+        assert_eq!(fields["groups"], "the two groups must be different");
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    analytics::{
+        cohort::{CohortQuery, CohortReport},
+        gap::{GapQuery, GapReport},
+    },
+    app::state::AppState,
+    core::error::*,
+    user::model::User,
+};
+use axum::{
+    extract::{Json, Query, State},
+    Extension,
+};
+use serde_json::json;
+
+/// Counts and percentages of pupils with each flag, grouped with `?rows=` and cross-tabulated
+/// with `?columns=`, for the pupils on roll in the user's years on `?as_at=`.
+pub async fn get_cohort(
+    State(state): State<AppState>,
+    Query(query): Query<CohortQuery>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested cohort analytics {:?}", query);
+    match CohortReport::from_db(&user, &query, state.config(), state.database().as_ref()).await {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::ValidationError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Compare two groups of pupils, picked by their flags, on assessment results or progression
+/// judgements: means, how the scores are spread and the gap between them at each point in time.
+pub async fn analyse_gap(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(query): Json<GapQuery>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested a gap analysis {:?}", query);
+    match GapReport::from_db(&user, &query, state.config(), state.database().as_ref()).await {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::AssessmentDoesNotExist => Err(AssessmentDoesNotExist!()),
+            ErrorKind::ValidationError | ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
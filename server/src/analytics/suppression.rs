@@ -0,0 +1,68 @@
+/// A count that's more than nothing but fewer than the threshold, so the pupils in it could be
+/// identified.
+pub fn is_small(count: usize, threshold: usize) -> bool {
+    count > 0 && count < threshold
+}
+
+/// Which cells of a grid to hide. Cells go first where `primary` says, then wherever a row or
+/// column is left with only one hidden the next smallest count in it goes too, since its total
+/// would otherwise give the hidden one away. Zeros are never hidden for that, there's nobody in
+/// them to identify.
+pub fn hide(counts: &[Vec<usize>], primary: impl Fn(usize, usize) -> bool) -> Vec<Vec<bool>> {
+    let mut hidden: Vec<Vec<bool>> = counts
+        .iter()
+        .enumerate()
+        .map(|(r, row)| (0..row.len()).map(|c| primary(r, c)).collect())
+        .collect();
+    let columns = counts.first().map(Vec::len).unwrap_or_default();
+    let lines: Vec<Vec<(usize, usize)>> = (0..counts.len())
+        .map(|r| (0..columns).map(|c| (r, c)).collect())
+        .chain((0..columns).map(|c| (0..counts.len()).map(|r| (r, c)).collect()))
+        .collect();
+    loop {
+        let mut changed = false;
+        for line in &lines {
+            if line.iter().filter(|(r, c)| hidden[*r][*c]).count() != 1 {
+                continue;
+            }
+            if let Some((r, c)) = line
+                .iter()
+                .filter(|(r, c)| !hidden[*r][*c] && counts[*r][*c] > 0)
+                .min_by_key(|(r, c)| counts[*r][*c])
+            {
+                hidden[*r][*c] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return hidden;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_a_lone_hidden_cell_takes_its_neighbours_with_it() {
+        let counts = vec![vec![20, 30, 25], vec![2, 40, 50], vec![15, 12, 9]];
+        let hidden = hide(&counts, |r, c| is_small(counts[r][c], 5));
+        assert_eq!(
+            hidden,
+            [
+                [false, false, false],
+                [true, true, false],
+                [true, true, false],
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_nothing_small_hides_nothing() {
+        let counts = vec![vec![20, 0], vec![7, 40]];
+        let hidden = hide(&counts, |r, c| is_small(counts[r][c], 5));
+        assert_eq!(hidden, [[false, false], [false, false]]);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    core::{constant, error::Result},
+    utils::functions::generate_secret,
+};
+
+/// School-specific settings, read from the environment with sensible defaults for a primary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub min_year: i32,
+    pub max_year: i32,
+    /// the share of possible sessions, as a percentage, a pupil has to miss to be persistently absent
+    pub persistent_absence_percent: f64,
+    pub attachment_max_bytes: usize,
+    /// how long an attachment download link works for
+    pub signed_url_seconds: i64,
+    /// signs attachment download links. If it isn't set a random one is made at startup, so links
+    /// stop working on restart and every server behind a load balancer needs the same one.
+    pub signing_key: String,
+    /// the local authority and establishment numbers that identify the school in a CTF
+    pub school_lea: String,
+    pub school_estab: String,
+    pub school_name: String,
+    /// analytics hide counts from 1 up to below this, so small groups of pupils can't be picked out
+    pub small_number_threshold: usize,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let default = Self::default();
+        Ok(Self {
+            min_year: env_or("SCHOOL_MIN_YEAR", default.min_year)?,
+            max_year: env_or("SCHOOL_MAX_YEAR", default.max_year)?,
+            persistent_absence_percent: env_or(
+                "SCHOOL_PERSISTENT_ABSENCE_PERCENT",
+                default.persistent_absence_percent,
+            )?,
+            attachment_max_bytes: env_or("ATTACHMENT_MAX_BYTES", default.attachment_max_bytes)?,
+            signed_url_seconds: env_or("SIGNED_URL_SECONDS", default.signed_url_seconds)?,
+            signing_key: env_or("ATTACHMENT_SIGNING_KEY", default.signing_key)?,
+            school_lea: env_or("SCHOOL_LEA", default.school_lea)?,
+            school_estab: env_or("SCHOOL_ESTAB", default.school_estab)?,
+            school_name: env_or("SCHOOL_NAME", default.school_name)?,
+            small_number_threshold: env_or(
+                "ANALYTICS_SMALL_NUMBER_THRESHOLD",
+                default.small_number_threshold,
+            )?,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            min_year: constant::DEFAULT_MIN_YEAR,
+            max_year: constant::DEFAULT_MAX_YEAR,
+            persistent_absence_percent: constant::DEFAULT_PERSISTENT_ABSENCE_PERCENT,
+            attachment_max_bytes: constant::DEFAULT_ATTACHMENT_MAX_BYTES,
+            signed_url_seconds: constant::DEFAULT_SIGNED_URL_SECONDS,
+            signing_key: hex::encode(generate_secret()),
+            school_lea: constant::DEFAULT_SCHOOL_LEA.into(),
+            school_estab: constant::DEFAULT_SCHOOL_ESTAB.into(),
+            school_name: String::new(),
+            small_number_threshold: constant::DEFAULT_SMALL_NUMBER_THRESHOLD,
+        }
+    }
+}
+
+fn env_or<T>(key: &str, default: T) -> Result<T>
+where
+    T: std::str::FromStr,
+{
+    match std::env::var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| ParseError!(format!("{key} has an invalid value {value}"))),
+        Err(_) => Ok(default),
+    }
+}
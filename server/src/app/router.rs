@@ -1,12 +1,31 @@
 use crate::{
+    aln::handlers::*,
+    analytics::handlers::*,
     app::state::AppState,
+    assessment::handlers::*,
+    attachment::handlers::*,
+    attendance::handlers::*,
     auth::{handlers::*, token::*},
+    census::handlers::*,
+    comment::handlers::*,
+    concern::handlers::*,
+    contact::handlers::*,
+    ctf::handlers::*,
+    curriculum::handlers::*,
+    eal::handlers::*,
+    flag::handlers::*,
+    intervention::handlers::*,
+    medical::handlers::*,
+    photo::handlers::*,
     pupil::handlers::*,
+    quality::handlers::*,
+    report::handlers::*,
+    subject_access::handlers::*,
     user::handlers::*,
 };
 use axum::{
     middleware::from_fn_with_state,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
 use hyper::Method;
@@ -19,16 +38,228 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/logout", get(logout_handler));
     let pupils_router = Router::new()
         .route("/", get(get_pupils).put(create_pupil))
+        .route("/import", post(import_pupils))
+        .route("/export", post(export_pupils))
+        .route("/ctf", post(import_ctf))
         .route(
             "/:id",
-            get(get_pupil_by_id).post(update_pupil).delete(delete_pupil),
+            get(get_pupil_by_id)
+                .post(update_pupil)
+                .patch(update_pupil)
+                .delete(delete_pupil),
+        )
+        .route("/:id/comments", get(get_pupil_comments).put(create_comment))
+        .route("/:id/results", get(get_pupil_results))
+        .route("/:id/concerns", get(get_pupil_concerns))
+        .route("/:id/attendance", get(get_pupil_attendance))
+        .route(
+            "/:id/progression",
+            get(get_pupil_progression).put(create_judgement),
+        )
+        .route("/:id/progression/:judgement_id", delete(delete_judgement))
+        .route("/:id/interventions", get(get_pupil_interventions))
+        .route(
+            "/:id/aln",
+            get(get_pupil_aln)
+                .put(save_pupil_aln)
+                .delete(close_pupil_aln),
+        )
+        .route("/:id/idp", get(get_pupil_idps).put(create_idp_version))
+        .route(
+            "/:id/eal",
+            get(get_pupil_eal_assessments).put(create_eal_assessment),
+        )
+        .route("/:id/eal/:assessment_id", delete(delete_eal_assessment))
+        .route(
+            "/:id/flags",
+            get(get_pupil_flag_periods).put(create_flag_period),
+        )
+        .route(
+            "/:id/flags/:period_id",
+            post(update_flag_period)
+                .patch(update_flag_period)
+                .delete(delete_flag_period),
+        )
+        .route("/:id/ctf", get(get_leaver_ctf))
+        .route("/:id/subject-access", post(export_subject_access))
+        .route(
+            "/:id/subject-access/preview",
+            post(preview_subject_access),
+        )
+        .route("/:id/contacts", get(get_pupil_contacts).put(create_contact))
+        .route(
+            "/:id/contacts/:contact_id",
+            put(link_contact)
+                .post(update_contact)
+                .patch(update_contact)
+                .delete(unlink_contact),
+        )
+        .route(
+            "/:id/medical",
+            get(get_pupil_medical).put(create_medical_item),
+        )
+        .route(
+            "/:id/medical/:item_id",
+            post(update_medical_item)
+                .patch(update_medical_item)
+                .delete(delete_medical_item),
+        )
+        .route(
+            "/:id/attachments",
+            get(get_pupil_attachments).put(add_pupil_attachment),
+        )
+        .route(
+            "/:id/attachments/:attachment_id",
+            get(get_attachment_link).delete(delete_attachment),
+        )
+        .route(
+            "/:id/photo",
+            get(get_pupil_photo)
+                .put(upload_pupil_photo)
+                .delete(delete_pupil_photo),
+        )
+        .route("/:id/photo/consent", post(set_photo_consent))
+        .route("/:id/targets", get(get_pupil_targets).put(create_target))
+        .route(
+            "/:id/targets/:target_id",
+            post(update_target)
+                .patch(update_target)
+                .delete(delete_target),
+        )
+        .route(
+            "/:id/comments/:comment_id",
+            get(get_comment_by_id)
+                .post(update_comment)
+                .patch(update_comment)
+                .delete(delete_comment),
+        )
+        .route(
+            "/:id/comments/:comment_id/attachments",
+            get(get_comment_attachments).put(add_comment_attachment),
+        );
+    let assessments_router = Router::new()
+        .route("/", get(get_assessments).put(create_assessment))
+        .route(
+            "/:id",
+            get(get_assessment_by_id)
+                .post(update_assessment)
+                .patch(update_assessment)
+                .delete(delete_assessment),
+        )
+        .route(
+            "/:id/results",
+            get(get_assessment_results)
+                .put(save_assessment_results)
+                .post(save_assessment_results),
+        );
+    let concerns_router = Router::new()
+        .route("/", get(get_concerns).put(create_concern))
+        .route("/log", get(check_concern_log))
+        .route(
+            "/:id",
+            get(get_concern_by_id)
+                .post(update_concern)
+                .patch(update_concern),
+        )
+        .route("/:id/status", post(update_concern_status))
+        .route(
+            "/:id/attachments",
+            get(get_concern_attachments).put(add_concern_attachment),
+        )
+        .route(
+            "/:id/attachments/:attachment_id",
+            get(download_concern_attachment),
+        )
+        .route("/:id/log", get(get_concern_log));
+    let attendance_router = Router::new()
+        .route(
+            "/codes",
+            get(get_attendance_codes)
+                .put(save_attendance_code)
+                .post(save_attendance_code),
+        )
+        .route(
+            "/register/:year/:date",
+            get(get_register).put(save_register).post(save_register),
+        );
+    let curriculum_router = Router::new()
+        .route("/framework", get(get_framework))
+        .route("/areas", put(save_area).post(save_area))
+        .route(
+            "/statements",
+            put(save_what_matters).post(save_what_matters),
+        )
+        .route("/steps", put(save_step).post(save_step))
+        .route("/overview/:year", get(get_overview));
+    let interventions_router = Router::new()
+        .route("/", get(get_interventions).put(create_intervention))
+        .route(
+            "/:id",
+            get(get_intervention_by_id)
+                .post(update_intervention)
+                .patch(update_intervention)
+                .delete(delete_intervention),
+        )
+        .route(
+            "/:id/members",
+            put(save_intervention_member).post(save_intervention_member),
+        )
+        .route("/:id/members/:pupil_id", delete(remove_intervention_member))
+        .route("/:id/sessions", put(log_intervention_session));
+    let aln_router = Router::new().route("/register", get(get_aln_register));
+    let medical_router = Router::new().route("/allergies/:year", get(get_allergy_report));
+    let analytics_router = Router::new()
+        .route("/cohort", get(get_cohort))
+        .route("/gap", post(analyse_gap));
+    let reports_router = Router::new()
+        .route(
+            "/",
+            get(get_report_templates).put(create_report_template),
+        )
+        .route(
+            "/:id",
+            post(update_report_template).delete(delete_report_template),
+        )
+        .route("/:id/years/:year", get(get_report_statuses))
+        .route("/:id/years/:year/zip", get(download_class_reports))
+        .route("/:id/pupils/:pupil_id", get(download_pupil_report))
+        .route(
+            "/:id/pupils/:pupil_id/sign-off",
+            put(sign_off_report).delete(withdraw_report_sign_off),
         );
-    let users_router = Router::new().route("/", put(create_user).get(get_users));
+    let census_router = Router::new()
+        .route("/:date", get(get_census))
+        .route("/:date/return", get(download_census_return));
+    let quality_router = Router::new()
+        .route("/", get(get_quality_report))
+        .route("/merge", post(merge_pupils));
+    let users_router = Router::new()
+        .route("/", put(create_user).get(get_users))
+        .route("/:email", post(update_user).patch(update_user));
     let data_router = Router::new()
         .nest("/pupils", pupils_router)
-        .nest("/users", users_router);
+        .nest("/users", users_router)
+        .nest("/assessments", assessments_router)
+        .nest("/concerns", concerns_router)
+        .nest("/attendance", attendance_router)
+        .nest("/curriculum", curriculum_router)
+        .nest("/interventions", interventions_router)
+        .nest("/aln", aln_router)
+        .nest("/medical", medical_router)
+        .nest("/analytics", analytics_router)
+        .nest("/reports", reports_router)
+        .nest("/census", census_router)
+        .nest("/quality", quality_router)
+        .route("/subject-access", get(get_subject_access_log))
+        .route("/comments", get(get_comments));
     let cors_layer = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
         .allow_origin(Any); // TODOSERVER this needs to only be the actual url (research this!!)
 
     Router::new().nest(
@@ -37,6 +268,8 @@ pub fn router(state: AppState) -> Router<AppState> {
             .nest("/data", data_router)
             .layer(from_fn_with_state(Arc::clone(&state), auth_service))
             .nest("/auth", auth_router)
+            .route("/files/:id", get(download_attachment))
+            .route("/files/photos/:pupil_id/:size", get(download_photo))
             .layer(cors_layer),
     )
 }
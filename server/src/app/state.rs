@@ -1,16 +1,33 @@
+use crate::{
+    app::config::Config,
+    attachment::{scan::VirusScanner, store::BlobStore},
+};
 use mockall::automock;
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
 
 pub struct AppStateObj {
     database: Arc<DatabaseConnection>,
+    config: Config,
+    blob_store: Arc<dyn BlobStore>,
+    scanner: Arc<dyn VirusScanner>,
 }
 
 pub type AppState = Arc<dyn AppStateTrait + Send + Sync>;
 
 impl AppStateObj {
-    pub fn new(database: Arc<DatabaseConnection>) -> Self {
-        Self { database }
+    pub fn new(
+        database: Arc<DatabaseConnection>,
+        config: Config,
+        blob_store: Arc<dyn BlobStore>,
+        scanner: Arc<dyn VirusScanner>,
+    ) -> Self {
+        Self {
+            database,
+            config,
+            blob_store,
+            scanner,
+        }
     }
 }
 
@@ -18,9 +35,24 @@ impl AppStateTrait for AppStateObj {
     fn database(&self) -> &Arc<DatabaseConnection> {
         &self.database
     }
+
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn blob_store(&self) -> &Arc<dyn BlobStore> {
+        &self.blob_store
+    }
+
+    fn scanner(&self) -> &Arc<dyn VirusScanner> {
+        &self.scanner
+    }
 }
 
 #[automock]
 pub trait AppStateTrait {
     fn database(&self) -> &Arc<DatabaseConnection>;
+    fn config(&self) -> &Config;
+    fn blob_store(&self) -> &Arc<dyn BlobStore>;
+    fn scanner(&self) -> &Arc<dyn VirusScanner>;
 }
@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    assessment::{model::*, result::*},
+    core::error::*,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn create_assessment(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(assessment): Json<Assessment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    assessment.validate(state.config())?;
+    assessment.check_year(&user)?;
+    match assessment.insert(state.database().as_ref()).await {
+        Ok(assessment) => Ok((StatusCode::CREATED, Json(json!(assessment)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_assessments(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested all assessments");
+    match Assessment::all_from_db(&user, state.database().as_ref()).await {
+        Ok(assessments) => Ok(Json(json!(assessments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_assessment_by_id(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested assessment {id}");
+    let id = Uuid::from_str(&id)?;
+    match Assessment::one_from_db(&user, id, state.database().as_ref()).await {
+        Ok(assessment) => Ok(Json(json!(assessment))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::AssessmentDoesNotExist => Err(AssessmentDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_assessment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(update): Json<AssessmentUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating assessment {id}");
+    let id = Uuid::from_str(&id)?;
+    let mut assessment = Assessment::one_from_db(&user, id, state.database()).await?;
+    assessment.set_from_update(update)?;
+    assessment.validate(state.config())?;
+    assessment.check_year(&user)?;
+    match assessment.update(state.database().as_ref()).await {
+        Ok(assessment) => Ok(Json(json!(assessment))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_assessment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting assessment {id}");
+    let id = Uuid::from_str(&id)?;
+    let assessment = Assessment::one_from_db(&user, id, state.database()).await?;
+    match assessment.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_assessment_results(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested results for assessment {id}");
+    let id = Uuid::from_str(&id)?;
+    let assessment = Assessment::one_from_db(&user, id, state.database()).await?;
+    match ResultGrid::from_db(assessment, state.database().as_ref()).await {
+        Ok(grid) => Ok(Json(json!(grid))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Takes the whole grid, or just the changed cells, and returns the grid with scores worked out.
+pub async fn save_assessment_results(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(entries): Json<Vec<ResultEntry>>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving {} results for assessment {id}", entries.len());
+    let id = Uuid::from_str(&id)?;
+    let assessment = Assessment::one_from_db(&user, id, state.database()).await?;
+    ResultGrid::save(&assessment, entries, state.database()).await?;
+    match ResultGrid::from_db(assessment, state.database().as_ref()).await {
+        Ok(grid) => Ok(Json(json!(grid))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_results(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested results for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match PupilResult::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(results) => Ok(Json(json!(results))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
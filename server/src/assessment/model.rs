@@ -0,0 +1,393 @@
+use crate::{app::config::Config, core::error::Result, user::model::User, utils::patch::Patch};
+use chrono::NaiveDate;
+use entity::assessment::{ActiveModel, Column, Entity, Model};
+use migration::Condition;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// One line of a standardisation table, mapping a raw score to its standardised score.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StandardisationRow {
+    pub(crate) raw: i32,
+    pub(crate) standardised: i32,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Assessment {
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) subject: String,
+    pub(crate) year: i32,
+    pub(crate) date: NaiveDate,
+    pub(crate) max_score: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) standardisation: Vec<StandardisationRow>,
+}
+
+/// A pupil's raw score along with the scores the server works out from it.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Score {
+    pub(crate) raw_score: i32,
+    pub(crate) percentage: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) standardised_score: Option<i32>,
+}
+
+impl Assessment {
+    pub async fn one_from_db<Id>(user: &User, id: Id, db: &DatabaseConnection) -> Result<Self>
+    where
+        Id: Into<Uuid>,
+    {
+        let id: Uuid = id.into();
+        match Entity::find_by_id(id).one(db).await? {
+            Some(assessment) => {
+                if user.years.contains(&(assessment.year as u32)) {
+                    Ok(assessment.into())
+                } else {
+                    Err(Unauthorised!(format!(
+                        "you don't have permission to view year {}",
+                        assessment.year
+                    )))
+                }
+            }
+            None => Err(AssessmentDoesNotExist!()),
+        }
+    }
+
+    /// Every assessment for the user's years, most recent first.
+    pub async fn all_from_db(user: &User, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        let mut cond = Condition::any();
+        for year in &user.years {
+            cond = cond.add(Column::Year.eq(*year));
+        }
+        Ok(Entity::find()
+            .filter(cond)
+            .order_by_desc(Column::Date)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting assessment {:?}", self);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name.clone()),
+            subject: Set(self.subject.clone()),
+            year: Set(self.year),
+            date: Set(self.date),
+            max_score: Set(self.max_score),
+            standardisation: Set(encode_standardisation(&self.standardisation)),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            name: Set(self.name.clone()),
+            subject: Set(self.subject.clone()),
+            year: Set(self.year),
+            date: Set(self.date),
+            max_score: Set(self.max_score),
+            standardisation: Set(encode_standardisation(&self.standardisation)),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Only users with access to the assessment's year can create or move assessments there.
+    pub fn check_year(&self, user: &User) -> Result<()> {
+        if user.years.contains(&(self.year as u32)) {
+            Ok(())
+        } else {
+            Err(Unauthorised!(format!(
+                "you don't have permission to view year {}",
+                self.year
+            )))
+        }
+    }
+
+    /// Apply a JSON merge patch, where a null standardisation removes the table.
+    pub fn set_from_update(&mut self, update: AssessmentUpdate) -> Result<()> {
+        update.name.apply("name", &mut self.name)?;
+        update.subject.apply("subject", &mut self.subject)?;
+        update.year.apply("year", &mut self.year)?;
+        update.date.apply("date", &mut self.date)?;
+        update.max_score.apply("max_score", &mut self.max_score)?;
+        match update.standardisation {
+            Patch::Missing => {}
+            Patch::Null => self.standardisation = vec![],
+            Patch::Value(rows) => self.standardisation = rows,
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "name cannot be empty".into());
+        }
+        if self.subject.trim().is_empty() {
+            errors.insert("subject".into(), "subject cannot be empty".into());
+        }
+        if self.year < config.min_year || self.year > config.max_year {
+            errors.insert(
+                "year".into(),
+                format!(
+                    "year must be between {} and {}",
+                    config.min_year, config.max_year
+                ),
+            );
+        }
+        if self.max_score < 1 {
+            errors.insert("max_score".into(), "max score must be at least 1".into());
+        }
+        let mut seen = BTreeSet::new();
+        for row in &self.standardisation {
+            if row.raw < 0 || row.raw > self.max_score {
+                errors.insert(
+                    "standardisation".into(),
+                    format!(
+                        "raw score {} must be between 0 and {}",
+                        row.raw, self.max_score
+                    ),
+                );
+            } else if !seen.insert(row.raw) {
+                errors.insert(
+                    "standardisation".into(),
+                    format!("raw score {} appears more than once", row.raw),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("assessment failed validation").with_fields(errors))
+        }
+    }
+
+    /// Work out the percentage and, if there's a table, the standardised score for a raw score.
+    /// A raw score between rows takes the standardised score of the row below it.
+    pub fn score(&self, raw_score: i32) -> Score {
+        let percentage = (raw_score as f64 * 1000.0 / self.max_score as f64).round() / 10.0;
+        let standardised_score = self
+            .standardisation
+            .iter()
+            .filter(|row| row.raw <= raw_score)
+            .max_by_key(|row| row.raw)
+            .map(|row| row.standardised);
+        Score {
+            raw_score,
+            percentage,
+            standardised_score,
+        }
+    }
+}
+
+/// Stored like the user's years, as "raw:standardised" pairs separated by commas.
+fn encode_standardisation(rows: &[StandardisationRow]) -> Option<String> {
+    if rows.is_empty() {
+        return None;
+    }
+    let mut rows = rows.to_vec();
+    rows.sort_by_key(|row| row.raw);
+    Some(
+        rows.iter()
+            .map(|row| format!("{}:{}", row.raw, row.standardised))
+            .collect::<Vec<String>>()
+            .join(","),
+    )
+}
+
+fn decode_standardisation(value: Option<String>) -> Vec<StandardisationRow> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| {
+            let (raw, standardised) = pair.split_once(':')?;
+            Some(StandardisationRow {
+                raw: raw.trim().parse().ok()?,
+                standardised: standardised.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct AssessmentUpdate {
+    name: Patch<String>,
+    subject: Patch<String>,
+    year: Patch<i32>,
+    date: Patch<NaiveDate>,
+    max_score: Patch<i32>,
+    standardisation: Patch<Vec<StandardisationRow>>,
+}
+
+impl From<Model> for Assessment {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            subject: value.subject,
+            year: value.year,
+            date: value.date,
+            max_score: value.max_score,
+            standardisation: decode_standardisation(value.standardisation),
+        }
+    }
+}
+
+impl From<Assessment> for Model {
+    fn from(value: Assessment) -> Self {
+        Self {
+            standardisation: encode_standardisation(&value.standardisation),
+            id: value.id,
+            name: value.name,
+            subject: value.subject,
+            year: value.year,
+            date: value.date,
+            max_score: value.max_score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    #[fixture]
+    fn test_assessment() -> Assessment {
+        Assessment {
+            id: "0c5a37b4-9f1e-4d7c-a6a8-2a3f2f9c1e01".parse().unwrap(),
+            name: "Spring reading".into(),
+            subject: "English".into(),
+            year: 6,
+            date: "2023-03-20".parse().unwrap(),
+            max_score: 40,
+            standardisation: vec![
+                StandardisationRow {
+                    raw: 0,
+                    standardised: 70,
+                },
+                StandardisationRow {
+                    raw: 20,
+                    standardised: 100,
+                },
+                StandardisationRow {
+                    raw: 30,
+                    standardised: 115,
+                },
+            ],
+        }
+    }
+
+    #[rstest]
+    async fn test_all_from_db(test_assessment: Assessment) {
+        let user = User::new("test", "user", "test@test.com", "pass", vec![5, 6]);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(test_assessment.clone())]])
+            .into_connection();
+        let assessments = Assessment::all_from_db(&user, &db).await.unwrap();
+        assert_eq!(assessments, vec![test_assessment]);
+        let t_log = db.into_transaction_log();
+        let exp_query = Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "assessment"."id", "assessment"."name", "assessment"."subject", "assessment"."year", "assessment"."date", "assessment"."max_score", "assessment"."standardisation" FROM "assessment" WHERE "assessment"."year" = $1 OR "assessment"."year" = $2 ORDER BY "assessment"."date" DESC"#,
+            [5u32.into(), 6u32.into()],
+        );
+        assert_eq!(t_log[0], exp_query);
+    }
+
+    #[rstest]
+    async fn test_one_from_db_outside_years(test_assessment: Assessment) {
+        let user = User::new("test", "user", "test@test.com", "pass", vec![1]);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(test_assessment.clone())]])
+            .into_connection();
+        let error = Assessment::one_from_db(&user, test_assessment.id, &db)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, crate::core::error::ErrorKind::Unauthorised);
+    }
+
+    #[rstest]
+    fn test_standardisation_round_trip(test_assessment: Assessment) {
+        let model = Model::from(test_assessment.clone());
+        assert_eq!(model.standardisation, Some("0:70,20:100,30:115".into()));
+        assert_eq!(Assessment::from(model), test_assessment);
+    }
+
+    #[rstest]
+    #[case(40, 100.0, Some(115))]
+    #[case(25, 62.5, Some(100))]
+    #[case(20, 50.0, Some(100))]
+    #[case(0, 0.0, Some(70))]
+    #[case(13, 32.5, Some(70))]
+    fn test_score(
+        test_assessment: Assessment,
+        #[case] raw: i32,
+        #[case] percentage: f64,
+        #[case] standardised: Option<i32>,
+    ) {
+        let score = test_assessment.score(raw);
+        assert_eq!(score.raw_score, raw);
+        assert_eq!(score.percentage, percentage);
+        assert_eq!(score.standardised_score, standardised);
+    }
+
+    #[rstest]
+    fn test_score_without_table(mut test_assessment: Assessment) {
+        test_assessment.standardisation = vec![];
+        test_assessment.max_score = 3;
+        let score = test_assessment.score(2);
+        assert_eq!(score.percentage, 66.7);
+        assert_eq!(score.standardised_score, None);
+    }
+
+    #[rstest]
+    fn test_set_from_update(mut test_assessment: Assessment) {
+        let update: AssessmentUpdate =
+            serde_json::from_str(r#"{"max_score": 50, "standardisation": null}"#).unwrap();
+        test_assessment.set_from_update(update).unwrap();
+        assert_eq!(test_assessment.max_score, 50);
+        assert!(test_assessment.standardisation.is_empty());
+        assert_eq!(test_assessment.name, "Spring reading");
+    }
+
+    #[rstest]
+    fn test_validate(mut test_assessment: Assessment) {
+        assert!(test_assessment.validate(&Config::default()).is_ok());
+        test_assessment.name = " ".into();
+        test_assessment.max_score = 25;
+        let fields = test_assessment
+            .validate(&Config::default())
+            .unwrap_err()
+            .fields
+            .unwrap();
+        assert_eq!(fields["name"], "name cannot be empty");
+        assert_eq!(
+            fields["standardisation"],
+            "raw score 30 must be between 0 and 25"
+        );
+    }
+}
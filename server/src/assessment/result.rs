@@ -0,0 +1,186 @@
+use crate::{
+    assessment::model::{Assessment, Score},
+    core::error::Result,
+    pupil::model::Pupil,
+    user::model::User,
+};
+use entity::assessment_result::{ActiveModel, Column, Entity};
+use migration::{Condition, OnConflict};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// One cell of the bulk entry grid, where a null score removes the pupil's result.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ResultEntry {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) raw_score: Option<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GridRow {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub(crate) score: Option<Score>,
+}
+
+/// An assessment with a row for every active pupil in its year, and anyone else with a result.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResultGrid {
+    pub(crate) assessment: Assessment,
+    pub(crate) rows: Vec<GridRow>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PupilResult {
+    pub(crate) assessment: Assessment,
+    #[serde(flatten)]
+    pub(crate) score: Score,
+}
+
+impl ResultGrid {
+    pub async fn from_db(assessment: Assessment, db: &DatabaseConnection) -> Result<Self> {
+        let results: HashMap<Uuid, i32> = Entity::find()
+            .filter(Column::AssessmentId.eq(assessment.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|result| (result.pupil_id, result.raw_score))
+            .collect();
+        let pupils = entity::pupil::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(entity::pupil::Column::Year.eq(assessment.year))
+                            .add(entity::pupil::Column::Active.eq(true)),
+                    )
+                    .add(entity::pupil::Column::Id.is_in(results.keys().cloned())),
+            )
+            .order_by_asc(entity::pupil::Column::LastName)
+            .order_by_asc(entity::pupil::Column::FirstNames)
+            .all(db)
+            .await?;
+        let rows = pupils
+            .into_iter()
+            .map(|pupil| GridRow {
+                score: results.get(&pupil.id).map(|raw| assessment.score(*raw)),
+                pupil_id: pupil.id,
+                first_names: pupil.first_names,
+                last_name: pupil.last_name,
+            })
+            .collect();
+        Ok(Self { assessment, rows })
+    }
+
+    /// Save a batch of scores in one go, checking them all before anything is written.
+    pub async fn save(
+        assessment: &Assessment,
+        entries: Vec<ResultEntry>,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        let in_year: HashSet<Uuid> = entity::pupil::Entity::find()
+            .filter(entity::pupil::Column::Id.is_in(entries.iter().map(|e| e.pupil_id)))
+            .filter(entity::pupil::Column::Year.eq(assessment.year))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|pupil| pupil.id)
+            .collect();
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        for entry in &entries {
+            if !seen.insert(entry.pupil_id) {
+                // one statement can't write the same row twice, so Postgres would reject the batch
+                errors.insert(
+                    entry.pupil_id.to_string(),
+                    "pupil appears more than once".into(),
+                );
+            } else if !in_year.contains(&entry.pupil_id) {
+                errors.insert(
+                    entry.pupil_id.to_string(),
+                    format!("pupil is not in year {}", assessment.year),
+                );
+            } else if let Some(raw) = entry.raw_score {
+                if raw < 0 || raw > assessment.max_score {
+                    errors.insert(
+                        entry.pupil_id.to_string(),
+                        format!("score must be between 0 and {}", assessment.max_score),
+                    );
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(ValidationError!("results failed validation").with_fields(errors));
+        }
+
+        let (scored, cleared): (Vec<ResultEntry>, Vec<ResultEntry>) =
+            entries.into_iter().partition(|e| e.raw_score.is_some());
+        let txn = db.begin().await?;
+        if !cleared.is_empty() {
+            Entity::delete_many()
+                .filter(Column::AssessmentId.eq(assessment.id))
+                .filter(Column::PupilId.is_in(cleared.iter().map(|e| e.pupil_id)))
+                .exec(&txn)
+                .await?;
+        }
+        if !scored.is_empty() {
+            Entity::insert_many(scored.into_iter().map(|entry| ActiveModel {
+                assessment_id: Set(assessment.id),
+                pupil_id: Set(entry.pupil_id),
+                raw_score: Set(entry.raw_score.unwrap_or_default()),
+            }))
+            .on_conflict(
+                OnConflict::columns([Column::AssessmentId, Column::PupilId])
+                    .update_column(Column::RawScore)
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+impl PupilResult {
+    /// Every result a pupil has for assessments in the user's years, oldest first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let results: HashMap<Uuid, i32> = Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|result| (result.assessment_id, result.raw_score))
+            .collect();
+        let mut years = Condition::any();
+        for year in &user.years {
+            years = years.add(entity::assessment::Column::Year.eq(*year));
+        }
+        Ok(entity::assessment::Entity::find()
+            .filter(entity::assessment::Column::Id.is_in(results.keys().cloned()))
+            .filter(years)
+            .order_by_asc(entity::assessment::Column::Date)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|model| {
+                let assessment = Assessment::from(model);
+                Self {
+                    score: assessment.score(results[&assessment.id]),
+                    assessment,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod handlers;
+pub mod model;
+pub mod scan;
+pub mod signed;
+pub mod store;
@@ -0,0 +1,186 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    attachment::{model::*, scan::ScanStatus, signed::SignedLink},
+    comment::model::Comment,
+    core::error::*,
+    pupil::model::Pupil,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Check, scan and store an upload. Shared by everything files can be attached to.
+pub(crate) async fn store_upload(
+    state: &AppState,
+    user: &User,
+    pupil_id: Uuid,
+    owner: Owner,
+    upload: NewAttachment,
+) -> Result<Attachment> {
+    let data = upload.decode()?;
+    let mut attachment = Attachment::new(pupil_id, owner, user, &upload, &data);
+    attachment.validate(&data, state.config().attachment_max_bytes)?;
+    attachment.scan_status = state.scanner().scan(&attachment.file_name, &data).await?;
+    if attachment.scan_status == ScanStatus::Infected {
+        tracing::warn!(
+            "{} uploaded {} which failed the virus scan",
+            user.email_address,
+            attachment.file_name
+        );
+        return Err(
+            ValidationError!("attachment failed validation").with_fields(BTreeMap::from([(
+                "data".into(),
+                "the file failed the virus scan".into(),
+            )])),
+        );
+    }
+    attachment
+        .save(&data, state.blob_store().as_ref(), state.database())
+        .await
+}
+
+/// A short lived link to download the attachment, made for this user.
+pub(crate) fn link_to(state: &AppState, user: &User, attachment: &Attachment) -> Value {
+    let link = SignedLink::new(attachment.id, user, Utc::now().naive_utc(), state.config());
+    json!({"url": link.url(attachment.id), "expires_at": link.expires_at()})
+}
+
+pub async fn get_pupil_attachments(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested attachments for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Attachment::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(attachments) => Ok(Json(json!(attachments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn add_pupil_attachment(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(upload): Json<NewAttachment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding attachment to pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let attachment = store_upload(&state, &user, pupil_id, Owner::Pupil, upload).await?;
+    Ok((StatusCode::CREATED, Json(json!(attachment))))
+}
+
+pub async fn get_comment_attachments(
+    State(state): State<AppState>,
+    Path((pupil_id, comment_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested attachments for comment {comment_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let comment_id = Uuid::from_str(&comment_id)?;
+    let comment = Comment::one_from_db(&user, pupil_id, comment_id, state.database()).await?;
+    match Attachment::all_for_comment(&comment, state.database().as_ref()).await {
+        Ok(attachments) => Ok(Json(json!(attachments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn add_comment_attachment(
+    State(state): State<AppState>,
+    Path((pupil_id, comment_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(upload): Json<NewAttachment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding attachment to comment {comment_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let comment_id = Uuid::from_str(&comment_id)?;
+    let comment = Comment::one_from_db(&user, pupil_id, comment_id, state.database()).await?;
+    comment.check_author(&user)?;
+    let owner = Owner::Comment(comment.id);
+    let attachment = store_upload(&state, &user, pupil_id, owner, upload).await?;
+    Ok((StatusCode::CREATED, Json(json!(attachment))))
+}
+
+pub async fn get_attachment_link(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested a download link for attachment {id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let attachment = Attachment::one_for_pupil(&user, pupil_id, id, state.database()).await?;
+    Ok(Json(link_to(&state, &user, &attachment)))
+}
+
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting attachment {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let attachment = Attachment::one_for_pupil(&user, pupil_id, id, state.database()).await?;
+    attachment.check_uploader(&user)?;
+    match attachment
+        .delete(state.blob_store().as_ref(), state.database())
+        .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::StorageError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Fetch a file through a signed link. This sits outside the token check, so the link is the
+/// credential, and the user it was made for must still be able to see the attachment.
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(link): Query<SignedLink>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("downloading attachment {id}");
+    let id = Uuid::from_str(&id)?;
+    link.verify(id, Utc::now().naive_utc(), state.config())?;
+    let user = User::one_from_db(&link.user, state.database()).await?;
+    let attachment = Attachment::one_from_db(&user, id, state.database()).await?;
+    let data = attachment.data(state.blob_store().as_ref()).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}\"",
+                    attachment.file_name.replace(['"', '\\'], "_")
+                ),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".into()),
+            (header::CACHE_CONTROL, "no-store".into()),
+        ],
+        data,
+    ))
+}
@@ -0,0 +1,412 @@
+use crate::{
+    attachment::{scan::ScanStatus, store::BlobStore},
+    comment::model::Comment,
+    concern::model::Concern,
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use base64::{engine::general_purpose, Engine};
+use chrono::{NaiveDateTime, Utc};
+use entity::attachment::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A file attached to a pupil, or to one of their comments or concerns. Only the metadata is kept
+/// in the database, the file is in the blob store and is downloaded through a signed link.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Attachment {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) comment_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) concern_id: Option<Uuid>,
+    pub(crate) file_name: String,
+    pub(crate) content_type: String,
+    pub(crate) size: usize,
+    /// sha256 of the file, hex encoded
+    pub(crate) checksum: String,
+    pub(crate) scan_status: ScanStatus,
+    pub(crate) description: String,
+    pub(crate) uploaded_by: String,
+    pub(crate) uploaded_at: NaiveDateTime,
+    #[serde(skip)]
+    pub(crate) storage_key: String,
+}
+
+/// What an attachment hangs off, which decides who can see it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Owner {
+    Pupil,
+    Comment(Uuid),
+    Concern(Uuid),
+}
+
+impl Attachment {
+    pub fn new(
+        pupil_id: Uuid,
+        owner: Owner,
+        user: &User,
+        upload: &NewAttachment,
+        data: &[u8],
+    ) -> Self {
+        let id = Uuid::new_v4();
+        Self {
+            id,
+            pupil_id,
+            comment_id: match owner {
+                Owner::Comment(id) => Some(id),
+                _ => None,
+            },
+            concern_id: match owner {
+                Owner::Concern(id) => Some(id),
+                _ => None,
+            },
+            file_name: upload.file_name.trim().to_owned(),
+            content_type: upload.content_type.clone(),
+            size: data.len(),
+            checksum: hex::encode(Sha256::digest(data)),
+            scan_status: ScanStatus::Unscanned,
+            description: upload.description.clone(),
+            uploaded_by: user.email_address.clone(),
+            uploaded_at: Utc::now().naive_utc(),
+            storage_key: format!("attachments/{pupil_id}/{id}"),
+        }
+    }
+
+    pub fn owner(&self) -> Owner {
+        match (self.comment_id, self.concern_id) {
+            (_, Some(concern_id)) => Owner::Concern(concern_id),
+            (Some(comment_id), None) => Owner::Comment(comment_id),
+            (None, None) => Owner::Pupil,
+        }
+    }
+
+    /// Files attached to the pupil themselves, newest first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Self::find(
+            Entity::find()
+                .filter(Column::PupilId.eq(pupil_id))
+                .filter(Column::CommentId.is_null())
+                .filter(Column::ConcernId.is_null()),
+            db,
+        )
+        .await
+    }
+
+    pub async fn all_for_comment(comment: &Comment, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Self::find(Entity::find().filter(Column::CommentId.eq(comment.id)), db).await
+    }
+
+    pub async fn all_for_concern(concern: &Concern, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Self::find(Entity::find().filter(Column::ConcernId.eq(concern.id)), db).await
+    }
+
+    async fn find(query: sea_orm::Select<Entity>, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(query
+            .order_by_desc(Column::UploadedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// An attachment on the pupil or one of their comments that the user can see. Concern
+    /// attachments are only reached through their concern.
+    pub async fn one_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .filter(Column::ConcernId.is_null())
+            .one(db)
+            .await?
+        {
+            Some(attachment) => {
+                let attachment = Attachment::from(attachment);
+                attachment.check_access(user, db).await?;
+                Ok(attachment)
+            }
+            None => Err(AttachmentDoesNotExist!()),
+        }
+    }
+
+    pub async fn one_for_concern(
+        concern: &Concern,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        match Entity::find_by_id(id)
+            .filter(Column::ConcernId.eq(concern.id))
+            .one(db)
+            .await?
+        {
+            Some(attachment) => Ok(attachment.into()),
+            None => Err(AttachmentDoesNotExist!()),
+        }
+    }
+
+    /// Any attachment, as long as the user can still see what it is attached to.
+    pub async fn one_from_db(user: &User, id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        match Entity::find_by_id(id).one(db).await? {
+            Some(attachment) => {
+                let attachment = Attachment::from(attachment);
+                attachment.check_access(user, db).await?;
+                Ok(attachment)
+            }
+            None => Err(AttachmentDoesNotExist!()),
+        }
+    }
+
+    /// The same rules as for what the file is attached to: the pupil's year, the comment's
+    /// visibility or who can see the concern.
+    async fn check_access(&self, user: &User, db: &DatabaseConnection) -> Result<()> {
+        match self.owner() {
+            Owner::Pupil => Pupil::one_from_db(user, self.pupil_id, db)
+                .await
+                .map(|_| ()),
+            Owner::Comment(id) => Comment::one_from_db(user, self.pupil_id, id, db)
+                .await
+                .map(|_| ()),
+            Owner::Concern(id) => Concern::one_from_db(user, id, db).await.map(|_| ()),
+        }
+    }
+
+    /// Store the file then record it. If recording fails the file is removed again.
+    pub async fn save(
+        &self,
+        data: &[u8],
+        store: &dyn BlobStore,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        tracing::debug!("storing {} as {}", self.file_name, self.storage_key);
+        store
+            .put(&self.storage_key, &self.content_type, data)
+            .await?;
+        match ActiveModel::from(Model::from(self.clone()))
+            .insert(db)
+            .await
+        {
+            Ok(attachment) => Ok(attachment.into()),
+            Err(error) => {
+                store.delete(&self.storage_key).await?;
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Fetch the file, refusing anything the scanner flagged.
+    pub async fn data(&self, store: &dyn BlobStore) -> Result<Vec<u8>> {
+        if self.scan_status == ScanStatus::Infected {
+            return Err(Unauthorised!("this file failed a virus scan"));
+        }
+        store.get(&self.storage_key).await
+    }
+
+    pub async fn delete(&self, store: &dyn BlobStore, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        store.delete(&self.storage_key).await
+    }
+
+    /// Only the person who uploaded a file or an admin can remove it.
+    pub fn check_uploader(&self, user: &User) -> Result<()> {
+        if self.uploaded_by == user.email_address || user.has_role(constant::ROLE_ADMIN) {
+            Ok(())
+        } else {
+            Err(Unauthorised!(
+                "only the person who uploaded a file can remove it"
+            ))
+        }
+    }
+
+    /// Check the file name, type and size, and that the file really is the type it claims to be.
+    pub fn validate(&self, data: &[u8], max_bytes: usize) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.file_name.is_empty() {
+            errors.insert("file_name".into(), "file name cannot be empty".into());
+        } else if self.file_name.contains(['/', '\\']) {
+            errors.insert("file_name".into(), "file name cannot contain a path".into());
+        }
+        if !constant::ATTACHMENT_TYPES.contains(&self.content_type.as_str()) {
+            errors.insert(
+                "content_type".into(),
+                format!(
+                    "content type must be one of {}",
+                    constant::ATTACHMENT_TYPES.join(", ")
+                ),
+            );
+        } else if !content_matches(&self.content_type, data) {
+            errors.insert(
+                "content_type".into(),
+                format!("the file is not {}", self.content_type),
+            );
+        }
+        if data.is_empty() || data.len() > max_bytes {
+            errors.insert(
+                "data".into(),
+                format!("file must be between 1 and {max_bytes} bytes"),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("attachment failed validation").with_fields(errors))
+        }
+    }
+}
+
+/// Whether the start of the file matches its declared type, so a renamed executable can't be
+/// uploaded as a PDF.
+fn content_matches(content_type: &str, data: &[u8]) -> bool {
+    match content_type {
+        "application/pdf" => data.starts_with(b"%PDF-"),
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            data.starts_with(b"PK\x03\x04")
+        }
+        "image/jpeg" => data.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/png" => data.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "text/plain" => std::str::from_utf8(data).is_ok_and(|text| !text.contains('\0')),
+        _ => false,
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewAttachment {
+    file_name: String,
+    content_type: String,
+    #[serde(default)]
+    description: String,
+    /// the file contents, base64 encoded
+    data: String,
+}
+
+impl NewAttachment {
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        Ok(general_purpose::STANDARD.decode(&self.data)?)
+    }
+}
+
+impl From<Model> for Attachment {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            comment_id: value.comment_id,
+            concern_id: value.concern_id,
+            file_name: value.file_name,
+            content_type: value.content_type,
+            size: value.size as usize,
+            checksum: value.checksum,
+            scan_status: ScanStatus::from_db(&value.scan_status),
+            description: value.description,
+            uploaded_by: value.uploaded_by,
+            uploaded_at: value.uploaded_at,
+            storage_key: value.storage_key,
+        }
+    }
+}
+
+impl From<Attachment> for Model {
+    fn from(value: Attachment) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            comment_id: value.comment_id,
+            concern_id: value.concern_id,
+            file_name: value.file_name,
+            content_type: value.content_type,
+            size: value.size as i64,
+            checksum: value.checksum,
+            storage_key: value.storage_key,
+            scan_status: value.scan_status.as_str().to_owned(),
+            description: value.description,
+            uploaded_by: value.uploaded_by,
+            uploaded_at: value.uploaded_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+    use serde_json::json;
+
+    fn attachment(file_name: &str, content_type: &str, data: &[u8]) -> Attachment {
+        let user = User::new("test", "user", "email", "pass", vec![6]);
+        let upload: NewAttachment = serde_json::from_value(json!({
+            "file_name": file_name,
+            "content_type": content_type,
+            "data": general_purpose::STANDARD.encode(data),
+        }))
+        .unwrap();
+        Attachment::new(Uuid::new_v4(), Owner::Pupil, &user, &upload, data)
+    }
+
+    #[rstest]
+    #[case("report.pdf", "application/pdf", b"%PDF-1.7 ...")]
+    #[case("photo.jpg", "image/jpeg", &[0xFF, 0xD8, 0xFF, 0xE0])]
+    #[case("scan.png", "image/png", b"\x89PNG\r\n\x1a\n....")]
+    #[case(
+        "letter.docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        b"PK\x03\x04...."
+    )]
+    #[case("note.txt", "text/plain", b"hello")]
+    fn valid_attachments(#[case] file_name: &str, #[case] content_type: &str, #[case] data: &[u8]) {
+        assert!(attachment(file_name, content_type, data)
+            .validate(data, 1024)
+            .is_ok());
+    }
+
+    #[rstest]
+    #[case("report.pdf", "application/pdf", b"MZ\x90\x00", "content_type")]
+    #[case("run.exe", "application/x-msdownload", b"MZ\x90\x00", "content_type")]
+    #[case("note.txt", "text/plain", b"\xFF\xFE\x00", "content_type")]
+    #[case("../note.txt", "text/plain", b"hello", "file_name")]
+    #[case(" ", "text/plain", b"hello", "file_name")]
+    #[case("note.txt", "text/plain", b"", "data")]
+    #[case("note.txt", "text/plain", &[b'a'; 2048], "data")]
+    fn invalid_attachments(
+        #[case] file_name: &str,
+        #[case] content_type: &str,
+        #[case] data: &[u8],
+        #[case] field: &str,
+    ) {
+        let error = attachment(file_name, content_type, data)
+            .validate(data, 1024)
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert!(error.fields.unwrap().contains_key(field));
+    }
+
+    #[rstest]
+    fn test_checksum_and_key() {
+        let attachment = attachment("note.txt", "text/plain", b"hello");
+        assert_eq!(
+            attachment.checksum,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(
+            attachment.storage_key,
+            format!("attachments/{}/{}", attachment.pupil_id, attachment.id)
+        );
+        assert_eq!(attachment.owner(), Owner::Pupil);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::core::error::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// What the virus scanner made of a file. Files are scanned on upload and infected ones are never
+/// stored, so `Infected` only turns up if a file is rescanned later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanStatus {
+    Unscanned,
+    Clean,
+    Infected,
+}
+
+impl ScanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanStatus::Unscanned => "unscanned",
+            ScanStatus::Clean => "clean",
+            ScanStatus::Infected => "infected",
+        }
+    }
+
+    /// Anything unrecognised is treated as infected so it can't be downloaded.
+    pub(crate) fn from_db(value: &str) -> Self {
+        match value {
+            "unscanned" => ScanStatus::Unscanned,
+            "clean" => ScanStatus::Clean,
+            _ => ScanStatus::Infected,
+        }
+    }
+}
+
+/// The hook for checking uploads before they are stored. An error rejects the upload rather than
+/// letting an unchecked file through.
+#[async_trait]
+pub trait VirusScanner: Send + Sync {
+    async fn scan(&self, file_name: &str, data: &[u8]) -> Result<ScanStatus>;
+}
+
+/// Use the scanning service at `VIRUS_SCAN_URL` if there is one, otherwise files are stored
+/// unscanned.
+pub fn from_env() -> Arc<dyn VirusScanner> {
+    match std::env::var("VIRUS_SCAN_URL") {
+        Ok(url) => Arc::new(HttpScanner::new(url)),
+        Err(_) => Arc::new(NoScanner),
+    }
+}
+
+/// For when there is no scanner, every file is marked as unscanned.
+pub struct NoScanner;
+
+#[async_trait]
+impl VirusScanner for NoScanner {
+    async fn scan(&self, _file_name: &str, _data: &[u8]) -> Result<ScanStatus> {
+        Ok(ScanStatus::Unscanned)
+    }
+}
+
+/// Posts the file to a scanning service, which answers with `{"infected": bool}`. Most scanners
+/// can be put behind a small service that does this.
+pub struct HttpScanner {
+    url: String,
+    client: Client,
+}
+
+#[derive(Deserialize)]
+struct ScanResponse {
+    infected: bool,
+}
+
+impl HttpScanner {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl VirusScanner for HttpScanner {
+    async fn scan(&self, file_name: &str, data: &[u8]) -> Result<ScanStatus> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("x-file-name", file_name)
+            .body(data.to_vec())
+            .send()
+            .await
+            .map_err(|error| ServerError!(format!("virus scan failed: {error}")))?;
+        if !response.status().is_success() {
+            return Err(ServerError!(format!(
+                "virus scan failed with {}",
+                response.status()
+            )));
+        }
+        match response.json::<ScanResponse>().await {
+            Ok(ScanResponse { infected: true }) => Ok(ScanStatus::Infected),
+            Ok(ScanResponse { infected: false }) => Ok(ScanStatus::Clean),
+            Err(error) => Err(ServerError!(format!("virus scan failed: {error}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("unscanned", ScanStatus::Unscanned)]
+    #[case("clean", ScanStatus::Clean)]
+    #[case("infected", ScanStatus::Infected)]
+    #[case("something else", ScanStatus::Infected)]
+    fn scan_status_from_db(#[case] value: &str, #[case] expected: ScanStatus) {
+        assert_eq!(ScanStatus::from_db(value), expected);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{
+    app::config::Config,
+    core::{constant, error::Result},
+    user::model::User,
+    utils::functions::uri_encode,
+};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fmt::Display;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The query string of a short lived download link. It names the user it was made for so their
+/// access can be checked again when the file is fetched, in case it changed in the meantime. The
+/// resource is the part of the path under `/api/files`, an attachment id or a pupil photo.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignedLink {
+    pub(crate) user: String,
+    /// unix seconds
+    pub(crate) expires: i64,
+    pub(crate) signature: String,
+}
+
+impl SignedLink {
+    pub fn new(resource: impl Display, user: &User, now: NaiveDateTime, config: &Config) -> Self {
+        let expires = now.timestamp() + config.signed_url_seconds;
+        Self {
+            signature: signature(&resource.to_string(), &user.email_address, expires, config),
+            user: user.email_address.clone(),
+            expires,
+        }
+    }
+
+    pub fn url(&self, resource: impl Display) -> String {
+        format!(
+            "{}/{resource}?user={}&expires={}&signature={}",
+            constant::FILES_ENDPOINT,
+            uri_encode(&self.user),
+            self.expires,
+            self.signature
+        )
+    }
+
+    pub fn expires_at(&self) -> Option<NaiveDateTime> {
+        Utc.timestamp_opt(self.expires, 0)
+            .single()
+            .map(|time| time.naive_utc())
+    }
+
+    /// Check the link was made by this server for this resource and hasn't run out.
+    pub fn verify(
+        &self,
+        resource: impl Display,
+        now: NaiveDateTime,
+        config: &Config,
+    ) -> Result<()> {
+        let given = hex::decode(&self.signature).unwrap_or_default();
+        if mac(&resource.to_string(), &self.user, self.expires, config)
+            .verify_slice(&given)
+            .is_err()
+        {
+            return Err(Unauthorised!("the download link is not valid"));
+        }
+        if now.timestamp() > self.expires {
+            return Err(Unauthorised!("the download link has expired"));
+        }
+        Ok(())
+    }
+}
+
+fn mac(resource: &str, user: &str, expires: i64, config: &Config) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(config.signing_key.as_bytes())
+        .expect("hmac takes a key of any length");
+    mac.update(format!("{resource}\n{user}\n{expires}").as_bytes());
+    mac
+}
+
+fn signature(resource: &str, user: &str, expires: i64, config: &Config) -> String {
+    hex::encode(mac(resource, user, expires, config).finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+    use uuid::Uuid;
+
+    fn now() -> NaiveDateTime {
+        "2023-05-24T09:00:00".parse().unwrap()
+    }
+
+    fn link(config: &Config) -> (Uuid, SignedLink) {
+        let user = User::new("test", "user", "a+b@school.com", "pass", vec![6]);
+        let id = Uuid::new_v4();
+        (id, SignedLink::new(id, &user, now(), config))
+    }
+
+    #[rstest]
+    fn test_link_verifies_until_it_expires() {
+        let config = Config::default();
+        let (id, link) = link(&config);
+        assert!(link.verify(id, now(), &config).is_ok());
+        let later = now() + chrono::Duration::seconds(config.signed_url_seconds + 1);
+        assert_eq!(
+            link.verify(id, later, &config).unwrap_err().kind,
+            ErrorKind::Unauthorised
+        );
+    }
+
+    #[rstest]
+    fn test_tampered_links_fail() {
+        let config = Config::default();
+        let (id, link) = link(&config);
+        let other_user = SignedLink {
+            user: "someone@school.com".into(),
+            ..link.clone()
+        };
+        assert!(other_user.verify(id, now(), &config).is_err());
+        let extended = SignedLink {
+            expires: link.expires + 3600,
+            ..link.clone()
+        };
+        assert!(extended.verify(id, now(), &config).is_err());
+        assert!(link.verify(Uuid::new_v4(), now(), &config).is_err());
+        assert!(link.verify(id, now(), &Config::default()).is_err());
+    }
+
+    #[rstest]
+    fn test_url_encodes_the_user() {
+        let config = Config::default();
+        let (id, link) = link(&config);
+        assert!(link
+            .url(id)
+            .starts_with(&format!("/api/files/{id}?user=a%2Bb%40school.com&expires=")));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod code;
+pub mod handlers;
+pub mod register;
+pub mod summary;
@@ -0,0 +1,109 @@
+use crate::{
+    core::{constant, error::Result},
+    user::model::User,
+};
+use entity::attendance_code::{ActiveModel, Column, Entity, Model};
+use migration::OnConflict;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a mark counts towards a pupil's attendance.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Meaning {
+    Present,
+    /// off site but on an approved educational activity, which counts as present
+    ApprovedActivity,
+    Authorised,
+    Unauthorised,
+    /// not a possible session, so left out of the percentage entirely
+    NotRequired,
+}
+
+impl Meaning {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Meaning::Present => "present",
+            Meaning::ApprovedActivity => "approved_activity",
+            Meaning::Authorised => "authorised",
+            Meaning::Unauthorised => "unauthorised",
+            Meaning::NotRequired => "not_required",
+        }
+    }
+
+    /// Anything unrecognised counts against the pupil so it gets noticed.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "present" => Meaning::Present,
+            "approved_activity" => Meaning::ApprovedActivity,
+            "authorised" => Meaning::Authorised,
+            "not_required" => Meaning::NotRequired,
+            _ => Meaning::Unauthorised,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct AttendanceCode {
+    pub(crate) code: String,
+    pub(crate) description: String,
+    pub(crate) meaning: Meaning,
+}
+
+impl AttendanceCode {
+    pub async fn all_from_db(db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(Entity::find()
+            .order_by_asc(Column::Code)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Add a code, or change what an existing one means. Only admins can change the codes.
+    pub async fn save(&self, user: &User, db: &DatabaseConnection) -> Result<Self> {
+        if !user.has_role(constant::ROLE_ADMIN) {
+            return Err(Unauthorised!("only an admin can change attendance codes"));
+        }
+        Entity::insert(ActiveModel {
+            code: Set(self.code.clone()),
+            description: Set(self.description.clone()),
+            meaning: Set(self.meaning.as_str().to_owned()),
+        })
+        .on_conflict(
+            OnConflict::column(Column::Code)
+                .update_columns([Column::Description, Column::Meaning])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(self.clone())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.code.chars().count() != 1 || self.code.trim().is_empty() {
+            errors.insert("code".into(), "code must be a single character".into());
+        }
+        if self.description.trim().is_empty() {
+            errors.insert("description".into(), "description cannot be empty".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("attendance code failed validation").with_fields(errors))
+        }
+    }
+}
+
+impl From<Model> for AttendanceCode {
+    fn from(value: Model) -> Self {
+        Self {
+            code: value.code,
+            description: value.description,
+            meaning: Meaning::from_db(&value.meaning),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    attendance::{code::*, register::*, summary::*},
+    core::error::*,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    Extension,
+};
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_attendance_codes(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested attendance codes");
+    match AttendanceCode::all_from_db(state.database().as_ref()).await {
+        Ok(codes) => Ok(Json(json!(codes))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_attendance_code(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(code): Json<AttendanceCode>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving attendance code {}", code.code);
+    code.validate()?;
+    match code.save(&user, state.database().as_ref()).await {
+        Ok(code) => Ok(Json(json!(code))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_register(
+    State(state): State<AppState>,
+    Path((year, date)): Path<(i32, NaiveDate)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested register for year {year} on {date}");
+    match Register::from_db(&user, year, date, state.database().as_ref()).await {
+        Ok(register) => Ok(Json(json!(register))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_register(
+    State(state): State<AppState>,
+    Path((year, date)): Path<(i32, NaiveDate)>,
+    Extension(user): Extension<User>,
+    Json(entries): Json<Vec<MarkEntry>>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving {} marks for year {year} on {date}", entries.len());
+    Register::save(&user, year, date, entries, state.database()).await?;
+    match Register::from_db(&user, year, date, state.database().as_ref()).await {
+        Ok(register) => Ok(Json(json!(register))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_attendance(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested attendance for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let today = Utc::now().date_naive();
+    match AttendanceSummary::for_pupil(
+        &user,
+        pupil_id,
+        state.config(),
+        today,
+        state.database().as_ref(),
+    )
+    .await
+    {
+        Ok(summary) => Ok(Json(json!(summary))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    attendance::code::AttendanceCode,
+    core::{constant, error::Result},
+    user::model::User,
+};
+use chrono::{NaiveDate, Utc};
+use entity::attendance_mark::{ActiveModel, Column, Entity};
+use migration::{Condition, OnConflict};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// One session mark to save, where a null code removes the mark.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct MarkEntry {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) session: String,
+    pub(crate) code: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RegisterRow {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) am: Option<String>,
+    pub(crate) pm: Option<String>,
+}
+
+/// The register for a year group on one day, with a row for every pupil enrolled that day.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Register {
+    pub(crate) year: i32,
+    pub(crate) date: NaiveDate,
+    pub(crate) rows: Vec<RegisterRow>,
+}
+
+impl Register {
+    pub async fn from_db(
+        user: &User,
+        year: i32,
+        date: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        user.check_year(year)?;
+        let pupils = enrolled(year, date, db).await?;
+        let mut marks: HashMap<(Uuid, String), String> = Entity::find()
+            .filter(Column::Date.eq(date))
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|mark| ((mark.pupil_id, mark.session), mark.code))
+            .collect();
+        let rows = pupils
+            .into_iter()
+            .map(|pupil| RegisterRow {
+                am: marks.remove(&(pupil.id, "am".into())),
+                pm: marks.remove(&(pupil.id, "pm".into())),
+                pupil_id: pupil.id,
+                first_names: pupil.first_names,
+                last_name: pupil.last_name,
+            })
+            .collect();
+        Ok(Self { year, date, rows })
+    }
+
+    /// Save a batch of marks in one go, checking them all before anything is written.
+    pub async fn save(
+        user: &User,
+        year: i32,
+        date: NaiveDate,
+        entries: Vec<MarkEntry>,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        user.check_year(year)?;
+        let enrolled: Vec<Uuid> = enrolled(year, date, db)
+            .await?
+            .into_iter()
+            .map(|pupil| pupil.id)
+            .collect();
+        let codes: Vec<String> = AttendanceCode::all_from_db(db)
+            .await?
+            .into_iter()
+            .map(|code| code.code)
+            .collect();
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        for entry in &entries {
+            let key = format!("{}.{}", entry.pupil_id, entry.session);
+            if !enrolled.contains(&entry.pupil_id) {
+                errors.insert(
+                    key,
+                    format!("pupil is not enrolled in year {year} on {date}"),
+                );
+            } else if !constant::ATTENDANCE_SESSIONS.contains(&entry.session.as_str()) {
+                errors.insert(key, "session must be am or pm".into());
+            } else if let Some(code) = &entry.code {
+                if !codes.contains(code) {
+                    errors.insert(key, format!("{code} is not an attendance code"));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(ValidationError!("register failed validation").with_fields(errors));
+        }
+
+        let (marked, cleared): (Vec<MarkEntry>, Vec<MarkEntry>) =
+            entries.into_iter().partition(|e| e.code.is_some());
+        let recorded_at = Utc::now().naive_utc();
+        let txn = db.begin().await?;
+        for entry in cleared {
+            Entity::delete_many()
+                .filter(Column::PupilId.eq(entry.pupil_id))
+                .filter(Column::Date.eq(date))
+                .filter(Column::Session.eq(entry.session))
+                .exec(&txn)
+                .await?;
+        }
+        if !marked.is_empty() {
+            Entity::insert_many(marked.into_iter().map(|entry| ActiveModel {
+                pupil_id: Set(entry.pupil_id),
+                date: Set(date),
+                session: Set(entry.session),
+                code: Set(entry.code.unwrap_or_default()),
+                recorded_by: Set(user.email_address.clone()),
+                recorded_at: Set(recorded_at),
+            }))
+            .on_conflict(
+                OnConflict::columns([Column::PupilId, Column::Date, Column::Session])
+                    .update_columns([Column::Code, Column::RecordedBy, Column::RecordedAt])
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// Pupils in the year who had started and not yet left on the date. Pupils with no end date
+/// only count while they are still active.
+async fn enrolled(
+    year: i32,
+    date: NaiveDate,
+    db: &DatabaseConnection,
+) -> Result<Vec<entity::pupil::Model>> {
+    use entity::pupil::Column as Pupil;
+    Ok(entity::pupil::Entity::find()
+        .filter(Pupil::Year.eq(year))
+        .filter(Pupil::StartDate.lte(date))
+        .filter(
+            Condition::any().add(Pupil::EndDate.gte(date)).add(
+                Condition::all()
+                    .add(Pupil::EndDate.is_null())
+                    .add(Pupil::Active.eq(true)),
+            ),
+        )
+        .order_by_asc(Pupil::LastName)
+        .order_by_asc(Pupil::FirstNames)
+        .all(db)
+        .await?)
+}
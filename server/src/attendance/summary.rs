@@ -0,0 +1,257 @@
+use crate::{
+    app::config::Config,
+    attendance::code::{AttendanceCode, Meaning},
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::{Datelike, NaiveDate};
+use entity::attendance_mark::{Column, Entity};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// A pupil's attendance over the academic year so far.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AttendanceSummary {
+    pub(crate) from: NaiveDate,
+    pub(crate) to: NaiveDate,
+    pub(crate) possible_sessions: u32,
+    pub(crate) present_sessions: u32,
+    pub(crate) authorised_absences: u32,
+    pub(crate) unauthorised_absences: u32,
+    /// to one decimal place, or none before there have been any possible sessions
+    pub(crate) percentage: Option<f64>,
+    pub(crate) persistent_absence: bool,
+}
+
+impl AttendanceSummary {
+    pub fn from_meanings<I>(meanings: I, threshold: f64) -> Self
+    where
+        I: IntoIterator<Item = Meaning>,
+    {
+        let mut summary = Self::default();
+        for meaning in meanings {
+            match meaning {
+                Meaning::Present | Meaning::ApprovedActivity => summary.present_sessions += 1,
+                Meaning::Authorised => summary.authorised_absences += 1,
+                Meaning::Unauthorised => summary.unauthorised_absences += 1,
+                Meaning::NotRequired => continue,
+            }
+            summary.possible_sessions += 1;
+        }
+        if summary.possible_sessions > 0 {
+            let percentage =
+                summary.present_sessions as f64 / summary.possible_sessions as f64 * 100.0;
+            summary.percentage = Some((percentage * 10.0).round() / 10.0);
+            let missed = summary.possible_sessions - summary.present_sessions;
+            summary.persistent_absence =
+                missed as f64 * 100.0 >= threshold * summary.possible_sessions as f64;
+        }
+        summary
+    }
+
+    pub async fn for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        config: &Config,
+        today: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Self::for_pupils(&[pupil_id], config, today, db)
+            .await?
+            .remove(&pupil_id)
+            .unwrap_or_else(|| Self::from_meanings([], config.persistent_absence_percent))
+            .with_dates(academic_year_start(today), today))
+    }
+
+    /// Summaries for every pupil with a mark this academic year, up to and including today.
+    pub async fn for_pupils(
+        pupil_ids: &[Uuid],
+        config: &Config,
+        today: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<HashMap<Uuid, Self>> {
+        Self::between(pupil_ids, academic_year_start(today), today, config, db).await
+    }
+
+    /// Summaries for every pupil with a mark between the two dates, including both.
+    pub async fn between(
+        pupil_ids: &[Uuid],
+        from: NaiveDate,
+        to: NaiveDate,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<HashMap<Uuid, Self>> {
+        if pupil_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let meanings: HashMap<String, Meaning> = AttendanceCode::all_from_db(db)
+            .await?
+            .into_iter()
+            .map(|code| (code.code, code.meaning))
+            .collect();
+        let marks: Vec<(Uuid, String)> = Entity::find()
+            .select_only()
+            .column(Column::PupilId)
+            .column(Column::Code)
+            .filter(Column::PupilId.is_in(pupil_ids.iter().copied()))
+            .filter(Column::Date.between(from, to))
+            .into_tuple()
+            .all(db)
+            .await?;
+        let mut by_pupil: HashMap<Uuid, Vec<Meaning>> = HashMap::new();
+        for (pupil_id, code) in marks {
+            by_pupil.entry(pupil_id).or_default().push(
+                meanings
+                    .get(&code)
+                    .copied()
+                    .unwrap_or(Meaning::Unauthorised),
+            );
+        }
+        Ok(by_pupil
+            .into_iter()
+            .map(|(pupil_id, meanings)| {
+                (
+                    pupil_id,
+                    Self::from_meanings(meanings, config.persistent_absence_percent)
+                        .with_dates(from, to),
+                )
+            })
+            .collect())
+    }
+
+    /// A pupil's attendance for every academic year they have marks in, keyed by the year the
+    /// academic year starts in, running up to their last mark that year.
+    pub async fn by_academic_year(
+        pupil_id: Uuid,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<BTreeMap<i32, Self>> {
+        let meanings: HashMap<String, Meaning> = AttendanceCode::all_from_db(db)
+            .await?
+            .into_iter()
+            .map(|code| (code.code, code.meaning))
+            .collect();
+        let marks: Vec<(NaiveDate, String)> = Entity::find()
+            .select_only()
+            .column(Column::Date)
+            .column(Column::Code)
+            .filter(Column::PupilId.eq(pupil_id))
+            .into_tuple()
+            .all(db)
+            .await?;
+        let mut by_year: BTreeMap<NaiveDate, (NaiveDate, Vec<Meaning>)> = BTreeMap::new();
+        for (date, code) in marks {
+            let (last, year) = by_year
+                .entry(academic_year_start(date))
+                .or_insert((date, vec![]));
+            *last = (*last).max(date);
+            year.push(
+                meanings
+                    .get(&code)
+                    .copied()
+                    .unwrap_or(Meaning::Unauthorised),
+            );
+        }
+        Ok(by_year
+            .into_iter()
+            .map(|(start, (last, meanings))| {
+                (
+                    start.year(),
+                    Self::from_meanings(meanings, config.persistent_absence_percent)
+                        .with_dates(start, last),
+                )
+            })
+            .collect())
+    }
+
+    /// Fill in the derived persistent absence flag on pupils about to be sent to the client.
+    pub async fn flag_persistent_absence(
+        pupils: &mut [Pupil],
+        config: &Config,
+        today: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        let ids: Vec<Uuid> = pupils.iter().map(|p| p.id).collect();
+        let summaries = Self::for_pupils(&ids, config, today, db).await?;
+        for pupil in pupils {
+            pupil.persistent_absence = summaries
+                .get(&pupil.id)
+                .map(|s| s.persistent_absence)
+                .unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    pub(crate) fn with_dates(mut self, from: NaiveDate, to: NaiveDate) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+}
+
+/// The first day of the academic year the date falls in.
+pub fn academic_year_start(date: NaiveDate) -> NaiveDate {
+    let year = if date.month() >= constant::ACADEMIC_YEAR_START_MONTH {
+        date.year()
+    } else {
+        date.year() - 1
+    };
+    NaiveDate::from_ymd_opt(year, constant::ACADEMIC_YEAR_START_MONTH, 1)
+        .expect("the first of a month is always a valid date")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("2023-03-01", "2022-09-01")]
+    #[case("2023-09-01", "2023-09-01")]
+    #[case("2023-08-31", "2022-09-01")]
+    fn test_academic_year_start(#[case] date: &str, #[case] expected: &str) {
+        assert_eq!(
+            academic_year_start(date.parse().unwrap()),
+            expected.parse::<NaiveDate>().unwrap()
+        );
+    }
+
+    #[rstest]
+    fn test_summary_ignores_sessions_not_required() {
+        let mut meanings = vec![Meaning::Present; 17];
+        meanings.extend([
+            Meaning::ApprovedActivity,
+            Meaning::Authorised,
+            Meaning::Unauthorised,
+            Meaning::NotRequired,
+            Meaning::NotRequired,
+        ]);
+        let summary = AttendanceSummary::from_meanings(meanings, 10.0);
+        assert_eq!(summary.possible_sessions, 20);
+        assert_eq!(summary.present_sessions, 18);
+        assert_eq!(summary.authorised_absences, 1);
+        assert_eq!(summary.unauthorised_absences, 1);
+        assert_eq!(summary.percentage, Some(90.0));
+        // missing exactly the threshold counts as persistent absence
+        assert!(summary.persistent_absence);
+    }
+
+    #[rstest]
+    #[case(vec![], None, false)]
+    #[case(vec![Meaning::NotRequired], None, false)]
+    #[case(vec![Meaning::Present, Meaning::Present, Meaning::Authorised], Some(66.7), true)]
+    #[case(vec![Meaning::Present; 3], Some(100.0), false)]
+    fn test_summary_percentage(
+        #[case] meanings: Vec<Meaning>,
+        #[case] percentage: Option<f64>,
+        #[case] persistent_absence: bool,
+    ) {
+        let summary = AttendanceSummary::from_meanings(meanings, 10.0);
+        assert_eq!(summary.percentage, percentage);
+        assert_eq!(summary.persistent_absence, persistent_absence);
+    }
+}
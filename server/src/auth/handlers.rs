@@ -35,7 +35,7 @@ pub async fn logout_handler(
     let user: User = User::one_from_db(&decoded.email_address, state.database()).await?;
     user.refresh_secret(state.database()).await?;
     debug!("refreshed secret for {}", decoded.email_address);
-    if let Ok(_) = authorize_token(auth_token.token(), &user.secret) {
+    if authorize_token(auth_token.token(), &user.secret).is_ok() {
         Ok(StatusCode::OK)
     } else {
         Err(InvalidJwt!())
@@ -59,8 +59,13 @@ async fn get_and_validate_user(
     db: &DatabaseConnection,
 ) -> Result<User> {
     let user: Result<User> = User::one_from_db(&email, db).await;
-    if let Ok(user) = user {
-        if pass == user.hashed_password {
+    if let Ok(mut user) = user {
+        if user.check_password(&pass) {
+            if user.has_legacy_password() {
+                debug!("hashing the legacy password for {email}");
+                user.set_password(&pass)?;
+                user = user.update(db).await?;
+            }
             Ok(user)
         } else {
             Err(InvalidCredentials!())
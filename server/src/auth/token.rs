@@ -31,6 +31,7 @@ pub fn generate_auth_token(user: &User) -> Result<String> {
         first_names: user.first_names.to_owned(),
         last_name: user.last_name.to_owned(),
         years: user.years.to_owned(),
+        roles: user.roles.to_owned(),
     };
     let header = Header::new(Algorithm::HS512);
     encode(&header, &claims, &EncodingKey::from_secret(&user.secret))
@@ -39,7 +40,7 @@ pub fn generate_auth_token(user: &User) -> Result<String> {
 
 pub fn authorize_token(token: &str, secret: &[u8]) -> Result<AuthToken> {
     Ok(decode::<AuthToken>(
-        &token,
+        token,
         &DecodingKey::from_secret(secret),
         &Validation::new(Algorithm::HS512),
     )?
@@ -66,7 +67,7 @@ pub async fn auth_service<B>(
 ) -> Result<Response> {
     let decoded = decode_token(auth_header.token())?;
     let user = User::one_from_db(&decoded.email_address, state.database()).await?;
-    if let Ok(_) = authorize_token(auth_header.token(), &user.secret) {
+    if authorize_token(auth_header.token(), &user.secret).is_ok() {
         request.extensions_mut().insert(user);
         let response = next.run(request).await;
         // create fresh token to pass in response
@@ -83,6 +84,8 @@ pub struct AuthToken {
     pub(crate) first_names: String,
     pub(crate) last_name: String,
     pub(crate) years: Vec<u32>,
+    #[serde(default)]
+    pub(crate) roles: Vec<String>,
 }
 
 #[cfg(test)]
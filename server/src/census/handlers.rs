@@ -0,0 +1,75 @@
+use crate::{app::state::AppState, census::model::CensusReturn, core::error::*, user::model::User};
+use axum::{
+    extract::{Json, Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+
+fn pass_through(error: Error) -> Error {
+    match error.kind {
+        ErrorKind::DatabaseError => DatabaseError!(error.to_string()),
+        ErrorKind::ValidationError | ErrorKind::Unauthorised | ErrorKind::ServerError => error,
+        _ => UnknownError!(),
+    }
+}
+
+/// The pupils on roll on the census date as they'll be returned, and the problems with their data
+/// that need fixing first, each naming the pupil.
+pub async fn get_census(
+    State(state): State<AppState>,
+    Path(census_date): Path<NaiveDate>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested the census as at {census_date}");
+    let now = Utc::now().naive_utc();
+    match CensusReturn::from_db(
+        &user,
+        census_date,
+        state.config(),
+        now,
+        state.database().as_ref(),
+    )
+    .await
+    {
+        Ok(census) => Ok(Json(json!(census))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+/// Download the return file, refused while the census still has errors.
+pub async fn download_census_return(
+    State(state): State<AppState>,
+    Path(census_date): Path<NaiveDate>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("generating the census return as at {census_date}");
+    let now = Utc::now().naive_utc();
+    let census = CensusReturn::from_db(
+        &user,
+        census_date,
+        state.config(),
+        now,
+        state.database().as_ref(),
+    )
+    .await
+    .map_err(pass_through)?;
+    let data = census.to_xml().map_err(pass_through)?;
+    let file_name = format!(
+        "{}{}_census_{census_date}.xml",
+        census.school.lea, census.school.estab
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/xml".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        data,
+    ))
+}
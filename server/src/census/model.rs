@@ -0,0 +1,352 @@
+use super::rules::{self, Problem, Severity};
+use crate::{
+    aln::register::{Provision, RegisterEntry},
+    app::config::Config,
+    attendance::summary::academic_year_start,
+    core::{constant, error::Result},
+    ctf::{
+        model::{language_code, nc_year, sex_code, School},
+        xml::Element,
+    },
+    eal::model::{EalAssessment, Stage},
+    flag::model::FlagPeriod,
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const ROOT: &str = "CensusReturn";
+
+/// One pupil as they stood on census day. Flags, EAL stage and ALN provision come from their
+/// dated history rather than how they are today.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CensusRecord {
+    pub(crate) pupil_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) upn: Option<String>,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_first_names: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) date_of_birth: Option<NaiveDate>,
+    pub(crate) sex: String,
+    pub(crate) year: i32,
+    pub(crate) nc_year: String,
+    pub(crate) start_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) end_date: Option<NaiveDate>,
+    pub(crate) active: bool,
+    pub(crate) fsm: bool,
+    pub(crate) eal: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) eal_stage: Option<Stage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) home_language: Option<String>,
+    pub(crate) aln: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) aln_provision: Option<Provision>,
+    pub(crate) looked_after: bool,
+}
+
+impl CensusRecord {
+    fn to_element(&self) -> Element {
+        Element::new("Pupil")
+            .with_some("UPN", self.upn.as_deref())
+            .with(Element::text("Surname", &self.last_name))
+            .with(Element::text("Forename", &self.first_names))
+            .with_some("PreferredSurname", self.preferred_last_name.as_deref())
+            .with_some("PreferredForename", self.preferred_first_names.as_deref())
+            .with_some("DOB", self.date_of_birth.map(|date| date.to_string()))
+            .with(Element::text("Sex", &self.sex))
+            .with(Element::text("NCyearActual", &self.nc_year))
+            .with(Element::text("EntryDate", self.start_date.to_string()))
+            .with_some("LeavingDate", self.end_date.map(|date| date.to_string()))
+            .with(Element::text("FSMeligible", self.fsm.to_string()))
+            .with(Element::text("EAL", self.eal.to_string()))
+            .with_some("EALacquisition", self.eal_stage.map(|stage| stage.as_str()))
+            .with_some(
+                "HomeLanguage",
+                self.home_language.as_deref().and_then(language_code),
+            )
+            .with_some(
+                "ALNprovision",
+                self.aln_provision.map(|provision| provision.as_str()),
+            )
+            .with(Element::text("InCare", self.looked_after.to_string()))
+    }
+}
+
+/// The pupils on roll on census day and everything wrong with their data. It's worked out afresh
+/// each time from the pupil records, so fixing a pupil shows straight away.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CensusReturn {
+    pub(crate) census_date: NaiveDate,
+    pub(crate) school: School,
+    pub(crate) created: NaiveDateTime,
+    pub(crate) pupils: Vec<CensusRecord>,
+    pub(crate) problems: Vec<Problem>,
+}
+
+impl CensusReturn {
+    /// Everyone in the school who had started by the census date and not left before it, whatever
+    /// the admin's own years, in the year group they were in on the day. Pupils marked as left
+    /// with no leave date are kept in so the rules can flag them.
+    pub async fn from_db(
+        user: &User,
+        census_date: NaiveDate,
+        config: &Config,
+        now: NaiveDateTime,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        user.check_role(
+            constant::ROLE_ADMIN,
+            "only an admin can prepare the census return",
+        )?;
+        let mut pupils: Vec<Pupil> = entity::pupil::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        pupils.retain(|pupil| {
+            pupil.start_date <= census_date
+                && pupil
+                    .end_date
+                    .is_none_or(|end_date| census_date <= end_date)
+        });
+        FlagPeriod::flag_as_at(&mut pupils, census_date, db).await?;
+        let ids: Vec<Uuid> = pupils.iter().map(|pupil| pupil.id).collect();
+        let stages: HashMap<Uuid, Stage> = entity::eal_assessment::Entity::find()
+            .filter(entity::eal_assessment::Column::PupilId.is_in(ids.clone()))
+            .filter(entity::eal_assessment::Column::AssessedOn.lte(census_date))
+            .order_by_asc(entity::eal_assessment::Column::AssessedOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|assessment| {
+                let assessment = EalAssessment::from(assessment);
+                (assessment.pupil_id, assessment.stage)
+            })
+            .collect();
+        let provisions: HashMap<Uuid, Provision> = entity::aln_register::Entity::find()
+            .filter(entity::aln_register::Column::PupilId.is_in(ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(RegisterEntry::from)
+            .filter(|entry| {
+                entry.added_on <= census_date
+                    && entry
+                        .closed_on
+                        .is_none_or(|closed_on| census_date < closed_on)
+            })
+            .map(|entry| (entry.pupil_id, entry.provision))
+            .collect();
+        let mut records: Vec<CensusRecord> = pupils
+            .into_iter()
+            .map(|pupil| (year_as_at(&pupil, census_date, now.date()), pupil))
+            .map(|(year, pupil)| CensusRecord {
+                pupil_id: pupil.id,
+                sex: sex_code(&pupil.gender).into(),
+                nc_year: nc_year(year),
+                eal_stage: stages.get(&pupil.id).copied(),
+                aln_provision: provisions.get(&pupil.id).copied(),
+                upn: pupil.upn,
+                first_names: pupil.first_names,
+                last_name: pupil.last_name,
+                preferred_first_names: pupil.preferred_first_names,
+                preferred_last_name: pupil.preferred_last_name,
+                date_of_birth: pupil.date_of_birth,
+                year,
+                start_date: pupil.start_date,
+                end_date: pupil.end_date,
+                active: pupil.active,
+                fsm: pupil.free_school_meals,
+                eal: pupil.english_as_additional_language,
+                home_language: pupil.home_language,
+                aln: pupil.additional_learning_needs,
+                looked_after: pupil.looked_after_child,
+            })
+            .collect();
+        records.sort_by(|a, b| {
+            (a.year, &a.last_name, &a.first_names).cmp(&(b.year, &b.last_name, &b.first_names))
+        });
+        Ok(Self {
+            problems: rules::check(&records, census_date, config),
+            census_date,
+            school: School::from_config(config),
+            created: now,
+            pupils: records,
+        })
+    }
+
+    pub fn errors(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn to_element(&self) -> Element {
+        let school = Element::new("School")
+            .with(Element::text("LEA", &self.school.lea))
+            .with(Element::text("Estab", &self.school.estab))
+            .with_some("SchoolName", self.school.name.as_deref());
+        let header = Element::new("Header")
+            .with(Element::text("DocumentName", "School Census"))
+            .with(Element::text("CensusDate", self.census_date.to_string()))
+            .with(Element::text(
+                "DateTime",
+                self.created.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ))
+            .with(school)
+            .with(Element::text("PupilCount", self.pupils.len().to_string()));
+        Element::new(ROOT).with(header).with(Element {
+            name: "Pupils".into(),
+            text: String::new(),
+            children: self.pupils.iter().map(CensusRecord::to_element).collect(),
+        })
+    }
+
+    /// The return file, which can't be made until every error is fixed. Queries don't stop it.
+    pub fn to_xml(&self) -> Result<Vec<u8>> {
+        match self.errors() {
+            0 => self.to_element().to_xml(),
+            1 => Err(ValidationError!(
+                "the census has 1 error to fix before the return can be made"
+            )),
+            errors => Err(ValidationError!(format!(
+                "the census has {errors} errors to fix before the return can be made"
+            ))),
+        }
+    }
+}
+
+/// The year group a pupil was in on the census date. A pupil's year is the one they're in today,
+/// or the one they left from, so it's taken back a year for each academic year since the census.
+fn year_as_at(pupil: &Pupil, census_date: NaiveDate, today: NaiveDate) -> i32 {
+    let current = pupil
+        .end_date
+        .filter(|end_date| *end_date < today)
+        .unwrap_or(today);
+    pupil.year - (academic_year_start(current).year() - academic_year_start(census_date).year())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn census(problems: Vec<Problem>) -> CensusReturn {
+        let record = CensusRecord {
+            pupil_id: Uuid::new_v4(),
+            upn: Some("H801200001001".into()),
+            first_names: "Alex".into(),
+            last_name: "Smith".into(),
+            preferred_first_names: None,
+            preferred_last_name: None,
+            date_of_birth: Some("2016-10-01".parse().unwrap()),
+            sex: "F".into(),
+            year: 2,
+            nc_year: "2".into(),
+            start_date: "2020-09-01".parse().unwrap(),
+            end_date: None,
+            active: true,
+            fsm: true,
+            eal: true,
+            eal_stage: Some(Stage::B),
+            home_language: Some("pol".into()),
+            aln: false,
+            aln_provision: None,
+            looked_after: false,
+        };
+        CensusReturn {
+            census_date: "2024-01-18".parse().unwrap(),
+            school: School {
+                lea: "801".into(),
+                estab: "2000".into(),
+                name: Some("Example Primary".into()),
+            },
+            created: "2024-01-19T10:00:00".parse().unwrap(),
+            problems: problems
+                .into_iter()
+                .map(|mut problem| {
+                    problem.pupil_id = record.pupil_id;
+                    problem
+                })
+                .collect(),
+            pupils: vec![record],
+        }
+    }
+
+    fn problem(severity: Severity) -> Problem {
+        Problem {
+            pupil_id: Uuid::nil(),
+            first_names: "Alex".into(),
+            last_name: "Smith".into(),
+            year: 2,
+            field: "upn".into(),
+            message: "UPN is missing".into(),
+            severity,
+        }
+    }
+
+    #[rstest]
+    #[case(None, "2024-01-18", "2024-03-01", 4)]
+    #[case(None, "2024-01-18", "2024-09-02", 3)]
+    #[case(None, "2023-01-19", "2024-09-02", 2)]
+    #[case(Some("2024-07-19"), "2024-01-18", "2025-01-10", 4)]
+    #[case(Some("2025-01-31"), "2024-01-18", "2025-01-10", 3)]
+    fn year_is_as_at_census_day(
+        #[case] end_date: Option<&str>,
+        #[case] census_date: &str,
+        #[case] today: &str,
+        #[case] year: i32,
+    ) {
+        let mut pupil = crate::pupil::import::blank("");
+        pupil.year = 4;
+        pupil.end_date = end_date.map(|date| date.parse().unwrap());
+        assert_eq!(
+            year_as_at(&pupil, census_date.parse().unwrap(), today.parse().unwrap()),
+            year
+        );
+    }
+
+    #[rstest]
+    fn return_file_holds_each_pupil() {
+        let xml =
+            String::from_utf8(census(vec![problem(Severity::Query)]).to_xml().unwrap()).unwrap();
+        let document = Element::parse(xml.as_bytes()).unwrap();
+        assert_eq!(
+            document.find(&["Header", "CensusDate"]).unwrap().text,
+            "2024-01-18"
+        );
+        assert_eq!(
+            document.find(&["Header", "School", "Estab"]).unwrap().text,
+            "2000"
+        );
+        let pupil = document.find(&["Pupils", "Pupil"]).unwrap();
+        assert_eq!(pupil.value("UPN").as_deref(), Some("H801200001001"));
+        assert_eq!(pupil.value("FSMeligible").as_deref(), Some("true"));
+        assert_eq!(pupil.value("EALacquisition").as_deref(), Some("B"));
+        assert_eq!(pupil.value("HomeLanguage").as_deref(), Some("POL"));
+        assert_eq!(pupil.value("ALNprovision"), None);
+    }
+
+    #[rstest]
+    #[case(vec![Severity::Error], "the census has 1 error to fix before the return can be made")]
+    #[case(vec![Severity::Error, Severity::Query, Severity::Error], "the census has 2 errors to fix before the return can be made")]
+    fn errors_stop_the_return_file(#[case] severities: Vec<Severity>, #[case] message: &str) {
+        let error = census(severities.into_iter().map(problem).collect())
+            .to_xml()
+            .unwrap_err();
+        assert_eq!(error.message, Some(message.into()));
+    }
+}
@@ -0,0 +1,328 @@
+use super::model::CensusRecord;
+use crate::{
+    app::config::Config, attendance::summary::academic_year_start, ctf::model::language_code, utils,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Errors have to be fixed before the return can be made. Queries are worth checking but can be
+/// right, like a pupil taught outside their age group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Query,
+}
+
+/// Something wrong with one pupil's data, naming the pupil so it can be fixed on their record.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Problem {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) year: i32,
+    pub(crate) field: String,
+    pub(crate) message: String,
+    pub(crate) severity: Severity,
+}
+
+impl Problem {
+    fn new(
+        record: &CensusRecord,
+        field: &str,
+        message: impl Into<String>,
+        severity: Severity,
+    ) -> Self {
+        Self {
+            pupil_id: record.pupil_id,
+            first_names: record.first_names.clone(),
+            last_name: record.last_name.clone(),
+            year: record.year,
+            field: field.into(),
+            message: message.into(),
+            severity,
+        }
+    }
+}
+
+/// A pupil's age on the 31st of August before the academic year the date falls in, which is what
+/// places them in a year group: four for reception, five for year 1 and so on.
+pub fn age_for_year_group(date_of_birth: NaiveDate, census_date: NaiveDate) -> i32 {
+    let cut_off = academic_year_start(census_date) - Duration::days(1);
+    let mut age = cut_off.year() - date_of_birth.year();
+    if (cut_off.month(), cut_off.day()) < (date_of_birth.month(), date_of_birth.day()) {
+        age -= 1;
+    }
+    age
+}
+
+/// Run every rule over the records, errors first and then by pupil.
+pub fn check(records: &[CensusRecord], census_date: NaiveDate, config: &Config) -> Vec<Problem> {
+    let mut upns: HashMap<&str, usize> = HashMap::new();
+    for upn in records.iter().filter_map(|record| record.upn.as_deref()) {
+        *upns.entry(upn).or_default() += 1;
+    }
+    let mut problems = vec![];
+    for record in records {
+        let mut problem = |field: &str, message: String, severity: Severity| {
+            problems.push(Problem::new(record, field, message, severity))
+        };
+        if record.first_names.trim().is_empty() {
+            problem(
+                "first_names",
+                "first names are missing".into(),
+                Severity::Error,
+            );
+        }
+        if record.last_name.trim().is_empty() {
+            problem("last_name", "last name is missing".into(), Severity::Error);
+        }
+        match record.upn.as_deref() {
+            None => problem("upn", "UPN is missing".into(), Severity::Error),
+            Some(upn) if !utils::is_valid_upn(upn) => {
+                problem("upn", format!("{upn} isn't a valid UPN"), Severity::Error)
+            }
+            Some(upn) if upns[upn] > 1 => problem(
+                "upn",
+                format!("{upn} is shared with another pupil on roll"),
+                Severity::Error,
+            ),
+            _ => {}
+        }
+        match record.date_of_birth {
+            None => problem(
+                "date_of_birth",
+                "date of birth is missing".into(),
+                Severity::Error,
+            ),
+            Some(date_of_birth) if date_of_birth >= record.start_date => problem(
+                "date_of_birth",
+                "date of birth isn't before their start date".into(),
+                Severity::Error,
+            ),
+            Some(date_of_birth) => {
+                let age = age_for_year_group(date_of_birth, census_date);
+                if age != record.year + 4 {
+                    problem(
+                        "year",
+                        format!(
+                            "aged {age} at the start of the school year, which is outside year {}",
+                            record.year
+                        ),
+                        Severity::Query,
+                    );
+                }
+            }
+        }
+        if record.year < config.min_year || record.year > config.max_year {
+            problem(
+                "year",
+                format!(
+                    "year {} isn't taught here, years run from {} to {}",
+                    record.year, config.min_year, config.max_year
+                ),
+                Severity::Error,
+            );
+        }
+        if record.sex == "U" {
+            problem(
+                "gender",
+                "sex will be returned as unknown".into(),
+                Severity::Query,
+            );
+        }
+        match (record.active, record.end_date) {
+            (false, None) => problem(
+                "end_date",
+                "marked as left but with no leave date, so it isn't clear they were on roll".into(),
+                Severity::Error,
+            ),
+            (true, Some(_)) => problem(
+                "end_date",
+                "still marked active but has a leave date".into(),
+                Severity::Error,
+            ),
+            _ => {}
+        }
+        if record.eal {
+            if record.eal_stage.is_none() {
+                problem(
+                    "eal_stage",
+                    "EAL but with no assessment of their stage by the census date".into(),
+                    Severity::Error,
+                );
+            }
+            if record.home_language.is_none() {
+                problem(
+                    "home_language",
+                    "EAL but their home language is missing".into(),
+                    Severity::Query,
+                );
+            }
+        }
+        if let Some(language) = &record.home_language {
+            if language_code(language).is_none() {
+                problem(
+                    "home_language",
+                    format!("{language} isn't a language code, so it will be left out"),
+                    Severity::Query,
+                );
+            }
+        }
+        match (record.aln, record.aln_provision) {
+            (true, None) => problem(
+                "aln_provision",
+                "flagged ALN but not on the ALN register at the census date".into(),
+                Severity::Error,
+            ),
+            (false, Some(_)) => problem(
+                "aln_provision",
+                "on the ALN register but not flagged ALN at the census date".into(),
+                Severity::Error,
+            ),
+            _ => {}
+        }
+    }
+    problems.sort_by(|a, b| {
+        (a.severity, &a.last_name, &a.first_names, a.pupil_id).cmp(&(
+            b.severity,
+            &b.last_name,
+            &b.first_names,
+            b.pupil_id,
+        ))
+    });
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aln::register::Provision, eal::model::Stage};
+    use rstest::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn record() -> CensusRecord {
+        CensusRecord {
+            pupil_id: Uuid::new_v4(),
+            upn: Some("H801200001001".into()),
+            first_names: "Alex".into(),
+            last_name: "Smith".into(),
+            preferred_first_names: None,
+            preferred_last_name: None,
+            date_of_birth: Some(date("2016-10-01")),
+            sex: "F".into(),
+            year: 2,
+            nc_year: "2".into(),
+            start_date: date("2020-09-01"),
+            end_date: None,
+            active: true,
+            fsm: false,
+            eal: false,
+            eal_stage: None,
+            home_language: None,
+            aln: false,
+            aln_provision: None,
+            looked_after: false,
+        }
+    }
+
+    fn fields(problems: &[Problem]) -> Vec<(&str, Severity)> {
+        problems
+            .iter()
+            .map(|problem| (problem.field.as_str(), problem.severity))
+            .collect()
+    }
+
+    #[fixture]
+    fn census_date() -> NaiveDate {
+        date("2024-01-18")
+    }
+
+    #[rstest]
+    #[case("2016-09-01", 6)]
+    #[case("2016-08-31", 7)]
+    #[case("2016-09-02", 6)]
+    #[case("2017-08-31", 6)]
+    fn ages_are_taken_on_the_last_day_of_august(
+        census_date: NaiveDate,
+        #[case] date_of_birth: &str,
+        #[case] age: i32,
+    ) {
+        assert_eq!(age_for_year_group(date(date_of_birth), census_date), age);
+    }
+
+    #[rstest]
+    fn complete_records_pass(census_date: NaiveDate) {
+        let mut eal = record();
+        eal.eal = true;
+        eal.eal_stage = Some(Stage::C);
+        eal.home_language = Some("POL".into());
+        eal.aln = true;
+        eal.aln_provision = Some(Provision::School);
+        let mut plain = record();
+        plain.upn = Some("X801200001002".into());
+        assert!(check(&[eal, plain], census_date, &Config::default()).is_empty());
+    }
+
+    #[rstest]
+    fn missing_and_inconsistent_data_is_flagged(census_date: NaiveDate) {
+        let mut pupil = record();
+        pupil.upn = None;
+        pupil.date_of_birth = None;
+        pupil.active = false;
+        pupil.eal = true;
+        pupil.home_language = Some("Polish".into());
+        pupil.aln_provision = Some(Provision::LocalAuthority);
+        pupil.sex = "U".into();
+        let problems = check(&[pupil], census_date, &Config::default());
+        assert_eq!(
+            fields(&problems),
+            [
+                ("upn", Severity::Error),
+                ("date_of_birth", Severity::Error),
+                ("end_date", Severity::Error),
+                ("eal_stage", Severity::Error),
+                ("aln_provision", Severity::Error),
+                ("gender", Severity::Query),
+                ("home_language", Severity::Query),
+            ]
+        );
+        assert!(problems[..5]
+            .iter()
+            .all(|problem| problem.last_name == "Smith"));
+    }
+
+    #[rstest]
+    fn shared_upns_are_flagged_on_both_pupils(census_date: NaiveDate) {
+        let first = record();
+        let second = record();
+        let problems = check(
+            &[first.clone(), second.clone()],
+            census_date,
+            &Config::default(),
+        );
+        assert_eq!(fields(&problems), [("upn", Severity::Error); 2]);
+        let mut ids = vec![first.pupil_id, second.pupil_id];
+        ids.sort_unstable();
+        let mut flagged: Vec<Uuid> = problems.iter().map(|problem| problem.pupil_id).collect();
+        flagged.sort_unstable();
+        assert_eq!(flagged, ids);
+    }
+
+    #[rstest]
+    fn out_of_age_group_is_a_query(census_date: NaiveDate) {
+        let mut pupil = record();
+        pupil.date_of_birth = Some(date("2015-10-01"));
+        let problems = check(&[pupil], census_date, &Config::default());
+        assert_eq!(fields(&problems), [("year", Severity::Query)]);
+        assert_eq!(
+            problems[0].message,
+            "aged 7 at the start of the school year, which is outside year 2"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState, comment::model::*, core::error::*, pupil::model::Pupil, user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn create_comment(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewComment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding comment to pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    // checks the pupil exists and is in one of the user's years
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let comment = Comment::new(pupil_id, &user, new);
+    comment.validate()?;
+    match comment.insert(state.database().as_ref()).await {
+        Ok(comment) => Ok((StatusCode::CREATED, Json(json!(comment)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_comments(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested all comments");
+    match Comment::all_from_db(&user, state.database().as_ref()).await {
+        Ok(comments) => Ok(Json(json!(comments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_comments(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested comments for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Comment::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(comments) => Ok(Json(json!(comments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_comment_by_id(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested comment {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    match Comment::one_from_db(&user, pupil_id, id, state.database().as_ref()).await {
+        Ok(comment) => Ok(Json(json!(comment))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::CommentDoesNotExist => Err(CommentDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_comment(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(update): Json<CommentUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating comment {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let mut comment = Comment::one_from_db(&user, pupil_id, id, state.database()).await?;
+    comment.check_author(&user)?;
+    comment.set_from_update(update)?;
+    comment.validate()?;
+    match comment.update(state.database().as_ref()).await {
+        Ok(comment) => Ok(Json(json!(comment))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting comment {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let comment = Comment::one_from_db(&user, pupil_id, id, state.database()).await?;
+    comment.check_author(&user)?;
+    match comment.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+    utils::patch::Patch,
+};
+use chrono::{NaiveDateTime, Utc};
+use entity::comment::{ActiveModel, Column, Entity, Model};
+use migration::{Condition, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Who can read a comment: only its author, or every user who can see the pupil's year.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    Private,
+    #[default]
+    Shared,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Private => "private",
+            Visibility::Shared => "shared",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "shared" => Visibility::Shared,
+            _ => Visibility::Private, // fail closed on anything unexpected
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Comment {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) author: String,
+    pub(crate) category: String,
+    pub(crate) body: String,
+    pub(crate) visibility: Visibility,
+    pub(crate) created_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) edited_at: Option<NaiveDateTime>,
+}
+
+impl Comment {
+    pub fn new(pupil_id: Uuid, author: &User, new: NewComment) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            author: author.email_address.clone(),
+            category: new.category,
+            body: new.body,
+            visibility: new.visibility,
+            created_at: Utc::now().naive_utc(),
+            edited_at: None,
+        }
+    }
+
+    /// Get a comment on a pupil the user can see, hiding other people's private comments.
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .filter(visible_to(user))
+            .one(db)
+            .await?
+        {
+            Some(comment) => Ok(comment.into()),
+            None => Err(CommentDoesNotExist!()),
+        }
+    }
+
+    /// All the comments on a pupil the user can see, newest first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .filter(visible_to(user))
+            .order_by_desc(Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Every comment the user can see on pupils in their years, newest first.
+    pub async fn all_from_db(user: &User, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        let mut years = Condition::any();
+        for year in &user.years {
+            years = years.add(entity::pupil::Column::Year.eq(*year));
+        }
+        Ok(Entity::find()
+            .filter(
+                Column::PupilId.in_subquery(
+                    Query::select()
+                        .column(entity::pupil::Column::Id)
+                        .from(entity::pupil::Entity)
+                        .cond_where(years)
+                        .to_owned(),
+                ),
+            )
+            .filter(visible_to(user))
+            .order_by_desc(Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting comment {:?}", self);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            author: Set(self.author.clone()),
+            category: Set(self.category.clone()),
+            body: Set(self.body.clone()),
+            visibility: Set(self.visibility.as_str().to_owned()),
+            created_at: Set(self.created_at),
+            edited_at: Set(self.edited_at),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            pupil_id: Unchanged(self.pupil_id),
+            author: Unchanged(self.author.clone()),
+            category: Set(self.category.clone()),
+            body: Set(self.body.clone()),
+            visibility: Set(self.visibility.as_str().to_owned()),
+            created_at: Unchanged(self.created_at),
+            edited_at: Set(self.edited_at),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Only the author may change or remove a comment.
+    pub fn check_author(&self, user: &User) -> Result<()> {
+        if self.author == user.email_address {
+            Ok(())
+        } else {
+            Err(Unauthorised!("only the author can change a comment"))
+        }
+    }
+
+    /// Apply a JSON merge patch, marking the comment as edited.
+    pub fn set_from_update(&mut self, update: CommentUpdate) -> Result<()> {
+        update.category.apply("category", &mut self.category)?;
+        update.body.apply("body", &mut self.body)?;
+        update
+            .visibility
+            .apply("visibility", &mut self.visibility)?;
+        self.edited_at = Some(Utc::now().naive_utc());
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.body.trim().is_empty() {
+            errors.insert("body".into(), "comment cannot be empty".into());
+        }
+        if !constant::COMMENT_CATEGORIES.contains(&self.category.as_str()) {
+            errors.insert(
+                "category".into(),
+                format!(
+                    "category must be one of {}",
+                    constant::COMMENT_CATEGORIES.join(", ")
+                ),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("comment failed validation").with_fields(errors))
+        }
+    }
+}
+
+fn visible_to(user: &User) -> Condition {
+    Condition::any()
+        .add(Column::Visibility.eq(Visibility::Shared.as_str()))
+        .add(Column::Author.eq(user.email_address.clone()))
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewComment {
+    category: String,
+    body: String,
+    #[serde(default)]
+    visibility: Visibility,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct CommentUpdate {
+    category: Patch<String>,
+    body: Patch<String>,
+    visibility: Patch<Visibility>,
+}
+
+impl From<Model> for Comment {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            author: value.author,
+            category: value.category,
+            body: value.body,
+            visibility: Visibility::from_db(&value.visibility),
+            created_at: value.created_at,
+            edited_at: value.edited_at,
+        }
+    }
+}
+
+impl From<Comment> for Model {
+    fn from(value: Comment) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            author: value.author,
+            category: value.category,
+            body: value.body,
+            visibility: value.visibility.as_str().to_owned(),
+            created_at: value.created_at,
+            edited_at: value.edited_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    #[fixture]
+    fn test_user() -> User {
+        User::new("test", "user", "test@test.com", "pass", vec![6])
+    }
+
+    #[fixture]
+    fn test_comment() -> Comment {
+        Comment {
+            id: "7a4c5a2e-7a43-4c4b-8d3c-5e0e7f5a9b11".parse().unwrap(),
+            pupil_id: "1164ce28-8915-4126-924d-fa580f1e9f01".parse().unwrap(),
+            author: "test@test.com".into(),
+            category: "general".into(),
+            body: "settled well".into(),
+            visibility: Visibility::Shared,
+            created_at: "2023-03-01T09:00:00".parse().unwrap(),
+            edited_at: None,
+        }
+    }
+
+    fn pupil_model(year: i32) -> entity::pupil::Model {
+        entity::pupil::Model {
+            id: "1164ce28-8915-4126-924d-fa580f1e9f01".parse().unwrap(),
+            first_names: "test".into(),
+            last_name: "pupil".into(),
+            year,
+            start_date: "2022-01-01".parse().unwrap(),
+            active: true,
+            gender: "female".into(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    async fn test_all_for_pupil(test_user: User, test_comment: Comment) {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![pupil_model(6)]])
+            .append_query_results(vec![vec![Model::from(test_comment.clone())]])
+            .into_connection();
+        let comments = Comment::all_for_pupil(&test_user, test_comment.pupil_id, &db)
+            .await
+            .unwrap();
+        assert_eq!(comments, vec![test_comment.clone()]);
+        let t_log = db.into_transaction_log();
+        let exp_query = Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "comment"."id", "comment"."pupil_id", "comment"."author", "comment"."category", "comment"."body", "comment"."visibility", "comment"."created_at", "comment"."edited_at" FROM "comment" WHERE "comment"."pupil_id" = $1 AND ("comment"."visibility" = $2 OR "comment"."author" = $3) ORDER BY "comment"."created_at" DESC"#,
+            [
+                test_comment.pupil_id.into(),
+                "shared".into(),
+                "test@test.com".into(),
+            ],
+        );
+        assert_eq!(t_log[1], exp_query);
+    }
+
+    #[rstest]
+    async fn test_all_for_pupil_outside_years(test_comment: Comment) {
+        let user = User::new("test", "user", "test@test.com", "pass", vec![1]);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![pupil_model(6)]])
+            .into_connection();
+        let error = Comment::all_for_pupil(&user, test_comment.pupil_id, &db)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind, crate::core::error::ErrorKind::Unauthorised);
+    }
+
+    #[rstest]
+    async fn test_insert(test_comment: Comment) {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(test_comment.clone())]])
+            .into_connection();
+        assert!(test_comment.insert(&db).await.is_ok());
+        let t_log = db.into_transaction_log();
+        let exp_query = Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"INSERT INTO "comment" ("id", "pupil_id", "author", "category", "body", "visibility", "created_at", "edited_at") VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING "id", "pupil_id", "author", "category", "body", "visibility", "created_at", "edited_at""#,
+            [
+                test_comment.id.into(),
+                test_comment.pupil_id.into(),
+                "test@test.com".into(),
+                "general".into(),
+                "settled well".into(),
+                "shared".into(),
+                test_comment.created_at.into(),
+                test_comment.edited_at.into(),
+            ],
+        );
+        assert_eq!(t_log[0], exp_query);
+    }
+
+    #[rstest]
+    fn test_set_from_update(mut test_comment: Comment) {
+        let update: CommentUpdate =
+            serde_json::from_str(r#"{"body": "much more settled", "visibility": "private"}"#)
+                .unwrap();
+        test_comment.set_from_update(update).unwrap();
+        assert_eq!(test_comment.body, "much more settled");
+        assert_eq!(test_comment.visibility, Visibility::Private);
+        assert_eq!(test_comment.category, "general");
+        assert!(test_comment.edited_at.is_some());
+    }
+
+    #[rstest]
+    fn test_check_author(test_comment: Comment) {
+        let other = User::new("other", "user", "other@test.com", "pass", vec![6]);
+        assert!(test_comment.check_author(&test_user()).is_ok());
+        assert!(test_comment.check_author(&other).is_err());
+    }
+
+    #[rstest]
+    #[case("general", "fine", None)]
+    #[case("general", " ", Some(("body", "comment cannot be empty")))]
+    #[case("gossip", "fine", Some(("category", "category must be one of general, academic, behaviour, pastoral, achievement")))]
+    fn test_validate(
+        mut test_comment: Comment,
+        #[case] category: &str,
+        #[case] body: &str,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        test_comment.category = category.into();
+        test_comment.body = body.into();
+        match expected {
+            None => assert!(test_comment.validate().is_ok()),
+            Some((field, message)) => {
+                let fields = test_comment.validate().unwrap_err().fields.unwrap();
+                assert_eq!(fields[field], message);
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    core::{constant, error::Result},
+    user::model::User,
+    utils::lock::{lock_for_transaction, CONCERN_ACCESS_LOG},
+};
+use chrono::{NaiveDateTime, SubsecRound, Utc};
+use entity::concern_access::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, NotSet, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const DSL_ONLY: &str = "only a DSL can view the concern access log";
+
+/// What was done to a concern, recorded in its access log.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Read,
+    List,
+    Chronology,
+    Create,
+    Update,
+    Status,
+    Upload,
+    Download,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Read => "read",
+            Action::List => "list",
+            Action::Chronology => "chronology",
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Status => "status",
+            Action::Upload => "upload",
+            Action::Download => "download",
+        }
+    }
+}
+
+/// One entry in the concern access log. Every entry carries the hash of the one before it, across
+/// all concerns, so removing or editing a row breaks the chain from that point on.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AccessEntry {
+    pub(crate) id: i32,
+    pub(crate) concern_id: Uuid,
+    pub(crate) accessed_by: String,
+    pub(crate) accessed_at: NaiveDateTime,
+    pub(crate) action: String,
+    pub(crate) previous_hash: String,
+    pub(crate) hash: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AccessLog {
+    pub(crate) entries: Vec<AccessEntry>,
+}
+
+/// Whether the whole chain, across every concern, still verifies.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ChainCheck {
+    pub(crate) intact: bool,
+    pub(crate) checked: usize,
+}
+
+impl AccessEntry {
+    /// Append an entry for each concern to the end of the chain.
+    pub async fn record(
+        user: &User,
+        concern_ids: &[Uuid],
+        action: Action,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        if concern_ids.is_empty() {
+            return Ok(());
+        }
+        // millisecond precision so the hash still matches after a round trip through the database
+        let accessed_at = Utc::now().naive_utc().trunc_subsecs(3);
+        let txn = db.begin().await?;
+        // held until the commit, so a concurrent view can't chain from the same entry
+        lock_for_transaction(&txn, CONCERN_ACCESS_LOG).await?;
+        let mut previous_hash = Entity::find()
+            .order_by_desc(Column::Id)
+            .one(&txn)
+            .await?
+            .map(|last| last.hash)
+            .unwrap_or_default();
+        for concern_id in concern_ids {
+            let hash = chain_hash(
+                &previous_hash,
+                *concern_id,
+                &user.email_address,
+                accessed_at,
+                action.as_str(),
+            );
+            ActiveModel {
+                id: NotSet,
+                concern_id: Set(*concern_id),
+                accessed_by: Set(user.email_address.clone()),
+                accessed_at: Set(accessed_at),
+                action: Set(action.as_str().to_owned()),
+                previous_hash: Set(previous_hash),
+                hash: Set(hash.clone()),
+            }
+            .insert(&txn)
+            .await?;
+            previous_hash = hash;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// The log for one concern. Only DSLs can see it.
+    pub async fn log_for_concern(
+        user: &User,
+        concern_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<AccessLog> {
+        user.check_role(constant::ROLE_DSL, DSL_ONLY)?;
+        let entries = Entity::find()
+            .filter(Column::ConcernId.eq(concern_id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(AccessLog { entries })
+    }
+
+    /// Verify the whole chain. It reads every entry, so it's kept apart from viewing one
+    /// concern's log.
+    pub async fn check_chain(user: &User, db: &DatabaseConnection) -> Result<ChainCheck> {
+        user.check_role(constant::ROLE_DSL, DSL_ONLY)?;
+        let chain: Vec<AccessEntry> = Entity::find()
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(ChainCheck {
+            intact: verify_chain(&chain),
+            checked: chain.len(),
+        })
+    }
+}
+
+pub fn chain_hash(
+    previous_hash: &str,
+    concern_id: Uuid,
+    accessed_by: &str,
+    accessed_at: NaiveDateTime,
+    action: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "{previous_hash}|{concern_id}|{accessed_by}|{}|{action}",
+            accessed_at.format("%Y-%m-%dT%H:%M:%S%.3f")
+        )
+        .as_bytes(),
+    );
+    hex::encode(hasher.finalize())
+}
+
+/// Check every entry links to the one before it and that its hash matches its contents.
+pub fn verify_chain(entries: &[AccessEntry]) -> bool {
+    let mut previous_hash = "";
+    for entry in entries {
+        let expected = chain_hash(
+            previous_hash,
+            entry.concern_id,
+            &entry.accessed_by,
+            entry.accessed_at,
+            &entry.action,
+        );
+        if entry.previous_hash != previous_hash || entry.hash != expected {
+            return false;
+        }
+        previous_hash = &entry.hash;
+    }
+    true
+}
+
+impl From<Model> for AccessEntry {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            concern_id: value.concern_id,
+            accessed_by: value.accessed_by,
+            accessed_at: value.accessed_at,
+            action: value.action,
+            previous_hash: value.previous_hash,
+            hash: value.hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[fixture]
+    fn chain() -> Vec<AccessEntry> {
+        let concern_id: Uuid = "5d1ad2b1-3c9e-4d59-9a0b-4f0e0c9d2a01".parse().unwrap();
+        let mut previous_hash = String::new();
+        let mut entries = vec![];
+        for (id, action) in [(1, "create"), (2, "read"), (3, "status")] {
+            let accessed_at = format!("2023-03-01T09:0{id}:00.123").parse().unwrap();
+            let hash = chain_hash(
+                &previous_hash,
+                concern_id,
+                "dsl@test.com",
+                accessed_at,
+                action,
+            );
+            entries.push(AccessEntry {
+                id,
+                concern_id,
+                accessed_by: "dsl@test.com".into(),
+                accessed_at,
+                action: action.into(),
+                previous_hash,
+                hash: hash.clone(),
+            });
+            previous_hash = hash;
+        }
+        entries
+    }
+
+    #[rstest]
+    fn test_verify_intact_chain(chain: Vec<AccessEntry>) {
+        assert!(verify_chain(&chain));
+        assert!(verify_chain(&[]));
+    }
+
+    #[rstest]
+    fn test_verify_edited_entry(mut chain: Vec<AccessEntry>) {
+        chain[1].accessed_by = "someone@else.com".into();
+        assert!(!verify_chain(&chain));
+    }
+
+    #[rstest]
+    fn test_verify_removed_entry(mut chain: Vec<AccessEntry>) {
+        chain.remove(1);
+        assert!(!verify_chain(&chain));
+    }
+}
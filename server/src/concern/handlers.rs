@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    attachment::{
+        handlers::{link_to, store_upload},
+        model::{Attachment, NewAttachment, Owner},
+    },
+    concern::{access::*, model::*},
+    core::error::*,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn create_concern(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewConcern>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("raising a concern");
+    let concern = Concern::new(new, &user);
+    check_pupil(&user, concern.pupil_id, state.database()).await?;
+    concern.validate()?;
+    match concern.insert(state.database().as_ref()).await {
+        Ok(concern) => {
+            AccessEntry::record(&user, &[concern.id], Action::Create, state.database()).await?;
+            Ok((StatusCode::CREATED, Json(json!(concern))))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_concerns(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested all concerns");
+    match Concern::all_from_db(&user, state.database().as_ref()).await {
+        Ok(concerns) => {
+            let ids: Vec<Uuid> = concerns.iter().map(|c| c.id).collect();
+            AccessEntry::record(&user, &ids, Action::List, state.database()).await?;
+            Ok(Json(json!(concerns)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_concern_by_id(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested concern {id}");
+    let id = Uuid::from_str(&id)?;
+    match Concern::one_from_db(&user, id, state.database().as_ref()).await {
+        Ok(concern) => {
+            AccessEntry::record(&user, &[concern.id], Action::Read, state.database()).await?;
+            Ok(Json(json!(concern)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::ConcernDoesNotExist => Err(ConcernDoesNotExist!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_concerns(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested concern chronology for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Concern::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(concerns) => {
+            let ids: Vec<Uuid> = concerns.iter().map(|c| c.id).collect();
+            AccessEntry::record(&user, &ids, Action::Chronology, state.database()).await?;
+            let mut chronology: Vec<ChronologyEntry> =
+                concerns.iter().flat_map(Concern::chronology).collect();
+            chronology.sort_by_key(|entry| entry.at);
+            Ok(Json(json!(chronology)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_concern(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(update): Json<ConcernUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let mut concern = Concern::one_from_db(&user, id, state.database()).await?;
+    concern.set_from_update(&user, update)?;
+    concern.validate()?;
+    match concern.update(state.database().as_ref()).await {
+        Ok(concern) => {
+            AccessEntry::record(&user, &[concern.id], Action::Update, state.database()).await?;
+            Ok(Json(json!(concern)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_concern_status(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(change): Json<StatusChange>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("changing status of concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let mut concern = Concern::one_from_db(&user, id, state.database()).await?;
+    concern.set_status(&user, change)?;
+    match concern.update(state.database().as_ref()).await {
+        Ok(concern) => {
+            AccessEntry::record(&user, &[concern.id], Action::Status, state.database()).await?;
+            Ok(Json(json!(concern)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn add_concern_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(upload): Json<NewAttachment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding attachment to concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let concern = Concern::one_from_db(&user, id, state.database()).await?;
+    let owner = Owner::Concern(concern.id);
+    let attachment = store_upload(&state, &user, concern.pupil_id, owner, upload).await?;
+    AccessEntry::record(&user, &[concern.id], Action::Upload, state.database()).await?;
+    Ok((StatusCode::CREATED, Json(json!(attachment))))
+}
+
+pub async fn get_concern_attachments(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested attachments for concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let concern = Concern::one_from_db(&user, id, state.database()).await?;
+    match Attachment::all_for_concern(&concern, state.database().as_ref()).await {
+        Ok(attachments) => {
+            AccessEntry::record(&user, &[concern.id], Action::Read, state.database()).await?;
+            Ok(Json(json!(attachments)))
+        }
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// A signed link to download the attachment. Handing out the link is what gets logged, as the
+/// download itself is not tied to a session.
+pub async fn download_concern_attachment(
+    State(state): State<AppState>,
+    Path((id, attachment_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested a download link for attachment {attachment_id} on concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let attachment_id = Uuid::from_str(&attachment_id)?;
+    let concern = Concern::one_from_db(&user, id, state.database()).await?;
+    let attachment = Attachment::one_for_concern(&concern, attachment_id, state.database()).await?;
+    AccessEntry::record(&user, &[concern.id], Action::Download, state.database()).await?;
+    Ok(Json(link_to(&state, &user, &attachment)))
+}
+
+pub async fn get_concern_log(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested access log for concern {id}");
+    let id = Uuid::from_str(&id)?;
+    let concern = Concern::one_from_db(&user, id, state.database()).await?;
+    match AccessEntry::log_for_concern(&user, concern.id, state.database().as_ref()).await {
+        Ok(log) => Ok(Json(json!(log))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn check_concern_log(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested a check of the concern access log");
+    match AccessEntry::check_chain(&user, state.database().as_ref()).await {
+        Ok(check) => Ok(Json(json!(check))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
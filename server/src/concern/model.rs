@@ -0,0 +1,527 @@
+use crate::{
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+    utils::patch::Patch,
+};
+use chrono::{NaiveDateTime, Utc};
+use entity::concern::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    Select, Set, Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Where a concern is in the safeguarding workflow: raised by anyone, triaged by a DSL, and then
+/// either closed in school or escalated to an outside agency.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Raised,
+    Triaged,
+    Closed,
+    Escalated,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Raised => "raised",
+            Status::Triaged => "triaged",
+            Status::Closed => "closed",
+            Status::Escalated => "escalated",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "triaged" => Status::Triaged,
+            "closed" => Status::Closed,
+            "escalated" => Status::Escalated,
+            _ => Status::Raised,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(self, Status::Raised | Status::Triaged)
+    }
+
+    fn can_move_to(&self, next: Status) -> bool {
+        matches!(
+            (self, next),
+            (Status::Raised, Status::Triaged)
+                | (Status::Triaged, Status::Closed)
+                | (Status::Triaged, Status::Escalated)
+                | (Status::Escalated, Status::Closed)
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Concern {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) reporter: String,
+    pub(crate) category: String,
+    pub(crate) narrative: String,
+    pub(crate) actions_taken: String,
+    pub(crate) status: Status,
+    pub(crate) raised_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) triaged_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) triaged_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) resolved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) resolved_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) outcome: Option<String>,
+}
+
+/// One dated event in a pupil's safeguarding chronology.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ChronologyEntry {
+    pub(crate) concern_id: Uuid,
+    pub(crate) at: NaiveDateTime,
+    pub(crate) event: Status,
+    pub(crate) by: String,
+    pub(crate) category: String,
+    pub(crate) summary: String,
+}
+
+impl Concern {
+    pub fn new(new: NewConcern, reporter: &User) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id: new.pupil_id,
+            reporter: reporter.email_address.clone(),
+            category: new.category,
+            narrative: new.narrative,
+            actions_taken: new.actions_taken,
+            status: Status::Raised,
+            raised_at: Utc::now().naive_utc(),
+            triaged_by: None,
+            triaged_at: None,
+            resolved_by: None,
+            resolved_at: None,
+            outcome: None,
+        }
+    }
+
+    /// Concerns are only ever visible to the person who raised them and to DSLs.
+    pub fn can_view(&self, user: &User) -> bool {
+        self.reporter == user.email_address || user.has_role(constant::ROLE_DSL)
+    }
+
+    /// Get a concern the user can see. Ones they can't see are reported as missing rather than
+    /// unauthorised so nobody can find out a concern exists.
+    pub async fn one_from_db(user: &User, id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        match Entity::find_by_id(id).one(db).await? {
+            Some(concern) => {
+                let concern = Concern::from(concern);
+                if concern.can_view(user) {
+                    Ok(concern)
+                } else {
+                    Err(ConcernDoesNotExist!())
+                }
+            }
+            None => Err(ConcernDoesNotExist!()),
+        }
+    }
+
+    /// Every concern the user can see, newest first.
+    pub async fn all_from_db(user: &User, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(visible_to(user)
+            .order_by_desc(Column::RaisedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// The concerns on one pupil the user can see, oldest first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        check_pupil(user, pupil_id, db).await?;
+        Ok(visible_to(user)
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_asc(Column::RaisedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting concern {}", self.id);
+        Ok(ActiveModel::from(Model::from(self.clone()))
+            .insert(db)
+            .await?
+            .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            pupil_id: Unchanged(self.pupil_id),
+            reporter: Unchanged(self.reporter.clone()),
+            category: Set(self.category.clone()),
+            narrative: Set(self.narrative.clone()),
+            actions_taken: Set(self.actions_taken.clone()),
+            status: Set(self.status.as_str().to_owned()),
+            raised_at: Unchanged(self.raised_at),
+            triaged_by: Set(self.triaged_by.clone()),
+            triaged_at: Set(self.triaged_at),
+            resolved_by: Set(self.resolved_by.clone()),
+            resolved_at: Set(self.resolved_at),
+            outcome: Set(self.outcome.clone()),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    /// Apply a JSON merge patch. The reporter can change a concern until a DSL has triaged it, and
+    /// after that only DSLs can add to it. Nothing can change once it's closed or escalated.
+    pub fn set_from_update(&mut self, user: &User, update: ConcernUpdate) -> Result<()> {
+        if !self.status.is_open() {
+            return Err(InvalidApiRequest!(format!(
+                "a {} concern cannot be changed",
+                self.status.as_str()
+            )));
+        }
+        let is_reporter = self.reporter == user.email_address && self.status == Status::Raised;
+        if !is_reporter && !user.has_role(constant::ROLE_DSL) {
+            return Err(Unauthorised!(
+                "only a DSL can change a concern once it has been triaged"
+            ));
+        }
+        update.category.apply("category", &mut self.category)?;
+        update.narrative.apply("narrative", &mut self.narrative)?;
+        update
+            .actions_taken
+            .apply("actions_taken", &mut self.actions_taken)?;
+        Ok(())
+    }
+
+    /// Move the concern along the workflow, which only DSLs can do.
+    pub fn set_status(&mut self, user: &User, change: StatusChange) -> Result<()> {
+        if !user.has_role(constant::ROLE_DSL) {
+            return Err(Unauthorised!(
+                "only a DSL can change the status of a concern"
+            ));
+        }
+        if !self.status.can_move_to(change.status) {
+            return Err(InvalidApiRequest!(format!(
+                "a {} concern cannot be {}",
+                self.status.as_str(),
+                change.status.as_str()
+            )));
+        }
+        let now = Utc::now().naive_utc();
+        match change.status {
+            Status::Triaged => {
+                self.triaged_by = Some(user.email_address.clone());
+                self.triaged_at = Some(now);
+            }
+            _ => {
+                self.resolved_by = Some(user.email_address.clone());
+                self.resolved_at = Some(now);
+            }
+        }
+        if change.outcome.is_some() {
+            self.outcome = change.outcome;
+        }
+        self.status = change.status;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.narrative.trim().is_empty() {
+            errors.insert("narrative".into(), "narrative cannot be empty".into());
+        }
+        if !constant::CONCERN_CATEGORIES.contains(&self.category.as_str()) {
+            errors.insert(
+                "category".into(),
+                format!(
+                    "category must be one of {}",
+                    constant::CONCERN_CATEGORIES.join(", ")
+                ),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("concern failed validation").with_fields(errors))
+        }
+    }
+
+    /// The dated events in this concern's history.
+    pub fn chronology(&self) -> Vec<ChronologyEntry> {
+        let entry = |at, event, by: &str, summary: &str| ChronologyEntry {
+            concern_id: self.id,
+            at,
+            event,
+            by: by.to_owned(),
+            category: self.category.clone(),
+            summary: summary.to_owned(),
+        };
+        let mut entries = vec![entry(
+            self.raised_at,
+            Status::Raised,
+            &self.reporter,
+            &self.narrative,
+        )];
+        if let (Some(at), Some(by)) = (self.triaged_at, &self.triaged_by) {
+            entries.push(entry(at, Status::Triaged, by, &self.actions_taken));
+        }
+        if let (Some(at), Some(by)) = (self.resolved_at, &self.resolved_by) {
+            entries.push(entry(
+                at,
+                self.status,
+                by,
+                self.outcome.as_deref().unwrap_or_default(),
+            ));
+        }
+        entries
+    }
+}
+
+/// Check the pupil exists and the user can raise or see concerns about them. DSLs work across the
+/// whole school but anyone else needs access to the pupil's year.
+pub async fn check_pupil(user: &User, pupil_id: Uuid, db: &DatabaseConnection) -> Result<()> {
+    if !user.has_role(constant::ROLE_DSL) {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+    } else if entity::pupil::Entity::find_by_id(pupil_id)
+        .one(db)
+        .await?
+        .is_none()
+    {
+        return Err(PupilDoesNotExist!());
+    }
+    Ok(())
+}
+
+fn visible_to(user: &User) -> Select<Entity> {
+    if user.has_role(constant::ROLE_DSL) {
+        Entity::find()
+    } else {
+        Entity::find().filter(Column::Reporter.eq(user.email_address.clone()))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewConcern {
+    pupil_id: Uuid,
+    category: String,
+    narrative: String,
+    #[serde(default)]
+    actions_taken: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct ConcernUpdate {
+    category: Patch<String>,
+    narrative: Patch<String>,
+    actions_taken: Patch<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct StatusChange {
+    status: Status,
+    #[serde(default)]
+    outcome: Option<String>,
+}
+
+impl From<Model> for Concern {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            reporter: value.reporter,
+            category: value.category,
+            narrative: value.narrative,
+            actions_taken: value.actions_taken,
+            status: Status::from_db(&value.status),
+            raised_at: value.raised_at,
+            triaged_by: value.triaged_by,
+            triaged_at: value.triaged_at,
+            resolved_by: value.resolved_by,
+            resolved_at: value.resolved_at,
+            outcome: value.outcome,
+        }
+    }
+}
+
+impl From<Concern> for Model {
+    fn from(value: Concern) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            reporter: value.reporter,
+            category: value.category,
+            narrative: value.narrative,
+            actions_taken: value.actions_taken,
+            status: value.status.as_str().to_owned(),
+            raised_at: value.raised_at,
+            triaged_by: value.triaged_by,
+            triaged_at: value.triaged_at,
+            resolved_by: value.resolved_by,
+            resolved_at: value.resolved_at,
+            outcome: value.outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, Transaction};
+
+    #[fixture]
+    fn reporter() -> User {
+        User::new("test", "user", "test@test.com", "pass", vec![6])
+    }
+
+    #[fixture]
+    fn dsl() -> User {
+        let mut user = User::new("safe", "guard", "dsl@test.com", "pass", vec![]);
+        user.roles = vec![constant::ROLE_DSL.into()];
+        user
+    }
+
+    #[fixture]
+    fn test_concern() -> Concern {
+        Concern {
+            id: "5d1ad2b1-3c9e-4d59-9a0b-4f0e0c9d2a01".parse().unwrap(),
+            pupil_id: "1164ce28-8915-4126-924d-fa580f1e9f01".parse().unwrap(),
+            reporter: "test@test.com".into(),
+            category: "neglect".into(),
+            narrative: "came in hungry again".into(),
+            actions_taken: "gave breakfast".into(),
+            status: Status::Raised,
+            raised_at: "2023-03-01T09:00:00".parse().unwrap(),
+            triaged_by: None,
+            triaged_at: None,
+            resolved_by: None,
+            resolved_at: None,
+            outcome: None,
+        }
+    }
+
+    #[rstest]
+    async fn test_all_from_db_as_reporter(reporter: User, test_concern: Concern) {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(test_concern.clone())]])
+            .into_connection();
+        let concerns = Concern::all_from_db(&reporter, &db).await.unwrap();
+        assert_eq!(concerns, vec![test_concern]);
+        let t_log = db.into_transaction_log();
+        let exp_query = Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "concern"."id", "concern"."pupil_id", "concern"."reporter", "concern"."category", "concern"."narrative", "concern"."actions_taken", "concern"."status", "concern"."raised_at", "concern"."triaged_by", "concern"."triaged_at", "concern"."resolved_by", "concern"."resolved_at", "concern"."outcome" FROM "concern" WHERE "concern"."reporter" = $1 ORDER BY "concern"."raised_at" DESC"#,
+            ["test@test.com".into()],
+        );
+        assert_eq!(t_log[0], exp_query);
+    }
+
+    #[rstest]
+    async fn test_one_from_db_hidden_from_others(test_concern: Concern) {
+        let other = User::new("other", "user", "other@test.com", "pass", vec![6]);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(test_concern.clone())]])
+            .into_connection();
+        let error = Concern::one_from_db(&other, test_concern.id, &db)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.kind,
+            crate::core::error::ErrorKind::ConcernDoesNotExist
+        );
+    }
+
+    #[rstest]
+    #[case(Status::Raised, Status::Triaged, true)]
+    #[case(Status::Raised, Status::Closed, false)]
+    #[case(Status::Triaged, Status::Closed, true)]
+    #[case(Status::Triaged, Status::Escalated, true)]
+    #[case(Status::Escalated, Status::Closed, true)]
+    #[case(Status::Closed, Status::Raised, false)]
+    fn test_status_workflow(
+        dsl: User,
+        mut test_concern: Concern,
+        #[case] from: Status,
+        #[case] to: Status,
+        #[case] allowed: bool,
+    ) {
+        test_concern.status = from;
+        let change = StatusChange {
+            status: to,
+            outcome: None,
+        };
+        assert_eq!(test_concern.set_status(&dsl, change).is_ok(), allowed);
+    }
+
+    #[rstest]
+    fn test_only_dsl_triages(reporter: User, dsl: User, mut test_concern: Concern) {
+        let change = StatusChange {
+            status: Status::Triaged,
+            outcome: None,
+        };
+        assert!(test_concern.set_status(&reporter, change.clone()).is_err());
+        test_concern.set_status(&dsl, change).unwrap();
+        assert_eq!(test_concern.triaged_by, Some("dsl@test.com".into()));
+        assert!(test_concern.triaged_at.is_some());
+    }
+
+    #[rstest]
+    fn test_reporter_edits_until_triaged(reporter: User, mut test_concern: Concern) {
+        let update: ConcernUpdate =
+            serde_json::from_str(r#"{"actions_taken": "gave breakfast, told DSL"}"#).unwrap();
+        test_concern
+            .set_from_update(&reporter, update.clone())
+            .unwrap();
+        assert_eq!(test_concern.actions_taken, "gave breakfast, told DSL");
+        test_concern.status = Status::Triaged;
+        assert!(test_concern.set_from_update(&reporter, update).is_err());
+    }
+
+    #[rstest]
+    fn test_chronology(dsl: User, mut test_concern: Concern) {
+        for status in [Status::Triaged, Status::Escalated] {
+            test_concern
+                .set_status(
+                    &dsl,
+                    StatusChange {
+                        status,
+                        outcome: Some("referred to social services".into()),
+                    },
+                )
+                .unwrap();
+        }
+        let events: Vec<(Status, String)> = test_concern
+            .chronology()
+            .into_iter()
+            .map(|e| (e.event, e.summary))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (Status::Raised, "came in hungry again".into()),
+                (Status::Triaged, "gave breakfast".into()),
+                (Status::Escalated, "referred to social services".into()),
+            ]
+        );
+    }
+}
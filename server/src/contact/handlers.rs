@@ -0,0 +1,113 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState, contact::model::*, core::error::*, pupil::model::Pupil, user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_pupil_contacts(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested contacts for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match PupilContact::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(contacts) => Ok(Json(json!(contacts))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_contact(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewContact>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding a contact for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let contact = PupilContact::new(pupil_id, new);
+    contact.validate()?;
+    match contact.save(state.database().as_ref()).await {
+        Ok(contact) => Ok((StatusCode::CREATED, Json(json!(contact)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Link an existing contact to the pupil, or change how they're linked if they already are.
+pub async fn link_contact(
+    State(state): State<AppState>,
+    Path((pupil_id, contact_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(link): Json<ContactLink>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("linking contact {contact_id} to pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let contact_id = Uuid::from_str(&contact_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let contact = Contact::visible_from_db(&user, contact_id, state.database()).await?;
+    let contact = PupilContact::link(pupil_id, contact, link);
+    contact.validate()?;
+    match contact.save(state.database().as_ref()).await {
+        Ok(contact) => Ok(Json(json!(contact))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_contact(
+    State(state): State<AppState>,
+    Path((pupil_id, contact_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(update): Json<ContactUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating contact {contact_id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let contact_id = Uuid::from_str(&contact_id)?;
+    let mut contact =
+        PupilContact::one_from_db(&user, pupil_id, contact_id, state.database()).await?;
+    contact.set_from_update(update)?;
+    contact.validate()?;
+    match contact.save(state.database().as_ref()).await {
+        Ok(contact) => Ok(Json(json!(contact))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn unlink_contact(
+    State(state): State<AppState>,
+    Path((pupil_id, contact_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("removing contact {contact_id} from pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let contact_id = Uuid::from_str(&contact_id)?;
+    let contact = PupilContact::one_from_db(&user, pupil_id, contact_id, state.database()).await?;
+    match contact.unlink(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
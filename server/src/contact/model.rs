@@ -0,0 +1,449 @@
+use crate::{
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+    utils::patch::Patch,
+};
+use entity::{contact, pupil_contact};
+use migration::{Condition, OnConflict};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// A parent, carer or anyone else the school may need to get in touch with. Siblings share the
+/// same contact, so changing their details changes them for every pupil they're linked to.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Contact {
+    #[serde(rename = "contact_id")]
+    pub(crate) id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_language: Option<String>,
+}
+
+impl Contact {
+    /// The contact, as long as the user can see at least one of the pupils linked to them.
+    pub async fn visible_from_db(user: &User, id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        let contact = match contact::Entity::find_by_id(id).one(db).await? {
+            Some(contact) => contact,
+            None => return Err(ContactDoesNotExist!()),
+        };
+        let pupil_ids: Vec<Uuid> = pupil_contact::Entity::find()
+            .filter(pupil_contact::Column::ContactId.eq(id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|link| link.pupil_id)
+            .collect();
+        let mut years = Condition::any();
+        for year in &user.years {
+            years = years.add(entity::pupil::Column::Year.eq(*year));
+        }
+        let visible = entity::pupil::Entity::find()
+            .filter(entity::pupil::Column::Id.is_in(pupil_ids))
+            .filter(years)
+            .count(db)
+            .await?;
+        if visible > 0 {
+            Ok(contact.into())
+        } else {
+            Err(Unauthorised!(
+                "you don't have permission to view this contact"
+            ))
+        }
+    }
+}
+
+/// A contact as they are to one pupil: how they're related, the order to try them in, and
+/// whether a court order means the school must not contact them about this pupil.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct PupilContact {
+    #[serde(flatten)]
+    pub(crate) contact: Contact,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) relationship: String,
+    pub(crate) priority: i32,
+    pub(crate) parental_responsibility: bool,
+    pub(crate) no_contact: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) restriction_notes: Option<String>,
+}
+
+impl PupilContact {
+    /// A new contact for the pupil.
+    pub fn new(pupil_id: Uuid, new: NewContact) -> Self {
+        let contact = Contact {
+            id: Uuid::new_v4(),
+            first_names: new.details.first_names,
+            last_name: new.details.last_name,
+            phone: non_empty(new.details.phone),
+            email: non_empty(new.details.email),
+            preferred_language: non_empty(new.details.preferred_language),
+        };
+        Self::link(pupil_id, contact, new.link)
+    }
+
+    /// Link an existing contact, such as a sibling's parent, to the pupil.
+    pub fn link(pupil_id: Uuid, contact: Contact, link: ContactLink) -> Self {
+        Self {
+            contact,
+            pupil_id,
+            relationship: link.relationship,
+            priority: link.priority,
+            parental_responsibility: link.parental_responsibility,
+            no_contact: link.no_contact,
+            restriction_notes: non_empty(link.restriction_notes),
+        }
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        contact_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let link = match pupil_contact::Entity::find_by_id((pupil_id, contact_id))
+            .one(db)
+            .await?
+        {
+            Some(link) => link,
+            None => return Err(ContactDoesNotExist!()),
+        };
+        match contact::Entity::find_by_id(contact_id).one(db).await? {
+            Some(contact) => Ok(Self::from_models(contact, link)),
+            None => Err(ContactDoesNotExist!()),
+        }
+    }
+
+    /// All of a pupil's contacts, in the order they should be tried.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let links = pupil_contact::Entity::find()
+            .filter(pupil_contact::Column::PupilId.eq(pupil_id))
+            .order_by_asc(pupil_contact::Column::Priority)
+            .all(db)
+            .await?;
+        let mut contacts: HashMap<Uuid, contact::Model> = contact::Entity::find()
+            .filter(contact::Column::Id.is_in(links.iter().map(|link| link.contact_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|contact| (contact.id, contact))
+            .collect();
+        Ok(links
+            .into_iter()
+            .filter_map(|link| {
+                let contact = contacts.remove(&link.contact_id)?;
+                Some(Self::from_models(contact, link))
+            })
+            .collect())
+    }
+
+    /// Save the contact's details and their link to the pupil together.
+    pub async fn save<C: ConnectionTrait + TransactionTrait>(&self, db: &C) -> Result<Self> {
+        tracing::debug!("saving contact {:?}", self);
+        let txn = db.begin().await?;
+        contact::Entity::insert(contact::ActiveModel {
+            id: Set(self.contact.id),
+            first_names: Set(self.contact.first_names.clone()),
+            last_name: Set(self.contact.last_name.clone()),
+            phone: Set(self.contact.phone.clone()),
+            email: Set(self.contact.email.clone()),
+            preferred_language: Set(self.contact.preferred_language.clone()),
+        })
+        .on_conflict(
+            OnConflict::column(contact::Column::Id)
+                .update_columns([
+                    contact::Column::FirstNames,
+                    contact::Column::LastName,
+                    contact::Column::Phone,
+                    contact::Column::Email,
+                    contact::Column::PreferredLanguage,
+                ])
+                .to_owned(),
+        )
+        .exec(&txn)
+        .await?;
+        pupil_contact::Entity::insert(pupil_contact::ActiveModel {
+            pupil_id: Set(self.pupil_id),
+            contact_id: Set(self.contact.id),
+            relationship: Set(self.relationship.clone()),
+            priority: Set(self.priority),
+            parental_responsibility: Set(self.parental_responsibility),
+            no_contact: Set(self.no_contact),
+            restriction_notes: Set(self.restriction_notes.clone()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                pupil_contact::Column::PupilId,
+                pupil_contact::Column::ContactId,
+            ])
+            .update_columns([
+                pupil_contact::Column::Relationship,
+                pupil_contact::Column::Priority,
+                pupil_contact::Column::ParentalResponsibility,
+                pupil_contact::Column::NoContact,
+                pupil_contact::Column::RestrictionNotes,
+            ])
+            .to_owned(),
+        )
+        .exec(&txn)
+        .await?;
+        txn.commit().await?;
+        Ok(self.clone())
+    }
+
+    /// Remove the contact from the pupil, and remove them altogether if that was their last pupil.
+    pub async fn unlink(&self, db: &DatabaseConnection) -> Result<()> {
+        let txn = db.begin().await?;
+        pupil_contact::Entity::delete_by_id((self.pupil_id, self.contact.id))
+            .exec(&txn)
+            .await?;
+        let remaining = pupil_contact::Entity::find()
+            .filter(pupil_contact::Column::ContactId.eq(self.contact.id))
+            .count(&txn)
+            .await?;
+        if remaining == 0 {
+            contact::Entity::delete_by_id(self.contact.id)
+                .exec(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Apply a JSON merge patch to both the contact's details and their link to this pupil.
+    pub fn set_from_update(&mut self, update: ContactUpdate) -> Result<()> {
+        update
+            .first_names
+            .apply("first_names", &mut self.contact.first_names)?;
+        update
+            .last_name
+            .apply("last_name", &mut self.contact.last_name)?;
+        update.phone.apply_nullable(&mut self.contact.phone);
+        update.email.apply_nullable(&mut self.contact.email);
+        update
+            .preferred_language
+            .apply_nullable(&mut self.contact.preferred_language);
+        update
+            .relationship
+            .apply("relationship", &mut self.relationship)?;
+        update.priority.apply("priority", &mut self.priority)?;
+        update
+            .parental_responsibility
+            .apply("parental_responsibility", &mut self.parental_responsibility)?;
+        update
+            .no_contact
+            .apply("no_contact", &mut self.no_contact)?;
+        update
+            .restriction_notes
+            .apply_nullable(&mut self.restriction_notes);
+        self.contact.phone = non_empty(self.contact.phone.take());
+        self.contact.email = non_empty(self.contact.email.take());
+        self.contact.preferred_language = non_empty(self.contact.preferred_language.take());
+        self.restriction_notes = non_empty(self.restriction_notes.take());
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.contact.first_names.trim().is_empty() {
+            errors.insert("first_names".into(), "first names cannot be empty".into());
+        }
+        if self.contact.last_name.trim().is_empty() {
+            errors.insert("last_name".into(), "last name cannot be empty".into());
+        }
+        if let Some(phone) = &self.contact.phone {
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            if digits < 7
+                || !phone
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || " +()-".contains(c))
+            {
+                errors.insert("phone".into(), format!("{phone} is not a phone number"));
+            }
+        }
+        if let Some(email) = &self.contact.email {
+            if !email.contains('@') || email.contains(char::is_whitespace) {
+                errors.insert("email".into(), format!("{email} is not an email address"));
+            }
+        }
+        if !self.no_contact && self.contact.phone.is_none() && self.contact.email.is_none() {
+            errors.insert(
+                "phone".into(),
+                "give a phone number or email address".into(),
+            );
+        }
+        if !constant::CONTACT_RELATIONSHIPS.contains(&self.relationship.as_str()) {
+            errors.insert(
+                "relationship".into(),
+                format!("{} is not a relationship", self.relationship),
+            );
+        }
+        if self.priority < 1 {
+            errors.insert("priority".into(), "priority starts from 1".into());
+        }
+        if self.no_contact && self.restriction_notes.is_none() {
+            errors.insert(
+                "restriction_notes".into(),
+                "say what the restriction is, such as the court order".into(),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("contact failed validation").with_fields(errors))
+        }
+    }
+
+    fn from_models(contact: contact::Model, link: pupil_contact::Model) -> Self {
+        Self {
+            contact: contact.into(),
+            pupil_id: link.pupil_id,
+            relationship: link.relationship,
+            priority: link.priority,
+            parental_responsibility: link.parental_responsibility,
+            no_contact: link.no_contact,
+            restriction_notes: link.restriction_notes,
+        }
+    }
+}
+
+/// Blank optional fields from a form are stored as missing.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_owned())
+        .filter(|value| !value.is_empty())
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct ContactDetails {
+    first_names: String,
+    last_name: String,
+    #[serde(default)]
+    phone: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    preferred_language: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct ContactLink {
+    relationship: String,
+    priority: i32,
+    #[serde(default)]
+    parental_responsibility: bool,
+    #[serde(default)]
+    no_contact: bool,
+    #[serde(default)]
+    restriction_notes: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewContact {
+    #[serde(flatten)]
+    details: ContactDetails,
+    #[serde(flatten)]
+    link: ContactLink,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct ContactUpdate {
+    first_names: Patch<String>,
+    last_name: Patch<String>,
+    phone: Patch<String>,
+    email: Patch<String>,
+    preferred_language: Patch<String>,
+    relationship: Patch<String>,
+    priority: Patch<i32>,
+    parental_responsibility: Patch<bool>,
+    no_contact: Patch<bool>,
+    restriction_notes: Patch<String>,
+}
+
+impl From<contact::Model> for Contact {
+    fn from(value: contact::Model) -> Self {
+        Self {
+            id: value.id,
+            first_names: value.first_names,
+            last_name: value.last_name,
+            phone: value.phone,
+            email: value.email,
+            preferred_language: value.preferred_language,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+    use serde_json::json;
+
+    fn contact_from(overrides: serde_json::Value) -> PupilContact {
+        let mut new = json!({
+            "first_names": "Jo",
+            "last_name": "Bloggs",
+            "phone": "01234 567890",
+            "relationship": "mother",
+            "priority": 1,
+            "parental_responsibility": true
+        });
+        for (k, v) in overrides.as_object().unwrap() {
+            new[k] = v.clone();
+        }
+        PupilContact::new(Uuid::new_v4(), serde_json::from_value(new).unwrap())
+    }
+
+    #[rstest]
+    #[case(json!({}))]
+    #[case(json!({"phone": null, "email": "jo@example.com"}))]
+    #[case(json!({"phone": "+44 (0)1234 567890"}))]
+    #[case(json!({"phone": "", "no_contact": true, "restriction_notes": "prohibited steps order"}))]
+    fn valid_contacts(#[case] overrides: serde_json::Value) {
+        assert!(contact_from(overrides).validate().is_ok());
+    }
+
+    #[rstest]
+    #[case(json!({"phone": "  "}), "phone")]
+    #[case(json!({"phone": "call the office"}), "phone")]
+    #[case(json!({"email": "jo.example.com"}), "email")]
+    #[case(json!({"relationship": "neighbour"}), "relationship")]
+    #[case(json!({"priority": 0}), "priority")]
+    #[case(json!({"no_contact": true, "restriction_notes": " "}), "restriction_notes")]
+    fn invalid_contacts(#[case] overrides: serde_json::Value, #[case] field: &str) {
+        let error = contact_from(overrides).validate().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert!(error.fields.unwrap().contains_key(field));
+    }
+
+    #[rstest]
+    fn update_clears_blanks() {
+        let mut contact = contact_from(json!({"email": "jo@example.com"}));
+        let update: ContactUpdate =
+            serde_json::from_str(r#"{"email": "", "priority": 2, "preferred_language": "Welsh"}"#)
+                .unwrap();
+        contact.set_from_update(update).unwrap();
+        assert_eq!(contact.contact.email, None);
+        assert_eq!(contact.contact.preferred_language.as_deref(), Some("Welsh"));
+        assert_eq!(contact.priority, 2);
+        assert_eq!(contact.contact.phone.as_deref(), Some("01234 567890"));
+    }
+}
@@ -1,5 +1,75 @@
 pub const PUPILS_ENDPOINT: &str = "/api/data/pupils";
+pub const ASSESSMENTS_ENDPOINT: &str = "/api/data/assessments";
+pub const COMMENTS_ENDPOINT: &str = "/api/data/comments";
+pub const CONCERNS_ENDPOINT: &str = "/api/data/concerns";
+pub const ATTENDANCE_ENDPOINT: &str = "/api/data/attendance";
+pub const CENSUS_ENDPOINT: &str = "/api/data/census";
+pub const CURRICULUM_ENDPOINT: &str = "/api/data/curriculum";
+pub const INTERVENTIONS_ENDPOINT: &str = "/api/data/interventions";
+pub const ALN_ENDPOINT: &str = "/api/data/aln";
+pub const MEDICAL_ENDPOINT: &str = "/api/data/medical";
+pub const ANALYTICS_ENDPOINT: &str = "/api/data/analytics";
+pub const REPORTS_ENDPOINT: &str = "/api/data/reports";
+pub const QUALITY_ENDPOINT: &str = "/api/data/quality";
+pub const SUBJECT_ACCESS_ENDPOINT: &str = "/api/data/subject-access";
 pub const USERS_ENDPOINT: &str = "/api/data/users";
+pub const FILES_ENDPOINT: &str = "/api/files";
 pub const LOGIN_ENDPOINT: &str = "/api/auth/login";
 
 pub const AUTH_TOKEN_EXPIRY_MINUTES: i64 = 1320;
+
+pub const DEFAULT_MIN_YEAR: i32 = 0;
+pub const DEFAULT_MAX_YEAR: i32 = 6;
+pub const DEFAULT_PERSISTENT_ABSENCE_PERCENT: f64 = 10.0;
+pub const DEFAULT_ATTACHMENT_MAX_BYTES: usize = 10 * 1024 * 1024;
+pub const DEFAULT_SIGNED_URL_SECONDS: i64 = 300;
+pub const DEFAULT_SCHOOL_LEA: &str = "000";
+pub const DEFAULT_SCHOOL_ESTAB: &str = "0000";
+pub const DEFAULT_SMALL_NUMBER_THRESHOLD: usize = 5;
+pub const ACADEMIC_YEAR_START_MONTH: u32 = 9;
+pub const GENDERS: [&str; 3] = ["female", "male", "other"];
+pub const ROLE_DSL: &str = "dsl";
+pub const ROLE_ADMIN: &str = "admin";
+pub const ROLE_ALNCO: &str = "alnco";
+pub const ROLE_MEDICAL: &str = "medical";
+pub const ROLES: [&str; 4] = [ROLE_DSL, ROLE_ADMIN, ROLE_ALNCO, ROLE_MEDICAL];
+pub const COMMENT_CATEGORIES: [&str; 5] = [
+    "general",
+    "academic",
+    "behaviour",
+    "pastoral",
+    "achievement",
+];
+pub const CONCERN_CATEGORIES: [&str; 8] = [
+    "neglect",
+    "physical",
+    "emotional",
+    "sexual",
+    "online",
+    "peer_on_peer",
+    "radicalisation",
+    "other",
+];
+pub const ATTACHMENT_TYPES: [&str; 5] = [
+    "application/pdf",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "image/jpeg",
+    "image/png",
+    "text/plain",
+];
+pub const CONTACT_RELATIONSHIPS: [&str; 7] = [
+    "mother",
+    "father",
+    "step_parent",
+    "carer",
+    "grandparent",
+    "sibling",
+    "other",
+];
+pub const ATTENDANCE_SESSIONS: [&str; 2] = ["am", "pm"];
+pub const ALN_AREAS_OF_NEED: [&str; 4] = [
+    "cognition_and_learning",
+    "communication_and_interaction",
+    "behaviour_emotional_and_social",
+    "sensory_and_physical",
+];
@@ -5,6 +5,7 @@ use axum::{
 use http::StatusCode;
 use macros::KindError;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, KindError)]
 pub enum ErrorKind {
@@ -12,12 +13,26 @@ pub enum ErrorKind {
     InvalidCredentials,
     UserDoesNotExist,
     PupilDoesNotExist,
+    CommentDoesNotExist,
+    AssessmentDoesNotExist,
+    ConcernDoesNotExist,
+    JudgementDoesNotExist,
+    InterventionDoesNotExist,
+    TargetDoesNotExist,
+    EalAssessmentDoesNotExist,
+    ContactDoesNotExist,
+    MedicalItemDoesNotExist,
+    AttachmentDoesNotExist,
+    PhotoDoesNotExist,
+    FlagPeriodDoesNotExist,
+    ReportTemplateDoesNotExist,
     MissingEnvVariable, // std::var::VarError
     AddrParseError,     // std::net::AddrParseError
     IoError,            // std::io::Error
     ParseIntError,      // std::num::ParseIntError
     DatabaseError,      // sea_orm
     ServerError,        // hyper
+    StorageError,       // attachment::store
     JWTTokenCreationError,
     InvalidJwt, // jsonwebtoken::errors::Error
     SerializeError,
@@ -26,6 +41,8 @@ pub enum ErrorKind {
     EncodeError,
     ParseError,
     Unauthorised,
+    Forbidden,
+    ValidationError,
 
     UnknownError,
 }
@@ -38,6 +55,7 @@ macro_rules! from_error {
                 Error {
                     kind: ErrorKind::$kind,
                     message: Some(value.to_string()),
+                    fields: None,
                 }
             }
         }
@@ -48,6 +66,7 @@ macro_rules! from_error {
                 Error {
                     kind: ErrorKind::$kind,
                     message: Some($msg.to_string()),
+                    fields: None,
                 }
             }
         }
@@ -59,17 +78,19 @@ macro_rules! error_macro {
     ($($kind:ident),+) => {
         $(macro_rules! $kind {
             () => {{
-                let e = crate::core::error::Error {
-                    kind: crate::core::error::ErrorKind::$kind,
+                let e = $crate::core::error::Error {
+                    kind: $crate::core::error::ErrorKind::$kind,
                     message: None,
+                    fields: None,
                 };
                 tracing::error!("{}", e.to_string());
                 e
             }};
             ($msg:expr) => {{
-                let e = crate::core::error::Error {
-                    kind: crate::core::error::ErrorKind::$kind,
+                let e = $crate::core::error::Error {
+                    kind: $crate::core::error::ErrorKind::$kind,
                     message: Some(String::from($msg)),
+                    fields: None,
                 };
                 tracing::error!("{}", e.to_string());
                 e
@@ -85,10 +106,28 @@ error_macro! { // creates an Error for each with optional message, logs it, then
     InvalidCredentials,
     UserDoesNotExist,
     PupilDoesNotExist,
+    CommentDoesNotExist,
+    AssessmentDoesNotExist,
+    ConcernDoesNotExist,
+    JudgementDoesNotExist,
+    InterventionDoesNotExist,
+    TargetDoesNotExist,
+    EalAssessmentDoesNotExist,
+    ContactDoesNotExist,
+    MedicalItemDoesNotExist,
+    AttachmentDoesNotExist,
+    PhotoDoesNotExist,
+    FlagPeriodDoesNotExist,
+    ReportTemplateDoesNotExist,
     InvalidJwt, // jsonwebtoken::errors::Error
     Unauthorised,
+    Forbidden,
+    ValidationError,
     DatabaseError,
     DecodeError,
+    ParseError,
+    StorageError,
+    ServerError,
     JWTTokenCreationError,
 
     UnknownError
@@ -104,6 +143,14 @@ from_error! {std::string::FromUtf8Error > ParseError}
 from_error! {uuid::Error > ParseError}
 from_error! {base64::DecodeError > DecodeError: "error decoding"}
 
+impl Error {
+    /// Attach per-field messages, which are returned alongside the error so forms can show them.
+    pub fn with_fields(mut self, fields: BTreeMap<String, String>) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+}
+
 impl IntoResponse for Error {
     // TODO integrate this with the KindError macro
     fn into_response(self) -> Response {
@@ -111,7 +158,21 @@ impl IntoResponse for Error {
             ErrorKind::InvalidApiRequest
             | ErrorKind::InvalidCredentials
             | ErrorKind::UserDoesNotExist
-            | ErrorKind::PupilDoesNotExist => StatusCode::BAD_REQUEST,
+            | ErrorKind::PupilDoesNotExist
+            | ErrorKind::CommentDoesNotExist
+            | ErrorKind::AssessmentDoesNotExist
+            | ErrorKind::ConcernDoesNotExist
+            | ErrorKind::JudgementDoesNotExist
+            | ErrorKind::InterventionDoesNotExist
+            | ErrorKind::TargetDoesNotExist
+            | ErrorKind::EalAssessmentDoesNotExist
+            | ErrorKind::ContactDoesNotExist
+            | ErrorKind::MedicalItemDoesNotExist
+            | ErrorKind::AttachmentDoesNotExist
+            | ErrorKind::PhotoDoesNotExist
+            | ErrorKind::FlagPeriodDoesNotExist
+            | ErrorKind::ReportTemplateDoesNotExist
+            | ErrorKind::ValidationError => StatusCode::BAD_REQUEST,
             ErrorKind::MissingEnvVariable
             | ErrorKind::AddrParseError
             | ErrorKind::IoError
@@ -124,14 +185,17 @@ impl IntoResponse for Error {
             | ErrorKind::DecodeError
             | ErrorKind::ParseError
             | ErrorKind::UnknownError
+            | ErrorKind::StorageError
             | ErrorKind::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
             ErrorKind::Unauthorised | ErrorKind::InvalidJwt => StatusCode::UNAUTHORIZED,
+            ErrorKind::Forbidden => StatusCode::FORBIDDEN,
         };
         (
             code,
             Json(ErrorResponse {
                 error: self.kind.to_string(),
                 details: self.message,
+                fields: self.fields,
             }),
         )
             .into_response()
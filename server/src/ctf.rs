@@ -0,0 +1,5 @@
+pub mod handlers;
+pub mod import;
+pub mod model;
+pub mod schema;
+pub mod xml;
@@ -0,0 +1,106 @@
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    ctf::{
+        import::CtfImport,
+        model::{CtfFile, School},
+    },
+    user::model::User,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// The school a leaver is going to, unknown when it isn't given.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Destination {
+    #[serde(default)]
+    dest_lea: Option<String>,
+    #[serde(default)]
+    dest_estab: Option<String>,
+}
+
+/// Bring in a CTF from another school, or with `dry_run` just see who it matches and what it
+/// would change. The report comes back either way, with `committed` saying whether anything was
+/// saved.
+pub async fn import_ctf(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(import): Json<CtfImport>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("importing a CTF, dry run: {}", import.dry_run);
+    match import
+        .run(&user, state.config(), state.database().as_ref())
+        .await
+    {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::ValidationError | ErrorKind::DecodeError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Download a leaver's CTF for the school they're going to, named the way schools expect:
+/// source school, CTF, destination school.
+pub async fn get_leaver_ctf(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(destination): Query<Destination>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("generating a leaver CTF for pupil {id}");
+    let id = Uuid::from_str(&id)?;
+    let unknown = School::unknown();
+    let destination = School {
+        lea: destination.dest_lea.unwrap_or(unknown.lea),
+        estab: destination.dest_estab.unwrap_or(unknown.estab),
+        name: None,
+    };
+    let now = Utc::now().naive_utc();
+    let file = match CtfFile::leaver(
+        &user,
+        id,
+        destination,
+        state.config(),
+        now,
+        state.database(),
+    )
+    .await
+    {
+        Ok(file) => file,
+        Err(error) => {
+            return match error.kind {
+                ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+                ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+                ErrorKind::Unauthorised => Err(error),
+                _ => Err(UnknownError!()),
+            }
+        }
+    };
+    let data = file.to_xml()?;
+    let file_name = format!(
+        "{}{}_CTF_{}{}_001.xml",
+        file.source.lea, file.source.estab, file.destination.lea, file.destination.estab
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/xml".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        data,
+    ))
+}
@@ -0,0 +1,513 @@
+use super::{
+    model::{gender_from_sex, year_from_nc, CtfContact, CtfFile, CtfPupil, StageResult, YearData},
+    xml::{file_error, Element},
+};
+use crate::{
+    app::config::Config,
+    contact::model::{Contact, PupilContact},
+    core::error::Result,
+    flag::model::FlagPeriod,
+    pupil::{
+        import::{blank, Action, Field},
+        model::Pupil,
+    },
+    user::model::User,
+};
+use base64::{engine::general_purpose, Engine};
+use chrono::{NaiveDate, Utc};
+use entity::pupil::Entity;
+use sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// A CTF from another school to bring in. Pupils are matched to ones already here, and nothing
+/// is saved on a dry run.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CtfImport {
+    /// the file, base64 encoded
+    pub(crate) data: String,
+    /// when new pupils start here, today if it isn't given
+    #[serde(default)]
+    pub(crate) start_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Upn,
+    NameAndDateOfBirth,
+    None,
+}
+
+/// The pupil already here that the file's pupil was taken to be.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Match {
+    pub(crate) kind: MatchKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) pupil_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ContactReport {
+    pub(crate) name: String,
+    pub(crate) relationship: String,
+    /// create, or unchanged when the pupil already has a contact with the name
+    pub(crate) action: Action,
+}
+
+/// What the import would do, or did, with one pupil in the file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PupilReport {
+    /// counting from 1 in the order the file lists them
+    pub(crate) index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) upn: Option<String>,
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) date_of_birth: Option<NaiveDate>,
+    pub(crate) matched: Match,
+    pub(crate) action: Action,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) changes: Vec<Field>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) errors: BTreeMap<String, String>,
+    pub(crate) contacts: Vec<ContactReport>,
+    /// things that won't stop the import but won't come across either, like a contact with no
+    /// way of reaching them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) warnings: Vec<String>,
+    /// shown for reference, the register here starts from the pupil's first day
+    pub(crate) attendance: Vec<YearData>,
+    /// shown for reference, results from another school's assessments aren't saved
+    pub(crate) assessments: Vec<StageResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CtfReport {
+    pub(crate) source: super::model::School,
+    pub(crate) pupils: Vec<PupilReport>,
+    pub(crate) creates: usize,
+    pub(crate) updates: usize,
+    pub(crate) unchanged: usize,
+    pub(crate) errors: usize,
+    /// saved, which only happens when it isn't a dry run and no pupil has an error
+    pub(crate) committed: bool,
+}
+
+/// A pupil that passed, with how they were before if they're already here and the contacts to
+/// add.
+struct Planned {
+    before: Option<Pupil>,
+    pupil: Pupil,
+    contacts: Vec<PupilContact>,
+}
+
+impl CtfImport {
+    pub fn decode(&self, max_bytes: usize) -> Result<Vec<u8>> {
+        let data = general_purpose::STANDARD.decode(self.data.trim())?;
+        if data.is_empty() || data.len() > max_bytes {
+            return Err(file_error(format!(
+                "file must be between 1 and {max_bytes} bytes"
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Match each pupil in the file to one already here, on UPN and failing that on name and date
+    /// of birth, and work out what would change. Unless it's a dry run, and as long as every
+    /// pupil passed, the pupils and their new contacts are saved together.
+    pub async fn run(
+        &self,
+        user: &User,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<CtfReport> {
+        let file =
+            CtfFile::from_element(&Element::parse(&self.decode(config.attachment_max_bytes)?)?)?;
+        let today = Utc::now().date_naive();
+        let mut existing: Vec<Pupil> = Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        FlagPeriod::flag_as_at(&mut existing, today, db).await?;
+
+        let mut report = CtfReport {
+            source: file.source.clone(),
+            pupils: vec![],
+            creates: 0,
+            updates: 0,
+            unchanged: 0,
+            errors: 0,
+            committed: false,
+        };
+        let mut seen: HashSet<Uuid> = HashSet::new();
+        let mut planned: Vec<Planned> = vec![];
+        for (index, ctf_pupil) in file.pupils.iter().enumerate() {
+            let before = find_match(ctf_pupil, &existing);
+            let existing_contacts = match &before {
+                Some((_, pupil)) if user.years.contains(&(pupil.year as u32)) => {
+                    PupilContact::all_for_pupil(user, pupil.id, db).await?
+                }
+                _ => vec![],
+            };
+            let (mut pupil_report, plan) = check_pupil(
+                ctf_pupil,
+                before,
+                &existing_contacts,
+                self.start_date.unwrap_or(today),
+                today,
+                user,
+                config,
+            );
+            pupil_report.index = index + 1;
+            if let Some(id) = pupil_report.matched.pupil_id {
+                if !seen.insert(id) {
+                    pupil_report.action = Action::Error;
+                    pupil_report.errors.insert(
+                        "pupil".into(),
+                        "matches the same pupil as an earlier one in the file".into(),
+                    );
+                }
+            }
+            match pupil_report.action {
+                Action::Create => report.creates += 1,
+                Action::Update => report.updates += 1,
+                Action::Unchanged => report.unchanged += 1,
+                Action::Error => report.errors += 1,
+            }
+            report.pupils.push(pupil_report);
+            planned.extend(plan);
+        }
+
+        if self.dry_run || report.errors > 0 {
+            return Ok(report);
+        }
+        let txn = db.begin().await?;
+        for Planned {
+            before,
+            pupil,
+            contacts,
+        } in &planned
+        {
+            match before {
+                Some(before) if before == pupil => {}
+                Some(_) => {
+                    pupil.update(&txn).await?;
+                }
+                None => {
+                    pupil.insert(&txn).await?;
+                }
+            };
+            FlagPeriod::follow_pupil(&txn, before.as_ref(), pupil, Some(user)).await?;
+            for contact in contacts {
+                contact.save(&txn).await?;
+            }
+        }
+        txn.commit().await?;
+        report.committed = true;
+        Ok(report)
+    }
+}
+
+fn find_match<'a>(ctf_pupil: &CtfPupil, existing: &'a [Pupil]) -> Option<(MatchKind, &'a Pupil)> {
+    let by_upn = ctf_pupil.upn.as_ref().and_then(|upn| {
+        existing
+            .iter()
+            .find(|pupil| pupil.upn.as_ref() == Some(upn))
+    });
+    if let Some(pupil) = by_upn {
+        return Some((MatchKind::Upn, pupil));
+    }
+    let same = |a: &str, b: &str| a.trim().to_lowercase() == b.trim().to_lowercase();
+    ctf_pupil.date_of_birth.and_then(|date_of_birth| {
+        existing
+            .iter()
+            .find(|pupil| {
+                pupil.date_of_birth == Some(date_of_birth)
+                    && same(&pupil.first_names, &ctf_pupil.forename)
+                    && same(&pupil.last_name, &ctf_pupil.surname)
+            })
+            .map(|pupil| (MatchKind::NameAndDateOfBirth, pupil))
+    })
+}
+
+fn check_pupil(
+    ctf_pupil: &CtfPupil,
+    matched: Option<(MatchKind, &Pupil)>,
+    existing_contacts: &[PupilContact],
+    start_date: NaiveDate,
+    today: NaiveDate,
+    user: &User,
+    config: &Config,
+) -> (PupilReport, Option<Planned>) {
+    let before = matched.map(|(_, pupil)| pupil.clone());
+    let mut pupil = before.clone().unwrap_or_else(|| Pupil {
+        start_date,
+        upn: None,
+        ..blank("")
+    });
+    let mut errors: BTreeMap<String, String> = BTreeMap::new();
+
+    if ctf_pupil.upn.is_some() {
+        pupil.upn = ctf_pupil.upn.clone();
+    }
+    pupil.first_names = ctf_pupil.forename.clone();
+    pupil.last_name = ctf_pupil.surname.clone();
+    pupil.gender = gender_from_sex(&ctf_pupil.sex).into();
+    match year_from_nc(&ctf_pupil.nc_year) {
+        Some(year) => pupil.year = year,
+        None => {
+            errors.insert(
+                "year".into(),
+                format!("{} is not a national curriculum year", ctf_pupil.nc_year),
+            );
+        }
+    }
+    match ctf_pupil.date_of_birth {
+        Some(date_of_birth) => pupil.date_of_birth = Some(date_of_birth),
+        None => {
+            errors.insert("date_of_birth".into(), "date of birth is required".into());
+        }
+    }
+    if let Some(name) = &ctf_pupil.preferred_forename {
+        pupil.preferred_first_names = Some(name.clone());
+    }
+    if let Some(name) = &ctf_pupil.preferred_surname {
+        pupil.preferred_last_name = Some(name.clone());
+    }
+    if let Some(language) = &ctf_pupil.language {
+        pupil.home_language = Some(language.clone());
+    }
+    pupil.looked_after_child = ctf_pupil.in_care;
+    pupil.free_school_meals = ctf_pupil.fsm_on(today);
+
+    if let Some(year) = [Some(pupil.year), before.as_ref().map(|b| b.year)]
+        .into_iter()
+        .flatten()
+        .find(|year| !user.years.contains(&(*year as u32)))
+    {
+        errors.entry("year".into()).or_insert(format!(
+            "you don't have permission to import into year {year}"
+        ));
+    }
+    if let Err(error) = pupil.validate(config) {
+        for (field, message) in error.fields.unwrap_or_default() {
+            errors.entry(field).or_insert(message);
+        }
+    }
+
+    let mut warnings = vec![];
+    let mut contact_reports = vec![];
+    let mut contacts = vec![];
+    for ctf_contact in &ctf_pupil.contacts {
+        let name = format!("{} {}", ctf_contact.forename, ctf_contact.surname)
+            .trim()
+            .to_owned();
+        let already = existing_contacts.iter().any(|existing| {
+            existing.contact.first_names.to_lowercase() == ctf_contact.forename.to_lowercase()
+                && existing.contact.last_name.to_lowercase() == ctf_contact.surname.to_lowercase()
+        });
+        let contact = new_contact(pupil.id, ctf_contact);
+        let action = if already {
+            Action::Unchanged
+        } else if let Err(error) = contact.validate() {
+            let problems: Vec<String> = error.fields.unwrap_or_default().into_values().collect();
+            warnings.push(format!("{name} won't be added: {}", problems.join(", ")));
+            Action::Error
+        } else {
+            contacts.push(contact);
+            Action::Create
+        };
+        contact_reports.push(ContactReport {
+            name,
+            relationship: ctf_contact.our_relationship().into(),
+            action,
+        });
+    }
+
+    let changes: Vec<Field> = match &before {
+        Some(before) => Field::ALL
+            .into_iter()
+            .filter(|field| field.value(before) != field.value(&pupil))
+            .collect(),
+        None => vec![],
+    };
+    let action = match (&before, errors.is_empty(), changes.is_empty()) {
+        (_, false, _) => Action::Error,
+        (None, true, _) => Action::Create,
+        (Some(_), true, true) if contacts.is_empty() => Action::Unchanged,
+        (Some(_), true, _) => Action::Update,
+    };
+    let report = PupilReport {
+        index: 0,
+        upn: ctf_pupil.upn.clone(),
+        name: format!("{} {}", ctf_pupil.forename, ctf_pupil.surname)
+            .trim()
+            .to_owned(),
+        date_of_birth: ctf_pupil.date_of_birth,
+        matched: match matched {
+            Some((kind, pupil)) => Match {
+                kind,
+                pupil_id: Some(pupil.id),
+                name: Some(format!("{} {}", pupil.first_names, pupil.last_name)),
+            },
+            None => Match {
+                kind: MatchKind::None,
+                pupil_id: None,
+                name: None,
+            },
+        },
+        action,
+        changes,
+        errors,
+        contacts: contact_reports,
+        warnings,
+        attendance: ctf_pupil.attendance.clone(),
+        assessments: ctf_pupil.assessments.clone(),
+    };
+    let plan = matches!(action, Action::Create | Action::Update).then_some(Planned {
+        before,
+        pupil,
+        contacts,
+    });
+    (report, plan)
+}
+
+/// A new contact for the pupil from the file, after any the school already has for them.
+fn new_contact(pupil_id: Uuid, ctf_contact: &CtfContact) -> PupilContact {
+    let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+    PupilContact {
+        contact: Contact {
+            id: Uuid::new_v4(),
+            first_names: ctf_contact.forename.clone(),
+            last_name: ctf_contact.surname.clone(),
+            phone: non_empty(&ctf_contact.phone),
+            email: non_empty(&ctf_contact.email),
+            preferred_language: None,
+        },
+        pupil_id,
+        relationship: ctf_contact.our_relationship().into(),
+        priority: ctf_contact.order.max(1),
+        parental_responsibility: ctf_contact.responsibility,
+        no_contact: false,
+        restriction_notes: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctf::model::FsmSpell;
+    use rstest::*;
+
+    fn ctf_pupil() -> CtfPupil {
+        CtfPupil {
+            upn: Some("H801200001001".into()),
+            surname: "Smith".into(),
+            forename: "Ann".into(),
+            date_of_birth: Some("2012-05-01".parse().unwrap()),
+            sex: "F".into(),
+            preferred_surname: None,
+            preferred_forename: None,
+            nc_year: "6".into(),
+            language: None,
+            in_care: false,
+            fsm: vec![FsmSpell {
+                start_date: "2021-09-01".parse().unwrap(),
+                end_date: None,
+            }],
+            contacts: vec![CtfContact {
+                order: 1,
+                surname: "Smith".into(),
+                forename: "Jo".into(),
+                gender: Some("F".into()),
+                relationship: "PAR".into(),
+                responsibility: true,
+                phone: None,
+                email: None,
+            }],
+            attendance: vec![],
+            assessments: vec![],
+            schools: vec![],
+        }
+    }
+
+    fn user() -> User {
+        User::new("test", "user", "teacher@school.com", "pass", vec![5, 6])
+    }
+
+    #[rstest]
+    fn test_matches_on_upn_then_name_and_date_of_birth() {
+        let mut pupil = Pupil {
+            first_names: "ann ".into(),
+            last_name: "SMITH".into(),
+            date_of_birth: Some("2012-05-01".parse().unwrap()),
+            upn: None,
+            ..blank("")
+        };
+        let existing = vec![pupil.clone()];
+        let (kind, _) = find_match(&ctf_pupil(), &existing).unwrap();
+        assert_eq!(kind, MatchKind::NameAndDateOfBirth);
+
+        pupil.upn = Some("H801200001001".into());
+        pupil.first_names = "Someone".into();
+        let existing = vec![pupil];
+        let (kind, _) = find_match(&ctf_pupil(), &existing).unwrap();
+        assert_eq!(kind, MatchKind::Upn);
+
+        let mut stranger = ctf_pupil();
+        stranger.upn = None;
+        stranger.date_of_birth = Some("2012-05-02".parse().unwrap());
+        assert!(find_match(&stranger, &existing).is_none());
+    }
+
+    #[rstest]
+    fn test_new_pupil_from_the_file() {
+        let today = "2023-09-04".parse().unwrap();
+        let (report, plan) = check_pupil(
+            &ctf_pupil(),
+            None,
+            &[],
+            today,
+            today,
+            &user(),
+            &Config::default(),
+        );
+        assert_eq!(report.action, Action::Create);
+        assert_eq!(report.matched.kind, MatchKind::None);
+        // no phone or email for the contact, so they're left out with a warning
+        assert_eq!(report.contacts[0].action, Action::Error);
+        assert_eq!(report.warnings.len(), 1);
+        let plan = plan.unwrap();
+        assert!(plan.contacts.is_empty());
+        assert_eq!(plan.pupil.gender, "female");
+        assert_eq!(plan.pupil.start_date, today);
+        assert!(plan.pupil.free_school_meals);
+    }
+
+    #[rstest]
+    fn test_year_outside_the_users_years_is_an_error() {
+        let mut ctf_pupil = ctf_pupil();
+        ctf_pupil.nc_year = "R".into();
+        let today = "2023-09-04".parse().unwrap();
+        let (report, plan) = check_pupil(
+            &ctf_pupil,
+            None,
+            &[],
+            today,
+            today,
+            &user(),
+            &Config::default(),
+        );
+        assert_eq!(report.action, Action::Error);
+        assert!(report.errors.contains_key("year"));
+        assert!(plan.is_none());
+    }
+}
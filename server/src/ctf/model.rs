@@ -0,0 +1,800 @@
+use super::{
+    schema::{self, CTF_VERSION, DOCUMENT_NAME, ROOT},
+    xml::{file_error, Element},
+};
+use crate::{
+    app::config::Config,
+    assessment::result::PupilResult,
+    attendance::summary::{academic_year_start, AttendanceSummary},
+    contact::model::PupilContact,
+    core::error::Result,
+    flag::model::{Flag, FlagPeriod},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A school as a CTF identifies it, by local authority and establishment number.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct School {
+    pub(crate) lea: String,
+    pub(crate) estab: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+}
+
+impl School {
+    /// The CTF's code for a school that isn't known yet, so the file goes to the lost pupils
+    /// database.
+    pub fn unknown() -> Self {
+        Self {
+            lea: "MMM".into(),
+            estab: "MMMM".into(),
+            name: None,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            lea: config.school_lea.clone(),
+            estab: config.school_estab.clone(),
+            name: (!config.school_name.is_empty()).then(|| config.school_name.clone()),
+        }
+    }
+
+    fn from_element(element: Option<&Element>) -> Self {
+        Self {
+            lea: element.and_then(|e| e.value("LEA")).unwrap_or_default(),
+            estab: element.and_then(|e| e.value("Estab")).unwrap_or_default(),
+            name: element.and_then(|e| e.value("SchoolName")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FsmSpell {
+    pub(crate) start_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) end_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CtfContact {
+    pub(crate) order: i32,
+    pub(crate) surname: String,
+    pub(crate) forename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) gender: Option<String>,
+    /// a relationship code from the common basic data set, like PAR
+    pub(crate) relationship: String,
+    pub(crate) responsibility: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) email: Option<String>,
+}
+
+/// Session counts for one academic year, the CTF doesn't carry individual marks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct YearData {
+    pub(crate) year: i32,
+    pub(crate) sessions_possible: u32,
+    pub(crate) sessions_authorised: u32,
+    pub(crate) sessions_unauthorised: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StageResult {
+    pub(crate) stage: String,
+    pub(crate) year: i32,
+    pub(crate) subject: String,
+    /// TT for a test, TA for a teacher assessment
+    pub(crate) method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) component: Option<String>,
+    pub(crate) result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) date: Option<NaiveDate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SchoolSpell {
+    pub(crate) school: School,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) entry_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) leaving_date: Option<NaiveDate>,
+}
+
+/// One pupil's record in a CTF, in the file's own terms. Reading is forgiving, anything missing or
+/// unreadable is left empty for the import to report on, and writing follows `schema`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CtfPupil {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) upn: Option<String>,
+    pub(crate) surname: String,
+    pub(crate) forename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) date_of_birth: Option<NaiveDate>,
+    pub(crate) sex: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_surname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_forename: Option<String>,
+    /// the national curriculum year, N1, N2, R or a number
+    pub(crate) nc_year: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) language: Option<String>,
+    pub(crate) in_care: bool,
+    pub(crate) fsm: Vec<FsmSpell>,
+    pub(crate) contacts: Vec<CtfContact>,
+    pub(crate) attendance: Vec<YearData>,
+    pub(crate) assessments: Vec<StageResult>,
+    pub(crate) schools: Vec<SchoolSpell>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CtfFile {
+    pub(crate) source: School,
+    pub(crate) destination: School,
+    pub(crate) created: NaiveDateTime,
+    pub(crate) pupils: Vec<CtfPupil>,
+}
+
+fn date(value: Option<String>) -> Option<NaiveDate> {
+    value.and_then(|value| NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok())
+}
+
+fn boolean(value: Option<String>) -> bool {
+    matches!(value.as_deref(), Some("true" | "1"))
+}
+
+fn number<T: std::str::FromStr + Default>(value: Option<String>) -> T {
+    value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+impl CtfFile {
+    pub fn from_element(document: &Element) -> Result<Self> {
+        if document.name != ROOT {
+            return Err(file_error(format!(
+                "this isn't a CTF, it starts with {} rather than {ROOT}",
+                document.name
+            )));
+        }
+        let header = document.child("Header");
+        Ok(Self {
+            source: School::from_element(header.and_then(|h| h.child("SourceSchool"))),
+            destination: School::from_element(header.and_then(|h| h.child("DestSchool"))),
+            created: header
+                .and_then(|h| h.value("DateTime"))
+                .and_then(|value| NaiveDateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M:%S").ok())
+                .unwrap_or_default(),
+            pupils: document
+                .child("CTFpupilData")
+                .map(|data| data.children("Pupil").map(CtfPupil::from_element).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn to_element(&self) -> Element {
+        let school = |name: &str, school: &School| {
+            Element::new(name)
+                .with(Element::text("LEA", &school.lea))
+                .with(Element::text("Estab", &school.estab))
+        };
+        let academic_year = academic_year_start(self.created.date()).year();
+        let header = Element::new("Header")
+            .with(Element::text("DocumentName", DOCUMENT_NAME))
+            .with(Element::text("CTFversion", CTF_VERSION))
+            .with(Element::text(
+                "DateTime",
+                self.created.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ))
+            .with(Element::text("DocumentQualifier", "full"))
+            .with(
+                school("SourceSchool", &self.source)
+                    .with_some("SchoolName", self.source.name.as_deref())
+                    .with(Element::text("AcademicYear", academic_year.to_string())),
+            )
+            .with(school("DestSchool", &self.destination));
+        let mut document = Element::new(ROOT).with(header);
+        if !self.pupils.is_empty() {
+            document = document.with(Element {
+                name: "CTFpupilData".into(),
+                text: String::new(),
+                children: self.pupils.iter().map(CtfPupil::to_element).collect(),
+            });
+        }
+        document
+    }
+
+    /// The file as XML, as long as it passes the schema. Otherwise the errors come back keyed by
+    /// where they are in the file.
+    pub fn to_xml(&self) -> Result<Vec<u8>> {
+        let document = self.to_element();
+        let errors = schema::validate(&document);
+        if !errors.is_empty() {
+            return Err(
+                ValidationError!("CTF failed schema validation").with_fields(
+                    errors
+                        .into_iter()
+                        .map(|error| (error.path, error.message))
+                        .collect(),
+                ),
+            );
+        }
+        document.to_xml()
+    }
+
+    /// A leaver's file for one pupil: their details, contacts, FSM history, attendance for each
+    /// year, assessment results and their time at this school.
+    pub async fn leaver(
+        user: &User,
+        pupil_id: Uuid,
+        destination: School,
+        config: &Config,
+        now: NaiveDateTime,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        let pupil = Pupil::one_from_db(user, pupil_id, db).await?;
+        let fsm: Vec<FsmSpell> = FlagPeriod::all_for_pupil(user, pupil_id, db)
+            .await?
+            .into_iter()
+            .filter(|period| period.flag == Flag::Fsm)
+            .rev()
+            .map(|period| FsmSpell {
+                start_date: period.start_date,
+                end_date: period.end_date,
+            })
+            .collect();
+        // a court order can't be expressed in a CTF, so contacts the school mustn't use are left
+        // out rather than sent on as ordinary contacts
+        let contacts: Vec<CtfContact> = PupilContact::all_for_pupil(user, pupil_id, db)
+            .await?
+            .into_iter()
+            .filter(|contact| !contact.no_contact)
+            .map(CtfContact::from)
+            .collect();
+        let attendance = AttendanceSummary::by_academic_year(pupil_id, config, db)
+            .await?
+            .into_iter()
+            .map(|(year, summary)| YearData {
+                year,
+                sessions_possible: summary.possible_sessions,
+                sessions_authorised: summary.authorised_absences,
+                sessions_unauthorised: summary.unauthorised_absences,
+            })
+            .collect();
+        let assessments = PupilResult::all_for_pupil(user, pupil_id, db)
+            .await?
+            .into_iter()
+            .map(StageResult::from)
+            .collect();
+        let source = School::from_config(config);
+        Ok(Self {
+            pupils: vec![CtfPupil {
+                upn: pupil.upn.clone(),
+                surname: pupil.last_name.clone(),
+                forename: pupil.first_names.clone(),
+                date_of_birth: pupil.date_of_birth,
+                sex: sex_code(&pupil.gender).into(),
+                preferred_surname: pupil.preferred_last_name.clone(),
+                preferred_forename: pupil.preferred_first_names.clone(),
+                nc_year: nc_year(pupil.year),
+                language: pupil.home_language.as_deref().and_then(language_code),
+                in_care: pupil.looked_after_child,
+                fsm,
+                contacts,
+                attendance,
+                assessments,
+                schools: vec![SchoolSpell {
+                    school: source.clone(),
+                    entry_date: Some(pupil.start_date),
+                    leaving_date: pupil.end_date,
+                }],
+            }],
+            source,
+            destination,
+            created: now,
+        })
+    }
+}
+
+impl CtfPupil {
+    fn from_element(pupil: &Element) -> Self {
+        let basic = pupil.child("BasicDetails");
+        let basic_value = |name: &str| basic.and_then(|b| b.value(name));
+        Self {
+            upn: pupil.value("UPN").map(|upn| upn.to_uppercase()),
+            surname: pupil.value("Surname").unwrap_or_default(),
+            forename: pupil.value("Forename").unwrap_or_default(),
+            date_of_birth: date(pupil.value("DOB")),
+            // older files call it gender
+            sex: pupil
+                .value("Sex")
+                .or_else(|| pupil.value("Gender"))
+                .unwrap_or_default(),
+            preferred_surname: basic_value("PreferredSurname"),
+            preferred_forename: basic_value("PreferredForename"),
+            nc_year: basic_value("NCyearActual").unwrap_or_default(),
+            language: basic
+                .and_then(|b| b.child("Languages"))
+                .and_then(|languages| {
+                    languages
+                        .children("Type")
+                        .find(|t| t.value("LanguageType").as_deref() == Some("F"))
+                })
+                .and_then(|first| first.value("Language")),
+            in_care: boolean(basic_value("InCare")),
+            fsm: pupil
+                .child("FSMhistory")
+                .map(|history| {
+                    history
+                        .children("FSMinstance")
+                        .filter_map(|instance| {
+                            Some(FsmSpell {
+                                start_date: date(instance.value("FSMstartDate"))?,
+                                end_date: date(instance.value("FSMendDate")),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            contacts: pupil
+                .child("Contacts")
+                .map(|contacts| {
+                    contacts
+                        .children("Contact")
+                        .map(CtfContact::from_element)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            attendance: pupil
+                .child("Attendance")
+                .map(|attendance| {
+                    attendance
+                        .children("YearData")
+                        .map(|year| YearData {
+                            year: number(year.value("Year")),
+                            sessions_possible: number(year.value("SessionsPossible")),
+                            sessions_authorised: number(year.value("SessionsAuthorised")),
+                            sessions_unauthorised: number(year.value("SessionsUnauthorised")),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            assessments: pupil
+                .child("StageAssessments")
+                .map(|stages| {
+                    stages
+                        .children("KeyStage")
+                        .flat_map(|key_stage| {
+                            let stage = key_stage.value("Stage").unwrap_or_default();
+                            key_stage
+                                .children("StageAssessment")
+                                .map(move |result| StageResult {
+                                    stage: stage.clone(),
+                                    year: number(result.value("Year")),
+                                    subject: result.value("Subject").unwrap_or_default(),
+                                    method: result.value("Method").unwrap_or_default(),
+                                    component: result.value("Component"),
+                                    result: result.value("Result").unwrap_or_default(),
+                                    date: date(result.value("ResultDate")),
+                                })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            schools: pupil
+                .child("SchoolHistory")
+                .map(|history| {
+                    history
+                        .children("School")
+                        .map(|school| SchoolSpell {
+                            school: School::from_element(Some(school)),
+                            entry_date: date(school.value("EntryDate")),
+                            leaving_date: date(school.value("LeavingDate")),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut basic = Element::new("BasicDetails")
+            .with_some("PreferredSurname", self.preferred_surname.as_deref())
+            .with_some("PreferredForename", self.preferred_forename.as_deref())
+            .with(Element::text("NCyearActual", &self.nc_year));
+        if let Some(language) = &self.language {
+            basic = basic.with(
+                Element::new("Languages").with(
+                    Element::new("Type")
+                        .with(Element::text("LanguageType", "F"))
+                        .with(Element::text("Language", language)),
+                ),
+            );
+        }
+        let mut pupil = Element::new("Pupil")
+            .with_some("UPN", self.upn.as_deref())
+            .with(Element::text("Surname", &self.surname))
+            .with(Element::text("Forename", &self.forename))
+            .with_some("DOB", self.date_of_birth.map(|date| date.to_string()))
+            .with(Element::text("Sex", &self.sex))
+            .with(basic.with(Element::text("InCare", self.in_care.to_string())));
+        if !self.fsm.is_empty() {
+            pupil = pupil.with(Element {
+                name: "FSMhistory".into(),
+                text: String::new(),
+                children: self
+                    .fsm
+                    .iter()
+                    .map(|spell| {
+                        Element::new("FSMinstance")
+                            .with(Element::text("FSMstartDate", spell.start_date.to_string()))
+                            .with_some("FSMendDate", spell.end_date.map(|d| d.to_string()))
+                    })
+                    .collect(),
+            });
+        }
+        if !self.contacts.is_empty() {
+            pupil = pupil.with(Element {
+                name: "Contacts".into(),
+                text: String::new(),
+                children: self.contacts.iter().map(CtfContact::to_element).collect(),
+            });
+        }
+        if !self.attendance.is_empty() {
+            pupil = pupil.with(Element {
+                name: "Attendance".into(),
+                text: String::new(),
+                children: self
+                    .attendance
+                    .iter()
+                    .map(|year| {
+                        Element::new("YearData")
+                            .with(Element::text("Year", year.year.to_string()))
+                            .with(Element::text(
+                                "SessionsPossible",
+                                year.sessions_possible.to_string(),
+                            ))
+                            .with(Element::text(
+                                "SessionsAuthorised",
+                                year.sessions_authorised.to_string(),
+                            ))
+                            .with(Element::text(
+                                "SessionsUnauthorised",
+                                year.sessions_unauthorised.to_string(),
+                            ))
+                    })
+                    .collect(),
+            });
+        }
+        if !self.assessments.is_empty() {
+            // results are grouped under their key stage, in the order the stages first appear
+            let mut stages: Vec<(String, Vec<Element>)> = vec![];
+            for result in &self.assessments {
+                let element = Element::new("StageAssessment")
+                    .with(Element::text("Year", result.year.to_string()))
+                    .with(Element::text("Subject", &result.subject))
+                    .with(Element::text("Method", &result.method))
+                    .with_some("Component", result.component.as_deref())
+                    .with(Element::text("Result", &result.result))
+                    .with_some("ResultDate", result.date.map(|d| d.to_string()));
+                match stages.iter_mut().find(|(stage, _)| *stage == result.stage) {
+                    Some((_, results)) => results.push(element),
+                    None => stages.push((result.stage.clone(), vec![element])),
+                }
+            }
+            pupil = pupil.with(Element {
+                name: "StageAssessments".into(),
+                text: String::new(),
+                children: stages
+                    .into_iter()
+                    .map(|(stage, results)| {
+                        let mut key_stage =
+                            Element::new("KeyStage").with(Element::text("Stage", stage));
+                        key_stage.children.extend(results);
+                        key_stage
+                    })
+                    .collect(),
+            });
+        }
+        if !self.schools.is_empty() {
+            pupil = pupil.with(Element {
+                name: "SchoolHistory".into(),
+                text: String::new(),
+                children: self
+                    .schools
+                    .iter()
+                    .map(|spell| {
+                        Element::new("School")
+                            .with(Element::text("LEA", &spell.school.lea))
+                            .with(Element::text("Estab", &spell.school.estab))
+                            .with_some("SchoolName", spell.school.name.as_deref())
+                            .with_some("EntryDate", spell.entry_date.map(|d| d.to_string()))
+                            .with_some("LeavingDate", spell.leaving_date.map(|d| d.to_string()))
+                    })
+                    .collect(),
+            });
+        }
+        pupil
+    }
+
+    /// Whether the pupil was getting free school meals on the date
+    pub fn fsm_on(&self, date: NaiveDate) -> bool {
+        self.fsm
+            .iter()
+            .any(|spell| spell.start_date <= date && spell.end_date.is_none_or(|end| date <= end))
+    }
+}
+
+impl CtfContact {
+    fn from_element(contact: &Element) -> Self {
+        let phones = contact.child("Phones");
+        Self {
+            order: number(contact.value("Order")),
+            surname: contact.value("Surname").unwrap_or_default(),
+            forename: contact.value("Forename").unwrap_or_default(),
+            gender: contact.value("Gender"),
+            relationship: contact.value("Relationship").unwrap_or_default(),
+            responsibility: boolean(contact.value("Responsibility")),
+            phone: phones
+                .and_then(|phones| phones.children("Phone").find_map(|p| p.value("PhoneNo"))),
+            email: contact.value("Email"),
+        }
+    }
+
+    fn to_element(&self) -> Element {
+        let mut contact = Element::new("Contact")
+            .with(Element::text("Order", self.order.to_string()))
+            .with(Element::text("Surname", &self.surname))
+            .with(Element::text("Forename", &self.forename))
+            .with_some("Gender", self.gender.as_deref())
+            .with(Element::text("Relationship", &self.relationship))
+            .with(Element::text(
+                "Responsibility",
+                self.responsibility.to_string(),
+            ));
+        if let Some(phone) = &self.phone {
+            contact = contact.with(
+                Element::new("Phones").with(
+                    Element::new("Phone")
+                        .with(Element::text("TelephoneType", "H"))
+                        .with(Element::text("PhoneNo", phone)),
+                ),
+            );
+        }
+        contact.with_some("Email", self.email.as_deref())
+    }
+
+    /// Our relationship for the contact's code. A parent is a mother or father when the file
+    /// gives their gender, anyone the codes don't tell us enough about is other.
+    pub fn our_relationship(&self) -> &'static str {
+        match (self.relationship.as_str(), self.gender.as_deref()) {
+            ("PAR", Some("F")) => "mother",
+            ("PAR", Some("M")) => "father",
+            ("STP", _) => "step_parent",
+            ("CAR" | "FOS", _) => "carer",
+            _ => "other",
+        }
+    }
+}
+
+impl From<PupilContact> for CtfContact {
+    fn from(contact: PupilContact) -> Self {
+        let (relationship, gender) = match contact.relationship.as_str() {
+            "mother" => ("PAR", Some("F")),
+            "father" => ("PAR", Some("M")),
+            "step_parent" => ("STP", None),
+            "carer" => ("CAR", None),
+            "grandparent" => ("REL", None),
+            "sibling" => ("FAM", None),
+            _ => ("OTH", None),
+        };
+        Self {
+            order: contact.priority,
+            surname: contact.contact.last_name,
+            forename: contact.contact.first_names,
+            gender: gender.map(str::to_owned),
+            relationship: relationship.into(),
+            responsibility: contact.parental_responsibility,
+            phone: contact.contact.phone,
+            email: contact.contact.email,
+        }
+    }
+}
+
+impl From<PupilResult> for StageResult {
+    /// Our assessments are tests, so each goes in as a test result for the key stage of the year
+    /// group that sat it, standardised where there's a table for it.
+    fn from(result: PupilResult) -> Self {
+        let assessment = result.assessment;
+        Self {
+            stage: key_stage(assessment.year).into(),
+            year: academic_year_start(assessment.date).year(),
+            subject: assessment.subject,
+            method: "TT".into(),
+            component: Some(assessment.name.chars().take(35).collect()),
+            result: result
+                .score
+                .standardised_score
+                .unwrap_or(result.score.raw_score)
+                .to_string(),
+            date: Some(assessment.date),
+        }
+    }
+}
+
+pub fn sex_code(gender: &str) -> &'static str {
+    match gender {
+        "female" => "F",
+        "male" => "M",
+        _ => "U",
+    }
+}
+
+pub fn gender_from_sex(sex: &str) -> &'static str {
+    match sex {
+        "F" => "female",
+        "M" => "male",
+        _ => "other",
+    }
+}
+
+/// Reception is year 0 here and R in a CTF, with the nursery years before it
+pub fn nc_year(year: i32) -> String {
+    match year {
+        0 => "R".into(),
+        -1 => "N2".into(),
+        i32::MIN..=-2 => "N1".into(),
+        _ => year.to_string(),
+    }
+}
+
+pub fn year_from_nc(nc_year: &str) -> Option<i32> {
+    match nc_year {
+        "R" => Some(0),
+        "N2" => Some(-1),
+        "N1" => Some(-2),
+        _ => nc_year.parse().ok(),
+    }
+}
+
+/// Foundation phase to year 2, then key stages 2, 3 and 4
+pub fn key_stage(year: i32) -> &'static str {
+    match year {
+        i32::MIN..=2 => "FP",
+        3..=6 => "KS2",
+        7..=9 => "KS3",
+        _ => "KS4",
+    }
+}
+
+/// Home languages are free text here, only ones already written as a code like POL go in a CTF.
+pub fn language_code(language: &str) -> Option<String> {
+    let code = language.trim().to_uppercase();
+    ((3..=4).contains(&code.len()) && code.chars().all(|c| c.is_ascii_uppercase())).then_some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn file() -> CtfFile {
+        CtfFile {
+            source: School {
+                lea: "801".into(),
+                estab: "2000".into(),
+                name: Some("Example Primary".into()),
+            },
+            destination: School::unknown(),
+            created: "2023-06-14T09:30:00".parse().unwrap(),
+            pupils: vec![CtfPupil {
+                upn: Some("H801200001001".into()),
+                surname: "Smith".into(),
+                forename: "Ann".into(),
+                date_of_birth: Some("2012-05-01".parse().unwrap()),
+                sex: "F".into(),
+                preferred_surname: None,
+                preferred_forename: Some("Annie".into()),
+                nc_year: "6".into(),
+                language: Some("POL".into()),
+                in_care: false,
+                fsm: vec![FsmSpell {
+                    start_date: "2021-09-01".parse().unwrap(),
+                    end_date: None,
+                }],
+                contacts: vec![CtfContact {
+                    order: 1,
+                    surname: "Smith".into(),
+                    forename: "Jo".into(),
+                    gender: Some("F".into()),
+                    relationship: "PAR".into(),
+                    responsibility: true,
+                    phone: Some("01234 567890".into()),
+                    email: None,
+                }],
+                attendance: vec![YearData {
+                    year: 2022,
+                    sessions_possible: 300,
+                    sessions_authorised: 10,
+                    sessions_unauthorised: 2,
+                }],
+                assessments: vec![StageResult {
+                    stage: "KS2".into(),
+                    year: 2022,
+                    subject: "maths".into(),
+                    method: "TT".into(),
+                    component: Some("Summer test".into()),
+                    result: "104".into(),
+                    date: Some("2023-06-01".parse().unwrap()),
+                }],
+                schools: vec![SchoolSpell {
+                    school: School {
+                        lea: "801".into(),
+                        estab: "2000".into(),
+                        name: None,
+                    },
+                    entry_date: Some("2016-09-05".parse().unwrap()),
+                    leaving_date: Some("2023-07-21".parse().unwrap()),
+                }],
+            }],
+        }
+    }
+
+    #[rstest]
+    fn test_generated_file_passes_the_schema_and_reads_back() {
+        let file = file();
+        let xml = file.to_xml().unwrap();
+        let document = Element::parse(&xml).unwrap();
+        assert_eq!(schema::validate(&document), vec![]);
+        let read = CtfFile::from_element(&document).unwrap();
+        assert_eq!(read.pupils, file.pupils);
+        assert_eq!(read.destination, file.destination);
+        assert_eq!(read.created, file.created);
+        assert_eq!(read.source.lea, "801");
+    }
+
+    #[rstest]
+    fn test_file_failing_the_schema_isnt_written() {
+        let mut file = file();
+        file.pupils[0].upn = None;
+        file.pupils[0].contacts[0].relationship = "MUM".into();
+        let error = file.to_xml().unwrap_err();
+        let fields = error.fields.unwrap();
+        assert!(fields.contains_key("CTfile/CTFpupilData/Pupil[1]/UPN"));
+        assert!(
+            fields.contains_key("CTfile/CTFpupilData/Pupil[1]/Contacts/Contact[1]/Relationship")
+        );
+    }
+
+    #[rstest]
+    #[case(0, "R")]
+    #[case(-1, "N2")]
+    #[case(6, "6")]
+    fn test_nc_year(#[case] year: i32, #[case] code: &str) {
+        assert_eq!(nc_year(year), code);
+        assert_eq!(year_from_nc(code), Some(year));
+    }
+
+    #[rstest]
+    fn test_relationships_round_trip() {
+        let contact = |relationship: &str, gender: Option<&str>| CtfContact {
+            relationship: relationship.into(),
+            gender: gender.map(str::to_owned),
+            ..file().pupils[0].contacts[0].clone()
+        };
+        assert_eq!(contact("PAR", Some("M")).our_relationship(), "father");
+        assert_eq!(contact("PAR", None).our_relationship(), "other");
+        assert_eq!(contact("FOS", None).our_relationship(), "carer");
+        assert_eq!(language_code("pol"), Some("POL".into()));
+        assert_eq!(language_code("Polish"), None);
+    }
+}
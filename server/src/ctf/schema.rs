@@ -0,0 +1,479 @@
+use super::xml::Element;
+use crate::utils;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+pub const CTF_VERSION: &str = "23.0";
+pub const DOCUMENT_NAME: &str = "Common Transfer File";
+pub const ROOT: &str = "CTfile";
+
+type Check = fn(&str) -> bool;
+
+enum Content {
+    /// text, with what it should look like for the error message
+    Text(Check, &'static str),
+    Elements(&'static [Rule]),
+}
+
+struct Rule {
+    name: &'static str,
+    min: usize,
+    max: usize,
+    content: Content,
+}
+
+const UNBOUNDED: usize = usize::MAX;
+
+const fn text(name: &'static str, min: usize, check: Check, expected: &'static str) -> Rule {
+    Rule {
+        name,
+        min,
+        max: 1,
+        content: Content::Text(check, expected),
+    }
+}
+
+const fn elements(name: &'static str, min: usize, max: usize, rules: &'static [Rule]) -> Rule {
+    Rule {
+        name,
+        min,
+        max,
+        content: Content::Elements(rules),
+    }
+}
+
+const SCHOOL_CODE: [Rule; 2] = [
+    text("LEA", 1, is_lea, "a 3 digit local authority number"),
+    text("Estab", 1, is_estab, "a 4 digit establishment number"),
+];
+
+const HEADER: [Rule; 7] = [
+    text("DocumentName", 1, is_document_name, DOCUMENT_NAME),
+    text("CTFversion", 1, is_version, "a version like 23.0"),
+    text(
+        "DateTime",
+        1,
+        is_date_time,
+        "a date and time like 2023-06-14T09:30:00",
+    ),
+    text("DocumentQualifier", 1, is_qualifier, "full or partial"),
+    text("SupplierID", 0, is_short_text, "up to 35 characters"),
+    elements("SourceSchool", 1, 1, &SOURCE_SCHOOL),
+    elements("DestSchool", 1, 1, &SCHOOL_CODE),
+];
+
+const SOURCE_SCHOOL: [Rule; 4] = [
+    text("LEA", 1, is_lea, "a 3 digit local authority number"),
+    text("Estab", 1, is_estab, "a 4 digit establishment number"),
+    text("SchoolName", 0, is_long_text, "up to 100 characters"),
+    text("AcademicYear", 1, is_academic_year, "a 4 digit year"),
+];
+
+const PUPIL: [Rule; 11] = [
+    text("UPN", 1, is_upn, "a valid UPN"),
+    text("Surname", 1, is_name, "a name of up to 35 characters"),
+    text("Forename", 1, is_name, "a name of up to 35 characters"),
+    text("DOB", 1, is_date, "a date like 2016-09-05"),
+    text("Sex", 1, is_sex, "M, F, U or 9"),
+    elements("BasicDetails", 1, 1, &BASIC_DETAILS),
+    elements("FSMhistory", 0, 1, &FSM_HISTORY),
+    elements("Contacts", 0, 1, &CONTACTS),
+    elements("Attendance", 0, 1, &ATTENDANCE),
+    elements("StageAssessments", 0, 1, &STAGE_ASSESSMENTS),
+    elements("SchoolHistory", 0, 1, &SCHOOL_HISTORY),
+];
+
+const BASIC_DETAILS: [Rule; 5] = [
+    text(
+        "PreferredSurname",
+        0,
+        is_name,
+        "a name of up to 35 characters",
+    ),
+    text(
+        "PreferredForename",
+        0,
+        is_name,
+        "a name of up to 35 characters",
+    ),
+    text(
+        "NCyearActual",
+        1,
+        is_nc_year,
+        "N1, N2, R or a year from 1 to 14",
+    ),
+    elements("Languages", 0, 1, &LANGUAGES),
+    text("InCare", 0, is_boolean, "true or false"),
+];
+
+const LANGUAGES: [Rule; 1] = [elements("Type", 1, UNBOUNDED, &LANGUAGE_TYPE)];
+
+const LANGUAGE_TYPE: [Rule; 2] = [
+    text("LanguageType", 1, is_language_type, "F, H or T"),
+    text("Language", 1, is_language, "a 3 or 4 letter language code"),
+];
+
+const FSM_HISTORY: [Rule; 1] = [elements("FSMinstance", 1, UNBOUNDED, &FSM_INSTANCE)];
+
+const FSM_INSTANCE: [Rule; 2] = [
+    text("FSMstartDate", 1, is_date, "a date like 2016-09-05"),
+    text("FSMendDate", 0, is_date, "a date like 2016-09-05"),
+];
+
+const CONTACTS: [Rule; 1] = [elements("Contact", 1, UNBOUNDED, &CONTACT)];
+
+const CONTACT: [Rule; 8] = [
+    text("Order", 1, is_count, "a whole number"),
+    text("Surname", 1, is_name, "a name of up to 35 characters"),
+    text("Forename", 1, is_name, "a name of up to 35 characters"),
+    text("Gender", 0, is_sex, "M, F, U or 9"),
+    text(
+        "Relationship",
+        1,
+        is_relationship,
+        "a relationship code like PAR",
+    ),
+    text("Responsibility", 1, is_boolean, "true or false"),
+    elements("Phones", 0, 1, &PHONES),
+    text("Email", 0, is_email, "an email address"),
+];
+
+const PHONES: [Rule; 1] = [elements("Phone", 1, UNBOUNDED, &PHONE)];
+
+const PHONE: [Rule; 2] = [
+    text("TelephoneType", 1, is_telephone_type, "H, M, W or D"),
+    text("PhoneNo", 1, is_phone, "a phone number"),
+];
+
+const ATTENDANCE: [Rule; 1] = [elements("YearData", 1, UNBOUNDED, &YEAR_DATA)];
+
+const YEAR_DATA: [Rule; 4] = [
+    text("Year", 1, is_academic_year, "a 4 digit year"),
+    text("SessionsPossible", 1, is_count, "a whole number"),
+    text("SessionsAuthorised", 1, is_count, "a whole number"),
+    text("SessionsUnauthorised", 1, is_count, "a whole number"),
+];
+
+const STAGE_ASSESSMENTS: [Rule; 1] = [elements("KeyStage", 1, UNBOUNDED, &KEY_STAGE)];
+
+const KEY_STAGE: [Rule; 2] = [
+    text("Stage", 1, is_stage, "FP, KS2, KS3 or KS4"),
+    elements("StageAssessment", 1, UNBOUNDED, &STAGE_ASSESSMENT),
+];
+
+const STAGE_ASSESSMENT: [Rule; 6] = [
+    text("Year", 1, is_academic_year, "a 4 digit year"),
+    text("Subject", 1, is_short_text, "up to 35 characters"),
+    text("Method", 1, is_method, "TA or TT"),
+    text("Component", 0, is_short_text, "up to 35 characters"),
+    text("Result", 1, is_result, "up to 10 characters"),
+    text("ResultDate", 0, is_date, "a date like 2016-09-05"),
+];
+
+const SCHOOL_HISTORY: [Rule; 1] = [elements("School", 1, UNBOUNDED, &SCHOOL)];
+
+const SCHOOL: [Rule; 5] = [
+    text("LEA", 1, is_lea, "a 3 digit local authority number"),
+    text("Estab", 1, is_estab, "a 4 digit establishment number"),
+    text("SchoolName", 0, is_long_text, "up to 100 characters"),
+    text("EntryDate", 0, is_date, "a date like 2016-09-05"),
+    text("LeavingDate", 0, is_date, "a date like 2016-09-05"),
+];
+
+/// The part of the CTF schema we write: the header, then for each pupil their core details,
+/// contacts, attendance, assessments and school history. Elements have to come in the order
+/// they're listed, as the XSD defines them as sequences.
+const DOCUMENT: [Rule; 2] = [
+    elements("Header", 1, 1, &HEADER),
+    elements("CTFpupilData", 0, 1, &PUPIL_DATA),
+];
+
+const PUPIL_DATA: [Rule; 1] = [elements("Pupil", 1, UNBOUNDED, &PUPIL)];
+
+/// Where in the file something is wrong, like `CTfile/CTFpupilData/Pupil[1]/DOB`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SchemaError {
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+/// Checks a whole document against the schema, returning every problem rather than the first.
+pub fn validate(document: &Element) -> Vec<SchemaError> {
+    let mut errors = vec![];
+    if document.name != ROOT {
+        errors.push(SchemaError {
+            path: document.name.clone(),
+            message: format!("the root element must be {ROOT}"),
+        });
+        return errors;
+    }
+    check_children(document, &DOCUMENT, ROOT, &mut errors);
+    errors
+}
+
+fn check_children(element: &Element, rules: &[Rule], path: &str, errors: &mut Vec<SchemaError>) {
+    let children = &element.children;
+    let mut next = 0;
+    for rule in rules {
+        let mut count = 0;
+        while next < children.len() && children[next].name == rule.name {
+            count += 1;
+            let child_path = if rule.max > 1 {
+                format!("{path}/{}[{count}]", rule.name)
+            } else {
+                format!("{path}/{}", rule.name)
+            };
+            check_element(&children[next], rule, &child_path, errors);
+            next += 1;
+        }
+        if count < rule.min {
+            errors.push(SchemaError {
+                path: format!("{path}/{}", rule.name),
+                message: format!("{} is missing", rule.name),
+            });
+        }
+        if count > rule.max {
+            errors.push(SchemaError {
+                path: format!("{path}/{}", rule.name),
+                message: format!("{} can only appear {} times", rule.name, rule.max),
+            });
+        }
+    }
+    for child in &children[next..] {
+        errors.push(SchemaError {
+            path: format!("{path}/{}", child.name),
+            message: format!("{} isn't expected here", child.name),
+        });
+    }
+}
+
+fn check_element(element: &Element, rule: &Rule, path: &str, errors: &mut Vec<SchemaError>) {
+    match &rule.content {
+        Content::Text(check, expected) => {
+            if !element.children.is_empty() {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("{} should only hold text", rule.name),
+                });
+            } else if !check(element.text.trim()) {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("'{}' is not {expected}", element.text.trim()),
+                });
+            }
+        }
+        Content::Elements(rules) => {
+            if !element.text.trim().is_empty() {
+                errors.push(SchemaError {
+                    path: path.to_owned(),
+                    message: format!("{} should only hold elements", rule.name),
+                });
+            }
+            check_children(element, rules, path, errors);
+        }
+    }
+}
+
+fn digits(value: &str, count: usize) -> bool {
+    value.len() == count && value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// MMM is the code for a destination that isn't known, and XXX for leaving the school system
+fn is_lea(value: &str) -> bool {
+    digits(value, 3) || value == "MMM" || value == "XXX"
+}
+
+fn is_estab(value: &str) -> bool {
+    digits(value, 4) || value == "MMMM" || value == "XXXX"
+}
+
+fn is_document_name(value: &str) -> bool {
+    value == DOCUMENT_NAME
+}
+
+fn is_version(value: &str) -> bool {
+    value
+        .split_once('.')
+        .is_some_and(|(major, minor)| major.parse::<u32>().is_ok() && minor.parse::<u32>().is_ok())
+}
+
+fn is_date_time(value: &str) -> bool {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").is_ok()
+}
+
+fn is_date(value: &str) -> bool {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+fn is_qualifier(value: &str) -> bool {
+    matches!(value, "full" | "partial")
+}
+
+fn is_short_text(value: &str) -> bool {
+    !value.is_empty() && value.chars().count() <= 35
+}
+
+fn is_long_text(value: &str) -> bool {
+    !value.is_empty() && value.chars().count() <= 100
+}
+
+fn is_name(value: &str) -> bool {
+    is_short_text(value)
+}
+
+fn is_academic_year(value: &str) -> bool {
+    digits(value, 4)
+}
+
+fn is_upn(value: &str) -> bool {
+    utils::is_valid_upn(value)
+}
+
+fn is_sex(value: &str) -> bool {
+    matches!(value, "M" | "F" | "U" | "9")
+}
+
+fn is_nc_year(value: &str) -> bool {
+    matches!(value, "N1" | "N2" | "R")
+        || value
+            .parse::<u32>()
+            .is_ok_and(|year| (1..=14).contains(&year))
+}
+
+fn is_boolean(value: &str) -> bool {
+    matches!(value, "true" | "false" | "1" | "0")
+}
+
+fn is_language_type(value: &str) -> bool {
+    matches!(value, "F" | "H" | "T")
+}
+
+fn is_language(value: &str) -> bool {
+    (3..=4).contains(&value.len()) && value.chars().all(|c| c.is_ascii_uppercase())
+}
+
+fn is_count(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_relationship(value: &str) -> bool {
+    RELATIONSHIP_CODES.contains(&value)
+}
+
+fn is_telephone_type(value: &str) -> bool {
+    matches!(value, "H" | "M" | "W" | "D")
+}
+
+fn is_phone(value: &str) -> bool {
+    value.chars().filter(char::is_ascii_digit).count() >= 7
+        && value.chars().count() <= 20
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || " +()-".contains(c))
+}
+
+fn is_email(value: &str) -> bool {
+    value.contains('@') && !value.contains(char::is_whitespace) && value.chars().count() <= 100
+}
+
+fn is_stage(value: &str) -> bool {
+    matches!(value, "FP" | "KS2" | "KS3" | "KS4")
+}
+
+fn is_method(value: &str) -> bool {
+    matches!(value, "TA" | "TT")
+}
+
+fn is_result(value: &str) -> bool {
+    !value.is_empty() && value.chars().count() <= 10
+}
+
+/// The contact relationship codes from the common basic data set
+pub const RELATIONSHIP_CODES: [&str; 9] = [
+    "PAR", "STP", "CAR", "FOS", "REL", "FAM", "SWR", "DOC", "OTH",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn header() -> Element {
+        Element::new("Header")
+            .with(Element::text("DocumentName", DOCUMENT_NAME))
+            .with(Element::text("CTFversion", CTF_VERSION))
+            .with(Element::text("DateTime", "2023-06-14T09:30:00"))
+            .with(Element::text("DocumentQualifier", "full"))
+            .with(
+                Element::new("SourceSchool")
+                    .with(Element::text("LEA", "801"))
+                    .with(Element::text("Estab", "2000"))
+                    .with(Element::text("AcademicYear", "2022")),
+            )
+            .with(
+                Element::new("DestSchool")
+                    .with(Element::text("LEA", "MMM"))
+                    .with(Element::text("Estab", "MMMM")),
+            )
+    }
+
+    fn pupil() -> Element {
+        Element::new("Pupil")
+            .with(Element::text("UPN", "H801200001001"))
+            .with(Element::text("Surname", "Smith"))
+            .with(Element::text("Forename", "Ann"))
+            .with(Element::text("DOB", "2012-05-01"))
+            .with(Element::text("Sex", "F"))
+            .with(Element::new("BasicDetails").with(Element::text("NCyearActual", "6")))
+    }
+
+    #[rstest]
+    fn test_valid_document() {
+        let document = Element::new(ROOT)
+            .with(header())
+            .with(Element::new("CTFpupilData").with(pupil()).with(pupil()));
+        assert_eq!(validate(&document), vec![]);
+    }
+
+    #[rstest]
+    fn test_errors_point_at_the_element() {
+        let mut second = pupil();
+        second.children[3].text = "01/05/2012".into();
+        let third = pupil().with(Element::text("Nickname", "Annie"));
+        let document = Element::new(ROOT).with(header()).with(
+            Element::new("CTFpupilData")
+                .with(pupil())
+                .with(second)
+                .with(third),
+        );
+        let errors = validate(&document);
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "CTfile/CTFpupilData/Pupil[2]/DOB",
+                "CTfile/CTFpupilData/Pupil[3]/Nickname",
+            ]
+        );
+        assert!(errors[0].message.contains("01/05/2012"));
+    }
+
+    #[rstest]
+    fn test_elements_out_of_order() {
+        let mut swapped = pupil();
+        swapped.children.swap(1, 2);
+        let document = Element::new(ROOT)
+            .with(header())
+            .with(Element::new("CTFpupilData").with(swapped));
+        let errors = validate(&document);
+        assert_eq!(errors[0].path, "CTfile/CTFpupilData/Pupil[1]/Surname");
+        assert_eq!(errors[0].message, "Surname is missing");
+    }
+
+    #[rstest]
+    fn test_missing_header() {
+        let errors = validate(&Element::new(ROOT));
+        assert_eq!(errors[0].path, "CTfile/Header");
+        assert!(!validate(&Element::new("Pupil")).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::core::error::Result;
+use quick_xml::{
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use std::collections::BTreeMap;
+
+/// Just enough of an XML document for a CTF: elements hold either other elements or text, and
+/// attributes and namespaces are left out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Element {
+    pub(crate) name: String,
+    pub(crate) text: String,
+    pub(crate) children: Vec<Element>,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn text(name: &str, text: impl Into<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            text: text.into(),
+            children: vec![],
+        }
+    }
+
+    pub fn with(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Adds the child only when there's something to put in it, for optional elements.
+    pub fn with_some(mut self, name: &str, text: Option<impl Into<String>>) -> Self {
+        if let Some(text) = text {
+            self.children.push(Element::text(name, text));
+        }
+        self
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The trimmed text of a child, or none when it's missing or empty
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .map(|child| child.text.trim().to_owned())
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Follows a path of child names, like `["BasicDetails", "NCyearActual"]`.
+    pub fn find(&self, path: &[&str]) -> Option<&Element> {
+        path.iter()
+            .try_fold(self, |element, name| element.child(name))
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader::from_reader(data);
+        reader.trim_text(true);
+        let mut buf = vec![];
+        let mut open: Vec<Element> = vec![];
+        let mut root: Option<Element> = None;
+        loop {
+            let event = reader
+                .read_event_into(&mut buf)
+                .map_err(|error| file_error(format!("not valid XML: {error}")))?;
+            match event {
+                Event::Start(start) => open.push(Element::new(&name_of(&start))),
+                Event::Empty(start) => {
+                    let element = Element::new(&name_of(&start));
+                    close(element, &mut open, &mut root)?;
+                }
+                Event::Text(text) => {
+                    let text = text
+                        .unescape()
+                        .map_err(|error| file_error(format!("not valid XML: {error}")))?;
+                    if let Some(element) = open.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(element) = open.last_mut() {
+                        element
+                            .text
+                            .push_str(&String::from_utf8_lossy(&data.into_inner()));
+                    }
+                }
+                Event::End(_) => {
+                    let element = open
+                        .pop()
+                        .ok_or_else(|| file_error("not valid XML: unexpected end tag".into()))?;
+                    close(element, &mut open, &mut root)?;
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        match (root, open.is_empty()) {
+            (Some(root), true) => Ok(root),
+            _ => Err(file_error(
+                "not valid XML: the document isn't complete".into(),
+            )),
+        }
+    }
+
+    pub fn to_xml(&self) -> Result<Vec<u8>> {
+        let mut writer = Writer::new_with_indent(vec![], b' ', 2);
+        writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+            .and_then(|_| self.write(&mut writer))
+            .map_err(|error| ServerError!(format!("failed to write XML: {error}")))?;
+        Ok(writer.into_inner())
+    }
+
+    fn write(&self, writer: &mut Writer<Vec<u8>>) -> quick_xml::Result<()> {
+        writer.write_event(Event::Start(BytesStart::new(self.name.as_str())))?;
+        if self.children.is_empty() {
+            writer.write_event(Event::Text(BytesText::new(&self.text)))?;
+        }
+        for child in &self.children {
+            child.write(writer)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new(self.name.as_str())))
+    }
+}
+
+fn name_of(start: &BytesStart) -> String {
+    String::from_utf8_lossy(start.local_name().as_ref()).into_owned()
+}
+
+/// Hands a finished element to its parent, or makes it the root when it has none.
+fn close(element: Element, open: &mut [Element], root: &mut Option<Element>) -> Result<()> {
+    match open.last_mut() {
+        Some(parent) => parent.children.push(element),
+        None if root.is_none() => *root = Some(element),
+        None => {
+            return Err(file_error(
+                "not valid XML: more than one root element".into(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn file_error(message: String) -> crate::core::error::Error {
+    ValidationError!("CTF failed validation")
+        .with_fields(BTreeMap::from([("data".into(), message)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_round_trip() {
+        let element = Element::new("Pupil")
+            .with(Element::text("Surname", "O'Brien & Sons"))
+            .with(Element::new("BasicDetails").with(Element::text("NCyearActual", "6")))
+            .with_some("UPN", None::<String>);
+        let xml = element.to_xml().unwrap();
+        let parsed = Element::parse(&xml).unwrap();
+        assert_eq!(parsed, element);
+        assert_eq!(
+            parsed.find(&["BasicDetails", "NCyearActual"]).unwrap().text,
+            "6"
+        );
+        assert_eq!(parsed.value("Surname").as_deref(), Some("O'Brien & Sons"));
+    }
+
+    #[rstest]
+    #[case("<CTfile><Header></CTfile>")]
+    #[case("<CTfile></CTfile><CTfile></CTfile>")]
+    #[case("not xml at all")]
+    fn test_parse_rejects_broken_documents(#[case] xml: &str) {
+        assert!(Element::parse(xml.as_bytes()).is_err());
+    }
+}
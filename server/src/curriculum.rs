@@ -0,0 +1,3 @@
+pub mod framework;
+pub mod handlers;
+pub mod progression;
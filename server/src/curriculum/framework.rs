@@ -0,0 +1,260 @@
+use crate::{
+    core::{constant, error::Result},
+    user::model::User,
+};
+use entity::{curriculum_area, progression_step, what_matters};
+use migration::OnConflict;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+const ADMIN_ONLY: &str = "only an admin can change the curriculum framework";
+
+/// An Area of Learning and Experience, with its statements of what matters when read back.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Area {
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) position: i32,
+    #[serde(default, skip_deserializing)]
+    pub(crate) what_matters: Vec<WhatMatters>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct WhatMatters {
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub(crate) id: Uuid,
+    pub(crate) area_id: Uuid,
+    pub(crate) statement: String,
+    pub(crate) position: i32,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Step {
+    pub(crate) step: i32,
+    pub(crate) name: String,
+    pub(crate) typical_age: i32,
+}
+
+/// The whole framework judgements are made against, in the order it should be shown.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Framework {
+    pub(crate) areas: Vec<Area>,
+    pub(crate) steps: Vec<Step>,
+}
+
+impl Framework {
+    pub async fn from_db(db: &DatabaseConnection) -> Result<Self> {
+        let mut statements: BTreeMap<Uuid, Vec<WhatMatters>> = BTreeMap::new();
+        for statement in WhatMatters::all_from_db(db).await? {
+            statements
+                .entry(statement.area_id)
+                .or_default()
+                .push(statement);
+        }
+        let areas = curriculum_area::Entity::find()
+            .order_by_asc(curriculum_area::Column::Position)
+            .order_by_asc(curriculum_area::Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|area| Area {
+                what_matters: statements.remove(&area.id).unwrap_or_default(),
+                ..area.into()
+            })
+            .collect();
+        Ok(Self {
+            areas,
+            steps: Step::all_from_db(db).await?,
+        })
+    }
+
+    /// Statements in the order they appear in the framework, area by area.
+    pub fn what_matters(&self) -> Vec<WhatMatters> {
+        self.areas
+            .iter()
+            .flat_map(|area| area.what_matters.iter().cloned())
+            .collect()
+    }
+}
+
+impl Area {
+    /// Add an area, or rename or reorder an existing one. Only admins can change the framework.
+    pub async fn save(&self, user: &User, db: &DatabaseConnection) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        curriculum_area::Entity::insert(curriculum_area::ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name.clone()),
+            position: Set(self.position),
+        })
+        .on_conflict(
+            OnConflict::column(curriculum_area::Column::Id)
+                .update_columns([
+                    curriculum_area::Column::Name,
+                    curriculum_area::Column::Position,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(self.clone())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "name cannot be empty".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("area failed validation").with_fields(errors))
+        }
+    }
+}
+
+impl WhatMatters {
+    /// Every statement, ordered within its area.
+    pub async fn all_from_db(db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(what_matters::Entity::find()
+            .order_by_asc(what_matters::Column::Position)
+            .order_by_asc(what_matters::Column::Statement)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Add a statement, or reword or move an existing one. Only admins can change the framework.
+    pub async fn save(&self, user: &User, db: &DatabaseConnection) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        if curriculum_area::Entity::find_by_id(self.area_id)
+            .one(db)
+            .await?
+            .is_none()
+        {
+            return Err(ValidationError!("statement failed validation").with_fields(
+                BTreeMap::from([("area_id".into(), "area does not exist".into())]),
+            ));
+        }
+        what_matters::Entity::insert(what_matters::ActiveModel {
+            id: Set(self.id),
+            area_id: Set(self.area_id),
+            statement: Set(self.statement.clone()),
+            position: Set(self.position),
+        })
+        .on_conflict(
+            OnConflict::column(what_matters::Column::Id)
+                .update_columns([
+                    what_matters::Column::AreaId,
+                    what_matters::Column::Statement,
+                    what_matters::Column::Position,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(self.clone())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.statement.trim().is_empty() {
+            errors.insert("statement".into(), "statement cannot be empty".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("statement failed validation").with_fields(errors))
+        }
+    }
+}
+
+impl Step {
+    pub async fn all_from_db(db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(progression_step::Entity::find()
+            .order_by_asc(progression_step::Column::Step)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Add a step, or rename an existing one. Only admins can change the framework.
+    pub async fn save(&self, user: &User, db: &DatabaseConnection) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        progression_step::Entity::insert(progression_step::ActiveModel {
+            step: Set(self.step),
+            name: Set(self.name.clone()),
+            typical_age: Set(self.typical_age),
+        })
+        .on_conflict(
+            OnConflict::column(progression_step::Column::Step)
+                .update_columns([
+                    progression_step::Column::Name,
+                    progression_step::Column::TypicalAge,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(self.clone())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.step < 1 {
+            errors.insert("step".into(), "step must be at least 1".into());
+        }
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "name cannot be empty".into());
+        }
+        if !(0..=25).contains(&self.typical_age) {
+            errors.insert(
+                "typical_age".into(),
+                "typical age must be between 0 and 25".into(),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("step failed validation").with_fields(errors))
+        }
+    }
+}
+
+impl From<curriculum_area::Model> for Area {
+    fn from(value: curriculum_area::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            position: value.position,
+            what_matters: vec![],
+        }
+    }
+}
+
+impl From<what_matters::Model> for WhatMatters {
+    fn from(value: what_matters::Model) -> Self {
+        Self {
+            id: value.id,
+            area_id: value.area_id,
+            statement: value.statement,
+            position: value.position,
+        }
+    }
+}
+
+impl From<progression_step::Model> for Step {
+    fn from(value: progression_step::Model) -> Self {
+        Self {
+            step: value.step,
+            name: value.name,
+            typical_age: value.typical_age,
+        }
+    }
+}
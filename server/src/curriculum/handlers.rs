@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    curriculum::{framework::*, progression::*},
+    pupil::model::Pupil,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_framework(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested curriculum framework");
+    match Framework::from_db(state.database().as_ref()).await {
+        Ok(framework) => Ok(Json(json!(framework))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_area(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(area): Json<Area>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving curriculum area {}", area.id);
+    area.validate()?;
+    match area.save(&user, state.database().as_ref()).await {
+        Ok(area) => Ok(Json(json!(area))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_what_matters(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(statement): Json<WhatMatters>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving statement of what matters {}", statement.id);
+    statement.validate()?;
+    match statement.save(&user, state.database().as_ref()).await {
+        Ok(statement) => Ok(Json(json!(statement))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised | ErrorKind::ValidationError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_step(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(step): Json<Step>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving progression step {}", step.step);
+    step.validate()?;
+    match step.save(&user, state.database().as_ref()).await {
+        Ok(step) => Ok(Json(json!(step))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_overview(
+    State(state): State<AppState>,
+    Path(year): Path<i32>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested progression overview for year {year}");
+    match Overview::from_db(&user, year, state.database().as_ref()).await {
+        Ok(overview) => Ok(Json(json!(overview))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_progression(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested progression for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Progression::for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(progression) => Ok(Json(json!(progression))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_judgement(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewJudgement>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("recording a judgement for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    // checks the pupil exists and is in one of the user's years
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let judgement = Judgement::new(pupil_id, &user, new);
+    let framework = Framework::from_db(state.database().as_ref()).await?;
+    judgement.validate(&framework, Utc::now().date_naive())?;
+    match judgement.insert(state.database().as_ref()).await {
+        Ok(judgement) => Ok((StatusCode::CREATED, Json(json!(judgement)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_judgement(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting judgement {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let judgement = Judgement::one_from_db(&user, pupil_id, id, state.database()).await?;
+    judgement.check_judge(&user)?;
+    match judgement.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    core::error::Result,
+    curriculum::framework::{Framework, WhatMatters},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::NaiveDate;
+use entity::progression_judgement::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A teacher's judgement, on a date, of the progression step a pupil has reached for one
+/// statement of what matters.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Judgement {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) what_matters_id: Uuid,
+    pub(crate) step: i32,
+    pub(crate) date: NaiveDate,
+    pub(crate) judged_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) notes: Option<String>,
+}
+
+impl Judgement {
+    pub fn new(pupil_id: Uuid, judge: &User, new: NewJudgement) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            what_matters_id: new.what_matters_id,
+            step: new.step,
+            date: new.date,
+            judged_by: judge.email_address.clone(),
+            notes: new.notes.filter(|notes| !notes.trim().is_empty()),
+        }
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .one(db)
+            .await?
+        {
+            Some(judgement) => Ok(judgement.into()),
+            None => Err(JudgementDoesNotExist!()),
+        }
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting judgement {:?}", self);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            what_matters_id: Set(self.what_matters_id),
+            step: Set(self.step),
+            date: Set(self.date),
+            judged_by: Set(self.judged_by.clone()),
+            notes: Set(self.notes.clone()),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Only the person who made a judgement may withdraw it.
+    pub fn check_judge(&self, user: &User) -> Result<()> {
+        if self.judged_by == user.email_address {
+            Ok(())
+        } else {
+            Err(Unauthorised!(
+                "only the person who made a judgement can remove it"
+            ))
+        }
+    }
+
+    pub fn validate(&self, framework: &Framework, today: NaiveDate) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if !framework
+            .areas
+            .iter()
+            .flat_map(|area| &area.what_matters)
+            .any(|statement| statement.id == self.what_matters_id)
+        {
+            errors.insert(
+                "what_matters_id".into(),
+                "statement of what matters does not exist".into(),
+            );
+        }
+        if !framework.steps.iter().any(|step| step.step == self.step) {
+            errors.insert(
+                "step".into(),
+                format!("{} is not a progression step", self.step),
+            );
+        }
+        if self.date > today {
+            errors.insert("date".into(), "date cannot be in the future".into());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("judgement failed validation").with_fields(errors))
+        }
+    }
+}
+
+/// The most recent judgement for each statement, keyed by statement. Judgements made on the same
+/// day are settled in favour of the higher step.
+pub fn latest_per_statement<I>(judgements: I) -> BTreeMap<Uuid, Judgement>
+where
+    I: IntoIterator<Item = Judgement>,
+{
+    let mut latest: BTreeMap<Uuid, Judgement> = BTreeMap::new();
+    for judgement in judgements {
+        match latest.get(&judgement.what_matters_id) {
+            Some(current) if (current.date, current.step) >= (judgement.date, judgement.step) => {}
+            _ => {
+                latest.insert(judgement.what_matters_id, judgement);
+            }
+        }
+    }
+    latest
+}
+
+/// Where a pupil is now against each statement, and every judgement that got them there.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Progression {
+    pub(crate) current: BTreeMap<Uuid, Judgement>,
+    /// newest first
+    pub(crate) history: Vec<Judgement>,
+}
+
+impl Progression {
+    pub async fn for_pupil(user: &User, pupil_id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let history: Vec<Judgement> = Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_desc(Column::Date)
+            .order_by_desc(Column::Step)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Self {
+            current: latest_per_statement(history.clone()),
+            history,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct OverviewRow {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    /// the current step for each statement the pupil has been judged against
+    pub(crate) steps: BTreeMap<Uuid, i32>,
+}
+
+/// A grid of current steps for every active pupil in a year, one column per statement.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Overview {
+    pub(crate) year: i32,
+    pub(crate) what_matters: Vec<WhatMatters>,
+    pub(crate) rows: Vec<OverviewRow>,
+}
+
+impl Overview {
+    pub async fn from_db(user: &User, year: i32, db: &DatabaseConnection) -> Result<Self> {
+        use entity::pupil::Column as Pupil;
+        user.check_year(year)?;
+        let pupils = entity::pupil::Entity::find()
+            .filter(Pupil::Year.eq(year))
+            .filter(Pupil::Active.eq(true))
+            .order_by_asc(Pupil::LastName)
+            .order_by_asc(Pupil::FirstNames)
+            .all(db)
+            .await?;
+        let mut by_pupil: BTreeMap<Uuid, Vec<Judgement>> = BTreeMap::new();
+        for judgement in Entity::find()
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .all(db)
+            .await?
+        {
+            by_pupil
+                .entry(judgement.pupil_id)
+                .or_default()
+                .push(judgement.into());
+        }
+        let rows = pupils
+            .into_iter()
+            .map(|pupil| OverviewRow {
+                steps: latest_per_statement(by_pupil.remove(&pupil.id).unwrap_or_default())
+                    .into_iter()
+                    .map(|(statement, judgement)| (statement, judgement.step))
+                    .collect(),
+                pupil_id: pupil.id,
+                first_names: pupil.first_names,
+                last_name: pupil.last_name,
+            })
+            .collect();
+        Ok(Self {
+            year,
+            what_matters: Framework::from_db(db).await?.what_matters(),
+            rows,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewJudgement {
+    what_matters_id: Uuid,
+    step: i32,
+    date: NaiveDate,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+impl From<Model> for Judgement {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            what_matters_id: value.what_matters_id,
+            step: value.step,
+            date: value.date,
+            judged_by: value.judged_by,
+            notes: value.notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curriculum::framework::{Area, Step};
+    use rstest::*;
+
+    fn judgement(what_matters_id: Uuid, step: i32, date: &str) -> Judgement {
+        Judgement {
+            id: Uuid::new_v4(),
+            pupil_id: Uuid::nil(),
+            what_matters_id,
+            step,
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            judged_by: "test@test.com".into(),
+            notes: None,
+        }
+    }
+
+    #[fixture]
+    fn framework() -> Framework {
+        let area_id = Uuid::new_v4();
+        Framework {
+            areas: vec![Area {
+                id: area_id,
+                name: "Humanities".into(),
+                position: 0,
+                what_matters: vec![WhatMatters {
+                    id: Uuid::nil(),
+                    area_id,
+                    statement: "Enquiry inspires curiosity".into(),
+                    position: 0,
+                }],
+            }],
+            steps: vec![
+                Step {
+                    step: 1,
+                    name: "Progression step 1".into(),
+                    typical_age: 5,
+                },
+                Step {
+                    step: 2,
+                    name: "Progression step 2".into(),
+                    typical_age: 8,
+                },
+            ],
+        }
+    }
+
+    #[rstest]
+    fn latest_judgement_wins_whatever_the_order() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let latest = latest_per_statement([
+            judgement(a, 1, "2022-09-10"),
+            judgement(a, 3, "2023-03-01"),
+            judgement(a, 2, "2022-12-01"),
+            judgement(b, 2, "2022-10-01"),
+        ]);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[&a].step, 3);
+        assert_eq!(latest[&b].step, 2);
+    }
+
+    #[rstest]
+    fn same_day_judgements_favour_the_higher_step() {
+        let a = Uuid::new_v4();
+        let latest =
+            latest_per_statement([judgement(a, 2, "2023-03-01"), judgement(a, 1, "2023-03-01")]);
+        assert_eq!(latest[&a].step, 2);
+    }
+
+    #[rstest]
+    #[case(Uuid::nil(), 2, "2023-03-01", vec![])]
+    #[case(Uuid::new_v4(), 2, "2023-03-01", vec!["what_matters_id"])]
+    #[case(Uuid::nil(), 6, "2023-03-01", vec!["step"])]
+    #[case(Uuid::nil(), 1, "2023-04-02", vec!["date"])]
+    fn validates_against_the_framework(
+        framework: Framework,
+        #[case] what_matters_id: Uuid,
+        #[case] step: i32,
+        #[case] date: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let today = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap();
+        let result = judgement(what_matters_id, step, date).validate(&framework, today);
+        match result {
+            Ok(()) => assert!(expected.is_empty()),
+            Err(error) => {
+                let fields = error.fields.unwrap();
+                assert_eq!(
+                    fields.keys().map(String::as_str).collect::<Vec<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState, core::error::*, eal::model::*, pupil::model::Pupil, user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_pupil_eal_assessments(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested EAL assessments for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match EalAssessment::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(assessments) => Ok(Json(json!(assessments))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_eal_assessment(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewEalAssessment>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("recording an EAL assessment for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let assessment = EalAssessment::new(pupil_id, &user, new);
+    assessment.validate(Utc::now().date_naive())?;
+    match assessment.insert(state.database().as_ref()).await {
+        Ok(assessment) => Ok((StatusCode::CREATED, Json(json!(assessment)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_eal_assessment(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting EAL assessment {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let assessment = EalAssessment::one_from_db(&user, pupil_id, id, state.database()).await?;
+    assessment.check_assessor(&user)?;
+    match assessment.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    core::error::Result,
+    flag::model::{Flag, FlagPeriod, EAL_SOURCE},
+    pupil::model::Pupil,
+    user::model::User,
+};
+use chrono::NaiveDate;
+use entity::eal_assessment::{ActiveModel, Column, Entity, Model};
+use migration::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Where a pupil is on the A to E scale of English acquisition, A being new to English.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Stage {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::A => "A",
+            Stage::B => "B",
+            Stage::C => "C",
+            Stage::D => "D",
+            Stage::E => "E",
+        }
+    }
+
+    /// Anything unrecognised is treated as new to English so the pupil gets the most support.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "B" => Stage::B,
+            "C" => Stage::C,
+            "D" => Stage::D,
+            "E" => Stage::E,
+            _ => Stage::A,
+        }
+    }
+}
+
+/// A dated judgement of a pupil's proficiency in English. The latest one gives their current stage,
+/// and recording one marks the pupil as EAL and brings their home language up to date.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct EalAssessment {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) stage: Stage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) home_language: Option<String>,
+    pub(crate) assessed_on: NaiveDate,
+    pub(crate) assessed_by: String,
+    pub(crate) notes: String,
+}
+
+impl EalAssessment {
+    pub fn new(pupil_id: Uuid, user: &User, new: NewEalAssessment) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            stage: new.stage,
+            home_language: new
+                .home_language
+                .map(|language| language.trim().to_owned())
+                .filter(|language| !language.is_empty()),
+            assessed_on: new.assessed_on,
+            assessed_by: user.email_address.clone(),
+            notes: new.notes,
+        }
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .one(db)
+            .await?
+        {
+            Some(assessment) => Ok(assessment.into()),
+            None => Err(EalAssessmentDoesNotExist!()),
+        }
+    }
+
+    /// All of a pupil's assessments, the latest first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_desc(Column::AssessedOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Save the assessment, and mark the pupil as EAL with the home language of their latest one.
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting EAL assessment {:?}", self);
+        let txn = db.begin().await?;
+        let inserted: Self = ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            stage: Set(self.stage.as_str().to_owned()),
+            home_language: Set(self.home_language.clone()),
+            assessed_on: Set(self.assessed_on),
+            assessed_by: Set(self.assessed_by.clone()),
+            notes: Set(self.notes.clone()),
+        }
+        .insert(&txn)
+        .await?
+        .into();
+        entity::pupil::Entity::update_many()
+            .col_expr(
+                entity::pupil::Column::EnglishAsAdditionalLanguage,
+                Expr::value(true),
+            )
+            .filter(entity::pupil::Column::Id.eq(self.pupil_id))
+            .exec(&txn)
+            .await?;
+        refresh_home_language(&txn, self.pupil_id, None).await?;
+        FlagPeriod::set_from(
+            &txn,
+            self.pupil_id,
+            Flag::Eal,
+            true,
+            self.assessed_on,
+            EAL_SOURCE,
+            None,
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Remove an assessment made in error. A pupil left with none is no longer marked as EAL, and the
+    /// EAL periods the assessments opened go too. The home language goes back to the latest one left.
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        let txn = db.begin().await?;
+        Entity::delete_by_id(self.id).exec(&txn).await?;
+        let remaining = Entity::find()
+            .filter(Column::PupilId.eq(self.pupil_id))
+            .count(&txn)
+            .await?;
+        entity::pupil::Entity::update_many()
+            .col_expr(
+                entity::pupil::Column::EnglishAsAdditionalLanguage,
+                Expr::value(remaining > 0),
+            )
+            .filter(entity::pupil::Column::Id.eq(self.pupil_id))
+            .exec(&txn)
+            .await?;
+        if remaining == 0 {
+            FlagPeriod::clear_source(&txn, self.pupil_id, Flag::Eal, EAL_SOURCE).await?;
+        }
+        refresh_home_language(&txn, self.pupil_id, self.home_language.as_deref()).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Only whoever made an assessment may remove it.
+    pub fn check_assessor(&self, user: &User) -> Result<()> {
+        if self.assessed_by == user.email_address {
+            Ok(())
+        } else {
+            Err(Unauthorised!(
+                "only the person who made an assessment can remove it"
+            ))
+        }
+    }
+
+    pub fn validate(&self, today: NaiveDate) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.assessed_on > today {
+            errors.insert(
+                "assessed_on".into(),
+                "assessment date cannot be in the future".into(),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("EAL assessment failed validation").with_fields(errors))
+        }
+    }
+
+    /// Fill in the derived current stage on pupils about to be sent to the client.
+    pub async fn flag_current_stage(pupils: &mut [Pupil], db: &DatabaseConnection) -> Result<()> {
+        let latest: HashMap<Uuid, Stage> = Entity::find()
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .order_by_asc(Column::AssessedOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|assessment| (assessment.pupil_id, Stage::from_db(&assessment.stage)))
+            .collect();
+        for pupil in pupils {
+            pupil.eal_stage = latest.get(&pupil.id).copied();
+        }
+        Ok(())
+    }
+}
+
+/// Set the pupil's home language from their latest assessment that gave one. If none is left, a
+/// language that only came from the removed assessment is cleared, while one that came from
+/// elsewhere, such as an import, is kept.
+async fn refresh_home_language<C>(db: &C, pupil_id: Uuid, removed: Option<&str>) -> Result<()>
+where
+    C: ConnectionTrait,
+{
+    let latest = Entity::find()
+        .filter(Column::PupilId.eq(pupil_id))
+        .filter(Column::HomeLanguage.is_not_null())
+        .order_by_desc(Column::AssessedOn)
+        .one(db)
+        .await?
+        .and_then(|assessment| assessment.home_language);
+    let update =
+        entity::pupil::Entity::update_many().filter(entity::pupil::Column::Id.eq(pupil_id));
+    match (latest, removed) {
+        (Some(language), _) => {
+            update
+                .col_expr(entity::pupil::Column::HomeLanguage, Expr::value(language))
+                .exec(db)
+                .await?;
+        }
+        (None, Some(removed)) => {
+            update
+                .col_expr(
+                    entity::pupil::Column::HomeLanguage,
+                    Expr::value(Option::<String>::None),
+                )
+                .filter(entity::pupil::Column::HomeLanguage.eq(removed))
+                .exec(db)
+                .await?;
+        }
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewEalAssessment {
+    stage: Stage,
+    #[serde(default)]
+    home_language: Option<String>,
+    assessed_on: NaiveDate,
+    #[serde(default)]
+    notes: String,
+}
+
+impl From<Model> for EalAssessment {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            stage: Stage::from_db(&value.stage),
+            home_language: value.home_language,
+            assessed_on: value.assessed_on,
+            assessed_by: value.assessed_by,
+            notes: value.notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("A", Stage::A)]
+    #[case("C", Stage::C)]
+    #[case("E", Stage::E)]
+    #[case("fluent", Stage::A)]
+    fn stage_from_db(#[case] value: &str, #[case] expected: Stage) {
+        assert_eq!(Stage::from_db(value), expected);
+    }
+
+    #[rstest]
+    #[case(r#""D""#, Stage::D)]
+    #[case(r#""B""#, Stage::B)]
+    fn stage_is_sent_as_its_letter(#[case] json: &str, #[case] stage: Stage) {
+        assert_eq!(serde_json::from_str::<Stage>(json).unwrap(), stage);
+        assert_eq!(serde_json::to_string(&stage).unwrap(), json);
+    }
+
+    #[rstest]
+    fn blank_home_language_is_left_out() {
+        let user = User::new("test", "user", "teacher@school.com", "pass", vec![3]);
+        let new: NewEalAssessment = serde_json::from_str(
+            r#"{"stage": "B", "home_language": "  ", "assessed_on": "2023-05-02"}"#,
+        )
+        .unwrap();
+        let assessment = EalAssessment::new(Uuid::new_v4(), &user, new);
+        assert_eq!(assessment.home_language, None);
+        assert_eq!(assessment.assessed_by, "teacher@school.com");
+        assert!(assessment.validate("2023-05-01".parse().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState, core::error::*, flag::model::*, pupil::model::Pupil, user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_pupil_flag_periods(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested flag periods for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match FlagPeriod::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(periods) => Ok(Json(json!(periods))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_flag_period(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewFlagPeriod>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding a flag period for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let period = FlagPeriod::new(pupil_id, new, &user);
+    period.validate(state.database()).await?;
+    match period.insert(state.database().as_ref()).await {
+        Ok(period) => Ok((StatusCode::CREATED, Json(json!(period)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_flag_period(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(update): Json<FlagPeriodUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating flag period {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let mut period = FlagPeriod::one_from_db(&user, pupil_id, id, state.database()).await?;
+    period.set_from_update(update, &user)?;
+    period.validate(state.database()).await?;
+    match period.update(state.database().as_ref()).await {
+        Ok(period) => Ok(Json(json!(period))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_flag_period(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting flag period {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let period = FlagPeriod::one_from_db(&user, pupil_id, id, state.database()).await?;
+    match period.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,566 @@
+use crate::{core::error::Result, pupil::model::Pupil, user::model::User, utils::patch::Patch};
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use entity::flag_period::{ActiveModel, Column, Entity, Model};
+use migration::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Sources given to periods opened and closed elsewhere in the app.
+pub const PUPIL_SOURCE: &str = "pupil record";
+pub const EAL_SOURCE: &str = "EAL assessment";
+pub const ALN_SOURCE: &str = "ALN register";
+
+/// The pupil flags that have a dated history. EAL and ALN periods are opened and closed by EAL
+/// assessments and the ALN register, the rest by whoever edits the pupil, and any of them can be
+/// corrected by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    Fsm,
+    Lac,
+    Eal,
+    Mat,
+    Aln,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 5] = [Flag::Fsm, Flag::Lac, Flag::Eal, Flag::Mat, Flag::Aln];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Flag::Fsm => "fsm",
+            Flag::Lac => "lac",
+            Flag::Eal => "eal",
+            Flag::Mat => "mat",
+            Flag::Aln => "aln",
+        }
+    }
+
+    /// Rows with a flag this version doesn't know are skipped rather than guessed at.
+    fn from_db(value: &str) -> Option<Self> {
+        Flag::ALL.into_iter().find(|flag| flag.as_str() == value)
+    }
+
+    /// The pupil's boolean for this flag
+    pub(crate) fn on(&self, pupil: &Pupil) -> bool {
+        match self {
+            Flag::Fsm => pupil.free_school_meals,
+            Flag::Lac => pupil.looked_after_child,
+            Flag::Eal => pupil.english_as_additional_language,
+            Flag::Mat => pupil.more_able_and_talented,
+            Flag::Aln => pupil.additional_learning_needs,
+        }
+    }
+
+    fn on_mut<'a>(&self, pupil: &'a mut Pupil) -> &'a mut bool {
+        match self {
+            Flag::Fsm => &mut pupil.free_school_meals,
+            Flag::Lac => &mut pupil.looked_after_child,
+            Flag::Eal => &mut pupil.english_as_additional_language,
+            Flag::Mat => &mut pupil.more_able_and_talented,
+            Flag::Aln => &mut pupil.additional_learning_needs,
+        }
+    }
+
+    fn column(&self) -> entity::pupil::Column {
+        match self {
+            Flag::Fsm => entity::pupil::Column::FreeSchoolMeals,
+            Flag::Lac => entity::pupil::Column::LookedAfterChild,
+            Flag::Eal => entity::pupil::Column::EnglishAsAdditionalLanguage,
+            Flag::Mat => entity::pupil::Column::MoreAbleAndTalented,
+            Flag::Aln => entity::pupil::Column::AdditionalLearningNeeds,
+        }
+    }
+}
+
+/// A stretch of time a flag applied to a pupil, from the start date to the end date inclusive. An
+/// open period with no end date still applies.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct FlagPeriod {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) flag: Flag,
+    pub(crate) start_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) end_date: Option<NaiveDate>,
+    pub(crate) source: String,
+    pub(crate) notes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) updated_by: Option<String>,
+    pub(crate) updated_at: NaiveDateTime,
+}
+
+impl FlagPeriod {
+    pub fn new(pupil_id: Uuid, new: NewFlagPeriod, user: &User) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            flag: new.flag,
+            start_date: new.start_date,
+            end_date: new.end_date,
+            source: new.source.trim().to_owned(),
+            notes: new.notes,
+            updated_by: Some(user.email_address.clone()),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && self.end_date.is_none_or(|end| date <= end)
+    }
+
+    /// Every period for a pupil the user can see, latest first within each flag.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Self::find(pupil_id, db).await
+    }
+
+    async fn find<C: ConnectionTrait>(pupil_id: Uuid, db: &C) -> Result<Vec<Self>> {
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_asc(Column::Flag)
+            .order_by_desc(Column::StartDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|period| period.try_into().ok())
+            .collect())
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .one(db)
+            .await?
+            .and_then(|period| Self::try_from(period).ok())
+        {
+            Some(period) => Ok(period),
+            None => Err(FlagPeriodDoesNotExist!()),
+        }
+    }
+
+    /// Save a period entered by hand, keeping the pupil's stored flag in step.
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting flag period {:?}", self);
+        let txn = db.begin().await?;
+        let inserted: Model = ActiveModel::from(Model::from(self.clone()))
+            .insert(&txn)
+            .await?;
+        store_current(&txn, self.pupil_id, self.flag).await?;
+        txn.commit().await?;
+        Self::try_from(inserted).map_err(|_| UnknownError!())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("updating flag period {:?}", self);
+        let txn = db.begin().await?;
+        let updated = ActiveModel::from(Model::from(self.clone()))
+            .reset_all()
+            .update(&txn)
+            .await?;
+        store_current(&txn, self.pupil_id, self.flag).await?;
+        txn.commit().await?;
+        Self::try_from(updated).map_err(|_| UnknownError!())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        let txn = db.begin().await?;
+        Entity::delete_by_id(self.id).exec(&txn).await?;
+        store_current(&txn, self.pupil_id, self.flag).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    pub fn set_from_update(&mut self, update: FlagPeriodUpdate, user: &User) -> Result<()> {
+        update
+            .start_date
+            .apply("start_date", &mut self.start_date)?;
+        update.end_date.apply_nullable(&mut self.end_date);
+        update.source.apply("source", &mut self.source)?;
+        self.source = self.source.trim().to_owned();
+        update.notes.apply("notes", &mut self.notes)?;
+        self.updated_by = Some(user.email_address.clone());
+        self.updated_at = Utc::now().naive_utc();
+        Ok(())
+    }
+
+    /// Dates in order, a source to say where it came from, and no overlap with another period for
+    /// the same flag.
+    pub async fn validate(&self, db: &DatabaseConnection) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.end_date.is_some_and(|end| end < self.start_date) {
+            errors.insert(
+                "end_date".into(),
+                "end date cannot be before the start date".into(),
+            );
+        }
+        if self.source.is_empty() {
+            errors.insert(
+                "source".into(),
+                "say where this came from, like a parent declaration or LA notification".into(),
+            );
+        }
+        if errors.is_empty() {
+            if let Some(other) = Self::find(self.pupil_id, db)
+                .await?
+                .into_iter()
+                .find(|other| {
+                    other.id != self.id && other.flag == self.flag && self.overlaps(other)
+                })
+            {
+                errors.insert(
+                    "start_date".into(),
+                    format!(
+                        "this overlaps the period starting {}",
+                        other.start_date.format("%d/%m/%Y")
+                    ),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("flag period failed validation").with_fields(errors))
+        }
+    }
+
+    /// Move a merged pupil's periods onto the pupil they were merged into, joining any that now
+    /// overlap for the same flag, and bring that pupil's stored flags up to date.
+    pub(crate) async fn move_to<C: ConnectionTrait>(db: &C, from: Uuid, to: Uuid) -> Result<()> {
+        Entity::update_many()
+            .col_expr(Column::PupilId, Expr::value(to))
+            .filter(Column::PupilId.eq(from))
+            .exec(db)
+            .await?;
+        let mut periods = Self::find(to, db).await?;
+        periods.sort_by_key(|period| (period.flag.as_str(), period.start_date));
+        let mut joined: Vec<Self> = vec![];
+        for period in periods {
+            match joined.last_mut() {
+                Some(last) if last.flag == period.flag && last.overlaps(&period) => {
+                    last.end_date = match (last.end_date, period.end_date) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        _ => None,
+                    };
+                    Entity::delete_by_id(period.id).exec(db).await?;
+                    ActiveModel::from(Model::from(last.clone()))
+                        .reset_all()
+                        .update(db)
+                        .await?;
+                }
+                _ => joined.push(period),
+            }
+        }
+        for flag in Flag::ALL {
+            store_current(db, to, flag).await?;
+        }
+        Ok(())
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.end_date.is_none_or(|end| other.start_date <= end)
+            && other.end_date.is_none_or(|end| self.start_date <= end)
+    }
+
+    /// Record that a flag is on or off from a date, for changes made elsewhere: editing the
+    /// pupil, an EAL assessment or the ALN register. Turning a flag on opens a period unless one
+    /// already covers the date. Turning it off ends the covering period the day before, or removes
+    /// it if it only started that day.
+    pub async fn set_from<C: ConnectionTrait>(
+        db: &C,
+        pupil_id: Uuid,
+        flag: Flag,
+        on: bool,
+        date: NaiveDate,
+        source: &str,
+        user: Option<&User>,
+    ) -> Result<()> {
+        let periods: Vec<Self> = Self::find(pupil_id, db)
+            .await?
+            .into_iter()
+            .filter(|period| period.flag == flag)
+            .collect();
+        let covering = periods.iter().find(|period| period.covers(date));
+        let now = Utc::now().naive_utc();
+        let updated_by = user.map(|user| user.email_address.clone());
+        match (on, covering) {
+            (true, None) => {
+                // stop short of a period that has already been entered for later on
+                let end_date = periods
+                    .iter()
+                    .filter(|period| period.start_date > date)
+                    .map(|period| period.start_date - Duration::days(1))
+                    .min();
+                ActiveModel::from(Model::from(Self {
+                    id: Uuid::new_v4(),
+                    pupil_id,
+                    flag,
+                    start_date: date,
+                    end_date,
+                    source: source.to_owned(),
+                    notes: String::new(),
+                    updated_by,
+                    updated_at: now,
+                }))
+                .insert(db)
+                .await?;
+            }
+            (false, Some(period)) if period.start_date >= date => {
+                Entity::delete_by_id(period.id).exec(db).await?;
+            }
+            (false, Some(period)) => {
+                ActiveModel {
+                    id: Set(period.id),
+                    end_date: Set(Some(date - Duration::days(1))),
+                    updated_by: Set(updated_by),
+                    updated_at: Set(now),
+                    ..Default::default()
+                }
+                .update(db)
+                .await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Follow the flags set on the pupil record. A new pupil's flags run from their start date, and
+    /// a flag changed on an existing pupil is changed from the day it was edited.
+    pub async fn follow_pupil<C: ConnectionTrait>(
+        db: &C,
+        before: Option<&Pupil>,
+        pupil: &Pupil,
+        user: Option<&User>,
+    ) -> Result<()> {
+        for flag in Flag::ALL {
+            let on = flag.on(pupil);
+            match before {
+                None if on => {
+                    Self::set_from(
+                        db,
+                        pupil.id,
+                        flag,
+                        true,
+                        pupil.start_date,
+                        PUPIL_SOURCE,
+                        user,
+                    )
+                    .await?
+                }
+                Some(before) if flag.on(before) != on => {
+                    let today = Utc::now().date_naive();
+                    Self::set_from(db, pupil.id, flag, on, today, PUPIL_SOURCE, user).await?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every period for a flag that came from one source, for when whatever opened them
+    /// turns out to have been recorded in error.
+    pub async fn clear_source<C: ConnectionTrait>(
+        db: &C,
+        pupil_id: Uuid,
+        flag: Flag,
+        source: &str,
+    ) -> Result<()> {
+        Entity::delete_many()
+            .filter(Column::PupilId.eq(pupil_id))
+            .filter(Column::Flag.eq(flag.as_str()))
+            .filter(Column::Source.eq(source))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Work out the five flags on pupils from their periods as they stood on the date.
+    pub async fn flag_as_at(
+        pupils: &mut [Pupil],
+        date: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        let mut periods: HashMap<Uuid, Vec<Self>> = HashMap::new();
+        for period in Entity::find()
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|period| Self::try_from(period).ok())
+        {
+            periods.entry(period.pupil_id).or_default().push(period);
+        }
+        for pupil in pupils {
+            let pupil_periods = periods.remove(&pupil.id).unwrap_or_default();
+            for flag in Flag::ALL {
+                *flag.on_mut(pupil) = pupil_periods
+                    .iter()
+                    .any(|period| period.flag == flag && period.covers(date));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Keep the stored boolean matching today's periods, for anything reading the pupil table
+/// directly.
+async fn store_current<C: ConnectionTrait>(db: &C, pupil_id: Uuid, flag: Flag) -> Result<()> {
+    let today = Utc::now().date_naive();
+    let on = FlagPeriod::find(pupil_id, db)
+        .await?
+        .iter()
+        .any(|period| period.flag == flag && period.covers(today));
+    entity::pupil::Entity::update_many()
+        .col_expr(flag.column(), Expr::value(on))
+        .filter(entity::pupil::Column::Id.eq(pupil_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+impl TryFrom<Model> for FlagPeriod {
+    type Error = ();
+
+    fn try_from(model: Model) -> std::result::Result<Self, ()> {
+        Ok(Self {
+            id: model.id,
+            pupil_id: model.pupil_id,
+            flag: Flag::from_db(&model.flag).ok_or(())?,
+            start_date: model.start_date,
+            end_date: model.end_date,
+            source: model.source,
+            notes: model.notes,
+            updated_by: model.updated_by,
+            updated_at: model.updated_at,
+        })
+    }
+}
+
+impl From<FlagPeriod> for Model {
+    fn from(period: FlagPeriod) -> Self {
+        Self {
+            id: period.id,
+            pupil_id: period.pupil_id,
+            flag: period.flag.as_str().to_owned(),
+            start_date: period.start_date,
+            end_date: period.end_date,
+            source: period.source,
+            notes: period.notes,
+            updated_by: period.updated_by,
+            updated_at: period.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewFlagPeriod {
+    pub(crate) flag: Flag,
+    pub(crate) start_date: NaiveDate,
+    #[serde(default)]
+    pub(crate) end_date: Option<NaiveDate>,
+    pub(crate) source: String,
+    #[serde(default)]
+    pub(crate) notes: String,
+}
+
+/// The flag itself can't be changed, remove the period and add one for the other flag instead.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FlagPeriodUpdate {
+    pub(crate) start_date: Patch<NaiveDate>,
+    pub(crate) end_date: Patch<NaiveDate>,
+    pub(crate) source: Patch<String>,
+    pub(crate) notes: Patch<String>,
+}
+
+/// `?as_at=` on the pupil endpoints, to see the flags as they were on another day
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AsAt {
+    pub(crate) as_at: Option<NaiveDate>,
+}
+
+impl AsAt {
+    pub fn date(&self) -> NaiveDate {
+        self.as_at.unwrap_or_else(|| Utc::now().date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn period(flag: Flag, start: &str, end: Option<&str>) -> FlagPeriod {
+        FlagPeriod {
+            id: Uuid::new_v4(),
+            pupil_id: Uuid::new_v4(),
+            flag,
+            start_date: date(start),
+            end_date: end.map(date),
+            source: "parent declaration".into(),
+            notes: String::new(),
+            updated_by: None,
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[rstest]
+    #[case("2022-08-31", false)]
+    #[case("2022-09-01", true)]
+    #[case("2023-07-31", true)]
+    #[case("2023-08-01", false)]
+    fn test_covers_both_ends(#[case] on: &str, #[case] expected: bool) {
+        let fsm = period(Flag::Fsm, "2022-09-01", Some("2023-07-31"));
+        assert_eq!(fsm.covers(date(on)), expected);
+    }
+
+    #[rstest]
+    fn test_open_periods_cover_from_the_start() {
+        let lac = period(Flag::Lac, "2022-09-01", None);
+        assert!(!lac.covers(date("2022-01-01")));
+        assert!(lac.covers(date("2030-01-01")));
+    }
+
+    #[rstest]
+    #[case(("2022-09-01", Some("2022-12-31")), ("2023-01-01", None), false)]
+    #[case(("2022-09-01", Some("2023-01-01")), ("2023-01-01", None), true)]
+    #[case(("2022-09-01", None), ("2020-01-01", Some("2021-01-01")), false)]
+    #[case(("2022-09-01", None), ("2020-01-01", None), true)]
+    fn test_overlaps(
+        #[case] first: (&str, Option<&str>),
+        #[case] second: (&str, Option<&str>),
+        #[case] expected: bool,
+    ) {
+        let first = period(Flag::Fsm, first.0, first.1);
+        let second = period(Flag::Fsm, second.0, second.1);
+        assert_eq!(first.overlaps(&second), expected);
+        assert_eq!(second.overlaps(&first), expected);
+    }
+
+    #[rstest]
+    fn test_unknown_flags_are_skipped() {
+        let mut model = Model::from(period(Flag::Mat, "2022-09-01", None));
+        assert!(FlagPeriod::try_from(model.clone()).is_ok());
+        model.flag = "gifted".into();
+        assert!(FlagPeriod::try_from(model).is_err());
+    }
+}
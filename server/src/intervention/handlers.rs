@@ -0,0 +1,280 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    intervention::{model::*, target::*},
+    pupil::model::Pupil,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn create_intervention(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(intervention): Json<Intervention>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("creating intervention {}", intervention.name);
+    intervention.validate(state.database()).await?;
+    match intervention.insert(state.database().as_ref()).await {
+        Ok(intervention) => Ok((
+            StatusCode::CREATED,
+            Json(json!(intervention.details(&user, state.database()).await?)),
+        )),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_interventions(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested all interventions");
+    match Intervention::all_from_db(state.database().as_ref()).await {
+        Ok(interventions) => Ok(Json(json!(interventions))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!()),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_intervention_by_id(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested intervention {id}");
+    let id = Uuid::from_str(&id)?;
+    let intervention = Intervention::one_from_db(id, state.database()).await?;
+    match intervention.details(&user, state.database()).await {
+        Ok(details) => Ok(Json(json!(details))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_intervention(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(update): Json<InterventionUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating intervention {id}");
+    let id = Uuid::from_str(&id)?;
+    let mut intervention = Intervention::one_from_db(id, state.database()).await?;
+    intervention.check_lead(&user)?;
+    intervention.set_from_update(update)?;
+    intervention.validate(state.database()).await?;
+    match intervention.update(state.database().as_ref()).await {
+        Ok(intervention) => Ok(Json(json!(
+            intervention.details(&user, state.database()).await?
+        ))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_intervention(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting intervention {id}");
+    let id = Uuid::from_str(&id)?;
+    let intervention = Intervention::one_from_db(id, state.database()).await?;
+    intervention.check_lead(&user)?;
+    match intervention.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn save_intervention_member(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(entry): Json<MemberEntry>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("saving pupil {} in intervention {id}", entry.pupil_id);
+    let id = Uuid::from_str(&id)?;
+    let intervention = Intervention::one_from_db(id, state.database()).await?;
+    intervention.check_lead(&user)?;
+    match intervention
+        .save_member(&user, entry, state.database().as_ref())
+        .await
+    {
+        Ok(_) => Ok(Json(json!(
+            intervention.details(&user, state.database()).await?
+        ))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn remove_intervention_member(
+    State(state): State<AppState>,
+    Path((id, pupil_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("removing pupil {pupil_id} from intervention {id}");
+    let id = Uuid::from_str(&id)?;
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let intervention = Intervention::one_from_db(id, state.database()).await?;
+    intervention.check_lead(&user)?;
+    match intervention
+        .remove_member(&user, pupil_id, state.database().as_ref())
+        .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn log_intervention_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewSession>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("logging a session for intervention {id}");
+    let id = Uuid::from_str(&id)?;
+    let intervention = Intervention::one_from_db(id, state.database()).await?;
+    intervention.check_lead(&user)?;
+    let session = Session::new(intervention.id, &user, new);
+    session.validate(&intervention, Utc::now().date_naive())?;
+    match session.insert(state.database().as_ref()).await {
+        Ok(session) => Ok((StatusCode::CREATED, Json(json!(session)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_interventions(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested interventions for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Intervention::all_for_pupil(
+        &user,
+        pupil_id,
+        Utc::now().date_naive(),
+        state.database().as_ref(),
+    )
+    .await
+    {
+        Ok(interventions) => Ok(Json(json!(interventions))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_pupil_targets(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested targets for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match Target::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(targets) => Ok(Json(json!(targets))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_target(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewTarget>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("setting a target for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    // checks the pupil exists and is in one of the user's years
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let target = Target::new(pupil_id, &user, new, Utc::now().date_naive());
+    target.validate(state.database()).await?;
+    match target.insert(state.database().as_ref()).await {
+        Ok(target) => Ok((StatusCode::CREATED, Json(json!(target)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_target(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(update): Json<TargetUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating target {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let mut target = Target::one_from_db(&user, pupil_id, id, state.database()).await?;
+    target.set_from_update(update, Utc::now().date_naive())?;
+    target.validate(state.database()).await?;
+    match target.update(state.database().as_ref()).await {
+        Ok(target) => Ok(Json(json!(target))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_target(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting target {id} for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let target = Target::one_from_db(&user, pupil_id, id, state.database()).await?;
+    target.check_setter(&user)?;
+    match target.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
@@ -0,0 +1,550 @@
+use crate::{
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+    utils::patch::Patch,
+};
+use chrono::NaiveDate;
+use entity::{
+    intervention::{ActiveModel, Column, Entity, Model},
+    intervention_member, intervention_session,
+};
+use migration::{Condition, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// A group of pupils getting extra support from a lead member of staff for a period, with an
+/// entry and exit measure recorded for each pupil so the impact can be seen.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Intervention {
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) description: String,
+    pub(crate) lead_staff: String,
+    pub(crate) start_date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) end_date: Option<NaiveDate>,
+    /// what the entry and exit measures are, e.g. "reading age in months"
+    #[serde(default)]
+    pub(crate) measure: String,
+}
+
+impl Intervention {
+    pub async fn one_from_db(id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        match Entity::find_by_id(id).one(db).await? {
+            Some(intervention) => Ok(intervention.into()),
+            None => Err(InterventionDoesNotExist!()),
+        }
+    }
+
+    /// Every intervention, most recently started first. Who is in them is only shown per year.
+    pub async fn all_from_db(db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(Entity::find()
+            .order_by_desc(Column::StartDate)
+            .order_by_asc(Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting intervention {:?}", self);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name.clone()),
+            description: Set(self.description.clone()),
+            lead_staff: Set(self.lead_staff.clone()),
+            start_date: Set(self.start_date),
+            end_date: Set(self.end_date),
+            measure: Set(self.measure.clone()),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            name: Set(self.name.clone()),
+            description: Set(self.description.clone()),
+            lead_staff: Set(self.lead_staff.clone()),
+            start_date: Set(self.start_date),
+            end_date: Set(self.end_date),
+            measure: Set(self.measure.clone()),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Only the lead or an admin can change an intervention or who is in it.
+    pub fn check_lead(&self, user: &User) -> Result<()> {
+        if self.lead_staff == user.email_address || user.has_role(constant::ROLE_ADMIN) {
+            Ok(())
+        } else {
+            Err(Unauthorised!(
+                "only the lead or an admin can change an intervention"
+            ))
+        }
+    }
+
+    /// Running on the date, so started and either open ended or not yet finished.
+    pub fn is_active(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && self.end_date.is_none_or(|end| end >= date)
+    }
+
+    /// Apply a JSON merge patch, where a null end date makes the intervention open ended.
+    pub fn set_from_update(&mut self, update: InterventionUpdate) -> Result<()> {
+        update.name.apply("name", &mut self.name)?;
+        update
+            .description
+            .apply("description", &mut self.description)?;
+        update
+            .lead_staff
+            .apply("lead_staff", &mut self.lead_staff)?;
+        update
+            .start_date
+            .apply("start_date", &mut self.start_date)?;
+        update.end_date.apply_nullable(&mut self.end_date);
+        update.measure.apply("measure", &mut self.measure)?;
+        Ok(())
+    }
+
+    pub async fn validate(&self, db: &DatabaseConnection) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "name cannot be empty".into());
+        }
+        if let Some(end_date) = self.end_date {
+            if end_date < self.start_date {
+                errors.insert(
+                    "end_date".into(),
+                    "end date cannot be before the start date".into(),
+                );
+            }
+        }
+        if User::one_from_db(&self.lead_staff, db).await.is_err() {
+            errors.insert(
+                "lead_staff".into(),
+                format!("{} is not a user", self.lead_staff),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("intervention failed validation").with_fields(errors))
+        }
+    }
+
+    /// The intervention with its sessions and the members in the user's years.
+    pub async fn details(&self, user: &User, db: &DatabaseConnection) -> Result<Details> {
+        let mut measures: HashMap<Uuid, intervention_member::Model> =
+            intervention_member::Entity::find()
+                .filter(intervention_member::Column::InterventionId.eq(self.id))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|member| (member.pupil_id, member))
+                .collect();
+        let mut years = Condition::any();
+        for year in &user.years {
+            years = years.add(entity::pupil::Column::Year.eq(*year));
+        }
+        let members = entity::pupil::Entity::find()
+            .filter(entity::pupil::Column::Id.is_in(measures.keys().copied()))
+            .filter(years)
+            .order_by_asc(entity::pupil::Column::LastName)
+            .order_by_asc(entity::pupil::Column::FirstNames)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|pupil| {
+                let member = measures.remove(&pupil.id)?;
+                Some(Member {
+                    pupil_id: pupil.id,
+                    first_names: pupil.first_names,
+                    last_name: pupil.last_name,
+                    year: pupil.year,
+                    entry_measure: member.entry_measure,
+                    exit_measure: member.exit_measure,
+                })
+            })
+            .collect();
+        let sessions = intervention_session::Entity::find()
+            .filter(intervention_session::Column::InterventionId.eq(self.id))
+            .order_by_desc(intervention_session::Column::Date)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Details {
+            intervention: self.clone(),
+            members,
+            sessions,
+        })
+    }
+
+    /// Add a pupil to the intervention, or record their measures if they're already in it.
+    pub async fn save_member(
+        &self,
+        user: &User,
+        entry: MemberEntry,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        Pupil::one_from_db(user, entry.pupil_id, db).await?;
+        intervention_member::Entity::insert(intervention_member::ActiveModel {
+            intervention_id: Set(self.id),
+            pupil_id: Set(entry.pupil_id),
+            entry_measure: Set(entry.entry_measure),
+            exit_measure: Set(entry.exit_measure),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                intervention_member::Column::InterventionId,
+                intervention_member::Column::PupilId,
+            ])
+            .update_columns([
+                intervention_member::Column::EntryMeasure,
+                intervention_member::Column::ExitMeasure,
+            ])
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_member(
+        &self,
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        intervention_member::Entity::delete_by_id((self.id, pupil_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// The interventions a pupil has been part of, with how they did in each.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        today: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<PupilIntervention>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let mut measures: HashMap<Uuid, intervention_member::Model> =
+            intervention_member::Entity::find()
+                .filter(intervention_member::Column::PupilId.eq(pupil_id))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|member| (member.intervention_id, member))
+                .collect();
+        Ok(Entity::find()
+            .filter(Column::Id.is_in(measures.keys().copied()))
+            .order_by_desc(Column::StartDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|intervention| {
+                let member = measures.remove(&intervention.id)?;
+                let intervention: Self = intervention.into();
+                Some(PupilIntervention {
+                    active: intervention.is_active(today),
+                    intervention,
+                    entry_measure: member.entry_measure,
+                    exit_measure: member.exit_measure,
+                })
+            })
+            .collect())
+    }
+
+    /// Fill in the derived active intervention flag on pupils about to be sent to the client.
+    pub async fn flag_active_members(
+        pupils: &mut [Pupil],
+        today: NaiveDate,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        let active: Vec<Uuid> = Entity::find()
+            .filter(Column::StartDate.lte(today))
+            .filter(
+                Condition::any()
+                    .add(Column::EndDate.is_null())
+                    .add(Column::EndDate.gte(today)),
+            )
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|intervention| intervention.id)
+            .collect();
+        let members: HashSet<Uuid> = intervention_member::Entity::find()
+            .filter(intervention_member::Column::InterventionId.is_in(active))
+            .filter(intervention_member::Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|member| member.pupil_id)
+            .collect();
+        for pupil in pupils {
+            pupil.in_intervention = members.contains(&pupil.id);
+        }
+        Ok(())
+    }
+}
+
+/// One session the group met, as logged by whoever ran it.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Session {
+    pub(crate) id: Uuid,
+    pub(crate) intervention_id: Uuid,
+    pub(crate) date: NaiveDate,
+    pub(crate) duration_minutes: i32,
+    pub(crate) notes: String,
+    pub(crate) recorded_by: String,
+}
+
+impl Session {
+    pub fn new(intervention_id: Uuid, user: &User, new: NewSession) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            intervention_id,
+            date: new.date,
+            duration_minutes: new.duration_minutes,
+            notes: new.notes,
+            recorded_by: user.email_address.clone(),
+        }
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(intervention_session::ActiveModel {
+            id: Set(self.id),
+            intervention_id: Set(self.intervention_id),
+            date: Set(self.date),
+            duration_minutes: Set(self.duration_minutes),
+            notes: Set(self.notes.clone()),
+            recorded_by: Set(self.recorded_by.clone()),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    /// Sessions have to fall within the intervention and can't be logged ahead of time.
+    pub fn validate(&self, intervention: &Intervention, today: NaiveDate) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.date > today {
+            errors.insert("date".into(), "date cannot be in the future".into());
+        } else if self.date < intervention.start_date
+            || intervention.end_date.is_some_and(|end| self.date > end)
+        {
+            errors.insert(
+                "date".into(),
+                "date must be while the intervention is running".into(),
+            );
+        }
+        if self.duration_minutes <= 0 {
+            errors.insert(
+                "duration_minutes".into(),
+                "duration must be more than 0 minutes".into(),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("session failed validation").with_fields(errors))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Member {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) year: i32,
+    pub(crate) entry_measure: Option<i32>,
+    pub(crate) exit_measure: Option<i32>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Details {
+    #[serde(flatten)]
+    pub(crate) intervention: Intervention,
+    pub(crate) members: Vec<Member>,
+    /// newest first
+    pub(crate) sessions: Vec<Session>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct PupilIntervention {
+    #[serde(flatten)]
+    pub(crate) intervention: Intervention,
+    pub(crate) active: bool,
+    pub(crate) entry_measure: Option<i32>,
+    pub(crate) exit_measure: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct MemberEntry {
+    pub(crate) pupil_id: Uuid,
+    #[serde(default)]
+    entry_measure: Option<i32>,
+    #[serde(default)]
+    exit_measure: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewSession {
+    date: NaiveDate,
+    duration_minutes: i32,
+    #[serde(default)]
+    notes: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct InterventionUpdate {
+    name: Patch<String>,
+    description: Patch<String>,
+    lead_staff: Patch<String>,
+    start_date: Patch<NaiveDate>,
+    end_date: Patch<NaiveDate>,
+    measure: Patch<String>,
+}
+
+impl From<Model> for Intervention {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            lead_staff: value.lead_staff,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            measure: value.measure,
+        }
+    }
+}
+
+impl From<intervention_session::Model> for Session {
+    fn from(value: intervention_session::Model) -> Self {
+        Self {
+            id: value.id,
+            intervention_id: value.intervention_id,
+            date: value.date,
+            duration_minutes: value.duration_minutes,
+            notes: value.notes,
+            recorded_by: value.recorded_by,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[fixture]
+    fn intervention() -> Intervention {
+        Intervention {
+            id: Uuid::new_v4(),
+            name: "Reading recovery".into(),
+            description: String::new(),
+            lead_staff: "lead@test.com".into(),
+            start_date: date("2023-01-09"),
+            end_date: Some(date("2023-03-31")),
+            measure: "reading age in months".into(),
+        }
+    }
+
+    #[rstest]
+    #[case("2023-01-08", Some("2023-03-31"), false)]
+    #[case("2023-01-09", Some("2023-03-31"), true)]
+    #[case("2023-03-31", Some("2023-03-31"), true)]
+    #[case("2023-04-01", Some("2023-03-31"), false)]
+    #[case("2024-04-01", None, true)]
+    fn active_while_running(
+        mut intervention: Intervention,
+        #[case] on: &str,
+        #[case] end_date: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        intervention.end_date = end_date.map(date);
+        assert_eq!(intervention.is_active(date(on)), expected);
+    }
+
+    #[rstest]
+    fn only_lead_or_admin_can_change(intervention: Intervention) {
+        let mut user = User::new("a", "b", "someone@test.com", "", vec![]);
+        assert!(intervention.check_lead(&user).is_err());
+        user.roles = vec![constant::ROLE_ADMIN.into()];
+        assert!(intervention.check_lead(&user).is_ok());
+        let lead = User::new("a", "b", "lead@test.com", "", vec![]);
+        assert!(intervention.check_lead(&lead).is_ok());
+    }
+
+    #[rstest]
+    fn null_end_date_makes_it_open_ended(mut intervention: Intervention) {
+        let update: InterventionUpdate =
+            serde_json::from_str(r#"{"end_date": null, "name": "Reading catch up"}"#).unwrap();
+        intervention.set_from_update(update).unwrap();
+        assert_eq!(intervention.end_date, None);
+        assert_eq!(intervention.name, "Reading catch up");
+    }
+
+    #[rstest]
+    #[case("2023-02-01", 30, vec![])]
+    #[case("2023-01-01", 30, vec!["date"])]
+    #[case("2023-04-03", 30, vec!["date"])]
+    #[case("2023-02-01", 0, vec!["duration_minutes"])]
+    fn sessions_fall_within_the_intervention(
+        intervention: Intervention,
+        #[case] on: &str,
+        #[case] duration_minutes: i32,
+        #[case] expected: Vec<&str>,
+    ) {
+        let user = User::new("a", "b", "lead@test.com", "", vec![]);
+        let session = Session::new(
+            intervention.id,
+            &user,
+            NewSession {
+                date: date(on),
+                duration_minutes,
+                notes: String::new(),
+            },
+        );
+        match session.validate(&intervention, date("2023-04-05")) {
+            Ok(()) => assert!(expected.is_empty()),
+            Err(error) => {
+                let fields = error.fields.unwrap();
+                assert_eq!(
+                    fields.keys().map(String::as_str).collect::<Vec<_>>(),
+                    expected
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{
+    core::error::Result, intervention::model::Intervention, pupil::model::Pupil, user::model::User,
+    utils::patch::Patch,
+};
+use chrono::NaiveDate;
+use entity::target::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// How a target turned out when it was reviewed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Achieved,
+    PartiallyAchieved,
+    NotAchieved,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Achieved => "achieved",
+            Outcome::PartiallyAchieved => "partially_achieved",
+            Outcome::NotAchieved => "not_achieved",
+        }
+    }
+
+    /// Anything unrecognised counts as not achieved so the target gets looked at again.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "achieved" => Outcome::Achieved,
+            "partially_achieved" => Outcome::PartiallyAchieved,
+            _ => Outcome::NotAchieved,
+        }
+    }
+}
+
+/// A SMART target for a pupil: what they should do, how we'll know, and when it's reviewed.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct Target {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) intervention_id: Option<Uuid>,
+    pub(crate) description: String,
+    pub(crate) success_criteria: String,
+    pub(crate) set_by: String,
+    pub(crate) set_on: NaiveDate,
+    pub(crate) review_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) outcome: Option<Outcome>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) reviewed_on: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) review_notes: Option<String>,
+}
+
+impl Target {
+    pub fn new(pupil_id: Uuid, user: &User, new: NewTarget, today: NaiveDate) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            intervention_id: new.intervention_id,
+            description: new.description,
+            success_criteria: new.success_criteria,
+            set_by: user.email_address.clone(),
+            set_on: today,
+            review_date: new.review_date,
+            outcome: None,
+            reviewed_on: None,
+            review_notes: None,
+        }
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .one(db)
+            .await?
+        {
+            Some(target) => Ok(target.into()),
+            None => Err(TargetDoesNotExist!()),
+        }
+    }
+
+    /// All of a pupil's targets, the next due for review first.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_asc(Column::ReviewDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting target {:?}", self);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            intervention_id: Set(self.intervention_id),
+            description: Set(self.description.clone()),
+            success_criteria: Set(self.success_criteria.clone()),
+            set_by: Set(self.set_by.clone()),
+            set_on: Set(self.set_on),
+            review_date: Set(self.review_date),
+            outcome: Set(self.outcome.map(|o| o.as_str().to_owned())),
+            reviewed_on: Set(self.reviewed_on),
+            review_notes: Set(self.review_notes.clone()),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            pupil_id: Unchanged(self.pupil_id),
+            intervention_id: Set(self.intervention_id),
+            description: Set(self.description.clone()),
+            success_criteria: Set(self.success_criteria.clone()),
+            set_by: Unchanged(self.set_by.clone()),
+            set_on: Unchanged(self.set_on),
+            review_date: Set(self.review_date),
+            outcome: Set(self.outcome.map(|o| o.as_str().to_owned())),
+            reviewed_on: Set(self.reviewed_on),
+            review_notes: Set(self.review_notes.clone()),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Only whoever set a target may remove it.
+    pub fn check_setter(&self, user: &User) -> Result<()> {
+        if self.set_by == user.email_address {
+            Ok(())
+        } else {
+            Err(Unauthorised!(
+                "only the person who set a target can remove it"
+            ))
+        }
+    }
+
+    /// Apply a JSON merge patch. Setting or clearing the outcome records or clears the review.
+    pub fn set_from_update(&mut self, update: TargetUpdate, today: NaiveDate) -> Result<()> {
+        update
+            .description
+            .apply("description", &mut self.description)?;
+        update
+            .success_criteria
+            .apply("success_criteria", &mut self.success_criteria)?;
+        update
+            .review_date
+            .apply("review_date", &mut self.review_date)?;
+        update
+            .intervention_id
+            .apply_nullable(&mut self.intervention_id);
+        update.review_notes.apply_nullable(&mut self.review_notes);
+        if !update.outcome.is_missing() {
+            update.outcome.apply_nullable(&mut self.outcome);
+            self.reviewed_on = self.outcome.map(|_| today);
+        }
+        Ok(())
+    }
+
+    pub async fn validate(&self, db: &DatabaseConnection) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.description.trim().is_empty() {
+            errors.insert("description".into(), "target cannot be empty".into());
+        }
+        if self.success_criteria.trim().is_empty() {
+            errors.insert(
+                "success_criteria".into(),
+                "say how you'll know the target has been met".into(),
+            );
+        }
+        if self.review_date < self.set_on {
+            errors.insert(
+                "review_date".into(),
+                "review date cannot be before the target was set".into(),
+            );
+        }
+        if let Some(intervention_id) = self.intervention_id {
+            if Intervention::one_from_db(intervention_id, db)
+                .await
+                .is_err()
+            {
+                errors.insert(
+                    "intervention_id".into(),
+                    "intervention does not exist".into(),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("target failed validation").with_fields(errors))
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewTarget {
+    #[serde(default)]
+    intervention_id: Option<Uuid>,
+    description: String,
+    success_criteria: String,
+    review_date: NaiveDate,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct TargetUpdate {
+    intervention_id: Patch<Uuid>,
+    description: Patch<String>,
+    success_criteria: Patch<String>,
+    review_date: Patch<NaiveDate>,
+    outcome: Patch<Outcome>,
+    review_notes: Patch<String>,
+}
+
+impl From<Model> for Target {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            intervention_id: value.intervention_id,
+            description: value.description,
+            success_criteria: value.success_criteria,
+            set_by: value.set_by,
+            set_on: value.set_on,
+            review_date: value.review_date,
+            outcome: value.outcome.as_deref().map(Outcome::from_db),
+            reviewed_on: value.reviewed_on,
+            review_notes: value.review_notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[fixture]
+    fn target() -> Target {
+        let user = User::new("a", "b", "teacher@test.com", "", vec![6]);
+        Target::new(
+            Uuid::new_v4(),
+            &user,
+            NewTarget {
+                intervention_id: None,
+                description: "Read a page aloud without help".into(),
+                success_criteria: "Three times in a row in guided reading".into(),
+                review_date: date("2023-05-01"),
+            },
+            date("2023-03-01"),
+        )
+    }
+
+    #[rstest]
+    fn reviewing_records_the_date(mut target: Target) {
+        let update: TargetUpdate =
+            serde_json::from_str(r#"{"outcome": "partially_achieved", "review_notes": "twice"}"#)
+                .unwrap();
+        target.set_from_update(update, date("2023-05-02")).unwrap();
+        assert_eq!(target.outcome, Some(Outcome::PartiallyAchieved));
+        assert_eq!(target.reviewed_on, Some(date("2023-05-02")));
+        assert_eq!(target.review_notes.as_deref(), Some("twice"));
+
+        let update: TargetUpdate = serde_json::from_str(r#"{"outcome": null}"#).unwrap();
+        target.set_from_update(update, date("2023-05-03")).unwrap();
+        assert_eq!(target.outcome, None);
+        assert_eq!(target.reviewed_on, None);
+    }
+
+    #[rstest]
+    fn other_changes_leave_the_review_alone(mut target: Target) {
+        target.outcome = Some(Outcome::Achieved);
+        target.reviewed_on = Some(date("2023-05-02"));
+        let update: TargetUpdate =
+            serde_json::from_str(r#"{"review_date": "2023-06-01"}"#).unwrap();
+        target.set_from_update(update, date("2023-05-10")).unwrap();
+        assert_eq!(target.review_date, date("2023-06-01"));
+        assert_eq!(target.reviewed_on, Some(date("2023-05-02")));
+    }
+
+    #[rstest]
+    #[case("achieved", Outcome::Achieved)]
+    #[case("partially_achieved", Outcome::PartiallyAchieved)]
+    #[case("something else", Outcome::NotAchieved)]
+    fn outcome_from_db(#[case] value: &str, #[case] expected: Outcome) {
+        assert_eq!(Outcome::from_db(value), expected);
+    }
+}
@@ -1,7 +1,26 @@
 #[macro_use]
 pub mod core;
+pub mod aln;
+pub mod analytics;
 pub mod app;
+pub mod assessment;
+pub mod attachment;
+pub mod attendance;
 pub mod auth;
+pub mod census;
+pub mod comment;
+pub mod concern;
+pub mod contact;
+pub mod ctf;
+pub mod curriculum;
+pub mod eal;
+pub mod flag;
+pub mod intervention;
+pub mod medical;
+pub mod photo;
 pub mod pupil;
+pub mod quality;
+pub mod report;
+pub mod subject_access;
 pub mod user;
 pub mod utils;
@@ -1,9 +1,11 @@
 use axum::Server;
 use lt_server::{
     app::{
+        config::Config,
         router::router,
         state::{AppState, AppStateObj},
     },
+    attachment::{scan, store},
     core::error::Result,
     utils::log::start_log,
 };
@@ -25,7 +27,12 @@ async fn main() -> Result<()> {
     } else {
         Migrator::up(db.as_ref(), None).await?;
     }
-    let app_state: AppState = Arc::new(AppStateObj::new(db));
+    let app_state: AppState = Arc::new(AppStateObj::new(
+        db,
+        Config::from_env()?,
+        store::from_env()?,
+        scan::from_env(),
+    ));
     let address: SocketAddr = std::env::var("SERVER_ADDR")?.parse()?;
     tracing::debug!("listening on {address}");
     Server::bind(&address)
@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    core::{constant, error::*},
+    medical::{model::*, report::AllergyRow},
+    pupil::model::Pupil,
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    Extension,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+const MEDICAL_ONLY: &str = "only medical staff or an admin can see medical records";
+
+pub async fn get_pupil_medical(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested medical record for pupil {pupil_id}");
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_MEDICAL, MEDICAL_ONLY)?;
+    }
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match MedicalItem::all_for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(items) => Ok(Json(json!(items))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn create_medical_item(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(new): Json<NewMedicalItem>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("adding to the medical record of pupil {pupil_id}");
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_MEDICAL, MEDICAL_ONLY)?;
+    }
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    Pupil::one_from_db(&user, pupil_id, state.database()).await?;
+    let item = MedicalItem::new(pupil_id, &user, new);
+    item.validate()?;
+    match item.insert(state.database().as_ref()).await {
+        Ok(item) => Ok((StatusCode::CREATED, Json(json!(item)))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn update_medical_item(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+    Json(update): Json<MedicalItemUpdate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating medical item {id} for pupil {pupil_id}");
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_MEDICAL, MEDICAL_ONLY)?;
+    }
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let mut item = MedicalItem::one_from_db(&user, pupil_id, id, state.database()).await?;
+    item.set_from_update(&user, update)?;
+    item.validate()?;
+    match item.update(state.database().as_ref()).await {
+        Ok(item) => Ok(Json(json!(item))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_medical_item(
+    State(state): State<AppState>,
+    Path((pupil_id, id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("deleting medical item {id} for pupil {pupil_id}");
+    if !user.has_role(constant::ROLE_ADMIN) {
+        user.check_role(constant::ROLE_MEDICAL, MEDICAL_ONLY)?;
+    }
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let id = Uuid::from_str(&id)?;
+    let item = MedicalItem::one_from_db(&user, pupil_id, id, state.database()).await?;
+    match item.delete(state.database().as_ref()).await {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn get_allergy_report(
+    State(state): State<AppState>,
+    Path(year): Path<i32>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested allergy report for year {year}");
+    match AllergyRow::for_year(&user, year, state.database().as_ref()).await {
+        Ok(rows) => Ok(Json(json!(rows))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
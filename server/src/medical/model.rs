@@ -0,0 +1,353 @@
+use crate::{core::error::Result, pupil::model::Pupil, user::model::User, utils::patch::Patch};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use entity::medical_item::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// The sections of a pupil's medical record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Condition,
+    Allergy,
+    Medication,
+    CarePlan,
+    Diet,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Condition => "condition",
+            Kind::Allergy => "allergy",
+            Kind::Medication => "medication",
+            Kind::CarePlan => "care_plan",
+            Kind::Diet => "diet",
+        }
+    }
+
+    /// Anything unrecognised is kept as a condition so it still shows on the record.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "allergy" => Kind::Allergy,
+            "medication" => Kind::Medication,
+            "care_plan" => Kind::CarePlan,
+            "diet" => Kind::Diet,
+            _ => Kind::Condition,
+        }
+    }
+}
+
+/// How bad a reaction to an allergen is, anaphylaxis being life threatening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Mild,
+    Moderate,
+    Severe,
+    Anaphylaxis,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Mild => "mild",
+            Severity::Moderate => "moderate",
+            Severity::Severe => "severe",
+            Severity::Anaphylaxis => "anaphylaxis",
+        }
+    }
+
+    /// Anything unrecognised is treated as the worst case.
+    fn from_db(value: &str) -> Self {
+        match value {
+            "mild" => Severity::Mild,
+            "moderate" => Severity::Moderate,
+            "severe" => Severity::Severe,
+            _ => Severity::Anaphylaxis,
+        }
+    }
+}
+
+/// One entry on a pupil's medical record. Only allergies have a severity, and `carried` is for
+/// medication the pupil keeps with them, like an inhaler or adrenaline pen.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct MedicalItem {
+    pub(crate) id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) kind: Kind,
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) severity: Option<Severity>,
+    pub(crate) details: String,
+    pub(crate) carried: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) review_date: Option<NaiveDate>,
+    pub(crate) updated_by: String,
+    pub(crate) updated_at: NaiveDateTime,
+}
+
+impl MedicalItem {
+    pub fn new(pupil_id: Uuid, user: &User, new: NewMedicalItem) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            pupil_id,
+            kind: new.kind,
+            name: new.name,
+            severity: new.severity,
+            details: new.details,
+            carried: new.carried,
+            review_date: new.review_date,
+            updated_by: user.email_address.clone(),
+            updated_at: Utc::now().naive_utc(),
+        }
+    }
+
+    pub async fn one_from_db(
+        user: &User,
+        pupil_id: Uuid,
+        id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        match Entity::find_by_id(id)
+            .filter(Column::PupilId.eq(pupil_id))
+            .one(db)
+            .await?
+        {
+            Some(item) => Ok(item.into()),
+            None => Err(MedicalItemDoesNotExist!()),
+        }
+    }
+
+    /// The pupil's whole record, grouped by section.
+    pub async fn all_for_pupil(
+        user: &User,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self>> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(Entity::find()
+            .filter(Column::PupilId.eq(pupil_id))
+            .order_by_asc(Column::Kind)
+            .order_by_asc(Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+        tracing::debug!("inserting medical item {} for {}", self.id, self.pupil_id);
+        Ok(ActiveModel {
+            id: Set(self.id),
+            pupil_id: Set(self.pupil_id),
+            kind: Set(self.kind.as_str().to_owned()),
+            name: Set(self.name.clone()),
+            severity: Set(self.severity.map(|s| s.as_str().to_owned())),
+            details: Set(self.details.clone()),
+            carried: Set(self.carried),
+            review_date: Set(self.review_date),
+            updated_by: Set(self.updated_by.clone()),
+            updated_at: Set(self.updated_at),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            id: Unchanged(self.id),
+            pupil_id: Unchanged(self.pupil_id),
+            kind: Set(self.kind.as_str().to_owned()),
+            name: Set(self.name.clone()),
+            severity: Set(self.severity.map(|s| s.as_str().to_owned())),
+            details: Set(self.details.clone()),
+            carried: Set(self.carried),
+            review_date: Set(self.review_date),
+            updated_by: Set(self.updated_by.clone()),
+            updated_at: Set(self.updated_at),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
+        Entity::delete_by_id(self.id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Apply a JSON merge patch, recording who made the change.
+    pub fn set_from_update(&mut self, user: &User, update: MedicalItemUpdate) -> Result<()> {
+        update.kind.apply("kind", &mut self.kind)?;
+        update.name.apply("name", &mut self.name)?;
+        update.severity.apply_nullable(&mut self.severity);
+        update.details.apply("details", &mut self.details)?;
+        update.carried.apply("carried", &mut self.carried)?;
+        update.review_date.apply_nullable(&mut self.review_date);
+        self.updated_by = user.email_address.clone();
+        self.updated_at = Utc::now().naive_utc();
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "name cannot be empty".into());
+        }
+        match (self.kind, self.severity) {
+            (Kind::Allergy, None) => {
+                errors.insert("severity".into(), "give the severity of the allergy".into());
+            }
+            (Kind::Allergy, Some(_)) | (_, None) => {}
+            (_, Some(_)) => {
+                errors.insert("severity".into(), "only allergies have a severity".into());
+            }
+        }
+        if self.carried && self.kind != Kind::Medication {
+            errors.insert(
+                "carried".into(),
+                "only medication can be carried by the pupil".into(),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("medical record failed validation").with_fields(errors))
+        }
+    }
+
+    /// Fill in the derived allergy and medication alerts on pupils about to be sent to the client.
+    /// These are shown to everyone who can see the pupil, the record itself is not.
+    pub async fn flag_alerts(pupils: &mut [Pupil], db: &DatabaseConnection) -> Result<()> {
+        let items = Entity::find()
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .filter(
+                Column::Kind
+                    .eq(Kind::Allergy.as_str())
+                    .or(Column::Carried.eq(true)),
+            )
+            .all(db)
+            .await?;
+        let allergic: HashSet<Uuid> = items
+            .iter()
+            .filter(|item| item.kind == Kind::Allergy.as_str())
+            .map(|item| item.pupil_id)
+            .collect();
+        let carrying: HashSet<Uuid> = items
+            .iter()
+            .filter(|item| item.carried)
+            .map(|item| item.pupil_id)
+            .collect();
+        for pupil in pupils {
+            pupil.has_allergy = allergic.contains(&pupil.id);
+            pupil.carries_medication = carrying.contains(&pupil.id);
+        }
+        Ok(())
+    }
+}
+
+/// Only medical staff or an admin can see or change medical records.
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct NewMedicalItem {
+    kind: Kind,
+    name: String,
+    #[serde(default)]
+    severity: Option<Severity>,
+    #[serde(default)]
+    details: String,
+    #[serde(default)]
+    carried: bool,
+    #[serde(default)]
+    review_date: Option<NaiveDate>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct MedicalItemUpdate {
+    kind: Patch<Kind>,
+    name: Patch<String>,
+    severity: Patch<Severity>,
+    details: Patch<String>,
+    carried: Patch<bool>,
+    review_date: Patch<NaiveDate>,
+}
+
+impl From<Model> for MedicalItem {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            kind: Kind::from_db(&value.kind),
+            name: value.name,
+            severity: value.severity.as_deref().map(Severity::from_db),
+            details: value.details,
+            carried: value.carried,
+            review_date: value.review_date,
+            updated_by: value.updated_by,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+    use serde_json::json;
+
+    fn item_from(value: serde_json::Value) -> MedicalItem {
+        let user = User::new("test", "user", "nurse@school.com", "pass", vec![3]);
+        MedicalItem::new(
+            Uuid::new_v4(),
+            &user,
+            serde_json::from_value(value).unwrap(),
+        )
+    }
+
+    #[rstest]
+    #[case(json!({"kind": "allergy", "name": "peanuts", "severity": "anaphylaxis"}))]
+    #[case(json!({"kind": "medication", "name": "salbutamol inhaler", "carried": true}))]
+    #[case(json!({"kind": "diet", "name": "vegetarian"}))]
+    #[case(json!({"kind": "care_plan", "name": "epilepsy", "review_date": "2024-01-01"}))]
+    fn valid_items(#[case] value: serde_json::Value) {
+        assert!(item_from(value).validate().is_ok());
+    }
+
+    #[rstest]
+    #[case(json!({"kind": "allergy", "name": "peanuts"}), "severity")]
+    #[case(json!({"kind": "condition", "name": "asthma", "severity": "mild"}), "severity")]
+    #[case(json!({"kind": "diet", "name": "halal", "carried": true}), "carried")]
+    #[case(json!({"kind": "condition", "name": " "}), "name")]
+    fn invalid_items(#[case] value: serde_json::Value, #[case] field: &str) {
+        let error = item_from(value).validate().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert!(error.fields.unwrap().contains_key(field));
+    }
+
+    #[rstest]
+    #[case("care_plan", Kind::CarePlan)]
+    #[case("allergy", Kind::Allergy)]
+    #[case("something new", Kind::Condition)]
+    fn kind_from_db(#[case] value: &str, #[case] expected: Kind) {
+        assert_eq!(Kind::from_db(value), expected);
+    }
+
+    #[rstest]
+    #[case("mild", Severity::Mild)]
+    #[case("severe", Severity::Severe)]
+    #[case("unknown", Severity::Anaphylaxis)]
+    fn severity_from_db(#[case] value: &str, #[case] expected: Severity) {
+        assert_eq!(Severity::from_db(value), expected);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    core::error::Result,
+    medical::model::{Kind, MedicalItem, Severity},
+    user::model::User,
+};
+use entity::medical_item::{Column, Entity};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// An allergen and how bad the reaction is, as shown on the kitchen and trip lists.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Allergen {
+    name: String,
+    severity: Severity,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    details: String,
+}
+
+/// What staff need to know to keep a pupil safe at lunch or on a trip, without the rest of their
+/// medical record.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct AllergyRow {
+    pupil_id: Uuid,
+    first_names: String,
+    last_name: String,
+    allergies: Vec<Allergen>,
+    diets: Vec<String>,
+    carries_medication: bool,
+}
+
+impl AllergyRow {
+    /// Every pupil in the year with an allergy or special diet, worst allergy first.
+    pub async fn for_year(user: &User, year: i32, db: &DatabaseConnection) -> Result<Vec<Self>> {
+        user.check_year(year)?;
+        let pupils = entity::pupil::Entity::find()
+            .filter(entity::pupil::Column::Year.eq(year))
+            .filter(entity::pupil::Column::Active.eq(true))
+            .all(db)
+            .await?;
+        let mut rows: BTreeMap<Uuid, AllergyRow> = pupils
+            .into_iter()
+            .map(|pupil| {
+                (
+                    pupil.id,
+                    AllergyRow {
+                        pupil_id: pupil.id,
+                        first_names: pupil.first_names,
+                        last_name: pupil.last_name,
+                        allergies: vec![],
+                        diets: vec![],
+                        carries_medication: false,
+                    },
+                )
+            })
+            .collect();
+        let items = Entity::find()
+            .filter(Column::PupilId.is_in(rows.keys().copied()))
+            .order_by_asc(Column::Name)
+            .all(db)
+            .await?;
+        for item in items.into_iter().map(MedicalItem::from) {
+            let Some(row) = rows.get_mut(&item.pupil_id) else {
+                continue;
+            };
+            match item.kind {
+                Kind::Allergy => row.allergies.push(Allergen {
+                    name: item.name,
+                    severity: item.severity.unwrap_or(Severity::Anaphylaxis),
+                    details: item.details,
+                }),
+                Kind::Diet => row.diets.push(item.name),
+                _ => {}
+            }
+            row.carries_medication |= item.carried;
+        }
+        let mut rows: Vec<AllergyRow> = rows
+            .into_values()
+            .filter(|row| !row.allergies.is_empty() || !row.diets.is_empty())
+            .collect();
+        rows.sort_by(|a, b| {
+            b.worst_severity()
+                .cmp(&a.worst_severity())
+                .then_with(|| a.last_name.cmp(&b.last_name))
+                .then_with(|| a.first_names.cmp(&b.first_names))
+        });
+        Ok(rows)
+    }
+
+    fn worst_severity(&self) -> Option<Severity> {
+        self.allergies
+            .iter()
+            .map(|allergen| allergen.severity)
+            .max()
+    }
+}
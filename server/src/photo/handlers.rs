@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+use crate::{
+    app::state::AppState,
+    attachment::signed::SignedLink,
+    core::error::*,
+    photo::{model::*, resize::resize},
+    user::model::*,
+};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+pub async fn get_pupil_photo(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested the photo for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    match PupilPhoto::for_pupil(&user, pupil_id, state.database().as_ref()).await {
+        Ok(photo) => Ok(Json(json!(photo.with_links(&user, state.config())))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
+            ErrorKind::Unauthorised => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn upload_pupil_photo(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(upload): Json<NewPhoto>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::debug!("uploading a photo for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let photo = PupilPhoto::for_pupil(&user, pupil_id, state.database()).await?;
+    let data = upload.decode(state.config().attachment_max_bytes)?;
+    let resized = resize(&data)?;
+    match photo
+        .save(
+            &user,
+            &resized,
+            state.blob_store().as_ref(),
+            state.database(),
+        )
+        .await
+    {
+        Ok(photo) => Ok((
+            StatusCode::CREATED,
+            Json(json!(photo.with_links(&user, state.config()))),
+        )),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::StorageError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn delete_pupil_photo(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<StatusCode> {
+    tracing::debug!("removing the photo for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let photo = PupilPhoto::for_pupil(&user, pupil_id, state.database()).await?;
+    match photo
+        .remove(state.blob_store().as_ref(), state.database())
+        .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::PhotoDoesNotExist => Err(PhotoDoesNotExist!()),
+            ErrorKind::StorageError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+pub async fn set_photo_consent(
+    State(state): State<AppState>,
+    Path(pupil_id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(consent): Json<PhotoConsent>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("setting photo consent for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let photo = PupilPhoto::for_pupil(&user, pupil_id, state.database()).await?;
+    match photo.set_consent(&user, consent, state.database()).await {
+        Ok(photo) => Ok(Json(json!(photo.with_links(&user, state.config())))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Fetch a photo through a signed link, for `<img>` tags. Like attachment downloads this sits
+/// outside the token check, and consent is checked again in case it was withdrawn since.
+pub async fn download_photo(
+    State(state): State<AppState>,
+    Path((pupil_id, size)): Path<(String, String)>,
+    Query(link): Query<SignedLink>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("downloading the {size} photo for pupil {pupil_id}");
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let size = Size::parse(&size)?;
+    link.verify(
+        resource(pupil_id, size),
+        Utc::now().naive_utc(),
+        state.config(),
+    )?;
+    let user = User::one_from_db(&link.user, state.database()).await?;
+    let photo = PupilPhoto::for_pupil(&user, pupil_id, state.database()).await?;
+    let data = photo.data(size, state.blob_store().as_ref()).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, CONTENT_TYPE.to_owned()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".into()),
+            (
+                header::CACHE_CONTROL,
+                format!("private, max-age={}", state.config().signed_url_seconds),
+            ),
+        ],
+        data,
+    ))
+}
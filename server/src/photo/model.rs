@@ -0,0 +1,370 @@
+use crate::{
+    app::config::Config,
+    attachment::{signed::SignedLink, store::BlobStore},
+    core::error::Result,
+    photo::resize::Resized,
+    pupil::model::Pupil,
+    user::model::User,
+};
+use base64::{engine::general_purpose, Engine};
+use chrono::{NaiveDateTime, Utc};
+use entity::pupil_photo::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+pub const CONTENT_TYPE: &str = "image/jpeg";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Size {
+    Thumbnail,
+    Medium,
+}
+
+impl Size {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Size::Thumbnail => "thumbnail",
+            Size::Medium => "medium",
+        }
+    }
+
+    pub fn parse(size: &str) -> Result<Self> {
+        match size {
+            "thumbnail" => Ok(Size::Thumbnail),
+            "medium" => Ok(Size::Medium),
+            _ => Err(PhotoDoesNotExist!()),
+        }
+    }
+}
+
+/// Where a pupil's photo can be fetched from. The links are signed for the user the pupil was sent
+/// to, since an `<img>` can't send the token.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PhotoLinks {
+    pub(crate) thumbnail: String,
+    pub(crate) medium: String,
+}
+
+impl PhotoLinks {
+    pub fn new(pupil_id: Uuid, user: &User, now: NaiveDateTime, config: &Config) -> Self {
+        let link = |size: Size| {
+            let resource = resource(pupil_id, size);
+            SignedLink::new(&resource, user, now, config).url(&resource)
+        };
+        Self {
+            thumbnail: link(Size::Thumbnail),
+            medium: link(Size::Medium),
+        }
+    }
+}
+
+/// The path a photo is downloaded from under `/api/files`, which is also what its link signs.
+pub fn resource(pupil_id: Uuid, size: Size) -> String {
+    format!("photos/{pupil_id}/{}", size.as_str())
+}
+
+/// A pupil's photo and whether they, or their parents, have agreed to it being shown. A pupil with
+/// no row has no photo and hasn't withdrawn consent. Withdrawing consent hides the photo
+/// everywhere without removing it, so it comes back if consent is given again.
+#[derive(Clone, Debug, Serialize, PartialEq, Default)]
+pub struct PupilPhoto {
+    pub(crate) pupil_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) uploaded_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) uploaded_at: Option<NaiveDateTime>,
+    pub(crate) consent_withdrawn: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) consent_updated_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) consent_updated_at: Option<NaiveDateTime>,
+    /// only filled in when the photo can be shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) links: Option<PhotoLinks>,
+}
+
+impl PupilPhoto {
+    /// The photo record for a pupil the user can see.
+    pub async fn for_pupil(user: &User, pupil_id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        Ok(match Entity::find_by_id(pupil_id).one(db).await? {
+            Some(photo) => photo.into(),
+            None => Self {
+                pupil_id,
+                ..Default::default()
+            },
+        })
+    }
+
+    pub fn visible(&self) -> bool {
+        self.uploaded_at.is_some() && !self.consent_withdrawn
+    }
+
+    pub fn with_links(mut self, user: &User, config: &Config) -> Self {
+        if self.visible() {
+            self.links = Some(PhotoLinks::new(
+                self.pupil_id,
+                user,
+                Utc::now().naive_utc(),
+                config,
+            ));
+        }
+        self
+    }
+
+    pub fn storage_key(&self, size: Size) -> String {
+        format!("photos/{}/{}.jpg", self.pupil_id, size.as_str())
+    }
+
+    /// Store both sizes, replacing any earlier photo, then record who uploaded it.
+    pub async fn save(
+        mut self,
+        user: &User,
+        resized: &Resized,
+        store: &dyn BlobStore,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        tracing::debug!("storing photo for pupil {}", self.pupil_id);
+        store
+            .put(
+                &self.storage_key(Size::Thumbnail),
+                CONTENT_TYPE,
+                &resized.thumbnail,
+            )
+            .await?;
+        store
+            .put(
+                &self.storage_key(Size::Medium),
+                CONTENT_TYPE,
+                &resized.medium,
+            )
+            .await?;
+        self.uploaded_by = Some(user.email_address.clone());
+        self.uploaded_at = Some(Utc::now().naive_utc());
+        self.upsert(db).await?;
+        Ok(self)
+    }
+
+    pub async fn data(&self, size: Size, store: &dyn BlobStore) -> Result<Vec<u8>> {
+        if !self.visible() {
+            return Err(PhotoDoesNotExist!());
+        }
+        store.get(&self.storage_key(size)).await
+    }
+
+    /// Remove the photo but keep the consent decision.
+    pub async fn remove(mut self, store: &dyn BlobStore, db: &DatabaseConnection) -> Result<Self> {
+        if self.uploaded_at.is_none() {
+            return Err(PhotoDoesNotExist!());
+        }
+        self.uploaded_by = None;
+        self.uploaded_at = None;
+        self.upsert(db).await?;
+        store.delete(&self.storage_key(Size::Thumbnail)).await?;
+        store.delete(&self.storage_key(Size::Medium)).await?;
+        Ok(self)
+    }
+
+    pub async fn set_consent(
+        mut self,
+        user: &User,
+        consent: PhotoConsent,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        tracing::debug!(
+            "{} set photo consent withdrawn to {} for pupil {}",
+            user.email_address,
+            consent.withdrawn,
+            self.pupil_id
+        );
+        self.consent_withdrawn = consent.withdrawn;
+        self.consent_updated_by = Some(user.email_address.clone());
+        self.consent_updated_at = Some(Utc::now().naive_utc());
+        self.upsert(db).await?;
+        Ok(self)
+    }
+
+    async fn upsert<C: ConnectionTrait>(&self, db: &C) -> Result<()> {
+        Entity::insert(ActiveModel::from(Model::from(self.clone())))
+            .on_conflict(
+                OnConflict::column(Column::PupilId)
+                    .update_columns([
+                        Column::UploadedBy,
+                        Column::UploadedAt,
+                        Column::ConsentWithdrawn,
+                        Column::ConsentUpdatedBy,
+                        Column::ConsentUpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Move a merged pupil's photo onto the pupil they were merged into, unless that pupil already
+    /// has one of their own. Consent withdrawn on either record stays withdrawn. Returns the keys
+    /// of the merged pupil's files, to remove once the merge has been committed.
+    pub(crate) async fn move_to<C: ConnectionTrait>(
+        from: Uuid,
+        to: Uuid,
+        store: &dyn BlobStore,
+        db: &C,
+    ) -> Result<Vec<String>> {
+        let Some(removed) = Entity::find_by_id(from).one(db).await?.map(Self::from) else {
+            return Ok(vec![]);
+        };
+        let mut kept = match Entity::find_by_id(to).one(db).await? {
+            Some(photo) => Self::from(photo),
+            None => Self {
+                pupil_id: to,
+                ..Default::default()
+            },
+        };
+        if kept.uploaded_at.is_none() && removed.uploaded_at.is_some() {
+            for size in [Size::Thumbnail, Size::Medium] {
+                let data = store.get(&removed.storage_key(size)).await?;
+                store.put(&kept.storage_key(size), CONTENT_TYPE, &data).await?;
+            }
+            kept.uploaded_by = removed.uploaded_by.clone();
+            kept.uploaded_at = removed.uploaded_at;
+        }
+        if removed.consent_withdrawn && !kept.consent_withdrawn {
+            kept.consent_withdrawn = true;
+            kept.consent_updated_by = removed.consent_updated_by.clone();
+            kept.consent_updated_at = removed.consent_updated_at;
+        }
+        Entity::delete_by_id(from).exec(db).await?;
+        kept.upsert(db).await?;
+        Ok(match removed.uploaded_at {
+            Some(_) => vec![
+                removed.storage_key(Size::Thumbnail),
+                removed.storage_key(Size::Medium),
+            ],
+            None => vec![],
+        })
+    }
+
+    /// Fill in photo links on pupils about to be sent to the client, leaving out anyone whose
+    /// consent has been withdrawn.
+    pub async fn flag_photos(
+        pupils: &mut [Pupil],
+        user: &User,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        let photos: HashSet<Uuid> = Entity::find()
+            .filter(Column::PupilId.is_in(pupils.iter().map(|p| p.id)))
+            .filter(Column::UploadedAt.is_not_null())
+            .filter(Column::ConsentWithdrawn.eq(false))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|photo| photo.pupil_id)
+            .collect();
+        let now = Utc::now().naive_utc();
+        for pupil in pupils {
+            pupil.photo = photos
+                .contains(&pupil.id)
+                .then(|| PhotoLinks::new(pupil.id, user, now, config));
+        }
+        Ok(())
+    }
+}
+
+impl From<Model> for PupilPhoto {
+    fn from(model: Model) -> Self {
+        Self {
+            pupil_id: model.pupil_id,
+            uploaded_by: model.uploaded_by,
+            uploaded_at: model.uploaded_at,
+            consent_withdrawn: model.consent_withdrawn,
+            consent_updated_by: model.consent_updated_by,
+            consent_updated_at: model.consent_updated_at,
+            links: None,
+        }
+    }
+}
+
+impl From<PupilPhoto> for Model {
+    fn from(photo: PupilPhoto) -> Self {
+        Self {
+            pupil_id: photo.pupil_id,
+            uploaded_by: photo.uploaded_by,
+            uploaded_at: photo.uploaded_at,
+            consent_withdrawn: photo.consent_withdrawn,
+            consent_updated_by: photo.consent_updated_by,
+            consent_updated_at: photo.consent_updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewPhoto {
+    /// base64 encoded JPEG or PNG
+    pub(crate) data: String,
+}
+
+impl NewPhoto {
+    pub fn decode(&self, max_bytes: usize) -> Result<Vec<u8>> {
+        let data = general_purpose::STANDARD.decode(self.data.trim())?;
+        if data.is_empty() || data.len() > max_bytes {
+            return Err(
+                ValidationError!("photo failed validation").with_fields(BTreeMap::from([(
+                    "data".into(),
+                    format!("photo must be between 1 and {max_bytes} bytes"),
+                )])),
+            );
+        }
+        Ok(data)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PhotoConsent {
+    pub(crate) withdrawn: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_photo_only_visible_with_consent() {
+        let mut photo = PupilPhoto::default();
+        assert!(!photo.visible());
+        photo.uploaded_at = Some(Utc::now().naive_utc());
+        assert!(photo.visible());
+        photo.consent_withdrawn = true;
+        assert!(!photo.visible());
+        let user = User::new("test", "user", "test@school.com", "pass", vec![6]);
+        assert_eq!(photo.with_links(&user, &Config::default()).links, None);
+    }
+
+    #[rstest]
+    fn test_links_are_signed_per_size() {
+        let id = Uuid::new_v4();
+        let user = User::new("test", "user", "test@school.com", "pass", vec![6]);
+        let config = Config::default();
+        let links = PhotoLinks::new(id, &user, Utc::now().naive_utc(), &config);
+        assert!(links
+            .thumbnail
+            .starts_with(&format!("/api/files/photos/{id}/thumbnail?")));
+        assert!(links
+            .medium
+            .starts_with(&format!("/api/files/photos/{id}/medium?")));
+    }
+
+    #[rstest]
+    fn test_size_parses_from_the_path() {
+        assert_eq!(Size::parse("thumbnail").unwrap(), Size::Thumbnail);
+        assert_eq!(Size::parse("medium").unwrap(), Size::Medium);
+        assert!(Size::parse("original").is_err());
+    }
+}
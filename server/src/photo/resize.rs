@@ -0,0 +1,236 @@
+use crate::core::error::{Error, Result};
+use image::{
+    codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+    io::{Limits, Reader},
+    ColorType, DynamicImage, ImageFormat, Rgb, RgbImage,
+};
+use std::{collections::BTreeMap, io::Cursor};
+
+/// Square crop shown next to names in lists
+pub const THUMBNAIL_SIZE: u32 = 96;
+/// Longest side of the photo shown on the pupil's details
+pub const MEDIUM_SIZE: u32 = 480;
+/// Anything bigger than this on either side is refused before it is decoded
+const MAX_DIMENSION: u32 = 10_000;
+const JPEG_QUALITY: u8 = 85;
+
+/// The two sizes kept for each photo, both re-encoded as JPEG. Nothing from the original file is
+/// carried over, so EXIF data such as location and camera details is dropped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resized {
+    pub thumbnail: Vec<u8>,
+    pub medium: Vec<u8>,
+}
+
+/// Decode an uploaded JPEG or PNG, turn it the right way up and make the thumbnail and medium
+/// sizes from it.
+pub fn resize(data: &[u8]) -> Result<Resized> {
+    let format = match image::guess_format(data) {
+        Ok(format @ (ImageFormat::Jpeg | ImageFormat::Png)) => format,
+        _ => return Err(invalid("the photo must be a JPEG or PNG image")),
+    };
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    let mut reader = Reader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let photo = reader
+        .decode()
+        .map_err(|_| invalid("the photo could not be read"))?;
+    let photo = match format {
+        ImageFormat::Jpeg => orient(photo, exif_orientation(data).unwrap_or(1)),
+        _ => photo,
+    };
+    let medium = if photo.width() > MEDIUM_SIZE || photo.height() > MEDIUM_SIZE {
+        photo.resize(MEDIUM_SIZE, MEDIUM_SIZE, FilterType::Triangle)
+    } else {
+        photo.clone()
+    };
+    let thumbnail = photo.resize_to_fill(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle);
+    Ok(Resized {
+        thumbnail: encode(&thumbnail)?,
+        medium: encode(&medium)?,
+    })
+}
+
+fn invalid(message: &str) -> Error {
+    ValidationError!("photo failed validation")
+        .with_fields(BTreeMap::from([("data".into(), message.into())]))
+}
+
+/// JPEG has no transparency, so see-through parts of a PNG go white rather than black.
+fn encode(photo: &DynamicImage) -> Result<Vec<u8>> {
+    let rgb = if photo.color().has_alpha() {
+        let rgba = photo.to_rgba8();
+        RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            let [r, g, b, a] = rgba.get_pixel(x, y).0;
+            let over_white = |c: u8| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+            Rgb([over_white(r), over_white(g), over_white(b)])
+        })
+    } else {
+        photo.to_rgb8()
+    };
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)
+        .encode(&rgb, rgb.width(), rgb.height(), ColorType::Rgb8)
+        .map_err(|error| ServerError!(error.to_string()))?;
+    Ok(out)
+}
+
+/// Phones save photos sideways and record which way up they go in the EXIF orientation tag.
+/// That tag is dropped along with the rest, so it is applied to the pixels first.
+fn orient(photo: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => photo.fliph(),
+        3 => photo.rotate180(),
+        4 => photo.flipv(),
+        5 => photo.rotate90().fliph(),
+        6 => photo.rotate90(),
+        7 => photo.rotate270().fliph(),
+        8 => photo.rotate270(),
+        _ => photo,
+    }
+}
+
+/// The orientation tag from a JPEG's EXIF segment, if there is one.
+fn exif_orientation(data: &[u8]) -> Option<u16> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut at = 2;
+    while at + 4 <= data.len() {
+        if data[at] != 0xFF {
+            return None;
+        }
+        let marker = data[at + 1];
+        // the image data starts, so there are no more metadata segments
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[at + 2], data[at + 3]]) as usize;
+        let segment = data.get(at + 4..at + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return tiff_orientation(&segment[6..]);
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+/// Look through the first IFD of the TIFF structure inside the EXIF segment for tag 0x0112.
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let big_endian = match tiff.get(0..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| {
+        tiff.get(at..at + 2).map(|b| match big_endian {
+            true => u16::from_be_bytes([b[0], b[1]]),
+            false => u16::from_le_bytes([b[0], b[1]]),
+        })
+    };
+    let u32_at = |at: usize| {
+        tiff.get(at..at + 4).map(|b| match big_endian {
+            true => u32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+            false => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        })
+    };
+    let ifd = u32_at(4)? as usize;
+    let entries = u16_at(ifd)? as usize;
+    (0..entries)
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| u16_at(entry) == Some(0x0112))
+        .and_then(|entry| u16_at(entry + 8))
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use image::{ImageOutputFormat, RgbaImage};
+    use rstest::*;
+
+    fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut out), ImageOutputFormat::Jpeg(90))
+            .unwrap();
+        out
+    }
+
+    /// A little endian EXIF segment with just an orientation tag, put straight after the SOI.
+    fn with_orientation(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend(8u32.to_le_bytes());
+        tiff.extend(1u16.to_le_bytes());
+        tiff.extend(0x0112u16.to_le_bytes());
+        tiff.extend(3u16.to_le_bytes());
+        tiff.extend(1u32.to_le_bytes());
+        tiff.extend(orientation.to_le_bytes());
+        tiff.extend([0, 0]);
+        tiff.extend(0u32.to_le_bytes());
+        let mut segment = b"Exif\0\0".to_vec();
+        segment.extend(tiff);
+        let mut out = jpeg[..2].to_vec();
+        out.extend([0xFF, 0xE1]);
+        out.extend(((segment.len() + 2) as u16).to_be_bytes());
+        out.extend(segment);
+        out.extend(&jpeg[2..]);
+        out
+    }
+
+    fn dimensions(data: &[u8]) -> (u32, u32) {
+        let photo = image::load_from_memory(data).unwrap();
+        (photo.width(), photo.height())
+    }
+
+    #[rstest]
+    fn test_large_photos_are_shrunk() {
+        let resized = resize(&jpeg(1200, 800)).unwrap();
+        assert_eq!(dimensions(&resized.thumbnail), (96, 96));
+        assert_eq!(dimensions(&resized.medium), (480, 320));
+    }
+
+    #[rstest]
+    fn test_small_photos_are_not_enlarged() {
+        let resized = resize(&jpeg(200, 100)).unwrap();
+        assert_eq!(dimensions(&resized.thumbnail), (96, 96));
+        assert_eq!(dimensions(&resized.medium), (200, 100));
+    }
+
+    #[rstest]
+    fn test_exif_is_applied_then_dropped() {
+        let original = with_orientation(&jpeg(200, 100), 6);
+        assert_eq!(exif_orientation(&original), Some(6));
+        let resized = resize(&original).unwrap();
+        assert_eq!(dimensions(&resized.medium), (100, 200));
+        for data in [&resized.thumbnail, &resized.medium] {
+            assert_eq!(exif_orientation(data), None);
+            assert!(!data.windows(4).any(|w| w == b"Exif"));
+        }
+    }
+
+    #[rstest]
+    fn test_transparent_png_goes_white() {
+        let mut out = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::new(50, 50))
+            .write_to(&mut Cursor::new(&mut out), ImageOutputFormat::Png)
+            .unwrap();
+        let resized = resize(&out).unwrap();
+        let medium = image::load_from_memory(&resized.medium).unwrap().to_rgb8();
+        assert!(medium.get_pixel(25, 25).0.iter().all(|c| *c > 240));
+    }
+
+    #[rstest]
+    #[case(b"GIF89a and the rest".to_vec())]
+    #[case(b"%PDF-1.7".to_vec())]
+    #[case(vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 0])]
+    fn test_other_files_are_refused(#[case] data: Vec<u8>) {
+        let error = resize(&data).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert!(error.fields.unwrap().contains_key("data"));
+    }
+}
@@ -1,2 +1,5 @@
+pub mod export;
 pub mod handlers;
+pub mod import;
 pub mod model;
+pub mod validation;
@@ -0,0 +1,482 @@
+use crate::{
+    core::error::Result,
+    pupil::{import::Format, model::Pupil},
+};
+use chrono::{Datelike, NaiveDate};
+use rust_xlsxwriter::{ExcelDateTime, Workbook, XlsxError};
+use serde::{Deserialize, Serialize};
+
+/// The same filters the pupil list applies on the client, so an export holds what's on screen.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    Active,
+    Inactive,
+    Mat,
+    Aln,
+    Fsm,
+    Lac,
+    Eal,
+    PersistentAbsence,
+    InIntervention,
+    Name(String),
+    Year(i32),
+    /// work the flags out as they stood on this date rather than filtering
+    AsAt(NaiveDate),
+}
+
+impl Filter {
+    pub fn apply(&self, pupil: &Pupil) -> bool {
+        match self {
+            Filter::Active => pupil.active,
+            Filter::Inactive => !pupil.active,
+            Filter::Mat => pupil.more_able_and_talented,
+            Filter::Aln => pupil.additional_learning_needs,
+            Filter::Fsm => pupil.free_school_meals,
+            Filter::Lac => pupil.looked_after_child,
+            Filter::Eal => pupil.english_as_additional_language,
+            Filter::PersistentAbsence => pupil.persistent_absence,
+            Filter::InIntervention => pupil.in_intervention,
+            Filter::Year(year) => pupil.year == *year,
+            Filter::AsAt(_) => true,
+            Filter::Name(name) => {
+                format!("{} {}", pupil.first_names, pupil.last_name).contains(name)
+            }
+        }
+    }
+}
+
+/// The columns an export can hold, including the flags worked out from other records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Upn,
+    FirstNames,
+    LastName,
+    PreferredFirstNames,
+    PreferredLastName,
+    Year,
+    Gender,
+    DateOfBirth,
+    StartDate,
+    EndDate,
+    Active,
+    HomeLanguage,
+    FreeSchoolMeals,
+    LookedAfterChild,
+    MoreAbleAndTalented,
+    EnglishAsAdditionalLanguage,
+    EalStage,
+    AdditionalLearningNeeds,
+    PersistentAbsence,
+    InIntervention,
+}
+
+/// A cell, kept typed until it's written so XLSX gets real numbers and dates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(i32),
+    Date(NaiveDate),
+    Bool(bool),
+    Empty,
+}
+
+impl Value {
+    /// Booleans are written as Y or N so the file can be imported again.
+    fn text(&self) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+            Value::Date(date) => date.to_string(),
+            Value::Bool(true) => "Y".to_owned(),
+            Value::Bool(false) => "N".to_owned(),
+            Value::Empty => String::new(),
+        }
+    }
+
+    /// Free text starting with `=`, `+`, `-` or `@` would be run as a formula when the CSV is
+    /// opened in a spreadsheet, so it's prefixed with `'` to keep it as text.
+    fn csv_text(&self) -> String {
+        match self {
+            Value::Text(text) if text.starts_with(['=', '+', '-', '@']) => format!("'{text}"),
+            other => other.text(),
+        }
+    }
+}
+
+impl Column {
+    pub const ALL: [Column; 20] = [
+        Column::Upn,
+        Column::FirstNames,
+        Column::LastName,
+        Column::PreferredFirstNames,
+        Column::PreferredLastName,
+        Column::Year,
+        Column::Gender,
+        Column::DateOfBirth,
+        Column::StartDate,
+        Column::EndDate,
+        Column::Active,
+        Column::HomeLanguage,
+        Column::FreeSchoolMeals,
+        Column::LookedAfterChild,
+        Column::MoreAbleAndTalented,
+        Column::EnglishAsAdditionalLanguage,
+        Column::EalStage,
+        Column::AdditionalLearningNeeds,
+        Column::PersistentAbsence,
+        Column::InIntervention,
+    ];
+
+    /// The headings the importer recognises, where it has one
+    pub fn heading(&self) -> &'static str {
+        match self {
+            Column::Upn => "UPN",
+            Column::FirstNames => "First names",
+            Column::LastName => "Last name",
+            Column::PreferredFirstNames => "Preferred first names",
+            Column::PreferredLastName => "Preferred last name",
+            Column::Year => "Year",
+            Column::Gender => "Gender",
+            Column::DateOfBirth => "Date of birth",
+            Column::StartDate => "Start date",
+            Column::EndDate => "Leave date",
+            Column::Active => "Active",
+            Column::HomeLanguage => "Home language",
+            Column::FreeSchoolMeals => "FSM",
+            Column::LookedAfterChild => "LAC",
+            Column::MoreAbleAndTalented => "MAT",
+            Column::EnglishAsAdditionalLanguage => "EAL",
+            Column::EalStage => "EAL stage",
+            Column::AdditionalLearningNeeds => "ALN",
+            Column::PersistentAbsence => "Persistent absence",
+            Column::InIntervention => "In intervention",
+        }
+    }
+
+    pub fn value(&self, pupil: &Pupil) -> Value {
+        let text = |text: &Option<String>| match text {
+            Some(text) => Value::Text(text.clone()),
+            None => Value::Empty,
+        };
+        match self {
+            Column::Upn => text(&pupil.upn),
+            Column::FirstNames => Value::Text(pupil.first_names.clone()),
+            Column::LastName => Value::Text(pupil.last_name.clone()),
+            Column::PreferredFirstNames => text(&pupil.preferred_first_names),
+            Column::PreferredLastName => text(&pupil.preferred_last_name),
+            Column::Year => Value::Number(pupil.year),
+            Column::Gender => Value::Text(pupil.gender.clone()),
+            Column::DateOfBirth => pupil.date_of_birth.map_or(Value::Empty, Value::Date),
+            Column::StartDate => Value::Date(pupil.start_date),
+            Column::EndDate => pupil.end_date.map_or(Value::Empty, Value::Date),
+            Column::Active => Value::Bool(pupil.active),
+            Column::HomeLanguage => text(&pupil.home_language),
+            Column::FreeSchoolMeals => Value::Bool(pupil.free_school_meals),
+            Column::LookedAfterChild => Value::Bool(pupil.looked_after_child),
+            Column::MoreAbleAndTalented => Value::Bool(pupil.more_able_and_talented),
+            Column::EnglishAsAdditionalLanguage => {
+                Value::Bool(pupil.english_as_additional_language)
+            }
+            Column::EalStage => pupil
+                .eal_stage
+                .map_or(Value::Empty, |stage| Value::Text(stage.as_str().to_owned())),
+            Column::AdditionalLearningNeeds => Value::Bool(pupil.additional_learning_needs),
+            Column::PersistentAbsence => Value::Bool(pupil.persistent_absence),
+            Column::InIntervention => Value::Bool(pupil.in_intervention),
+        }
+    }
+}
+
+/// A download of the pupil list. With no columns chosen every column is included.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PupilExport {
+    pub(crate) format: Format,
+    #[serde(default)]
+    pub(crate) filters: Vec<Filter>,
+    #[serde(default)]
+    pub(crate) columns: Vec<Column>,
+}
+
+impl PupilExport {
+    /// The date the flags should be worked out for, if not today
+    pub fn as_at(&self) -> Option<NaiveDate> {
+        self.filters.iter().find_map(|filter| match filter {
+            Filter::AsAt(date) => Some(*date),
+            _ => None,
+        })
+    }
+
+    fn columns(&self) -> &[Column] {
+        if self.columns.is_empty() {
+            &Column::ALL
+        } else {
+            &self.columns
+        }
+    }
+
+    /// Keeps the pupils every filter lets through, in year then name order.
+    pub fn select(&self, mut pupils: Vec<Pupil>) -> Vec<Pupil> {
+        pupils.retain(|pupil| self.filters.iter().all(|filter| filter.apply(pupil)));
+        pupils.sort_by(|a, b| {
+            (a.year, &a.last_name, &a.first_names).cmp(&(b.year, &b.last_name, &b.first_names))
+        });
+        pupils
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self.format {
+            Format::Csv => "text/csv; charset=utf-8",
+            Format::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    pub fn file_name(&self, date: NaiveDate) -> String {
+        match self.format {
+            Format::Csv => format!("pupils-{date}.csv"),
+            Format::Xlsx => format!("pupils-{date}.xlsx"),
+        }
+    }
+
+    /// The whole file at once. A CSV can also be sent a row at a time with `csv_lines`, but an
+    /// XLSX is a ZIP that can only be written once every row is known.
+    pub fn write(&self, pupils: &[Pupil]) -> Result<Vec<u8>> {
+        match self.format {
+            Format::Csv => self
+                .csv_lines(pupils.to_vec())
+                .collect::<Result<Vec<_>>>()
+                .map(|lines| lines.concat()),
+            Format::Xlsx => {
+                let rows = pupils
+                    .iter()
+                    .map(|pupil| self.columns().iter().map(|column| column.value(pupil)));
+                self.write_xlsx(rows).map_err(|error: XlsxError| {
+                    ServerError!(format!("failed to write pupil export: {error}"))
+                })
+            }
+        }
+    }
+
+    /// The CSV heading then one line per pupil, each encoded on its own so the response can be
+    /// streamed.
+    pub fn csv_lines(&self, pupils: Vec<Pupil>) -> impl Iterator<Item = Result<Vec<u8>>> {
+        let columns = self.columns().to_vec();
+        let heading = csv_line(columns.iter().map(|column| column.heading()));
+        std::iter::once(heading).chain(pupils.into_iter().map(move |pupil| {
+            csv_line(columns.iter().map(|column| column.value(&pupil).csv_text()))
+        }))
+    }
+
+    fn write_xlsx<R, V>(&self, rows: R) -> std::result::Result<Vec<u8>, XlsxError>
+    where
+        R: Iterator<Item = V>,
+        V: Iterator<Item = Value>,
+    {
+        let mut workbook = Workbook::new();
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        let date = rust_xlsxwriter::Format::new().set_num_format("dd/mm/yyyy");
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Pupils")?;
+        for (col, column) in self.columns().iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, column.heading(), &bold)?;
+        }
+        for (row, values) in rows.enumerate() {
+            let row = row as u32 + 1;
+            for (col, value) in values.enumerate() {
+                let col = col as u16;
+                match value {
+                    Value::Number(number) => sheet.write_number(row, col, number)?,
+                    Value::Date(day) => {
+                        let day = ExcelDateTime::from_ymd(
+                            day.year() as u16,
+                            day.month() as u8,
+                            day.day() as u8,
+                        )?;
+                        sheet.write_date_with_format(row, col, &day, &date)?
+                    }
+                    Value::Empty => continue,
+                    // always a string cell, so text that looks like a formula is never run
+                    Value::Text(text) => sheet.write_string(row, col, text)?,
+                    other => sheet.write_string(row, col, other.text())?,
+                };
+            }
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+        workbook.save_to_buffer()
+    }
+}
+
+fn csv_line<I, T>(fields: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer
+        .write_record(fields)
+        .map_err(|error| ServerError!(format!("failed to write pupil export: {error}")))?;
+    writer
+        .into_inner()
+        .map_err(|error| ServerError!(format!("failed to write pupil export: {error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pupil::import::blank;
+    use calamine::{open_workbook_from_rs, Data, Reader, Xlsx};
+    use rstest::*;
+    use std::io::Cursor;
+
+    fn pupils() -> Vec<Pupil> {
+        let mut first = blank("H801200001001");
+        first.first_names = "Ann".to_owned();
+        first.last_name = "Smith".to_owned();
+        first.year = 6;
+        first.free_school_meals = true;
+        first.start_date = "2021-09-01".parse().unwrap();
+        let mut second = blank("X801200001002");
+        second.first_names = "Bob".to_owned();
+        second.last_name = "Jones".to_owned();
+        second.year = 6;
+        second.start_date = "2020-09-01".parse().unwrap();
+        let mut third = blank("L801200001003");
+        third.first_names = "Cat".to_owned();
+        third.last_name = "Brown".to_owned();
+        third.year = 2;
+        third.active = false;
+        vec![first, second, third]
+    }
+
+    fn export(format: Format, filters: Vec<Filter>, columns: Vec<Column>) -> PupilExport {
+        PupilExport {
+            format,
+            filters,
+            columns,
+        }
+    }
+
+    #[rstest]
+    fn test_filters_match_the_client() {
+        let all = export(Format::Csv, vec![], vec![]).select(pupils());
+        let names: Vec<_> = all.iter().map(|p| p.first_names.as_str()).collect();
+        assert_eq!(names, ["Cat", "Bob", "Ann"]);
+
+        let filtered = export(
+            Format::Csv,
+            vec![
+                Filter::Active,
+                Filter::Year(6),
+                Filter::Name("nn Sm".to_owned()),
+            ],
+            vec![],
+        )
+        .select(pupils());
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].first_names, "Ann");
+
+        let filters: Vec<Filter> =
+            serde_json::from_str(r#"["fsm", {"year": 6}, {"as_at": "2023-01-01"}]"#).unwrap();
+        let fsm = export(Format::Csv, filters, vec![]);
+        assert_eq!(fsm.as_at(), Some("2023-01-01".parse().unwrap()));
+        assert_eq!(fsm.select(pupils()).len(), 1);
+    }
+
+    #[rstest]
+    fn test_write_csv_with_chosen_columns() {
+        let export = export(
+            Format::Csv,
+            vec![Filter::Year(6)],
+            vec![
+                Column::Upn,
+                Column::LastName,
+                Column::StartDate,
+                Column::FreeSchoolMeals,
+            ],
+        );
+        let data = export.write(&export.select(pupils())).unwrap();
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "UPN,Last name,Start date,FSM\n\
+             X801200001002,Jones,2020-09-01,N\n\
+             H801200001001,Smith,2021-09-01,Y\n"
+        );
+    }
+
+    #[rstest]
+    fn test_csv_lines_are_one_per_pupil() {
+        let export = export(
+            Format::Csv,
+            vec![Filter::Year(6)],
+            vec![Column::LastName, Column::Year],
+        );
+        let lines: Vec<String> = export
+            .csv_lines(export.select(pupils()))
+            .map(|line| String::from_utf8(line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(lines, ["Last name,Year\n", "Jones,6\n", "Smith,6\n"]);
+    }
+
+    #[rstest]
+    fn test_write_xlsx() {
+        let export = export(Format::Xlsx, vec![], vec![Column::LastName, Column::Year]);
+        let data = export.write(&export.select(pupils())).unwrap();
+        let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(data)).unwrap();
+        let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+        assert_eq!(
+            range.get((0, 0)),
+            Some(&Data::String("Last name".to_owned()))
+        );
+        assert_eq!(range.get((1, 0)), Some(&Data::String("Brown".to_owned())));
+        assert_eq!(range.get((3, 1)), Some(&Data::Float(6.0)));
+    }
+
+    fn formula_pupils() -> Vec<Pupil> {
+        let mut pupils = pupils();
+        pupils[0].first_names = "=HYPERLINK(\"http://example.com\",\"Ann\")".to_owned();
+        pupils[0].home_language = Some("@SUM(1+1)".to_owned());
+        pupils[1].preferred_last_name = Some("-Jones".to_owned());
+        pupils
+    }
+
+    #[rstest]
+    fn test_csv_text_that_looks_like_a_formula_is_kept_as_text() {
+        let export = export(
+            Format::Csv,
+            vec![Filter::Year(6)],
+            vec![
+                Column::FirstNames,
+                Column::PreferredLastName,
+                Column::HomeLanguage,
+                Column::Year,
+            ],
+        );
+        let data = export.write(&export.select(formula_pupils())).unwrap();
+        assert_eq!(
+            String::from_utf8(data).unwrap(),
+            "First names,Preferred last name,Home language,Year\n\
+             Bob,'-Jones,,6\n\
+             \"'=HYPERLINK(\"\"http://example.com\"\",\"\"Ann\"\")\",,'@SUM(1+1),6\n"
+        );
+    }
+
+    #[rstest]
+    fn test_xlsx_text_that_looks_like_a_formula_is_a_string() {
+        let export = export(Format::Xlsx, vec![], vec![Column::FirstNames]);
+        let data = export.write(&export.select(formula_pupils())).unwrap();
+        let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(data)).unwrap();
+        let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+        assert_eq!(
+            range.get((3, 0)),
+            Some(&Data::String(
+                "=HYPERLINK(\"http://example.com\",\"Ann\")".to_owned()
+            ))
+        );
+        let formulas = workbook.worksheet_formula("Pupils").unwrap();
+        assert!(formulas
+            .used_cells()
+            .all(|(_, _, formula)| formula.is_empty()));
+    }
+}
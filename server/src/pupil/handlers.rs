@@ -1,20 +1,64 @@
 use std::str::FromStr;
 
-use crate::{app::state::AppState, core::error::*, pupil::model::*, user::model::*};
+use crate::{
+    app::state::AppState,
+    attendance::summary::AttendanceSummary,
+    core::error::*,
+    eal::model::EalAssessment,
+    flag::model::*,
+    intervention::model::Intervention,
+    medical::model::MedicalItem,
+    photo::model::PupilPhoto,
+    pupil::{
+        export::PupilExport,
+        import::{Format, PupilImport},
+        model::*,
+    },
+    user::model::*,
+};
 use axum::{
-    extract::{Json, Path, State},
-    http::StatusCode,
+    body::{boxed, Body},
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Extension,
 };
+use chrono::{NaiveDate, Utc};
+use sea_orm::TransactionTrait;
 use serde_json::json;
 use uuid::Uuid;
 
+/// Fills in the fields that come from other records rather than the pupil's own row.
+async fn work_out_derived(
+    pupils: &mut [Pupil],
+    user: &User,
+    date: NaiveDate,
+    state: &AppState,
+) -> Result<()> {
+    FlagPeriod::flag_as_at(pupils, date, state.database()).await?;
+    AttendanceSummary::flag_persistent_absence(pupils, state.config(), date, state.database())
+        .await?;
+    Intervention::flag_active_members(pupils, date, state.database()).await?;
+    EalAssessment::flag_current_stage(pupils, state.database()).await?;
+    MedicalItem::flag_alerts(pupils, state.database()).await?;
+    PupilPhoto::flag_photos(pupils, user, state.config(), state.database()).await?;
+    Ok(())
+}
+
 pub async fn create_pupil(
     State(state): State<AppState>,
     Json(pupil): Json<Pupil>,
 ) -> Result<StatusCode> {
-    match pupil.insert(state.database().as_ref()).await {
-        Ok(_) => Ok(StatusCode::CREATED),
+    pupil.validate(state.config())?;
+    pupil.validate_unique(state.database()).await?;
+    // the census and gap analysis read the flag periods, so they must agree with the pupil row
+    let txn = state.database().begin().await?;
+    match pupil.insert(&txn).await {
+        Ok(pupil) => {
+            FlagPeriod::follow_pupil(&txn, None, &pupil, None).await?;
+            txn.commit().await?;
+            Ok(StatusCode::CREATED)
+        }
         Err(error) => match error.kind {
             ErrorKind::DatabaseError => Err(DatabaseError!()),
             _ => Err(UnknownError!()),
@@ -22,13 +66,19 @@ pub async fn create_pupil(
     }
 }
 
+/// Pass `?as_at=` to see the pupils' flags as they stood on another day.
 pub async fn get_pupils(
     State(state): State<AppState>,
+    Query(as_at): Query<AsAt>,
     Extension(user): Extension<User>,
 ) -> Result<Json<serde_json::Value>> {
     tracing::debug!("requested all pupils");
+    let date = as_at.date();
     match Pupil::all_from_db(&user, state.database().as_ref()).await {
-        Ok(pupils) => Ok(Json(json!(pupils))),
+        Ok(mut pupils) => {
+            work_out_derived(&mut pupils, &user, date, &state).await?;
+            Ok(Json(json!(pupils)))
+        }
         Err(error) => match error.kind {
             ErrorKind::DatabaseError => Err(DatabaseError!()),
             _ => Err(UnknownError!()),
@@ -39,12 +89,18 @@ pub async fn get_pupils(
 pub async fn get_pupil_by_id(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(as_at): Query<AsAt>,
     Extension(user): Extension<User>,
 ) -> Result<Json<serde_json::Value>> {
     tracing::debug!("requested pupil {id}");
     let id = Uuid::from_str(&id)?;
+    let date = as_at.date();
     match Pupil::one_from_db(&user, id, state.database().as_ref()).await {
-        Ok(pupil) => Ok(Json(json!(pupil))),
+        Ok(pupil) => {
+            let mut pupils = [pupil];
+            work_out_derived(&mut pupils, &user, date, &state).await?;
+            Ok(Json(json!(pupils[0])))
+        }
         Err(error) => match error.kind {
             ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
             ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
@@ -61,10 +117,20 @@ pub async fn update_pupil(
 ) -> Result<Json<serde_json::Value>> {
     tracing::debug!("updating pupil {id}");
     let id = Uuid::from_str(&id)?;
-    let mut pupil = Pupil::one_from_db(&user, id, state.database()).await?;
-    pupil.set_from_update(update);
-    match pupil.update(state.database().as_ref()).await {
-        Ok(pupil) => Ok(Json(json!(pupil))),
+    let mut pupils = [Pupil::one_from_db(&user, id, state.database()).await?];
+    FlagPeriod::flag_as_at(&mut pupils, Utc::now().date_naive(), state.database()).await?;
+    let [before] = pupils;
+    let mut pupil = before.clone();
+    pupil.set_from_update(update)?;
+    pupil.validate(state.config())?;
+    pupil.validate_unique(state.database()).await?;
+    let txn = state.database().begin().await?;
+    match pupil.update(&txn).await {
+        Ok(pupil) => {
+            FlagPeriod::follow_pupil(&txn, Some(&before), &pupil, Some(&user)).await?;
+            txn.commit().await?;
+            Ok(Json(json!(pupil)))
+        }
         Err(error) => match error.kind {
             ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
             ErrorKind::PupilDoesNotExist => Err(PupilDoesNotExist!()),
@@ -73,6 +139,75 @@ pub async fn update_pupil(
     }
 }
 
+/// Bring in a CSV or XLSX file of pupils, or with `dry_run` just see what it would do. The report
+/// comes back either way, with `committed` saying whether anything was saved.
+pub async fn import_pupils(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(import): Json<PupilImport>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("importing pupils, dry run: {}", import.dry_run);
+    match import
+        .run(&user, state.config(), state.database().as_ref())
+        .await
+    {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            ErrorKind::ValidationError | ErrorKind::DecodeError => Err(error),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
+/// Download the pupil list as CSV or XLSX. It takes the same filters as the list on the client,
+/// including `as_at`, and only holds pupils in the user's years.
+pub async fn export_pupils(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(export): Json<PupilExport>,
+) -> Result<Response> {
+    tracing::debug!("exporting pupils as {:?}", export.format);
+    let today = Utc::now().date_naive();
+    let mut pupils = Pupil::all_from_db(&user, state.database()).await?;
+    work_out_derived(&mut pupils, &user, export.as_at().unwrap_or(today), &state).await?;
+    let pupils = export.select(pupils);
+    let headers = [
+        (header::CONTENT_TYPE, export.content_type().to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", export.file_name(today)),
+        ),
+        (header::CACHE_CONTROL, "no-store".to_owned()),
+    ];
+    match export.format {
+        Format::Csv => {
+            let (mut sender, body) = Body::channel();
+            let lines = export.csv_lines(pupils);
+            tokio::spawn(async move {
+                for line in lines {
+                    match line {
+                        Ok(line) => {
+                            if sender.send_data(line.into()).await.is_err() {
+                                // the client went away
+                                return;
+                            }
+                        }
+                        Err(error) => {
+                            tracing::error!("stopped streaming pupil export: {error}");
+                            // so the client sees a failed download rather than a short file
+                            sender.abort();
+                            return;
+                        }
+                    }
+                }
+            });
+            Ok((headers, boxed(body)).into_response())
+        }
+        Format::Xlsx => Ok((headers, export.write(&pupils)?).into_response()),
+    }
+}
+
 pub async fn delete_pupil(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -0,0 +1,668 @@
+use crate::{
+    app::config::Config,
+    core::{constant, error::Result},
+    flag::model::FlagPeriod,
+    pupil::model::Pupil,
+    user::model::User,
+};
+use base64::{engine::general_purpose, Engine};
+use calamine::{open_workbook_from_rs, Data, Reader, Xlsx, XlsxError};
+use chrono::{Duration, NaiveDate, Utc};
+use entity::pupil::{Column, Entity};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::Cursor,
+};
+use uuid::Uuid;
+
+/// The pupil fields a spreadsheet column can be mapped onto. EAL and ALN aren't here, they follow
+/// EAL assessments and the ALN register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Upn,
+    FirstNames,
+    LastName,
+    Year,
+    Gender,
+    StartDate,
+    EndDate,
+    Active,
+    DateOfBirth,
+    PreferredFirstNames,
+    PreferredLastName,
+    HomeLanguage,
+    MoreAbleAndTalented,
+    FreeSchoolMeals,
+    LookedAfterChild,
+}
+
+impl Field {
+    pub const ALL: [Field; 15] = [
+        Field::Upn,
+        Field::FirstNames,
+        Field::LastName,
+        Field::Year,
+        Field::Gender,
+        Field::StartDate,
+        Field::EndDate,
+        Field::Active,
+        Field::DateOfBirth,
+        Field::PreferredFirstNames,
+        Field::PreferredLastName,
+        Field::HomeLanguage,
+        Field::MoreAbleAndTalented,
+        Field::FreeSchoolMeals,
+        Field::LookedAfterChild,
+    ];
+
+    /// Every file has to have a column for these, new pupils can't be made without them.
+    pub const REQUIRED: [Field; 6] = [
+        Field::Upn,
+        Field::FirstNames,
+        Field::LastName,
+        Field::Year,
+        Field::Gender,
+        Field::StartDate,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Upn => "upn",
+            Field::FirstNames => "first_names",
+            Field::LastName => "last_name",
+            Field::Year => "year",
+            Field::Gender => "gender",
+            Field::StartDate => "start_date",
+            Field::EndDate => "end_date",
+            Field::Active => "active",
+            Field::DateOfBirth => "date_of_birth",
+            Field::PreferredFirstNames => "preferred_first_names",
+            Field::PreferredLastName => "preferred_last_name",
+            Field::HomeLanguage => "home_language",
+            Field::MoreAbleAndTalented => "more_able_and_talented",
+            Field::FreeSchoolMeals => "free_school_meals",
+            Field::LookedAfterChild => "looked_after_child",
+        }
+    }
+
+    /// Column headings the usual MIS exports use for the field, lowercase with anything other than
+    /// letters and digits taken out.
+    fn headings(&self) -> &'static [&'static str] {
+        match self {
+            Field::Upn => &["upn", "uniquepupilnumber"],
+            Field::FirstNames => &[
+                "firstnames",
+                "firstname",
+                "forename",
+                "forenames",
+                "legalforename",
+            ],
+            Field::LastName => &["lastname", "surname", "legalsurname", "familyname"],
+            Field::Year => &["year", "yeargroup", "ncyear", "ncyearactual"],
+            Field::Gender => &["gender", "sex"],
+            Field::StartDate => &["startdate", "entrydate", "admissiondate", "dateofentry"],
+            Field::EndDate => &["enddate", "leavedate", "leavingdate", "dateofleaving"],
+            Field::Active => &["active", "onroll"],
+            Field::DateOfBirth => &["dateofbirth", "dob", "birthdate"],
+            Field::PreferredFirstNames => {
+                &["preferredfirstnames", "preferredforename", "preferredname"]
+            }
+            Field::PreferredLastName => &["preferredlastname", "preferredsurname"],
+            Field::HomeLanguage => &["homelanguage", "firstlanguage"],
+            Field::MoreAbleAndTalented => &["mat", "moreableandtalented"],
+            Field::FreeSchoolMeals => &["fsm", "efsm", "freeschoolmeals"],
+            Field::LookedAfterChild => &["lac", "cla", "lookedafter", "lookedafterchild"],
+        }
+    }
+
+    pub(crate) fn value(&self, pupil: &Pupil) -> String {
+        fn date(value: Option<NaiveDate>) -> String {
+            value.map(|d| d.to_string()).unwrap_or_default()
+        }
+        match self {
+            Field::Upn => pupil.upn.clone().unwrap_or_default(),
+            Field::FirstNames => pupil.first_names.clone(),
+            Field::LastName => pupil.last_name.clone(),
+            Field::Year => pupil.year.to_string(),
+            Field::Gender => pupil.gender.clone(),
+            Field::StartDate => pupil.start_date.to_string(),
+            Field::EndDate => date(pupil.end_date),
+            Field::Active => pupil.active.to_string(),
+            Field::DateOfBirth => date(pupil.date_of_birth),
+            Field::PreferredFirstNames => pupil.preferred_first_names.clone().unwrap_or_default(),
+            Field::PreferredLastName => pupil.preferred_last_name.clone().unwrap_or_default(),
+            Field::HomeLanguage => pupil.home_language.clone().unwrap_or_default(),
+            Field::MoreAbleAndTalented => pupil.more_able_and_talented.to_string(),
+            Field::FreeSchoolMeals => pupil.free_school_meals.to_string(),
+            Field::LookedAfterChild => pupil.looked_after_child.to_string(),
+        }
+    }
+
+    /// Set the field on the pupil from a cell, saying what was wrong with the cell if it can't.
+    fn apply(&self, pupil: &mut Pupil, cell: &str) -> std::result::Result<(), String> {
+        let cell = cell.trim();
+        let optional = || (!cell.is_empty()).then(|| cell.to_owned());
+        if cell.is_empty() && Field::REQUIRED.contains(self) {
+            return Err(format!("{} is required", self.as_str().replace('_', " ")));
+        }
+        match self {
+            Field::Upn => pupil.upn = Some(cell.to_uppercase()),
+            Field::FirstNames => pupil.first_names = cell.to_owned(),
+            Field::LastName => pupil.last_name = cell.to_owned(),
+            Field::Year => pupil.year = parse_year(cell)?,
+            Field::Gender => pupil.gender = parse_gender(cell)?,
+            Field::StartDate => pupil.start_date = parse_date(cell)?,
+            Field::EndDate => pupil.end_date = optional().map(|d| parse_date(&d)).transpose()?,
+            Field::Active => pupil.active = parse_bool(cell)?,
+            Field::DateOfBirth => {
+                pupil.date_of_birth = optional().map(|d| parse_date(&d)).transpose()?
+            }
+            Field::PreferredFirstNames => pupil.preferred_first_names = optional(),
+            Field::PreferredLastName => pupil.preferred_last_name = optional(),
+            Field::HomeLanguage => pupil.home_language = optional(),
+            Field::MoreAbleAndTalented => pupil.more_able_and_talented = parse_bool(cell)?,
+            Field::FreeSchoolMeals => pupil.free_school_meals = parse_bool(cell)?,
+            Field::LookedAfterChild => pupil.looked_after_child = parse_bool(cell)?,
+        }
+        Ok(())
+    }
+}
+
+/// "6", "Year 6", "Y6" and "6.0" from a spreadsheet number cell
+fn parse_year(cell: &str) -> std::result::Result<i32, String> {
+    let digits = cell
+        .trim_start_matches(|c: char| c.is_alphabetic() || c.is_whitespace())
+        .trim_end_matches(".0");
+    digits
+        .parse()
+        .map_err(|_| format!("{cell} is not a year group"))
+}
+
+fn parse_gender(cell: &str) -> std::result::Result<String, String> {
+    let gender = match cell.to_lowercase().as_str() {
+        "f" | "female" | "girl" => "female",
+        "m" | "male" | "boy" => "male",
+        "o" | "other" | "x" => "other",
+        _ => {
+            return Err(format!(
+                "{cell} is not a gender, use one of {}",
+                constant::GENDERS.join(", ")
+            ))
+        }
+    };
+    Ok(gender.to_owned())
+}
+
+/// ISO dates, and the day first dates a UK spreadsheet shows
+fn parse_date(cell: &str) -> std::result::Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(cell, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(cell, "%d/%m/%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(cell, "%d-%m-%Y"))
+        .map_err(|_| format!("{cell} is not a date, use YYYY-MM-DD or DD/MM/YYYY"))
+}
+
+fn parse_bool(cell: &str) -> std::result::Result<bool, String> {
+    match cell.to_lowercase().as_str() {
+        "y" | "yes" | "true" | "t" | "1" => Ok(true),
+        "n" | "no" | "false" | "f" | "0" | "" => Ok(false),
+        _ => Err(format!("{cell} is not yes or no")),
+    }
+}
+
+fn normalise(heading: &str) -> String {
+    heading
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    Csv,
+    Xlsx,
+}
+
+/// A spreadsheet of pupils to bring in. Columns are matched to fields by their heading unless
+/// `mapping` says otherwise, and nothing is saved on a dry run.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PupilImport {
+    pub(crate) format: Format,
+    /// the file, base64 encoded
+    pub(crate) data: String,
+    /// field to column heading, for headings that aren't recognised, or an empty heading to leave
+    /// a field out
+    #[serde(default)]
+    pub(crate) mapping: BTreeMap<Field, String>,
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+/// The first row of headings, then the cells of every other row as text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub(crate) headings: Vec<String>,
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn read(format: Format, data: &[u8]) -> Result<Self> {
+        let mut rows: Vec<Vec<String>> = match format {
+            Format::Csv => csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(data)
+                .records()
+                .map(|record| {
+                    record
+                        .map(|r| r.iter().map(str::to_owned).collect())
+                        .map_err(|error| file_error(error.to_string()))
+                })
+                .collect::<Result<_>>()?,
+            Format::Xlsx => {
+                let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(data))
+                    .map_err(|error: XlsxError| file_error(error.to_string()))?;
+                let range = workbook
+                    .worksheet_range_at(0)
+                    .ok_or_else(|| file_error("the workbook has no sheets".into()))?
+                    .map_err(|error: XlsxError| file_error(error.to_string()))?;
+                range
+                    .rows()
+                    .map(|row| row.iter().map(cell_text).collect())
+                    .collect()
+            }
+        };
+        rows.retain(|row: &Vec<String>| row.iter().any(|cell| !cell.trim().is_empty()));
+        if rows.is_empty() {
+            return Err(file_error("the file is empty".into()));
+        }
+        let headings = rows
+            .remove(0)
+            .into_iter()
+            .map(|h| h.trim().to_owned())
+            .collect();
+        Ok(Self { headings, rows })
+    }
+
+    /// Work out which column each field comes from, the caller's mapping first then the headings.
+    pub fn columns(&self, mapping: &BTreeMap<Field, String>) -> BTreeMap<Field, usize> {
+        let mut columns = BTreeMap::new();
+        for field in Field::ALL {
+            let found = match mapping.get(&field) {
+                Some(heading) if heading.is_empty() => None,
+                Some(heading) => self.headings.iter().position(|h| h == heading),
+                None => self
+                    .headings
+                    .iter()
+                    .position(|h| field.headings().contains(&normalise(h).as_str())),
+            };
+            if let Some(index) = found {
+                columns.insert(field, index);
+            }
+        }
+        columns
+    }
+}
+
+fn file_error(message: String) -> crate::core::error::Error {
+    ValidationError!("import failed validation")
+        .with_fields(BTreeMap::from([("data".into(), message)]))
+}
+
+/// Dates in a workbook are day counts from 1900, and whole numbers come back as floats.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(value) => {
+            let days = value.as_f64().floor() as i64;
+            // Excel counts a 29th February 1900 that never happened
+            let epoch = NaiveDate::from_ymd_opt(1899, 12, if days < 60 { 31 } else { 30 })
+                .expect("a valid date");
+            (epoch + Duration::days(days)).to_string()
+        }
+        Data::Float(value) if value.fract() == 0.0 => format!("{value:.0}"),
+        Data::Empty => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Create,
+    Update,
+    Unchanged,
+    Error,
+}
+
+/// What the import would do, or did, with one row of the file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RowReport {
+    /// the row number a spreadsheet would show, counting the headings as row 1
+    pub(crate) row: usize,
+    pub(crate) action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) upn: Option<String>,
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) changes: Vec<Field>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) errors: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub(crate) headings: Vec<String>,
+    /// the heading each field was read from
+    pub(crate) mapping: BTreeMap<Field, String>,
+    /// required fields with no column, when there are any no rows are read
+    pub(crate) missing: Vec<Field>,
+    pub(crate) rows: Vec<RowReport>,
+    pub(crate) creates: usize,
+    pub(crate) updates: usize,
+    pub(crate) unchanged: usize,
+    pub(crate) errors: usize,
+    /// saved, which only happens when it isn't a dry run and no row has an error
+    pub(crate) committed: bool,
+}
+
+/// A row that passed, with the pupil as it was before if it's already on roll
+struct Planned {
+    before: Option<Pupil>,
+    pupil: Pupil,
+}
+
+impl PupilImport {
+    pub fn decode(&self, max_bytes: usize) -> Result<Vec<u8>> {
+        let data = general_purpose::STANDARD.decode(self.data.trim())?;
+        if data.is_empty() || data.len() > max_bytes {
+            return Err(file_error(format!(
+                "file must be between 1 and {max_bytes} bytes"
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Check every row against the pupils already on roll, matched on UPN so importing the same
+    /// file twice changes nothing the second time. Unless it's a dry run, and as long as every row
+    /// passed, the creates and updates are saved together.
+    pub async fn run(
+        &self,
+        user: &User,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<ImportReport> {
+        let table = Table::read(self.format, &self.decode(config.attachment_max_bytes)?)?;
+        let columns = table.columns(&self.mapping);
+        let mut report = ImportReport {
+            mapping: columns
+                .iter()
+                .map(|(field, index)| (*field, table.headings[*index].clone()))
+                .collect(),
+            missing: Field::REQUIRED
+                .into_iter()
+                .filter(|field| !columns.contains_key(field))
+                .collect(),
+            headings: table.headings.clone(),
+            rows: vec![],
+            creates: 0,
+            updates: 0,
+            unchanged: 0,
+            errors: 0,
+            committed: false,
+        };
+        if !report.missing.is_empty() {
+            return Ok(report);
+        }
+
+        let upns: Vec<String> = table
+            .rows
+            .iter()
+            .filter_map(|row| row.get(columns[&Field::Upn]))
+            .map(|upn| upn.trim().to_uppercase())
+            .collect();
+        let mut existing: Vec<Pupil> = Entity::find()
+            .filter(Column::Upn.is_in(upns))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        FlagPeriod::flag_as_at(&mut existing, Utc::now().date_naive(), db).await?;
+        let existing: HashMap<String, Pupil> = existing
+            .into_iter()
+            .filter_map(|pupil| Some((pupil.upn.clone()?, pupil)))
+            .collect();
+
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut planned: Vec<Planned> = vec![];
+        for (index, row) in table.rows.iter().enumerate() {
+            let (row_report, plan) =
+                check_row(index + 2, row, &columns, &existing, &mut seen, user, config);
+            match row_report.action {
+                Action::Create => report.creates += 1,
+                Action::Update => report.updates += 1,
+                Action::Unchanged => report.unchanged += 1,
+                Action::Error => report.errors += 1,
+            }
+            report.rows.push(row_report);
+            planned.extend(plan);
+        }
+
+        if self.dry_run || report.errors > 0 {
+            return Ok(report);
+        }
+        let txn = db.begin().await?;
+        for Planned { before, pupil } in &planned {
+            match before {
+                Some(_) => pupil.update(&txn).await?,
+                None => pupil.insert(&txn).await?,
+            };
+            FlagPeriod::follow_pupil(&txn, before.as_ref(), pupil, Some(user)).await?;
+        }
+        txn.commit().await?;
+        report.committed = true;
+        Ok(report)
+    }
+}
+
+fn check_row(
+    row_number: usize,
+    row: &[String],
+    columns: &BTreeMap<Field, usize>,
+    existing: &HashMap<String, Pupil>,
+    seen: &mut HashSet<String>,
+    user: &User,
+    config: &Config,
+) -> (RowReport, Option<Planned>) {
+    let cell = |field: &Field| {
+        columns
+            .get(field)
+            .and_then(|index| row.get(*index))
+            .map(String::as_str)
+            .unwrap_or_default()
+    };
+    let upn = cell(&Field::Upn).trim().to_uppercase();
+    let before = existing.get(&upn).cloned();
+    let mut pupil = before.clone().unwrap_or_else(|| blank(&upn));
+    let mut errors: BTreeMap<String, String> = BTreeMap::new();
+    for field in columns.keys() {
+        if let Err(message) = field.apply(&mut pupil, cell(field)) {
+            errors.insert(field.as_str().into(), message);
+        }
+    }
+    if !columns.contains_key(&Field::Active) {
+        pupil.active = pupil.end_date.is_none();
+    }
+    if !upn.is_empty() && !seen.insert(upn.clone()) {
+        errors.insert("upn".into(), "UPN is on an earlier row too".into());
+    }
+    if let Some(year) = [Some(pupil.year), before.as_ref().map(|b| b.year)]
+        .into_iter()
+        .flatten()
+        .find(|year| !user.years.contains(&(*year as u32)))
+    {
+        errors.entry("year".into()).or_insert(format!(
+            "you don't have permission to import into year {year}"
+        ));
+    }
+    if let Err(error) = pupil.validate(config) {
+        for (field, message) in error.fields.unwrap_or_default() {
+            errors.entry(field).or_insert(message);
+        }
+    }
+
+    let changes: Vec<Field> = match &before {
+        Some(before) => Field::ALL
+            .into_iter()
+            .filter(|field| field.value(before) != field.value(&pupil))
+            .collect(),
+        None => vec![],
+    };
+    let action = match (&before, errors.is_empty(), changes.is_empty()) {
+        (_, false, _) => Action::Error,
+        (None, true, _) => Action::Create,
+        (Some(_), true, true) => Action::Unchanged,
+        (Some(_), true, false) => Action::Update,
+    };
+    let report = RowReport {
+        row: row_number,
+        action,
+        upn: (!upn.is_empty()).then_some(upn),
+        name: format!("{} {}", pupil.first_names, pupil.last_name)
+            .trim()
+            .to_owned(),
+        changes,
+        errors,
+    };
+    let plan =
+        matches!(action, Action::Create | Action::Update).then_some(Planned { before, pupil });
+    (report, plan)
+}
+
+/// A new pupil for the row's cells to be written onto, every required field has a column so
+/// nothing here survives
+pub(crate) fn blank(upn: &str) -> Pupil {
+    Pupil {
+        id: Uuid::new_v4(),
+        first_names: String::new(),
+        last_name: String::new(),
+        year: 0,
+        start_date: Utc::now().date_naive(),
+        end_date: None,
+        active: true,
+        more_able_and_talented: false,
+        english_as_additional_language: false,
+        free_school_meals: false,
+        additional_learning_needs: false,
+        looked_after_child: false,
+        gender: String::new(),
+        date_of_birth: None,
+        upn: Some(upn.to_owned()),
+        preferred_first_names: None,
+        preferred_last_name: None,
+        home_language: None,
+        persistent_absence: false,
+        in_intervention: false,
+        eal_stage: None,
+        has_allergy: false,
+        carries_medication: false,
+        photo: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("6", Ok(6))]
+    #[case("Year 6", Ok(6))]
+    #[case("Y10", Ok(10))]
+    #[case("6.0", Ok(6))]
+    #[case("six", Err("six is not a year group".to_owned()))]
+    fn test_parse_year(#[case] cell: &str, #[case] expected: std::result::Result<i32, String>) {
+        assert_eq!(parse_year(cell), expected);
+    }
+
+    #[rstest]
+    #[case("2016-09-05")]
+    #[case("05/09/2016")]
+    #[case("05-09-2016")]
+    fn test_parse_date(#[case] cell: &str) {
+        assert_eq!(parse_date(cell), Ok("2016-09-05".parse().unwrap()));
+    }
+
+    #[rstest]
+    fn test_parse_gender_and_flags() {
+        assert_eq!(parse_gender("F"), Ok("female".to_owned()));
+        assert_eq!(parse_gender("Boy"), Ok("male".to_owned()));
+        assert!(parse_gender("unknown").is_err());
+        assert_eq!(parse_bool("Y"), Ok(true));
+        assert_eq!(parse_bool(""), Ok(false));
+        assert!(parse_bool("maybe").is_err());
+    }
+
+    #[rstest]
+    fn test_read_csv_skips_blank_rows() {
+        let csv = "Surname,Forename,UPN\nJones,Ben,H801200001001\n,,\nSmith,Ann,X801200001002\n";
+        let table = Table::read(Format::Csv, csv.as_bytes()).unwrap();
+        assert_eq!(table.headings, ["Surname", "Forename", "UPN"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[1][1], "Ann");
+    }
+
+    #[rstest]
+    fn test_empty_file_is_refused() {
+        let error = Table::read(Format::Csv, b"\n,,\n").unwrap_err();
+        assert!(error.fields.unwrap().contains_key("data"));
+    }
+
+    #[rstest]
+    fn test_columns_from_headings_and_mapping() {
+        let table = Table {
+            headings: vec![
+                "Legal Surname".into(),
+                "Forename".into(),
+                "NC Year (actual)".into(),
+                "Pupil ref".into(),
+            ],
+            rows: vec![],
+        };
+        let guessed = table.columns(&BTreeMap::new());
+        assert_eq!(guessed.get(&Field::LastName), Some(&0));
+        assert_eq!(guessed.get(&Field::FirstNames), Some(&1));
+        assert_eq!(guessed.get(&Field::Year), Some(&2));
+        assert_eq!(guessed.get(&Field::Upn), None);
+
+        let mapped = table.columns(&BTreeMap::from([
+            (Field::Upn, "Pupil ref".to_owned()),
+            (Field::FirstNames, "Not there".to_owned()),
+            (Field::LastName, String::new()),
+        ]));
+        assert_eq!(mapped.get(&Field::Upn), Some(&3));
+        assert_eq!(mapped.get(&Field::FirstNames), None);
+        assert_eq!(mapped.get(&Field::LastName), None);
+    }
+
+    #[rstest]
+    #[case(Data::Float(6.0), "6")]
+    #[case(Data::Float(6.5), "6.5")]
+    #[case(Data::String("Ben".into()), "Ben")]
+    #[case(Data::Empty, "")]
+    #[case(
+        Data::DateTime(calamine::ExcelDateTime::new(
+            42618.0,
+            calamine::ExcelDateTimeType::DateTime,
+            false
+        )),
+        "2016-09-05"
+    )]
+    fn test_cell_text(#[case] cell: Data, #[case] expected: &str) {
+        assert_eq!(cell_text(&cell), expected);
+    }
+}
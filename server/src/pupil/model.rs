@@ -1,9 +1,13 @@
-use crate::{core::error::Result, user::model::*};
+use crate::{
+    core::error::Result, eal::model::Stage, photo::model::PhotoLinks, user::model::*,
+    utils::patch::Patch,
+};
 use chrono::NaiveDate;
 use entity::pupil::{ActiveModel, Column, Entity, Model};
 use migration::Condition;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, Unchanged,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    Set, Unchanged,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -11,20 +15,53 @@ use uuid::Uuid;
 #[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
 pub struct Pupil {
     #[serde(default = "uuid::Uuid::new_v4")]
-    id: Uuid,
-    first_names: String,
-    last_name: String,
-    year: i32,
-    start_date: NaiveDate,
+    pub(crate) id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) year: i32,
+    pub(crate) start_date: NaiveDate,
     #[serde(skip_serializing_if="Option::is_none")]
-    end_date: Option<NaiveDate>,
-    active: bool,
-    more_able_and_talented: bool,
-    english_as_additional_language: bool,
-    free_school_meals: bool,
-    additional_learning_needs: bool,
-    looked_after_child: bool,
-    gender: String,
+    pub(crate) end_date: Option<NaiveDate>,
+    pub(crate) active: bool,
+    pub(crate) more_able_and_talented: bool,
+    /// kept in step with EAL assessments, see `eal::model`
+    #[serde(default, skip_deserializing)]
+    pub(crate) english_as_additional_language: bool,
+    pub(crate) free_school_meals: bool,
+    /// kept in step with the ALN register, see `aln::register`
+    #[serde(default, skip_deserializing)]
+    pub(crate) additional_learning_needs: bool,
+    pub(crate) looked_after_child: bool,
+    pub(crate) gender: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) date_of_birth: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) upn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_first_names: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) preferred_last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) home_language: Option<String>,
+    /// worked out from the attendance register rather than stored, see `attendance::summary`
+    #[serde(default)]
+    pub(crate) persistent_absence: bool,
+    /// worked out from intervention membership, see `intervention::model`
+    #[serde(default)]
+    pub(crate) in_intervention: bool,
+    /// the stage from the latest EAL assessment, see `eal::model`
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub(crate) eal_stage: Option<Stage>,
+    /// worked out from the medical record so staff without access to it still see the alert, see
+    /// `medical::model`
+    #[serde(default)]
+    pub(crate) has_allergy: bool,
+    #[serde(default)]
+    pub(crate) carries_medication: bool,
+    /// signed links to the pupil's photo, left out when there isn't one or consent is withdrawn,
+    /// see `photo::model`
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub(crate) photo: Option<PhotoLinks>,
 }
 
 impl Pupil {
@@ -62,15 +99,15 @@ impl Pupil {
             .collect())
     }
 
-    pub async fn insert(&self, db: &DatabaseConnection) -> Result<Self> {
+    pub async fn insert<C: ConnectionTrait>(&self, db: &C) -> Result<Self> {
         tracing::debug!("inserting pupil {:?}", self);
         Ok(ActiveModel {
-            id: Set(self.id.clone()),
+            id: Set(self.id),
             first_names: Set(self.first_names.clone()),
             last_name: Set(self.last_name.clone()),
             year: Set(self.year),
-            start_date: Set(self.start_date.clone()),
-            end_date: Set(self.end_date.clone()),
+            start_date: Set(self.start_date),
+            end_date: Set(self.end_date),
             active: Set(self.active),
             more_able_and_talented: Set(self.more_able_and_talented),
             english_as_additional_language: Set(self.english_as_additional_language),
@@ -78,20 +115,25 @@ impl Pupil {
             additional_learning_needs: Set(self.additional_learning_needs),
             looked_after_child: Set(self.looked_after_child),
             gender: Set(self.gender.clone()),
+            date_of_birth: Set(self.date_of_birth),
+            upn: Set(self.upn.clone()),
+            preferred_first_names: Set(self.preferred_first_names.clone()),
+            preferred_last_name: Set(self.preferred_last_name.clone()),
+            home_language: Set(self.home_language.clone()),
         }
         .insert(db)
         .await?
         .into())
     }
 
-    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+    pub async fn update<C: ConnectionTrait>(&self, db: &C) -> Result<Self> {
         Ok(ActiveModel {
-            id: Unchanged(self.id.clone()),
+            id: Unchanged(self.id),
             first_names: Set(self.first_names.clone()),
             last_name: Set(self.last_name.clone()),
             year: Set(self.year),
-            start_date: Set(self.start_date.clone()),
-            end_date: Set(self.end_date.clone()),
+            start_date: Set(self.start_date),
+            end_date: Set(self.end_date),
             active: Set(self.active),
             more_able_and_talented: Set(self.more_able_and_talented),
             english_as_additional_language: Set(self.english_as_additional_language),
@@ -99,50 +141,45 @@ impl Pupil {
             additional_learning_needs: Set(self.additional_learning_needs),
             looked_after_child: Set(self.looked_after_child),
             gender: Set(self.gender.clone()),
+            date_of_birth: Set(self.date_of_birth),
+            upn: Set(self.upn.clone()),
+            preferred_first_names: Set(self.preferred_first_names.clone()),
+            preferred_last_name: Set(self.preferred_last_name.clone()),
+            home_language: Set(self.home_language.clone()),
         }
         .update(db)
         .await?
         .into())
     }
 
-    pub fn set_from_update(&mut self, update: PupilUpdate) {
-        // TODO is there a nicer way of doing this?
-        if update.first_names.is_some() {
-            self.first_names = update.first_names.unwrap();
-        }
-        if update.last_name.is_some() {
-            self.last_name = update.last_name.unwrap();
-        }
-        if update.year.is_some() {
-            self.year = update.year.unwrap();
-        }
-        if update.start_date.is_some() {
-            self.start_date = update.start_date.unwrap();
-        }
-        if update.end_date.is_some() {
-            self.end_date = Some(update.end_date.unwrap());
-        }
-        if update.active.is_some() {
-            self.active = update.active.unwrap();
-        }
-        if update.more_able_and_talented.is_some() {
-            self.more_able_and_talented = update.more_able_and_talented.unwrap();
-        }
-        if update.english_as_additional_language.is_some() {
-            self.english_as_additional_language = update.english_as_additional_language.unwrap();
-        }
-        if update.free_school_meals.is_some() {
-            self.free_school_meals = update.free_school_meals.unwrap();
-        }
-        if update.additional_learning_needs.is_some() {
-            self.additional_learning_needs = update.additional_learning_needs.unwrap();
-        }
-        if update.looked_after_child.is_some() {
-            self.looked_after_child = update.looked_after_child.unwrap();
-        }
-        if update.gender.is_some() {
-            self.gender = update.gender.unwrap();
-        }
+    /// Apply a JSON merge patch, where nullable fields like `end_date` can be cleared with `null`.
+    pub fn set_from_update(&mut self, update: PupilUpdate) -> Result<()> {
+        update.first_names.apply("first_names", &mut self.first_names)?;
+        update.last_name.apply("last_name", &mut self.last_name)?;
+        update.year.apply("year", &mut self.year)?;
+        update.start_date.apply("start_date", &mut self.start_date)?;
+        update.end_date.apply_nullable(&mut self.end_date);
+        update.active.apply("active", &mut self.active)?;
+        update
+            .more_able_and_talented
+            .apply("more_able_and_talented", &mut self.more_able_and_talented)?;
+        update
+            .free_school_meals
+            .apply("free_school_meals", &mut self.free_school_meals)?;
+        update
+            .looked_after_child
+            .apply("looked_after_child", &mut self.looked_after_child)?;
+        update.gender.apply("gender", &mut self.gender)?;
+        update.date_of_birth.apply_nullable(&mut self.date_of_birth);
+        update.upn.apply_nullable(&mut self.upn);
+        update
+            .preferred_first_names
+            .apply_nullable(&mut self.preferred_first_names);
+        update
+            .preferred_last_name
+            .apply_nullable(&mut self.preferred_last_name);
+        update.home_language.apply_nullable(&mut self.home_language);
+        Ok(())
     }
 
     pub async fn delete(&self, db: &DatabaseConnection) -> Result<()> {
@@ -152,19 +189,79 @@ impl Pupil {
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
 pub struct PupilUpdate {
-    first_names: Option<String>,
-    last_name: Option<String>,
-    year: Option<i32>,
-    start_date: Option<NaiveDate>,
-    end_date: Option<NaiveDate>,
-    active: Option<bool>,
-    more_able_and_talented: Option<bool>,
-    english_as_additional_language: Option<bool>,
-    free_school_meals: Option<bool>,
-    additional_learning_needs: Option<bool>,
-    looked_after_child: Option<bool>,
-    gender: Option<String>,
+    first_names: Patch<String>,
+    last_name: Patch<String>,
+    year: Patch<i32>,
+    start_date: Patch<NaiveDate>,
+    end_date: Patch<NaiveDate>,
+    active: Patch<bool>,
+    more_able_and_talented: Patch<bool>,
+    free_school_meals: Patch<bool>,
+    looked_after_child: Patch<bool>,
+    gender: Patch<String>,
+    date_of_birth: Patch<NaiveDate>,
+    upn: Patch<String>,
+    preferred_first_names: Patch<String>,
+    preferred_last_name: Patch<String>,
+    home_language: Patch<String>,
+}
+
+impl From<Model> for Pupil {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            first_names: value.first_names,
+            last_name: value.last_name,
+            year: value.year,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            active: value.active,
+            more_able_and_talented: value.more_able_and_talented,
+            english_as_additional_language: value.english_as_additional_language,
+            free_school_meals: value.free_school_meals,
+            additional_learning_needs: value.additional_learning_needs,
+            looked_after_child: value.looked_after_child,
+            gender: value.gender,
+            date_of_birth: value.date_of_birth,
+            upn: value.upn,
+            preferred_first_names: value.preferred_first_names,
+            preferred_last_name: value.preferred_last_name,
+            home_language: value.home_language,
+            persistent_absence: false,
+            in_intervention: false,
+            eal_stage: None,
+            has_allergy: false,
+            carries_medication: false,
+            photo: None,
+        }
+    }
+}
+
+impl From<Pupil> for Model {
+    fn from(value: Pupil) -> Self {
+        Self {
+            id: value.id,
+            first_names: value.first_names,
+            last_name: value.last_name,
+            year: value.year,
+            start_date: value.start_date,
+            end_date: value.end_date,
+            active: value.active,
+            more_able_and_talented: value.more_able_and_talented,
+            english_as_additional_language: value.english_as_additional_language,
+            free_school_meals: value.free_school_meals,
+            additional_learning_needs: value.additional_learning_needs,
+            looked_after_child: value.looked_after_child,
+            gender: value.gender,
+            date_of_birth: value.date_of_birth,
+            upn: value.upn,
+            preferred_first_names: value.preferred_first_names,
+            preferred_last_name: value.preferred_last_name,
+            home_language: value.home_language,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -189,6 +286,17 @@ mod tests {
             additional_learning_needs: false,
             looked_after_child: false,
             gender: "gender".into(),
+            date_of_birth: Some("2015-03-04".parse().unwrap()),
+            upn: Some("H801200001001".into()),
+            preferred_first_names: None,
+            preferred_last_name: None,
+            home_language: Some("English".into()),
+            persistent_absence: false,
+            in_intervention: false,
+            eal_stage: None,
+            has_allergy: false,
+            carries_medication: false,
+            photo: None,
         }
     }
 
@@ -215,7 +323,7 @@ mod tests {
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"SELECT "pupil"."id", "pupil"."first_names", "pupil"."last_name", "pupil"."year", "pupil"."start_date", "pupil"."end_date", "pupil"."active", "pupil"."more_able_and_talented", "pupil"."english_as_additional_language", "pupil"."free_school_meals", "pupil"."additional_learning_needs", "pupil"."looked_after_child", "pupil"."gender" FROM "pupil" WHERE "pupil"."id" = $1 LIMIT $2"#,
+            r#"SELECT "pupil"."id", "pupil"."first_names", "pupil"."last_name", "pupil"."year", "pupil"."start_date", "pupil"."end_date", "pupil"."active", "pupil"."more_able_and_talented", "pupil"."english_as_additional_language", "pupil"."free_school_meals", "pupil"."additional_learning_needs", "pupil"."looked_after_child", "pupil"."gender", "pupil"."date_of_birth", "pupil"."upn", "pupil"."preferred_first_names", "pupil"."preferred_last_name", "pupil"."home_language" FROM "pupil" WHERE "pupil"."id" = $1 LIMIT $2"#,
             [results[0].id.into(), 1u64.into()],
         );
         assert_eq!(t_log[0], exp_query);
@@ -263,7 +371,7 @@ mod tests {
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"SELECT "pupil"."id", "pupil"."first_names", "pupil"."last_name", "pupil"."year", "pupil"."start_date", "pupil"."end_date", "pupil"."active", "pupil"."more_able_and_talented", "pupil"."english_as_additional_language", "pupil"."free_school_meals", "pupil"."additional_learning_needs", "pupil"."looked_after_child", "pupil"."gender" FROM "pupil" WHERE "pupil"."year" = $1"#,
+            r#"SELECT "pupil"."id", "pupil"."first_names", "pupil"."last_name", "pupil"."year", "pupil"."start_date", "pupil"."end_date", "pupil"."active", "pupil"."more_able_and_talented", "pupil"."english_as_additional_language", "pupil"."free_school_meals", "pupil"."additional_learning_needs", "pupil"."looked_after_child", "pupil"."gender", "pupil"."date_of_birth", "pupil"."upn", "pupil"."preferred_first_names", "pupil"."preferred_last_name", "pupil"."home_language" FROM "pupil" WHERE "pupil"."year" = $1"#,
             [1u32.into()],
         );
         assert_eq!(t_log[0], exp_query);
@@ -272,14 +380,14 @@ mod tests {
     #[rstest]
     async fn test_save(test_pupil: Pupil) {
         let db = MockDatabase::new(DatabaseBackend::Postgres)
-            .append_query_results(vec![vec![<Pupil as Into<Model>>::into(test_pupil.clone())]])
+            .append_query_results(vec![vec![Model::from(test_pupil.clone())]])
             .into_connection();
         let result = test_pupil.insert(&db).await;
         assert!(result.is_ok());
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"INSERT INTO "pupil" ("id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender") VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) RETURNING "id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender""#,
+            r#"INSERT INTO "pupil" ("id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender", "date_of_birth", "upn", "preferred_first_names", "preferred_last_name", "home_language") VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18) RETURNING "id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender", "date_of_birth", "upn", "preferred_first_names", "preferred_last_name", "home_language""#,
             [
                 test_pupil.id.into(),
                 "test".into(),
@@ -294,6 +402,11 @@ mod tests {
                 false.into(),
                 false.into(),
                 "gender".into(),
+                test_pupil.date_of_birth.into(),
+                test_pupil.upn.clone().into(),
+                test_pupil.preferred_first_names.clone().into(),
+                test_pupil.preferred_last_name.clone().into(),
+                test_pupil.home_language.clone().into(),
             ],
         );
         assert_eq!(t_log[0], exp_query);
@@ -302,20 +415,20 @@ mod tests {
     #[rstest]
     async fn test_update(mut test_pupil: Pupil) {
         let db = MockDatabase::new(DatabaseBackend::Postgres)
-            .append_query_results(vec![vec![<Pupil as Into<Model>>::into(test_pupil.clone())]])
+            .append_query_results(vec![vec![Model::from(test_pupil.clone())]])
             .into_connection();
         let update = PupilUpdate {
-            last_name: Some("newname".into()),
-            free_school_meals: Some(true),
+            last_name: Patch::Value("newname".into()),
+            free_school_meals: Patch::Value(true),
             ..Default::default()
         };
-        test_pupil.set_from_update(update);
+        test_pupil.set_from_update(update).unwrap();
         let result = test_pupil.update(&db).await;
         assert!(result.is_ok());
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"UPDATE "pupil" SET "first_names" = $1, "last_name" = $2, "year" = $3, "start_date" = $4, "end_date" = $5, "active" = $6, "more_able_and_talented" = $7, "english_as_additional_language" = $8, "free_school_meals" = $9, "additional_learning_needs" = $10, "looked_after_child" = $11, "gender" = $12 WHERE "pupil"."id" = $13 RETURNING "id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender""#,
+            r#"UPDATE "pupil" SET "first_names" = $1, "last_name" = $2, "year" = $3, "start_date" = $4, "end_date" = $5, "active" = $6, "more_able_and_talented" = $7, "english_as_additional_language" = $8, "free_school_meals" = $9, "additional_learning_needs" = $10, "looked_after_child" = $11, "gender" = $12, "date_of_birth" = $13, "upn" = $14, "preferred_first_names" = $15, "preferred_last_name" = $16, "home_language" = $17 WHERE "pupil"."id" = $18 RETURNING "id", "first_names", "last_name", "year", "start_date", "end_date", "active", "more_able_and_talented", "english_as_additional_language", "free_school_meals", "additional_learning_needs", "looked_after_child", "gender", "date_of_birth", "upn", "preferred_first_names", "preferred_last_name", "home_language""#,
             [
                 "test".into(),
                 "newname".into(),
@@ -329,6 +442,11 @@ mod tests {
                 false.into(),
                 false.into(),
                 "gender".into(),
+                test_pupil.date_of_birth.into(),
+                test_pupil.upn.clone().into(),
+                test_pupil.preferred_first_names.clone().into(),
+                test_pupil.preferred_last_name.clone().into(),
+                test_pupil.home_language.clone().into(),
                 "1164ce28-8915-4126-924d-fa580f1e9f01"
                     .parse::<Uuid>()
                     .unwrap()
@@ -338,8 +456,8 @@ mod tests {
         assert_eq!(t_log[0], exp_query);
     }
 
-    #[rstest] // TODO add more cases
-    #[case(PupilUpdate{last_name: Some("newname".into()), ..Default::default()}, Pupil {
+    #[rstest]
+    #[case(PupilUpdate{last_name: Patch::Value("newname".into()), ..Default::default()}, Pupil {
         id: "1164ce28-8915-4126-924d-fa580f1e9f01".parse().unwrap(),
         first_names: "test".into(),
         last_name: "newname".into(),
@@ -353,16 +471,79 @@ mod tests {
         additional_learning_needs: false,
         looked_after_child: false,
         gender: "gender".into(),
+        date_of_birth: Some("2015-03-04".parse().unwrap()),
+        upn: Some("H801200001001".into()),
+        preferred_first_names: None,
+        preferred_last_name: None,
+        home_language: Some("English".into()),
+        persistent_absence: false,
+        in_intervention: false,
+        eal_stage: None,
+        has_allergy: false,
+        carries_medication: false,
+        photo: None,
+    })]
+    #[case(PupilUpdate{end_date: Patch::Value("2022-07-21".parse().unwrap()), active: Patch::Value(false), ..Default::default()}, Pupil {
+        id: "1164ce28-8915-4126-924d-fa580f1e9f01".parse().unwrap(),
+        first_names: "test".into(),
+        last_name: "pupil".into(),
+        year: 6,
+        start_date: "2021-01-01".parse().unwrap(),
+        end_date: Some("2022-07-21".parse().unwrap()),
+        active: false,
+        more_able_and_talented: false,
+        english_as_additional_language: false,
+        free_school_meals: false,
+        additional_learning_needs: false,
+        looked_after_child: false,
+        gender: "gender".into(),
+        date_of_birth: Some("2015-03-04".parse().unwrap()),
+        upn: Some("H801200001001".into()),
+        preferred_first_names: None,
+        preferred_last_name: None,
+        home_language: Some("English".into()),
+        persistent_absence: false,
+        in_intervention: false,
+        eal_stage: None,
+        has_allergy: false,
+        carries_medication: false,
+        photo: None,
     })]
     async fn test_set_from_update(
         mut test_pupil: Pupil,
         #[case] update: PupilUpdate,
         #[case] expected: Pupil,
     ) {
-        test_pupil.set_from_update(update);
+        test_pupil.set_from_update(update).unwrap();
         assert_eq!(expected, test_pupil);
     }
 
+    #[rstest]
+    async fn test_set_from_update_clears_end_date(mut test_pupil: Pupil) {
+        test_pupil.end_date = Some("2022-07-21".parse().unwrap());
+        test_pupil.active = false;
+        let update: PupilUpdate =
+            serde_json::from_str(r#"{"end_date": null, "active": true}"#).unwrap();
+        test_pupil.set_from_update(update).unwrap();
+        assert_eq!(test_pupil.end_date, None);
+        assert!(test_pupil.active);
+    }
+
+    #[rstest]
+    #[case(r#"{"first_names": null}"#, "first_names cannot be null")]
+    #[case(r#"{"year": null}"#, "year cannot be null")]
+    #[case(r#"{"active": null, "last_name": "newname"}"#, "active cannot be null")]
+    async fn test_set_from_update_rejects_null(
+        mut test_pupil: Pupil,
+        #[case] json: &str,
+        #[case] message: &str,
+    ) {
+        let update: PupilUpdate = serde_json::from_str(json).unwrap();
+        let error = test_pupil.set_from_update(update).unwrap_err();
+        assert_eq!(error.kind, crate::core::error::ErrorKind::InvalidApiRequest);
+        assert_eq!(error.message, Some(message.into()));
+    }
+
     #[rstest]
     async fn test_delete(test_pupil: Pupil) {
         let db = MockDatabase::new(DatabaseBackend::Postgres)
@@ -385,43 +566,3 @@ mod tests {
         assert_eq!(t_log[0], exp_query);
     }
 }
-
-impl From<Model> for Pupil {
-    fn from(value: Model) -> Self {
-        Self {
-            id: value.id,
-            first_names: value.first_names,
-            last_name: value.last_name,
-            year: value.year,
-            start_date: value.start_date,
-            end_date: value.end_date,
-            active: value.active,
-            more_able_and_talented: value.more_able_and_talented,
-            english_as_additional_language: value.english_as_additional_language,
-            free_school_meals: value.free_school_meals,
-            additional_learning_needs: value.additional_learning_needs,
-            looked_after_child: value.looked_after_child,
-            gender: value.gender,
-        }
-    }
-}
-
-impl Into<Model> for Pupil {
-    fn into(self) -> Model {
-        Model {
-            id: self.id,
-            first_names: self.first_names,
-            last_name: self.last_name,
-            year: self.year,
-            start_date: self.start_date,
-            end_date: self.end_date,
-            active: self.active,
-            more_able_and_talented: self.more_able_and_talented,
-            english_as_additional_language: self.english_as_additional_language,
-            free_school_meals: self.free_school_meals,
-            additional_learning_needs: self.additional_learning_needs,
-            looked_after_child: self.looked_after_child,
-            gender: self.gender,
-        }
-    }
-}
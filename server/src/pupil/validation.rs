@@ -0,0 +1,190 @@
+use crate::{
+    app::config::Config,
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    utils,
+};
+use entity::pupil::{Column, Entity};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use std::collections::BTreeMap;
+
+impl Pupil {
+    /// Check the pupil makes sense for this school, returning every problem found keyed by field.
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.first_names.trim().is_empty() {
+            errors.insert("first_names".into(), "first names cannot be empty".into());
+        }
+        if self.last_name.trim().is_empty() {
+            errors.insert("last_name".into(), "last name cannot be empty".into());
+        }
+        if self.year < config.min_year || self.year > config.max_year {
+            errors.insert(
+                "year".into(),
+                format!(
+                    "year must be between {} and {}",
+                    config.min_year, config.max_year
+                ),
+            );
+        }
+        if !constant::GENDERS.contains(&self.gender.as_str()) {
+            errors.insert(
+                "gender".into(),
+                format!("gender must be one of {}", constant::GENDERS.join(", ")),
+            );
+        }
+        match (self.active, self.end_date) {
+            (true, Some(_)) => {
+                errors.insert(
+                    "end_date".into(),
+                    "an active pupil cannot have a leave date".into(),
+                );
+            }
+            (false, None) => {
+                errors.insert(
+                    "end_date".into(),
+                    "an inactive pupil must have a leave date".into(),
+                );
+            }
+            (false, Some(end_date)) if end_date < self.start_date => {
+                errors.insert(
+                    "end_date".into(),
+                    "leave date cannot be before start date".into(),
+                );
+            }
+            _ => {}
+        }
+        if let Some(date_of_birth) = self.date_of_birth {
+            if date_of_birth >= self.start_date {
+                errors.insert(
+                    "date_of_birth".into(),
+                    "date of birth must be before start date".into(),
+                );
+            }
+        }
+        if let Some(upn) = &self.upn {
+            if !utils::is_valid_upn(upn) {
+                errors.insert(
+                    "upn".into(),
+                    "UPN must be a check letter followed by 12 characters, with a valid check letter"
+                        .into(),
+                );
+            }
+        }
+        for (field, value) in [
+            ("preferred_first_names", &self.preferred_first_names),
+            ("preferred_last_name", &self.preferred_last_name),
+            ("home_language", &self.home_language),
+        ] {
+            if value.as_ref().is_some_and(|v| v.trim().is_empty()) {
+                errors.insert(
+                    field.into(),
+                    format!("{} cannot be blank, leave it out instead", field.replace('_', " ")),
+                );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("pupil failed validation").with_fields(errors))
+        }
+    }
+
+    /// Check no other pupil already has this pupil's UPN, which the database would also refuse.
+    pub async fn validate_unique(&self, db: &DatabaseConnection) -> Result<()> {
+        if let Some(upn) = &self.upn {
+            let existing = Entity::find()
+                .filter(Column::Upn.eq(upn.clone()))
+                .filter(Column::Id.ne(self.id))
+                .one(db)
+                .await?;
+            if existing.is_some() {
+                return Err(ValidationError!("pupil failed validation").with_fields(
+                    BTreeMap::from([("upn".into(), "UPN already belongs to another pupil".into())]),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+    use serde_json::json;
+
+    fn pupil_from(value: serde_json::Value) -> Pupil {
+        let mut pupil = json!({
+            "first_names": "test",
+            "last_name": "pupil",
+            "year": 3,
+            "start_date": "2021-09-01",
+            "active": true,
+            "more_able_and_talented": false,
+            "english_as_additional_language": false,
+            "free_school_meals": false,
+            "additional_learning_needs": false,
+            "looked_after_child": false,
+            "gender": "female"
+        });
+        for (k, v) in value.as_object().unwrap() {
+            pupil[k] = v.clone();
+        }
+        serde_json::from_value(pupil).unwrap()
+    }
+
+    #[rstest]
+    #[case(json!({}))]
+    #[case(json!({"year": 0}))]
+    #[case(json!({"year": 6}))]
+    #[case(json!({"active": false, "end_date": "2021-09-01"}))]
+    #[case(json!({"upn": "H801200001001", "date_of_birth": "2016-02-29"}))]
+    #[case(json!({"upn": "H80120000100B"}))]
+    #[case(json!({"preferred_first_names": "Benji", "home_language": "Welsh"}))]
+    fn test_validate_ok(#[case] overrides: serde_json::Value) {
+        assert!(pupil_from(overrides).validate(&Config::default()).is_ok());
+    }
+
+    #[rstest]
+    #[case(json!({"first_names": " "}), vec![("first_names", "first names cannot be empty")])]
+    #[case(json!({"last_name": ""}), vec![("last_name", "last name cannot be empty")])]
+    #[case(json!({"year": -1}), vec![("year", "year must be between 0 and 6")])]
+    #[case(json!({"year": 7}), vec![("year", "year must be between 0 and 6")])]
+    #[case(json!({"gender": "gender"}), vec![("gender", "gender must be one of female, male, other")])]
+    #[case(json!({"end_date": "2022-07-21"}), vec![("end_date", "an active pupil cannot have a leave date")])]
+    #[case(json!({"active": false}), vec![("end_date", "an inactive pupil must have a leave date")])]
+    #[case(json!({"active": false, "end_date": "2021-07-21"}), vec![("end_date", "leave date cannot be before start date")])]
+    #[case(json!({"date_of_birth": "2021-09-01"}), vec![("date_of_birth", "date of birth must be before start date")])]
+    #[case(json!({"upn": "G801200001001"}), vec![("upn", "UPN must be a check letter followed by 12 characters, with a valid check letter")])]
+    #[case(json!({"upn": "H80120000100"}), vec![("upn", "UPN must be a check letter followed by 12 characters, with a valid check letter")])]
+    #[case(json!({"upn": "f801200001001"}), vec![("upn", "UPN must be a check letter followed by 12 characters, with a valid check letter")])]
+    #[case(json!({"preferred_first_names": " "}), vec![("preferred_first_names", "preferred first names cannot be blank, leave it out instead")])]
+    #[case(json!({"first_names": "", "year": 9}), vec![("first_names", "first names cannot be empty"), ("year", "year must be between 0 and 6")])]
+    fn test_validate_errors(
+        #[case] overrides: serde_json::Value,
+        #[case] expected: Vec<(&str, &str)>,
+    ) {
+        let error = pupil_from(overrides)
+            .validate(&Config::default())
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        let expected = expected
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<String, String>>();
+        assert_eq!(error.fields, Some(expected));
+    }
+
+    #[rstest]
+    fn test_validate_uses_configured_years() {
+        let config = Config {
+            min_year: 7,
+            max_year: 11,
+            ..Default::default()
+        };
+        assert!(pupil_from(json!({"year": 9})).validate(&config).is_ok());
+        assert!(pupil_from(json!({"year": 3})).validate(&config).is_err());
+    }
+}
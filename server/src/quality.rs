@@ -0,0 +1,4 @@
+pub mod duplicates;
+pub mod handlers;
+pub mod merge;
+pub mod report;
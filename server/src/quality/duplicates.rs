@@ -0,0 +1,336 @@
+use crate::{pupil::model::Pupil, user::model::User};
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// How alike two names have to be, from 0 to 1, before the records are worth a look.
+pub const PUPIL_NAME_THRESHOLD: f64 = 0.85;
+pub const USER_NAME_THRESHOLD: f64 = 0.9;
+/// Dates of birth this close are taken as the same date typed wrongly.
+pub const DATE_OF_BIRTH_DAYS: i64 = 7;
+/// Without dates of birth to go on, pupils who started this close together are compared instead.
+pub const START_DATE_DAYS: i64 = 31;
+
+/// Lower case letters with single spaces between words, so punctuation, case and spacing don't
+/// count as differences. Apostrophes are dropped rather than splitting the name.
+fn normalise(name: &str) -> String {
+    name.to_lowercase()
+        .replace(['\'', '\u{2019}'], "")
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// One minus the edit distance as a share of the longer name, so 1 is the same name.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (
+        normalise(a).chars().collect(),
+        normalise(b).chars().collect(),
+    );
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// How alike two people's names are. Each part is compared on its own, and the other way round in
+/// case first and last names were swapped when one of them was entered.
+pub fn name_similarity(first: (&str, &str), second: (&str, &str)) -> f64 {
+    let same_way = (similarity(first.0, second.0) + similarity(first.1, second.1)) / 2.0;
+    let swapped = (similarity(first.0, second.1) + similarity(first.1, second.0)) / 2.0;
+    same_way.max(swapped)
+}
+
+fn rounded(score: f64) -> f64 {
+    (score * 100.0).round() / 100.0
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PupilSummary {
+    pub(crate) id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    pub(crate) year: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) date_of_birth: Option<NaiveDate>,
+    pub(crate) start_date: NaiveDate,
+    pub(crate) active: bool,
+}
+
+impl From<&Pupil> for PupilSummary {
+    fn from(pupil: &Pupil) -> Self {
+        Self {
+            id: pupil.id,
+            first_names: pupil.first_names.clone(),
+            last_name: pupil.last_name.clone(),
+            year: pupil.year,
+            date_of_birth: pupil.date_of_birth,
+            start_date: pupil.start_date,
+            active: pupil.active,
+        }
+    }
+}
+
+/// Two pupils who may be the same child, and why they were matched.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PupilPair {
+    pub(crate) first: PupilSummary,
+    pub(crate) second: PupilSummary,
+    pub(crate) score: f64,
+    pub(crate) reasons: Vec<String>,
+}
+
+/// The reason two pupils' dates put them close enough together to compare names, if they do.
+fn close_dates(first: &Pupil, second: &Pupil) -> Option<String> {
+    match (first.date_of_birth, second.date_of_birth) {
+        (Some(a), Some(b)) => {
+            let days = (a - b).num_days().abs();
+            match days {
+                0 => Some("same date of birth".into()),
+                1 => Some("dates of birth a day apart".into()),
+                _ if days <= DATE_OF_BIRTH_DAYS => {
+                    Some(format!("dates of birth {days} days apart"))
+                }
+                _ => None,
+            }
+        }
+        _ => {
+            let days = (first.start_date - second.start_date).num_days().abs();
+            match days {
+                0 => Some("started on the same day".into()),
+                _ if days <= START_DATE_DAYS => Some(format!("started {days} days apart")),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Every pair of pupils with close dates and alike names, the likeliest first. Dates are checked
+/// first as they're cheaper than comparing names.
+pub fn pupil_pairs(pupils: &[Pupil]) -> Vec<PupilPair> {
+    let mut pairs = vec![];
+    for (i, first) in pupils.iter().enumerate() {
+        for second in &pupils[i + 1..] {
+            let Some(dates) = close_dates(first, second) else {
+                continue;
+            };
+            let score = name_similarity(
+                (&first.first_names, &first.last_name),
+                (&second.first_names, &second.last_name),
+            );
+            if score >= PUPIL_NAME_THRESHOLD {
+                let names = if score == 1.0 {
+                    "same name"
+                } else {
+                    "similar names"
+                };
+                pairs.push(PupilPair {
+                    first: first.into(),
+                    second: second.into(),
+                    score: rounded(score),
+                    reasons: vec![names.into(), dates],
+                });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.score.total_cmp(&a.score));
+    pairs
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UserSummary {
+    pub(crate) email_address: String,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+}
+
+impl From<&User> for UserSummary {
+    fn from(user: &User) -> Self {
+        Self {
+            email_address: user.email_address.clone(),
+            first_names: user.first_names.clone(),
+            last_name: user.last_name.clone(),
+        }
+    }
+}
+
+/// Two accounts that may belong to the same member of staff.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UserPair {
+    pub(crate) first: UserSummary,
+    pub(crate) second: UserSummary,
+    pub(crate) score: f64,
+    pub(crate) reasons: Vec<String>,
+}
+
+/// Accounts whose email addresses differ only in case, or whose names are alike.
+pub fn user_pairs(users: &[User]) -> Vec<UserPair> {
+    let mut pairs = vec![];
+    for (i, first) in users.iter().enumerate() {
+        for second in &users[i + 1..] {
+            let score = name_similarity(
+                (&first.first_names, &first.last_name),
+                (&second.first_names, &second.last_name),
+            );
+            let mut reasons = vec![];
+            if first
+                .email_address
+                .eq_ignore_ascii_case(&second.email_address)
+            {
+                reasons.push("same email address apart from case".into());
+            }
+            if score >= USER_NAME_THRESHOLD {
+                reasons.push(
+                    if score == 1.0 {
+                        "same name"
+                    } else {
+                        "similar names"
+                    }
+                    .into(),
+                );
+            }
+            if !reasons.is_empty() {
+                pairs.push(UserPair {
+                    first: first.into(),
+                    second: second.into(),
+                    score: rounded(score),
+                    reasons,
+                });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.score.total_cmp(&a.score));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn pupil(
+        first_names: &str,
+        last_name: &str,
+        date_of_birth: Option<&str>,
+        start_date: &str,
+    ) -> Pupil {
+        let mut pupil = crate::pupil::import::blank("");
+        pupil.id = Uuid::new_v4();
+        pupil.first_names = first_names.into();
+        pupil.last_name = last_name.into();
+        pupil.date_of_birth = date_of_birth.map(|date| date.parse().unwrap());
+        pupil.start_date = start_date.parse().unwrap();
+        pupil
+    }
+
+    #[rstest]
+    #[case("Ben", "Ben", 1.0)]
+    #[case("O'Brien", "obrien", 1.0)]
+    #[case("Jones", "Jone", 0.8)]
+    #[case("Ben", "Sam", 0.0)]
+    #[case("", "", 0.0)]
+    fn names_are_compared_by_edits(#[case] a: &str, #[case] b: &str, #[case] expected: f64) {
+        assert_eq!(similarity(a, b), expected);
+    }
+
+    #[rstest]
+    fn swapped_names_still_match() {
+        assert_eq!(name_similarity(("Ben", "Jones"), ("Jones", "Ben")), 1.0);
+    }
+
+    #[rstest]
+    fn pupils_need_alike_names_and_close_dates() {
+        let pupils = [
+            pupil("Ben", "Jones", Some("2015-03-01"), "2019-09-01"),
+            pupil("Ben", "Jones", Some("2015-03-04"), "2020-09-01"),
+            pupil("Ben", "Jones", Some("2016-03-01"), "2019-09-01"),
+            pupil("Benn", "Jones", None, "2019-09-10"),
+            pupil("Sam", "Jones", Some("2015-03-01"), "2019-09-01"),
+        ];
+        let pairs = pupil_pairs(&pupils);
+        let found: Vec<(Uuid, Uuid, &[String])> = pairs
+            .iter()
+            .map(|pair| (pair.first.id, pair.second.id, pair.reasons.as_slice()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    pupils[0].id,
+                    pupils[1].id,
+                    &[
+                        "same name".to_owned(),
+                        "dates of birth 3 days apart".to_owned()
+                    ][..]
+                ),
+                (
+                    pupils[0].id,
+                    pupils[3].id,
+                    &[
+                        "similar names".to_owned(),
+                        "started 9 days apart".to_owned()
+                    ][..]
+                ),
+                (
+                    pupils[2].id,
+                    pupils[3].id,
+                    &[
+                        "similar names".to_owned(),
+                        "started 9 days apart".to_owned()
+                    ][..]
+                ),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn users_match_on_email_case_or_name() {
+        let users = [
+            User::new("Ann", "Smith", "ann@school.org", "pass", vec![5]),
+            User::new("Anne", "Smyth", "ANN@school.org", "pass", vec![5]),
+            User::new("Ann", "Smith", "a.smith@school.org", "pass", vec![6]),
+            User::new("Tom", "Hughes", "tom@school.org", "pass", vec![6]),
+        ];
+        let pairs = user_pairs(&users);
+        let found: Vec<(&str, &str, &[String])> = pairs
+            .iter()
+            .map(|pair| {
+                (
+                    pair.first.email_address.as_str(),
+                    pair.second.email_address.as_str(),
+                    pair.reasons.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "ann@school.org",
+                    "a.smith@school.org",
+                    &["same name".to_owned()][..]
+                ),
+                (
+                    "ann@school.org",
+                    "ANN@school.org",
+                    &["same email address apart from case".to_owned()][..]
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    quality::{merge::MergeRequest, report::QualityReport},
+    user::model::User,
+};
+use axum::{
+    extract::{Json, State},
+    Extension,
+};
+use serde_json::json;
+
+fn pass_through(error: Error) -> Error {
+    match error.kind {
+        ErrorKind::DatabaseError => DatabaseError!(error.to_string()),
+        ErrorKind::ValidationError
+        | ErrorKind::Unauthorised
+        | ErrorKind::PupilDoesNotExist
+        | ErrorKind::StorageError => error,
+        _ => UnknownError!(),
+    }
+}
+
+/// Likely duplicate pupils and users, and records with missing or inconsistent data.
+pub async fn get_quality_report(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("checking data quality");
+    match QualityReport::from_db(&user, state.config(), state.database().as_ref()).await {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+/// Merge one pupil into another. A dry run shows what would change without saving anything.
+pub async fn merge_pupils(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(merge): Json<MergeRequest>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!(
+        "merging pupil {} into {}, dry run: {}",
+        merge.remove,
+        merge.keep,
+        merge.dry_run
+    );
+    match merge
+        .run(
+            &user,
+            state.config(),
+            state.blob_store().as_ref(),
+            state.database().as_ref(),
+        )
+        .await
+    {
+        Ok(report) => Ok(Json(json!(report))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
@@ -0,0 +1,503 @@
+use super::report::ADMIN_ONLY;
+use crate::{
+    app::config::Config,
+    attachment::store::BlobStore,
+    core::{constant, error::Result},
+    flag::model::FlagPeriod,
+    photo::model::PupilPhoto,
+    pupil::model::Pupil,
+    user::model::User,
+};
+use migration::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, Set, TransactionTrait, Unchanged,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashSet},
+    hash::Hash,
+};
+use uuid::Uuid;
+
+/// The parts of the pupil's own record that can be taken from the pupil being merged away. Flags
+/// aren't here as their periods are combined instead.
+pub const FIELDS: [&str; 12] = [
+    "first_names",
+    "last_name",
+    "year",
+    "start_date",
+    "end_date",
+    "active",
+    "gender",
+    "date_of_birth",
+    "upn",
+    "preferred_first_names",
+    "preferred_last_name",
+    "home_language",
+];
+
+/// Merge `remove` into `keep`. Everything linked to `remove` moves to `keep`, the fields named in
+/// `take` are copied across, and `remove` is deleted. With `dry_run` nothing is saved, which is
+/// how the client previews a merge.
+#[derive(Clone, Debug, Deserialize)]
+pub struct MergeRequest {
+    pub(crate) keep: Uuid,
+    pub(crate) remove: Uuid,
+    #[serde(default)]
+    pub(crate) take: Vec<String>,
+    #[serde(default)]
+    pub(crate) dry_run: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Difference {
+    pub(crate) field: String,
+    pub(crate) keep: Value,
+    pub(crate) remove: Value,
+}
+
+/// What happens to one kind of linked data. Rows that clash with one the kept pupil already has,
+/// like a second attendance mark for the same session, are dropped and the kept pupil's is used.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LinkedRows {
+    pub(crate) table: &'static str,
+    pub(crate) moved: usize,
+    pub(crate) dropped: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MergeReport {
+    /// the kept pupil as they are, or will be, after the merge
+    pub(crate) merged: Pupil,
+    pub(crate) removed: Pupil,
+    pub(crate) differences: Vec<Difference>,
+    pub(crate) linked: Vec<LinkedRows>,
+    pub(crate) committed: bool,
+}
+
+fn take_field(pupil: &mut Pupil, from: &Pupil, field: &str) {
+    match field {
+        "first_names" => pupil.first_names = from.first_names.clone(),
+        "last_name" => pupil.last_name = from.last_name.clone(),
+        "year" => pupil.year = from.year,
+        "start_date" => pupil.start_date = from.start_date,
+        "end_date" => pupil.end_date = from.end_date,
+        "active" => pupil.active = from.active,
+        "gender" => pupil.gender = from.gender.clone(),
+        "date_of_birth" => pupil.date_of_birth = from.date_of_birth,
+        "upn" => pupil.upn = from.upn.clone(),
+        "preferred_first_names" => pupil.preferred_first_names = from.preferred_first_names.clone(),
+        "preferred_last_name" => pupil.preferred_last_name = from.preferred_last_name.clone(),
+        "home_language" => pupil.home_language = from.home_language.clone(),
+        _ => {}
+    }
+}
+
+/// The fields that differ between the two records, for the admin to choose between.
+pub fn differences(keep: &Pupil, remove: &Pupil) -> Vec<Difference> {
+    let (keep, remove) = (json!(keep), json!(remove));
+    FIELDS
+        .iter()
+        .filter_map(|field| {
+            let kept = keep.get(field).cloned().unwrap_or(Value::Null);
+            let removed = remove.get(field).cloned().unwrap_or(Value::Null);
+            (kept != removed).then(|| Difference {
+                field: (*field).into(),
+                keep: kept,
+                remove: removed,
+            })
+        })
+        .collect()
+}
+
+/// Count, and unless it's a dry run move, one table's rows from `remove` to `keep`. `key` picks out
+/// what can't be repeated for one pupil, so the removed pupil's rows with a key the kept pupil
+/// already has are dropped.
+async fn move_rows<C, E, A, K>(
+    db: &C,
+    table: &'static str,
+    column: E::Column,
+    keep: Uuid,
+    remove: Uuid,
+    key: impl Fn(&E::Model) -> K,
+    apply: bool,
+) -> Result<LinkedRows>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+    E::Model: IntoActiveModel<A>,
+    A: ActiveModelTrait<Entity = E> + Send,
+    K: Eq + Hash,
+{
+    let kept: HashSet<K> = E::find()
+        .filter(column.eq(keep))
+        .all(db)
+        .await?
+        .iter()
+        .map(&key)
+        .collect();
+    let (dropped, moved): (Vec<E::Model>, Vec<E::Model>) = E::find()
+        .filter(column.eq(remove))
+        .all(db)
+        .await?
+        .into_iter()
+        .partition(|row| kept.contains(&key(row)));
+    let linked = LinkedRows {
+        table,
+        moved: moved.len(),
+        dropped: dropped.len(),
+    };
+    if apply {
+        for row in dropped {
+            E::delete(row.into_active_model()).exec(db).await?;
+        }
+        E::update_many()
+            .col_expr(column, Expr::value(keep))
+            .filter(column.eq(remove))
+            .exec(db)
+            .await?;
+    }
+    Ok(linked)
+}
+
+/// IDP versions are numbered per pupil, so the removed pupil's follow on after the kept pupil's.
+async fn move_idps<C: ConnectionTrait>(
+    db: &C,
+    keep: Uuid,
+    remove: Uuid,
+    apply: bool,
+) -> Result<LinkedRows> {
+    use entity::idp::{ActiveModel, Column, Entity};
+    let latest = Entity::find()
+        .filter(Column::PupilId.eq(keep))
+        .order_by_desc(Column::Version)
+        .one(db)
+        .await?
+        .map(|idp| idp.version)
+        .unwrap_or_default();
+    let idps = Entity::find()
+        .filter(Column::PupilId.eq(remove))
+        .order_by_asc(Column::Version)
+        .all(db)
+        .await?;
+    let linked = LinkedRows {
+        table: "IDPs",
+        moved: idps.len(),
+        dropped: 0,
+    };
+    if apply {
+        for idp in idps {
+            let version = idp.version + latest;
+            let mut idp = ActiveModel::from(idp);
+            idp.pupil_id = Set(keep);
+            idp.version = Set(version);
+            idp.update(db).await?;
+        }
+    }
+    Ok(linked)
+}
+
+/// Every kind of data held against a pupil. Flag periods and the photo are only counted here, as
+/// they're moved on their own.
+async fn move_linked<C: ConnectionTrait>(
+    db: &C,
+    keep: Uuid,
+    remove: Uuid,
+    apply: bool,
+) -> Result<Vec<LinkedRows>> {
+    use entity::*;
+    Ok(vec![
+        move_rows::<_, comment::Entity, _, _>(
+            db,
+            "comments",
+            comment::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, concern::Entity, _, _>(
+            db,
+            "concerns",
+            concern::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, pupil_contact::Entity, _, _>(
+            db,
+            "contacts",
+            pupil_contact::Column::PupilId,
+            keep,
+            remove,
+            |row| row.contact_id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, assessment_result::Entity, _, _>(
+            db,
+            "assessment results",
+            assessment_result::Column::PupilId,
+            keep,
+            remove,
+            |row| row.assessment_id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, attendance_mark::Entity, _, _>(
+            db,
+            "attendance marks",
+            attendance_mark::Column::PupilId,
+            keep,
+            remove,
+            |row| (row.date, row.session.clone()),
+            apply,
+        )
+        .await?,
+        move_rows::<_, attachment::Entity, _, _>(
+            db,
+            "attachments",
+            attachment::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, eal_assessment::Entity, _, _>(
+            db,
+            "EAL assessments",
+            eal_assessment::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, aln_register::Entity, _, _>(
+            db,
+            "ALN register",
+            aln_register::Column::PupilId,
+            keep,
+            remove,
+            |_| (),
+            apply,
+        )
+        .await?,
+        move_idps(db, keep, remove, apply).await?,
+        move_rows::<_, medical_item::Entity, _, _>(
+            db,
+            "medical items",
+            medical_item::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, progression_judgement::Entity, _, _>(
+            db,
+            "progression judgements",
+            progression_judgement::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, target::Entity, _, _>(
+            db,
+            "targets",
+            target::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, intervention_member::Entity, _, _>(
+            db,
+            "interventions",
+            intervention_member::Column::PupilId,
+            keep,
+            remove,
+            |row| row.intervention_id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, report_sign_off::Entity, _, _>(
+            db,
+            "report sign-offs",
+            report_sign_off::Column::PupilId,
+            keep,
+            remove,
+            |row| row.template_id,
+            apply,
+        )
+        .await?,
+        move_rows::<_, flag_period::Entity, _, _>(
+            db,
+            "flag periods",
+            flag_period::Column::PupilId,
+            keep,
+            remove,
+            |row| row.id,
+            false,
+        )
+        .await?,
+        move_rows::<_, pupil_photo::Entity, _, _>(
+            db,
+            "photo",
+            pupil_photo::Column::PupilId,
+            keep,
+            remove,
+            |_| (),
+            false,
+        )
+        .await?,
+    ])
+}
+
+impl MergeRequest {
+    pub async fn run(
+        &self,
+        user: &User,
+        config: &Config,
+        store: &dyn BlobStore,
+        db: &DatabaseConnection,
+    ) -> Result<MergeReport> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.keep == self.remove {
+            errors.insert("remove".into(), "choose two different pupils".into());
+        }
+        if let Some(field) = self
+            .take
+            .iter()
+            .find(|field| !FIELDS.contains(&field.as_str()))
+        {
+            errors.insert(
+                "take".into(),
+                format!("{field} can't be taken from the other pupil"),
+            );
+        }
+        if !errors.is_empty() {
+            return Err(ValidationError!("merge failed validation").with_fields(errors));
+        }
+        let keep = Pupil::one_from_db(user, self.keep, db).await?;
+        let remove = Pupil::one_from_db(user, self.remove, db).await?;
+        let mut merged = keep.clone();
+        for field in &self.take {
+            take_field(&mut merged, &remove, field);
+        }
+        merged.validate(config)?;
+        // the removed pupil's UPN is freed up by the merge
+        if merged.upn != remove.upn {
+            merged.validate_unique(db).await?;
+        }
+        let txn = db.begin().await?;
+        let linked = move_linked(&txn, keep.id, remove.id, !self.dry_run).await?;
+        let differences = differences(&keep, &remove);
+        if self.dry_run {
+            txn.rollback().await?;
+            return Ok(MergeReport {
+                merged,
+                removed: remove,
+                differences,
+                linked,
+                committed: false,
+            });
+        }
+        tracing::info!(
+            "{} merging pupil {} into {}",
+            user.email_address,
+            remove.id,
+            keep.id
+        );
+        // everything still linked to the removed pupil goes when they're deleted, so their photo
+        // and flag periods are moved first, and their UPN cleared so the kept pupil can take it
+        let old_photos = PupilPhoto::move_to(remove.id, keep.id, store, &txn).await?;
+        entity::pupil::ActiveModel {
+            id: Unchanged(remove.id),
+            upn: Set(None),
+            ..Default::default()
+        }
+        .update(&txn)
+        .await?;
+        merged.update(&txn).await?;
+        FlagPeriod::move_to(&txn, remove.id, keep.id).await?;
+        entity::pupil::Entity::delete_by_id(remove.id)
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        for key in old_photos {
+            store.delete(&key).await?;
+        }
+        Ok(MergeReport {
+            merged: Pupil::one_from_db(user, keep.id, db).await?,
+            removed: remove,
+            differences,
+            linked,
+            committed: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn every_field_can_be_taken() {
+        let keep = crate::pupil::import::blank("H801200001001");
+        let mut remove = crate::pupil::import::blank("X801200001002");
+        remove.first_names = "Benjamin".into();
+        remove.last_name = "Jones".into();
+        remove.year = 4;
+        remove.start_date = "2020-09-01".parse().unwrap();
+        remove.end_date = Some("2023-07-21".parse().unwrap());
+        remove.active = false;
+        remove.gender = "male".into();
+        remove.date_of_birth = Some("2015-03-01".parse().unwrap());
+        remove.preferred_first_names = Some("Ben".into());
+        remove.preferred_last_name = Some("Jones-Smith".into());
+        remove.home_language = Some("WEL".into());
+        let mut merged = keep.clone();
+        for field in FIELDS {
+            take_field(&mut merged, &remove, field);
+        }
+        assert_eq!(merged.id, keep.id);
+        merged.id = remove.id;
+        assert_eq!(merged, remove);
+    }
+
+    #[rstest]
+    fn differences_are_listed_by_field() {
+        let keep = crate::pupil::import::blank("H801200001001");
+        let mut remove = keep.clone();
+        remove.id = Uuid::new_v4();
+        remove.first_names = "Ben".into();
+        remove.date_of_birth = Some("2015-03-01".parse().unwrap());
+        assert_eq!(
+            differences(&keep, &remove),
+            [
+                Difference {
+                    field: "first_names".into(),
+                    keep: json!(""),
+                    remove: json!("Ben"),
+                },
+                Difference {
+                    field: "date_of_birth".into(),
+                    keep: Value::Null,
+                    remove: json!("2015-03-01"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use super::duplicates::{pupil_pairs, user_pairs, PupilPair, UserPair};
+use crate::{
+    app::config::Config,
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    user::model::User,
+    utils,
+};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+pub(crate) const ADMIN_ONLY: &str = "only an admin can check data quality or merge pupils";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subject {
+    Pupil,
+    User,
+}
+
+/// A missing or inconsistent value on one record. `id` is the pupil's id or the user's email.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Issue {
+    pub(crate) subject: Subject,
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) field: String,
+    pub(crate) message: String,
+}
+
+/// Fields a pupil can be saved without that the school still needs, then whatever the pupil's own
+/// validation turns up.
+pub fn pupil_issues(pupil: &Pupil, config: &Config) -> Vec<Issue> {
+    let mut found: Vec<(String, String)> = vec![];
+    if pupil.upn.is_none() {
+        found.push(("upn".into(), "UPN is missing".into()));
+    }
+    if pupil.date_of_birth.is_none() {
+        found.push(("date_of_birth".into(), "date of birth is missing".into()));
+    }
+    if let Err(error) = pupil.validate(config) {
+        found.extend(error.fields.unwrap_or_default());
+    }
+    found
+        .into_iter()
+        .map(|(field, message)| Issue {
+            subject: Subject::Pupil,
+            id: pupil.id.to_string(),
+            name: format!("{} {}", pupil.first_names, pupil.last_name),
+            field,
+            message,
+        })
+        .collect()
+}
+
+/// The checks made when an account is created, for accounts made before they were.
+pub fn user_issues(user: &User) -> Vec<Issue> {
+    let mut found: Vec<(&str, String)> = vec![];
+    if user.first_names.trim().is_empty() {
+        found.push(("first_names", "first names are missing".into()));
+    }
+    if user.last_name.trim().is_empty() {
+        found.push(("last_name", "last name is missing".into()));
+    }
+    if !utils::is_valid_email(&user.email_address) {
+        found.push(("email_address", "email address is invalid".into()));
+    }
+    if user.years.is_empty() {
+        found.push(("years", "not given any year groups".into()));
+    }
+    for role in user
+        .roles
+        .iter()
+        .filter(|role| !constant::ROLES.contains(&role.as_str()))
+    {
+        found.push(("roles", format!("{role} is not a role")));
+    }
+    found
+        .into_iter()
+        .map(|(field, message)| Issue {
+            subject: Subject::User,
+            id: user.email_address.clone(),
+            name: format!("{} {}", user.first_names, user.last_name),
+            field: field.into(),
+            message,
+        })
+        .collect()
+}
+
+/// Likely duplicates and problems across the pupils in the admin's years and every user account.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QualityReport {
+    pub(crate) checked_pupils: usize,
+    pub(crate) checked_users: usize,
+    pub(crate) pupil_duplicates: Vec<PupilPair>,
+    pub(crate) user_duplicates: Vec<UserPair>,
+    pub(crate) issues: Vec<Issue>,
+}
+
+impl QualityReport {
+    pub async fn from_db(user: &User, config: &Config, db: &DatabaseConnection) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        let pupils = Pupil::all_from_db(user, db).await?;
+        let users = User::all_from_db(db).await?;
+        Ok(Self::build(&pupils, &users, config))
+    }
+
+    pub fn build(pupils: &[Pupil], users: &[User], config: &Config) -> Self {
+        let mut issues: Vec<Issue> = pupils
+            .iter()
+            .flat_map(|pupil| pupil_issues(pupil, config))
+            .chain(users.iter().flat_map(user_issues))
+            .collect();
+        issues.sort_by(|a, b| (a.subject, &a.name, &a.id).cmp(&(b.subject, &b.name, &b.id)));
+        Self {
+            checked_pupils: pupils.len(),
+            checked_users: users.len(),
+            pupil_duplicates: pupil_pairs(pupils),
+            user_duplicates: user_pairs(users),
+            issues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn fields(issues: &[Issue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.field.as_str()).collect()
+    }
+
+    #[rstest]
+    fn inactive_pupil_without_a_leave_date() {
+        let mut pupil = crate::pupil::import::blank("H801200001001");
+        pupil.first_names = "Ben".into();
+        pupil.last_name = "Jones".into();
+        pupil.gender = "male".into();
+        pupil.active = false;
+        let issues = pupil_issues(&pupil, &Config::default());
+        assert_eq!(fields(&issues), ["date_of_birth", "end_date"]);
+        assert_eq!(
+            issues[1].message,
+            "an inactive pupil must have a leave date"
+        );
+        assert_eq!(issues[1].id, pupil.id.to_string());
+    }
+
+    #[rstest]
+    fn users_are_checked_like_new_accounts() {
+        let mut user = User::new("", "Teacher", "not an email", "pass", vec![]);
+        user.roles = vec!["admin".into(), "caretaker".into()];
+        let issues = user_issues(&user);
+        assert_eq!(
+            fields(&issues),
+            ["first_names", "email_address", "years", "roles"]
+        );
+        assert_eq!(issues[3].message, "caretaker is not a role");
+    }
+
+    #[rstest]
+    fn report_puts_pupils_before_users() {
+        let mut pupil = crate::pupil::import::blank("");
+        pupil.first_names = "Zoe".into();
+        pupil.last_name = "Young".into();
+        pupil.gender = "female".into();
+        pupil.upn = None;
+        let user = User::new("Amy", "Adams", "amy@school.org", "pass", vec![]);
+        let report = QualityReport::build(&[pupil], &[user], &Config::default());
+        assert_eq!(report.checked_pupils, 1);
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .map(|issue| (issue.subject, issue.field.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (Subject::Pupil, "upn"),
+                (Subject::Pupil, "date_of_birth"),
+                (Subject::User, "years"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod handlers;
+pub mod model;
+pub mod pdf;
+pub mod render;
@@ -0,0 +1,210 @@
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    pupil::model::Pupil,
+    report::model::{ReportTemplate, SignOff},
+    user::model::User,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+use chrono::Utc;
+use serde_json::json;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn pass_through(error: Error) -> Error {
+    match error.kind {
+        ErrorKind::DatabaseError => DatabaseError!(error.to_string()),
+        ErrorKind::ReportTemplateDoesNotExist => ReportTemplateDoesNotExist!(),
+        ErrorKind::PupilDoesNotExist => PupilDoesNotExist!(),
+        ErrorKind::ValidationError | ErrorKind::Unauthorised | ErrorKind::ServerError => error,
+        _ => UnknownError!(),
+    }
+}
+
+pub async fn get_report_templates(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested report templates");
+    match ReportTemplate::all_from_db(state.database().as_ref()).await {
+        Ok(templates) => Ok(Json(json!(templates))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+pub async fn create_report_template(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+    Json(mut template): Json<ReportTemplate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("creating report template {:?}", template);
+    template.id = Uuid::new_v4();
+    match template.save(&user, state.database().as_ref()).await {
+        Ok(template) => Ok(Json(json!(template))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+pub async fn update_report_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(mut template): Json<ReportTemplate>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("updating report template {id}");
+    let id = Uuid::from_str(&id)?;
+    let db = state.database().as_ref();
+    let result = match ReportTemplate::one_from_db(id, db).await {
+        Ok(_) => {
+            template.id = id;
+            template.save(&user, db).await
+        }
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(template) => Ok(Json(json!(template))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+pub async fn delete_report_template(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+) -> Result<()> {
+    tracing::debug!("deleting report template {id}");
+    let id = Uuid::from_str(&id)?;
+    let db = state.database().as_ref();
+    let result = match ReportTemplate::one_from_db(id, db).await {
+        Ok(template) => template.delete(&user, db).await,
+        Err(error) => Err(error),
+    };
+    result.map_err(pass_through)
+}
+
+/// The active pupils in a year and who has signed off each of their reports.
+pub async fn get_report_statuses(
+    State(state): State<AppState>,
+    Path((id, year)): Path<(String, i32)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested report statuses for {id} in year {year}");
+    let id = Uuid::from_str(&id)?;
+    let db = state.database().as_ref();
+    let result = match ReportTemplate::one_from_db(id, db).await {
+        Ok(template) => template.statuses(&user, year, db).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(statuses) => Ok(Json(json!(statuses))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+/// Every report in a year as PDFs in one ZIP, with a CSV of their sign-off status.
+pub async fn download_class_reports(
+    State(state): State<AppState>,
+    Path((id, year)): Path<(String, i32)>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("generating the year {year} reports for {id}");
+    let id = Uuid::from_str(&id)?;
+    let db = state.database().as_ref();
+    let (template, data) = match ReportTemplate::one_from_db(id, db).await {
+        Ok(template) => match template.class_zip(&user, year, state.config(), db).await {
+            Ok(data) => (template, data),
+            Err(error) => return Err(pass_through(error)),
+        },
+        Err(error) => return Err(pass_through(error)),
+    };
+    let file_name = format!(
+        "{}_year_{year}_{}.zip",
+        template.name.replace(|c: char| !c.is_alphanumeric(), "_"),
+        Utc::now().date_naive().format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        data,
+    ))
+}
+
+/// A single pupil's report as a PDF, marked as a draft until it's signed off.
+pub async fn download_pupil_report(
+    State(state): State<AppState>,
+    Path((id, pupil_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("generating report {id} for pupil {pupil_id}");
+    let id = Uuid::from_str(&id)?;
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let db = state.database().as_ref();
+    let template = ReportTemplate::one_from_db(id, db)
+        .await
+        .map_err(pass_through)?;
+    let pupil = Pupil::one_from_db(&user, pupil_id, db)
+        .await
+        .map_err(pass_through)?;
+    let mut taken = Default::default();
+    let file_name = crate::report::model::file_name(&pupil, &mut taken);
+    let document = template
+        .render_for(&user, pupil, state.config(), db)
+        .await
+        .map_err(pass_through)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        document.to_pdf(),
+    ))
+}
+
+pub async fn sign_off_report(
+    State(state): State<AppState>,
+    Path((id, pupil_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("signing off report {id} for pupil {pupil_id}");
+    let id = Uuid::from_str(&id)?;
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let db = state.database().as_ref();
+    let result = match ReportTemplate::one_from_db(id, db).await {
+        Ok(template) => SignOff::sign(&user, &template, pupil_id, db).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(sign_off) => Ok(Json(json!(sign_off))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+pub async fn withdraw_report_sign_off(
+    State(state): State<AppState>,
+    Path((id, pupil_id)): Path<(String, String)>,
+    Extension(user): Extension<User>,
+) -> Result<()> {
+    tracing::debug!("withdrawing sign-off of report {id} for pupil {pupil_id}");
+    let id = Uuid::from_str(&id)?;
+    let pupil_id = Uuid::from_str(&pupil_id)?;
+    let db = state.database().as_ref();
+    let result = match ReportTemplate::one_from_db(id, db).await {
+        Ok(template) => SignOff::withdraw(&user, &template, pupil_id, db).await,
+        Err(error) => Err(error),
+    };
+    result.map_err(pass_through)
+}
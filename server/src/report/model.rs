@@ -0,0 +1,484 @@
+use crate::{
+    app::config::Config,
+    assessment::result::PupilResult,
+    attendance::summary::AttendanceSummary,
+    comment::model::{Comment, Visibility},
+    core::{constant, error::Result},
+    pupil::model::Pupil,
+    report::{pdf::Document, render::*},
+    user::model::User,
+};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use entity::{report_sign_off, report_template};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{Cursor, Write},
+};
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+const ADMIN_ONLY: &str = "only an admin can change report templates";
+
+/// An end of term report, written once with placeholders and filled in for each pupil from what's
+/// been recorded between its dates.
+#[derive(Clone, Debug, Serialize, PartialEq, Deserialize)]
+pub struct ReportTemplate {
+    #[serde(default = "uuid::Uuid::new_v4")]
+    pub(crate) id: Uuid,
+    pub(crate) name: String,
+    pub(crate) body: String,
+    pub(crate) from_date: NaiveDate,
+    pub(crate) to_date: NaiveDate,
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub(crate) updated_by: Option<String>,
+    #[serde(default, skip_deserializing)]
+    pub(crate) updated_at: NaiveDateTime,
+}
+
+/// A teacher's confirmation that a pupil's report is ready to go home.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SignOff {
+    pub(crate) template_id: Uuid,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) signed_off_by: String,
+    pub(crate) signed_off_at: NaiveDateTime,
+}
+
+/// Where a pupil's report has got to, for the class list.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ReportStatus {
+    pub(crate) pupil_id: Uuid,
+    pub(crate) first_names: String,
+    pub(crate) last_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) signed_off_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) signed_off_at: Option<NaiveDateTime>,
+}
+
+impl ReportTemplate {
+    pub async fn one_from_db(id: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        match report_template::Entity::find_by_id(id).one(db).await? {
+            Some(template) => Ok(template.into()),
+            None => Err(ReportTemplateDoesNotExist!()),
+        }
+    }
+
+    /// Every template, the latest reporting period first.
+    pub async fn all_from_db(db: &DatabaseConnection) -> Result<Vec<Self>> {
+        Ok(report_template::Entity::find()
+            .order_by_desc(report_template::Column::ToDate)
+            .order_by_asc(report_template::Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Create or replace the template, which only an admin can do.
+    pub async fn save(&mut self, user: &User, db: &DatabaseConnection) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        self.validate()?;
+        self.updated_by = Some(user.email_address.clone());
+        self.updated_at = Utc::now().naive_utc();
+        let model = report_template::ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name.clone()),
+            body: Set(self.body.clone()),
+            from_date: Set(self.from_date),
+            to_date: Set(self.to_date),
+            updated_by: Set(self.updated_by.clone()),
+            updated_at: Set(self.updated_at),
+        };
+        let saved = if report_template::Entity::find_by_id(self.id)
+            .one(db)
+            .await?
+            .is_some()
+        {
+            model.update(db).await?
+        } else {
+            model.insert(db).await?
+        };
+        Ok(saved.into())
+    }
+
+    /// Remove the template along with its sign-offs, which only an admin can do.
+    pub async fn delete(&self, user: &User, db: &DatabaseConnection) -> Result<()> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        report_sign_off::Entity::delete_many()
+            .filter(report_sign_off::Column::TemplateId.eq(self.id))
+            .exec(db)
+            .await?;
+        report_template::Entity::delete_by_id(self.id)
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut errors: BTreeMap<String, String> = BTreeMap::new();
+        if self.name.trim().is_empty() {
+            errors.insert("name".into(), "a report needs a name".into());
+        }
+        if self.to_date < self.from_date {
+            errors.insert(
+                "to_date".into(),
+                "the report can't end before it starts".into(),
+            );
+        }
+        let problems = check_body(&self.body);
+        if !problems.is_empty() {
+            errors.insert("body".into(), problems.join("; "));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError!("report template failed validation").with_fields(errors))
+        }
+    }
+
+    /// Everything the pupil's report is filled in from, limited to the reporting period.
+    pub async fn data_for(
+        &self,
+        user: &User,
+        pupil: Pupil,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<ReportData> {
+        let in_period = |date: NaiveDate| date >= self.from_date && date <= self.to_date;
+        let comments = Comment::all_for_pupil(user, pupil.id, db)
+            .await?
+            .into_iter()
+            .filter(|comment| {
+                comment.visibility == Visibility::Shared && in_period(comment.created_at.date())
+            })
+            .rev()
+            .collect();
+        let results = PupilResult::all_for_pupil(user, pupil.id, db)
+            .await?
+            .into_iter()
+            .filter(|result| in_period(result.assessment.date))
+            .collect();
+        let attendance =
+            AttendanceSummary::between(&[pupil.id], self.from_date, self.to_date, config, db)
+                .await?
+                .remove(&pupil.id)
+                .unwrap_or_else(|| {
+                    AttendanceSummary::from_meanings([], config.persistent_absence_percent)
+                        .with_dates(self.from_date, self.to_date)
+                });
+        Ok(ReportData {
+            pupil,
+            comments,
+            results,
+            attendance,
+            school: config.school_name.clone(),
+            today: Utc::now().date_naive(),
+        })
+    }
+
+    /// The pupil's report, marked as a draft in the footer until it's signed off.
+    pub async fn render_for(
+        &self,
+        user: &User,
+        pupil: Pupil,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<Document> {
+        let sign_off = SignOff::one_from_db(self.id, pupil.id, db).await?;
+        let data = self.data_for(user, pupil, config, db).await?;
+        Ok(data.render(&self.name, &self.body, footer(sign_off.as_ref())))
+    }
+
+    /// One PDF per active pupil in the year, named after them, with a CSV of who has been signed
+    /// off and by whom.
+    pub async fn class_zip(
+        &self,
+        user: &User,
+        year: i32,
+        config: &Config,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<u8>> {
+        user.check_year(year)?;
+        let sign_offs = SignOff::all_for_template(self.id, db).await?;
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let zip_error = |error: zip::result::ZipError| {
+            ServerError!(format!("failed to write report zip: {error}"))
+        };
+        let io_error =
+            |error: std::io::Error| ServerError!(format!("failed to write report zip: {error}"));
+        let mut taken: HashSet<String> = HashSet::new();
+        let mut csv = csv::Writer::from_writer(vec![]);
+        let csv_error =
+            |error: csv::Error| ServerError!(format!("failed to write report zip: {error}"));
+        csv.write_record([
+            "last_name",
+            "first_names",
+            "file",
+            "signed_off_by",
+            "signed_off_at",
+        ])
+        .map_err(csv_error)?;
+        for pupil in class(year, db).await? {
+            let file = file_name(&pupil, &mut taken);
+            let sign_off = sign_offs.get(&pupil.id);
+            csv.write_record([
+                pupil.last_name.as_str(),
+                pupil.first_names.as_str(),
+                file.as_str(),
+                sign_off
+                    .map(|s| s.signed_off_by.as_str())
+                    .unwrap_or_default(),
+                sign_off
+                    .map(|s| s.signed_off_at.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default()
+                    .as_str(),
+            ])
+            .map_err(csv_error)?;
+            let data = self.data_for(user, pupil, config, db).await?;
+            let document = data.render(&self.name, &self.body, footer(sign_off));
+            zip.start_file(file, options).map_err(zip_error)?;
+            zip.write_all(&document.to_pdf()).map_err(io_error)?;
+        }
+        zip.start_file("sign_off.csv", options).map_err(zip_error)?;
+        zip.write_all(
+            &csv.into_inner()
+                .map_err(|error| ServerError!(format!("failed to write report zip: {error}")))?,
+        )
+        .map_err(io_error)?;
+        Ok(zip.finish().map_err(zip_error)?.into_inner())
+    }
+
+    /// The active pupils in the year and whether their reports have been signed off.
+    pub async fn statuses(
+        &self,
+        user: &User,
+        year: i32,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<ReportStatus>> {
+        user.check_year(year)?;
+        let mut sign_offs = SignOff::all_for_template(self.id, db).await?;
+        Ok(class(year, db)
+            .await?
+            .into_iter()
+            .map(|pupil| {
+                let sign_off = sign_offs.remove(&pupil.id);
+                ReportStatus {
+                    pupil_id: pupil.id,
+                    first_names: pupil.first_names,
+                    last_name: pupil.last_name,
+                    signed_off_by: sign_off.as_ref().map(|s| s.signed_off_by.clone()),
+                    signed_off_at: sign_off.map(|s| s.signed_off_at),
+                }
+            })
+            .collect())
+    }
+}
+
+impl SignOff {
+    pub async fn one_from_db(
+        template_id: Uuid,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Option<Self>> {
+        Ok(report_sign_off::Entity::find_by_id((template_id, pupil_id))
+            .one(db)
+            .await?
+            .map(Into::into))
+    }
+
+    async fn all_for_template(
+        template_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<HashMap<Uuid, Self>> {
+        Ok(report_sign_off::Entity::find()
+            .filter(report_sign_off::Column::TemplateId.eq(template_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|model| (model.pupil_id, model.into()))
+            .collect())
+    }
+
+    /// Sign off a pupil's report as the user, who must be able to see the pupil. Signing off again
+    /// keeps the first sign-off.
+    pub async fn sign(
+        user: &User,
+        template: &ReportTemplate,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        if let Some(existing) = Self::one_from_db(template.id, pupil_id, db).await? {
+            return Ok(existing);
+        }
+        Ok(report_sign_off::ActiveModel {
+            template_id: Set(template.id),
+            pupil_id: Set(pupil_id),
+            signed_off_by: Set(user.email_address.clone()),
+            signed_off_at: Set(Utc::now().naive_utc()),
+        }
+        .insert(db)
+        .await?
+        .into())
+    }
+
+    /// Take a sign-off back so the report can be changed, which only whoever signed it off or an
+    /// admin can do.
+    pub async fn withdraw(
+        user: &User,
+        template: &ReportTemplate,
+        pupil_id: Uuid,
+        db: &DatabaseConnection,
+    ) -> Result<()> {
+        Pupil::one_from_db(user, pupil_id, db).await?;
+        let Some(existing) = Self::one_from_db(template.id, pupil_id, db).await? else {
+            return Ok(());
+        };
+        if existing.signed_off_by != user.email_address && !user.has_role(constant::ROLE_ADMIN) {
+            return Err(Unauthorised!(
+                "only whoever signed off a report or an admin can withdraw it"
+            ));
+        }
+        report_sign_off::Entity::delete_by_id((template.id, pupil_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Active pupils in the year, in register order.
+async fn class(year: i32, db: &DatabaseConnection) -> Result<Vec<Pupil>> {
+    Ok(entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Year.eq(year))
+        .filter(entity::pupil::Column::Active.eq(true))
+        .order_by_asc(entity::pupil::Column::LastName)
+        .order_by_asc(entity::pupil::Column::FirstNames)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
+fn footer(sign_off: Option<&SignOff>) -> String {
+    match sign_off {
+        Some(sign_off) => format!(
+            "Signed off by {} on {}",
+            sign_off.signed_off_by,
+            sign_off.signed_off_at.format("%d/%m/%Y")
+        ),
+        None => "Draft - not yet signed off".into(),
+    }
+}
+
+/// `Last_First.pdf` with anything that isn't safe in a file name swapped for an underscore, and a
+/// number added when two pupils share a name.
+pub fn file_name(pupil: &Pupil, taken: &mut HashSet<String>) -> String {
+    let safe = |name: &str| -> String {
+        name.trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let stem = format!("{}_{}", safe(&pupil.last_name), safe(&pupil.first_names));
+    let mut name = format!("{stem}.pdf");
+    let mut number = 1;
+    while !taken.insert(name.to_lowercase()) {
+        number += 1;
+        name = format!("{stem}_{number}.pdf");
+    }
+    name
+}
+
+impl From<report_template::Model> for ReportTemplate {
+    fn from(value: report_template::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            body: value.body,
+            from_date: value.from_date,
+            to_date: value.to_date,
+            updated_by: value.updated_by,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl From<report_sign_off::Model> for SignOff {
+    fn from(value: report_sign_off::Model) -> Self {
+        Self {
+            template_id: value.template_id,
+            pupil_id: value.pupil_id,
+            signed_off_by: value.signed_off_by,
+            signed_off_at: value.signed_off_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pupil::import::blank;
+    use rstest::*;
+
+    fn template(name: &str, from: &str, to: &str, body: &str) -> ReportTemplate {
+        ReportTemplate {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            body: body.into(),
+            from_date: from.parse().unwrap(),
+            to_date: to.parse().unwrap(),
+            updated_by: None,
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[rstest]
+    fn test_validate() {
+        assert!(template("Summer", "2023-04-17", "2023-07-21", "{{name}}")
+            .validate()
+            .is_ok());
+        let error = template(" ", "2023-07-21", "2023-04-17", "{{nmae}}")
+            .validate()
+            .unwrap_err();
+        let fields = error.fields.unwrap();
+        assert_eq!(
+            fields.keys().collect::<Vec<&String>>(),
+            ["body", "name", "to_date"]
+        );
+        assert_eq!(fields["body"], "line 1: there's no {{nmae}} placeholder");
+    }
+
+    #[rstest]
+    fn test_file_names_are_safe_and_unique() {
+        let mut taken = HashSet::new();
+        let mut pupil = blank("");
+        pupil.first_names = "Mary Anne".into();
+        pupil.last_name = "O'Neill".into();
+        assert_eq!(file_name(&pupil, &mut taken), "O_Neill_Mary_Anne.pdf");
+        assert_eq!(file_name(&pupil, &mut taken), "O_Neill_Mary_Anne_2.pdf");
+        pupil.last_name = "o/neill".into();
+        assert_eq!(file_name(&pupil, &mut taken), "o_neill_Mary_Anne_3.pdf");
+    }
+
+    #[rstest]
+    fn test_only_admins_change_templates() {
+        let mut user = User::new("test", "user", "t@t.com", "pass", vec![5, 6]);
+        assert!(user.check_role(constant::ROLE_ADMIN, "admin").is_err());
+        user.roles = vec![constant::ROLE_ADMIN.into()];
+        assert!(user.check_role(constant::ROLE_ADMIN, "admin").is_ok());
+    }
+}
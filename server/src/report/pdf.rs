@@ -0,0 +1,462 @@
+//! Just enough PDF to lay out a report: A4 pages of wrapped text in the standard Helvetica fonts,
+//! which every reader has built in, so nothing needs embedding and no browser is involved. The
+//! Welsh letters outside WinAnsi are put together from a letter and an accent the fonts do have.
+
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 56.0;
+const INDENT: f64 = 14.0;
+const FOOTER_SIZE: f64 = 9.0;
+
+/// WinAnsi's circumflex accent, drawn over a letter for the Welsh ones the standard fonts lack
+const CIRCUMFLEX: u8 = 0x88;
+const CIRCUMFLEX_WIDTH: u16 = 333;
+/// How far a capital's accent is raised above a small letter's, the cap height less the x-height
+const CAPITAL_RISE: f64 = 195.0;
+/// Codes WinAnsi leaves unused, standing in for ŵ, ŷ, Ŵ and Ŷ until they're drawn
+const ACCENTED: [(char, u8, u8); 4] = [
+    ('ŵ', 0x81, b'w'),
+    ('ŷ', 0x8d, b'y'),
+    ('Ŵ', 0x8f, b'W'),
+    ('Ŷ', 0x90, b'Y'),
+];
+
+/// The letter an accented code is drawn on, if it is one
+fn base_letter(byte: u8) -> Option<u8> {
+    ACCENTED
+        .iter()
+        .find(|(_, code, _)| *code == byte)
+        .map(|(_, _, base)| *base)
+}
+
+/// Helvetica widths for the printable ASCII characters, in thousandths of the font size
+const REGULAR_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+const BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    Title(String),
+    Heading(String),
+    Paragraph(String),
+    /// a bulleted line
+    Item(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn name(&self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+
+    /// Width of one encoded character in thousandths of the font size
+    fn char_width(&self, byte: u8) -> u16 {
+        let widths = match self {
+            Font::Regular => &REGULAR_WIDTHS,
+            Font::Bold => &BOLD_WIDTHS,
+        };
+        match base_letter(byte).unwrap_or(byte) {
+            byte @ 32..=126 => widths[(byte - 32) as usize],
+            0x95 => 350,
+            0x97 => 1000,
+            0x91 | 0x92 => 222,
+            0x93 | 0x94 | CIRCUMFLEX => CIRCUMFLEX_WIDTH,
+            _ => 556,
+        }
+    }
+
+    /// Width of the text in points, after it's been encoded
+    fn width(&self, text: &[u8], size: f64) -> f64 {
+        text.iter()
+            .map(|byte| self.char_width(*byte) as f64)
+            .sum::<f64>()
+            * size
+            / 1000.0
+    }
+
+    /// The operators to show encoded text from the current point. The Welsh letters with a
+    /// circumflex are drawn as their base letter with the accent centred over it.
+    fn show(&self, text: &[u8], size: f64) -> String {
+        let mut out = String::new();
+        let mut run: Vec<u8> = vec![];
+        for byte in text {
+            let Some(base) = base_letter(*byte) else {
+                run.push(*byte);
+                continue;
+            };
+            run.push(base);
+            let letter = self.char_width(base) as f64;
+            let side = (letter - CIRCUMFLEX_WIDTH as f64) / 2.0;
+            // back from the end of the letter to where the accent starts, then on past it
+            out.push_str(&format!("[{} {}] TJ ", literal(&run), letter - side));
+            run.clear();
+            let rise = base
+                .is_ascii_uppercase()
+                .then(|| CAPITAL_RISE * size / 1000.0);
+            if let Some(rise) = rise {
+                out.push_str(&format!("{rise} Ts "));
+            }
+            out.push_str(&format!("[{} {}] TJ ", literal(&[CIRCUMFLEX]), -side));
+            if rise.is_some() {
+                out.push_str("0 Ts ");
+            }
+        }
+        if !run.is_empty() || out.is_empty() {
+            out.push_str(&format!("{} Tj ", literal(&run)));
+        }
+        out
+    }
+}
+
+/// Text as WinAnsi bytes. Anything the standard fonts can't show becomes a question mark.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            'ŵ' | 'ŷ' | 'Ŵ' | 'Ŷ' => ACCENTED
+                .iter()
+                .find(|(letter, _, _)| *letter == c)
+                .map(|(_, code, _)| *code)
+                .unwrap_or(b'?'),
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '€' => 0x80,
+            '\t' => b' ',
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// A PDF literal string, escaping brackets and backslashes and anything outside ASCII
+fn literal(text: &[u8]) -> String {
+    let mut out = String::from("(");
+    for byte in text {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(*byte as char);
+            }
+            32..=126 => out.push(*byte as char),
+            _ => out.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// A PDF text string for the document information, in UTF-16 so nothing has to be replaced
+fn text_string(text: &str) -> String {
+    let mut out = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        out.push_str(&format!("{unit:04X}"));
+    }
+    out.push('>');
+    out
+}
+
+/// Split text into lines no wider than the width, breaking between words where it can.
+fn wrap(text: &[u8], font: Font, size: f64, width: f64) -> Vec<Vec<u8>> {
+    let mut lines: Vec<Vec<u8>> = vec![];
+    let mut line: Vec<u8> = vec![];
+    for word in text.split(|b| *b == b' ').filter(|w| !w.is_empty()) {
+        let mut candidate = line.clone();
+        if !candidate.is_empty() {
+            candidate.push(b' ');
+        }
+        candidate.extend_from_slice(word);
+        if font.width(&candidate, size) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        // a word too long for a line of its own is broken wherever it runs out
+        for byte in word {
+            line.push(*byte);
+            if font.width(&line, size) > width && line.len() > 1 {
+                let last = line.pop().unwrap_or_default();
+                lines.push(std::mem::replace(&mut line, vec![last]));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// A line placed on a page
+struct Placed {
+    font: Font,
+    size: f64,
+    x: f64,
+    y: f64,
+    text: Vec<u8>,
+}
+
+/// A report ready to be written out, with a footer on every page
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    pub(crate) title: String,
+    pub(crate) footer: String,
+    pub(crate) blocks: Vec<Block>,
+}
+
+impl Document {
+    fn layout(&self) -> Vec<Vec<Placed>> {
+        let top = PAGE_HEIGHT - MARGIN;
+        let bottom = MARGIN + FOOTER_SIZE * 2.0;
+        let width = PAGE_WIDTH - MARGIN * 2.0;
+        let mut pages: Vec<Vec<Placed>> = vec![vec![]];
+        let mut y = top;
+        for block in &self.blocks {
+            let (font, size, indent, space_before, space_after) = match block {
+                Block::Title(_) => (Font::Bold, 18.0, 0.0, 0.0, 8.0),
+                Block::Heading(_) => (Font::Bold, 13.0, 0.0, 8.0, 4.0),
+                Block::Paragraph(_) => (Font::Regular, 11.0, 0.0, 0.0, 6.0),
+                Block::Item(_) => (Font::Regular, 11.0, INDENT, 0.0, 2.0),
+            };
+            let text = match block {
+                Block::Title(text)
+                | Block::Heading(text)
+                | Block::Paragraph(text)
+                | Block::Item(text) => encode(text),
+            };
+            let line_height = size * 1.3;
+            if y < top {
+                y -= space_before;
+            }
+            for (i, line) in wrap(&text, font, size, width - indent)
+                .into_iter()
+                .enumerate()
+            {
+                if y - line_height < bottom {
+                    pages.push(vec![]);
+                    y = top;
+                }
+                y -= line_height;
+                let page = pages.last_mut().expect("there's always a page");
+                if i == 0 && matches!(block, Block::Item(_)) {
+                    page.push(Placed {
+                        font,
+                        size,
+                        x: MARGIN + 3.0,
+                        y,
+                        text: vec![0x95],
+                    });
+                }
+                page.push(Placed {
+                    font,
+                    size,
+                    x: MARGIN + indent,
+                    y,
+                    text: line,
+                });
+            }
+            y -= space_after;
+        }
+        pages
+    }
+
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let pages = self.layout();
+        let count = pages.len();
+        // the catalog, the page tree, two fonts and the document info come first, then a page
+        // and its contents for each page
+        let mut objects: Vec<String> = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".into(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {count} >>",
+                (0..count)
+                    .map(|i| format!("{} 0 R", 6 + i * 2))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .into(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .into(),
+            format!(
+                "<< /Title {} /Producer (Learner Tracker) >>",
+                text_string(&self.title)
+            ),
+        ];
+        for (number, page) in pages.into_iter().enumerate() {
+            let mut content = String::new();
+            let footer = encode(&format!(
+                "{}    Page {} of {count}",
+                self.footer,
+                number + 1
+            ));
+            let footer = Placed {
+                font: Font::Regular,
+                x: (PAGE_WIDTH - Font::Regular.width(&footer, FOOTER_SIZE)) / 2.0,
+                y: MARGIN - FOOTER_SIZE,
+                size: FOOTER_SIZE,
+                text: footer,
+            };
+            for line in page.iter().chain([&footer]) {
+                content.push_str(&format!(
+                    "BT /{} {} Tf {:.2} {:.2} Td {}ET\n",
+                    line.font.name(),
+                    line.size,
+                    line.x,
+                    line.y,
+                    line.font.show(&line.text, line.size)
+                ));
+            }
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                7 + number * 2
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ));
+        }
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+        }
+        let xref = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn find(haystack: &[u8], needle: &str) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle.as_bytes())
+    }
+
+    #[rstest]
+    fn test_wrap_breaks_between_words() {
+        let text = encode("the quick brown fox jumps over the lazy dog");
+        let lines = wrap(&text, Font::Regular, 10.0, 100.0);
+        assert_eq!(lines.len(), 3);
+        assert!(lines
+            .iter()
+            .all(|line| Font::Regular.width(line, 10.0) <= 100.0));
+        assert_eq!(lines[0], b"the quick brown fox");
+        let long = wrap(&[b'm'; 40], Font::Regular, 10.0, 100.0);
+        assert_eq!(long[0].len(), 12);
+    }
+
+    #[rstest]
+    fn test_encode_and_escape() {
+        assert_eq!(encode("café • (ok) 😀"), b"caf\xe9 \x95 (ok) ?");
+        assert_eq!(literal(b"a (b) \\ \xe9"), "(a \\(b\\) \\\\ \\351)");
+    }
+
+    #[rstest]
+    fn test_welsh_circumflexes_are_drawn_with_an_accent() {
+        let text = encode("Ŵyr ŷd");
+        assert!(!text.contains(&b'?'));
+        assert_eq!(
+            Font::Regular.width(&text, 10.0),
+            Font::Regular.width(b"Wyr yd", 10.0)
+        );
+        assert_eq!(
+            Font::Regular.show(&text, 10.0),
+            "[(W) 638.5] TJ 1.95 Ts [(\\210) -305.5] TJ 0 Ts \
+             [(yr y) 416.5] TJ [(\\210) -83.5] TJ (d) Tj "
+        );
+        let document = Document {
+            title: "Adroddiad Gŵyl".into(),
+            footer: "Ysgol".into(),
+            blocks: vec![Block::Paragraph("Mae hi'n ŵyr i Siôn.".into())],
+        };
+        let pdf = document.to_pdf();
+        assert!(find(
+            &pdf,
+            "[(Mae hi'n w) 527.5] TJ [(\\210) -194.5] TJ (yr i Si\\364n.) Tj "
+        )
+        .is_some());
+        assert!(find(&pdf, "/Title <FEFF").is_some());
+        assert!(find(&pdf, "0175").is_some());
+    }
+
+    #[rstest]
+    fn test_cross_reference_table_points_at_every_object() {
+        let document = Document {
+            title: "Summer report".into(),
+            footer: "Draft".into(),
+            blocks: (0..50)
+                .map(|i| Block::Paragraph(format!("Paragraph {i} about how the term went.")))
+                .collect(),
+        };
+        let pdf = document.to_pdf();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let startxref = find(&pdf, "startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = std::str::from_utf8(&pdf[startxref..])
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[xref..].starts_with(b"xref\n"));
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        let entries: Vec<&str> = table
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .collect();
+        // two pages of paragraphs, so five fixed objects and two for each page
+        assert_eq!(entries.len(), 9);
+        for (i, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+        assert!(find(&pdf, "(Draft    Page 2 of 2)").is_some());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{
+    assessment::result::PupilResult,
+    attendance::summary::AttendanceSummary,
+    comment::model::Comment,
+    pupil::model::Pupil,
+    report::pdf::{Block, Document},
+};
+use chrono::NaiveDate;
+
+/// Placeholders that can go anywhere in a line of a template.
+pub const INLINE_PLACEHOLDERS: [&str; 15] = [
+    "first_names",
+    "last_name",
+    "name",
+    "year",
+    "gender",
+    "date_of_birth",
+    "upn",
+    "start_date",
+    "school",
+    "report",
+    "today",
+    "from",
+    "to",
+    "attendance",
+    "attendance_percentage",
+];
+
+/// Placeholders that expand to several lines, so must be on a line of their own. Comments can also
+/// be narrowed to one category with `{{comments:<category>}}`.
+pub const BLOCK_PLACEHOLDERS: [&str; 2] = ["comments", "assessments"];
+
+/// Everything a report can be filled in from, already narrowed to the template's dates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportData {
+    pub(crate) pupil: Pupil,
+    /// shared comments made in the reporting period
+    pub(crate) comments: Vec<Comment>,
+    /// results for assessments sat in the reporting period
+    pub(crate) results: Vec<PupilResult>,
+    pub(crate) attendance: AttendanceSummary,
+    pub(crate) school: String,
+    pub(crate) today: NaiveDate,
+}
+
+/// The placeholders in a line, without their braces, or an error for one left open.
+fn placeholders(line: &str) -> Result<Vec<&str>, String> {
+    let mut found = vec![];
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            return Err(format!(
+                "\"{}\" is missing its closing }}}}",
+                &rest[start..]
+            ));
+        };
+        found.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+    Ok(found)
+}
+
+fn is_block(placeholder: &str) -> bool {
+    BLOCK_PLACEHOLDERS.contains(&placeholder) || placeholder.starts_with("comments:")
+}
+
+/// Everything wrong with a template body, one message per problem, empty when it's fine.
+pub fn check_body(body: &str) -> Vec<String> {
+    let mut problems = vec![];
+    for (number, line) in body.lines().enumerate() {
+        let found = match placeholders(line) {
+            Ok(found) => found,
+            Err(problem) => {
+                problems.push(format!("line {}: {problem}", number + 1));
+                continue;
+            }
+        };
+        for placeholder in found {
+            if is_block(placeholder) {
+                if !found_alone(line) {
+                    problems.push(format!(
+                        "line {}: {{{{{placeholder}}}}} must be on a line of its own",
+                        number + 1
+                    ));
+                }
+            } else if !INLINE_PLACEHOLDERS.contains(&placeholder) {
+                problems.push(format!(
+                    "line {}: there's no {{{{{placeholder}}}}} placeholder",
+                    number + 1
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Whether the line is nothing but a single placeholder, allowing for spaces inside the braces.
+fn found_alone(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("{{") && line.ends_with("}}") && line.matches("{{").count() == 1
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%d/%m/%Y").to_string()
+}
+
+impl ReportData {
+    fn name(&self) -> String {
+        format!(
+            "{} {}",
+            self.pupil
+                .preferred_first_names
+                .as_ref()
+                .unwrap_or(&self.pupil.first_names),
+            self.pupil
+                .preferred_last_name
+                .as_ref()
+                .unwrap_or(&self.pupil.last_name)
+        )
+    }
+
+    fn attendance_percentage(&self) -> String {
+        match self.attendance.percentage {
+            Some(percentage) => format!("{percentage}%"),
+            None => "no sessions recorded".into(),
+        }
+    }
+
+    fn inline(&self, placeholder: &str, report: &str) -> String {
+        let pupil = &self.pupil;
+        match placeholder {
+            "first_names" => pupil.first_names.clone(),
+            "last_name" => pupil.last_name.clone(),
+            "name" => self.name(),
+            "year" => pupil.year.to_string(),
+            "gender" => pupil.gender.clone(),
+            "date_of_birth" => pupil.date_of_birth.map(date).unwrap_or_default(),
+            "upn" => pupil.upn.clone().unwrap_or_default(),
+            "start_date" => date(pupil.start_date),
+            "school" => self.school.clone(),
+            "report" => report.to_owned(),
+            "today" => date(self.today),
+            "from" => date(self.attendance.from),
+            "to" => date(self.attendance.to),
+            "attendance" => format!(
+                "{} of {} sessions ({})",
+                self.attendance.present_sessions,
+                self.attendance.possible_sessions,
+                self.attendance_percentage()
+            ),
+            "attendance_percentage" => self.attendance_percentage(),
+            _ => String::new(),
+        }
+    }
+
+    fn fill(&self, line: &str, report: &str) -> String {
+        let mut out = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            out.push_str(&self.inline(rest[start + 2..start + end].trim(), report));
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn block(&self, placeholder: &str) -> Vec<Block> {
+        let blocks: Vec<Block> = match placeholder.split_once(':') {
+            Some((_, category)) => self
+                .comments
+                .iter()
+                .filter(|comment| comment.category.eq_ignore_ascii_case(category.trim()))
+                .map(|comment| Block::Paragraph(comment.body.clone()))
+                .collect(),
+            None if placeholder == "comments" => self
+                .comments
+                .iter()
+                .map(|comment| Block::Paragraph(format!("{}: {}", comment.category, comment.body)))
+                .collect(),
+            None => self
+                .results
+                .iter()
+                .map(|result| {
+                    let mut line = format!(
+                        "{} ({}, {}): {} out of {}, {}%",
+                        result.assessment.name,
+                        result.assessment.subject,
+                        date(result.assessment.date),
+                        result.score.raw_score,
+                        result.assessment.max_score,
+                        result.score.percentage
+                    );
+                    if let Some(standardised) = result.score.standardised_score {
+                        line.push_str(&format!(", standardised score {standardised}"));
+                    }
+                    Block::Item(line)
+                })
+                .collect(),
+        };
+        if blocks.is_empty() {
+            vec![Block::Paragraph("None recorded.".into())]
+        } else {
+            blocks
+        }
+    }
+
+    /// Fill in a template for this pupil. Lines starting `# ` are headings and `- ` bullets, block
+    /// placeholders expand in place, and any other lines run together into paragraphs until a
+    /// blank line.
+    pub fn render(&self, report: &str, body: &str, footer: String) -> Document {
+        let mut blocks = vec![Block::Title(self.name())];
+        let heading = [self.school.as_str(), report]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(" - ");
+        if !heading.is_empty() {
+            blocks.push(Block::Paragraph(heading));
+        }
+        let mut paragraph: Vec<String> = vec![];
+        let end_paragraph = |paragraph: &mut Vec<String>, blocks: &mut Vec<Block>| {
+            if !paragraph.is_empty() {
+                blocks.push(Block::Paragraph(paragraph.join(" ")));
+                paragraph.clear();
+            }
+        };
+        for line in body.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                end_paragraph(&mut paragraph, &mut blocks);
+            } else if found_alone(trimmed) && is_block(trimmed[2..trimmed.len() - 2].trim()) {
+                end_paragraph(&mut paragraph, &mut blocks);
+                blocks.extend(self.block(trimmed[2..trimmed.len() - 2].trim()));
+            } else if let Some(heading) = trimmed.strip_prefix("# ") {
+                end_paragraph(&mut paragraph, &mut blocks);
+                blocks.push(Block::Heading(self.fill(heading.trim(), report)));
+            } else if let Some(item) = trimmed.strip_prefix("- ") {
+                end_paragraph(&mut paragraph, &mut blocks);
+                blocks.push(Block::Item(self.fill(item.trim(), report)));
+            } else {
+                paragraph.push(self.fill(trimmed, report));
+            }
+        }
+        end_paragraph(&mut paragraph, &mut blocks);
+        Document {
+            title: format!("{} - {report}", self.name()),
+            footer,
+            blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assessment::model::{Assessment, Score},
+        comment::model::Visibility,
+        pupil::import::blank,
+    };
+    use rstest::*;
+    use uuid::Uuid;
+
+    fn comment(category: &str, body: &str) -> Comment {
+        Comment {
+            id: Uuid::new_v4(),
+            pupil_id: Uuid::new_v4(),
+            author: "t@t.com".into(),
+            category: category.into(),
+            body: body.into(),
+            visibility: Visibility::Shared,
+            created_at: "2023-03-01T09:00:00".parse().unwrap(),
+            edited_at: None,
+        }
+    }
+
+    #[fixture]
+    fn data() -> ReportData {
+        let mut pupil = blank("");
+        pupil.first_names = "Benjamin".into();
+        pupil.preferred_first_names = Some("Ben".into());
+        pupil.last_name = "Jones".into();
+        pupil.year = 6;
+        ReportData {
+            pupil,
+            comments: vec![
+                comment("academic", "Reads widely."),
+                comment("pastoral", "A kind friend."),
+            ],
+            results: vec![PupilResult {
+                assessment: Assessment {
+                    id: Uuid::new_v4(),
+                    name: "Spring reading".into(),
+                    subject: "English".into(),
+                    year: 6,
+                    date: "2023-03-10".parse().unwrap(),
+                    max_score: 40,
+                    standardisation: vec![],
+                },
+                score: Score {
+                    raw_score: 30,
+                    percentage: 75.0,
+                    standardised_score: Some(104),
+                },
+            }],
+            attendance: AttendanceSummary {
+                possible_sessions: 20,
+                present_sessions: 19,
+                percentage: Some(95.0),
+                ..AttendanceSummary::from_meanings([], 10.0)
+                    .with_dates("2023-01-04".parse().unwrap(), "2023-03-31".parse().unwrap())
+            },
+            school: "Ysgol Test".into(),
+            today: "2023-04-01".parse().unwrap(),
+        }
+    }
+
+    #[rstest]
+    #[case("Dear {{name}}, year {{ year }}.", vec![])]
+    #[case("# Reading\n{{comments:academic}}\n{{assessments}}", vec![])]
+    #[case("Hello {{nickname}}", vec!["line 1: there's no {{nickname}} placeholder"])]
+    #[case("Comments: {{comments}}", vec!["line 1: {{comments}} must be on a line of its own"])]
+    #[case("\n{{name", vec!["line 2: \"{{name\" is missing its closing }}"])]
+    fn test_check_body(#[case] body: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(check_body(body), expected);
+    }
+
+    #[rstest]
+    fn test_render(data: ReportData) {
+        let body = "# About {{first_names}}\n\
+            {{name}} is in year {{year}}\nand attended {{attendance}}.\n\n\
+            # Learning\n{{comments:Academic}}\n{{assessments}}\n\
+            # Everything\n{{comments}}\n- Report from {{from}} to {{to}}";
+        let document = data.render("Spring report", body, "Draft".into());
+        assert_eq!(document.title, "Ben Jones - Spring report");
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::Title("Ben Jones".into()),
+                Block::Paragraph("Ysgol Test - Spring report".into()),
+                Block::Heading("About Benjamin".into()),
+                Block::Paragraph(
+                    "Ben Jones is in year 6 and attended 19 of 20 sessions (95%).".into()
+                ),
+                Block::Heading("Learning".into()),
+                Block::Paragraph("Reads widely.".into()),
+                Block::Item(
+                    "Spring reading (English, 10/03/2023): 30 out of 40, 75%, standardised score 104"
+                        .into()
+                ),
+                Block::Heading("Everything".into()),
+                Block::Paragraph("academic: Reads widely.".into()),
+                Block::Paragraph("pastoral: A kind friend.".into()),
+                Block::Item("Report from 04/01/2023 to 31/03/2023".into()),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_render_with_nothing_recorded(mut data: ReportData) {
+        data.comments.clear();
+        data.results.clear();
+        data.attendance = AttendanceSummary::from_meanings([], 10.0);
+        let document = data.render("", "{{comments}}\n{{attendance_percentage}}", "".into());
+        assert_eq!(
+            document.blocks[2..],
+            [
+                Block::Paragraph("None recorded.".into()),
+                Block::Paragraph("no sessions recorded".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod audit;
+pub mod export;
+pub mod handlers;
+pub mod redact;
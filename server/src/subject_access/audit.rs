@@ -0,0 +1,200 @@
+use super::export::{SubjectAccessExport, ADMIN_ONLY};
+use crate::{
+    core::{constant, error::Result},
+    user::model::User,
+    utils::lock::{lock_for_transaction, SUBJECT_ACCESS_LOG},
+};
+use chrono::NaiveDateTime;
+use entity::subject_access_log::{ActiveModel, Column, Entity, Model};
+use sea_orm::{
+    ActiveModelTrait, DatabaseConnection, EntityTrait, NotSet, QueryOrder, Set, TransactionTrait,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// One subject access export. As with the concern access log, every entry carries the hash of the
+/// one before it, so removing or editing a row breaks the chain from that point on.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ExportEntry {
+    pub(crate) id: i32,
+    pub(crate) pupil_id: Uuid,
+    pub(crate) pupil_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) reference: Option<String>,
+    pub(crate) exported_by: String,
+    pub(crate) exported_at: NaiveDateTime,
+    /// sha256 of the ZIP that was sent, hex encoded, to show later exactly what was disclosed
+    pub(crate) checksum: String,
+    pub(crate) redactions: i32,
+    pub(crate) previous_hash: String,
+    pub(crate) hash: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct ExportLog {
+    pub(crate) intact: bool,
+    pub(crate) entries: Vec<ExportEntry>,
+}
+
+impl ExportEntry {
+    /// Append the export to the end of the chain.
+    pub async fn record(
+        user: &User,
+        export: &SubjectAccessExport,
+        zip: &[u8],
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        let txn = db.begin().await?;
+        // held until the commit, so a concurrent export can't chain from the same entry
+        lock_for_transaction(&txn, SUBJECT_ACCESS_LOG).await?;
+        let previous_hash = Entity::find()
+            .order_by_desc(Column::Id)
+            .one(&txn)
+            .await?
+            .map(|last| last.hash)
+            .unwrap_or_default();
+        let mut entry = Self {
+            id: 0,
+            pupil_id: export.pupil.id,
+            pupil_name: format!("{} {}", export.pupil.first_names, export.pupil.last_name),
+            reference: export.reference.clone(),
+            exported_by: user.email_address.clone(),
+            exported_at: export.exported_at,
+            checksum: hex::encode(Sha256::digest(zip)),
+            redactions: export.redactions() as i32,
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = chain_hash(&entry);
+        let inserted = ActiveModel {
+            id: NotSet,
+            pupil_id: Set(entry.pupil_id),
+            pupil_name: Set(entry.pupil_name.clone()),
+            reference: Set(entry.reference.clone()),
+            exported_by: Set(entry.exported_by.clone()),
+            exported_at: Set(entry.exported_at),
+            checksum: Set(entry.checksum.clone()),
+            redactions: Set(entry.redactions),
+            previous_hash: Set(entry.previous_hash.clone()),
+            hash: Set(entry.hash.clone()),
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+        Ok(inserted.into())
+    }
+
+    /// Every export, oldest first, along with whether the chain still verifies.
+    pub async fn log(user: &User, db: &DatabaseConnection) -> Result<ExportLog> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        let entries: Vec<ExportEntry> = Entity::find()
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(ExportLog {
+            intact: verify_chain(&entries),
+            entries,
+        })
+    }
+}
+
+/// The hash of an entry's contents and the hash before it.
+pub fn chain_hash(entry: &ExportEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}",
+            entry.previous_hash,
+            entry.pupil_id,
+            entry.pupil_name,
+            entry.reference.as_deref().unwrap_or_default(),
+            entry.exported_by,
+            entry.exported_at.format("%Y-%m-%dT%H:%M:%S%.3f"),
+            entry.checksum,
+            entry.redactions
+        )
+        .as_bytes(),
+    );
+    hex::encode(hasher.finalize())
+}
+
+/// Check every entry links to the one before it and that its hash matches its contents.
+pub fn verify_chain(entries: &[ExportEntry]) -> bool {
+    let mut previous_hash = "";
+    for entry in entries {
+        if entry.previous_hash != previous_hash || entry.hash != chain_hash(entry) {
+            return false;
+        }
+        previous_hash = &entry.hash;
+    }
+    true
+}
+
+impl From<Model> for ExportEntry {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            pupil_id: value.pupil_id,
+            pupil_name: value.pupil_name,
+            reference: value.reference,
+            exported_by: value.exported_by,
+            exported_at: value.exported_at,
+            checksum: value.checksum,
+            redactions: value.redactions,
+            previous_hash: value.previous_hash,
+            hash: value.hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[fixture]
+    fn chain() -> Vec<ExportEntry> {
+        let mut previous_hash = String::new();
+        let mut entries = vec![];
+        for id in 1..=3 {
+            let mut entry = ExportEntry {
+                id,
+                pupil_id: "5d1ad2b1-3c9e-4d59-9a0b-4f0e0c9d2a01".parse().unwrap(),
+                pupil_name: "Alex Hughes".into(),
+                reference: Some(format!("SAR-{id}")),
+                exported_by: "admin@test.com".into(),
+                exported_at: format!("2023-06-0{id}T09:00:00.123").parse().unwrap(),
+                checksum: "ab".repeat(32),
+                redactions: id,
+                previous_hash,
+                hash: String::new(),
+            };
+            entry.hash = chain_hash(&entry);
+            previous_hash = entry.hash.clone();
+            entries.push(entry);
+        }
+        entries
+    }
+
+    #[rstest]
+    fn intact_chain_verifies(chain: Vec<ExportEntry>) {
+        assert!(verify_chain(&chain));
+        assert!(verify_chain(&[]));
+    }
+
+    #[rstest]
+    fn edited_reference_breaks_the_chain(mut chain: Vec<ExportEntry>) {
+        chain[1].reference = None;
+        assert!(!verify_chain(&chain));
+    }
+
+    #[rstest]
+    fn removed_entry_breaks_the_chain(mut chain: Vec<ExportEntry>) {
+        chain.remove(0);
+        assert!(!verify_chain(&chain));
+    }
+}
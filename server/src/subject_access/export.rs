@@ -0,0 +1,1138 @@
+use super::redact::{Redaction, Section};
+use crate::{
+    aln::{
+        idp::Idp,
+        register::{Provision, RegisterEntry},
+    },
+    app::config::Config,
+    assessment::{model::Assessment, result::PupilResult},
+    attachment::{model::Attachment, scan::ScanStatus, store::BlobStore},
+    attendance::{code::AttendanceCode, summary::AttendanceSummary},
+    comment::model::Comment,
+    contact::model::PupilContact,
+    core::{constant, error::Result},
+    curriculum::progression::Judgement,
+    eal::model::EalAssessment,
+    flag::model::{Flag, FlagPeriod},
+    intervention::{
+        model::{Intervention, PupilIntervention},
+        target::Target,
+    },
+    medical::model::MedicalItem,
+    photo::model::{PupilPhoto, Size},
+    pupil::model::Pupil,
+    report::{
+        model::SignOff,
+        pdf::{Block, Document},
+    },
+    user::model::User,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Write},
+};
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+pub(crate) const ADMIN_ONLY: &str = "only an admin can answer a subject access request";
+
+/// What the admin asks for when answering a subject access request.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SubjectAccessRequest {
+    /// the school's reference for the request, kept in the log
+    #[serde(default)]
+    pub(crate) reference: Option<String>,
+    /// comments, contacts and attachments to leave out in full, usually because they're mostly
+    /// about someone else
+    #[serde(default)]
+    pub(crate) withhold: Vec<Uuid>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AttendanceMark {
+    pub(crate) date: NaiveDate,
+    pub(crate) session: String,
+    pub(crate) code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) meaning: Option<String>,
+}
+
+/// Something taken out of the export, so the summary can say so.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Redacted {
+    pub(crate) section: Section,
+    pub(crate) what: String,
+    pub(crate) count: usize,
+}
+
+/// A progression judgement with the statement of what matters it was made against.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProgressionJudgement {
+    #[serde(flatten)]
+    pub(crate) judgement: Judgement,
+    pub(crate) what_matters: String,
+}
+
+/// A report sign-off with the name of the report that was signed off.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReportSignOff {
+    #[serde(flatten)]
+    pub(crate) sign_off: SignOff,
+    pub(crate) report: String,
+}
+
+/// Everything held about one pupil apart from safeguarding concerns, which the DSL reviews on
+/// their own as they're the most likely to be about other people. Intervention session notes are
+/// left out too, as they're written about the whole group rather than any one pupil.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SubjectAccessExport {
+    pub(crate) pupil: Pupil,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) reference: Option<String>,
+    pub(crate) exported_by: String,
+    pub(crate) exported_at: NaiveDateTime,
+    pub(crate) flags: Vec<FlagPeriod>,
+    pub(crate) comments: Vec<Comment>,
+    pub(crate) contacts: Vec<PupilContact>,
+    pub(crate) assessments: Vec<PupilResult>,
+    /// keyed by the year each academic year starts in
+    pub(crate) attendance: BTreeMap<i32, AttendanceSummary>,
+    pub(crate) attendance_marks: Vec<AttendanceMark>,
+    pub(crate) eal_assessments: Vec<EalAssessment>,
+    pub(crate) medical: Vec<MedicalItem>,
+    /// the pupil's entry on the ALN register, open or closed
+    pub(crate) aln_register: Option<RegisterEntry>,
+    /// every version of the pupil's IDP, the first first
+    pub(crate) idps: Vec<Idp>,
+    pub(crate) interventions: Vec<PupilIntervention>,
+    pub(crate) targets: Vec<Target>,
+    pub(crate) progression: Vec<ProgressionJudgement>,
+    /// who uploaded the photo and the consent decision, with the photo itself in the ZIP
+    pub(crate) photo: Option<PupilPhoto>,
+    pub(crate) report_sign_offs: Vec<ReportSignOff>,
+    pub(crate) attachments: Vec<Attachment>,
+    pub(crate) redacted: Vec<Redacted>,
+}
+
+impl SubjectAccessExport {
+    /// Gather the pupil's data, leave out anything withheld and run the redactions over what's
+    /// left. Private comments are included, as they're still held about the pupil.
+    pub async fn from_db(
+        user: &User,
+        pupil_id: Uuid,
+        request: &SubjectAccessRequest,
+        config: &Config,
+        now: NaiveDateTime,
+        redactions: &[Box<dyn Redaction>],
+        db: &DatabaseConnection,
+    ) -> Result<Self> {
+        user.check_role(constant::ROLE_ADMIN, ADMIN_ONLY)?;
+        let pupil = Pupil::one_from_db(user, pupil_id, db).await?;
+        let flags: Vec<FlagPeriod> = entity::flag_period::Entity::find()
+            .filter(entity::flag_period::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::flag_period::Column::Flag)
+            .order_by_asc(entity::flag_period::Column::StartDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|period| period.try_into().ok())
+            .collect();
+        let comments: Vec<Comment> = entity::comment::Entity::find()
+            .filter(entity::comment::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::comment::Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let contacts = PupilContact::all_for_pupil(user, pupil_id, db).await?;
+        let scores: HashMap<Uuid, i32> = entity::assessment_result::Entity::find()
+            .filter(entity::assessment_result::Column::PupilId.eq(pupil_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|result| (result.assessment_id, result.raw_score))
+            .collect();
+        let assessments = entity::assessment::Entity::find()
+            .filter(entity::assessment::Column::Id.is_in(scores.keys().cloned()))
+            .order_by_asc(entity::assessment::Column::Date)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|model| {
+                let assessment = Assessment::from(model);
+                PupilResult {
+                    score: assessment.score(scores[&assessment.id]),
+                    assessment,
+                }
+            })
+            .collect();
+        let meanings: HashMap<String, String> = AttendanceCode::all_from_db(db)
+            .await?
+            .into_iter()
+            .map(|code| (code.code, code.meaning.as_str().to_owned()))
+            .collect();
+        let attendance_marks = entity::attendance_mark::Entity::find()
+            .filter(entity::attendance_mark::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::attendance_mark::Column::Date)
+            .order_by_asc(entity::attendance_mark::Column::Session)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|mark| AttendanceMark {
+                meaning: meanings.get(&mark.code).cloned(),
+                date: mark.date,
+                session: mark.session,
+                code: mark.code,
+            })
+            .collect();
+        let eal_assessments = entity::eal_assessment::Entity::find()
+            .filter(entity::eal_assessment::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::eal_assessment::Column::AssessedOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let medical = entity::medical_item::Entity::find()
+            .filter(entity::medical_item::Column::PupilId.eq(pupil_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let aln_register = entity::aln_register::Entity::find_by_id(pupil_id)
+            .one(db)
+            .await?
+            .map(Into::into);
+        let idps = entity::idp::Entity::find()
+            .filter(entity::idp::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::idp::Column::Version)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let mut members: HashMap<Uuid, entity::intervention_member::Model> =
+            entity::intervention_member::Entity::find()
+                .filter(entity::intervention_member::Column::PupilId.eq(pupil_id))
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|member| (member.intervention_id, member))
+                .collect();
+        let interventions = entity::intervention::Entity::find()
+            .filter(entity::intervention::Column::Id.is_in(members.keys().copied()))
+            .order_by_asc(entity::intervention::Column::StartDate)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|intervention| {
+                let member = members.remove(&intervention.id)?;
+                let intervention = Intervention::from(intervention);
+                Some(PupilIntervention {
+                    active: intervention.is_active(now.date()),
+                    intervention,
+                    entry_measure: member.entry_measure,
+                    exit_measure: member.exit_measure,
+                })
+            })
+            .collect();
+        let targets = entity::target::Entity::find()
+            .filter(entity::target::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::target::Column::SetOn)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let statements: HashMap<Uuid, String> = entity::what_matters::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|what_matters| (what_matters.id, what_matters.statement))
+            .collect();
+        let progression = entity::progression_judgement::Entity::find()
+            .filter(entity::progression_judgement::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::progression_judgement::Column::Date)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|judgement| ProgressionJudgement {
+                what_matters: statements
+                    .get(&judgement.what_matters_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                judgement: judgement.into(),
+            })
+            .collect();
+        let photo = entity::pupil_photo::Entity::find_by_id(pupil_id)
+            .one(db)
+            .await?
+            .map(Into::into);
+        let sign_offs = entity::report_sign_off::Entity::find()
+            .filter(entity::report_sign_off::Column::PupilId.eq(pupil_id))
+            .order_by_asc(entity::report_sign_off::Column::SignedOffAt)
+            .all(db)
+            .await?;
+        let reports: HashMap<Uuid, String> = entity::report_template::Entity::find()
+            .filter(
+                entity::report_template::Column::Id
+                    .is_in(sign_offs.iter().map(|sign_off| sign_off.template_id)),
+            )
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|template| (template.id, template.name))
+            .collect();
+        let report_sign_offs = sign_offs
+            .into_iter()
+            .map(|sign_off| ReportSignOff {
+                report: reports
+                    .get(&sign_off.template_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                sign_off: sign_off.into(),
+            })
+            .collect();
+        let attachments = entity::attachment::Entity::find()
+            .filter(entity::attachment::Column::PupilId.eq(pupil_id))
+            .filter(entity::attachment::Column::ConcernId.is_null())
+            .order_by_asc(entity::attachment::Column::UploadedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let mut export = Self {
+            pupil,
+            reference: request
+                .reference
+                .as_ref()
+                .map(|reference| reference.trim().to_owned())
+                .filter(|reference| !reference.is_empty()),
+            exported_by: user.email_address.clone(),
+            exported_at: now,
+            flags,
+            comments,
+            contacts,
+            assessments,
+            attendance: AttendanceSummary::by_academic_year(pupil_id, config, db).await?,
+            attendance_marks,
+            eal_assessments,
+            medical,
+            aln_register,
+            idps,
+            interventions,
+            targets,
+            progression,
+            photo,
+            report_sign_offs,
+            attachments,
+            redacted: vec![],
+        };
+        export.withhold(&request.withhold);
+        export.redact(redactions);
+        Ok(export)
+    }
+
+    /// Leave out the comments, contacts and attachments asked for. A withheld comment's
+    /// attachments go with it.
+    pub fn withhold(&mut self, ids: &[Uuid]) {
+        let comments = self.comments.len();
+        self.comments.retain(|comment| !ids.contains(&comment.id));
+        let contacts = self.contacts.len();
+        self.contacts
+            .retain(|contact| !ids.contains(&contact.contact.id));
+        let attachments = self.attachments.len();
+        self.attachments.retain(|attachment| {
+            !ids.contains(&attachment.id)
+                && attachment
+                    .comment_id
+                    .is_none_or(|comment_id| !ids.contains(&comment_id))
+        });
+        for (section, before, after) in [
+            (Section::Comments, comments, self.comments.len()),
+            (Section::Contacts, contacts, self.contacts.len()),
+            (Section::Attachments, attachments, self.attachments.len()),
+        ] {
+            if before > after {
+                self.redacted.push(Redacted {
+                    section,
+                    what: "withheld in full".into(),
+                    count: before - after,
+                });
+            }
+        }
+    }
+
+    /// Pass every piece of free text through each redaction, noting what each took out where.
+    pub fn redact(&mut self, redactions: &[Box<dyn Redaction>]) {
+        let mut texts: Vec<(Section, &mut String)> = vec![];
+        texts.extend(
+            self.flags
+                .iter_mut()
+                .map(|p| (Section::Flags, &mut p.notes)),
+        );
+        texts.extend(
+            self.comments
+                .iter_mut()
+                .map(|c| (Section::Comments, &mut c.body)),
+        );
+        texts.extend(
+            self.contacts
+                .iter_mut()
+                .filter_map(|c| c.restriction_notes.as_mut())
+                .map(|notes| (Section::Contacts, notes)),
+        );
+        texts.extend(
+            self.eal_assessments
+                .iter_mut()
+                .map(|a| (Section::EalAssessments, &mut a.notes)),
+        );
+        texts.extend(
+            self.medical
+                .iter_mut()
+                .map(|m| (Section::Medical, &mut m.details)),
+        );
+        texts.extend(
+            self.idps
+                .iter_mut()
+                .flat_map(|i| [&mut i.outcomes, &mut i.provision])
+                .map(|text| (Section::Aln, text)),
+        );
+        texts.extend(
+            self.targets
+                .iter_mut()
+                .flat_map(|t| {
+                    [
+                        Some(&mut t.description),
+                        Some(&mut t.success_criteria),
+                        t.review_notes.as_mut(),
+                    ]
+                })
+                .flatten()
+                .map(|text| (Section::Targets, text)),
+        );
+        texts.extend(
+            self.progression
+                .iter_mut()
+                .filter_map(|p| p.judgement.notes.as_mut())
+                .map(|notes| (Section::Progression, notes)),
+        );
+        texts.extend(
+            self.attachments
+                .iter_mut()
+                .map(|a| (Section::Attachments, &mut a.description)),
+        );
+        let mut counts: BTreeMap<(Section, &'static str), usize> = BTreeMap::new();
+        for (section, text) in texts {
+            for redaction in redactions {
+                let count = redaction.redact(section, text);
+                if count > 0 {
+                    *counts.entry((section, redaction.describe())).or_default() += count;
+                }
+            }
+        }
+        self.redacted
+            .extend(counts.into_iter().map(|((section, what), count)| Redacted {
+                section,
+                what: what.into(),
+                count,
+            }));
+    }
+
+    /// How many things were withheld or redacted altogether.
+    pub fn redactions(&self) -> usize {
+        self.redacted.iter().map(|redacted| redacted.count).sum()
+    }
+
+    /// The same information as the JSON, laid out to be read.
+    pub fn summary(&self) -> Document {
+        let date = |date: NaiveDate| date.format("%d/%m/%Y").to_string();
+        let pupil = &self.pupil;
+        let name = format!("{} {}", pupil.first_names, pupil.last_name);
+        let none = || Block::Item("None held".into());
+        let mut blocks = vec![
+            Block::Title(format!("Information held about {name}")),
+            Block::Paragraph(format!(
+                "Prepared on {}{}. The same information is in data.json, in a form other software can read, and any files are in the attachments folder.",
+                date(self.exported_at.date()),
+                self.reference
+                    .as_ref()
+                    .map(|reference| format!(" in answer to subject access request {reference}"))
+                    .unwrap_or_default()
+            )),
+            Block::Heading("Pupil record".into()),
+        ];
+        let mut record = vec![format!("Name: {name}")];
+        if pupil.preferred_first_names.is_some() || pupil.preferred_last_name.is_some() {
+            record.push(format!(
+                "Preferred name: {} {}",
+                pupil
+                    .preferred_first_names
+                    .as_ref()
+                    .unwrap_or(&pupil.first_names),
+                pupil
+                    .preferred_last_name
+                    .as_ref()
+                    .unwrap_or(&pupil.last_name)
+            ));
+        }
+        if let Some(date_of_birth) = pupil.date_of_birth {
+            record.push(format!("Date of birth: {}", date(date_of_birth)));
+        }
+        if let Some(upn) = &pupil.upn {
+            record.push(format!("Unique pupil number: {upn}"));
+        }
+        record.push(format!("Gender: {}", pupil.gender));
+        record.push(format!("Year group: {}", pupil.year));
+        record.push(format!("Started: {}", date(pupil.start_date)));
+        if let Some(end_date) = pupil.end_date {
+            record.push(format!("Left: {}", date(end_date)));
+        }
+        if let Some(home_language) = &pupil.home_language {
+            record.push(format!("Home language: {home_language}"));
+        }
+        blocks.extend(record.into_iter().map(Block::Item));
+
+        blocks.push(Block::Heading("Flags".into()));
+        if self.flags.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.flags.iter().map(|period| {
+            Block::Item(format!(
+                "{} from {} {}{}",
+                flag_name(period.flag),
+                date(period.start_date),
+                period
+                    .end_date
+                    .map(|end_date| format!("to {}", date(end_date)))
+                    .unwrap_or("onwards".into()),
+                with_notes(&period.notes)
+            ))
+        }));
+
+        blocks.push(Block::Heading("Comments".into()));
+        if self.comments.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.comments.iter().map(|comment| {
+            Block::Item(format!(
+                "{}, {} comment by {}: {}",
+                date(comment.created_at.date()),
+                comment.category,
+                comment.author,
+                comment.body
+            ))
+        }));
+
+        blocks.push(Block::Heading("Contacts".into()));
+        if self.contacts.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.contacts.iter().map(|contact| {
+            let mut parts = vec![
+                format!(
+                    "{} {}",
+                    contact.contact.first_names, contact.contact.last_name
+                ),
+                contact.relationship.replace('_', " "),
+            ];
+            if contact.parental_responsibility {
+                parts.push("has parental responsibility".into());
+            }
+            if contact.no_contact {
+                parts.push("not to be contacted".into());
+            }
+            parts.extend(contact.contact.phone.clone());
+            parts.extend(contact.contact.email.clone());
+            Block::Item(format!(
+                "{}{}",
+                parts.join(", "),
+                with_notes(contact.restriction_notes.as_deref().unwrap_or_default())
+            ))
+        }));
+
+        blocks.push(Block::Heading("Assessments".into()));
+        if self.assessments.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.assessments.iter().map(|result| {
+            Block::Item(format!(
+                "{}, {} ({}): {} out of {}, {}%{}",
+                date(result.assessment.date),
+                result.assessment.name,
+                result.assessment.subject,
+                result.score.raw_score,
+                result.assessment.max_score,
+                result.score.percentage,
+                result
+                    .score
+                    .standardised_score
+                    .map(|score| format!(", standardised score {score}"))
+                    .unwrap_or_default()
+            ))
+        }));
+
+        blocks.push(Block::Heading("Attendance".into()));
+        if self.attendance.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.attendance.iter().map(|(year, summary)| {
+            Block::Item(format!(
+                "{year}/{:02}: {} possible sessions, {} present, {} authorised and {} unauthorised absences{}",
+                (year + 1) % 100,
+                summary.possible_sessions,
+                summary.present_sessions,
+                summary.authorised_absences,
+                summary.unauthorised_absences,
+                summary
+                    .percentage
+                    .map(|percentage| format!(", {percentage}% attendance"))
+                    .unwrap_or_default()
+            ))
+        }));
+        if !self.attendance_marks.is_empty() {
+            blocks.push(Block::Paragraph(
+                "Every mark from the register is in data.json.".into(),
+            ));
+        }
+
+        blocks.push(Block::Heading("English as an additional language".into()));
+        if self.eal_assessments.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.eal_assessments.iter().map(|assessment| {
+            Block::Item(format!(
+                "{}, stage {} assessed by {}{}",
+                date(assessment.assessed_on),
+                assessment.stage.as_str(),
+                assessment.assessed_by,
+                with_notes(&assessment.notes)
+            ))
+        }));
+
+        blocks.push(Block::Heading("Medical".into()));
+        if self.medical.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.medical.iter().map(|item| {
+            Block::Item(format!(
+                "{} ({}{}){}",
+                item.name,
+                item.kind.as_str().replace('_', " "),
+                item.severity
+                    .map(|severity| format!(", {}", severity.as_str()))
+                    .unwrap_or_default(),
+                with_notes(&item.details)
+            ))
+        }));
+
+        blocks.push(Block::Heading("Additional learning needs".into()));
+        if self.aln_register.is_none() && self.idps.is_empty() {
+            blocks.push(none());
+        }
+        if let Some(entry) = &self.aln_register {
+            blocks.push(Block::Item(format!(
+                "On the ALN register from {}{}, with a {} IDP for {}, to be reviewed by {}{}",
+                date(entry.added_on),
+                entry
+                    .closed_on
+                    .map(|closed_on| format!(" to {}", date(closed_on)))
+                    .unwrap_or_default(),
+                match entry.provision {
+                    Provision::School => "school maintained",
+                    Provision::LocalAuthority => "local authority maintained",
+                },
+                entry.areas_of_need.join(", "),
+                date(entry.review_date),
+                entry
+                    .last_reviewed
+                    .map(|last_reviewed| format!(", last reviewed {}", date(last_reviewed)))
+                    .unwrap_or_default()
+            )));
+        }
+        blocks.extend(self.idps.iter().map(|idp| {
+            Block::Item(format!(
+                "IDP version {} by {} on {}, outcomes: {}; provision: {}",
+                idp.version,
+                idp.created_by,
+                date(idp.created_at.date()),
+                idp.outcomes,
+                idp.provision
+            ))
+        }));
+
+        blocks.push(Block::Heading("Interventions".into()));
+        if self.interventions.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.interventions.iter().map(|member| {
+            let intervention = &member.intervention;
+            let measure = |name: &str, value: Option<i32>| {
+                value
+                    .map(|value| format!(", {name} measure {value}"))
+                    .unwrap_or_default()
+            };
+            Block::Item(format!(
+                "{} from {} {}, led by {}{}{}",
+                intervention.name,
+                date(intervention.start_date),
+                intervention
+                    .end_date
+                    .map(|end_date| format!("to {}", date(end_date)))
+                    .unwrap_or("onwards".into()),
+                intervention.lead_staff,
+                measure("entry", member.entry_measure),
+                measure("exit", member.exit_measure)
+            ))
+        }));
+
+        blocks.push(Block::Heading("Targets".into()));
+        if self.targets.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.targets.iter().map(|target| {
+            Block::Item(format!(
+                "{} (success criteria: {}), set on {} by {} to be reviewed by {}{}",
+                target.description,
+                target.success_criteria,
+                date(target.set_on),
+                target.set_by,
+                date(target.review_date),
+                target
+                    .outcome
+                    .map(|outcome| format!(
+                        ", {}{}",
+                        outcome.as_str().replace('_', " "),
+                        with_notes(target.review_notes.as_deref().unwrap_or_default())
+                    ))
+                    .unwrap_or_default()
+            ))
+        }));
+
+        blocks.push(Block::Heading("Progression".into()));
+        if self.progression.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.progression.iter().map(|progression| {
+            let judgement = &progression.judgement;
+            Block::Item(format!(
+                "{}, progression step {} in {} judged by {}{}",
+                date(judgement.date),
+                judgement.step,
+                progression.what_matters,
+                judgement.judged_by,
+                with_notes(judgement.notes.as_deref().unwrap_or_default())
+            ))
+        }));
+
+        blocks.push(Block::Heading("Photo".into()));
+        match &self.photo {
+            None => blocks.push(none()),
+            Some(photo) => {
+                if let (Some(uploaded_at), Some(uploaded_by)) =
+                    (photo.uploaded_at, &photo.uploaded_by)
+                {
+                    blocks.push(Block::Item(format!(
+                        "Uploaded on {} by {uploaded_by}, and included as photo.jpg",
+                        date(uploaded_at.date())
+                    )));
+                }
+                blocks.push(Block::Item(if photo.consent_withdrawn {
+                    format!(
+                        "Consent to show the photo withdrawn{}",
+                        photo
+                            .consent_updated_at
+                            .map(|updated_at| format!(" on {}", date(updated_at.date())))
+                            .unwrap_or_default()
+                    )
+                } else {
+                    "Consent to show the photo hasn't been withdrawn".into()
+                }));
+            }
+        }
+
+        blocks.push(Block::Heading("Reports".into()));
+        if self.report_sign_offs.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.report_sign_offs.iter().map(|signed| {
+            Block::Item(format!(
+                "{} signed off on {} by {}",
+                signed.report,
+                date(signed.sign_off.signed_off_at.date()),
+                signed.sign_off.signed_off_by
+            ))
+        }));
+
+        blocks.push(Block::Heading("Files".into()));
+        if self.attachments.is_empty() {
+            blocks.push(none());
+        }
+        blocks.extend(self.attachments.iter().map(|attachment| {
+            Block::Item(format!(
+                "{} uploaded on {} by {}{}{}",
+                attachment.file_name,
+                date(attachment.uploaded_at.date()),
+                attachment.uploaded_by,
+                with_notes(&attachment.description),
+                if attachment.scan_status == ScanStatus::Infected {
+                    " (not included as it failed a virus scan)"
+                } else {
+                    ""
+                }
+            ))
+        }));
+
+        blocks.push(Block::Heading("Redactions".into()));
+        if self.redacted.is_empty() {
+            blocks.push(Block::Item("Nothing has been redacted".into()));
+        }
+        blocks.extend(self.redacted.iter().map(|redacted| {
+            Block::Item(format!(
+                "{} {} from {}",
+                redacted.count,
+                redacted.what,
+                redacted.section.as_str()
+            ))
+        }));
+        blocks.push(Block::Heading("Not included".into()));
+        blocks.push(Block::Paragraph(
+            "Safeguarding records are kept apart from the rest of the pupil's record and aren't included here. They are reviewed separately before deciding what can be disclosed.".into(),
+        ));
+        blocks.push(Block::Paragraph(
+            "Notes from intervention sessions are written about the whole group rather than any one pupil, so they aren't included either.".into(),
+        ));
+
+        Document {
+            title: format!("Subject access request: {name}"),
+            footer: format!("{name}, prepared {}", date(self.exported_at.date())),
+            blocks,
+        }
+    }
+
+    /// The ZIP to send: the data as JSON, the summary as a PDF, and the files themselves.
+    pub async fn to_zip(&self, store: &dyn BlobStore) -> Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let zip_error = |error: zip::result::ZipError| {
+            ServerError!(format!("failed to write subject access zip: {error}"))
+        };
+        let io_error = |error: std::io::Error| {
+            ServerError!(format!("failed to write subject access zip: {error}"))
+        };
+        zip.start_file("data.json", options).map_err(zip_error)?;
+        zip.write_all(&serde_json::to_vec_pretty(self)?)
+            .map_err(io_error)?;
+        zip.start_file("summary.pdf", options).map_err(zip_error)?;
+        zip.write_all(&self.summary().to_pdf()).map_err(io_error)?;
+        // held whatever the consent decision, so it's sent even if it's no longer shown
+        if let Some(photo) = self
+            .photo
+            .as_ref()
+            .filter(|photo| photo.uploaded_at.is_some())
+        {
+            let data = store.get(&photo.storage_key(Size::Medium)).await?;
+            zip.start_file("photo.jpg", options).map_err(zip_error)?;
+            zip.write_all(&data).map_err(io_error)?;
+        }
+        for attachment in &self.attachments {
+            if attachment.scan_status == ScanStatus::Infected {
+                continue;
+            }
+            let data = attachment.data(store).await?;
+            zip.start_file(
+                format!(
+                    "attachments/{}_{}",
+                    attachment.id,
+                    safe(&attachment.file_name)
+                ),
+                options,
+            )
+            .map_err(zip_error)?;
+            zip.write_all(&data).map_err(io_error)?;
+        }
+        Ok(zip.finish().map_err(zip_error)?.into_inner())
+    }
+}
+
+fn flag_name(flag: Flag) -> &'static str {
+    match flag {
+        Flag::Fsm => "Free school meals",
+        Flag::Lac => "Looked after child",
+        Flag::Eal => "English as an additional language",
+        Flag::Mat => "More able and talented",
+        Flag::Aln => "Additional learning needs",
+    }
+}
+
+fn with_notes(notes: &str) -> String {
+    match notes.trim() {
+        "" => String::new(),
+        notes => format!(": {notes}"),
+    }
+}
+
+/// A file name that can't reach outside its folder in the ZIP.
+fn safe(file_name: &str) -> String {
+    file_name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comment::model::Visibility, contact::model::Contact, subject_access::redact::OtherPupils,
+    };
+    use rstest::*;
+
+    #[fixture]
+    fn export() -> SubjectAccessExport {
+        let mut pupil = crate::pupil::import::blank("H801200001001");
+        pupil.first_names = "Alex".into();
+        pupil.last_name = "Hughes".into();
+        let comment = |body: &str| Comment {
+            id: Uuid::new_v4(),
+            pupil_id: pupil.id,
+            author: "teacher@test.com".into(),
+            category: "general".into(),
+            body: body.into(),
+            visibility: Visibility::Shared,
+            created_at: "2023-05-02T10:00:00".parse().unwrap(),
+            edited_at: None,
+        };
+        let comments = vec![
+            comment("Fell out with Ben Jones at lunch"),
+            comment("Ben Jones's parents called about the fall out"),
+        ];
+        let attachment = |comment_id: Option<Uuid>| Attachment {
+            id: Uuid::new_v4(),
+            pupil_id: pupil.id,
+            comment_id,
+            concern_id: None,
+            file_name: "letter.pdf".into(),
+            content_type: "application/pdf".into(),
+            size: 10,
+            checksum: String::new(),
+            scan_status: ScanStatus::Clean,
+            description: "letter about Ben Jones".into(),
+            uploaded_by: "teacher@test.com".into(),
+            uploaded_at: "2023-05-02T10:00:00".parse().unwrap(),
+            storage_key: String::new(),
+        };
+        let attachments = vec![attachment(None), attachment(Some(comments[1].id))];
+        SubjectAccessExport {
+            reference: Some("SAR-7".into()),
+            exported_by: "admin@test.com".into(),
+            exported_at: "2023-06-01T09:00:00".parse().unwrap(),
+            flags: vec![],
+            comments,
+            contacts: vec![PupilContact {
+                contact: Contact {
+                    id: Uuid::new_v4(),
+                    first_names: "Sam".into(),
+                    last_name: "Hughes".into(),
+                    phone: Some("01234 567890".into()),
+                    email: None,
+                    preferred_language: None,
+                },
+                pupil_id: pupil.id,
+                relationship: "mother".into(),
+                priority: 1,
+                parental_responsibility: true,
+                no_contact: false,
+                restriction_notes: None,
+            }],
+            assessments: vec![],
+            attendance: BTreeMap::new(),
+            attendance_marks: vec![],
+            eal_assessments: vec![],
+            medical: vec![],
+            aln_register: None,
+            idps: vec![],
+            interventions: vec![],
+            targets: vec![],
+            progression: vec![],
+            photo: None,
+            report_sign_offs: vec![],
+            attachments,
+            redacted: vec![],
+            pupil,
+        }
+    }
+
+    fn redacted(export: &SubjectAccessExport) -> Vec<(Section, &str, usize)> {
+        export
+            .redacted
+            .iter()
+            .map(|redacted| (redacted.section, redacted.what.as_str(), redacted.count))
+            .collect()
+    }
+
+    #[rstest]
+    fn withheld_comments_take_their_attachments(mut export: SubjectAccessExport) {
+        let comment_id = export.comments[1].id;
+        export.withhold(&[comment_id]);
+        assert_eq!(export.comments.len(), 1);
+        assert_eq!(export.attachments.len(), 1);
+        assert_eq!(export.attachments[0].comment_id, None);
+        assert_eq!(
+            redacted(&export),
+            [
+                (Section::Comments, "withheld in full", 1),
+                (Section::Attachments, "withheld in full", 1),
+            ]
+        );
+    }
+
+    #[rstest]
+    fn redactions_are_counted_by_section(mut export: SubjectAccessExport) {
+        let mut other = crate::pupil::import::blank("X801200001002");
+        other.first_names = "Ben".into();
+        other.last_name = "Jones".into();
+        let redactions: Vec<Box<dyn Redaction>> = vec![Box::new(OtherPupils::new(
+            &[export.pupil.clone(), other],
+            export.pupil.id,
+        ))];
+        export.redact(&redactions);
+        assert_eq!(
+            export.comments[0].body,
+            "Fell out with [another pupil] at lunch"
+        );
+        assert_eq!(
+            export.comments[1].body,
+            "[another pupil]'s parents called about the fall out"
+        );
+        assert_eq!(
+            redacted(&export),
+            [
+                (Section::Comments, "names of other pupils", 2),
+                (Section::Attachments, "names of other pupils", 2),
+            ]
+        );
+        assert_eq!(export.redactions(), 4);
+    }
+
+    #[rstest]
+    fn summary_covers_every_section(mut export: SubjectAccessExport) {
+        let contact_id = export.contacts[0].contact.id;
+        export.withhold(&[contact_id]);
+        let summary = export.summary();
+        assert_eq!(summary.title, "Subject access request: Alex Hughes");
+        let texts: Vec<&str> = summary
+            .blocks
+            .iter()
+            .map(|block| match block {
+                Block::Title(text)
+                | Block::Heading(text)
+                | Block::Paragraph(text)
+                | Block::Item(text) => text.as_str(),
+            })
+            .collect();
+        assert!(texts[1].contains("in answer to subject access request SAR-7"));
+        assert!(texts.contains(&"Unique pupil number: H801200001001"));
+        assert!(texts.contains(
+            &"02/05/2023, general comment by teacher@test.com: Fell out with Ben Jones at lunch"
+        ));
+        assert!(texts.contains(&"1 withheld in full from contacts"));
+        let contacts = texts.iter().position(|text| *text == "Contacts").unwrap();
+        assert_eq!(texts[contacts + 1], "None held");
+        assert!(!texts.iter().any(|text| text.contains("01234 567890")));
+    }
+
+    #[rstest]
+    fn summary_covers_support_progression_photo_and_reports(mut export: SubjectAccessExport) {
+        let pupil_id = export.pupil.id;
+        let day = |date: &str| date.parse::<NaiveDate>().unwrap();
+        let at = |time: &str| time.parse::<NaiveDateTime>().unwrap();
+        export.aln_register = Some(RegisterEntry {
+            pupil_id,
+            provision: Provision::School,
+            areas_of_need: vec!["literacy".into()],
+            alnco: None,
+            added_on: day("2022-09-05"),
+            review_date: day("2023-09-05"),
+            last_reviewed: None,
+            closed_on: None,
+        });
+        export.idps = vec![Idp {
+            id: Uuid::new_v4(),
+            pupil_id,
+            version: 1,
+            outcomes: "Read with Ben Jones".into(),
+            provision: "Daily phonics".into(),
+            created_by: "alnco@test.com".into(),
+            created_at: at("2022-09-06T09:00:00"),
+        }];
+        export.interventions = vec![PupilIntervention {
+            intervention: Intervention {
+                id: Uuid::new_v4(),
+                name: "Reading club".into(),
+                description: String::new(),
+                lead_staff: "teacher@test.com".into(),
+                start_date: day("2022-10-03"),
+                end_date: Some(day("2022-12-16")),
+                measure: "reading age".into(),
+            },
+            active: false,
+            entry_measure: Some(84),
+            exit_measure: None,
+        }];
+        export.photo = Some(PupilPhoto {
+            pupil_id,
+            uploaded_by: Some("teacher@test.com".into()),
+            uploaded_at: Some(at("2022-09-01T09:00:00")),
+            consent_withdrawn: true,
+            consent_updated_by: Some("admin@test.com".into()),
+            consent_updated_at: Some(at("2023-01-09T09:00:00")),
+            links: None,
+        });
+        export.report_sign_offs = vec![ReportSignOff {
+            sign_off: SignOff {
+                template_id: Uuid::new_v4(),
+                pupil_id,
+                signed_off_by: "teacher@test.com".into(),
+                signed_off_at: at("2023-07-14T15:00:00"),
+            },
+            report: "Summer report".into(),
+        }];
+        let mut other = crate::pupil::import::blank("X801200001002");
+        other.first_names = "Ben".into();
+        other.last_name = "Jones".into();
+        export.redact(&[Box::new(OtherPupils::new(
+            &[export.pupil.clone(), other],
+            pupil_id,
+        ))]);
+        let summary = export.summary();
+        let texts: Vec<&str> = summary
+            .blocks
+            .iter()
+            .map(|block| match block {
+                Block::Title(text)
+                | Block::Heading(text)
+                | Block::Paragraph(text)
+                | Block::Item(text) => text.as_str(),
+            })
+            .collect();
+        assert!(texts.contains(&"On the ALN register from 05/09/2022, with a school maintained IDP for literacy, to be reviewed by 05/09/2023"));
+        assert!(texts.contains(&"IDP version 1 by alnco@test.com on 06/09/2022, outcomes: Read with [another pupil]; provision: Daily phonics"));
+        assert!(texts.contains(&"Reading club from 03/10/2022 to 16/12/2022, led by teacher@test.com, entry measure 84"));
+        assert!(texts
+            .contains(&"Uploaded on 01/09/2022 by teacher@test.com, and included as photo.jpg"));
+        assert!(texts.contains(&"Consent to show the photo withdrawn on 09/01/2023"));
+        assert!(texts.contains(&"Summer report signed off on 14/07/2023 by teacher@test.com"));
+        for heading in ["Targets", "Progression"] {
+            let position = texts.iter().position(|text| *text == heading).unwrap();
+            assert_eq!(texts[position + 1], "None held");
+        }
+        assert!(texts.contains(&"1 names of other pupils from ALN"));
+    }
+}
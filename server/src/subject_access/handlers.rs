@@ -0,0 +1,113 @@
+use crate::{
+    app::state::AppState,
+    core::error::*,
+    subject_access::{
+        audit::ExportEntry,
+        export::{SubjectAccessExport, SubjectAccessRequest},
+        redact::default_redactions,
+    },
+    user::model::User,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension,
+};
+use chrono::{SubsecRound, Utc};
+use serde_json::json;
+use std::str::FromStr;
+use uuid::Uuid;
+
+fn pass_through(error: Error) -> Error {
+    match error.kind {
+        ErrorKind::DatabaseError => DatabaseError!(error.to_string()),
+        ErrorKind::Unauthorised
+        | ErrorKind::PupilDoesNotExist
+        | ErrorKind::StorageError
+        | ErrorKind::ServerError => error,
+        _ => UnknownError!(),
+    }
+}
+
+async fn gather(
+    state: &AppState,
+    user: &User,
+    id: &str,
+    request: &SubjectAccessRequest,
+) -> Result<SubjectAccessExport> {
+    let id = Uuid::from_str(id)?;
+    let db = state.database().as_ref();
+    // millisecond precision so the log's hash still matches after a round trip through the database
+    let now = Utc::now().naive_utc().trunc_subsecs(3);
+    let redactions = default_redactions(id, db).await?;
+    SubjectAccessExport::from_db(user, id, request, state.config(), now, &redactions, db).await
+}
+
+/// What the export will hold, for the admin to choose anything to withhold. Nothing leaves the
+/// school, so it isn't logged.
+pub async fn preview_subject_access(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(request): Json<SubjectAccessRequest>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("previewing a subject access export for pupil {id}");
+    match gather(&state, &user, &id, &request).await {
+        Ok(export) => Ok(Json(json!(export))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
+
+/// Download the export as a ZIP, recording it in the subject access log first.
+pub async fn export_subject_access(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Extension(user): Extension<User>,
+    Json(request): Json<SubjectAccessRequest>,
+) -> Result<impl IntoResponse> {
+    tracing::debug!("exporting subject access data for pupil {id}");
+    let export = gather(&state, &user, &id, &request)
+        .await
+        .map_err(pass_through)?;
+    let data = export
+        .to_zip(state.blob_store().as_ref())
+        .await
+        .map_err(pass_through)?;
+    ExportEntry::record(&user, &export, &data, state.database().as_ref())
+        .await
+        .map_err(pass_through)?;
+    tracing::info!(
+        "{} exported subject access data for pupil {}",
+        user.email_address,
+        export.pupil.id
+    );
+    let file_name = format!(
+        "subject_access_{}_{}.zip",
+        export.pupil.id,
+        export.exported_at.format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        data,
+    ))
+}
+
+/// Every subject access export made, and whether the log is intact.
+pub async fn get_subject_access_log(
+    State(state): State<AppState>,
+    Extension(user): Extension<User>,
+) -> Result<Json<serde_json::Value>> {
+    tracing::debug!("requested the subject access log");
+    match ExportEntry::log(&user, state.database().as_ref()).await {
+        Ok(log) => Ok(Json(json!(log))),
+        Err(error) => Err(pass_through(error)),
+    }
+}
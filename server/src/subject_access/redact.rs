@@ -0,0 +1,191 @@
+use crate::{core::error::Result, pupil::model::Pupil};
+use regex::{Regex, RegexBuilder};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde::Serialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const OTHER_PUPIL: &str = "[another pupil]";
+
+/// Where a piece of free text in an export came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Section {
+    Flags,
+    Comments,
+    Contacts,
+    EalAssessments,
+    Medical,
+    Aln,
+    Targets,
+    Progression,
+    Attachments,
+}
+
+impl Section {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::Flags => "flags",
+            Section::Comments => "comments",
+            Section::Contacts => "contacts",
+            Section::EalAssessments => "EAL assessments",
+            Section::Medical => "medical",
+            Section::Aln => "ALN",
+            Section::Targets => "targets",
+            Section::Progression => "progression",
+            Section::Attachments => "attachments",
+        }
+    }
+}
+
+/// A hook for taking third-party information out of an export before it leaves the school. Every
+/// piece of free text in the export is passed through each redaction in turn.
+pub trait Redaction: Send + Sync {
+    /// what's taken out, for the summary
+    fn describe(&self) -> &'static str;
+    /// Redact the text in place, returning how many times something was taken out.
+    fn redact(&self, section: Section, text: &mut String) -> usize;
+}
+
+/// Other pupils named in free text, by their full name or their preferred one. A name the subject
+/// also goes by is left alone, as it can't be told apart from the subject's own.
+pub struct OtherPupils {
+    pattern: Option<Regex>,
+}
+
+/// The full and preferred names of a pupil, lowercased and with single spaces.
+fn names_of(pupil: &Pupil) -> impl Iterator<Item = String> {
+    let last_name = pupil
+        .preferred_last_name
+        .as_ref()
+        .unwrap_or(&pupil.last_name);
+    [
+        Some(format!("{} {}", pupil.first_names, pupil.last_name)),
+        pupil
+            .preferred_first_names
+            .as_ref()
+            .map(|first_names| format!("{first_names} {last_name}")),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|name| {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    })
+    .filter(|name| !name.is_empty())
+}
+
+impl OtherPupils {
+    pub fn new(pupils: &[Pupil], subject: Uuid) -> Self {
+        let own: HashSet<String> = pupils
+            .iter()
+            .filter(|pupil| pupil.id == subject)
+            .flat_map(names_of)
+            .collect();
+        let mut names: Vec<String> = pupils
+            .iter()
+            .filter(|pupil| pupil.id != subject)
+            .flat_map(names_of)
+            .filter(|name| !own.contains(name))
+            .map(|name| {
+                name.split(' ')
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(r"\s+")
+            })
+            .collect();
+        // the longest first, so a name isn't half redacted as a shorter one inside it
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        names.dedup();
+        let pattern = (!names.is_empty()).then(|| {
+            RegexBuilder::new(&format!(r"\b(?:{})\b", names.join("|")))
+                .case_insensitive(true)
+                .size_limit(1 << 26)
+                .build()
+                .expect("escaped names make a valid pattern")
+        });
+        Self { pattern }
+    }
+
+    /// Every pupil in the school, whatever the user's years, as anyone can be named in a comment.
+    pub async fn from_db(subject: Uuid, db: &DatabaseConnection) -> Result<Self> {
+        let pupils: Vec<Pupil> = entity::pupil::Entity::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        Ok(Self::new(&pupils, subject))
+    }
+}
+
+impl Redaction for OtherPupils {
+    fn describe(&self) -> &'static str {
+        "names of other pupils"
+    }
+
+    fn redact(&self, _: Section, text: &mut String) -> usize {
+        let Some(pattern) = &self.pattern else {
+            return 0;
+        };
+        let count = pattern.find_iter(text).count();
+        if count > 0 {
+            *text = pattern.replace_all(text, OTHER_PUPIL).into_owned();
+        }
+        count
+    }
+}
+
+/// The redactions made to every export.
+pub async fn default_redactions(
+    subject: Uuid,
+    db: &DatabaseConnection,
+) -> Result<Vec<Box<dyn Redaction>>> {
+    Ok(vec![Box::new(OtherPupils::from_db(subject, db).await?)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn pupil(first_names: &str, last_name: &str, preferred: Option<&str>) -> Pupil {
+        let mut pupil = crate::pupil::import::blank("");
+        pupil.id = Uuid::new_v4();
+        pupil.first_names = first_names.into();
+        pupil.last_name = last_name.into();
+        pupil.preferred_first_names = preferred.map(Into::into);
+        pupil
+    }
+
+    #[rstest]
+    #[case("Pushed Ben Jones at lunch", "Pushed [another pupil] at lunch", 1)]
+    #[case(
+        "benjamin  JONES and Sam Smith-Brown",
+        "[another pupil] and [another pupil]",
+        2
+    )]
+    #[case("Sam Smith-Browne joined", "Sam Smith-Browne joined", 0)]
+    #[case("Alex Hughes read well", "Alex Hughes read well", 0)]
+    #[case(
+        "ALEX hughes sat with Alexander Hughes",
+        "ALEX hughes sat with [another pupil]",
+        1
+    )]
+    fn other_pupils_are_redacted(#[case] text: &str, #[case] expected: &str, #[case] count: usize) {
+        let subject = pupil("Alex", "Hughes", None);
+        let pupils = [
+            subject.clone(),
+            pupil("Benjamin", "Jones", Some("Ben")),
+            pupil("Sam", "Smith-Brown", None),
+            // goes by the subject's name, which stays as it is
+            pupil("Alexander", "Hughes", Some("Alex")),
+        ];
+        let redaction = OtherPupils::new(&pupils, subject.id);
+        let mut text = text.to_owned();
+        assert_eq!(redaction.redact(Section::Comments, &mut text), count);
+        assert_eq!(text, expected);
+    }
+}
@@ -1,19 +1,28 @@
 use crate::{
     app::state::AppState,
-    core::error::{ErrorKind, Result},
+    core::{
+        constant,
+        error::{ErrorKind, Result},
+    },
     user::model::*,
     utils,
 };
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Path, State},
     http::StatusCode,
+    Extension,
 };
 use serde::{Deserialize, Serialize};
 
+/// Only an admin can add a user, as the new account's years and roles decide what it can see.
 pub async fn create_user(
     State(state): State<AppState>,
+    Extension(by): Extension<User>,
     Json(req): Json<RequestUser>,
 ) -> Result<StatusCode> {
+    if !by.has_role(constant::ROLE_ADMIN) {
+        return Err(Forbidden!("only an admin can add a user"));
+    }
     req.validate()?;
     let user = User::new(
         &req.first_names,
@@ -22,6 +31,11 @@ pub async fn create_user(
         &req.hashed_password,
         req.years,
     );
+    let mut user = User {
+        roles: req.roles,
+        ..user
+    };
+    user.set_password(&req.hashed_password)?;
     match user.save(state.database().as_ref()).await {
         Ok(_) => Ok(StatusCode::CREATED),
         Err(error) => match error.kind {
@@ -44,6 +58,24 @@ pub async fn get_users(State(state): State<AppState>) -> Result<Json<UsersRespon
     }
 }
 
+pub async fn update_user(
+    State(state): State<AppState>,
+    Path(email): Path<String>,
+    Extension(by): Extension<User>,
+    Json(update): Json<UserUpdate>,
+) -> Result<Json<ResponseUser>> {
+    tracing::debug!("updating user {email}");
+    let mut user = User::one_from_db(&email, state.database()).await?;
+    user.set_from_update(update, &by)?;
+    match user.update(state.database().as_ref()).await {
+        Ok(user) => Ok(Json(user.into())),
+        Err(error) => match error.kind {
+            ErrorKind::DatabaseError => Err(DatabaseError!(error.to_string())),
+            _ => Err(UnknownError!()),
+        },
+    }
+}
+
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct RequestUser {
@@ -52,6 +84,8 @@ pub struct RequestUser {
     email_address: String,
     hashed_password: String,
     years: Vec<u32>,
+    #[serde(default)]
+    roles: Vec<String>,
 }
 
 impl RequestUser {
@@ -64,6 +98,12 @@ impl RequestUser {
             Err(InvalidApiRequest!("must specify at least 1 year group"))
         } else if !utils::is_valid_email(&self.email_address) {
             Err(InvalidApiRequest!("email address is invalid"))
+        } else if let Some(role) = self
+            .roles
+            .iter()
+            .find(|role| !constant::ROLES.contains(&role.as_str()))
+        {
+            Err(InvalidApiRequest!(format!("{role} is not a role")))
         } else {
             Ok(())
         }
@@ -76,6 +116,8 @@ pub struct ResponseUser {
     last_name: String,
     email_address: String,
     years: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    roles: Vec<String>,
 }
 
 impl From<User> for ResponseUser {
@@ -85,6 +127,7 @@ impl From<User> for ResponseUser {
             last_name: value.last_name,
             email_address: value.email_address,
             years: value.years,
+            roles: value.roles,
         }
     }
 }
@@ -103,12 +146,12 @@ mod tests {
     #[rstest]
     #[case("test", "user", "test@test.com", "password", vec![2,3],  Ok(()))]
     #[case("", "user", "test@test.com", "password", vec![2,3],      Err(InvalidApiRequest!("names cannot be empty")))]
-    #[case("test", "", "test@test.com", "password", vec![2,3],      Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("names cannot be empty".into())}))]
-    #[case("test", "user", "test@test.", "password", vec![2,3],     Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into())}))]
-    #[case("test", "user", "testattest.com", "password", vec![2,3], Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into())}))]
-    #[case("test", "user", "", "password", vec![2,3],               Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into())}))]
-    #[case("test", "user", "test@test.com", "", vec![2,3],          Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("password cannot be empty".into())}))]
-    #[case("test", "user", "test@test.com", "password", vec![],     Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("must specify at least 1 year group".into())}))]
+    #[case("test", "", "test@test.com", "password", vec![2,3],      Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("names cannot be empty".into()), fields: None}))]
+    #[case("test", "user", "test@test.", "password", vec![2,3],     Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into()), fields: None}))]
+    #[case("test", "user", "testattest.com", "password", vec![2,3], Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into()), fields: None}))]
+    #[case("test", "user", "", "password", vec![2,3],               Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("email address is invalid".into()), fields: None}))]
+    #[case("test", "user", "test@test.com", "", vec![2,3],          Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("password cannot be empty".into()), fields: None}))]
+    #[case("test", "user", "test@test.com", "password", vec![],     Err(Error {kind: ErrorKind::InvalidApiRequest, message: Some("must specify at least 1 year group".into()), fields: None}))]
     fn test_validate_request_user(
         #[case] first_names: String,
         #[case] last_name: String,
@@ -123,6 +166,7 @@ mod tests {
             email_address,
             hashed_password,
             years,
+            roles: vec![],
         };
         match exp {
             Ok(_) => assert!(req.validate().is_ok()),
@@ -1,9 +1,17 @@
-use crate::{core::error::Result, utils::functions::generate_secret};
+use crate::{
+    core::{constant, error::Result},
+    utils::{functions::generate_secret, patch::Patch},
+};
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+};
 use chrono::{NaiveDateTime, Utc};
 use entity::user::{ActiveModel, Entity, Model};
 use sea_orm::{ActiveModelTrait, EntityTrait};
-use sea_orm::{DatabaseConnection, Set};
+use sea_orm::{DatabaseConnection, Set, Unchanged};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, PartialOrd)]
 pub struct User {
@@ -15,6 +23,8 @@ pub struct User {
     pub(crate) years: Vec<u32>,
     pub(crate) secret: Vec<u8>,
     pub(crate) last_refresh: NaiveDateTime,
+    #[serde(default)]
+    pub(crate) roles: Vec<String>,
 }
 
 impl User {
@@ -33,6 +43,58 @@ impl User {
             years,
             secret: generate_secret().to_vec(),
             last_refresh: Utc::now().naive_utc(),
+            roles: vec![],
+        }
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// Store an argon2 hash of the password, never the password itself.
+    pub fn set_password(&mut self, password: &str) -> Result<()> {
+        let salt = SaltString::generate(&mut OsRng);
+        self.hashed_password = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|error| ServerError!(error.to_string()))?
+            .to_string();
+        Ok(())
+    }
+
+    /// Accounts made before passwords were hashed on the server still hold the value they were
+    /// created with, so anything that isn't an argon2 hash is compared as it is.
+    pub fn check_password(&self, password: &str) -> bool {
+        match PasswordHash::new(&self.hashed_password) {
+            Ok(hash) => Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok(),
+            Err(_) => password == self.hashed_password,
+        }
+    }
+
+    /// Whether the password is still held as it was created, and should be hashed at the next
+    /// successful login.
+    pub fn has_legacy_password(&self) -> bool {
+        PasswordHash::new(&self.hashed_password).is_err()
+    }
+
+    /// Check the user has a role, for actions only that role can take.
+    pub fn check_role(&self, role: &str, message: &str) -> Result<()> {
+        if self.has_role(role) {
+            Ok(())
+        } else {
+            Err(Unauthorised!(message))
+        }
+    }
+
+    /// Check the user can see a whole year group, for views that aren't about a single pupil.
+    pub fn check_year(&self, year: i32) -> Result<()> {
+        if self.years.contains(&(year as u32)) {
+            Ok(())
+        } else {
+            Err(Unauthorised!(format!(
+                "you don't have permission to view year {year}"
+            )))
         }
     }
 
@@ -49,7 +111,8 @@ impl User {
                 .collect::<Vec<String>>()
                 .join(",")),
             secret: Set(self.secret.clone()),
-            last_refresh: Set(self.last_refresh.clone()),
+            last_refresh: Set(self.last_refresh),
+            roles: Set(self.roles.join(",")),
         }
         .insert(db)
         .await?
@@ -74,6 +137,68 @@ impl User {
             .collect())
     }
 
+    pub async fn update(&self, db: &DatabaseConnection) -> Result<Self> {
+        Ok(ActiveModel {
+            first_names: Set(self.first_names.clone()),
+            last_name: Set(self.last_name.clone()),
+            email_address: Unchanged(self.email_address.clone()),
+            hashed_password: Set(self.hashed_password.clone()),
+            years: Set(self
+                .years
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(",")),
+            secret: Set(self.secret.clone()),
+            last_refresh: Set(self.last_refresh),
+            roles: Set(self.roles.join(",")),
+        }
+        .update(db)
+        .await?
+        .into())
+    }
+
+    /// Apply a JSON merge patch made by `by`. None of a user's fields are nullable so any `null`
+    /// is rejected. Anyone can change their own names and password, but only an admin can change
+    /// another user or anyone's years and roles, as they decide what a user can see.
+    pub fn set_from_update(&mut self, update: UserUpdate, by: &User) -> Result<()> {
+        if !by.has_role(constant::ROLE_ADMIN) {
+            if by.email_address != self.email_address {
+                return Err(Unauthorised!("only an admin can change another user"));
+            }
+            if !update.years.is_missing() || !update.roles.is_missing() {
+                return Err(Unauthorised!("only an admin can change years or roles"));
+            }
+        }
+        if let Patch::Value(password) = &update.password {
+            if password.is_empty() {
+                return Err(ValidationError!("user failed validation").with_fields(
+                    BTreeMap::from([("password".to_owned(), "password is empty".to_owned())]),
+                ));
+            }
+        }
+        if let Patch::Value(roles) = &update.roles {
+            if let Some(role) = roles
+                .iter()
+                .find(|role| !constant::ROLES.contains(&role.as_str()))
+            {
+                return Err(ValidationError!("user failed validation").with_fields(
+                    BTreeMap::from([("roles".to_owned(), format!("{role} is not a role"))]),
+                ));
+            }
+        }
+        update.first_names.apply("first_names", &mut self.first_names)?;
+        update.last_name.apply("last_name", &mut self.last_name)?;
+        if !update.password.is_missing() {
+            let mut password = String::new();
+            update.password.apply("password", &mut password)?;
+            self.set_password(&password)?;
+        }
+        update.years.apply("years", &mut self.years)?;
+        update.roles.apply("roles", &mut self.roles)?;
+        Ok(())
+    }
+
     pub async fn refresh_secret(&self, db: &DatabaseConnection) -> Result<User> {
         let new_secret = generate_secret();
         let mut active: ActiveModel = <User as Into<Model>>::into(self.clone()).into();
@@ -83,9 +208,20 @@ impl User {
     }
 }
 
+#[derive(Clone, PartialEq, Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct UserUpdate {
+    first_names: Patch<String>,
+    last_name: Patch<String>,
+    password: Patch<String>,
+    years: Patch<Vec<u32>>,
+    roles: Patch<Vec<String>>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::error::ErrorKind;
     use migration::{Migrator, MigratorTrait};
     use rstest::*;
     use sea_orm::{Database, DatabaseBackend, MockDatabase, Transaction};
@@ -100,6 +236,7 @@ mod tests {
             years: "2,3".into(),
             secret: vec![127; 64],
             last_refresh: Utc::now().naive_utc(),
+            roles: "".into(),
         }];
         let db = MockDatabase::new(DatabaseBackend::Postgres)
             .append_query_results(vec![results.clone()])
@@ -111,7 +248,7 @@ mod tests {
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"SELECT "user"."first_names", "user"."last_name", "user"."email_address", "user"."hashed_password", "user"."years", "user"."secret", "user"."last_refresh" FROM "user" WHERE "user"."email_address" = $1 LIMIT $2"#,
+            r#"SELECT "user"."first_names", "user"."last_name", "user"."email_address", "user"."hashed_password", "user"."years", "user"."secret", "user"."last_refresh", "user"."roles" FROM "user" WHERE "user"."email_address" = $1 LIMIT $2"#,
             [results[0].email_address.clone().into(), 1u64.into()],
         );
         assert_eq!(t_log[0], exp_query);
@@ -128,6 +265,7 @@ mod tests {
                 years: "2,3".into(),
                 secret: vec![127; 64],
                 last_refresh: Utc::now().naive_utc(),
+                roles: "".into(),
             },
             Model {
                 first_names: "test2".into(),
@@ -137,6 +275,7 @@ mod tests {
                 years: "1,2,3,4,5,6".into(),
                 secret: vec![127; 64],
                 last_refresh: Utc::now().naive_utc(),
+                roles: "".into(),
             },
         ];
         let db = MockDatabase::new(DatabaseBackend::Postgres)
@@ -152,7 +291,7 @@ mod tests {
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"SELECT "user"."first_names", "user"."last_name", "user"."email_address", "user"."hashed_password", "user"."years", "user"."secret", "user"."last_refresh" FROM "user""#,
+            r#"SELECT "user"."first_names", "user"."last_name", "user"."email_address", "user"."hashed_password", "user"."years", "user"."secret", "user"."last_refresh", "user"."roles" FROM "user""#,
             [],
         );
         assert_eq!(t_log[0], exp_query);
@@ -172,7 +311,8 @@ mod tests {
             hashed_password: "hashedpass".into(),
             years: "1,2,3".into(),
             secret: secret.to_vec(),
-            last_refresh: refresh_dt.clone(),
+            last_refresh: refresh_dt,
+            roles: "".into(),
         };
         let db = MockDatabase::new(DatabaseBackend::Postgres)
             .append_query_results(vec![vec![model.clone()]])
@@ -182,7 +322,7 @@ mod tests {
         let t_log = db.into_transaction_log();
         let exp_query = Transaction::from_sql_and_values(
             DatabaseBackend::Postgres,
-            r#"INSERT INTO "user" ("first_names", "last_name", "email_address", "hashed_password", "years", "secret", "last_refresh") VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING "first_names", "last_name", "email_address", "hashed_password", "years", "secret", "last_refresh""#,
+            r#"INSERT INTO "user" ("first_names", "last_name", "email_address", "hashed_password", "years", "secret", "last_refresh", "roles") VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING "first_names", "last_name", "email_address", "hashed_password", "years", "secret", "last_refresh", "roles""#,
             [
                 "test".into(),
                 "user".into(),
@@ -191,11 +331,127 @@ mod tests {
                 "1,2,3".into(),
                 secret.to_vec().into(),
                 refresh_dt.into(),
+                "".into(),
+            ],
+        );
+        assert_eq!(t_log[0], exp_query);
+    }
+
+    #[rstest]
+    async fn test_update() {
+        let refresh_dt: NaiveDateTime = NaiveDateTime::from_timestamp_millis(1662921288).unwrap();
+        let mut user = User::new("test", "user", "test@test.com", "hashedpass", vec![1, 2, 3]);
+        user.last_refresh = refresh_dt;
+        user.secret = vec![129; 64];
+        let update: UserUpdate =
+            serde_json::from_str(r#"{"last_name": "newname", "years": [4, 5], "roles": ["dsl"]}"#)
+                .unwrap();
+        let mut admin = User::new("admin", "user", "admin@test.com", "hashedpass", vec![1]);
+        admin.roles = vec![constant::ROLE_ADMIN.into()];
+        user.set_from_update(update, &admin).unwrap();
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results(vec![vec![Model::from(user.clone())]])
+            .into_connection();
+        let result = user.update(&db).await;
+        assert!(result.is_ok());
+        let t_log = db.into_transaction_log();
+        let exp_query = Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"UPDATE "user" SET "first_names" = $1, "last_name" = $2, "hashed_password" = $3, "years" = $4, "secret" = $5, "last_refresh" = $6, "roles" = $7 WHERE "user"."email_address" = $8 RETURNING "first_names", "last_name", "email_address", "hashed_password", "years", "secret", "last_refresh", "roles""#,
+            [
+                "test".into(),
+                "newname".into(),
+                "hashedpass".into(),
+                "4,5".into(),
+                vec![129u8; 64].into(),
+                refresh_dt.into(),
+                "dsl".into(),
+                "test@test.com".into(),
             ],
         );
         assert_eq!(t_log[0], exp_query);
     }
 
+    #[rstest]
+    #[case(r#"{"first_names": null}"#, "first_names cannot be null")]
+    #[case(r#"{"years": null}"#, "years cannot be null")]
+    fn test_set_from_update_rejects_null(#[case] json: &str, #[case] message: &str) {
+        let mut user = User::new("test", "user", "test@test.com", "hashedpass", vec![1]);
+        user.roles = vec![constant::ROLE_ADMIN.into()];
+        let update: UserUpdate = serde_json::from_str(json).unwrap();
+        let error = user.set_from_update(update, &user.clone()).unwrap_err();
+        assert_eq!(error.message, Some(message.into()));
+    }
+
+    #[rstest]
+    #[case(r#"{"years": [1, 2]}"#, "only an admin can change years or roles")]
+    #[case(r#"{"roles": ["dsl"]}"#, "only an admin can change years or roles")]
+    fn test_set_from_update_needs_admin(#[case] json: &str, #[case] message: &str) {
+        let mut user = User::new("test", "user", "test@test.com", "hashedpass", vec![1]);
+        let update: UserUpdate = serde_json::from_str(json).unwrap();
+        let error = user.set_from_update(update, &user.clone()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Unauthorised);
+        assert_eq!(error.message, Some(message.into()));
+    }
+
+    #[rstest]
+    fn test_set_from_update_rejects_unknown_roles() {
+        let mut user = User::new("test", "user", "test@test.com", "hashedpass", vec![1]);
+        user.roles = vec![constant::ROLE_ADMIN.into()];
+        let update: UserUpdate = serde_json::from_str(r#"{"roles": ["dsl", "head"]}"#).unwrap();
+        let error = user.set_from_update(update, &user.clone()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert_eq!(
+            error.fields,
+            Some(BTreeMap::from([(
+                "roles".to_owned(),
+                "head is not a role".to_owned()
+            )]))
+        );
+        assert_eq!(user.roles, [constant::ROLE_ADMIN]);
+    }
+
+    #[rstest]
+    fn test_set_from_update_other_user_needs_admin() {
+        let mut user = User::new("test", "user", "test@test.com", "hashedpass", vec![1]);
+        let other = User::new("other", "user", "other@test.com", "hashedpass", vec![1]);
+        let update: UserUpdate = serde_json::from_str(r#"{"last_name": "newname"}"#).unwrap();
+        let error = user.set_from_update(update, &other).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Unauthorised);
+        assert_eq!(user.last_name, "user");
+    }
+
+    #[rstest]
+    fn test_password_is_hashed() {
+        let mut user = User::new("test", "user", "test@test.com", "oldpass", vec![1]);
+        assert!(user.check_password("oldpass"));
+        let update: UserUpdate =
+            serde_json::from_str(r#"{"first_names": "new", "password": "newpass"}"#).unwrap();
+        user.set_from_update(update, &user.clone()).unwrap();
+        assert_eq!(user.first_names, "new");
+        assert!(user.hashed_password.starts_with("$argon2"));
+        assert!(user.check_password("newpass"));
+        assert!(!user.check_password("oldpass"));
+        assert!(!user.check_password(&user.hashed_password.clone()));
+        assert!(!user.has_legacy_password());
+    }
+
+    #[rstest]
+    fn test_set_from_update_rejects_empty_password() {
+        let mut user = User::new("test", "user", "test@test.com", "oldpass", vec![1]);
+        let update: UserUpdate = serde_json::from_str(r#"{"password": ""}"#).unwrap();
+        let error = user.set_from_update(update, &user.clone()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ValidationError);
+        assert_eq!(
+            error.fields,
+            Some(BTreeMap::from([(
+                "password".to_owned(),
+                "password is empty".to_owned()
+            )]))
+        );
+        assert!(user.check_password("oldpass"));
+    }
+
     #[rstest]
     async fn test_refresh_secret() {
         let secret = [129; 64];
@@ -224,14 +480,21 @@ impl From<Model> for User {
             hashed_password: value.hashed_password,
             years: value
                 .years
-                .split(",")
+                .split(',')
+                .filter(|x| !x.is_empty())
                 .map(|x| {
                     x.parse::<u32>()
                         .expect("should be comma-sep'd list of ints")
                 })
                 .collect(),
-            secret: value.secret.into(),
+            secret: value.secret,
             last_refresh: value.last_refresh,
+            roles: value
+                .roles
+                .split(',')
+                .filter(|role| !role.is_empty())
+                .map(String::from)
+                .collect(),
         }
     }
 }
@@ -251,6 +514,7 @@ impl From<User> for Model {
                 .join(","),
             secret: value.secret,
             last_refresh: value.last_refresh,
+            roles: value.roles.join(","),
         }
     }
 }
@@ -261,6 +525,7 @@ mod trait_tests {
     use rstest::*;
 
     #[rstest]
+    #[case("")]
     #[case("1")]
     #[case("1,2")]
     #[case("1,2,3")]
@@ -273,6 +538,7 @@ mod trait_tests {
             years: years_string,
             secret: vec![127; 64],
             last_refresh: Utc::now().naive_utc(),
+            roles: "".into(),
         };
         let _user_attempt = User::from(model);
     }
@@ -293,6 +559,7 @@ mod trait_tests {
             years: years_string,
             secret: vec![127; 64],
             last_refresh: Utc::now().naive_utc(),
+            roles: "".into(),
         };
         let _user_attempt = User::from(model);
     }
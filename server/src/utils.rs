@@ -1,4 +1,6 @@
 pub mod functions;
+pub mod lock;
 pub mod log;
+pub mod patch;
 
 pub use shared_utils::*;
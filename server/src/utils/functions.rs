@@ -6,3 +6,16 @@ pub fn generate_secret() -> [u8; 64] {
     rand::thread_rng().fill_bytes(&mut secret);
     secret
 }
+
+/// Percent encode everything but the unreserved characters, as URLs and AWS signatures need.
+pub fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
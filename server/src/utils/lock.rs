@@ -0,0 +1,21 @@
+use crate::core::error::Result;
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseTransaction, Statement};
+
+/// Keys for the hash-chained logs, so two requests can't both append after the same entry.
+pub const CONCERN_ACCESS_LOG: i64 = 1;
+pub const SUBJECT_ACCESS_LOG: i64 = 2;
+
+/// Hold a lock until the transaction commits or rolls back. Postgres takes an advisory lock on the
+/// key, which works even while the table is empty and there's no row to lock. SQLite already
+/// only allows one writer at a time, so there's nothing to do.
+pub async fn lock_for_transaction(txn: &DatabaseTransaction, key: i64) -> Result<()> {
+    if txn.get_database_backend() == DatabaseBackend::Postgres {
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1)",
+            [key.into()],
+        ))
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::core::error::Result;
+use serde::{Deserialize, Deserializer};
+
+/// A single field of an RFC 7396 JSON Merge Patch. A key that is absent from the patch is
+/// `Missing` and leaves the field alone, an explicit `null` is `Null` and clears it, and anything
+/// else is the new `Value`. Fields using this need `#[serde(default)]` so absent keys are `Missing`.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub enum Patch<T> {
+    #[default]
+    Missing,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Apply the patch to a field that can't be null, erroring if the patch tries to clear it.
+    pub fn apply(self, field: &str, target: &mut T) -> Result<()> {
+        match self {
+            Patch::Missing => Ok(()),
+            Patch::Null => Err(InvalidApiRequest!(format!("{field} cannot be null"))),
+            Patch::Value(value) => {
+                *target = value;
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply the patch to a nullable field, where `null` sets it back to `None`.
+    pub fn apply_nullable(self, target: &mut Option<T>) {
+        match self {
+            Patch::Missing => {}
+            Patch::Null => *target = None,
+            Patch::Value(value) => *target = Some(value),
+        }
+    }
+
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Patch::Missing)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // only called when the key is present, so a None here was an explicit null
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorKind;
+    use rstest::*;
+
+    #[derive(Deserialize, Default, Debug, PartialEq)]
+    #[serde(default)]
+    struct TestPatch {
+        name: Patch<String>,
+        count: Patch<i32>,
+    }
+
+    #[rstest]
+    #[case(r#"{}"#, TestPatch { name: Patch::Missing, count: Patch::Missing })]
+    #[case(r#"{"name": null}"#, TestPatch { name: Patch::Null, count: Patch::Missing })]
+    #[case(r#"{"name": "test", "count": 3}"#, TestPatch { name: Patch::Value("test".into()), count: Patch::Value(3) })]
+    fn test_deserialize_patch(#[case] json: &str, #[case] expected: TestPatch) {
+        assert_eq!(serde_json::from_str::<TestPatch>(json).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(Patch::Missing, Some(1), Some(1))]
+    #[case(Patch::Null, Some(1), None)]
+    #[case(Patch::Value(2), Some(1), Some(2))]
+    #[case(Patch::Value(2), None, Some(2))]
+    fn test_apply_nullable(
+        #[case] patch: Patch<i32>,
+        #[case] mut target: Option<i32>,
+        #[case] expected: Option<i32>,
+    ) {
+        patch.apply_nullable(&mut target);
+        assert_eq!(target, expected);
+    }
+
+    #[rstest]
+    fn test_apply_null_to_non_nullable() {
+        let mut target = 1;
+        let error = Patch::<i32>::Null.apply("count", &mut target).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidApiRequest);
+        assert_eq!(error.message, Some("count cannot be null".into()));
+        assert_eq!(target, 1);
+    }
+}
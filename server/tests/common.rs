@@ -1,8 +1,13 @@
 use axum_test_helper::TestClient;
 use entity::{pupil::Model as Pupil, user::Model as User};
 use lt_server::{
+    app::config::Config,
     app::router::router,
     app::state::{AppStateTrait, MockAppStateTrait},
+    attachment::{
+        scan::{NoScanner, VirusScanner},
+        store::{BlobStore, LocalStore},
+    },
     core::constant,
 };
 use migration::{Migrator, MigratorTrait};
@@ -11,6 +16,8 @@ use sea_orm::{Database, DatabaseConnection, EntityTrait};
 use serde_json::json;
 use std::{collections::HashMap, sync::Arc};
 
+pub const ADMIN_USER: &str = "admin_user@integration.com";
+
 #[fixture]
 pub async fn mock_ctx() -> MockCtx {
     let mut mock_state = MockAppStateTrait::new();
@@ -25,6 +32,13 @@ pub async fn mock_ctx() -> MockCtx {
     mock_state
         .expect_database()
         .return_const(Arc::clone(&mock_db));
+    mock_state.expect_config().return_const(Config::default());
+    let blob_store: Arc<dyn BlobStore> = Arc::new(LocalStore::new(
+        std::env::temp_dir().join(format!("lt_server_test_{}", uuid::Uuid::new_v4())),
+    ));
+    mock_state.expect_blob_store().return_const(blob_store);
+    let scanner: Arc<dyn VirusScanner> = Arc::new(NoScanner);
+    mock_state.expect_scanner().return_const(scanner);
     let state: Arc<dyn AppStateTrait + Send + Sync> = Arc::new(mock_state);
     let app = router(Arc::clone(&state)).with_state(Arc::clone(&state));
     let client = TestClient::new(app);
@@ -57,6 +71,31 @@ impl MockCtx {
         let token = login.json::<HashMap<String, String>>().await;
         token["token"].to_owned()
     }
+
+    /// Adds an admin in the test user's years, and returns their token.
+    pub async fn login_admin(&self) -> String {
+        entity::user::Entity::insert(entity::user::ActiveModel::from(User {
+            first_names: "Head".into(),
+            last_name: "Teacher".into(),
+            email_address: ADMIN_USER.into(),
+            hashed_password: "password".into(),
+            years: "5,6".into(),
+            secret: vec![4; 64],
+            last_refresh: "2021-01-01T00:00:00".parse().unwrap(),
+            roles: constant::ROLE_ADMIN.into(),
+        }))
+        .exec(self.check_db())
+        .await
+        .expect("insert admin user");
+        let login = self
+            .client()
+            .post(constant::LOGIN_ENDPOINT)
+            .json(&json!({"email_address": ADMIN_USER, "hashed_password": "password"}))
+            .send()
+            .await;
+        assert_eq!(login.status(), http::StatusCode::OK);
+        login.json::<HashMap<String, String>>().await["token"].to_owned()
+    }
 }
 
 pub async fn add_user(secret: &[u8], last_refresh: &str, db: &DatabaseConnection) -> User {
@@ -68,6 +107,7 @@ pub async fn add_user(secret: &[u8], last_refresh: &str, db: &DatabaseConnection
         years: "5,6".into(),
         secret: secret.to_vec(),
         last_refresh: last_refresh.parse().expect("parse last_refresh"),
+        roles: "".into(),
     };
     entity::user::Entity::insert(<User as Into<entity::user::ActiveModel>>::into(
         user.clone(),
@@ -85,7 +125,7 @@ pub async fn add_pupils(db: &DatabaseConnection) -> Vec<&'static str> {
             first_names: "first".into(),
             last_name: "student".into(),
             start_date: "2021-01-01".parse().unwrap(),
-            gender: "gender".into(),
+            gender: "female".into(),
             year: 6,
             active: true,
             ..Default::default()
@@ -95,7 +135,7 @@ pub async fn add_pupils(db: &DatabaseConnection) -> Vec<&'static str> {
             first_names: "second".into(),
             last_name: "student".into(),
             start_date: "2021-01-01".parse().unwrap(),
-            gender: "gender".into(),
+            gender: "female".into(),
             year: 6,
             active: true,
             ..Default::default()
@@ -105,7 +145,7 @@ pub async fn add_pupils(db: &DatabaseConnection) -> Vec<&'static str> {
             first_names: "third".into(),
             last_name: "student".into(),
             start_date: "2021-01-01".parse().unwrap(),
-            gender: "gender".into(),
+            gender: "female".into(),
             year: 2,
             active: true,
             ..Default::default()
@@ -0,0 +1,171 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const ALNCO_USER: &str = "alnco_user@integration.com";
+
+/// Adds a user with the ALNCo role in the test user's years, and returns their token.
+async fn login_alnco(ctx: &MockCtx) -> String {
+    entity::user::Entity::insert(entity::user::ActiveModel::from(entity::user::Model {
+        first_names: "Additional".into(),
+        last_name: "Needs".into(),
+        email_address: ALNCO_USER.into(),
+        hashed_password: "password".into(),
+        years: "5,6".into(),
+        secret: vec![2; 64],
+        last_refresh: "2021-01-01T00:00:00".parse().unwrap(),
+        roles: constant::ROLE_ALNCO.into(),
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("insert alnco user");
+    let login = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": ALNCO_USER, "hashed_password": "password"}))
+        .send()
+        .await;
+    assert_eq!(login.status(), StatusCode::OK);
+    login.json::<HashMap<String, String>>().await["token"].to_owned()
+}
+
+async fn pupil_flag(ctx: &MockCtx, token: &str, pupil_id: &str) -> Value {
+    let res = ctx
+        .client()
+        .get(&format!("{}/{pupil_id}", constant::PUPILS_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await["additional_learning_needs"].clone()
+}
+
+#[rstest]
+async fn register_entry_drives_the_aln_flag(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let alnco = login_alnco(&ctx).await;
+    let url = format!("{}/{}/aln", constant::PUPILS_ENDPOINT, ids[0]);
+    let entry = json!({
+        "provision": "school",
+        "areas_of_need": ["communication_and_interaction"],
+        "alnco": ALNCO_USER,
+        "review_date": "2099-01-01"
+    });
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&entry)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({"provision": "school", "areas_of_need": ["juggling"], "review_date": "2099-01-01"}))
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&entry)
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(pupil_flag(&ctx, &token, ids[0]).await, true);
+    assert_eq!(pupil_flag(&ctx, &token, ids[1]).await, false);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/register", constant::ALN_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let register = res.json::<Vec<Value>>().await;
+    assert_eq!(register.len(), 1);
+    assert_eq!(register[0]["first_names"], "first");
+    assert_eq!(
+        register[0]["areas_of_need"],
+        json!(["communication_and_interaction"])
+    );
+
+    let res = ctx
+        .client()
+        .delete(&url)
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(pupil_flag(&ctx, &token, ids[0]).await, false);
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await["closed_on"].is_string());
+}
+
+#[rstest]
+async fn idps_are_versioned(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let alnco = login_alnco(&ctx).await;
+    let url = format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]);
+    let plan = json!({"outcomes": "reads aloud to the class", "provision": "daily 1:1 reading"});
+
+    let res = ctx
+        .client()
+        .put(&format!("{url}/idp"))
+        .json(&plan)
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .put(&format!("{url}/aln"))
+        .json(&json!({
+            "provision": "local_authority",
+            "areas_of_need": ["cognition_and_learning"],
+            "review_date": "2099-01-01"
+        }))
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    for _ in 0..2 {
+        let res = ctx
+            .client()
+            .put(&format!("{url}/idp"))
+            .json(&plan)
+            .header("Authorization", format!("Bearer {alnco}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+    let res = ctx
+        .client()
+        .get(&format!("{url}/idp"))
+        .header("Authorization", format!("Bearer {alnco}"))
+        .send()
+        .await;
+    let versions = res.json::<Vec<Value>>().await;
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["version"], 2);
+    assert_eq!(versions[1]["version"], 1);
+    assert_eq!(versions[0]["created_by"], ALNCO_USER);
+}
@@ -0,0 +1,172 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+#[rstest]
+async fn cohort_cross_tab_hides_small_numbers(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/cohort?rows=year&columns=gender&as_at=2023-01-01",
+            constant::ANALYTICS_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let report = res.json::<Value>().await;
+    // only the two year 6 pupils are in the test user's years
+    assert_eq!(report["row_keys"], json!(["6"]));
+    assert_eq!(report["columns"], "gender");
+    assert_eq!(report["threshold"], 5);
+    assert_eq!(report["total"]["count"], Value::Null);
+    let female = constant::GENDERS
+        .iter()
+        .position(|gender| *gender == "female")
+        .unwrap();
+    assert_eq!(report["cells"][0][female]["count"], Value::Null);
+    assert_eq!(
+        report["cells"][0][female]["flags"]["fsm"]["percentage"],
+        Value::Null
+    );
+}
+
+#[rstest]
+async fn cohort_needs_two_different_dimensions(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/cohort?rows=fsm&columns=fsm",
+            constant::ANALYTICS_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Twelve year 6 pupils, the first six on free school meals since the start of the year.
+async fn add_year_group(ctx: &MockCtx) -> Vec<uuid::Uuid> {
+    let ids: Vec<uuid::Uuid> = (0..12).map(|_| uuid::Uuid::new_v4()).collect();
+    entity::pupil::Entity::insert_many(ids.iter().enumerate().map(|(i, id)| {
+        entity::pupil::ActiveModel::from(entity::pupil::Model {
+            id: *id,
+            first_names: format!("pupil {i}"),
+            last_name: "gap".into(),
+            start_date: "2021-09-01".parse().unwrap(),
+            gender: "female".into(),
+            year: 6,
+            active: true,
+            free_school_meals: i < 6,
+            ..Default::default()
+        })
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("insert year group");
+    entity::flag_period::Entity::insert_many(ids[..6].iter().map(|id| {
+        entity::flag_period::ActiveModel::from(entity::flag_period::Model {
+            id: uuid::Uuid::new_v4(),
+            pupil_id: *id,
+            flag: "fsm".into(),
+            start_date: "2022-09-01".parse().unwrap(),
+            source: "pupil record".into(),
+            updated_at: "2022-09-01T00:00:00".parse().unwrap(),
+            ..Default::default()
+        })
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("insert FSM periods");
+    ids
+}
+
+async fn assessment_with_results(ctx: &MockCtx, token: &str, date: &str, results: Value) -> String {
+    let res = ctx
+        .client()
+        .put(constant::ASSESSMENTS_ENDPOINT)
+        .json(&json!({
+            "name": format!("Reading {date}"),
+            "subject": "English",
+            "year": 6,
+            "date": date,
+            "max_score": 40
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let id = res.json::<Value>().await["id"].as_str().unwrap().to_owned();
+    let res = ctx
+        .client()
+        .put(&format!("{}/{id}/results", constant::ASSESSMENTS_ENDPOINT))
+        .json(&results)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.status().is_success());
+    id
+}
+
+#[rstest]
+async fn fsm_gap_on_assessments_over_time(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_year_group(&ctx).await;
+    let token = ctx.login().await;
+    let scores = |boost: i32| {
+        json!(ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let raw = 10 + 2 * (i as i32 % 6) + if i < 6 { boost } else { 10 };
+                json!({"pupil_id": id, "raw_score": raw})
+            })
+            .collect::<Vec<Value>>())
+    };
+    let spring = assessment_with_results(&ctx, &token, "2023-03-20", scores(0)).await;
+    let summer = assessment_with_results(&ctx, &token, "2023-07-03", scores(4)).await;
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/gap", constant::ANALYTICS_ENDPOINT))
+        .json(&json!({
+            "measure": {"kind": "assessments", "assessment_ids": [summer, spring]},
+            "groups": [{"flags": {"fsm": true}}, {"flags": {"fsm": false}}]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let report = res.json::<Value>().await;
+    assert_eq!(report["groups"], json!(["FSM", "not FSM"]));
+    // oldest first, whatever order they were asked for in
+    assert_eq!(report["points"][0]["label"], "Reading 2023-03-20");
+    assert_eq!(report["points"][0]["scale"], "percentage");
+    assert_eq!(report["points"][0]["groups"][0]["pupils"], 6);
+    assert_eq!(report["points"][0]["groups"][0]["mean"], 37.5);
+    assert_eq!(report["points"][0]["groups"][1]["mean"], 62.5);
+    assert_eq!(report["points"][0]["gap"], -25.0);
+    assert_eq!(report["points"][1]["gap"], -15.0);
+    assert_eq!(report["gap_change"], 10.0);
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/gap", constant::ANALYTICS_ENDPOINT))
+        .json(&json!({
+            "measure": {"kind": "assessments", "assessment_ids": [uuid::Uuid::new_v4()]},
+            "groups": [{"flags": {"fsm": true}}, {}]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>().await["error"], "ASSESSMENT DOES NOT EXIST");
+}
@@ -0,0 +1,213 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+async fn create_assessment(ctx: &MockCtx, token: &str) -> String {
+    let res = ctx
+        .client()
+        .put(constant::ASSESSMENTS_ENDPOINT)
+        .json(&json!({
+            "name": "Spring reading",
+            "subject": "English",
+            "year": 6,
+            "date": "2023-03-20",
+            "max_score": 40,
+            "standardisation": [
+                {"raw": 0, "standardised": 70},
+                {"raw": 20, "standardised": 100},
+                {"raw": 30, "standardised": 115}
+            ]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    res.json::<Value>().await["id"]
+        .as_str()
+        .expect("id of new assessment")
+        .to_owned()
+}
+
+#[rstest]
+async fn login_and_create_assessment(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let id = create_assessment(&ctx, &token).await;
+    let inserted = &entity::assessment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()[0];
+    assert_eq!(inserted.id.to_string(), id);
+    assert_eq!(inserted.standardisation, Some("0:70,20:100,30:115".into()));
+    let res = ctx
+        .client()
+        .get(constant::ASSESSMENTS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(res_body[0]["name"], "Spring reading");
+    assert_eq!(res_body[0]["standardisation"][2]["standardised"], 115);
+}
+
+#[rstest]
+async fn login_and_create_assessment_outside_years(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let res = ctx
+        .client()
+        .put(constant::ASSESSMENTS_ENDPOINT)
+        .json(&json!({
+            "name": "Phonics check",
+            "subject": "English",
+            "year": 2,
+            "date": "2023-03-20",
+            "max_score": 40
+        }))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+async fn login_and_enter_results(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = create_assessment(&ctx, &token).await;
+    let results_endpoint = format!("{}/{id}/results", constant::ASSESSMENTS_ENDPOINT);
+    let res = ctx
+        .client()
+        .get(&results_endpoint)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let grid = res.json::<Value>().await;
+    // only the two year 6 pupils, with no scores yet
+    assert_eq!(grid["rows"].as_array().unwrap().len(), 2);
+    assert!(grid["rows"][0].get("raw_score").is_none());
+
+    let res = ctx
+        .client()
+        .put(&results_endpoint)
+        .json(&json!([
+            {"pupil_id": ids[0], "raw_score": 25},
+            {"pupil_id": ids[1], "raw_score": 10}
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let grid = res.json::<Value>().await;
+    assert_eq!(
+        grid["rows"][0],
+        json!({
+            "pupil_id": ids[0],
+            "first_names": "first",
+            "last_name": "student",
+            "raw_score": 25,
+            "percentage": 62.5,
+            "standardised_score": 100
+        })
+    );
+    assert_eq!(grid["rows"][1]["standardised_score"], 70);
+
+    // re-saving updates in place and a null removes the result
+    let res = ctx
+        .client()
+        .put(&results_endpoint)
+        .json(&json!([
+            {"pupil_id": ids[0], "raw_score": 40},
+            {"pupil_id": ids[1], "raw_score": null}
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let saved = entity::assessment_result::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].raw_score, 40);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}/results", constant::PUPILS_ENDPOINT, ids[0]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let results = res.json::<Value>().await;
+    assert_eq!(results[0]["assessment"]["name"], "Spring reading");
+    assert_eq!(results[0]["percentage"], 100.0);
+    assert_eq!(results[0]["standardised_score"], 115);
+}
+
+#[rstest]
+async fn login_and_enter_invalid_results(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = create_assessment(&ctx, &token).await;
+    let res = ctx
+        .client()
+        .put(&format!("{}/{id}/results", constant::ASSESSMENTS_ENDPOINT))
+        .json(&json!([
+            {"pupil_id": ids[0], "raw_score": 41},
+            {"pupil_id": ids[1], "raw_score": 20},
+            {"pupil_id": ids[2], "raw_score": 20}
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(
+        res_body["fields"],
+        json!({
+            ids[0]: "score must be between 0 and 40",
+            ids[2]: "pupil is not in year 6"
+        })
+    );
+    assert!(entity::assessment_result::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+async fn login_and_enter_repeated_results(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = create_assessment(&ctx, &token).await;
+    let res = ctx
+        .client()
+        .put(&format!("{}/{id}/results", constant::ASSESSMENTS_ENDPOINT))
+        .json(&json!([
+            {"pupil_id": ids[0], "raw_score": 30},
+            {"pupil_id": ids[1], "raw_score": 20},
+            {"pupil_id": ids[0], "raw_score": null}
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(
+        res_body["fields"],
+        json!({ids[0]: "pupil appears more than once"})
+    );
+    assert!(entity::assessment_result::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,168 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use serde_json::{json, Value};
+
+/// "%PDF-1.4 test", base64 encoded
+const PDF: &str = "JVBERi0xLjQgdGVzdA==";
+
+#[rstest]
+async fn pupil_attachments_download_through_signed_links(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/{}/attachments", constant::PUPILS_ENDPOINT, ids[0]);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({"file_name": "report.pdf", "content_type": "application/pdf", "data": "aGVsbG8="}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/attachments",
+            constant::PUPILS_ENDPOINT,
+            ids[2]
+        ))
+        .json(&json!({"file_name": "report.pdf", "content_type": "application/pdf", "data": PDF}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({
+            "file_name": "report.pdf",
+            "content_type": "application/pdf",
+            "description": "educational psychologist report",
+            "data": PDF
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let attachment = res.json::<Value>().await;
+    assert_eq!(attachment["size"], 13);
+    assert_eq!(attachment["scan_status"], "unscanned");
+    let id = attachment["id"].as_str().unwrap().to_owned();
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.json::<Vec<Value>>().await.len(), 1);
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/{id}"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let link = res.json::<Value>().await["url"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    let res = ctx.client().get(&link).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/pdf");
+    assert_eq!(res.text().await, "%PDF-1.4 test");
+
+    let tampered = format!("{}0", link.trim_end_matches(char::is_alphanumeric));
+    let res = ctx.client().get(&tampered).send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // the link stops working once the user loses access to the pupil's year
+    let user = entity::user::Entity::find_by_id("test_user@integration.com".to_owned())
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut user = entity::user::ActiveModel::from(user);
+    user.years = Set("2".into());
+    user.update(ctx.check_db()).await.unwrap();
+    let res = ctx.client().get(&link).send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+async fn comment_attachments(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "academic", "body": "see the attached letter"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let comment_id = res.json::<Value>().await["id"].as_str().unwrap().to_owned();
+    let url = format!(
+        "{}/{}/comments/{comment_id}/attachments",
+        constant::PUPILS_ENDPOINT,
+        ids[0]
+    );
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({"file_name": "letter.txt", "content_type": "text/plain", "data": "aGVsbG8="}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let id = res.json::<Value>().await["id"].as_str().unwrap().to_owned();
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let attachments = res.json::<Vec<Value>>().await;
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0]["comment_id"], comment_id.as_str());
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/attachments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Vec<Value>>().await.is_empty());
+
+    let res = ctx
+        .client()
+        .delete(&format!(
+            "{}/{}/attachments/{id}",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(entity::attachment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()
+        .is_empty());
+}
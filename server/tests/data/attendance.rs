@@ -0,0 +1,227 @@
+use crate::common::*;
+use chrono::Utc;
+use entity::pupil::Model as Pupil;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+/// Adds a year 6 pupil who left at February half term and one who joined after it.
+async fn add_movers(ctx: &MockCtx) {
+    let movers = vec![
+        Pupil {
+            id: uuid::Uuid::new_v4(),
+            first_names: "left".into(),
+            last_name: "student".into(),
+            start_date: "2021-01-01".parse().unwrap(),
+            end_date: Some("2023-02-17".parse().unwrap()),
+            gender: "male".into(),
+            year: 6,
+            active: false,
+            ..Default::default()
+        },
+        Pupil {
+            id: uuid::Uuid::new_v4(),
+            first_names: "joined".into(),
+            last_name: "student".into(),
+            start_date: "2023-02-27".parse().unwrap(),
+            gender: "male".into(),
+            year: 6,
+            active: true,
+            ..Default::default()
+        },
+    ];
+    entity::pupil::Entity::insert_many(
+        movers
+            .into_iter()
+            .map(entity::pupil::ActiveModel::from)
+            .collect::<Vec<_>>(),
+    )
+    .exec(ctx.check_db())
+    .await
+    .expect("insert movers");
+}
+
+fn names(register: &Value) -> Vec<&str> {
+    register["rows"]
+        .as_array()
+        .expect("array of rows")
+        .iter()
+        .map(|row| row["first_names"].as_str().unwrap())
+        .collect()
+}
+
+#[rstest]
+async fn register_lists_pupils_enrolled_on_the_day(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    add_pupils(ctx.check_db()).await;
+    add_movers(&ctx).await;
+    let token = ctx.login().await;
+    for (date, expected) in [
+        ("2023-02-13", vec!["first", "left", "second"]),
+        ("2023-03-01", vec!["first", "joined", "second"]),
+    ] {
+        let res = ctx
+            .client()
+            .get(&format!(
+                "{}/register/6/{date}",
+                constant::ATTENDANCE_ENDPOINT
+            ))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(names(&res.json::<Value>().await), expected);
+    }
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/register/2/2023-03-01",
+            constant::ATTENDANCE_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+async fn save_register_marks(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/register/6/2023-03-01", constant::ATTENDANCE_ENDPOINT);
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!([
+            {"pupil_id": ids[0], "session": "am", "code": "/"},
+            {"pupil_id": ids[0], "session": "pm", "code": "I"},
+            {"pupil_id": ids[1], "session": "am", "code": "L"},
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let register = res.json::<Value>().await;
+    assert_eq!(register["rows"][0]["am"], "/");
+    assert_eq!(register["rows"][0]["pm"], "I");
+    assert_eq!(register["rows"][1]["am"], "L");
+    assert!(register["rows"][1]["pm"].is_null());
+    let res = ctx
+        .client()
+        .post(&url)
+        .json(&json!([{"pupil_id": ids[0], "session": "pm", "code": null}]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await["rows"][0]["pm"].is_null());
+    assert_eq!(
+        entity::attendance_mark::Entity::find()
+            .all(ctx.check_db())
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!([
+            {"pupil_id": ids[0], "session": "am", "code": "Z"},
+            {"pupil_id": ids[2], "session": "am", "code": "/"},
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let fields = &res.json::<Value>().await["fields"];
+    assert_eq!(
+        fields[format!("{}.am", ids[0])],
+        "Z is not an attendance code"
+    );
+    assert_eq!(
+        fields[format!("{}.am", ids[2])],
+        "pupil is not enrolled in year 6 on 2023-03-01"
+    );
+}
+
+#[rstest]
+async fn persistent_absence_is_flagged(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let today = Utc::now().date_naive();
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/register/6/{today}",
+            constant::ATTENDANCE_ENDPOINT
+        ))
+        .json(&json!([
+            {"pupil_id": ids[0], "session": "am", "code": "/"},
+            {"pupil_id": ids[0], "session": "pm", "code": "O"},
+            {"pupil_id": ids[1], "session": "am", "code": "/"},
+            {"pupil_id": ids[1], "session": "pm", "code": "X"},
+        ]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/attendance",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let summary = res.json::<Value>().await;
+    assert_eq!(summary["possible_sessions"], 2);
+    assert_eq!(summary["unauthorised_absences"], 1);
+    assert_eq!(summary["percentage"], 50.0);
+    assert_eq!(summary["persistent_absence"], true);
+    let res = ctx
+        .client()
+        .get(constant::PUPILS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let pupils = res.json::<Value>().await;
+    let flagged: Vec<bool> = pupils
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["persistent_absence"].as_bool().unwrap())
+        .collect();
+    assert_eq!(flagged, vec![true, false]);
+}
+
+#[rstest]
+async fn only_admins_change_codes(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .get(&format!("{}/codes", constant::ATTENDANCE_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let codes = res.json::<Value>().await;
+    assert_eq!(codes.as_array().unwrap().len(), 17);
+    assert_eq!(codes[0]["code"], "#");
+    assert_eq!(codes[0]["meaning"], "not_required");
+    let res = ctx
+        .client()
+        .put(&format!("{}/codes", constant::ATTENDANCE_ENDPOINT))
+        .json(
+            &json!({"code": "W", "description": "Work experience", "meaning": "approved_activity"}),
+        )
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
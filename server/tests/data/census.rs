@@ -0,0 +1,107 @@
+use crate::common::*;
+use chrono::{Datelike, Utc};
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set};
+use serde_json::{json, Value};
+
+#[rstest]
+async fn census_return_waits_for_errors_to_be_fixed(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    // the pupils' years are as they stand today, so the census is taken today too
+    let census_date = Utc::now().date_naive();
+    let intake = if census_date.month() >= 9 {
+        census_date.year()
+    } else {
+        census_date.year() - 1
+    };
+    let url = format!("{}/{census_date}", constant::CENSUS_ENDPOINT);
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let census = res.json::<Value>().await;
+    // the year 2 pupil is on roll too, though outside the admin's years
+    assert_eq!(census["pupils"].as_array().unwrap().len(), 3);
+    assert_eq!(census["pupils"][0]["nc_year"], "2");
+    assert_eq!(census["pupils"][2]["nc_year"], "6");
+    let problems = census["problems"].as_array().unwrap();
+    assert_eq!(problems.len(), 6);
+    assert_eq!(problems[0]["pupil_id"], ids[0]);
+    assert_eq!(problems[0]["field"], "upn");
+    assert_eq!(problems[0]["severity"], "error");
+    assert_eq!(problems[1]["field"], "date_of_birth");
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/return"))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json::<Value>().await["details"],
+        "the census has 6 errors to fix before the return can be made"
+    );
+
+    // no one logged in teaches year 2, so that pupil is fixed straight in the database
+    entity::pupil::ActiveModel {
+        id: Set(ids[2].parse().unwrap()),
+        upn: Set(Some("L801200001003".into())),
+        date_of_birth: Set(Some(format!("{}-10-01", intake - 7).parse().unwrap())),
+        ..Default::default()
+    }
+    .update(ctx.check_db())
+    .await
+    .unwrap();
+    for (id, upn, date_of_birth) in [
+        (ids[0], "H801200001001", format!("{}-10-01", intake - 11)),
+        (ids[1], "X801200001002", format!("{}-11-01", intake - 11)),
+    ] {
+        let res = ctx
+            .client()
+            .post(&format!("{}/{id}", constant::PUPILS_ENDPOINT))
+            .json(&json!({"upn": upn, "date_of_birth": date_of_birth}))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.json::<Value>().await["problems"], json!([]));
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/return"))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/xml");
+    let xml = res.text().await;
+    assert!(xml.contains(&format!("<CensusDate>{census_date}</CensusDate>")));
+    assert!(xml.contains("<UPN>X801200001002</UPN>"));
+    assert!(xml.contains("<FSMeligible>false</FSMeligible>"));
+}
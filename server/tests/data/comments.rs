@@ -0,0 +1,283 @@
+use crate::common::*;
+use entity::comment::Model as Comment;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const OTHER_USER: &str = "other_user@integration.com";
+
+/// Adds a second user with a shared and a private comment on the first pupil.
+async fn add_other_comments(pupil_id: &str, db: &DatabaseConnection) -> Vec<Uuid> {
+    entity::user::Entity::insert(entity::user::ActiveModel::from(entity::user::Model {
+        first_names: "Other".into(),
+        last_name: "User".into(),
+        email_address: OTHER_USER.into(),
+        hashed_password: "password".into(),
+        years: "6".into(),
+        secret: vec![1; 64],
+        last_refresh: "2021-01-01T00:00:00".parse().unwrap(),
+        roles: "".into(),
+    }))
+    .exec(db)
+    .await
+    .expect("insert other user");
+    let comments = vec![
+        Comment {
+            id: Uuid::new_v4(),
+            pupil_id: pupil_id.parse().unwrap(),
+            author: OTHER_USER.into(),
+            category: "pastoral".into(),
+            body: "shared with the year team".into(),
+            visibility: "shared".into(),
+            created_at: "2023-03-01T09:00:00".parse().unwrap(),
+            edited_at: None,
+        },
+        Comment {
+            id: Uuid::new_v4(),
+            pupil_id: pupil_id.parse().unwrap(),
+            author: OTHER_USER.into(),
+            category: "general".into(),
+            body: "only for me".into(),
+            visibility: "private".into(),
+            created_at: "2023-03-02T09:00:00".parse().unwrap(),
+            edited_at: None,
+        },
+    ];
+    let ids = comments.iter().map(|c| c.id).collect();
+    entity::comment::Entity::insert_many(
+        comments
+            .into_iter()
+            .map(entity::comment::ActiveModel::from)
+            .collect::<Vec<_>>(),
+    )
+    .exec(db)
+    .await
+    .expect("insert comments");
+    ids
+}
+
+#[rstest]
+async fn login_and_create_comment(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "academic", "body": "great reading progress"}))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let inserted = &entity::comment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()[0];
+    assert_eq!(inserted.pupil_id.to_string(), ids[0]);
+    assert_eq!(inserted.author, "test_user@integration.com");
+    assert_eq!(inserted.category, "academic");
+    assert_eq!(inserted.body, "great reading progress");
+    assert_eq!(inserted.visibility, "shared");
+    assert_eq!(inserted.edited_at, None);
+}
+
+#[rstest]
+async fn login_and_create_comment_outside_years(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[2]
+        ))
+        .json(&json!({"category": "academic", "body": "year 2 pupil"}))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(entity::comment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+async fn login_and_create_invalid_comment(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "gossip", "body": ""}))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(res_body["fields"]["body"], "comment cannot be empty");
+    assert!(res_body["fields"]["category"].is_string());
+}
+
+#[rstest]
+async fn login_and_get_pupil_comments(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    add_other_comments(ids[0], ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "general", "body": "my private note", "visibility": "private"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res_body = res.json::<Value>().await;
+    let bodies: Vec<&str> = res_body
+        .as_array()
+        .expect("array of comments")
+        .iter()
+        .map(|c| c["body"].as_str().unwrap())
+        .collect();
+    // newest first, and the other user's private comment is hidden
+    assert_eq!(bodies, vec!["my private note", "shared with the year team"]);
+    let res = ctx
+        .client()
+        .get(constant::COMMENTS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.json::<Value>().await.as_array().unwrap().len(), 2);
+}
+
+#[rstest]
+async fn login_and_get_private_comment(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let comment_ids = add_other_comments(ids[0], ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/comments/{}",
+            constant::PUPILS_ENDPOINT,
+            ids[0],
+            comment_ids[1]
+        ))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>().await["error"], "COMMENT DOES NOT EXIST");
+}
+
+#[rstest]
+async fn login_and_edit_comments(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let comment_ids = add_other_comments(ids[0], ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "general", "body": "first draft"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let own_id = res.json::<Value>().await["id"].as_str().unwrap().to_owned();
+    let res = ctx
+        .client()
+        .patch(&format!(
+            "{}/{}/comments/{own_id}",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"body": "second draft"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let edited = entity::comment::Entity::find_by_id(own_id.parse::<Uuid>().unwrap())
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(edited.body, "second draft");
+    assert!(edited.edited_at.is_some());
+    let res = ctx
+        .client()
+        .patch(&format!(
+            "{}/{}/comments/{}",
+            constant::PUPILS_ENDPOINT,
+            ids[0],
+            comment_ids[0]
+        ))
+        .json(&json!({"body": "not mine to change"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .delete(&format!(
+            "{}/{}/comments/{}",
+            constant::PUPILS_ENDPOINT,
+            ids[0],
+            comment_ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .delete(&format!(
+            "{}/{}/comments/{own_id}",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        entity::comment::Entity::find()
+            .all(ctx.check_db())
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,279 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{EntityTrait, QueryOrder};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const DSL_USER: &str = "dsl_user@integration.com";
+
+/// Adds a user with the DSL role and no years, and returns their token.
+async fn login_dsl(ctx: &MockCtx) -> String {
+    entity::user::Entity::insert(entity::user::ActiveModel::from(entity::user::Model {
+        first_names: "Designated".into(),
+        last_name: "Lead".into(),
+        email_address: DSL_USER.into(),
+        hashed_password: "password".into(),
+        years: "".into(),
+        secret: vec![1; 64],
+        last_refresh: "2021-01-01T00:00:00".parse().unwrap(),
+        roles: constant::ROLE_DSL.into(),
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("insert dsl user");
+    let login = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": DSL_USER, "hashed_password": "password"}))
+        .send()
+        .await;
+    assert_eq!(login.status(), StatusCode::OK);
+    login.json::<HashMap<String, String>>().await["token"].to_owned()
+}
+
+async fn raise_concern(ctx: &MockCtx, token: &str, pupil_id: &str) -> String {
+    let res = ctx
+        .client()
+        .put(constant::CONCERNS_ENDPOINT)
+        .json(&json!({
+            "pupil_id": pupil_id,
+            "category": "neglect",
+            "narrative": "came in hungry again",
+            "actions_taken": "gave breakfast"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    res.json::<Value>().await["id"].as_str().unwrap().to_owned()
+}
+
+#[rstest]
+async fn login_and_raise_concern(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    raise_concern(&ctx, &token, ids[0]).await;
+    let inserted = &entity::concern::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()[0];
+    assert_eq!(inserted.pupil_id.to_string(), ids[0]);
+    assert_eq!(inserted.reporter, "test_user@integration.com");
+    assert_eq!(inserted.status, "raised");
+    let res = ctx
+        .client()
+        .put(constant::CONCERNS_ENDPOINT)
+        .json(&json!({"pupil_id": ids[2], "category": "neglect", "narrative": "year 2"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .put(constant::CONCERNS_ENDPOINT)
+        .json(&json!({"pupil_id": ids[0], "category": "gossip", "narrative": " "}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(res_body["fields"]["narrative"], "narrative cannot be empty");
+    assert!(res_body["fields"]["category"].is_string());
+}
+
+#[rstest]
+async fn concerns_hidden_from_other_staff(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let dsl_token = login_dsl(&ctx).await;
+    let id = raise_concern(&ctx, &dsl_token, ids[0]).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .get(&format!("{}/{id}", constant::CONCERNS_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.json::<Value>().await["error"], "CONCERN DOES NOT EXIST");
+    let res = ctx
+        .client()
+        .get(constant::CONCERNS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await.as_array().unwrap().is_empty());
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/concerns",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await.as_array().unwrap().is_empty());
+}
+
+#[rstest]
+async fn dsl_triages_and_closes_concern(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = raise_concern(&ctx, &token, ids[0]).await;
+    let status_url = format!("{}/{id}/status", constant::CONCERNS_ENDPOINT);
+    let res = ctx
+        .client()
+        .post(&status_url)
+        .json(&json!({"status": "triaged"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let dsl_token = login_dsl(&ctx).await;
+    for (status, expected) in [
+        ("closed", StatusCode::BAD_REQUEST),
+        ("triaged", StatusCode::OK),
+        ("closed", StatusCode::OK),
+    ] {
+        let res = ctx
+            .client()
+            .post(&status_url)
+            .json(&json!({"status": status, "outcome": "spoke to parents"}))
+            .header("Authorization", format!("Bearer {dsl_token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), expected);
+    }
+    let res = ctx
+        .client()
+        .patch(&format!("{}/{id}", constant::CONCERNS_ENDPOINT))
+        .json(&json!({"narrative": "changed after closing"}))
+        .header("Authorization", format!("Bearer {dsl_token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/concerns",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let events: Vec<String> = res
+        .json::<Value>()
+        .await
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["event"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(events, vec!["raised", "triaged", "closed"]);
+}
+
+#[rstest]
+async fn concern_reads_are_logged(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = raise_concern(&ctx, &token, ids[0]).await;
+    let res = ctx
+        .client()
+        .put(&format!("{}/{id}/attachments", constant::CONCERNS_ENDPOINT))
+        .json(&json!({"file_name": "note.txt", "content_type": "text/plain", "data": "aGVsbG8="}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let attachment_id = res.json::<Value>().await["id"].as_str().unwrap().to_owned();
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{id}/attachments/{attachment_id}",
+            constant::CONCERNS_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let url = res.json::<Value>().await["url"].as_str().unwrap().to_owned();
+    let res = ctx.client().get(&url).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "text/plain");
+    assert_eq!(res.text().await, "hello");
+    let dsl_token = login_dsl(&ctx).await;
+    let res = ctx
+        .client()
+        .get(&format!("{}/{id}", constant::CONCERNS_ENDPOINT))
+        .header("Authorization", format!("Bearer {dsl_token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let log_url = format!("{}/{id}/log", constant::CONCERNS_ENDPOINT);
+    let res = ctx
+        .client()
+        .get(&log_url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .get(&log_url)
+        .header("Authorization", format!("Bearer {dsl_token}"))
+        .send()
+        .await;
+    let log = res.json::<Value>().await;
+    let actions: Vec<&str> = log["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["create", "upload", "download", "read"]);
+    let check_url = format!("{}/log", constant::CONCERNS_ENDPOINT);
+    let res = ctx
+        .client()
+        .get(&check_url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .get(&check_url)
+        .header("Authorization", format!("Bearer {dsl_token}"))
+        .send()
+        .await;
+    assert_eq!(
+        res.json::<Value>().await,
+        json!({"intact": true, "checked": 4})
+    );
+    // tampering with an earlier entry is detected
+    let first = entity::concern_access::Entity::find()
+        .order_by_asc(entity::concern_access::Column::Id)
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut tampered = entity::concern_access::ActiveModel::from(first);
+    tampered.accessed_by = sea_orm::Set("someone@else.com".into());
+    sea_orm::ActiveModelTrait::update(tampered, ctx.check_db())
+        .await
+        .unwrap();
+    let res = ctx
+        .client()
+        .get(&check_url)
+        .header("Authorization", format!("Bearer {dsl_token}"))
+        .send()
+        .await;
+    assert_eq!(res.json::<Value>().await["intact"], false);
+}
@@ -0,0 +1,143 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+async fn get_contacts(ctx: &MockCtx, token: &str, pupil_id: &str) -> Vec<Value> {
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{pupil_id}/contacts",
+            constant::PUPILS_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Vec<Value>>().await
+}
+
+#[rstest]
+async fn siblings_share_a_contact(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/contacts",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({
+            "first_names": "Jo",
+            "last_name": "Student",
+            "phone": "01234 567890",
+            "relationship": "mother",
+            "priority": 1,
+            "parental_responsibility": true
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let contact_id = res.json::<Value>().await["contact_id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+    let sibling_url = format!(
+        "{}/{}/contacts/{contact_id}",
+        constant::PUPILS_ENDPOINT,
+        ids[1]
+    );
+    let res = ctx
+        .client()
+        .put(&sibling_url)
+        .json(&json!({"relationship": "mother", "priority": 2, "no_contact": true}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = ctx
+        .client()
+        .put(&sibling_url)
+        .json(&json!({
+            "relationship": "mother",
+            "priority": 2,
+            "no_contact": true,
+            "restriction_notes": "prohibited steps order"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = ctx
+        .client()
+        .patch(&sibling_url)
+        .json(&json!({"phone": "07700 900000"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let first = get_contacts(&ctx, &token, ids[0]).await;
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0]["phone"], "07700 900000");
+    assert_eq!(first[0]["no_contact"], false);
+    let second = get_contacts(&ctx, &token, ids[1]).await;
+    assert_eq!(second[0]["no_contact"], true);
+    assert_eq!(second[0]["priority"], 2);
+
+    for pupil_id in [ids[0], ids[1]] {
+        let res = ctx
+            .client()
+            .delete(&format!(
+                "{}/{pupil_id}/contacts/{contact_id}",
+                constant::PUPILS_ENDPOINT
+            ))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    assert!(get_contacts(&ctx, &token, ids[1]).await.is_empty());
+    let remaining = entity::contact::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert!(remaining.is_empty());
+}
+
+#[rstest]
+async fn contacts_follow_year_access(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/contacts",
+            constant::PUPILS_ENDPOINT,
+            ids[2]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/contacts/{}",
+            constant::PUPILS_ENDPOINT,
+            ids[0],
+            uuid::Uuid::new_v4()
+        ))
+        .json(&json!({"relationship": "carer", "priority": 1}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,194 @@
+use crate::common::*;
+use base64::{engine::general_purpose, Engine};
+use http::StatusCode;
+use lt_server::{
+    core::constant,
+    ctf::{schema, xml::Element},
+};
+use rstest::*;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+
+const INCOMING: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CTfile>
+  <Header>
+    <DocumentName>Common Transfer File</DocumentName>
+    <CTFversion>23.0</CTFversion>
+    <DateTime>2023-07-20T10:00:00</DateTime>
+    <DocumentQualifier>full</DocumentQualifier>
+    <SourceSchool><LEA>802</LEA><Estab>3000</Estab><AcademicYear>2022</AcademicYear></SourceSchool>
+    <DestSchool><LEA>000</LEA><Estab>0000</Estab></DestSchool>
+  </Header>
+  <CTFpupilData>
+    <Pupil>
+      <UPN>H801200001001</UPN>
+      <Surname>student</Surname>
+      <Forename>first</Forename>
+      <DOB>2012-05-01</DOB>
+      <Sex>F</Sex>
+      <BasicDetails><NCyearActual>6</NCyearActual></BasicDetails>
+      <FSMhistory><FSMinstance><FSMstartDate>2022-09-01</FSMstartDate></FSMinstance></FSMhistory>
+    </Pupil>
+    <Pupil>
+      <UPN>X801200001002</UPN>
+      <Surname>Pupil</Surname>
+      <Forename>New</Forename>
+      <DOB>2013-02-14</DOB>
+      <Sex>M</Sex>
+      <BasicDetails><NCyearActual>5</NCyearActual></BasicDetails>
+      <Contacts>
+        <Contact>
+          <Order>1</Order>
+          <Surname>Pupil</Surname>
+          <Forename>Sam</Forename>
+          <Gender>M</Gender>
+          <Relationship>PAR</Relationship>
+          <Responsibility>true</Responsibility>
+          <Phones><Phone><TelephoneType>M</TelephoneType><PhoneNo>07700 900123</PhoneNo></Phone></Phones>
+        </Contact>
+      </Contacts>
+      <Attendance>
+        <YearData><Year>2022</Year><SessionsPossible>320</SessionsPossible><SessionsAuthorised>6</SessionsAuthorised><SessionsUnauthorised>0</SessionsUnauthorised></YearData>
+      </Attendance>
+    </Pupil>
+  </CTFpupilData>
+</CTfile>"#;
+
+async fn import(ctx: &MockCtx, token: &str, dry_run: bool) -> Value {
+    let res = ctx
+        .client()
+        .post(&format!("{}/ctf", constant::PUPILS_ENDPOINT))
+        .json(&json!({
+            "data": general_purpose::STANDARD.encode(INCOMING),
+            "start_date": "2023-09-04",
+            "dry_run": dry_run
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await
+}
+
+#[rstest]
+async fn leaver_ctf_passes_the_schema(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"upn": "H801200001001", "date_of_birth": "2012-05-01"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/contacts",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({
+            "first_names": "Jo",
+            "last_name": "Student",
+            "phone": "01234 567890",
+            "relationship": "mother",
+            "priority": 1,
+            "parental_responsibility": true
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/ctf?dest_lea=802&dest_estab=3000",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/xml");
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"0000000_CTF_8023000_001.xml\""
+    );
+    let document = Element::parse(res.text().await.as_bytes()).unwrap();
+    assert!(schema::validate(&document).is_empty());
+    let pupil = document.find(&["CTFpupilData", "Pupil"]).unwrap();
+    assert_eq!(pupil.value("UPN").as_deref(), Some("H801200001001"));
+    let contact = pupil.find(&["Contacts", "Contact"]).unwrap();
+    assert_eq!(contact.value("Relationship").as_deref(), Some("PAR"));
+
+    // a pupil with no UPN can't have a valid CTF, so there's nothing to download
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}/ctf", constant::PUPILS_ENDPOINT, ids[1]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+async fn ctf_import_previews_matches_then_commits(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"date_of_birth": "2012-05-01"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let report = import(&ctx, &token, true).await;
+    assert_eq!(report["source"]["lea"], "802");
+    assert_eq!(report["creates"], 1);
+    assert_eq!(report["updates"], 1);
+    assert_eq!(report["committed"], false);
+    let first = &report["pupils"][0];
+    assert_eq!(first["matched"]["kind"], "name_and_date_of_birth");
+    assert_eq!(first["matched"]["pupil_id"], ids[0]);
+    assert_eq!(first["changes"], json!(["upn", "free_school_meals"]));
+    let second = &report["pupils"][1];
+    assert_eq!(second["matched"]["kind"], "none");
+    assert_eq!(second["contacts"][0]["relationship"], "father");
+    assert_eq!(second["attendance"][0]["sessions_possible"], 320);
+    let found = entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Upn.eq("X801200001002"))
+        .one(ctx.check_db())
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let report = import(&ctx, &token, false).await;
+    assert_eq!(report["committed"], true);
+    let created = entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Upn.eq("X801200001002"))
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(created.year, 5);
+    assert_eq!(created.start_date, "2023-09-04".parse().unwrap());
+    let contacts = entity::pupil_contact::Entity::find()
+        .filter(entity::pupil_contact::Column::PupilId.eq(created.id))
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert_eq!(contacts.len(), 1);
+
+    // the same file again now matches both pupils on UPN and changes nothing
+    let report = import(&ctx, &token, true).await;
+    assert_eq!(report["unchanged"], 2);
+    assert_eq!(report["pupils"][1]["matched"]["kind"], "upn");
+}
@@ -0,0 +1,158 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::{json, Value};
+
+async fn framework(ctx: &MockCtx, token: &str) -> Value {
+    let res = ctx
+        .client()
+        .get(&format!("{}/framework", constant::CURRICULUM_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await
+}
+
+#[rstest]
+async fn framework_is_seeded_and_only_admins_change_it(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let framework = framework(&ctx, &token).await;
+    let areas = framework["areas"].as_array().unwrap();
+    assert_eq!(areas.len(), 6);
+    assert_eq!(areas[0]["name"], "Expressive Arts");
+    assert_eq!(areas[0]["what_matters"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        areas
+            .iter()
+            .map(|area| area["what_matters"].as_array().unwrap().len())
+            .sum::<usize>(),
+        27
+    );
+    assert_eq!(framework["steps"].as_array().unwrap().len(), 5);
+    assert_eq!(framework["steps"][2]["typical_age"], 11);
+    let res = ctx
+        .client()
+        .put(&format!("{}/areas", constant::CURRICULUM_ENDPOINT))
+        .json(&json!({"name": "Welsh", "position": 6}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+async fn judgements_make_up_progression(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let framework = framework(&ctx, &token).await;
+    let numbers = framework["areas"][4]["what_matters"][0]["id"].clone();
+    let algebra = framework["areas"][4]["what_matters"][1]["id"].clone();
+    let url = format!("{}/{}/progression", constant::PUPILS_ENDPOINT, ids[0]);
+    let mut judgement_ids = vec![];
+    for (statement, step, date) in [
+        (&numbers, 2, "2022-10-01"),
+        (&numbers, 3, "2023-03-01"),
+        (&algebra, 2, "2023-03-01"),
+    ] {
+        let res = ctx
+            .client()
+            .put(&url)
+            .json(&json!({"what_matters_id": statement, "step": step, "date": date}))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        judgement_ids.push(res.json::<Value>().await["id"].as_str().unwrap().to_owned());
+    }
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let progression = res.json::<Value>().await;
+    assert_eq!(progression["history"].as_array().unwrap().len(), 3);
+    assert_eq!(progression["current"][numbers.as_str().unwrap()]["step"], 3);
+    assert_eq!(progression["current"][algebra.as_str().unwrap()]["step"], 2);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/overview/6", constant::CURRICULUM_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let overview = res.json::<Value>().await;
+    assert_eq!(overview["what_matters"].as_array().unwrap().len(), 27);
+    assert_eq!(overview["rows"][0]["first_names"], "first");
+    assert_eq!(overview["rows"][0]["steps"][numbers.as_str().unwrap()], 3);
+    assert_eq!(overview["rows"][1]["first_names"], "second");
+    assert_eq!(overview["rows"][1]["steps"], json!({}));
+
+    let res = ctx
+        .client()
+        .delete(&format!("{url}/{}", judgement_ids[1]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(
+        res.json::<Value>().await["current"][numbers.as_str().unwrap()]["step"],
+        2
+    );
+}
+
+#[rstest]
+async fn judgements_are_checked(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let framework = framework(&ctx, &token).await;
+    let statement = framework["areas"][0]["what_matters"][0]["id"].clone();
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/progression",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"what_matters_id": statement, "step": 9, "date": "2999-01-01"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let fields = &res.json::<Value>().await["fields"];
+    assert_eq!(fields["step"], "9 is not a progression step");
+    assert_eq!(fields["date"], "date cannot be in the future");
+
+    // year 2 isn't one of the test user's years
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/progression",
+            constant::PUPILS_ENDPOINT,
+            ids[2]
+        ))
+        .json(&json!({"what_matters_id": statement, "step": 1, "date": "2023-01-01"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = ctx
+        .client()
+        .get(&format!("{}/overview/2", constant::CURRICULUM_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,110 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::{json, Value};
+
+async fn get_pupil(ctx: &MockCtx, token: &str, pupil_id: &str) -> Value {
+    let res = ctx
+        .client()
+        .get(&format!("{}/{pupil_id}", constant::PUPILS_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await
+}
+
+#[rstest]
+async fn latest_assessment_gives_the_stage(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/{}/eal", constant::PUPILS_ENDPOINT, ids[0]);
+    assert_eq!(
+        get_pupil(&ctx, &token, ids[0]).await["english_as_additional_language"],
+        false
+    );
+
+    let mut created = Vec::new();
+    // the back-dated assessment doesn't replace the latest one's home language
+    for (stage, language, assessed_on) in [
+        ("C", "Polish", "2023-01-09"),
+        ("A", "Ukrainian", "2022-09-05"),
+    ] {
+        let res = ctx
+            .client()
+            .put(&url)
+            .json(&json!({"stage": stage, "home_language": language, "assessed_on": assessed_on}))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        created.push(res.json::<Value>().await["id"].as_str().unwrap().to_owned());
+    }
+    let pupil = get_pupil(&ctx, &token, ids[0]).await;
+    assert_eq!(pupil["english_as_additional_language"], true);
+    assert_eq!(pupil["eal_stage"], "C");
+    assert_eq!(pupil["home_language"], "Polish");
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let assessments = res.json::<Vec<Value>>().await;
+    assert_eq!(assessments.len(), 2);
+    assert_eq!(assessments[0]["stage"], "C");
+
+    for (id, language) in created.iter().zip([json!("Ukrainian"), Value::Null]) {
+        let res = ctx
+            .client()
+            .delete(&format!("{url}/{id}"))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let pupil = get_pupil(&ctx, &token, ids[0]).await;
+        assert_eq!(
+            pupil.get("home_language").unwrap_or(&Value::Null),
+            &language
+        );
+    }
+    let pupil = get_pupil(&ctx, &token, ids[0]).await;
+    assert_eq!(pupil["english_as_additional_language"], false);
+    assert!(pupil.get("eal_stage").is_none());
+}
+
+#[rstest]
+async fn assessments_are_checked(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    for (pupil_id, body, status) in [
+        (
+            ids[0],
+            json!({"stage": "B", "assessed_on": "2999-01-01"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            ids[0],
+            json!({"stage": "F", "assessed_on": "2023-01-01"}),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            ids[2],
+            json!({"stage": "B", "assessed_on": "2023-01-01"}),
+            StatusCode::UNAUTHORIZED,
+        ),
+    ] {
+        let res = ctx
+            .client()
+            .put(&format!("{}/{pupil_id}/eal", constant::PUPILS_ENDPOINT))
+            .json(&body)
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), status);
+    }
+}
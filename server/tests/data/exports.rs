@@ -0,0 +1,65 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::json;
+
+#[rstest]
+async fn export_follows_filters_and_year_access(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[1]))
+        .json(&json!({"upn": "X801200001002"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // the year 2 pupil is outside the user's years so never appears
+    let res = ctx
+        .client()
+        .post(&format!("{}/export", constant::PUPILS_ENDPOINT))
+        .json(&json!({
+            "format": "csv",
+            "columns": ["upn", "first_names", "year", "free_school_meals"]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "text/csv; charset=utf-8");
+    assert_eq!(
+        res.text().await,
+        "UPN,First names,Year,FSM\n,first,6,N\nX801200001002,second,6,N\n"
+    );
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/export", constant::PUPILS_ENDPOINT))
+        .json(&json!({
+            "format": "csv",
+            "filters": ["active", {"name": "second"}, {"as_at": "2022-01-01"}],
+            "columns": ["upn"]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await, "UPN\nX801200001002\n");
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/export", constant::PUPILS_ENDPOINT))
+        .json(&json!({"format": "xlsx", "filters": [{"year": 2}]}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .ends_with(".xlsx\""));
+}
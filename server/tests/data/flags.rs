@@ -0,0 +1,175 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::{json, Value};
+
+async fn get_pupil_as_at(ctx: &MockCtx, token: &str, pupil_id: &str, as_at: &str) -> Value {
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{pupil_id}?as_at={as_at}",
+            constant::PUPILS_ENDPOINT
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await
+}
+
+#[rstest]
+async fn flags_are_worked_out_as_at_a_date(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/{}/flags", constant::PUPILS_ENDPOINT, ids[0]);
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({
+            "flag": "fsm",
+            "start_date": "2022-09-01",
+            "end_date": "2022-12-31",
+            "source": "parent declaration"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let period = res.json::<Value>().await;
+
+    for (as_at, expected) in [
+        ("2022-08-31", false),
+        ("2022-10-01", true),
+        ("2023-01-01", false),
+    ] {
+        let pupil = get_pupil_as_at(&ctx, &token, ids[0], as_at).await;
+        assert_eq!(pupil["free_school_meals"], expected, "as at {as_at}");
+    }
+
+    let res = ctx
+        .client()
+        .get(&format!("{}?as_at=2022-10-01", constant::PUPILS_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let pupils = res.json::<Vec<Value>>().await;
+    let on: Vec<&Value> = pupils
+        .iter()
+        .filter(|p| p["free_school_meals"] == true)
+        .map(|p| &p["id"])
+        .collect();
+    assert_eq!(on, [ids[0]]);
+
+    let res = ctx
+        .client()
+        .delete(&format!("{url}/{}", period["id"].as_str().unwrap()))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let pupil = get_pupil_as_at(&ctx, &token, ids[0], "2022-10-01").await;
+    assert_eq!(pupil["free_school_meals"], false);
+}
+
+#[rstest]
+async fn editing_the_pupil_opens_and_closes_periods(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let pupil_url = format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]);
+    let res = ctx
+        .client()
+        .post(&pupil_url)
+        .json(&json!({"looked_after_child": true}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = ctx
+        .client()
+        .get(&format!("{pupil_url}/flags"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let periods = res.json::<Vec<Value>>().await;
+    assert_eq!(periods.len(), 1);
+    assert_eq!(periods[0]["flag"], "lac");
+    assert_eq!(periods[0]["source"], "pupil record");
+    assert!(periods[0].get("end_date").is_none());
+    let pupil = get_pupil_as_at(&ctx, &token, ids[0], "2020-01-01").await;
+    assert_eq!(pupil["looked_after_child"], false);
+
+    // turned off the same day it was turned on, so nothing is left
+    let res = ctx
+        .client()
+        .post(&pupil_url)
+        .json(&json!({"looked_after_child": false}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&format!("{pupil_url}/flags"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Vec<Value>>().await.is_empty());
+}
+
+#[rstest]
+async fn periods_are_checked(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(&format!("{}/{}/flags", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"flag": "mat", "start_date": "2022-09-01", "source": "teacher"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    for (pupil_id, body, status) in [
+        (
+            ids[0],
+            json!({"flag": "mat", "start_date": "2023-01-01", "source": "teacher"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            ids[0],
+            json!({"flag": "fsm", "start_date": "2023-01-01", "end_date": "2022-01-01", "source": "LA"}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            ids[0],
+            json!({"flag": "lac", "start_date": "2023-01-01", "source": " "}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            ids[0],
+            json!({"flag": "gifted", "start_date": "2023-01-01", "source": "teacher"}),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            ids[2],
+            json!({"flag": "fsm", "start_date": "2023-01-01", "source": "LA"}),
+            StatusCode::UNAUTHORIZED,
+        ),
+    ] {
+        let res = ctx
+            .client()
+            .put(&format!("{}/{pupil_id}/flags", constant::PUPILS_ENDPOINT))
+            .json(&body)
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), status, "{body}");
+    }
+}
@@ -0,0 +1,117 @@
+use crate::common::*;
+use base64::{engine::general_purpose, Engine};
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+
+const HEADINGS: &str = "UPN,Forename,Surname,Year,Gender,Admission date,FSM";
+
+async fn import(ctx: &MockCtx, token: &str, csv: &str, dry_run: bool) -> Value {
+    let res = ctx
+        .client()
+        .post(&format!("{}/import", constant::PUPILS_ENDPOINT))
+        .json(&json!({
+            "format": "csv",
+            "data": general_purpose::STANDARD.encode(csv),
+            "dry_run": dry_run
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json::<Value>().await
+}
+
+#[rstest]
+async fn import_previews_then_commits_once(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"upn": "H801200001001"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let rows = [
+        "H801200001001,first,student,6,F,2021-01-01,Y",
+        "X801200001002,New,Pupil,Year 6,M,05/09/2022,N",
+    ];
+    let with_error = format!(
+        "{HEADINGS}\n{}\n{}\nL801200001003,Other,Year,2,M,2022-09-05,N\n",
+        rows[0], rows[1]
+    );
+    let report = import(&ctx, &token, &with_error, true).await;
+    assert_eq!(report["creates"], 1);
+    assert_eq!(report["updates"], 1);
+    assert_eq!(report["errors"], 1);
+    assert_eq!(report["committed"], false);
+    assert_eq!(report["rows"][0]["changes"], json!(["free_school_meals"]));
+    assert_eq!(report["rows"][2]["row"], 4);
+    assert!(report["rows"][2]["errors"]["year"].is_string());
+    assert_eq!(report["mapping"]["start_date"], "Admission date");
+
+    // a file with errors isn't saved even when it isn't a dry run
+    let report = import(&ctx, &token, &with_error, false).await;
+    assert_eq!(report["committed"], false);
+    let found = entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Upn.eq("X801200001002"))
+        .one(ctx.check_db())
+        .await
+        .unwrap();
+    assert!(found.is_none());
+
+    let clean = format!("{HEADINGS}\n{}\n{}\n", rows[0], rows[1]);
+    let report = import(&ctx, &token, &clean, false).await;
+    assert_eq!(report["committed"], true);
+    let created = entity::pupil::Entity::find()
+        .filter(entity::pupil::Column::Upn.eq("X801200001002"))
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(created.gender, "male");
+    assert_eq!(created.start_date, "2022-09-05".parse().unwrap());
+    let updated = entity::pupil::Entity::find_by_id(ids[0].parse::<uuid::Uuid>().unwrap())
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(updated.free_school_meals);
+
+    let report = import(&ctx, &token, &clean, false).await;
+    assert_eq!(report["unchanged"], 2);
+    assert_eq!(report["creates"], 0);
+    assert_eq!(report["updates"], 0);
+}
+
+#[rstest]
+async fn import_needs_the_required_columns(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let report = import(
+        &ctx,
+        &token,
+        "UPN,Forename,Surname,Year\nX801200001002,New,Pupil,6\n",
+        true,
+    )
+    .await;
+    assert_eq!(report["missing"], json!(["gender", "start_date"]));
+    assert_eq!(report["rows"], json!([]));
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/import", constant::PUPILS_ENDPOINT))
+        .json(
+            &json!({"format": "xlsx", "data": general_purpose::STANDARD.encode("not a workbook")}),
+        )
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,172 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::{json, Value};
+
+async fn create_intervention(ctx: &MockCtx, token: &str) -> String {
+    let res = ctx
+        .client()
+        .put(constant::INTERVENTIONS_ENDPOINT)
+        .json(&json!({
+            "name": "Reading catch up",
+            "lead_staff": "test_user@integration.com",
+            "start_date": "2023-01-09",
+            "measure": "reading age in months"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    res.json::<Value>().await["id"].as_str().unwrap().to_owned()
+}
+
+#[rstest]
+async fn members_are_flagged_as_in_an_intervention(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let id = create_intervention(&ctx, &token).await;
+    let url = format!("{}/{id}", constant::INTERVENTIONS_ENDPOINT);
+    let res = ctx
+        .client()
+        .put(&format!("{url}/members"))
+        .json(&json!({"pupil_id": ids[0], "entry_measure": 84}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let details = res.json::<Value>().await;
+    assert_eq!(details["members"].as_array().unwrap().len(), 1);
+    assert_eq!(details["members"][0]["first_names"], "first");
+    assert_eq!(details["members"][0]["entry_measure"], 84);
+
+    let res = ctx
+        .client()
+        .put(&format!("{url}/sessions"))
+        .json(&json!({"date": "2023-01-10", "duration_minutes": 30, "notes": "phonics"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = ctx
+        .client()
+        .get(constant::PUPILS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let pupils = res.json::<Vec<Value>>().await;
+    assert_eq!(pupils[0]["in_intervention"], true);
+    assert_eq!(pupils[1]["in_intervention"], false);
+
+    let res = ctx
+        .client()
+        .get(&format!(
+            "{}/{}/interventions",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let interventions = res.json::<Vec<Value>>().await;
+    assert_eq!(interventions.len(), 1);
+    assert_eq!(interventions[0]["active"], true);
+
+    // ending the intervention takes the flag away
+    let res = ctx
+        .client()
+        .patch(&url)
+        .json(&json!({"end_date": "2023-03-31"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.json::<Value>().await["in_intervention"], false);
+
+    // year 2 isn't one of the test user's years
+    let res = ctx
+        .client()
+        .put(&format!("{url}/members"))
+        .json(&json!({"pupil_id": ids[2]}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[rstest]
+async fn targets_are_set_and_reviewed(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/{}/targets", constant::PUPILS_ENDPOINT, ids[1]);
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({
+            "description": "",
+            "success_criteria": "Three times in a row",
+            "review_date": "2000-01-01"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let fields = &res.json::<Value>().await["fields"];
+    assert_eq!(fields["description"], "target cannot be empty");
+    assert_eq!(
+        fields["review_date"],
+        "review date cannot be before the target was set"
+    );
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({
+            "description": "Read a page aloud without help",
+            "success_criteria": "Three times in a row",
+            "review_date": "2999-01-01"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let target = res.json::<Value>().await;
+    assert_eq!(target["set_by"], "test_user@integration.com");
+    let target_id = target["id"].as_str().unwrap();
+
+    let res = ctx
+        .client()
+        .patch(&format!("{url}/{target_id}"))
+        .json(&json!({"outcome": "achieved", "review_notes": "well done"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let target = res.json::<Value>().await;
+    assert_eq!(target["outcome"], "achieved");
+    assert!(target["reviewed_on"].is_string());
+
+    let res = ctx
+        .client()
+        .delete(&format!("{url}/{target_id}"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Vec<Value>>().await.is_empty());
+}
@@ -0,0 +1,168 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const MEDICAL_USER: &str = "medical_user@integration.com";
+
+/// Adds a user with the medical role in the test user's years, and returns their token.
+async fn login_medical(ctx: &MockCtx) -> String {
+    entity::user::Entity::insert(entity::user::ActiveModel::from(entity::user::Model {
+        first_names: "School".into(),
+        last_name: "Nurse".into(),
+        email_address: MEDICAL_USER.into(),
+        hashed_password: "password".into(),
+        years: "5,6".into(),
+        secret: vec![3; 64],
+        last_refresh: "2021-01-01T00:00:00".parse().unwrap(),
+        roles: constant::ROLE_MEDICAL.into(),
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("insert medical user");
+    let login = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": MEDICAL_USER, "hashed_password": "password"}))
+        .send()
+        .await;
+    assert_eq!(login.status(), StatusCode::OK);
+    login.json::<HashMap<String, String>>().await["token"].to_owned()
+}
+
+#[rstest]
+async fn medical_record_needs_the_medical_role(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let nurse = login_medical(&ctx).await;
+    let url = format!("{}/{}/medical", constant::PUPILS_ENDPOINT, ids[0]);
+    let allergy = json!({"kind": "allergy", "name": "peanuts", "severity": "anaphylaxis"});
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&allergy)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({"kind": "allergy", "name": "peanuts"}))
+        .header("Authorization", format!("Bearer {nurse}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&allergy)
+        .header("Authorization", format!("Bearer {nurse}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let item = res.json::<Value>().await;
+    assert_eq!(item["updated_by"], MEDICAL_USER);
+
+    let res = ctx
+        .client()
+        .patch(&format!("{url}/{}", item["id"].as_str().unwrap()))
+        .json(&json!({"details": "adrenaline pen in the office"}))
+        .header("Authorization", format!("Bearer {nurse}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {nurse}"))
+        .send()
+        .await;
+    let record = res.json::<Vec<Value>>().await;
+    assert_eq!(record.len(), 1);
+    assert_eq!(record[0]["details"], "adrenaline pen in the office");
+    assert_eq!(record[0]["severity"], "anaphylaxis");
+}
+
+#[rstest]
+async fn alerts_and_allergy_report_are_shown_to_everyone(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let nurse = login_medical(&ctx).await;
+    for (pupil_id, item) in [
+        (
+            ids[0],
+            json!({"kind": "allergy", "name": "sesame", "severity": "mild"}),
+        ),
+        (
+            ids[1],
+            json!({"kind": "allergy", "name": "peanuts", "severity": "anaphylaxis"}),
+        ),
+        (
+            ids[1],
+            json!({"kind": "medication", "name": "adrenaline pen", "carried": true}),
+        ),
+        (ids[1], json!({"kind": "diet", "name": "vegetarian"})),
+        (ids[1], json!({"kind": "condition", "name": "asthma"})),
+    ] {
+        let res = ctx
+            .client()
+            .put(&format!("{}/{pupil_id}/medical", constant::PUPILS_ENDPOINT))
+            .json(&item)
+            .header("Authorization", format!("Bearer {nurse}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[1]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let pupil = res.json::<Value>().await;
+    assert_eq!(pupil["has_allergy"], true);
+    assert_eq!(pupil["carries_medication"], true);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/allergies/6", constant::MEDICAL_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let report = res.json::<Vec<Value>>().await;
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0]["first_names"], "second");
+    assert_eq!(report[0]["allergies"][0]["name"], "peanuts");
+    assert_eq!(report[0]["diets"], json!(["vegetarian"]));
+    assert_eq!(report[0]["carries_medication"], true);
+    assert_eq!(report[1]["first_names"], "first");
+    assert_eq!(report[1]["carries_medication"], false);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/allergies/2", constant::MEDICAL_ENDPOINT))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
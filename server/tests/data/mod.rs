@@ -1,2 +1,23 @@
+pub mod aln;
+pub mod analytics;
+pub mod assessments;
+pub mod attachments;
+pub mod attendance;
+pub mod census;
+pub mod comments;
+pub mod concerns;
+pub mod contacts;
+pub mod ctf;
+pub mod curriculum;
+pub mod eal;
+pub mod exports;
+pub mod flags;
+pub mod imports;
+pub mod interventions;
+pub mod medical;
+pub mod photos;
 pub mod pupils;
+pub mod quality;
+pub mod reports;
+pub mod subject_access;
 pub mod users;
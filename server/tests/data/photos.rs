@@ -0,0 +1,136 @@
+use crate::common::*;
+use base64::{engine::general_purpose, Engine};
+use http::StatusCode;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use lt_server::core::constant;
+use rstest::*;
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+fn photo() -> String {
+    let mut out = Vec::new();
+    DynamicImage::ImageRgb8(RgbImage::new(640, 480))
+        .write_to(&mut Cursor::new(&mut out), ImageOutputFormat::Jpeg(90))
+        .unwrap();
+    general_purpose::STANDARD.encode(out)
+}
+
+#[rstest]
+async fn photos_show_in_lists_until_consent_is_withdrawn(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let url = format!("{}/{}/photo", constant::PUPILS_ENDPOINT, ids[0]);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({ "data": "JVBERi0xLjQgdGVzdA==" }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .put(&format!("{}/{}/photo", constant::PUPILS_ENDPOINT, ids[2]))
+        .json(&json!({ "data": photo() }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .put(&url)
+        .json(&json!({ "data": photo() }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(res.json::<Value>().await["consent_withdrawn"], false);
+
+    let res = ctx
+        .client()
+        .get(constant::PUPILS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let pupils = res.json::<Vec<Value>>().await;
+    let id = ids[0].to_string();
+    let pupil = pupils.iter().find(|p| p["id"] == id).unwrap();
+    let thumbnail = pupil["photo"]["thumbnail"].as_str().unwrap().to_owned();
+    let medium = pupil["photo"]["medium"].as_str().unwrap().to_owned();
+    assert!(pupils
+        .iter()
+        .filter(|p| p["id"] != id)
+        .all(|p| p.get("photo").is_none()));
+
+    let res = ctx.client().get(&thumbnail).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "image/jpeg");
+    let res = ctx.client().get(&medium).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let medium_photo = image::load_from_memory(&res.bytes().await).unwrap();
+    assert_eq!((medium_photo.width(), medium_photo.height()), (480, 360));
+
+    // a thumbnail link can't be used for the medium photo
+    let swapped = thumbnail.replace("/thumbnail?", "/medium?");
+    let res = ctx.client().get(&swapped).send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .post(&format!("{url}/consent"))
+        .json(&json!({ "withdrawn": true }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let photo = res.json::<Value>().await;
+    assert_eq!(photo["consent_withdrawn"], true);
+    assert!(photo.get("links").is_none());
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await.get("photo").is_none());
+    let res = ctx.client().get(&thumbnail).send().await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = ctx
+        .client()
+        .post(&format!("{url}/consent"))
+        .json(&json!({ "withdrawn": false }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await.get("links").is_some());
+
+    let res = ctx
+        .client()
+        .delete(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    let photo = res.json::<Value>().await;
+    assert!(photo.get("uploaded_at").is_none());
+    assert!(photo.get("links").is_none());
+    let res = ctx
+        .client()
+        .delete(&url)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -81,26 +81,34 @@ async fn login_and_get_pupils(#[future] mock_ctx: MockCtx) {
             "last_name": "student",
             "year": 6,
             "start_date": "2021-01-01",
-            "gender": "gender",
+            "gender": "female",
             "more_able_and_talented": false,
             "english_as_additional_language": false,
             "free_school_meals": false,
             "additional_learning_needs": false,
             "looked_after_child": false,
-            "active": true
+            "active": true,
+            "persistent_absence": false,
+            "in_intervention": false,
+            "has_allergy": false,
+            "carries_medication": false
         }),
         json!({
             "first_names": "second",
             "last_name": "student",
             "year": 6,
             "start_date": "2021-01-01",
-            "gender": "gender",
+            "gender": "female",
             "more_able_and_talented": false,
             "english_as_additional_language": false,
             "free_school_meals": false,
             "additional_learning_needs": false,
             "looked_after_child": false,
-            "active": true
+            "active": true,
+            "persistent_absence": false,
+            "in_intervention": false,
+            "has_allergy": false,
+            "carries_medication": false
         }),
     ];
     assert_eq!(*pupils, exp_pupils);
@@ -116,7 +124,7 @@ async fn login_and_get_pupil_by_id(#[future] mock_ctx: MockCtx) {
         last_name: "student".into(),
         start_date: "2021-01-01".parse().unwrap(),
         end_date: Some("2027-01-01".parse().unwrap()),
-        gender: "gender".into(),
+        gender: "female".into(),
         year: 6,
         ..Default::default()
     }];
@@ -145,14 +153,18 @@ async fn login_and_get_pupil_by_id(#[future] mock_ctx: MockCtx) {
             "last_name": "student",
             "start_date": "2021-01-01",
             "end_date": "2027-01-01",
-            "gender": "gender",
+            "gender": "female",
             "year": 6,
             "active": false,
             "more_able_and_talented": false,
             "english_as_additional_language": false,
             "free_school_meals": false,
             "additional_learning_needs": false,
-            "looked_after_child": false
+            "looked_after_child": false,
+            "persistent_absence": false,
+            "in_intervention": false,
+            "has_allergy": false,
+            "carries_medication": false
         })
     );
 }
@@ -187,7 +199,7 @@ async fn login_and_update_pupil(#[future] mock_ctx: MockCtx) {
             last_name: "newname".into(),
             start_date: "2021-01-01".parse().unwrap(),
             end_date: None,
-            gender: "gender".into(),
+            gender: "female".into(),
             year: 6,
             active: true,
             looked_after_child: true,
@@ -215,3 +227,171 @@ async fn login_and_delete_pupil(#[future] mock_ctx: MockCtx) {
             .expect("successful query");
     assert_eq!(updated_pupil, None);
 }
+
+#[rstest]
+async fn login_and_patch_pupil_clears_end_date(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let id = Uuid::new_v4();
+    entity::pupil::Entity::insert(entity::pupil::ActiveModel::from(Pupil {
+        id,
+        first_names: "first".into(),
+        last_name: "student".into(),
+        start_date: "2021-01-01".parse().unwrap(),
+        end_date: Some("2022-07-21".parse().unwrap()),
+        gender: "female".into(),
+        year: 6,
+        active: false,
+        ..Default::default()
+    }))
+    .exec(ctx.check_db())
+    .await
+    .expect("adding pupil");
+    let res = ctx
+        .client()
+        .patch(&format!("{}/{id}", constant::PUPILS_ENDPOINT))
+        .header("Content-Type", "application/merge-patch+json")
+        .body(r#"{"end_date": null, "active": true}"#)
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let updated_pupil = entity::pupil::Entity::find_by_id(id)
+        .one(ctx.check_db())
+        .await
+        .expect("successful query")
+        .expect("found updated pupil");
+    assert_eq!(updated_pupil.end_date, None);
+    assert!(updated_pupil.active);
+}
+
+#[rstest]
+async fn login_and_patch_pupil_with_null_name(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .patch(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"last_name": null}))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(res_body["details"], "last_name cannot be null");
+}
+
+#[rstest]
+async fn login_and_create_invalid_pupil(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let res = ctx
+        .client()
+        .put(constant::PUPILS_ENDPOINT)
+        .json(&json!({
+            "first_names": "",
+            "last_name": "last",
+            "year": -1,
+            "start_date": "2022-01-01",
+            "end_date": "2021-01-01",
+            "gender": "male",
+            "more_able_and_talented": false,
+            "english_as_additional_language": false,
+            "free_school_meals": false,
+            "additional_learning_needs": false,
+            "looked_after_child": false,
+            "active": false
+        }))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(res_body["error"], "VALIDATION ERROR");
+    assert_eq!(
+        res_body["fields"],
+        json!({
+            "first_names": "first names cannot be empty",
+            "year": "year must be between 0 and 6",
+            "end_date": "leave date cannot be before start date"
+        })
+    );
+    assert!(entity::pupil::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+async fn login_and_update_pupil_inconsistently(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let res = ctx
+        .client()
+        .patch(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({"end_date": "2022-07-21"}))
+        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(
+        res_body["fields"]["end_date"],
+        "an active pupil cannot have a leave date"
+    );
+}
+
+#[rstest]
+async fn login_and_create_pupils_with_identity(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let pupil = json!({
+        "first_names": "Ben",
+        "last_name": "Jones",
+        "preferred_first_names": "Benji",
+        "year": 6,
+        "start_date": "2017-09-01",
+        "date_of_birth": "2013-02-14",
+        "upn": "H801200001001",
+        "home_language": "Welsh",
+        "gender": "male",
+        "more_able_and_talented": false,
+        "english_as_additional_language": false,
+        "free_school_meals": false,
+        "additional_learning_needs": false,
+        "looked_after_child": false,
+        "active": true
+    });
+    let res = ctx
+        .client()
+        .put(constant::PUPILS_ENDPOINT)
+        .json(&pupil)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let inserted = &entity::pupil::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap()[0];
+    assert_eq!(inserted.date_of_birth, Some("2013-02-14".parse().unwrap()));
+    assert_eq!(inserted.upn, Some("H801200001001".into()));
+    assert_eq!(inserted.preferred_first_names, Some("Benji".into()));
+    assert_eq!(inserted.preferred_last_name, None);
+    assert_eq!(inserted.home_language, Some("Welsh".into()));
+
+    let mut second = pupil.clone();
+    second["date_of_birth"] = json!("2013-06-30");
+    let res = ctx
+        .client()
+        .put(constant::PUPILS_ENDPOINT)
+        .json(&second)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res_body = res.json::<Value>().await;
+    assert_eq!(
+        res_body["fields"]["upn"],
+        "UPN already belongs to another pupil"
+    );
+}
@@ -0,0 +1,184 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+#[rstest]
+async fn duplicate_pupils_are_found_and_merged(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[1]))
+        .json(&json!({"first_names": "First", "last_name": "Student", "upn": "H801200001001"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[1]
+        ))
+        .json(&json!({"category": "general", "body": "joined from another school"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let res = ctx
+        .client()
+        .get(constant::QUALITY_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .get(constant::QUALITY_ENDPOINT)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let report = res.json::<Value>().await;
+    assert_eq!(report["checked_pupils"], 2);
+    assert_eq!(report["pupil_duplicates"].as_array().unwrap().len(), 1);
+    assert_eq!(report["pupil_duplicates"][0]["first"]["id"], ids[0]);
+    assert_eq!(report["pupil_duplicates"][0]["second"]["id"], ids[1]);
+    assert_eq!(
+        report["pupil_duplicates"][0]["reasons"],
+        json!(["same name", "started on the same day"])
+    );
+
+    let merge = json!({"keep": ids[0], "remove": ids[1], "take": ["upn"], "dry_run": true});
+    let res = ctx
+        .client()
+        .post(&format!("{}/merge", constant::QUALITY_ENDPOINT))
+        .json(&merge)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let preview = res.json::<Value>().await;
+    assert_eq!(preview["committed"], false);
+    assert_eq!(preview["merged"]["upn"], "H801200001001");
+    assert_eq!(preview["differences"][0]["field"], "first_names");
+    assert_eq!(preview["linked"][0]["table"], "comments");
+    assert_eq!(preview["linked"][0]["moved"], 1);
+    let comments = entity::comment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert_eq!(comments[0].pupil_id.to_string(), ids[1]);
+
+    let mut merge = merge;
+    merge["dry_run"] = json!(false);
+    let res = ctx
+        .client()
+        .post(&format!("{}/merge", constant::QUALITY_ENDPOINT))
+        .json(&merge)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let merged = res.json::<Value>().await;
+    assert_eq!(merged["committed"], true);
+    assert_eq!(merged["merged"]["first_names"], "first");
+    assert_eq!(merged["merged"]["upn"], "H801200001001");
+    let comments = entity::comment::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert_eq!(comments[0].pupil_id.to_string(), ids[0]);
+
+    let res = ctx
+        .client()
+        .get(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[1]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+async fn merged_pupil_keeps_flag_periods(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+
+    let res = ctx
+        .client()
+        .post(&format!("{}/{}", constant::PUPILS_ENDPOINT, ids[1]))
+        .json(&json!({"upn": "X801200001002"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .put(&format!("{}/{}/flags", constant::PUPILS_ENDPOINT, ids[1]))
+        .json(&json!({
+            "flag": "fsm",
+            "start_date": "2022-09-01",
+            "source": "LA notification"
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let merge = json!({"keep": ids[0], "remove": ids[1], "take": ["upn"], "dry_run": true});
+    let res = ctx
+        .client()
+        .post(&format!("{}/merge", constant::QUALITY_ENDPOINT))
+        .json(&merge)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let preview = res.json::<Value>().await;
+    let flag_periods = preview["linked"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|linked| linked["table"] == "flag periods")
+        .unwrap();
+    assert_eq!(flag_periods["moved"], 1);
+
+    let mut merge = merge;
+    merge["dry_run"] = json!(false);
+    let res = ctx
+        .client()
+        .post(&format!("{}/merge", constant::QUALITY_ENDPOINT))
+        .json(&merge)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let merged = res.json::<Value>().await;
+    assert_eq!(merged["merged"]["upn"], "X801200001002");
+    assert_eq!(merged["merged"]["free_school_meals"], true);
+
+    let periods = entity::flag_period::Entity::find()
+        .all(ctx.check_db())
+        .await
+        .unwrap();
+    assert_eq!(periods.len(), 1);
+    assert_eq!(periods[0].pupil_id.to_string(), ids[0]);
+    assert_eq!(periods[0].source, "LA notification");
+    let kept = entity::pupil::Entity::find_by_id(ids[0].parse::<uuid::Uuid>().unwrap())
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(kept.free_school_meals);
+}
@@ -0,0 +1,266 @@
+use crate::common::*;
+use http::StatusCode;
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use std::io::{Cursor, Read};
+use uuid::Uuid;
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle.as_bytes())
+}
+
+#[rstest]
+async fn only_admins_write_valid_templates(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    let template = json!({
+        "name": "Spring report",
+        "body": "Dear {{name}}",
+        "from_date": "2023-01-01",
+        "to_date": "2023-03-31",
+    });
+
+    let res = ctx
+        .client()
+        .put(constant::REPORTS_ENDPOINT)
+        .json(&template)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .put(constant::REPORTS_ENDPOINT)
+        .json(&json!({
+            "name": "Spring report",
+            "body": "Comments: {{comments}}\n{{favourite_colour}}",
+            "from_date": "2023-01-01",
+            "to_date": "2023-03-31",
+        }))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json::<Value>().await["fields"]["body"],
+        "line 1: {{comments}} must be on a line of its own; \
+         line 2: there's no {{favourite_colour}} placeholder"
+    );
+
+    let res = ctx
+        .client()
+        .put(constant::REPORTS_ENDPOINT)
+        .json(&template)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let created = res.json::<Value>().await;
+    assert_eq!(created["updated_by"], ADMIN_USER);
+    let url = format!(
+        "{}/{}",
+        constant::REPORTS_ENDPOINT,
+        created["id"].as_str().unwrap()
+    );
+
+    let res = ctx
+        .client()
+        .post(&url)
+        .json(&json!({
+            "name": "Spring term report",
+            "body": "Dear {{first_names}}",
+            "from_date": "2023-01-01",
+            "to_date": "2023-03-31",
+        }))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = ctx
+        .client()
+        .get(constant::REPORTS_ENDPOINT)
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let templates = res.json::<Value>().await;
+    assert_eq!(templates.as_array().unwrap().len(), 1);
+    assert_eq!(templates[0]["name"], "Spring term report");
+
+    let res = ctx
+        .client()
+        .delete(&url)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/years/6"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json::<Value>().await["error"],
+        "REPORT TEMPLATE DOES NOT EXIST"
+    );
+}
+
+#[rstest]
+async fn class_reports_with_sign_off(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    entity::comment::Entity::insert_many(
+        [
+            ("Settled in well this term", "2023-02-01T09:00:00"),
+            ("Left over from the autumn", "2022-11-01T09:00:00"),
+        ]
+        .map(|(body, created_at)| {
+            entity::comment::ActiveModel::from(entity::comment::Model {
+                id: Uuid::new_v4(),
+                pupil_id: ids[0].parse().unwrap(),
+                author: ADMIN_USER.into(),
+                category: "pastoral".into(),
+                body: body.into(),
+                visibility: "shared".into(),
+                created_at: created_at.parse().unwrap(),
+                edited_at: None,
+            })
+        }),
+    )
+    .exec(ctx.check_db())
+    .await
+    .expect("insert comments");
+    let res = ctx
+        .client()
+        .put(constant::REPORTS_ENDPOINT)
+        .json(&json!({
+            "name": "Spring report",
+            "body": "# How {{first_names}} is getting on\n{{comments:pastoral}}",
+            "from_date": "2023-01-01",
+            "to_date": "2023-03-31",
+        }))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let url = format!(
+        "{}/{}",
+        constant::REPORTS_ENDPOINT,
+        res.json::<Value>().await["id"].as_str().unwrap()
+    );
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/pupils/{}", ids[0]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/pdf");
+    let pdf = res.bytes().await;
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert!(contains(&pdf, "(How first is getting on)"));
+    assert!(contains(&pdf, "(Settled in well this term)"));
+    assert!(!contains(&pdf, "Left over from the autumn"));
+    assert!(contains(
+        &pdf,
+        "(Draft - not yet signed off    Page 1 of 1)"
+    ));
+
+    let res = ctx
+        .client()
+        .put(&format!("{url}/pupils/{}/sign-off", ids[0]))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.json::<Value>().await["signed_off_by"],
+        "test_user@integration.com"
+    );
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/years/6"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let statuses = res.json::<Value>().await;
+    assert_eq!(statuses.as_array().unwrap().len(), 2);
+    assert_eq!(statuses[0]["first_names"], "first");
+    assert_eq!(statuses[0]["signed_off_by"], "test_user@integration.com");
+    assert!(statuses[1].get("signed_off_by").is_none());
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/years/6/zip"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/zip");
+    let mut zip = zip::ZipArchive::new(Cursor::new(res.bytes().await.to_vec())).unwrap();
+    let mut names: Vec<&str> = zip.file_names().collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        ["sign_off.csv", "student_first.pdf", "student_second.pdf"]
+    );
+    let mut csv = String::new();
+    zip.by_name("sign_off.csv")
+        .unwrap()
+        .read_to_string(&mut csv)
+        .unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        rows[0],
+        "last_name,first_names,file,signed_off_by,signed_off_at"
+    );
+    assert!(rows[1].starts_with("student,first,student_first.pdf,test_user@integration.com,"));
+    assert_eq!(rows[2], "student,second,student_second.pdf,,");
+    let mut pdf = vec![];
+    zip.by_name("student_first.pdf")
+        .unwrap()
+        .read_to_end(&mut pdf)
+        .unwrap();
+    assert!(contains(
+        &pdf,
+        "(Signed off by test_user@integration.com on "
+    ));
+
+    let res = ctx
+        .client()
+        .get(&format!("{url}/years/2/zip"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .delete(&format!("{url}/pupils/{}/sign-off", ids[0]))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .get(&format!("{url}/years/6"))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert!(res.json::<Value>().await[0].get("signed_off_by").is_none());
+}
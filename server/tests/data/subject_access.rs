@@ -0,0 +1,305 @@
+use crate::common::*;
+use base64::{engine::general_purpose, Engine};
+use http::StatusCode;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use lt_server::core::constant;
+use rstest::*;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde_json::{json, Value};
+use std::io::{Cursor, Read};
+use uuid::Uuid;
+
+/// "%PDF-1.4 test", base64 encoded
+const PDF: &str = "JVBERi0xLjQgdGVzdA==";
+
+fn photo() -> String {
+    let mut out = Vec::new();
+    DynamicImage::ImageRgb8(RgbImage::new(640, 480))
+        .write_to(&mut Cursor::new(&mut out), ImageOutputFormat::Jpeg(90))
+        .unwrap();
+    general_purpose::STANDARD.encode(out)
+}
+
+/// Puts the pupil on the ALN register with an IDP, in an intervention with a target, and adds a
+/// progression judgement and a signed off report.
+async fn add_support(pupil_id: Uuid, db: &DatabaseConnection) {
+    use entity::*;
+    let date = |date: &str| date.parse().unwrap();
+    let at = |time: &str| time.parse().unwrap();
+    aln_register::Entity::insert(aln_register::ActiveModel::from(aln_register::Model {
+        pupil_id,
+        provision: "school".into(),
+        areas_of_need: "literacy".into(),
+        alnco: None,
+        added_on: date("2022-09-05"),
+        review_date: date("2023-09-05"),
+        last_reviewed: None,
+        closed_on: None,
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+    idp::Entity::insert(idp::ActiveModel::from(idp::Model {
+        id: Uuid::new_v4(),
+        pupil_id,
+        version: 1,
+        outcomes: "Read a chapter book".into(),
+        provision: "Daily phonics".into(),
+        created_by: ADMIN_USER.into(),
+        created_at: at("2022-09-06T09:00:00"),
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+    let intervention_id = Uuid::new_v4();
+    intervention::Entity::insert(intervention::ActiveModel::from(intervention::Model {
+        id: intervention_id,
+        name: "Reading club".into(),
+        description: String::new(),
+        lead_staff: ADMIN_USER.into(),
+        start_date: date("2022-10-03"),
+        end_date: None,
+        measure: "reading age".into(),
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+    intervention_member::Entity::insert(intervention_member::ActiveModel::from(
+        intervention_member::Model {
+            intervention_id,
+            pupil_id,
+            entry_measure: Some(84),
+            exit_measure: None,
+        },
+    ))
+    .exec(db)
+    .await
+    .unwrap();
+    target::Entity::insert(target::ActiveModel::from(target::Model {
+        id: Uuid::new_v4(),
+        pupil_id,
+        intervention_id: Some(intervention_id),
+        description: "Blend three letter words".into(),
+        success_criteria: "Reads ten words unaided".into(),
+        set_by: ADMIN_USER.into(),
+        set_on: date("2022-10-03"),
+        review_date: date("2022-12-16"),
+        outcome: None,
+        reviewed_on: None,
+        review_notes: None,
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+    let what_matters = what_matters::Entity::find()
+        .one(db)
+        .await
+        .unwrap()
+        .expect("seeded framework");
+    progression_judgement::Entity::insert(progression_judgement::ActiveModel::from(
+        progression_judgement::Model {
+            id: Uuid::new_v4(),
+            pupil_id,
+            what_matters_id: what_matters.id,
+            step: 2,
+            date: date("2023-03-01"),
+            judged_by: ADMIN_USER.into(),
+            notes: None,
+        },
+    ))
+    .exec(db)
+    .await
+    .unwrap();
+    let template_id = Uuid::new_v4();
+    report_template::Entity::insert(report_template::ActiveModel::from(report_template::Model {
+        id: template_id,
+        name: "Summer report".into(),
+        body: "{first_names} has had a good term.".into(),
+        from_date: date("2023-04-17"),
+        to_date: date("2023-07-21"),
+        updated_by: None,
+        updated_at: at("2023-04-17T09:00:00"),
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+    report_sign_off::Entity::insert(report_sign_off::ActiveModel::from(report_sign_off::Model {
+        template_id,
+        pupil_id,
+        signed_off_by: ADMIN_USER.into(),
+        signed_off_at: at("2023-07-14T15:00:00"),
+    }))
+    .exec(db)
+    .await
+    .unwrap();
+}
+
+#[rstest]
+async fn subject_access_export_is_redacted_and_logged(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let ids = add_pupils(ctx.check_db()).await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    let url = format!("{}/{}/subject-access", constant::PUPILS_ENDPOINT, ids[0]);
+
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/comments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"category": "pastoral", "body": "Argued with Second Student at lunch"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = ctx
+        .client()
+        .put(&format!(
+            "{}/{}/attachments",
+            constant::PUPILS_ENDPOINT,
+            ids[0]
+        ))
+        .json(&json!({"file_name": "report.pdf", "content_type": "application/pdf", "data": PDF}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let res = ctx
+        .client()
+        .put(&format!("{}/{}/photo", constant::PUPILS_ENDPOINT, ids[0]))
+        .json(&json!({ "data": photo() }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+    add_support(ids[0].parse().unwrap(), ctx.check_db()).await;
+
+    let res = ctx
+        .client()
+        .post(&url)
+        .json(&json!({}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = ctx
+        .client()
+        .post(&format!("{url}/preview"))
+        .json(&json!({"reference": "SAR-1"}))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let preview = res.json::<Value>().await;
+    assert_eq!(
+        preview["comments"][0]["body"],
+        "Argued with [another pupil] at lunch"
+    );
+    assert_eq!(
+        preview["redacted"],
+        json!([{"section": "comments", "what": "names of other pupils", "count": 1}])
+    );
+    let attachment_id = preview["attachments"][0]["id"].as_str().unwrap().to_owned();
+
+    let res = ctx
+        .client()
+        .get(constant::SUBJECT_ACCESS_ENDPOINT)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.json::<Value>().await["entries"], json!([]));
+
+    let res = ctx
+        .client()
+        .post(&url)
+        .json(&json!({"reference": "SAR-1"}))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/zip");
+    let mut zip = zip::ZipArchive::new(Cursor::new(res.bytes().await.to_vec())).unwrap();
+    let mut names: Vec<&str> = zip.file_names().collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        [
+            format!("attachments/{attachment_id}_report.pdf").as_str(),
+            "data.json",
+            "photo.jpg",
+            "summary.pdf"
+        ]
+    );
+    let mut data = String::new();
+    zip.by_name("data.json")
+        .unwrap()
+        .read_to_string(&mut data)
+        .unwrap();
+    let data: Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(data["pupil"]["id"], ids[0]);
+    assert_eq!(data["reference"], "SAR-1");
+    assert_eq!(data["exported_by"], ADMIN_USER);
+    assert_eq!(data["aln_register"]["areas_of_need"], json!(["literacy"]));
+    assert_eq!(data["idps"][0]["outcomes"], "Read a chapter book");
+    assert_eq!(data["interventions"][0]["name"], "Reading club");
+    assert_eq!(data["interventions"][0]["entry_measure"], 84);
+    assert_eq!(
+        data["targets"][0]["description"],
+        "Blend three letter words"
+    );
+    assert_eq!(data["progression"][0]["step"], 2);
+    assert!(!data["progression"][0]["what_matters"]
+        .as_str()
+        .unwrap()
+        .is_empty());
+    assert_eq!(data["photo"]["uploaded_by"], "test_user@integration.com");
+    assert_eq!(data["photo"]["consent_withdrawn"], false);
+    assert_eq!(data["report_sign_offs"][0]["report"], "Summer report");
+    assert_eq!(data["report_sign_offs"][0]["signed_off_by"], ADMIN_USER);
+    let mut summary = vec![];
+    zip.by_name("summary.pdf")
+        .unwrap()
+        .read_to_end(&mut summary)
+        .unwrap();
+    let summary = String::from_utf8_lossy(&summary);
+    for heading in [
+        "Additional learning needs",
+        "Interventions",
+        "Targets",
+        "Progression",
+        "Photo",
+        "Reports",
+    ] {
+        assert!(
+            summary.contains(heading),
+            "summary has no {heading} section"
+        );
+    }
+    let mut file = vec![];
+    zip.by_name(&format!("attachments/{attachment_id}_report.pdf"))
+        .unwrap()
+        .read_to_end(&mut file)
+        .unwrap();
+    assert_eq!(file, b"%PDF-1.4 test");
+
+    let res = ctx
+        .client()
+        .get(constant::SUBJECT_ACCESS_ENDPOINT)
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let log = res.json::<Value>().await;
+    assert_eq!(log["intact"], true);
+    let entries = log["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["pupil_id"], ids[0]);
+    assert_eq!(entries[0]["pupil_name"], "first student");
+    assert_eq!(entries[0]["reference"], "SAR-1");
+    assert_eq!(entries[0]["exported_by"], ADMIN_USER);
+    assert_eq!(entries[0]["redactions"], 1);
+    assert_eq!(entries[0]["checksum"].as_str().unwrap().len(), 64);
+}
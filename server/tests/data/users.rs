@@ -1,7 +1,7 @@
 use crate::common::*;
 use chrono::Utc;
 use http::StatusCode;
-use lt_server::core::constant;
+use lt_server::{core::constant, user::model::User};
 use rstest::*;
 use sea_orm::EntityTrait;
 use serde_json::json;
@@ -18,6 +18,7 @@ async fn login_and_get_users(#[future] mock_ctx: MockCtx) {
             years: "5,6".into(),
             secret: vec![127; 64],
             last_refresh: Utc::now().naive_utc(),
+            roles: "".into(),
         },
         entity::user::Model {
             first_names: "second".into(),
@@ -27,6 +28,7 @@ async fn login_and_get_users(#[future] mock_ctx: MockCtx) {
             years: "2".into(),
             secret: vec![127; 64],
             last_refresh: Utc::now().naive_utc(),
+            roles: "".into(),
         },
     ];
     let to_insert: Vec<entity::user::ActiveModel> = users
@@ -86,7 +88,7 @@ async fn login_and_create_user(#[future] mock_ctx: MockCtx) {
         .client()
         .put(constant::USERS_ENDPOINT)
         .json(&new_user_json)
-        .header("Authorization", format!("Bearer {}", ctx.login().await))
+        .header("Authorization", format!("Bearer {}", ctx.login_admin().await))
         .send()
         .await;
     assert_eq!(res.status(), StatusCode::CREATED);
@@ -98,3 +100,206 @@ async fn login_and_create_user(#[future] mock_ctx: MockCtx) {
     assert_eq!(inserted.email_address, "test@test.com");
     assert_eq!(inserted.years, "2,3");
 }
+
+#[rstest]
+async fn only_admin_can_create_users(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .put(constant::USERS_ENDPOINT)
+        .json(&json!({
+            "first_names": "test",
+            "last_name": "user",
+            "email_address": "test@test.com",
+            "hashed_password": "password",
+            "years": [1, 2, 3, 4, 5, 6],
+            "roles": ["admin", "dsl"]
+        }))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    let inserted = entity::user::Entity::find_by_id("test@test.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap();
+    assert!(inserted.is_none());
+}
+
+#[rstest]
+async fn login_and_patch_own_names_and_password(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .patch(&format!("{}/test_user@integration.com", constant::USERS_ENDPOINT))
+        .json(&json!({"last_name": "Newname", "password": "new password"}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let updated = entity::user::Entity::find_by_id("test_user@integration.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.last_name, "Newname");
+    assert_eq!(updated.first_names, "Integration Test");
+    assert!(updated.hashed_password.starts_with("$argon2"));
+    let res = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": "test_user@integration.com", "hashed_password": "new password"}))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": "test_user@integration.com", "hashed_password": updated.hashed_password}))
+        .send()
+        .await;
+    assert_ne!(res.status(), StatusCode::OK);
+}
+
+#[rstest]
+async fn login_hashes_a_legacy_password(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let stored = add_user(&[127; 64], "2021-01-01T00:00:00", ctx.check_db()).await;
+    assert!(User::from(stored).has_legacy_password());
+    let res = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": "test_user@integration.com", "hashed_password": "password"}))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let rehashed: User = entity::user::Entity::find_by_id("test_user@integration.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap()
+        .into();
+    assert!(!rehashed.has_legacy_password());
+    assert!(rehashed.check_password("password"));
+    let res = ctx
+        .client()
+        .post(constant::LOGIN_ENDPOINT)
+        .json(&json!({"email_address": "test_user@integration.com", "hashed_password": "password"}))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[rstest]
+async fn empty_password_is_rejected(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let res = ctx
+        .client()
+        .patch(&format!(
+            "{}/test_user@integration.com",
+            constant::USERS_ENDPOINT
+        ))
+        .json(&json!({"password": ""}))
+        .header("Authorization", format!("Bearer {token}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        res.json::<serde_json::Value>().await["fields"]["password"],
+        "password is empty"
+    );
+}
+
+#[rstest]
+async fn only_admin_can_patch_years_or_other_users(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    let own = format!("{}/test_user@integration.com", constant::USERS_ENDPOINT);
+    let other = format!("{}/{ADMIN_USER}", constant::USERS_ENDPOINT);
+    for (url, body) in [
+        (&own, json!({"years": [1, 2]})),
+        (&other, json!({"last_name": "Newname"})),
+        (&other, json!({"password": "taken over"})),
+    ] {
+        let res = ctx
+            .client()
+            .patch(url)
+            .json(&body)
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+    let unchanged = entity::user::Entity::find_by_id(ADMIN_USER)
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(User::from(unchanged).check_password("password"));
+
+    let res = ctx
+        .client()
+        .patch(&own)
+        .json(&json!({"years": [1, 2]}))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let updated = entity::user::Entity::find_by_id("test_user@integration.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated.years, "1,2");
+}
+
+#[rstest]
+async fn only_admin_can_patch_roles(#[future] mock_ctx: MockCtx) {
+    let ctx = mock_ctx.await;
+    let token = ctx.login().await;
+    let admin = ctx.login_admin().await;
+    let url = format!("{}/test_user@integration.com", constant::USERS_ENDPOINT);
+    for roles in [json!(["dsl"]), json!(["admin"])] {
+        let res = ctx
+            .client()
+            .patch(&url)
+            .json(&json!({ "roles": roles }))
+            .header("Authorization", format!("Bearer {token}"))
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+    let res = ctx
+        .client()
+        .patch(&url)
+        .json(&json!({"roles": ["headteacher"]}))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let user = entity::user::Entity::find_by_id("test_user@integration.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(user.roles, "");
+
+    let res = ctx
+        .client()
+        .patch(&url)
+        .json(&json!({"roles": ["dsl"]}))
+        .header("Authorization", format!("Bearer {admin}"))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let user = entity::user::Entity::find_by_id("test_user@integration.com")
+        .one(ctx.check_db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(user.roles, "dsl");
+}
@@ -8,4 +8,27 @@ pub fn is_valid_email(email: &str) -> bool {
     EMAIL_REGEX.is_match(email)
 }
 
+/// Letters used for UPN check characters and temporary UPN serials (I, O and S are left out).
+const UPN_LETTERS: &str = "ABCDEFGHJKLMNPQRTUVWXYZ";
 
+/// Checks a unique pupil number: a check letter, 11 digits identifying the LA, school, year and
+/// pupil, then a final digit or (for a temporary UPN) a letter. The check letter is the weighted
+/// sum of characters 2-13, using weights 2-13, modulo 23.
+pub fn is_valid_upn(upn: &str) -> bool {
+    lazy_static! {
+        static ref UPN_REGEX: Regex = Regex::new(r"^[A-HJ-NP-RT-Z][0-9]{11}[0-9A-HJ-NP-RT-Z]$").unwrap();
+    }
+    if !UPN_REGEX.is_match(upn) {
+        return false;
+    }
+    let sum: usize = upn
+        .chars()
+        .skip(1)
+        .zip(2..)
+        .map(|(c, weight)| match c.to_digit(10) {
+            Some(digit) => digit as usize * weight,
+            None => UPN_LETTERS.find(c).expect("letter checked by regex") * weight,
+        })
+        .sum();
+    UPN_LETTERS.chars().nth(sum % 23) == upn.chars().next()
+}